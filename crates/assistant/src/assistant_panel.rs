@@ -232,6 +232,7 @@ impl AssistantPanel {
             // TODO: deserialize state.
             let workspace_handle = workspace.clone();
             workspace.update(&mut cx, |workspace, cx| {
+                let workspace_view = cx.view().clone();
                 cx.new_view::<Self>(|cx| {
                     let toolbar = cx.new_view(|cx| {
                         let mut toolbar = Toolbar::new();
@@ -270,6 +271,7 @@ impl AssistantPanel {
                             &saved_context_picker,
                             Self::handle_saved_context_picker_event,
                         ),
+                        cx.subscribe(&workspace_view, Self::handle_workspace_event),
                     ];
 
                     cx.observe_global::<FileIcons>(|_, cx| {
@@ -504,6 +506,50 @@ impl AssistantPanel {
         }
     }
 
+    /// Adds the files and directories sent from elsewhere in the workspace, like the project
+    /// panel, to the active context as `/file` commands.
+    fn handle_workspace_event(
+        &mut self,
+        workspace: View<Workspace>,
+        event: &workspace::Event,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let workspace::Event::AddToAssistantContext(project_paths) = event else {
+            return;
+        };
+        if !AssistantSettings::get_global(cx).enabled {
+            return;
+        }
+        let project = workspace.read(cx).project().read(cx);
+        let paths = project_paths
+            .iter()
+            .filter_map(|project_path| {
+                let worktree = project.worktree_for_id(project_path.worktree_id, cx)?;
+                let mut full_path = PathBuf::from(worktree.read(cx).root_name());
+                full_path.push(&project_path.path);
+                Some(full_path.to_string_lossy().into_owned())
+            })
+            .collect::<Vec<_>>();
+        if paths.is_empty() {
+            return;
+        }
+
+        if !self.focus_handle.contains_focused(cx) {
+            workspace.update(cx, |workspace, cx| {
+                workspace.toggle_panel_focus::<AssistantPanel>(cx);
+            });
+        }
+        if let Some(context_editor) = self
+            .active_context_editor()
+            .cloned()
+            .or_else(|| self.new_context(cx))
+        {
+            context_editor.update(cx, |context_editor, cx| {
+                context_editor.insert_file_commands(&paths, cx)
+            });
+        }
+    }
+
     fn new_context(&mut self, cx: &mut ViewContext<Self>) -> Option<View<ContextEditor>> {
         let workspace = self.workspace.upgrade()?;
 
@@ -2466,28 +2512,32 @@ impl ContextEditor {
             .collect()
     }
 
+    /// Moves the cursor to an empty line, where a command can be inserted.
+    fn move_to_command_line(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+        editor.change_selections(Some(Autoscroll::fit()), cx, |s| s.try_cancel());
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let newest_cursor = editor.selections.newest::<Point>(cx).head();
+        if newest_cursor.column > 0
+            || snapshot
+                .chars_at(newest_cursor)
+                .next()
+                .map_or(false, |ch| ch != '\n')
+        {
+            editor.move_to_end_of_line(
+                &MoveToEndOfLine {
+                    stop_at_soft_wraps: false,
+                },
+                cx,
+            );
+            editor.newline(&Newline, cx);
+        }
+    }
+
     fn insert_command(&mut self, name: &str, cx: &mut ViewContext<Self>) {
         if let Some(command) = self.slash_command_registry.command(name) {
             self.editor.update(cx, |editor, cx| {
                 editor.transact(cx, |editor, cx| {
-                    editor.change_selections(Some(Autoscroll::fit()), cx, |s| s.try_cancel());
-                    let snapshot = editor.buffer().read(cx).snapshot(cx);
-                    let newest_cursor = editor.selections.newest::<Point>(cx).head();
-                    if newest_cursor.column > 0
-                        || snapshot
-                            .chars_at(newest_cursor)
-                            .next()
-                            .map_or(false, |ch| ch != '\n')
-                    {
-                        editor.move_to_end_of_line(
-                            &MoveToEndOfLine {
-                                stop_at_soft_wraps: false,
-                            },
-                            cx,
-                        );
-                        editor.newline(&Newline, cx);
-                    }
-
+                    Self::move_to_command_line(editor, cx);
                     editor.insert(&format!("/{name}"), cx);
                     if command.requires_argument() {
                         editor.insert(" ", cx);
@@ -2501,6 +2551,40 @@ impl ContextEditor {
         }
     }
 
+    /// Inserts and runs a `/file` command for each of the paths.
+    fn insert_file_commands(&mut self, paths: &[String], cx: &mut ViewContext<Self>) {
+        let workspace = self.workspace.clone();
+        for path in paths {
+            self.editor.update(cx, |editor, cx| {
+                editor.transact(cx, |editor, cx| {
+                    Self::move_to_command_line(editor, cx);
+                    editor.insert(&format!("/file {path}"), cx);
+                })
+            });
+            let cursor = self
+                .editor
+                .read(cx)
+                .selections
+                .newest_anchor()
+                .head()
+                .text_anchor;
+            let command = self.context.update(cx, |context, cx| {
+                context.reparse_slash_commands(cx);
+                context.pending_command_for_position(cursor, cx).cloned()
+            });
+            if let Some(command) = command {
+                self.run_command(
+                    command.source_range,
+                    &command.name,
+                    command.argument.as_deref(),
+                    true,
+                    workspace.clone(),
+                    cx,
+                );
+            }
+        }
+    }
+
     pub fn confirm_command(&mut self, _: &ConfirmCommand, cx: &mut ViewContext<Self>) {
         let selections = self.editor.read(cx).selections.disjoint_anchors();
         let mut commands_by_range = HashMap::default();
@@ -60,6 +60,7 @@ impl DiagnosticsCommand {
                             .map_or(false, |entry| entry.is_ignored),
                         include_root_name: true,
                         candidates: project::Candidates::Entries,
+                        directory: None,
                     }
                 })
                 .collect::<Vec<_>>();
@@ -61,6 +61,7 @@ impl FileSlashCommand {
                             .map_or(false, |entry| entry.is_ignored),
                         include_root_name: true,
                         candidates: project::Candidates::Entries,
+                        directory: None,
                     }
                 })
                 .collect::<Vec<_>>();
@@ -558,6 +558,7 @@ impl Server {
             .add_message_handler(broadcast_project_message_from_host::<proto::BufferReloaded>)
             .add_message_handler(broadcast_project_message_from_host::<proto::BufferSaved>)
             .add_message_handler(broadcast_project_message_from_host::<proto::UpdateDiffBase>)
+            .add_message_handler(broadcast_project_message_from_host::<proto::UpdateSharedTreeView>)
             .add_request_handler(get_users)
             .add_request_handler(user_handler(fuzzy_search_users))
             .add_request_handler(user_handler(request_contact))
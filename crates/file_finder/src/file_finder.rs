@@ -15,6 +15,7 @@ use itertools::Itertools;
 use new_path_prompt::NewPathPrompt;
 use picker::{Picker, PickerDelegate};
 use project::{PathMatchCandidateSet, Project, ProjectPath, WorktreeId};
+use project_panel::ProjectPanel;
 use settings::Settings;
 use std::{
     cmp,
@@ -47,7 +48,7 @@ impl FileFinder {
     fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
         workspace.register_action(|workspace, action: &workspace::ToggleFileFinder, cx| {
             let Some(file_finder) = workspace.active_modal::<Self>(cx) else {
-                Self::open(workspace, action.separate_history, cx);
+                Self::open(workspace, action.separate_history, None, cx);
                 return;
             };
 
@@ -58,9 +59,24 @@ impl FileFinder {
                 });
             });
         });
+        workspace.register_action(|workspace, _: &project_panel::FindFileInDirectory, cx| {
+            let Some(directory) = workspace
+                .panel::<ProjectPanel>(cx)
+                .and_then(|project_panel| project_panel.read(cx).selected_directory(cx))
+            else {
+                return;
+            };
+            Self::open(workspace, false, Some(directory), cx);
+        });
     }
 
-    fn open(workspace: &mut Workspace, separate_history: bool, cx: &mut ViewContext<Workspace>) {
+    /// Opens the file finder, only looking for files inside of `directory` when it is set.
+    fn open(
+        workspace: &mut Workspace,
+        separate_history: bool,
+        directory: Option<ProjectPath>,
+        cx: &mut ViewContext<Workspace>,
+    ) {
         let project = workspace.project().read(cx);
 
         let currently_opened_path = workspace
@@ -80,6 +96,12 @@ impl FileFinder {
                 Some(abs_path) => history_file_exists(abs_path),
                 None => true,
             })
+            .filter(|(history_path, _)| {
+                directory.as_ref().map_or(true, |directory| {
+                    history_path.worktree_id == directory.worktree_id
+                        && history_path.path.starts_with(&directory.path)
+                })
+            })
             .map(|(history_path, abs_path)| FoundPath::new(history_path, abs_path))
             .collect::<Vec<_>>();
 
@@ -93,6 +115,7 @@ impl FileFinder {
                 currently_opened_path,
                 history_items,
                 separate_history,
+                directory,
                 cx,
             );
 
@@ -163,6 +186,10 @@ pub struct FileFinderDelegate {
     cancel_flag: Arc<AtomicBool>,
     history_items: Vec<FoundPath>,
     separate_history: bool,
+    /// The directory the search is limited to, if any.
+    directory: Option<ProjectPath>,
+    /// A relative query that matched nothing, offered as an extra entry to create that path.
+    create_new_query: Option<String>,
 }
 
 /// Use a custom ordering for file finder: the regular one
@@ -416,6 +443,7 @@ impl FileFinderDelegate {
         currently_opened_path: Option<FoundPath>,
         history_items: Vec<FoundPath>,
         separate_history: bool,
+        directory: Option<ProjectPath>,
         cx: &mut ViewContext<FileFinder>,
     ) -> Self {
         Self::subscribe_to_updates(&project, cx);
@@ -434,6 +462,8 @@ impl FileFinderDelegate {
             cancel_flag: Arc::new(AtomicBool::new(false)),
             history_items,
             separate_history,
+            directory,
+            create_new_query: None,
         }
     }
 
@@ -464,8 +494,13 @@ impl FileFinderDelegate {
             .project
             .read(cx)
             .visible_worktrees(cx)
+            .filter(|worktree| {
+                self.directory.as_ref().map_or(true, |directory| {
+                    worktree.read(cx).id() == directory.worktree_id
+                })
+            })
             .collect::<Vec<_>>();
-        let include_root_name = worktrees.len() > 1;
+        let include_root_name = self.project.read(cx).visible_worktrees(cx).count() > 1;
         let candidate_sets = worktrees
             .into_iter()
             .map(|worktree| {
@@ -477,6 +512,10 @@ impl FileFinderDelegate {
                         .map_or(false, |entry| entry.is_ignored),
                     include_root_name,
                     candidates: project::Candidates::Files,
+                    directory: self
+                        .directory
+                        .as_ref()
+                        .map(|directory| directory.path.clone()),
                 }
             })
             .collect::<Vec<_>>();
@@ -532,6 +571,12 @@ impl FileFinderDelegate {
                 matches.into_iter(),
                 extend_old_matches,
             );
+            let project = self.project.read(cx);
+            self.create_new_query = (self.matches.len() == 0
+                && !did_cancel
+                && !project.is_read_only()
+                && project.visible_worktrees(cx).next().is_some())
+            .then(|| query.path_like.path_query().to_string());
             self.latest_search_query = Some(query);
             self.latest_search_did_cancel = did_cancel;
             self.selected_index = self.calculate_selected_index();
@@ -539,6 +584,24 @@ impl FileFinderDelegate {
         }
     }
 
+    fn create_path_for_query(&self, query: &str, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(project_panel) = self
+            .workspace
+            .upgrade()
+            .and_then(|workspace| workspace.read(cx).panel::<ProjectPanel>(cx))
+        else {
+            return;
+        };
+        project_panel
+            .update(cx, |project_panel, cx| {
+                project_panel.create_entry_for_query(query, cx)
+            })
+            .detach_and_log_err(cx);
+        self.file_finder
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
     fn labels_for_match(
         &self,
         path_match: &Match,
@@ -726,12 +789,24 @@ impl FileFinderDelegate {
 impl PickerDelegate for FileFinderDelegate {
     type ListItem = ListItem;
 
-    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
-        "Search project files...".into()
+    fn placeholder_text(&self, cx: &mut WindowContext) -> Arc<str> {
+        let Some(directory) = &self.directory else {
+            return "Search project files...".into();
+        };
+        let mut directory_path = PathBuf::new();
+        if let Some(worktree) = self
+            .project
+            .read(cx)
+            .worktree_for_id(directory.worktree_id, cx)
+        {
+            directory_path.push(worktree.read(cx).root_name());
+        }
+        directory_path.push(&directory.path);
+        format!("Search files in {}...", directory_path.display()).into()
     }
 
     fn match_count(&self) -> usize {
-        self.matches.len()
+        self.matches.len() + self.create_new_query.is_some() as usize
     }
 
     fn selected_index(&self) -> usize {
@@ -769,6 +844,7 @@ impl PickerDelegate for FileFinderDelegate {
     ) -> Task<()> {
         let raw_query = raw_query.replace(' ', "");
         let raw_query = raw_query.trim();
+        self.create_new_query = None;
         if raw_query.is_empty() {
             let project = self.project.read(cx);
             self.latest_search_id = post_inc(&mut self.search_count);
@@ -815,6 +891,12 @@ impl PickerDelegate for FileFinderDelegate {
     }
 
     fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<FileFinderDelegate>>) {
+        if self.selected_index() == self.matches.len() {
+            if let Some(query) = self.create_new_query.clone() {
+                self.create_path_for_query(&query, cx);
+            }
+            return;
+        }
         if let Some(m) = self.matches.get(self.selected_index()) {
             if let Some(workspace) = self.workspace.upgrade() {
                 let open_task = workspace.update(cx, move |workspace, cx| {
@@ -944,6 +1026,21 @@ impl PickerDelegate for FileFinderDelegate {
         selected: bool,
         cx: &mut ViewContext<Picker<Self>>,
     ) -> Option<Self::ListItem> {
+        if ix == self.matches.len() {
+            let query = self.create_new_query.as_ref()?;
+            return Some(
+                ListItem::new(ix)
+                    .spacing(ListItemSpacing::Sparse)
+                    .inset(true)
+                    .selected(selected)
+                    .start_slot(
+                        Icon::new(IconName::Plus)
+                            .color(Color::Muted)
+                            .size(IconSize::Small),
+                    )
+                    .child(Label::new(format!("Create '{query}'…"))),
+            );
+        }
         let path_match = self
             .matches
             .get(ix)
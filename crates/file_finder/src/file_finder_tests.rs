@@ -590,6 +590,113 @@ async fn test_search_worktree_without_files(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_search_in_directory(cx: &mut TestAppContext) {
+    let app_state = init_test(cx);
+    app_state
+        .fs
+        .as_fake()
+        .insert_tree(
+            "/root",
+            json!({
+                "a": {
+                    "banana": "",
+                    "nested": { "bandana": "" },
+                },
+                "b": {
+                    "banana": "",
+                },
+                "c": {
+                    "bandit": "",
+                },
+            }),
+        )
+        .await;
+
+    let project = Project::test(app_state.fs.clone(), ["/root".as_ref()], cx).await;
+    let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+    let worktree_id = cx.read(|cx| {
+        let worktrees = workspace.read(cx).worktrees(cx).collect::<Vec<_>>();
+        assert_eq!(worktrees.len(), 1);
+        WorktreeId::from_usize(worktrees[0].entity_id().as_u64() as usize)
+    });
+
+    for (directory, expected_paths) in [
+        ("a", vec!["a/banana", "a/nested/bandana"]),
+        ("b", vec!["b/banana"]),
+        ("a/nested", vec!["a/nested/bandana"]),
+    ] {
+        workspace.update(cx, |workspace, cx| {
+            FileFinder::open(
+                workspace,
+                false,
+                Some(ProjectPath {
+                    worktree_id,
+                    path: Arc::from(Path::new(directory)),
+                }),
+                cx,
+            )
+        });
+        let picker = active_file_picker(&workspace, cx);
+        picker
+            .update(cx, |picker, cx| {
+                picker.delegate.spawn_search(test_path_like("ban"), cx)
+            })
+            .await;
+        picker.update(cx, |picker, _| {
+            let mut paths = collect_search_matches(picker).search_paths_only();
+            paths.sort();
+            assert_eq!(
+                paths,
+                expected_paths.iter().map(PathBuf::from).collect::<Vec<_>>(),
+                "Wrong matches inside of {directory:?}"
+            );
+        });
+        picker.update(cx, |picker, cx| picker.delegate.dismissed(cx));
+        cx.run_until_parked();
+    }
+}
+
+#[gpui::test]
+async fn test_offer_creating_unmatched_query(cx: &mut TestAppContext) {
+    let app_state = init_test(cx);
+    app_state
+        .fs
+        .as_fake()
+        .insert_tree("/root", json!({ "a": { "banana": "" } }))
+        .await;
+
+    let project = Project::test(app_state.fs.clone(), ["/root".as_ref()], cx).await;
+    let (picker, _workspace, cx) = build_find_picker(project, cx);
+
+    picker
+        .update(cx, |picker, cx| {
+            picker
+                .delegate
+                .update_matches("a/cherry.txt".to_string(), cx)
+        })
+        .await;
+    picker.update(cx, |picker, _| {
+        assert_eq!(picker.delegate.matches.len(), 0);
+        assert_eq!(
+            picker.delegate.create_new_query.as_deref(),
+            Some("a/cherry.txt")
+        );
+        assert_eq!(picker.delegate.match_count(), 1);
+    });
+
+    picker
+        .update(cx, |picker, cx| {
+            picker.delegate.update_matches("bna".to_string(), cx)
+        })
+        .await;
+    picker.update(cx, |picker, _| {
+        assert_eq!(picker.delegate.matches.len(), 1);
+        assert_eq!(picker.delegate.create_new_query, None);
+        assert_eq!(picker.delegate.match_count(), 1);
+    });
+}
+
 #[gpui::test]
 async fn test_query_history(cx: &mut gpui::TestAppContext) {
     let app_state = init_test(cx);
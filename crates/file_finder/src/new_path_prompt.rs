@@ -279,6 +279,7 @@ impl PickerDelegate for NewPathDelegate {
                         .map_or(false, |entry| entry.is_ignored),
                     include_root_name,
                     candidates: project::Candidates::Directories,
+                    directory: None,
                 }
             })
             .collect::<Vec<_>>();
@@ -7,7 +7,7 @@ use ashpd::desktop::trash;
 use std::{fs::File, os::fd::AsFd};
 
 #[cfg(unix)]
-use std::os::unix::fs::MetadataExt;
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
 
 use async_tar::Archive;
 use futures::{future::BoxFuture, AsyncRead, Stream, StreamExt};
@@ -15,6 +15,7 @@ use git::repository::{GitRepository, RealGitRepository};
 use rope::Rope;
 use smol::io::AsyncWriteExt;
 use std::{
+    borrow::Cow,
     io::{self, Write},
     path::{Component, Path, PathBuf},
     pin::Pin,
@@ -92,6 +93,8 @@ pub trait Fs: Send + Sync {
     fn open_repo(&self, abs_dot_git: &Path) -> Option<Arc<dyn GitRepository>>;
     fn is_fake(&self) -> bool;
     async fn is_case_sensitive(&self) -> Result<bool>;
+    /// Returns how many bytes can still be written to the volume containing the path.
+    async fn available_space(&self, path: &Path) -> Result<u64>;
     #[cfg(any(test, feature = "test-support"))]
     fn as_fake(&self) -> &FakeFs;
 }
@@ -126,6 +129,11 @@ pub struct Metadata {
     pub mtime: SystemTime,
     pub is_symlink: bool,
     pub is_dir: bool,
+    /// Whether this is a FIFO, a socket or a device rather than a regular file or directory.
+    /// Reading such files can block forever or never end.
+    pub is_special_file: bool,
+    /// Size in bytes.
+    pub len: u64,
 }
 
 #[derive(Default)]
@@ -154,15 +162,20 @@ impl RealFs {
 #[async_trait::async_trait]
 impl Fs for RealFs {
     async fn create_dir(&self, path: &Path) -> Result<()> {
+        let path = extended_length_path(path);
         Ok(smol::fs::create_dir_all(path).await?)
     }
 
     async fn create_symlink(&self, path: &Path, target: PathBuf) -> Result<()> {
+        let path = extended_length_path(path);
         #[cfg(unix)]
         smol::fs::unix::symlink(target, path).await?;
 
         #[cfg(windows)]
-        if smol::fs::metadata(&target).await?.is_dir() {
+        if smol::fs::metadata(&*extended_length_path(&target))
+            .await?
+            .is_dir()
+        {
             smol::fs::windows::symlink_dir(target, path).await?
         } else {
             smol::fs::windows::symlink_file(target, path).await?
@@ -172,6 +185,7 @@ impl Fs for RealFs {
     }
 
     async fn create_file(&self, path: &Path, options: CreateOptions) -> Result<()> {
+        let path = extended_length_path(path);
         let mut open_options = smol::fs::OpenOptions::new();
         open_options.write(true).create(true);
         if options.overwrite {
@@ -179,7 +193,7 @@ impl Fs for RealFs {
         } else if !options.ignore_if_exists {
             open_options.create_new(true);
         }
-        open_options.open(path).await?;
+        open_options.open(&*path).await?;
         Ok(())
     }
 
@@ -188,7 +202,7 @@ impl Fs for RealFs {
         path: &Path,
         content: Pin<&mut (dyn AsyncRead + Send)>,
     ) -> Result<()> {
-        let mut file = smol::fs::File::create(&path).await?;
+        let mut file = smol::fs::File::create(extended_length_path(path)).await?;
         futures::io::copy(content, &mut file).await?;
         Ok(())
     }
@@ -198,12 +212,14 @@ impl Fs for RealFs {
         path: &Path,
         content: Archive<Pin<&mut (dyn AsyncRead + Send)>>,
     ) -> Result<()> {
-        content.unpack(path).await?;
+        content.unpack(extended_length_path(path)).await?;
         Ok(())
     }
 
     async fn copy_file(&self, source: &Path, target: &Path, options: CopyOptions) -> Result<()> {
-        if !options.overwrite && smol::fs::metadata(target).await.is_ok() {
+        let source = extended_length_path(source);
+        let target = extended_length_path(target);
+        if !options.overwrite && smol::fs::metadata(&*target).await.is_ok() {
             if options.ignore_if_exists {
                 return Ok(());
             } else {
@@ -211,12 +227,14 @@ impl Fs for RealFs {
             }
         }
 
-        smol::fs::copy(source, target).await?;
+        smol::fs::copy(&*source, &*target).await?;
         Ok(())
     }
 
     async fn rename(&self, source: &Path, target: &Path, options: RenameOptions) -> Result<()> {
-        if !options.overwrite && smol::fs::metadata(target).await.is_ok() {
+        let source = extended_length_path(source);
+        let target = extended_length_path(target);
+        if !options.overwrite && smol::fs::metadata(&*target).await.is_ok() {
             if options.ignore_if_exists {
                 return Ok(());
             } else {
@@ -224,15 +242,16 @@ impl Fs for RealFs {
             }
         }
 
-        smol::fs::rename(source, target).await?;
+        smol::fs::rename(&*source, &*target).await?;
         Ok(())
     }
 
     async fn remove_dir(&self, path: &Path, options: RemoveOptions) -> Result<()> {
+        let path = extended_length_path(path);
         let result = if options.recursive {
-            smol::fs::remove_dir_all(path).await
+            smol::fs::remove_dir_all(&*path).await
         } else {
-            smol::fs::remove_dir(path).await
+            smol::fs::remove_dir(&*path).await
         };
         match result {
             Ok(()) => Ok(()),
@@ -259,7 +278,7 @@ impl Fs for RealFs {
             }
         }
 
-        match smol::fs::remove_file(path).await {
+        match smol::fs::remove_file(extended_length_path(path)).await {
             Ok(()) => Ok(()),
             Err(err) if err.kind() == io::ErrorKind::NotFound && options.ignore_if_not_exists => {
                 Ok(())
@@ -310,16 +329,17 @@ impl Fs for RealFs {
     }
 
     async fn open_sync(&self, path: &Path) -> Result<Box<dyn io::Read>> {
-        Ok(Box::new(std::fs::File::open(path)?))
+        Ok(Box::new(std::fs::File::open(extended_length_path(path))?))
     }
 
     async fn load(&self, path: &Path) -> Result<String> {
-        let path = path.to_path_buf();
+        let path = extended_length_path(path).into_owned();
         let text = smol::unblock(|| std::fs::read_to_string(path)).await?;
         Ok(text)
     }
 
     async fn atomic_write(&self, path: PathBuf, data: String) -> Result<()> {
+        let path = extended_length_path(&path).into_owned();
         smol::unblock(move || {
             let mut tmp_file = if cfg!(target_os = "linux") {
                 // Use the directory of the destination as temp dir to avoid
@@ -343,7 +363,7 @@ impl Fs for RealFs {
         if let Some(path) = path.parent() {
             self.create_dir(path).await?;
         }
-        let file = smol::fs::File::create(path).await?;
+        let file = smol::fs::File::create(extended_length_path(path)).await?;
         let mut writer = smol::io::BufWriter::with_capacity(buffer_size, file);
         for chunk in chunks(text, line_ending) {
             writer.write_all(chunk.as_bytes()).await?;
@@ -353,23 +373,24 @@ impl Fs for RealFs {
     }
 
     async fn canonicalize(&self, path: &Path) -> Result<PathBuf> {
-        Ok(smol::fs::canonicalize(path).await?)
+        Ok(smol::fs::canonicalize(extended_length_path(path)).await?)
     }
 
     async fn is_file(&self, path: &Path) -> bool {
-        smol::fs::metadata(path)
+        smol::fs::metadata(extended_length_path(path))
             .await
             .map_or(false, |metadata| metadata.is_file())
     }
 
     async fn is_dir(&self, path: &Path) -> bool {
-        smol::fs::metadata(path)
+        smol::fs::metadata(extended_length_path(path))
             .await
             .map_or(false, |metadata| metadata.is_dir())
     }
 
     async fn metadata(&self, path: &Path) -> Result<Option<Metadata>> {
-        let symlink_metadata = match smol::fs::symlink_metadata(path).await {
+        let path = extended_length_path(path);
+        let symlink_metadata = match smol::fs::symlink_metadata(&*path).await {
             Ok(metadata) => metadata,
             Err(err) => {
                 return match (err.kind(), err.raw_os_error()) {
@@ -382,7 +403,7 @@ impl Fs for RealFs {
 
         let is_symlink = symlink_metadata.file_type().is_symlink();
         let metadata = if is_symlink {
-            smol::fs::metadata(path).await?
+            smol::fs::metadata(&*path).await?
         } else {
             symlink_metadata
         };
@@ -391,18 +412,30 @@ impl Fs for RealFs {
         let inode = metadata.ino();
 
         #[cfg(windows)]
-        let inode = file_id(path).await?;
+        let inode = file_id(&*path).await?;
+
+        let file_type = metadata.file_type();
+        #[cfg(unix)]
+        let is_special_file = file_type.is_fifo()
+            || file_type.is_socket()
+            || file_type.is_block_device()
+            || file_type.is_char_device();
+
+        #[cfg(windows)]
+        let is_special_file = false;
 
         Ok(Some(Metadata {
             inode,
             mtime: metadata.modified().unwrap(),
             is_symlink,
-            is_dir: metadata.file_type().is_dir(),
+            is_dir: file_type.is_dir(),
+            is_special_file,
+            len: metadata.len(),
         }))
     }
 
     async fn read_link(&self, path: &Path) -> Result<PathBuf> {
-        let path = smol::fs::read_link(path).await?;
+        let path = smol::fs::read_link(extended_length_path(path)).await?;
         Ok(path)
     }
 
@@ -410,10 +443,15 @@ impl Fs for RealFs {
         &self,
         path: &Path,
     ) -> Result<Pin<Box<dyn Send + Stream<Item = Result<PathBuf>>>>> {
-        let result = smol::fs::read_dir(path).await?.map(|entry| match entry {
-            Ok(entry) => Ok(entry.path()),
-            Err(error) => Err(anyhow!("failed to read dir entry {:?}", error)),
-        });
+        // The entries are joined to `path` rather than to its extended-length form, so that
+        // their paths compare equal to the ones built by callers.
+        let dir_path = path.to_path_buf();
+        let result = smol::fs::read_dir(extended_length_path(path))
+            .await?
+            .map(move |entry| match entry {
+                Ok(entry) => Ok(dir_path.join(entry.file_name())),
+                Err(error) => Err(anyhow!("failed to read dir entry {:?}", error)),
+            });
         Ok(Box::pin(result))
     }
 
@@ -595,6 +633,10 @@ impl Fs for RealFs {
         case_sensitive
     }
 
+    async fn available_space(&self, path: &Path) -> Result<u64> {
+        available_space(&extended_length_path(path))
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     fn as_fake(&self) -> &FakeFs {
         panic!("called `RealFs::as_fake`")
@@ -648,6 +690,7 @@ struct FakeFsState {
     buffered_events: Vec<PathBuf>,
     metadata_call_count: usize,
     read_dir_call_count: usize,
+    available_space: u64,
 }
 
 #[cfg(any(test, feature = "test-support"))]
@@ -798,6 +841,7 @@ impl FakeFs {
                 buffered_events: Vec::new(),
                 events_paused: false,
                 read_dir_call_count: 0,
+                available_space: u64::MAX,
                 metadata_call_count: 0,
             }),
         })
@@ -872,6 +916,10 @@ impl FakeFs {
         entry.file_content(&path).cloned()
     }
 
+    pub fn set_available_space(&self, available_space: u64) {
+        self.state.lock().available_space = available_space;
+    }
+
     pub fn pause_events(&self) {
         self.state.lock().events_paused = true;
     }
@@ -1496,17 +1544,25 @@ impl Fs for FakeFs {
 
             let entry = entry.lock();
             Ok(Some(match &*entry {
-                FakeFsEntry::File { inode, mtime, .. } => Metadata {
+                FakeFsEntry::File {
+                    inode,
+                    mtime,
+                    content,
+                } => Metadata {
                     inode: *inode,
                     mtime: *mtime,
                     is_dir: false,
                     is_symlink,
+                    is_special_file: false,
+                    len: content.len() as u64,
                 },
                 FakeFsEntry::Dir { inode, mtime, .. } => Metadata {
                     inode: *inode,
                     mtime: *mtime,
                     is_dir: true,
                     is_symlink,
+                    is_special_file: false,
+                    len: 0,
                 },
                 FakeFsEntry::Symlink { .. } => unreachable!(),
             }))
@@ -1597,6 +1653,13 @@ impl Fs for FakeFs {
         Ok(true)
     }
 
+    async fn available_space(&self, path: &Path) -> Result<u64> {
+        self.simulate_random_delay().await;
+        let state = self.state.lock();
+        state.read_path(path)?;
+        Ok(state.available_space)
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     fn as_fake(&self) -> &FakeFs {
         self
@@ -1645,11 +1708,25 @@ pub fn normalize_path(path: &Path) -> PathBuf {
     ret
 }
 
+/// How many levels deep `copy_recursive` descends, so that copying a directory containing a
+/// symlink to one of its ancestors fails rather than never finishing.
+const MAX_COPY_DEPTH: usize = 256;
+
 pub fn copy_recursive<'a>(
     fs: &'a dyn Fs,
     source: &'a Path,
     target: &'a Path,
     options: CopyOptions,
+) -> BoxFuture<'a, Result<()>> {
+    copy_recursive_at_depth(fs, source, target, options, 0)
+}
+
+fn copy_recursive_at_depth<'a>(
+    fs: &'a dyn Fs,
+    source: &'a Path,
+    target: &'a Path,
+    options: CopyOptions,
+    depth: usize,
 ) -> BoxFuture<'a, Result<()>> {
     use futures::future::FutureExt;
 
@@ -1659,6 +1736,12 @@ pub fn copy_recursive<'a>(
             .await?
             .ok_or_else(|| anyhow!("path does not exist: {}", source.display()))?;
         if metadata.is_dir {
+            if depth >= MAX_COPY_DEPTH {
+                return Err(anyhow!(
+                    "{} is nested more than {MAX_COPY_DEPTH} levels deep",
+                    source.display()
+                ));
+            }
             if !options.overwrite && fs.metadata(target).await.is_ok_and(|m| m.is_some()) {
                 if options.ignore_if_exists {
                     return Ok(());
@@ -1681,13 +1764,35 @@ pub fn copy_recursive<'a>(
             while let Some(child_path) = children.next().await {
                 if let Ok(child_path) = child_path {
                     if let Some(file_name) = child_path.file_name() {
+                        // Leave special files out of copied directories, rather than failing.
+                        if fs
+                            .metadata(&child_path)
+                            .await
+                            .ok()
+                            .flatten()
+                            .map_or(false, |metadata| metadata.is_special_file)
+                        {
+                            continue;
+                        }
                         let child_target_path = target.join(file_name);
-                        copy_recursive(fs, &child_path, &child_target_path, options).await?;
+                        copy_recursive_at_depth(
+                            fs,
+                            &child_path,
+                            &child_target_path,
+                            options,
+                            depth + 1,
+                        )
+                        .await?;
                     }
                 }
             }
 
             Ok(())
+        } else if metadata.is_special_file {
+            Err(anyhow!(
+                "{} is a FIFO, socket or device and can't be copied",
+                source.display()
+            ))
         } else {
             fs.copy_file(source, target, options).await
         }
@@ -1695,6 +1800,88 @@ pub fn copy_recursive<'a>(
     .boxed()
 }
 
+/// Windows rejects paths longer than `MAX_PATH` (260 UTF-16 units), unless they are
+/// given in the extended-length form, prefixed with `\\?\`.
+#[cfg(target_os = "windows")]
+fn extended_length_path(path: &Path) -> Cow<'_, Path> {
+    use std::{ffi::OsString, os::windows::ffi::OsStrExt, path::Prefix};
+
+    const MAX_PATH: usize = 260;
+
+    if path.as_os_str().encode_wide().count() < MAX_PATH || !path.is_absolute() {
+        return Cow::Borrowed(path);
+    }
+
+    // Extended-length paths are passed to the file system as is, so `.` and `..` are
+    // resolved beforehand and the separators must all be backslashes.
+    let normalized_path = normalize_path(path);
+    let mut components = normalized_path.components();
+    let mut extended = OsString::from(r"\\?\");
+    match components.next() {
+        Some(Component::Prefix(prefix)) => match prefix.kind() {
+            Prefix::Disk(_) => extended.push(prefix.as_os_str()),
+            Prefix::UNC(server, share) => {
+                extended.push(r"UNC\");
+                extended.push(server);
+                extended.push(r"\");
+                extended.push(share);
+            }
+            // Verbatim and device paths are already used as is.
+            _ => return Cow::Borrowed(path),
+        },
+        _ => return Cow::Borrowed(path),
+    }
+    for component in components {
+        if let Component::Normal(name) = component {
+            extended.push(r"\");
+            extended.push(name);
+        }
+    }
+    Cow::Owned(PathBuf::from(extended))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn extended_length_path(path: &Path) -> Cow<'_, Path> {
+    Cow::Borrowed(path)
+}
+
+#[cfg(unix)]
+#[allow(clippy::unnecessary_cast)]
+fn available_space(path: &Path) -> Result<u64> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let path = CString::new(path.as_os_str().as_bytes())?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+    // The field types differ between platforms.
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(target_os = "windows")]
+fn available_space(path: &Path) -> Result<u64> {
+    use std::os::windows::ffi::OsStrExt;
+
+    use windows::{core::PCWSTR, Win32::Storage::FileSystem::GetDiskFreeSpaceExW};
+
+    let path = path
+        .as_os_str()
+        .encode_wide()
+        .chain(Some(0))
+        .collect::<Vec<u16>>();
+    let mut available_space = 0;
+    unsafe {
+        GetDiskFreeSpaceExW(
+            PCWSTR(path.as_ptr()),
+            Some(&mut available_space),
+            None,
+            None,
+        )?
+    };
+    Ok(available_space)
+}
+
 // todo(windows)
 // can we get file id not open the file twice?
 // https://github.com/rust-lang/rust/issues/63010
@@ -1784,4 +1971,43 @@ mod tests {
             "D",
         );
     }
+
+    #[gpui::test]
+    async fn test_copy_recursive_symlink_cycle(executor: BackgroundExecutor) {
+        let fs = FakeFs::new(executor.clone());
+        fs.insert_tree("/root", json!({ "dir": { "a": "A" } }))
+            .await;
+        fs.create_symlink("/root/dir/loop".as_ref(), ".".into())
+            .await
+            .unwrap();
+
+        let error = copy_recursive(
+            fs.as_ref(),
+            "/root/dir".as_ref(),
+            "/root/copy".as_ref(),
+            CopyOptions::default(),
+        )
+        .await
+        .unwrap_err();
+        assert!(error.to_string().contains("levels deep"), "{error}");
+        assert_eq!(fs.load("/root/copy/a".as_ref()).await.unwrap(), "A");
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn test_extended_length_path() {
+        let short_path = Path::new(r"C:\dir\..\file.txt");
+        assert_eq!(extended_length_path(short_path), Cow::Borrowed(short_path));
+
+        let name = "a".repeat(200);
+        assert_eq!(
+            extended_length_path(&PathBuf::from(format!(r"C:\dir\.\sub\..\{name}\{name}"))),
+            PathBuf::from(format!(r"\\?\C:\dir\{name}\{name}")),
+            "`.` and `..` should be resolved, as Windows leaves them in extended-length paths"
+        );
+        assert_eq!(
+            extended_length_path(&PathBuf::from(format!(r"\\server\share/{name}/{name}"))),
+            PathBuf::from(format!(r"\\?\UNC\server\share\{name}\{name}")),
+        );
+    }
 }
@@ -47,6 +47,21 @@ pub trait GitRepository: Send + Sync {
     fn create_branch(&self, _: &str) -> Result<()>;
 
     fn blame(&self, path: &Path, content: Rope) -> Result<crate::blame::Blame>;
+
+    /// Returns the working directories of the worktrees linked to this repository with `git worktree add`.
+    fn linked_worktrees(&self) -> Result<Vec<PathBuf>>;
+
+    /// Returns the paths of the submodules registered in this repository, relative to its working directory.
+    fn submodule_paths(&self) -> Result<Vec<PathBuf>>;
+
+    /// Returns the tracked files deleted from the working directory, but not from the index.
+    fn deleted_paths(&self) -> Result<Vec<RepoPath>>;
+
+    /// Checks out the given files from the index, restoring them in the working directory.
+    fn restore_paths(&self, paths: &[RepoPath]) -> Result<()>;
+
+    /// Removes the given files, deleted from the working directory, from the index.
+    fn stage_deletions(&self, paths: &[RepoPath]) -> Result<()>;
 }
 
 impl std::fmt::Debug for dyn GitRepository {
@@ -200,6 +215,66 @@ impl GitRepository for RealGitRepository {
             self.hosting_provider_registry.clone(),
         )
     }
+
+    fn linked_worktrees(&self) -> Result<Vec<PathBuf>> {
+        let repo = self.repository.lock();
+        let names = repo.worktrees()?;
+        Ok(names
+            .iter()
+            .flatten()
+            .filter_map(|name| repo.find_worktree(name).ok())
+            .map(|worktree| worktree.path().to_path_buf())
+            .collect())
+    }
+
+    fn submodule_paths(&self) -> Result<Vec<PathBuf>> {
+        let repo = self.repository.lock();
+        Ok(repo
+            .submodules()?
+            .iter()
+            .map(|submodule| submodule.path().to_path_buf())
+            .collect())
+    }
+
+    fn deleted_paths(&self) -> Result<Vec<RepoPath>> {
+        let repo = self.repository.lock();
+        let mut options = git2::StatusOptions::new();
+        options.include_untracked(false).include_ignored(false);
+        let statuses = repo.statuses(Some(&mut options))?;
+        let mut paths = statuses
+            .iter()
+            .filter(|entry| entry.status().contains(git2::Status::WT_DELETED))
+            .filter_map(|entry| Some(RepoPath::new(PathBuf::from(entry.path()?))))
+            .collect::<Vec<_>>();
+        paths.sort_unstable();
+        Ok(paths)
+    }
+
+    fn restore_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        if paths.is_empty() {
+            return Ok(());
+        }
+        let repo = self.repository.lock();
+        let mut checkout = git2::build::CheckoutBuilder::new();
+        checkout.force().disable_pathspec_match(true);
+        for path in paths {
+            check_path_to_repo_path_errors(path)?;
+            checkout.path(path.as_path());
+        }
+        repo.checkout_index(None, Some(&mut checkout))?;
+        Ok(())
+    }
+
+    fn stage_deletions(&self, paths: &[RepoPath]) -> Result<()> {
+        let repo = self.repository.lock();
+        let mut index = repo.index()?;
+        for path in paths {
+            check_path_to_repo_path_errors(path)?;
+            index.remove_path(path)?;
+        }
+        index.write()?;
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -213,6 +288,9 @@ pub struct FakeGitRepositoryState {
     pub blames: HashMap<PathBuf, Blame>,
     pub worktree_statuses: HashMap<RepoPath, GitFileStatus>,
     pub branch_name: Option<String>,
+    pub linked_worktrees: Vec<PathBuf>,
+    pub submodule_paths: Vec<PathBuf>,
+    pub deleted_paths: Vec<RepoPath>,
 }
 
 impl FakeGitRepository {
@@ -285,6 +363,35 @@ impl GitRepository for FakeGitRepository {
             .with_context(|| format!("failed to get blame for {:?}", path))
             .cloned()
     }
+
+    fn linked_worktrees(&self) -> Result<Vec<PathBuf>> {
+        let state = self.state.lock();
+        Ok(state.linked_worktrees.clone())
+    }
+
+    fn submodule_paths(&self) -> Result<Vec<PathBuf>> {
+        let state = self.state.lock();
+        Ok(state.submodule_paths.clone())
+    }
+
+    fn deleted_paths(&self) -> Result<Vec<RepoPath>> {
+        let state = self.state.lock();
+        let mut paths = state.deleted_paths.clone();
+        paths.sort_unstable();
+        Ok(paths)
+    }
+
+    fn restore_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let mut state = self.state.lock();
+        state.deleted_paths.retain(|path| !paths.contains(path));
+        Ok(())
+    }
+
+    fn stage_deletions(&self, paths: &[RepoPath]) -> Result<()> {
+        let mut state = self.state.lock();
+        state.deleted_paths.retain(|path| !paths.contains(path));
+        Ok(())
+    }
 }
 
 fn check_path_to_repo_path_errors(relative_file_path: &Path) -> Result<()> {
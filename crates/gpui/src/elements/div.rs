@@ -1246,6 +1246,8 @@ pub struct Interactivity {
     pub(crate) tracked_focus_handle: Option<FocusHandle>,
     pub(crate) tracked_scroll_handle: Option<ScrollHandle>,
     pub(crate) scroll_offset: Option<Rc<RefCell<Point<Pixels>>>>,
+    /// How much scroll wheel deltas are multiplied by, 1 when unset.
+    pub(crate) scroll_sensitivity: Option<f32>,
     pub(crate) group: Option<SharedString>,
     /// The base style of the element, before any modifications are applied
     /// by focus, active, etc.
@@ -2046,12 +2048,14 @@ impl Interactivity {
         if let Some(scroll_offset) = self.scroll_offset.clone() {
             let overflow = style.overflow;
             let line_height = cx.line_height();
+            let sensitivity = self.scroll_sensitivity.unwrap_or(1.);
             let hitbox = hitbox.clone();
             cx.on_mouse_event(move |event: &ScrollWheelEvent, phase, cx| {
                 if phase == DispatchPhase::Bubble && hitbox.is_hovered(cx) {
                     let mut scroll_offset = scroll_offset.borrow_mut();
                     let old_scroll_offset = *scroll_offset;
                     let delta = event.delta.pixel_delta(line_height);
+                    let delta = point(delta.x * sensitivity, delta.y * sensitivity);
 
                     if overflow.x == Overflow::Scroll {
                         let mut delta_x = Pixels::ZERO;
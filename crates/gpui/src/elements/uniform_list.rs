@@ -318,6 +318,12 @@ impl UniformList {
         self
     }
 
+    /// Multiplies the distance scrolled by the mouse wheel and trackpad.
+    pub fn with_scroll_sensitivity(mut self, sensitivity: f32) -> Self {
+        self.interactivity.scroll_sensitivity = Some(sensitivity);
+        self
+    }
+
     fn measure_item(&self, list_width: Option<Pixels>, cx: &mut WindowContext) -> Size<Pixels> {
         if self.item_count == 0 {
             return Size::default();
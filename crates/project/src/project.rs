@@ -34,7 +34,7 @@ use futures::{
 };
 use fuzzy::CharBag;
 use git::{blame::Blame, repository::GitRepository};
-use globset::{Glob, GlobSet, GlobSetBuilder};
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
 use gpui::{
     AnyModel, AppContext, AsyncAppContext, BackgroundExecutor, BorrowAppContext, Context, Entity,
     EventEmitter, Model, ModelContext, PromptLevel, SharedString, Task, WeakModel, WindowContext,
@@ -333,6 +333,7 @@ pub enum Event {
     WorktreeRemoved(WorktreeId),
     WorktreeUpdatedEntries(WorktreeId, UpdatedEntriesSet),
     WorktreeUpdatedGitRepositories,
+    BufferOpened(Model<Buffer>),
     DiskBasedDiagnosticsStarted {
         language_server_id: LanguageServerId,
     },
@@ -355,6 +356,10 @@ pub enum Event {
     CollaboratorLeft(proto::PeerId),
     RefreshInlayHints,
     RevealInProjectPanel(ProjectEntryId),
+    SelectInProjectPanel(Vec<ProjectEntryId>),
+    /// The host of a remote project started or stopped sharing their project panel tree view,
+    /// or changed it.
+    HostTreeViewUpdated(Option<SharedTreeView>),
     SnippetEdit(BufferId, Vec<(lsp::Range, Snippet)>),
 }
 
@@ -388,6 +393,13 @@ pub struct LanguageServerProgress {
     pub last_update_at: Instant,
 }
 
+/// Expansion and selection of a project panel, shared by the host of a project with its guests.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SharedTreeView {
+    pub expanded_entry_ids: Vec<ProjectEntryId>,
+    pub selected_entry_id: Option<ProjectEntryId>,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
 pub struct ProjectPath {
     pub worktree_id: WorktreeId,
@@ -697,6 +709,7 @@ impl Project {
         client.add_model_request_handler(Self::handle_open_new_buffer);
         client.add_model_request_handler(Self::handle_save_buffer);
         client.add_model_message_handler(Self::handle_update_diff_base);
+        client.add_model_message_handler(Self::handle_update_shared_tree_view);
         client.add_model_request_handler(Self::handle_lsp_command::<lsp_ext_command::ExpandMacro>);
         client.add_model_request_handler(Self::handle_blame_buffer);
         client.add_model_request_handler(Self::handle_multi_lsp_query);
@@ -1444,11 +1457,64 @@ impl Project {
                 "No worktree for path {project_path:?}"
             ))));
         };
-        worktree.update(cx, |worktree, cx| {
+        let create_entry = worktree.update(cx, |worktree, cx| {
             worktree.create_entry(project_path.path, is_directory, cx)
+        });
+        if !self.is_local() {
+            return create_entry;
+        }
+
+        let worktree_id = project_path.worktree_id;
+        cx.spawn(move |project, mut cx| async move {
+            let created_entry = create_entry.await?;
+            if let CreatedEntry::Included(entry) = &created_entry {
+                let abs_path = worktree
+                    .update(&mut cx, |worktree, _| worktree.abs_path().join(&entry.path))?;
+                project.update(&mut cx, |project, _| {
+                    project.notify_language_servers_of_created_entry(
+                        worktree_id,
+                        &abs_path,
+                        entry.is_dir(),
+                    )
+                })?;
+            }
+            Ok(created_entry)
         })
     }
 
+    /// Sends `workspace/didCreateFiles` to the worktree's language servers whose file operation
+    /// filters match the created entry.
+    pub fn notify_language_servers_of_created_entry(
+        &self,
+        worktree_id: WorktreeId,
+        abs_path: &Path,
+        is_dir: bool,
+    ) {
+        let Ok(uri) = lsp::Url::from_file_path(abs_path) else {
+            return;
+        };
+        for (_, _, server) in self.language_servers_for_worktree(worktree_id) {
+            let wants_did_create = server
+                .capabilities()
+                .workspace
+                .as_ref()
+                .and_then(|workspace| workspace.file_operations.as_ref())
+                .and_then(|file_operations| file_operations.did_create.as_ref())
+                .map_or(false, |did_create| {
+                    file_operation_filters_match(&did_create.filters, abs_path, is_dir)
+                });
+            if wants_did_create {
+                server
+                    .notify::<lsp::notification::DidCreateFiles>(lsp::CreateFilesParams {
+                        files: vec![lsp::FileCreate {
+                            uri: uri.to_string(),
+                        }],
+                    })
+                    .log_err();
+            }
+        }
+    }
+
     pub fn copy_entry(
         &mut self,
         entry_id: ProjectEntryId,
@@ -1458,8 +1524,28 @@ impl Project {
         let Some(worktree) = self.worktree_for_entry(entry_id, cx) else {
             return Task::ready(Ok(None));
         };
-        worktree.update(cx, |worktree, cx| {
+        let copy_entry = worktree.update(cx, |worktree, cx| {
             worktree.copy_entry(entry_id, new_path, cx)
+        });
+        if !self.is_local() {
+            return copy_entry;
+        }
+
+        let worktree_id = worktree.read(cx).id();
+        cx.spawn(move |project, mut cx| async move {
+            let new_entry = copy_entry.await?;
+            if let Some(entry) = &new_entry {
+                let abs_path = worktree
+                    .update(&mut cx, |worktree, _| worktree.abs_path().join(&entry.path))?;
+                project.update(&mut cx, |project, _| {
+                    project.notify_language_servers_of_created_entry(
+                        worktree_id,
+                        &abs_path,
+                        entry.is_dir(),
+                    )
+                })?;
+            }
+            Ok(new_entry)
         })
     }
 
@@ -2462,6 +2548,7 @@ impl Project {
                 sender.send(Ok(buffer.clone())).ok();
             }
         }
+        cx.emit(Event::BufferOpened(buffer.clone()));
         Ok(())
     }
 
@@ -7645,6 +7732,32 @@ impl Project {
         None
     }
 
+    /// Sends the host's project panel tree view to the guests of the shared project, or tells
+    /// them it is no longer shared.
+    pub fn share_tree_view(&self, tree_view: Option<SharedTreeView>) {
+        if !self.is_local() {
+            return;
+        }
+        if let Some(project_id) = self.remote_id() {
+            self.client
+                .send(proto::UpdateSharedTreeView {
+                    project_id,
+                    tree_view: tree_view.map(|tree_view| proto::SharedTreeView {
+                        expanded_entry_ids: tree_view
+                            .expanded_entry_ids
+                            .iter()
+                            .map(ProjectEntryId::to_proto)
+                            .collect(),
+                        selected_entry_id: tree_view
+                            .selected_entry_id
+                            .as_ref()
+                            .map(ProjectEntryId::to_proto),
+                    }),
+                })
+                .log_err();
+        }
+    }
+
     pub fn is_shared(&self) -> bool {
         match &self.client_state {
             ProjectClientState::Shared { .. } => true,
@@ -9186,6 +9299,24 @@ impl Project {
         })?
     }
 
+    async fn handle_update_shared_tree_view(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::UpdateSharedTreeView>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        let tree_view = envelope.payload.tree_view.map(|tree_view| SharedTreeView {
+            expanded_entry_ids: tree_view
+                .expanded_entry_ids
+                .into_iter()
+                .map(ProjectEntryId::from_proto)
+                .collect(),
+            selected_entry_id: tree_view.selected_entry_id.map(ProjectEntryId::from_proto),
+        });
+        this.update(&mut cx, |_, cx| {
+            cx.emit(Event::HostTreeViewUpdated(tree_view));
+        })
+    }
+
     async fn handle_update_diff_base(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::UpdateDiffBase>,
@@ -11362,6 +11493,49 @@ pub struct PathMatchCandidateSet {
     pub include_ignored: bool,
     pub include_root_name: bool,
     pub candidates: Candidates,
+    /// Only consider entries inside of this directory, when set.
+    pub directory: Option<Arc<Path>>,
+}
+
+impl PathMatchCandidateSet {
+    fn traversal_kinds(&self) -> (bool, bool) {
+        match self.candidates {
+            Candidates::Directories => (false, true),
+            Candidates::Files => (true, false),
+            Candidates::Entries => (true, true),
+        }
+    }
+
+    fn total_len(&self) -> usize {
+        if self.include_ignored {
+            self.snapshot.file_count()
+        } else {
+            self.snapshot.visible_file_count()
+        }
+    }
+
+    /// Returns the offsets of the candidates inside of the directory, as entries of a directory
+    /// are contiguous in the traversal.
+    fn directory_offsets(&self, directory: &Path) -> Range<usize> {
+        let (include_files, include_dirs) = self.traversal_kinds();
+        let offset_of = |path: &Path| {
+            self.snapshot
+                .traverse_from_path(include_files, include_dirs, self.include_ignored, path)
+                .start_offset()
+        };
+        let start = offset_of(directory);
+        let mut entries = self
+            .snapshot
+            .traverse_from_path(true, true, true, directory);
+        let end = if entries.advance_to_sibling() {
+            entries
+                .entry()
+                .map_or_else(|| self.total_len(), |sibling| offset_of(&sibling.path))
+        } else {
+            self.total_len()
+        };
+        start..end.max(start)
+    }
 }
 
 pub enum Candidates {
@@ -11381,10 +11555,9 @@ impl<'a> fuzzy::PathMatchCandidateSet<'a> for PathMatchCandidateSet {
     }
 
     fn len(&self) -> usize {
-        if self.include_ignored {
-            self.snapshot.file_count()
-        } else {
-            self.snapshot.visible_file_count()
+        match &self.directory {
+            Some(directory) => self.directory_offsets(directory).len(),
+            None => self.total_len(),
         }
     }
 
@@ -11399,6 +11572,10 @@ impl<'a> fuzzy::PathMatchCandidateSet<'a> for PathMatchCandidateSet {
     }
 
     fn candidates(&'a self, start: usize) -> Self::Candidates {
+        let start = match &self.directory {
+            Some(directory) => self.directory_offsets(directory).start + start,
+            None => start,
+        };
         PathMatchCandidateSetIter {
             traversal: match self.candidates {
                 Candidates::Directories => self.snapshot.directories(self.include_ignored, start),
@@ -11676,6 +11853,42 @@ fn is_not_found_error(error: &anyhow::Error) -> bool {
         .is_some_and(|err| err.kind() == io::ErrorKind::NotFound)
 }
 
+/// Whether any of the filters a language server registered for a file operation matches the
+/// entry at `abs_path`.
+fn file_operation_filters_match(
+    filters: &[lsp::FileOperationFilter],
+    abs_path: &Path,
+    is_dir: bool,
+) -> bool {
+    filters.iter().any(|filter| {
+        if filter
+            .scheme
+            .as_deref()
+            .map_or(false, |scheme| scheme != "file")
+        {
+            return false;
+        }
+        let kind_matches = match filter.pattern.matches {
+            Some(lsp::FileOperationPatternKind::File) => !is_dir,
+            Some(lsp::FileOperationPatternKind::Folder) => is_dir,
+            None => true,
+        };
+        let ignore_case = filter
+            .pattern
+            .options
+            .as_ref()
+            .and_then(|options| options.ignore_case)
+            .unwrap_or(false);
+        kind_matches
+            && GlobBuilder::new(&filter.pattern.glob)
+                .case_insensitive(ignore_case)
+                .literal_separator(true)
+                .build()
+                .log_err()
+                .map_or(false, |glob| glob.compile_matcher().is_match(abs_path))
+    })
+}
+
 fn include_text(server: &lsp::LanguageServer) -> bool {
     server
         .capabilities()
@@ -11959,11 +12172,35 @@ impl DiagnosticSummary {
     }
 }
 
+/// Where directories are sorted relative to the files next to them.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum DirectoriesPosition {
+    #[default]
+    First,
+    Last,
+    /// Interleaved with the files, by name.
+    Mixed,
+}
+
+/// How worktree entries are ordered among their siblings.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct EntryOrdering {
+    pub directories_position: DirectoriesPosition,
+    /// Whether names are compared byte-wise, uppercase letters coming before lowercase ones
+    /// like in `ls`, instead of ignoring case.
+    pub case_sensitive: bool,
+}
+
 pub fn sort_worktree_entries(entries: &mut Vec<Entry>) {
+    sort_worktree_entries_with_ordering(entries, EntryOrdering::default());
+}
+
+pub fn sort_worktree_entries_with_ordering(entries: &mut Vec<Entry>, ordering: EntryOrdering) {
     entries.sort_by(|entry_a, entry_b| {
-        compare_paths(
+        compare_paths_with_ordering(
             (&entry_a.path, entry_a.is_file()),
             (&entry_b.path, entry_b.is_file()),
+            ordering,
         )
     });
 }
@@ -12040,9 +12277,14 @@ fn sort_search_matches(search_matches: &mut Vec<SearchMatchCandidate>, cx: &AppC
     });
 }
 
-fn compare_paths(
+fn compare_paths(a: (&Path, bool), b: (&Path, bool)) -> cmp::Ordering {
+    compare_paths_with_ordering(a, b, EntryOrdering::default())
+}
+
+fn compare_paths_with_ordering(
     (path_a, a_is_file): (&Path, bool),
     (path_b, b_is_file): (&Path, bool),
+    ordering: EntryOrdering,
 ) -> cmp::Ordering {
     let mut components_a = path_a.components().peekable();
     let mut components_b = path_b.components().peekable();
@@ -12051,7 +12293,12 @@ fn compare_paths(
             (Some(component_a), Some(component_b)) => {
                 let a_is_file = components_a.peek().is_none() && a_is_file;
                 let b_is_file = components_b.peek().is_none() && b_is_file;
-                let ordering = a_is_file.cmp(&b_is_file).then_with(|| {
+                let kind_ordering = match ordering.directories_position {
+                    DirectoriesPosition::First => a_is_file.cmp(&b_is_file),
+                    DirectoriesPosition::Last => b_is_file.cmp(&a_is_file),
+                    DirectoriesPosition::Mixed => cmp::Ordering::Equal,
+                };
+                let ordering = kind_ordering.then_with(|| {
                     let maybe_numeric_ordering = maybe!({
                         let num_and_remainder_a = Path::new(component_a.as_os_str())
                             .file_stem()
@@ -12066,6 +12313,9 @@ fn compare_paths(
                     });
 
                     maybe_numeric_ordering.unwrap_or_else(|| {
+                        if ordering.case_sensitive {
+                            return component_a.as_os_str().cmp(component_b.as_os_str());
+                        }
                         let name_a = UniCase::new(component_a.as_os_str().to_string_lossy());
                         let name_b = UniCase::new(component_b.as_os_str().to_string_lossy());
 
@@ -859,6 +859,108 @@ async fn test_reporting_fs_changes_to_language_servers(cx: &mut gpui::TestAppCon
     );
 }
 
+#[gpui::test]
+async fn test_notifying_language_servers_of_created_files(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/the-root",
+        json!({
+            "src": {
+                "a.rs": "",
+            },
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/the-root".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            name: "the-language-server",
+            capabilities: lsp::ServerCapabilities {
+                workspace: Some(lsp::WorkspaceServerCapabilities {
+                    file_operations: Some(lsp::WorkspaceFileOperationsServerCapabilities {
+                        did_create: Some(lsp::FileOperationRegistrationOptions {
+                            filters: vec![lsp::FileOperationFilter {
+                                scheme: Some("file".to_string()),
+                                pattern: lsp::FileOperationPattern {
+                                    glob: "**/*.rs".to_string(),
+                                    matches: Some(lsp::FileOperationPatternKind::File),
+                                    options: None,
+                                },
+                            }],
+                        }),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let _buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/the-root/src/a.rs", cx)
+        })
+        .await
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+    let created_files = Arc::new(Mutex::new(Vec::new()));
+    fake_server.handle_notification::<lsp::notification::DidCreateFiles, _>({
+        let created_files = created_files.clone();
+        move |params, _| {
+            created_files
+                .lock()
+                .extend(params.files.into_iter().map(|file| file.uri));
+        }
+    });
+    cx.executor().run_until_parked();
+
+    let worktree_id = project.update(cx, |project, cx| {
+        project.worktrees().next().unwrap().read(cx).id()
+    });
+    for (path, is_dir) in [
+        ("src/b.rs", false),
+        ("src/c.txt", false),
+        ("src/d.rs", true),
+    ] {
+        project
+            .update(cx, |project, cx| {
+                project.create_entry((worktree_id, Path::new(path)), is_dir, cx)
+            })
+            .await
+            .unwrap();
+    }
+    let entry_id = project.update(cx, |project, cx| {
+        project
+            .entry_for_path(&(worktree_id, Path::new("src/a.rs")).into(), cx)
+            .unwrap()
+            .id
+    });
+    project
+        .update(cx, |project, cx| {
+            project.copy_entry(entry_id, Path::new("src/e.rs"), cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    assert_eq!(
+        *created_files.lock(),
+        &[
+            "file:///the-root/src/b.rs".to_string(),
+            "file:///the-root/src/e.rs".to_string(),
+        ],
+        "Only created files matching the server's filters should be reported"
+    );
+}
+
 #[gpui::test]
 async fn test_single_file_worktrees_diagnostics(cx: &mut gpui::TestAppContext) {
     init_test(cx);
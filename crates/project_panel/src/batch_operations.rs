@@ -0,0 +1,82 @@
+use std::path::{Path, PathBuf};
+
+use project::Worktree;
+
+/// An operation on the entries of a worktree, with paths relative to its root.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BatchOperation {
+    Create { path: PathBuf, is_dir: bool },
+    Move { from: PathBuf, to: PathBuf },
+    Delete { path: PathBuf, trash: bool },
+}
+
+impl BatchOperation {
+    /// The path the operation creates, which must not exist yet.
+    pub(crate) fn destination(&self) -> Option<&Path> {
+        match self {
+            Self::Create { path, .. } => Some(path),
+            Self::Move { to, .. } => Some(to),
+            Self::Delete { .. } => None,
+        }
+    }
+
+    pub(crate) fn with_destination(self, destination: PathBuf) -> Self {
+        match self {
+            Self::Create { is_dir, .. } => Self::Create {
+                path: destination,
+                is_dir,
+            },
+            Self::Move { from, .. } => Self::Move {
+                from,
+                to: destination,
+            },
+            delete @ Self::Delete { .. } => delete,
+        }
+    }
+}
+
+/// What to do with an operation whose destination already exists.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Stop at the operation, keeping the ones applied before it.
+    #[default]
+    Fail,
+    /// Leave the operation out and go on with the next one.
+    Skip,
+    /// Use a free name next to the destination, like pasting does.
+    KeepBoth,
+}
+
+/// The operations of a batch that were applied, with the destinations they ended up using,
+/// and the ones left out because of conflicts.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BatchOutcome {
+    pub applied: Vec<BatchOperation>,
+    pub skipped: Vec<BatchOperation>,
+}
+
+/// Returns `path`, or the first of "name copy.ext", "name copy 1.ext"… that doesn't exist.
+pub(crate) fn available_path(worktree: &Worktree, mut path: PathBuf) -> PathBuf {
+    let Some(file_name_without_extension) = path.file_stem().map(|stem| stem.to_os_string()) else {
+        return path;
+    };
+    let extension = path.extension().map(|e| e.to_os_string());
+    let mut ix = 0;
+    while worktree.entry_for_path(&path).is_some() {
+        path.pop();
+
+        let mut new_file_name = file_name_without_extension.clone();
+        new_file_name.push(" copy");
+        if ix > 0 {
+            new_file_name.push(format!(" {}", ix));
+        }
+        if let Some(extension) = extension.as_ref() {
+            new_file_name.push(".");
+            new_file_name.push(extension);
+        }
+
+        path.push(new_file_name);
+        ix += 1;
+    }
+    path
+}
@@ -0,0 +1,233 @@
+mod available_space;
+mod code_owners;
+mod deployment_status;
+mod file_headers;
+mod modification_heat;
+
+use std::{
+    any::Any,
+    future::Future,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Result;
+use gpui::{AnyElement, AppContext, SharedString, Task};
+use project::{Entry, Fs, Project, ProjectEntryId, WorktreeId};
+use smol::future::{Boxed, FutureExt as _};
+
+pub(crate) use available_space::AvailableSpaceDecoration;
+pub(crate) use code_owners::CodeOwnerDecoration;
+pub(crate) use deployment_status::{deployment_for_path, DeploymentStatusDecoration};
+pub(crate) use file_headers::{license_header_for_path, with_license_header, FileHeaderDecoration};
+pub(crate) use modification_heat::ModificationHeatDecoration;
+
+#[cfg(test)]
+pub(crate) use {
+    available_space::AVAILABLE_SPACE_DEBOUNCE,
+    deployment_status::parse_checksums,
+    file_headers::{FILE_HEADER_BATCH_INTERVAL, FILE_HEADER_BATCH_SIZE},
+    modification_heat::ModificationAge,
+};
+
+/// How many rows above and below the rendered ones are decorated in advance, so that rows
+/// scrolled into view are decorated already.
+pub(crate) const DECORATION_PREFETCH_ROWS: usize = 100;
+
+/// A kind of row decoration, computed in the background for the rows being rendered and
+/// drawn onto them.
+pub(crate) trait DecorationProvider: 'static {
+    /// Names the decoration in logs.
+    fn name(&self) -> &'static str;
+
+    /// Whether the decoration is shown at all. Disabled decorations are cleared and not
+    /// computed.
+    fn is_enabled(&self, project: &Project, cx: &AppContext) -> bool;
+
+    /// Starts computing what is missing for the rows, or returns `None` when they are all
+    /// decorated already.
+    fn compute(
+        &mut self,
+        rows: &RenderedRows,
+        project: &Project,
+        fs: &Arc<dyn Fs>,
+        cx: &AppContext,
+    ) -> Option<DecorationJob>;
+
+    /// Stores the outcome of the last job returned by `compute`.
+    fn apply(&mut self, outcome: Box<dyn Any + Send>);
+
+    /// Forgets what the changed entries invalidate, returning whether a job in progress
+    /// is outdated by them.
+    fn entries_changed(&mut self, _worktree_id: WorktreeId, _paths: &[&Path]) -> bool {
+        false
+    }
+
+    /// Picks up the new settings, returning whether a job in progress is outdated by them.
+    fn settings_changed(&mut self, _cx: &AppContext) -> bool {
+        false
+    }
+
+    fn clear(&mut self);
+
+    fn decorate(&self, row: &DecoratedRow, decorations: &mut RowDecorations);
+
+    fn as_any(&self) -> &dyn Any;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+/// Background work of a decoration provider, whose outcome is handed back to its `apply`.
+pub(crate) struct DecorationJob {
+    pub(crate) debounce: Option<Duration>,
+    pub(crate) computation: Boxed<Result<Box<dyn Any + Send>>>,
+}
+
+impl DecorationJob {
+    pub(crate) fn new<T: Send + 'static>(
+        debounce: Option<Duration>,
+        computation: impl Future<Output = Result<T>> + Send + 'static,
+    ) -> Self {
+        Self {
+            debounce,
+            computation: async move {
+                let outcome: Box<dyn Any + Send> = Box::new(computation.await?);
+                anyhow::Ok(outcome)
+            }
+            .boxed(),
+        }
+    }
+}
+
+/// The rendered rows and the ones within `DECORATION_PREFETCH_ROWS` of them.
+#[derive(Default)]
+pub(crate) struct RenderedRows {
+    pub(crate) worktrees: Vec<worktree::Snapshot>,
+    pub(crate) rows: Vec<RenderedRow>,
+}
+
+pub(crate) struct RenderedRow {
+    pub(crate) worktree_id: WorktreeId,
+    pub(crate) entry: Entry,
+    /// How many rows away from the rendered ones the row is, zero for rendered rows.
+    pub(crate) distance: usize,
+}
+
+impl RenderedRows {
+    pub(crate) fn worktree(&self, worktree_id: WorktreeId) -> Option<&worktree::Snapshot> {
+        self.worktrees
+            .iter()
+            .find(|snapshot| snapshot.id() == worktree_id)
+    }
+
+    pub(crate) fn abs_path(&self, worktree_id: WorktreeId, path: &Path) -> Option<PathBuf> {
+        Some(self.worktree(worktree_id)?.abs_path().join(path))
+    }
+}
+
+/// The row being drawn.
+pub(crate) struct DecoratedRow<'a> {
+    pub(crate) worktree_id: WorktreeId,
+    pub(crate) entry_id: ProjectEntryId,
+    pub(crate) path: &'a Path,
+    pub(crate) is_dir: bool,
+    pub(crate) mtime: Option<SystemTime>,
+}
+
+/// What the decorations add to a row, drawn by the panel along with the rest of the row.
+#[derive(Default)]
+pub(crate) struct RowDecorations {
+    pub(crate) start_slot: Option<AnyElement>,
+    pub(crate) badges: Vec<AnyElement>,
+    pub(crate) labels: Vec<SharedString>,
+    pub(crate) tooltip_lines: Vec<String>,
+}
+
+pub(crate) struct DecorationSlot {
+    pub(crate) provider: Box<dyn DecorationProvider>,
+    /// The job in progress, if any. Dropping it cancels the job.
+    pub(crate) task: Option<Task<()>>,
+}
+
+/// The row decorations of the panel, in the order their badges are drawn.
+pub(crate) struct Decorations(Vec<DecorationSlot>);
+
+impl Decorations {
+    pub(crate) fn new(cx: &AppContext) -> Self {
+        let providers: [Box<dyn DecorationProvider>; 5] = [
+            Box::new(FileHeaderDecoration::new(cx)),
+            Box::new(DeploymentStatusDecoration::new(cx)),
+            Box::<ModificationHeatDecoration>::default(),
+            Box::<CodeOwnerDecoration>::default(),
+            Box::<AvailableSpaceDecoration>::default(),
+        ];
+        Self(
+            providers
+                .into_iter()
+                .map(|provider| DecorationSlot {
+                    provider,
+                    task: None,
+                })
+                .collect(),
+        )
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub(crate) fn slot(&self, ix: usize) -> &DecorationSlot {
+        &self.0[ix]
+    }
+
+    pub(crate) fn slot_mut(&mut self, ix: usize) -> &mut DecorationSlot {
+        &mut self.0[ix]
+    }
+
+    pub(crate) fn slots_mut(&mut self) -> impl Iterator<Item = &mut DecorationSlot> {
+        self.0.iter_mut()
+    }
+
+    pub(crate) fn position(&self, name: &str) -> Option<usize> {
+        self.0.iter().position(|slot| slot.provider.name() == name)
+    }
+
+    pub(crate) fn get<T: DecorationProvider>(&self) -> &T {
+        self.0
+            .iter()
+            .find_map(|slot| slot.provider.as_any().downcast_ref())
+            .expect("decoration provider is registered")
+    }
+
+    pub(crate) fn get_mut<T: DecorationProvider>(&mut self) -> &mut T {
+        self.0
+            .iter_mut()
+            .find_map(|slot| slot.provider.as_any_mut().downcast_mut())
+            .expect("decoration provider is registered")
+    }
+
+    /// Updates the provider of type `T`, cancelling its job in progress.
+    pub(crate) fn restart<T: DecorationProvider>(&mut self, update: impl FnOnce(&mut T)) {
+        let slot = self
+            .0
+            .iter_mut()
+            .find(|slot| slot.provider.as_any().is::<T>())
+            .expect("decoration provider is registered");
+        slot.task = None;
+        update(
+            slot.provider
+                .as_any_mut()
+                .downcast_mut()
+                .expect("decoration provider is registered"),
+        );
+    }
+
+    pub(crate) fn decorate(&self, row: &DecoratedRow) -> RowDecorations {
+        let mut decorations = RowDecorations::default();
+        for slot in &self.0 {
+            slot.provider.decorate(row, &mut decorations);
+        }
+        decorations
+    }
+}
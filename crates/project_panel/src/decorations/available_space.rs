@@ -0,0 +1,114 @@
+use std::{any::Any, path::Path, sync::Arc, time::Duration};
+
+use collections::HashMap;
+use gpui::AppContext;
+use project::{Fs, Project, WorktreeId};
+use util::ResultExt as _;
+
+use super::{DecoratedRow, DecorationJob, DecorationProvider, RenderedRows, RowDecorations};
+use crate::folder_statistics::format_size;
+
+pub(crate) const AVAILABLE_SPACE_DEBOUNCE: Duration = Duration::from_secs(1);
+
+/// How much space is left on the volumes of the local worktrees, shown in the tooltip of
+/// their root and in the low disk space warning. As the warning is shown along with any
+/// rows, all visible worktrees are checked, not only the ones with rendered rows.
+#[derive(Default)]
+pub(crate) struct AvailableSpaceDecoration {
+    /// Bytes left on the volume of each checked worktree, `None` when that couldn't be told.
+    available_space: HashMap<WorktreeId, Option<u64>>,
+    /// Whether entries changed since the volumes were checked.
+    is_outdated: bool,
+}
+
+impl AvailableSpaceDecoration {
+    pub(crate) fn available_space(&self, worktree_id: WorktreeId) -> Option<u64> {
+        *self.available_space.get(&worktree_id)?
+    }
+}
+
+impl DecorationProvider for AvailableSpaceDecoration {
+    fn name(&self) -> &'static str {
+        "available space"
+    }
+
+    fn is_enabled(&self, project: &Project, _: &AppContext) -> bool {
+        project.is_local()
+    }
+
+    fn compute(
+        &mut self,
+        rows: &RenderedRows,
+        project: &Project,
+        fs: &Arc<dyn Fs>,
+        cx: &AppContext,
+    ) -> Option<DecorationJob> {
+        if rows.rows.is_empty() {
+            return None;
+        }
+        let worktrees = project
+            .visible_worktrees(cx)
+            .map(|worktree| {
+                let worktree = worktree.read(cx);
+                (worktree.id(), worktree.abs_path())
+            })
+            .collect::<Vec<_>>();
+        if !self.is_outdated
+            && worktrees
+                .iter()
+                .all(|(worktree_id, _)| self.available_space.contains_key(worktree_id))
+        {
+            return None;
+        }
+
+        let fs = fs.clone();
+        Some(DecorationJob::new(
+            Some(AVAILABLE_SPACE_DEBOUNCE),
+            async move {
+                let mut available_space = Vec::with_capacity(worktrees.len());
+                for (worktree_id, abs_path) in worktrees {
+                    let space = fs.available_space(&abs_path).await.log_err();
+                    available_space.push((worktree_id, space));
+                }
+                Ok(available_space)
+            },
+        ))
+    }
+
+    fn apply(&mut self, outcome: Box<dyn Any + Send>) {
+        let Ok(available_space) = outcome.downcast::<Vec<(WorktreeId, Option<u64>)>>() else {
+            return;
+        };
+        self.available_space = HashMap::from_iter(*available_space);
+        self.is_outdated = false;
+    }
+
+    fn entries_changed(&mut self, _: WorktreeId, _: &[&Path]) -> bool {
+        self.is_outdated = true;
+        true
+    }
+
+    fn clear(&mut self) {
+        self.available_space.clear();
+        self.is_outdated = false;
+    }
+
+    fn decorate(&self, row: &DecoratedRow, decorations: &mut RowDecorations) {
+        if row.path != Path::new("") {
+            return;
+        }
+        if let Some(space) = self.available_space(row.worktree_id) {
+            decorations
+                .tooltip_lines
+                .push(format!("{} available", format_size(space)));
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -0,0 +1,205 @@
+use std::{any::Any, collections::HashSet, path::Path, sync::Arc};
+
+use collections::HashMap;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use gpui::AppContext;
+use project::{Fs, Project, WorktreeId};
+use util::ResultExt as _;
+
+use super::{DecoratedRow, DecorationJob, DecorationProvider, RenderedRows, RowDecorations};
+
+/// Locations GitHub looks for a `CODEOWNERS` file in, in the order it checks them.
+const CODEOWNERS_PATHS: [&str; 3] = [".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+/// Labels with the owners of the entries, from the `CODEOWNERS` file of their worktree,
+/// shown where they differ from the owners of the parent directory.
+#[derive(Default)]
+pub(crate) struct CodeOwnerDecoration {
+    /// The parsed `CODEOWNERS` file of each worktree with rendered rows, `None` when there
+    /// is none.
+    code_owners: HashMap<WorktreeId, Option<Arc<CodeOwners>>>,
+    /// Worktrees whose `CODEOWNERS` file changed since it was loaded.
+    outdated_worktrees: HashSet<WorktreeId>,
+}
+
+impl CodeOwnerDecoration {
+    pub(crate) fn owners_for_path(
+        &self,
+        worktree_id: WorktreeId,
+        path: &Path,
+    ) -> Option<&[String]> {
+        self.code_owners
+            .get(&worktree_id)?
+            .as_ref()?
+            .owners_for_path(path)
+    }
+}
+
+impl DecorationProvider for CodeOwnerDecoration {
+    fn name(&self) -> &'static str {
+        "code owner"
+    }
+
+    fn is_enabled(&self, project: &Project, _: &AppContext) -> bool {
+        project.is_local()
+    }
+
+    /// Loads the `CODEOWNERS` file of the worktrees of the rows, unless it is loaded already.
+    fn compute(
+        &mut self,
+        rows: &RenderedRows,
+        _: &Project,
+        fs: &Arc<dyn Fs>,
+        _: &AppContext,
+    ) -> Option<DecorationJob> {
+        let unloaded_worktrees = rows
+            .worktrees
+            .iter()
+            .filter(|snapshot| {
+                !self.code_owners.contains_key(&snapshot.id())
+                    || self.outdated_worktrees.contains(&snapshot.id())
+            })
+            .map(|snapshot| {
+                let abs_path = CODEOWNERS_PATHS
+                    .iter()
+                    .find(|path| {
+                        snapshot
+                            .entry_for_path(path)
+                            .map_or(false, |entry| entry.is_file())
+                    })
+                    .map(|path| snapshot.abs_path().join(path));
+                (snapshot.id(), abs_path)
+            })
+            .collect::<Vec<_>>();
+        if unloaded_worktrees.is_empty() {
+            return None;
+        }
+
+        let fs = fs.clone();
+        Some(DecorationJob::new(None, async move {
+            let mut code_owners = Vec::with_capacity(unloaded_worktrees.len());
+            for (worktree_id, abs_path) in unloaded_worktrees {
+                let owners = match abs_path {
+                    Some(abs_path) => fs
+                        .load(&abs_path)
+                        .await
+                        .log_err()
+                        .map(|text| Arc::new(CodeOwners::parse(&text))),
+                    None => None,
+                };
+                code_owners.push((worktree_id, owners));
+            }
+            Ok(code_owners)
+        }))
+    }
+
+    fn apply(&mut self, outcome: Box<dyn Any + Send>) {
+        let Ok(code_owners) = outcome.downcast::<Vec<(WorktreeId, Option<Arc<CodeOwners>>)>>()
+        else {
+            return;
+        };
+        for (worktree_id, owners) in *code_owners {
+            self.outdated_worktrees.remove(&worktree_id);
+            self.code_owners.insert(worktree_id, owners);
+        }
+    }
+
+    fn entries_changed(&mut self, worktree_id: WorktreeId, paths: &[&Path]) -> bool {
+        let changed = paths
+            .iter()
+            .any(|path| CODEOWNERS_PATHS.iter().any(|p| *path == Path::new(p)));
+        if changed {
+            self.outdated_worktrees.insert(worktree_id);
+        }
+        changed
+    }
+
+    fn clear(&mut self) {
+        self.code_owners.clear();
+        self.outdated_worktrees.clear();
+    }
+
+    fn decorate(&self, row: &DecoratedRow, decorations: &mut RowDecorations) {
+        let Some(owners) = self
+            .owners_for_path(row.worktree_id, row.path)
+            .filter(|owners| !owners.is_empty())
+        else {
+            return;
+        };
+        let parent_owners = row
+            .path
+            .parent()
+            .and_then(|parent| self.owners_for_path(row.worktree_id, parent));
+        if parent_owners != Some(owners) {
+            decorations.labels.push(owners.join(", ").into());
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// The rules of a `CODEOWNERS` file. Patterns follow `.gitignore` syntax and the last rule
+/// matching a path determines its owners.
+#[derive(Debug, Default)]
+pub(crate) struct CodeOwners {
+    rules: Vec<(GlobSet, Vec<String>)>,
+}
+
+impl CodeOwners {
+    pub(crate) fn parse(text: &str) -> Self {
+        let rules = text
+            .lines()
+            .filter_map(|line| {
+                let mut tokens = line
+                    .split_whitespace()
+                    .take_while(|token| !token.starts_with('#'));
+                let pattern = tokens.next()?;
+                let owners = tokens.map(ToOwned::to_owned).collect();
+                Some((pattern_glob_set(pattern)?, owners))
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Returns the owners of the last rule matching `path`, which are empty for rules that
+    /// explicitly leave paths without an owner.
+    pub(crate) fn owners_for_path(&self, path: &Path) -> Option<&[String]> {
+        self.rules
+            .iter()
+            .rev()
+            .find(|(glob_set, _)| glob_set.is_match(path))
+            .map(|(_, owners)| owners.as_slice())
+    }
+}
+
+fn pattern_glob_set(pattern: &str) -> Option<GlobSet> {
+    let trimmed = pattern.trim_end_matches('/');
+    // Patterns with a slash anywhere but at the end are relative to the repository root.
+    let is_anchored = trimmed.contains('/');
+    let trimmed = trimmed.trim_start_matches('/');
+    if trimmed.is_empty() {
+        return None;
+    }
+    let base = if is_anchored {
+        trimmed.to_string()
+    } else {
+        format!("**/{trimmed}")
+    };
+
+    let mut builder = GlobSetBuilder::new();
+    for glob in [base.clone(), format!("{base}/**")] {
+        builder.add(
+            GlobBuilder::new(&glob)
+                .literal_separator(true)
+                .build()
+                .ok()?,
+        );
+    }
+    builder.build().ok()
+}
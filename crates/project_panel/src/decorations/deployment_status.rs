@@ -0,0 +1,266 @@
+use std::{
+    any::Any,
+    collections::HashSet,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::Result;
+use collections::HashMap;
+use gpui::AppContext;
+use project::{Fs, Project, WorktreeId};
+use settings::Settings as _;
+use sha2::{Digest, Sha256};
+use ui::{prelude::*, Tooltip};
+use util::ResultExt as _;
+
+use super::{DecoratedRow, DecorationJob, DecorationProvider, RenderedRows, RowDecorations};
+use crate::{project_panel_settings::DeploymentMapping, ProjectPanelSettings};
+
+const DEPLOYMENT_STATUS_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Badges for the files of the configured deployments whose checksum differs from the remote
+/// one, and for the directories containing them. Remote checksums come from each deployment's
+/// command, local ones are hashed in the background.
+pub(crate) struct DeploymentStatusDecoration {
+    deployments: Vec<DeploymentMapping>,
+    /// Paths of the out of sync files of each checked deployment, along with their ancestors,
+    /// keyed by the worktree and the local directory of the deployment.
+    out_of_sync_paths: HashMap<(WorktreeId, PathBuf), HashSet<Arc<Path>>>,
+    /// Deployments checked since their last change.
+    checked_deployments: HashSet<(WorktreeId, PathBuf)>,
+}
+
+impl DeploymentStatusDecoration {
+    pub(crate) fn new(cx: &AppContext) -> Self {
+        Self {
+            deployments: ProjectPanelSettings::get_global(cx).deployments.clone(),
+            out_of_sync_paths: HashMap::default(),
+            checked_deployments: HashSet::default(),
+        }
+    }
+
+    /// Checks the deployments again the next time their rows are rendered.
+    pub(crate) fn recheck(&mut self) {
+        self.checked_deployments.clear();
+    }
+}
+
+impl DecorationProvider for DeploymentStatusDecoration {
+    fn name(&self) -> &'static str {
+        "deployment status"
+    }
+
+    fn is_enabled(&self, project: &Project, _: &AppContext) -> bool {
+        project.is_local() && !self.deployments.is_empty()
+    }
+
+    /// Checks the deployments of the rows, the ones containing them or inside of them, that
+    /// weren't checked since their last change.
+    fn compute(
+        &mut self,
+        rows: &RenderedRows,
+        _: &Project,
+        fs: &Arc<dyn Fs>,
+        _: &AppContext,
+    ) -> Option<DecorationJob> {
+        let mut unchecked_deployments = HashMap::default();
+        for row in &rows.rows {
+            for deployment in &self.deployments {
+                let key = (row.worktree_id, deployment.local.clone());
+                if (row.entry.path.starts_with(&deployment.local)
+                    || deployment.local.starts_with(&row.entry.path))
+                    && !self.checked_deployments.contains(&key)
+                {
+                    unchecked_deployments.entry(key).or_insert(deployment);
+                }
+            }
+        }
+        if unchecked_deployments.is_empty() {
+            return None;
+        }
+
+        let unchecked_deployments = unchecked_deployments
+            .into_iter()
+            .filter_map(|((worktree_id, local), deployment)| {
+                let snapshot = rows.worktree(worktree_id)?.clone();
+                Some(((worktree_id, local), snapshot, deployment.clone()))
+            })
+            .collect::<Vec<_>>();
+        let fs = fs.clone();
+        Some(DecorationJob::new(
+            Some(DEPLOYMENT_STATUS_DEBOUNCE),
+            async move {
+                let mut out_of_sync_paths = Vec::with_capacity(unchecked_deployments.len());
+                for (key, snapshot, deployment) in unchecked_deployments {
+                    let mut paths = HashSet::default();
+                    if let Some(files) = out_of_sync_files(fs.as_ref(), &snapshot, &deployment)
+                        .await
+                        .log_err()
+                    {
+                        for file in files {
+                            for ancestor in file.ancestors().skip(1) {
+                                if !paths.insert(Arc::from(ancestor)) {
+                                    break;
+                                }
+                            }
+                            paths.insert(file);
+                        }
+                    }
+                    out_of_sync_paths.push((key, paths));
+                }
+                Ok(out_of_sync_paths)
+            },
+        ))
+    }
+
+    fn apply(&mut self, outcome: Box<dyn Any + Send>) {
+        let Ok(out_of_sync_paths) =
+            outcome.downcast::<Vec<((WorktreeId, PathBuf), HashSet<Arc<Path>>)>>()
+        else {
+            return;
+        };
+        for (key, paths) in *out_of_sync_paths {
+            self.checked_deployments.insert(key.clone());
+            self.out_of_sync_paths.insert(key, paths);
+        }
+    }
+
+    fn entries_changed(&mut self, worktree_id: WorktreeId, paths: &[&Path]) -> bool {
+        let mut changed = false;
+        for deployment in &self.deployments {
+            if paths.iter().any(|path| path.starts_with(&deployment.local)) {
+                self.checked_deployments
+                    .remove(&(worktree_id, deployment.local.clone()));
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    fn settings_changed(&mut self, cx: &AppContext) -> bool {
+        let deployments = &ProjectPanelSettings::get_global(cx).deployments;
+        if self.deployments == *deployments {
+            return false;
+        }
+        self.deployments = deployments.clone();
+        self.clear();
+        true
+    }
+
+    fn clear(&mut self) {
+        self.out_of_sync_paths.clear();
+        self.checked_deployments.clear();
+    }
+
+    fn decorate(&self, row: &DecoratedRow, decorations: &mut RowDecorations) {
+        let is_out_of_sync = self
+            .out_of_sync_paths
+            .iter()
+            .any(|((worktree_id, _), paths)| {
+                *worktree_id == row.worktree_id && paths.contains(row.path)
+            });
+        if is_out_of_sync {
+            decorations.badges.push(
+                div()
+                    .id("out_of_sync_icon")
+                    .tooltip(|cx| Tooltip::text("Out of Sync with Deployment", cx))
+                    .child(
+                        Icon::new(IconName::ArrowCircle)
+                            .size(IconSize::Small)
+                            .color(Color::Warning),
+                    )
+                    .into_any_element(),
+            );
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+pub(crate) fn deployment_for_path<'a>(
+    path: &Path,
+    cx: &'a AppContext,
+) -> Option<&'a DeploymentMapping> {
+    ProjectPanelSettings::get_global(cx)
+        .deployments
+        .iter()
+        .find(|deployment| path.starts_with(&deployment.local))
+}
+
+/// Returns the files of `deployment` whose contents don't match the remote checksums,
+/// including the ones missing on the remote.
+async fn out_of_sync_files(
+    fs: &dyn Fs,
+    snapshot: &worktree::Snapshot,
+    deployment: &DeploymentMapping,
+) -> Result<Vec<Arc<Path>>> {
+    if !snapshot
+        .entry_for_path(&deployment.local)
+        .map_or(false, |entry| entry.is_dir())
+    {
+        return Ok(Vec::new());
+    }
+    let abs_dir = snapshot.abs_path().join(&deployment.local);
+    let output = smol::process::Command::new("sh")
+        .args(["-c", &deployment.checksum_command])
+        .current_dir(&abs_dir)
+        .output()
+        .await?;
+    anyhow::ensure!(
+        output.status.success(),
+        "deployment checksum command {:?} failed: {}",
+        deployment.checksum_command,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let remote_checksums = parse_checksums(&String::from_utf8_lossy(&output.stdout));
+
+    let mut out_of_sync_files = Vec::new();
+    for entry in snapshot.files(false, 0) {
+        if entry.is_special_file {
+            continue;
+        }
+        let Ok(deployment_path) = entry.path.strip_prefix(&deployment.local) else {
+            continue;
+        };
+        // The reader isn't `Send`, so it must not be held across an await point.
+        let contents = {
+            let Some(mut file) = fs.open_sync(&abs_dir.join(deployment_path)).await.log_err()
+            else {
+                continue;
+            };
+            let mut contents = Vec::new();
+            if file.read_to_end(&mut contents).log_err().is_none() {
+                continue;
+            }
+            contents
+        };
+        let checksum = format!("{:x}", Sha256::digest(&contents));
+        if remote_checksums.get(deployment_path) != Some(&checksum) {
+            out_of_sync_files.push(entry.path.clone());
+        }
+    }
+    Ok(out_of_sync_files)
+}
+
+/// Parses `sha256sum` output: a checksum, then a space and either a space or `*`, then the path.
+pub(crate) fn parse_checksums(output: &str) -> HashMap<PathBuf, String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (checksum, path) = line.split_once(' ')?;
+            let path = path.strip_prefix([' ', '*']).unwrap_or(path);
+            let path = Path::new(path);
+            let path = path.strip_prefix(".").unwrap_or(path);
+            Some((path.to_path_buf(), checksum.to_ascii_lowercase()))
+        })
+        .collect()
+}
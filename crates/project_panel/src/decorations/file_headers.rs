@@ -0,0 +1,276 @@
+use std::{
+    any::Any,
+    collections::HashSet,
+    io::Read,
+    mem,
+    path::Path,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use collections::HashMap;
+use gpui::AppContext;
+use project::{Fs, Project, ProjectEntryId};
+use settings::Settings as _;
+use ui::{prelude::*, Tooltip};
+
+use super::{DecoratedRow, DecorationJob, DecorationProvider, RenderedRows, RowDecorations};
+use crate::ProjectPanelSettings;
+
+const FILE_HEADER_LEN: u64 = 4096;
+/// How many file headers are sniffed at once, pausing for `FILE_HEADER_BATCH_INTERVAL` in between.
+pub(crate) const FILE_HEADER_BATCH_SIZE: usize = 32;
+pub(crate) const FILE_HEADER_BATCH_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct FileHeader {
+    pub(crate) is_encrypted: bool,
+    pub(crate) is_missing_license_header: bool,
+}
+
+/// Badges for files encrypted with git-crypt or sops and for files missing their license
+/// header, sniffed from the first bytes of the files.
+pub(crate) struct FileHeaderDecoration {
+    /// What the headers of file entries tell about them, as sniffed for their last seen mtime.
+    file_headers: HashMap<ProjectEntryId, (Option<SystemTime>, FileHeader)>,
+    /// Encrypted files decrypted from the panel and not re-encrypted yet.
+    decrypted_entries: HashSet<ProjectEntryId>,
+    /// License headers the file headers were checked against, by file extension.
+    license_headers: HashMap<String, String>,
+    /// Whether a batch was just sniffed, the next one waiting `FILE_HEADER_BATCH_INTERVAL`.
+    after_batch: bool,
+}
+
+impl FileHeaderDecoration {
+    pub(crate) fn new(cx: &AppContext) -> Self {
+        Self {
+            file_headers: HashMap::default(),
+            decrypted_entries: HashSet::default(),
+            license_headers: ProjectPanelSettings::get_global(cx).license_headers.clone(),
+            after_batch: false,
+        }
+    }
+
+    pub(crate) fn file_header(&self, entry_id: ProjectEntryId) -> Option<FileHeader> {
+        self.file_headers
+            .get(&entry_id)
+            .map(|(_, file_header)| *file_header)
+    }
+
+    pub(crate) fn is_encrypted(&self, entry_id: ProjectEntryId) -> bool {
+        self.file_header(entry_id)
+            .map_or(false, |file_header| file_header.is_encrypted)
+    }
+
+    pub(crate) fn is_decrypted(&self, entry_id: ProjectEntryId) -> bool {
+        self.decrypted_entries.contains(&entry_id)
+    }
+
+    pub(crate) fn set_decrypted(&mut self, entry_id: ProjectEntryId, is_decrypted: bool) {
+        if is_decrypted {
+            self.decrypted_entries.insert(entry_id);
+        } else {
+            self.decrypted_entries.remove(&entry_id);
+        }
+    }
+}
+
+impl DecorationProvider for FileHeaderDecoration {
+    fn name(&self) -> &'static str {
+        "file header"
+    }
+
+    fn is_enabled(&self, project: &Project, _: &AppContext) -> bool {
+        project.is_local()
+    }
+
+    /// Sniffs the headers of the files not checked since their last change, the nearest to
+    /// the rendered rows first. Each batch is followed by the next one until all of them are
+    /// checked.
+    fn compute(
+        &mut self,
+        rows: &RenderedRows,
+        _: &Project,
+        fs: &Arc<dyn Fs>,
+        _: &AppContext,
+    ) -> Option<DecorationJob> {
+        let debounce = mem::take(&mut self.after_batch).then_some(FILE_HEADER_BATCH_INTERVAL);
+        let mut unchecked_files = rows
+            .rows
+            .iter()
+            .filter(|row| {
+                row.entry.is_file()
+                    && !row.entry.is_special_file
+                    && self
+                        .file_headers
+                        .get(&row.entry.id)
+                        .map_or(true, |(mtime, _)| *mtime != row.entry.mtime)
+            })
+            .filter_map(|row| {
+                Some((
+                    row.distance,
+                    row.entry.id,
+                    row.entry.mtime,
+                    rows.abs_path(row.worktree_id, &row.entry.path)?,
+                    license_header_for_path(&self.license_headers, &row.entry.path)
+                        .map(str::to_owned),
+                ))
+            })
+            .collect::<Vec<_>>();
+        if unchecked_files.is_empty() {
+            return None;
+        }
+        unchecked_files.sort_by_key(|(distance, ..)| *distance);
+        unchecked_files.truncate(FILE_HEADER_BATCH_SIZE);
+
+        let fs = fs.clone();
+        Some(DecorationJob::new(debounce, async move {
+            let mut sniffed = Vec::with_capacity(unchecked_files.len());
+            for (_, entry_id, mtime, abs_path, license_header) in unchecked_files {
+                let file_header =
+                    sniff_file_header(fs.as_ref(), &abs_path, license_header.as_deref()).await;
+                sniffed.push((entry_id, mtime, file_header));
+            }
+            Ok(sniffed)
+        }))
+    }
+
+    fn apply(&mut self, outcome: Box<dyn Any + Send>) {
+        let Ok(sniffed) =
+            outcome.downcast::<Vec<(ProjectEntryId, Option<SystemTime>, FileHeader)>>()
+        else {
+            return;
+        };
+        for (entry_id, mtime, file_header) in *sniffed {
+            if file_header.is_encrypted {
+                self.decrypted_entries.remove(&entry_id);
+            }
+            self.file_headers.insert(entry_id, (mtime, file_header));
+        }
+        self.after_batch = true;
+    }
+
+    fn settings_changed(&mut self, cx: &AppContext) -> bool {
+        let license_headers = &ProjectPanelSettings::get_global(cx).license_headers;
+        if self.license_headers == *license_headers {
+            return false;
+        }
+        self.license_headers = license_headers.clone();
+        self.file_headers.clear();
+        true
+    }
+
+    fn clear(&mut self) {
+        self.file_headers.clear();
+    }
+
+    fn decorate(&self, row: &DecoratedRow, decorations: &mut RowDecorations) {
+        let file_header = self.file_header(row.entry_id).unwrap_or_default();
+        if file_header.is_missing_license_header {
+            decorations.badges.push(
+                div()
+                    .id("license_header_icon")
+                    .tooltip(|cx| Tooltip::text("Missing License Header", cx))
+                    .child(
+                        Icon::new(IconName::FileDoc)
+                            .size(IconSize::Small)
+                            .color(Color::Warning),
+                    )
+                    .into_any_element(),
+            );
+        }
+        let is_decrypted = self.is_decrypted(row.entry_id);
+        if file_header.is_encrypted || is_decrypted {
+            decorations.badges.push(
+                div()
+                    .id("encryption_icon")
+                    .tooltip(move |cx| {
+                        if is_decrypted {
+                            Tooltip::text("Decrypted, Re-encrypt Before Committing", cx)
+                        } else {
+                            Tooltip::text("Encrypted File", cx)
+                        }
+                    })
+                    .child(Icon::new(IconName::FileLock).size(IconSize::Small).color(
+                        if is_decrypted {
+                            Color::Warning
+                        } else {
+                            Color::Muted
+                        },
+                    ))
+                    .into_any_element(),
+            );
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+async fn sniff_file_header(
+    fs: &dyn Fs,
+    abs_path: &Path,
+    license_header: Option<&str>,
+) -> FileHeader {
+    let Ok(file) = fs.open_sync(abs_path).await else {
+        return FileHeader::default();
+    };
+    let mut header = Vec::new();
+    if file.take(FILE_HEADER_LEN).read_to_end(&mut header).is_err() {
+        return FileHeader::default();
+    }
+    let is_encrypted = is_encrypted_header(&header);
+    FileHeader {
+        is_encrypted,
+        is_missing_license_header: !is_encrypted
+            && license_header.map_or(false, |license_header| {
+                with_license_header(&String::from_utf8_lossy(&header), license_header).is_some()
+            }),
+    }
+}
+
+pub(crate) fn license_header_for_path<'a>(
+    license_headers: &'a HashMap<String, String>,
+    path: &Path,
+) -> Option<&'a str> {
+    let extension = path.extension()?.to_str()?;
+    license_headers.get(extension).map(String::as_str)
+}
+
+/// Returns `text` with `header` inserted at its top, below the shebang line if there is one,
+/// or `None` when the text already starts with the header.
+pub(crate) fn with_license_header(text: &str, header: &str) -> Option<String> {
+    let header = header.trim_end();
+    let body_start = if text.starts_with("#!") {
+        text.find('\n').map_or(text.len(), |ix| ix + 1)
+    } else {
+        0
+    };
+    let (shebang, body) = text.split_at(body_start);
+    let mut body_lines = body.lines();
+    if header.lines().all(|line| body_lines.next() == Some(line)) {
+        return None;
+    }
+    let separator = if shebang.is_empty() || shebang.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    Some(format!("{shebang}{separator}{header}\n{body}"))
+}
+
+/// git-crypt prefixes encrypted files with a magic header, while sops keeps the file structure
+/// and replaces values with `ENC[...]` strings.
+fn is_encrypted_header(header: &[u8]) -> bool {
+    const GIT_CRYPT_HEADER: &[u8] = b"\0GITCRYPT\0";
+    const SOPS_VALUE_PREFIX: &[u8] = b"ENC[AES256_GCM,";
+    header.starts_with(GIT_CRYPT_HEADER)
+        || header
+            .windows(SOPS_VALUE_PREFIX.len())
+            .any(|window| window == SOPS_VALUE_PREFIX)
+}
@@ -0,0 +1,212 @@
+use std::{
+    any::Any,
+    path::Path,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use collections::HashMap;
+use gpui::AppContext;
+use project::{Fs, Project, WorktreeId};
+use settings::Settings as _;
+use ui::{prelude::*, Indicator, Tooltip};
+
+use super::{DecoratedRow, DecorationJob, DecorationProvider, RenderedRows, RowDecorations};
+use crate::ProjectPanelSettings;
+
+const MODIFICATION_HEAT_DEBOUNCE: Duration = Duration::from_millis(500);
+const MODIFICATION_HEAT_LEGEND: &str =
+    "Hottest: within a day, then within a week, a month and a year. Coldest: older.";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ModificationAge {
+    Day,
+    Week,
+    Month,
+    Year,
+    Older,
+}
+
+impl ModificationAge {
+    pub(crate) fn new(mtime: SystemTime, now: SystemTime) -> Self {
+        const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+        let age = now.duration_since(mtime).unwrap_or_default();
+        if age < DAY {
+            Self::Day
+        } else if age < DAY * 7 {
+            Self::Week
+        } else if age < DAY * 30 {
+            Self::Month
+        } else if age < DAY * 365 {
+            Self::Year
+        } else {
+            Self::Older
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Self::Day => Color::Error,
+            Self::Week => Color::Warning,
+            Self::Month => Color::Info,
+            Self::Year => Color::Muted,
+            Self::Older => Color::Disabled,
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Self::Day => "Modified within a day",
+            Self::Week => "Modified within a week",
+            Self::Month => "Modified within a month",
+            Self::Year => "Modified within a year",
+            Self::Older => "Not modified for over a year",
+        }
+    }
+}
+
+/// A dot colored by how recently the file, or the most recently modified file inside the
+/// directory, was modified.
+#[derive(Default)]
+pub(crate) struct ModificationHeatDecoration {
+    /// Modification time of the most recently modified file inside the rendered directories,
+    /// `None` for directories without files.
+    newest_descendant_mtimes: HashMap<WorktreeId, HashMap<Arc<Path>, Option<SystemTime>>>,
+}
+
+impl DecorationProvider for ModificationHeatDecoration {
+    fn name(&self) -> &'static str {
+        "modification heat"
+    }
+
+    fn is_enabled(&self, _: &Project, cx: &AppContext) -> bool {
+        ProjectPanelSettings::get_global(cx).modification_heat
+    }
+
+    /// Finds the most recently modified file of the directories that don't know theirs.
+    fn compute(
+        &mut self,
+        rows: &RenderedRows,
+        _: &Project,
+        _: &Arc<dyn Fs>,
+        _: &AppContext,
+    ) -> Option<DecorationJob> {
+        let mut unknown_dirs = HashMap::<WorktreeId, Vec<Arc<Path>>>::default();
+        for row in &rows.rows {
+            if row.entry.is_dir()
+                && !self
+                    .newest_descendant_mtimes
+                    .get(&row.worktree_id)
+                    .map_or(false, |mtimes| mtimes.contains_key(&row.entry.path))
+            {
+                unknown_dirs
+                    .entry(row.worktree_id)
+                    .or_default()
+                    .push(row.entry.path.clone());
+            }
+        }
+        if unknown_dirs.is_empty() {
+            return None;
+        }
+
+        let snapshots = unknown_dirs
+            .into_iter()
+            .filter_map(|(worktree_id, dirs)| Some((rows.worktree(worktree_id)?.clone(), dirs)))
+            .collect::<Vec<_>>();
+        Some(DecorationJob::new(
+            Some(MODIFICATION_HEAT_DEBOUNCE),
+            async move {
+                Ok(snapshots
+                    .iter()
+                    .map(|(snapshot, dirs)| {
+                        (snapshot.id(), newest_descendant_mtimes(snapshot, dirs))
+                    })
+                    .collect::<Vec<_>>())
+            },
+        ))
+    }
+
+    fn apply(&mut self, outcome: Box<dyn Any + Send>) {
+        let Ok(newest_descendant_mtimes) =
+            outcome.downcast::<Vec<(WorktreeId, HashMap<Arc<Path>, Option<SystemTime>>)>>()
+        else {
+            return;
+        };
+        for (worktree_id, mtimes) in *newest_descendant_mtimes {
+            self.newest_descendant_mtimes
+                .entry(worktree_id)
+                .or_default()
+                .extend(mtimes);
+        }
+    }
+
+    fn entries_changed(&mut self, worktree_id: WorktreeId, paths: &[&Path]) -> bool {
+        if let Some(mtimes) = self.newest_descendant_mtimes.get_mut(&worktree_id) {
+            for path in paths {
+                for ancestor in path.ancestors() {
+                    mtimes.remove(ancestor);
+                }
+            }
+        }
+        !paths.is_empty()
+    }
+
+    fn clear(&mut self) {
+        self.newest_descendant_mtimes.clear();
+    }
+
+    fn decorate(&self, row: &DecoratedRow, decorations: &mut RowDecorations) {
+        let mtime = if row.is_dir {
+            self.newest_descendant_mtimes
+                .get(&row.worktree_id)
+                .and_then(|mtimes| *mtimes.get(row.path)?)
+        } else {
+            row.mtime
+        };
+        let Some(mtime) = mtime else {
+            return;
+        };
+        let age = ModificationAge::new(mtime, SystemTime::now());
+        decorations.start_slot = Some(
+            div()
+                .id("modification_heat")
+                .tooltip(move |cx| {
+                    Tooltip::with_meta(age.description(), None, MODIFICATION_HEAT_LEGEND, cx)
+                })
+                .child(Indicator::dot().color(age.color()))
+                .into_any_element(),
+        );
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Returns the modification time of the most recently modified file inside each of `dirs`.
+fn newest_descendant_mtimes(
+    snapshot: &worktree::Snapshot,
+    dirs: &[Arc<Path>],
+) -> HashMap<Arc<Path>, Option<SystemTime>> {
+    let mut newest_mtimes = dirs
+        .iter()
+        .map(|dir| (dir.clone(), None))
+        .collect::<HashMap<_, Option<SystemTime>>>();
+    for entry in snapshot.files(true, 0) {
+        let Some(mtime) = entry.mtime else {
+            continue;
+        };
+        for ancestor in entry.path.ancestors().skip(1) {
+            if let Some(newest_mtime) = newest_mtimes.get_mut(ancestor) {
+                if newest_mtime.map_or(true, |newest_mtime| newest_mtime < mtime) {
+                    *newest_mtime = Some(mtime);
+                }
+            }
+        }
+    }
+    newest_mtimes
+}
@@ -0,0 +1,59 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use collections::HashMap;
+use db::kvp::KEY_VALUE_STORE;
+use serde::{Deserialize, Serialize};
+
+/// How many times files were opened from each directory, keyed by the absolute path of the
+/// worktree and the path of the directory inside of it.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct DirectoryUsage(HashMap<PathBuf, HashMap<PathBuf, u32>>);
+
+impl DirectoryUsage {
+    pub(crate) fn record(&mut self, worktree_abs_path: &Path, dir_path: &Path) {
+        *self
+            .0
+            .entry(worktree_abs_path.to_path_buf())
+            .or_default()
+            .entry(dir_path.to_path_buf())
+            .or_default() += 1;
+    }
+
+    /// Returns up to `limit` directories of the worktree, the most used first.
+    pub(crate) fn most_used(&self, worktree_abs_path: &Path, limit: usize) -> Vec<&Path> {
+        let Some(directories) = self.0.get(worktree_abs_path) else {
+            return Vec::new();
+        };
+        let mut directories = directories.iter().collect::<Vec<_>>();
+        directories.sort_by(|(a_path, a_count), (b_path, b_count)| {
+            b_count.cmp(a_count).then_with(|| a_path.cmp(b_path))
+        });
+        directories
+            .into_iter()
+            .take(limit)
+            .map(|(path, _)| path.as_path())
+            .collect()
+    }
+}
+
+pub(crate) fn read_directory_usage(key: &str) -> Result<DirectoryUsage> {
+    Ok(KEY_VALUE_STORE
+        .read_kvp(key)?
+        .map(|usage| serde_json::from_str(&usage))
+        .transpose()?
+        .unwrap_or_default())
+}
+
+pub(crate) async fn record_directory_usage(
+    key: String,
+    worktree_abs_path: PathBuf,
+    dir_path: PathBuf,
+) -> Result<()> {
+    let mut usage = read_directory_usage(&key)?;
+    usage.record(&worktree_abs_path, &dir_path);
+    KEY_VALUE_STORE
+        .write_kvp(key, serde_json::to_string(&usage)?)
+        .await
+}
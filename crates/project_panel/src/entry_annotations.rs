@@ -0,0 +1,233 @@
+use std::{
+    future::Future,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use collections::HashMap;
+use db::kvp::KEY_VALUE_STORE;
+use editor::Editor;
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Render, Subscription, View,
+    WeakView,
+};
+use project::WorktreeId;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use ui::prelude::*;
+use workspace::ModalView;
+
+use crate::ProjectPanel;
+
+/// Annotations attached to entries, keyed by the absolute path of their worktree and their path
+/// inside of it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct EntryAnnotations<T>(HashMap<PathBuf, HashMap<PathBuf, T>>);
+
+pub(crate) type EntryNotes = EntryAnnotations<String>;
+pub(crate) type EntryTags = EntryAnnotations<Vec<String>>;
+
+impl<T> Default for EntryAnnotations<T> {
+    fn default() -> Self {
+        Self(HashMap::default())
+    }
+}
+
+impl<T> EntryAnnotations<T> {
+    pub(crate) fn get(&self, worktree_abs_path: &Path, path: &Path) -> Option<&T> {
+        self.0.get(worktree_abs_path)?.get(path)
+    }
+
+    /// Annotates the entry, or removes its annotation when `annotation` is `None`.
+    pub(crate) fn set(&mut self, worktree_abs_path: &Path, path: &Path, annotation: Option<T>) {
+        match annotation {
+            Some(annotation) => {
+                self.0
+                    .entry(worktree_abs_path.to_path_buf())
+                    .or_default()
+                    .insert(path.to_path_buf(), annotation);
+            }
+            None => {
+                if let Some(annotations) = self.0.get_mut(worktree_abs_path) {
+                    annotations.remove(path);
+                    if annotations.is_empty() {
+                        self.0.remove(worktree_abs_path);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the annotated entries of the worktree.
+    pub(crate) fn entries(&self, worktree_abs_path: &Path) -> impl Iterator<Item = (&Path, &T)> {
+        self.0
+            .get(worktree_abs_path)
+            .into_iter()
+            .flatten()
+            .map(|(path, annotation)| (path.as_path(), annotation))
+    }
+}
+
+pub(crate) fn read_entry_annotations<T: DeserializeOwned>(
+    key: &str,
+) -> Result<EntryAnnotations<T>> {
+    Ok(KEY_VALUE_STORE
+        .read_kvp(key)?
+        .map(|annotations| serde_json::from_str(&annotations))
+        .transpose()?
+        .unwrap_or_default())
+}
+
+pub(crate) fn write_entry_annotations<T: Serialize>(
+    key: String,
+    annotations: &EntryAnnotations<T>,
+) -> impl Future<Output = Result<()>> {
+    let annotations = serde_json::to_string(annotations);
+    async move { KEY_VALUE_STORE.write_kvp(key, annotations?).await }
+}
+
+/// Splits comma or whitespace separated tags, dropping duplicates.
+pub(crate) fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags = Vec::<String>::new();
+    for tag in text.split(|c: char| c == ',' || c.is_whitespace()) {
+        let tag = tag.trim_start_matches('#');
+        if !tag.is_empty() && !tags.iter().any(|existing| existing == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum EntryAnnotationKind {
+    Note,
+    Tags,
+}
+
+pub(crate) struct EntryAnnotationEditor {
+    project_panel: WeakView<ProjectPanel>,
+    kind: EntryAnnotationKind,
+    worktree_id: WorktreeId,
+    path: PathBuf,
+    entry_name: SharedString,
+    text_editor: View<Editor>,
+    _subscription: Subscription,
+}
+
+impl ModalView for EntryAnnotationEditor {}
+
+impl FocusableView for EntryAnnotationEditor {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.text_editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for EntryAnnotationEditor {}
+
+impl EntryAnnotationEditor {
+    pub(crate) fn new(
+        project_panel: WeakView<ProjectPanel>,
+        kind: EntryAnnotationKind,
+        worktree_id: WorktreeId,
+        path: PathBuf,
+        entry_name: SharedString,
+        text: Option<String>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let text_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text(
+                match kind {
+                    EntryAnnotationKind::Note => "Add a note…",
+                    EntryAnnotationKind::Tags => "Add tags, separated by commas…",
+                },
+                cx,
+            );
+            if let Some(text) = text {
+                editor.set_text(text, cx);
+                editor.select_all(&editor::actions::SelectAll, cx);
+            }
+            editor
+        });
+        let subscription = cx.subscribe(&text_editor, |_, _, event, cx| {
+            if let editor::EditorEvent::Blurred = event {
+                cx.emit(DismissEvent);
+            }
+        });
+        Self {
+            project_panel,
+            kind,
+            worktree_id,
+            path,
+            entry_name,
+            text_editor,
+            _subscription: subscription,
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let text = self.text_editor.read(cx).text(cx);
+        self.project_panel
+            .update(cx, |project_panel, cx| match self.kind {
+                EntryAnnotationKind::Note => {
+                    project_panel.set_entry_note(self.worktree_id, &self.path, &text, cx)
+                }
+                EntryAnnotationKind::Tags => project_panel.set_entry_tags(
+                    self.worktree_id,
+                    &self.path,
+                    parse_tags(&text),
+                    cx,
+                ),
+            })
+            .ok();
+        cx.emit(DismissEvent);
+    }
+}
+
+impl Render for EntryAnnotationEditor {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let help_text = match self.kind {
+            EntryAnnotationKind::Note => {
+                format!("Note for {}, leave empty to remove it", self.entry_name)
+            }
+            EntryAnnotationKind::Tags => {
+                format!("Tags of {}, leave empty to remove them", self.entry_name)
+            }
+        };
+        div()
+            .elevation_2(cx)
+            .key_context("EntryAnnotationEditor")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .w_96()
+            .child(
+                v_flex()
+                    .px_1()
+                    .pt_0p5()
+                    .gap_px()
+                    .child(
+                        v_flex()
+                            .py_0p5()
+                            .px_1()
+                            .child(div().px_1().py_0p5().child(self.text_editor.clone())),
+                    )
+                    .child(
+                        div()
+                            .h_px()
+                            .w_full()
+                            .bg(cx.theme().colors().element_background),
+                    )
+                    .child(
+                        h_flex()
+                            .justify_between()
+                            .px_2()
+                            .py_1()
+                            .child(Label::new(help_text).color(Color::Muted)),
+                    ),
+            )
+    }
+}
@@ -0,0 +1,180 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::Result;
+use gpui::Task;
+use language::LanguageRegistry;
+use project::{copy_recursive, CopyOptions, Fs, RemoveOptions, RenameOptions, WorktreeId};
+
+/// How many changes can be undone.
+const MAX_UNDO_HISTORY: usize = 100;
+
+/// A change to the entries of a worktree, with paths relative to its root, except for the
+/// changes between worktrees.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum FileOp {
+    Create {
+        path: PathBuf,
+        is_dir: bool,
+    },
+    Rename {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    /// Copies an entry, adding a comment naming the source at the top of the copy when
+    /// `source_comment` is set.
+    Copy {
+        from: PathBuf,
+        to: PathBuf,
+        source_comment: Option<String>,
+    },
+    /// Copies an entry from another worktree, with absolute paths.
+    CopyAcross {
+        from: PathBuf,
+        to: PathBuf,
+        source_comment: Option<String>,
+    },
+    /// Moves an entry to another worktree, with absolute paths.
+    MoveAcross {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    Trash {
+        path: PathBuf,
+    },
+}
+
+/// A change made from the panel, like a rename or a paste, undone and redone as a whole.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct FileOperation {
+    pub redo: Vec<(WorktreeId, FileOp)>,
+    pub undo: Vec<(WorktreeId, FileOp)>,
+}
+
+impl FileOperation {
+    /// Adds a step to the change, undone before the steps added earlier.
+    pub(crate) fn push(&mut self, worktree_id: WorktreeId, redo: FileOp, undo: FileOp) {
+        self.redo.push((worktree_id, redo));
+        self.undo.insert(0, (worktree_id, undo));
+    }
+}
+
+/// A step of a change being applied, recorded once it succeeds.
+pub(crate) struct PendingFileOp {
+    pub worktree_id: WorktreeId,
+    pub task: Task<Result<()>>,
+    pub redo: FileOp,
+    pub undo: FileOp,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct OperationHistory {
+    undo_stack: Vec<FileOperation>,
+    redo_stack: Vec<FileOperation>,
+}
+
+impl OperationHistory {
+    /// Records a new change, which can't be followed by the ones undone before anymore.
+    pub(crate) fn push(&mut self, operation: FileOperation) {
+        if operation.redo.is_empty() {
+            return;
+        }
+        self.redo_stack.clear();
+        self.undo_stack.push(operation);
+        if self.undo_stack.len() > MAX_UNDO_HISTORY {
+            self.undo_stack.remove(0);
+        }
+    }
+
+    /// Takes the last change to undo, to hand back to `finish_undo` once it is applied.
+    pub(crate) fn start_undo(&mut self) -> Option<FileOperation> {
+        self.undo_stack.pop()
+    }
+
+    /// Moves a change to the changes to redo once it is undone, or back to the changes to undo
+    /// when undoing it failed.
+    pub(crate) fn finish_undo(&mut self, operation: FileOperation, undone: bool) {
+        if undone {
+            self.redo_stack.push(operation);
+        } else {
+            self.undo_stack.push(operation);
+        }
+    }
+
+    /// Takes the last undone change to redo, to hand back to `finish_redo` once it is applied.
+    pub(crate) fn start_redo(&mut self) -> Option<FileOperation> {
+        self.redo_stack.pop()
+    }
+
+    /// Moves a change back to the changes to undo once it is redone, or to the changes to redo
+    /// when redoing it failed.
+    pub(crate) fn finish_redo(&mut self, operation: FileOperation, redone: bool) {
+        if redone {
+            self.undo_stack.push(operation);
+        } else {
+            self.redo_stack.push(operation);
+        }
+    }
+}
+
+/// Moves the entry at `from` to `to`, copying it and trashing the original when the rename fails,
+/// e.g. as they are on different volumes.
+pub(crate) async fn move_across(fs: &dyn Fs, from: &Path, to: &Path) -> Result<()> {
+    if fs.rename(from, to, RenameOptions::default()).await.is_ok() {
+        return Ok(());
+    }
+    copy_recursive(fs, from, to, CopyOptions::default()).await?;
+    let options = RemoveOptions {
+        recursive: true,
+        ignore_if_not_exists: false,
+    };
+    if fs
+        .metadata(from)
+        .await?
+        .map_or(false, |metadata| metadata.is_dir)
+    {
+        fs.trash_dir(from, options).await
+    } else {
+        fs.trash_file(from, options).await
+    }
+}
+
+/// Adds a comment naming `source` at the top of the copied file, when it is a text file in a
+/// language with comments.
+pub(crate) async fn prepend_source_comment(
+    fs: &dyn Fs,
+    languages: &Arc<LanguageRegistry>,
+    abs_path: &Path,
+    source: &str,
+) -> Result<()> {
+    if !fs.is_file(abs_path).await {
+        return Ok(());
+    }
+    let Ok(language) = languages.language_for_file_path(abs_path).await else {
+        return Ok(());
+    };
+    let scope = language.default_scope();
+    let comment = if let Some(prefix) = scope.line_comment_prefixes().first() {
+        format!("{prefix}Copied from {source}")
+    } else if let Some((start, end)) = scope.block_comment_delimiters() {
+        format!("{start}Copied from {source}{end}")
+    } else {
+        return Ok(());
+    };
+    let text = fs.load(abs_path).await?;
+    fs.atomic_write(abs_path.to_path_buf(), prepend_comment(&text, &comment))
+        .await
+}
+
+/// Inserts the comment at the top of the text, keeping a shebang line first.
+fn prepend_comment(text: &str, comment: &str) -> String {
+    if text.starts_with("#!") {
+        let (shebang, rest) = text.split_at(text.find('\n').map_or(text.len(), |ix| ix + 1));
+        let separator = if shebang.ends_with('\n') { "" } else { "\n" };
+        format!("{shebang}{separator}{comment}\n{rest}")
+    } else {
+        format!("{comment}\n{text}")
+    }
+}
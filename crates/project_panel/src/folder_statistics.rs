@@ -0,0 +1,303 @@
+use std::{
+    cmp::Reverse,
+    fmt::Write as _,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use collections::HashMap;
+use gpui::{
+    ClickEvent, ClipboardItem, DismissEvent, EventEmitter, FocusHandle, FocusableView, Render,
+    ScrollHandle, Task,
+};
+use project::Fs;
+use ui::{prelude::*, ElevationIndex, Modal, ModalFooter, ModalHeader, Section, SectionHeader};
+use workspace::ModalView;
+
+const LARGEST_FILES_LIMIT: usize = 10;
+const READ_CHUNK_LEN: usize = 64 * 1024;
+const NO_EXTENSION: &str = "no extension";
+
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct FolderStatistics {
+    pub file_count: usize,
+    pub total_bytes: u64,
+    pub total_lines: usize,
+    /// Statistics per file extension, with the most lines first.
+    pub extensions: Vec<ExtensionStatistics>,
+    /// Folder-relative paths of the largest files with their sizes, largest first.
+    pub largest_files: Vec<(PathBuf, u64)>,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct ExtensionStatistics {
+    pub extension: String,
+    pub file_count: usize,
+    pub lines: usize,
+}
+
+impl FolderStatistics {
+    /// Reads the given files, pairs of a folder-relative and an absolute path, counting their
+    /// lines and bytes. Lines of binary files are not counted.
+    pub(crate) async fn compute(fs: Arc<dyn Fs>, files: Vec<(PathBuf, PathBuf)>) -> Self {
+        let mut statistics = Self::default();
+        let mut extensions = HashMap::<String, ExtensionStatistics>::default();
+        let mut file_sizes = Vec::with_capacity(files.len());
+        for (path, abs_path) in files {
+            let Some((bytes, lines)) = count_file(fs.as_ref(), &abs_path).await else {
+                continue;
+            };
+            let extension = path
+                .extension()
+                .map_or(NO_EXTENSION.to_string(), |extension| {
+                    extension.to_string_lossy().into_owned()
+                });
+            let extension_statistics =
+                extensions
+                    .entry(extension.clone())
+                    .or_insert_with(|| ExtensionStatistics {
+                        extension,
+                        file_count: 0,
+                        lines: 0,
+                    });
+            extension_statistics.file_count += 1;
+            extension_statistics.lines += lines;
+
+            statistics.file_count += 1;
+            statistics.total_bytes += bytes;
+            statistics.total_lines += lines;
+            file_sizes.push((path, bytes));
+        }
+
+        statistics.extensions = extensions.into_values().collect();
+        statistics.extensions.sort_by(|a, b| {
+            (Reverse(a.lines), Reverse(a.file_count), &a.extension).cmp(&(
+                Reverse(b.lines),
+                Reverse(b.file_count),
+                &b.extension,
+            ))
+        });
+        file_sizes.sort_by(|(a_path, a_bytes), (b_path, b_bytes)| {
+            (Reverse(a_bytes), a_path).cmp(&(Reverse(b_bytes), b_path))
+        });
+        file_sizes.truncate(LARGEST_FILES_LIMIT);
+        statistics.largest_files = file_sizes;
+        statistics
+    }
+
+    pub(crate) fn summary(&self, folder_name: &str) -> String {
+        let mut summary = format!(
+            "Statistics for {folder_name}\nFiles: {}\nLines: {}\nSize: {}\n",
+            self.file_count,
+            self.total_lines,
+            format_size(self.total_bytes)
+        );
+        if !self.extensions.is_empty() {
+            summary.push_str("\nBy extension:\n");
+            for extension in &self.extensions {
+                writeln!(
+                    summary,
+                    "  {}: {} files, {} lines",
+                    extension.extension, extension.file_count, extension.lines
+                )
+                .ok();
+            }
+        }
+        if !self.largest_files.is_empty() {
+            summary.push_str("\nLargest files:\n");
+            for (path, bytes) in &self.largest_files {
+                writeln!(summary, "  {}: {}", path.display(), format_size(*bytes)).ok();
+            }
+        }
+        summary
+    }
+}
+
+/// Returns the size of the file and its line count, which is zero for binary files.
+async fn count_file(fs: &dyn Fs, abs_path: &Path) -> Option<(u64, usize)> {
+    let mut file = fs.open_sync(abs_path).await.ok()?;
+    let mut buffer = vec![0; READ_CHUNK_LEN];
+    let mut bytes = 0;
+    let mut lines = 0;
+    let mut is_binary = false;
+    let mut ends_with_newline = true;
+    loop {
+        let read = file.read(&mut buffer).ok()?;
+        if read == 0 {
+            break;
+        }
+        let chunk = &buffer[..read];
+        if bytes == 0 && chunk.contains(&0) {
+            is_binary = true;
+        }
+        bytes += read as u64;
+        if !is_binary {
+            lines += chunk.iter().filter(|byte| **byte == b'\n').count();
+            ends_with_newline = chunk.last() == Some(&b'\n');
+        }
+    }
+    if is_binary {
+        lines = 0;
+    } else if !ends_with_newline {
+        lines += 1;
+    }
+    Some((bytes, lines))
+}
+
+pub(crate) fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.;
+    let mut unit = UNITS[0];
+    for next_unit in &UNITS[1..] {
+        if size < 1024. {
+            break;
+        }
+        size /= 1024.;
+        unit = next_unit;
+    }
+    format!("{size:.1} {unit}")
+}
+
+pub(crate) struct FolderStatisticsModal {
+    folder_name: SharedString,
+    statistics: Option<FolderStatistics>,
+    focus_handle: FocusHandle,
+    scroll_handle: ScrollHandle,
+    _compute_task: Task<()>,
+}
+
+impl EventEmitter<DismissEvent> for FolderStatisticsModal {}
+
+impl FocusableView for FolderStatisticsModal {
+    fn focus_handle(&self, _: &gpui::AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl ModalView for FolderStatisticsModal {}
+
+impl FolderStatisticsModal {
+    pub(crate) fn new(
+        folder_name: SharedString,
+        fs: Arc<dyn Fs>,
+        files: Vec<(PathBuf, PathBuf)>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let compute_task = cx.spawn(|this, mut cx| async move {
+            let statistics = cx
+                .background_executor()
+                .spawn(FolderStatistics::compute(fs, files))
+                .await;
+            this.update(&mut cx, |this, cx| {
+                this.statistics = Some(statistics);
+                cx.notify();
+            })
+            .ok();
+        });
+        Self {
+            folder_name,
+            statistics: None,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: ScrollHandle::new(),
+            _compute_task: compute_task,
+        }
+    }
+
+    fn copy_summary(&mut self, _: &ClickEvent, cx: &mut ViewContext<Self>) {
+        if let Some(statistics) = &self.statistics {
+            cx.write_to_clipboard(ClipboardItem::new(statistics.summary(&self.folder_name)));
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent)
+    }
+
+    fn render_row(label: impl Into<SharedString>, value: impl Into<SharedString>) -> Div {
+        h_flex()
+            .w_full()
+            .px_2()
+            .py_1()
+            .gap_4()
+            .justify_between()
+            .child(Label::new(label).single_line())
+            .child(Label::new(value).color(Color::Muted))
+    }
+}
+
+impl Render for FolderStatisticsModal {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let modal = Modal::new("folder-statistics", Some(self.scroll_handle.clone())).header(
+            ModalHeader::new().show_dismiss_button(true).child(
+                Headline::new(format!("Statistics for {}", self.folder_name))
+                    .size(HeadlineSize::Small),
+            ),
+        );
+        let modal = match &self.statistics {
+            None => modal
+                .section(Section::new().child(Label::new("Counting files…").color(Color::Muted))),
+            Some(statistics) => modal
+                .section(
+                    Section::new()
+                        .child(Self::render_row("Files", statistics.file_count.to_string()))
+                        .child(Self::render_row(
+                            "Lines",
+                            statistics.total_lines.to_string(),
+                        ))
+                        .child(Self::render_row(
+                            "Size",
+                            format_size(statistics.total_bytes),
+                        )),
+                )
+                .when(!statistics.extensions.is_empty(), |modal| {
+                    modal.section(
+                        Section::new()
+                            .header(SectionHeader::new("By Extension"))
+                            .children(statistics.extensions.iter().map(|extension| {
+                                Self::render_row(
+                                    extension.extension.clone(),
+                                    format!(
+                                        "{} files, {} lines",
+                                        extension.file_count, extension.lines
+                                    ),
+                                )
+                            })),
+                    )
+                })
+                .when(!statistics.largest_files.is_empty(), |modal| {
+                    modal.section(
+                        Section::new()
+                            .header(SectionHeader::new("Largest Files"))
+                            .children(statistics.largest_files.iter().map(|(path, bytes)| {
+                                Self::render_row(path.display().to_string(), format_size(*bytes))
+                            })),
+                    )
+                }),
+        };
+
+        div()
+            .track_focus(&self.focus_handle)
+            .elevation_3(cx)
+            .on_action(cx.listener(Self::cancel))
+            .occlude()
+            .w(rems(28.))
+            .max_h(rems(40.))
+            .child(
+                modal.footer(
+                    ModalFooter::new().end_slot(
+                        Button::new("copy-summary", "Copy Summary")
+                            .style(ButtonStyle::Filled)
+                            .layer(ElevationIndex::ModalSurface)
+                            .icon(IconName::Copy)
+                            .icon_position(IconPosition::Start)
+                            .disabled(self.statistics.is_none())
+                            .on_click(cx.listener(Self::copy_summary)),
+                    ),
+                ),
+            )
+    }
+}
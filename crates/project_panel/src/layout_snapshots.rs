@@ -0,0 +1,234 @@
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::Result;
+use db::kvp::KEY_VALUE_STORE;
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Render, Task, View,
+    WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use serde::{Deserialize, Serialize};
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::ModalView;
+
+use crate::ProjectPanel;
+
+/// A named state of the project panel tree. Entries are stored as their worktree's absolute
+/// path and their path inside of it, which stay valid across sessions unlike entry ids.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct LayoutSnapshot {
+    pub name: String,
+    pub expanded_dirs: Vec<(PathBuf, Vec<PathBuf>)>,
+    pub selection: Option<(PathBuf, PathBuf)>,
+    pub focused_directory: Option<(PathBuf, PathBuf)>,
+}
+
+pub(crate) fn read_layout_snapshots(key: &str) -> Result<Vec<LayoutSnapshot>> {
+    Ok(KEY_VALUE_STORE
+        .read_kvp(key)?
+        .map(|snapshots| serde_json::from_str(&snapshots))
+        .transpose()?
+        .unwrap_or_default())
+}
+
+/// Stores the snapshot, replacing any previous snapshot with the same name.
+pub(crate) async fn write_layout_snapshot(key: String, snapshot: LayoutSnapshot) -> Result<()> {
+    let mut snapshots = read_layout_snapshots(&key)?;
+    snapshots.retain(|existing| existing.name != snapshot.name);
+    snapshots.push(snapshot);
+    snapshots.sort_by(|a, b| a.name.cmp(&b.name));
+    KEY_VALUE_STORE
+        .write_kvp(key, serde_json::to_string(&snapshots)?)
+        .await
+}
+
+pub(crate) struct LayoutSnapshots {
+    picker: View<Picker<LayoutSnapshotsDelegate>>,
+}
+
+impl LayoutSnapshots {
+    pub(crate) fn new(
+        project_panel: WeakView<ProjectPanel>,
+        snapshots: Vec<LayoutSnapshot>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let delegate =
+            LayoutSnapshotsDelegate::new(cx.view().downgrade(), project_panel, snapshots);
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+impl Render for LayoutSnapshots {
+    fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for LayoutSnapshots {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for LayoutSnapshots {}
+impl ModalView for LayoutSnapshots {}
+
+pub(crate) struct LayoutSnapshotsDelegate {
+    layout_snapshots: WeakView<LayoutSnapshots>,
+    project_panel: WeakView<ProjectPanel>,
+    snapshots: Vec<LayoutSnapshot>,
+    matches: Vec<StringMatch>,
+    /// Name the current layout can be saved under, offered after the matches.
+    new_snapshot_name: Option<String>,
+    selected_index: usize,
+}
+
+impl LayoutSnapshotsDelegate {
+    fn new(
+        layout_snapshots: WeakView<LayoutSnapshots>,
+        project_panel: WeakView<ProjectPanel>,
+        snapshots: Vec<LayoutSnapshot>,
+    ) -> Self {
+        Self {
+            layout_snapshots,
+            project_panel,
+            snapshots,
+            matches: Vec::new(),
+            new_snapshot_name: None,
+            selected_index: 0,
+        }
+    }
+
+    fn save_snapshot(&self, name: String, cx: &mut ViewContext<Picker<Self>>) {
+        self.project_panel
+            .update(cx, |project_panel, cx| {
+                project_panel.save_layout_snapshot(name, cx)
+            })
+            .log_err();
+    }
+}
+
+impl PickerDelegate for LayoutSnapshotsDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Restore a layout, or type a name to save the current one…".into()
+    }
+
+    fn no_matches_text(&self, _cx: &mut WindowContext) -> SharedString {
+        "No saved layouts".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len() + usize::from(self.new_snapshot_name.is_some())
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .snapshots
+            .iter()
+            .enumerate()
+            .map(|(id, snapshot)| StringMatchCandidate::new(id, snapshot.name.clone()))
+            .collect::<Vec<_>>();
+        let query = query.trim().to_string();
+        let new_snapshot_name = (!query.is_empty()
+            && !self.snapshots.iter().any(|snapshot| snapshot.name == query))
+        .then(|| query.clone());
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.new_snapshot_name = new_snapshot_name;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.match_count().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    /// Restores the selected layout, or overwrites it with the current one on secondary confirm.
+    fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(mat) = self.matches.get(self.selected_index) {
+            let snapshot = self.snapshots[mat.candidate_id].clone();
+            if secondary {
+                self.save_snapshot(snapshot.name, cx);
+            } else {
+                self.project_panel
+                    .update(cx, |project_panel, cx| {
+                        project_panel.restore_layout_snapshot(&snapshot, cx)
+                    })
+                    .log_err();
+            }
+        } else if let Some(name) = self.new_snapshot_name.clone() {
+            self.save_snapshot(name, cx);
+        }
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.layout_snapshots
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let item = ListItem::new(ix)
+            .inset(true)
+            .spacing(ListItemSpacing::Sparse)
+            .selected(selected);
+        match self.matches.get(ix) {
+            Some(mat) => Some(item.child(HighlightedLabel::new(
+                mat.string.clone(),
+                mat.positions.clone(),
+            ))),
+            None => {
+                let name = self.new_snapshot_name.as_ref()?;
+                Some(
+                    item.start_slot(Icon::new(IconName::Plus).color(Color::Muted))
+                        .child(Label::new(format!("Save Current Layout as “{name}”"))),
+                )
+            }
+        }
+    }
+}
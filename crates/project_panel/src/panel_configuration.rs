@@ -0,0 +1,95 @@
+use std::path::{Path, PathBuf};
+
+use collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    entry_annotations::{EntryNotes, EntryTags},
+    layout_snapshots::LayoutSnapshot,
+};
+
+/// Location of the shared panel configuration, relative to the root of a worktree.
+pub(crate) const PANEL_CONFIGURATION_PATH: &str = ".zed/panel.json";
+
+/// The notes, tags and layout snapshots of a worktree, with paths relative to its root so that
+/// the file can be committed and shared with other checkouts.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct PanelConfiguration {
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub notes: BTreeMap<PathBuf, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<PathBuf, Vec<String>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub layout_snapshots: Vec<SharedLayoutSnapshot>,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct SharedLayoutSnapshot {
+    pub name: String,
+    #[serde(default)]
+    pub expanded_dirs: Vec<PathBuf>,
+    #[serde(default)]
+    pub selection: Option<PathBuf>,
+    #[serde(default)]
+    pub focused_directory: Option<PathBuf>,
+}
+
+impl PanelConfiguration {
+    pub(crate) fn new(
+        worktree_abs_path: &Path,
+        notes: &EntryNotes,
+        tags: &EntryTags,
+        layout_snapshots: &[LayoutSnapshot],
+    ) -> Self {
+        let path_in_worktree = |location: &Option<(PathBuf, PathBuf)>| {
+            location
+                .as_ref()
+                .filter(|(abs_path, _)| abs_path == worktree_abs_path)
+                .map(|(_, path)| path.clone())
+        };
+        Self {
+            notes: notes
+                .entries(worktree_abs_path)
+                .map(|(path, note)| (path.to_path_buf(), note.clone()))
+                .collect(),
+            tags: tags
+                .entries(worktree_abs_path)
+                .map(|(path, tags)| (path.to_path_buf(), tags.clone()))
+                .collect(),
+            layout_snapshots: layout_snapshots
+                .iter()
+                .map(|snapshot| SharedLayoutSnapshot {
+                    name: snapshot.name.clone(),
+                    expanded_dirs: snapshot
+                        .expanded_dirs
+                        .iter()
+                        .filter(|(abs_path, _)| abs_path == worktree_abs_path)
+                        .flat_map(|(_, paths)| paths.iter().cloned())
+                        .collect(),
+                    selection: path_in_worktree(&snapshot.selection),
+                    focused_directory: path_in_worktree(&snapshot.focused_directory),
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the layout snapshots of the configuration, as layouts of the worktree.
+    pub(crate) fn layout_snapshots(&self, worktree_abs_path: &Path) -> Vec<LayoutSnapshot> {
+        let location = |path: &Option<PathBuf>| {
+            path.clone()
+                .map(|path| (worktree_abs_path.to_path_buf(), path))
+        };
+        self.layout_snapshots
+            .iter()
+            .map(|snapshot| LayoutSnapshot {
+                name: snapshot.name.clone(),
+                expanded_dirs: vec![(
+                    worktree_abs_path.to_path_buf(),
+                    snapshot.expanded_dirs.clone(),
+                )],
+                selection: location(&snapshot.selection),
+                focused_directory: location(&snapshot.focused_directory),
+            })
+            .collect()
+    }
+}
@@ -1,8 +1,37 @@
+mod batch_operations;
+mod decorations;
+mod directory_usage;
+mod entry_annotations;
+mod file_ops;
+mod folder_statistics;
+mod layout_snapshots;
+mod panel_configuration;
 mod project_panel_settings;
 mod scrollbar;
+mod visible_entry_picker;
+use batch_operations::available_path;
+pub use batch_operations::{BatchOperation, BatchOutcome, ConflictPolicy};
 use client::{ErrorCode, ErrorExt};
+use decorations::{
+    deployment_for_path, license_header_for_path, with_license_header, AvailableSpaceDecoration,
+    CodeOwnerDecoration, DecoratedRow, Decorations, DeploymentStatusDecoration,
+    FileHeaderDecoration, RenderedRow, RenderedRows, DECORATION_PREFETCH_ROWS,
+};
+use directory_usage::{read_directory_usage, record_directory_usage, DirectoryUsage};
+use entry_annotations::{
+    read_entry_annotations, write_entry_annotations, EntryAnnotationEditor, EntryAnnotationKind,
+    EntryNotes, EntryTags,
+};
+use file_ops::{
+    move_across, prepend_source_comment, FileOp, FileOperation, OperationHistory, PendingFileOp,
+};
+use folder_statistics::{format_size, FolderStatisticsModal};
+use layout_snapshots::{
+    read_layout_snapshots, write_layout_snapshot, LayoutSnapshot, LayoutSnapshots,
+};
+use panel_configuration::{PanelConfiguration, PANEL_CONFIGURATION_PATH};
 use scrollbar::ProjectPanelScrollbar;
-use settings::{Settings, SettingsStore};
+use settings::{Settings, SettingsLocation, SettingsStore};
 
 use db::kvp::KEY_VALUE_STORE;
 use editor::{
@@ -11,44 +40,99 @@ use editor::{
     Editor,
 };
 use file_icons::FileIcons;
+use fuzzy::{CharBag, PathMatchCandidate};
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context as _, Result};
 use collections::{hash_map, BTreeSet, HashMap};
-use git::repository::GitFileStatus;
+use git::repository::{GitFileStatus, GitRepository, RepoPath};
 use gpui::{
-    actions, anchored, deferred, div, impl_actions, px, uniform_list, Action, AnyElement,
-    AppContext, AssetSource, AsyncWindowContext, ClipboardItem, DismissEvent, Div, DragMoveEvent,
-    EventEmitter, ExternalPaths, FocusHandle, FocusableView, InteractiveElement, KeyContext,
-    ListSizingBehavior, Model, MouseButton, MouseDownEvent, ParentElement, Pixels, Point,
-    PromptLevel, Render, Stateful, Styled, Subscription, Task, UniformListScrollHandle, View,
-    ViewContext, VisualContext as _, WeakView, WindowContext,
+    actions, anchored, deferred, div, impl_actions, percentage, point, px, rems, uniform_list,
+    Action, AnyElement, AppContext, AssetSource, AsyncWindowContext, ClipboardItem, DismissEvent,
+    Div, DragMoveEvent, EntityId, EventEmitter, ExternalPaths, FocusHandle, FocusableView,
+    InteractiveElement, KeyContext, KeyDownEvent, ListSizingBehavior, Model, MouseButton,
+    MouseDownEvent, ParentElement, Pixels, Point, PromptLevel, Render, Stateful, Styled,
+    Subscription, Task, Transformation, UniformListScrollHandle, View, ViewContext,
+    VisualContext as _, WeakView, WindowContext,
 };
+use language::{Buffer, BufferEvent};
 use menu::{Confirm, SelectFirst, SelectLast, SelectNext, SelectPrev};
-use project::{Entry, EntryKind, Fs, Project, ProjectEntryId, ProjectPath, Worktree, WorktreeId};
-use project_panel_settings::{ProjectPanelDockPosition, ProjectPanelSettings, ShowScrollbar};
+use paths::local_settings_file_relative_path;
+use picker::Picker;
+use project::{
+    copy_recursive, CopyOptions, DiagnosticSummary, Entry, EntryKind, EntryOrdering, Fs, Project,
+    ProjectEntryId, ProjectPath, SharedTreeView, TaskSourceKind, Worktree, WorktreeId,
+    WorktreeSettings,
+};
+use project_panel_settings::{
+    EntrySortMode, FoldersPosition, GitStatusPropagation, NavigationCaseSensitivity, OpenNewFiles,
+    ProjectPanelDockPosition, ProjectPanelSettings, ShowDiagnostics, ShowScrollbar,
+};
 use serde::{Deserialize, Serialize};
 use std::{
     cell::{Cell, OnceCell},
+    cmp,
     collections::HashSet,
     ffi::OsStr,
+    future::Future,
+    mem,
     ops::Range,
     path::{Path, PathBuf},
     rc::Rc,
     sync::Arc,
-    time::Duration,
+    time::{Duration, SystemTime},
 };
+use task::{TaskContext, TaskTemplate, TaskVariables, VariableName};
 use theme::ThemeSettings;
-use ui::{prelude::*, v_flex, ContextMenu, Icon, KeyBinding, Label, ListItem, Tooltip};
-use util::{maybe, ResultExt, TryFutureExt};
+use ui::{
+    prelude::*, v_flex, ContextMenu, Icon, Indicator, KeyBinding, Label, ListHeader, ListItem,
+    ListItemSpacing, Tooltip,
+};
+use util::{
+    maybe,
+    paths::{PathExt, PathMatcher},
+    ResultExt, TryFutureExt,
+};
+use visible_entry_picker::VisibleEntriesDelegate;
 use workspace::{
     dock::{DockPosition, Panel, PanelEvent},
+    item::{ItemHandle, TabContentParams},
     notifications::{DetachAndPromptErr, NotifyTaskExt},
-    DraggedSelection, OpenInTerminal, SelectedEntry, Workspace,
+    pane::render_item_indicator,
+    tasks::schedule_task,
+    DraggedSelection, LocalPaths, OpenInTerminal, Pane, SaveIntent, SelectedEntry,
+    SerializedWorkspaceLocation, Workspace, WORKSPACE_DB,
 };
-use worktree::CreatedEntry;
+use worktree::{CreatedEntry, GitStatuses};
 
 const PROJECT_PANEL_KEY: &str = "ProjectPanel";
 const NEW_ENTRY_ID: ProjectEntryId = ProjectEntryId::MAX;
+const PANEL_WIDTH_STEP: Pixels = px(20.);
+const MIN_PANEL_WIDTH: Pixels = px(120.);
+/// Files longer than this are left out of "Copy File Contents".
+const MAX_COPIED_FILE_LEN: usize = 256 * 1024;
+const FREQUENTLY_USED_DIRECTORIES_LIMIT: usize = 10;
+const TREE_VIEW_SHARING_DEBOUNCE: Duration = Duration::from_millis(100);
+/// Language servers report diagnostics one file at a time, so their sums are recomputed at
+/// most this often while a check is running.
+const DIAGNOSTIC_SUMMARIES_THROTTLE: Duration = Duration::from_millis(100);
+/// Keeps the panel scrollable when `scroll_sensitivity` is set to zero or less.
+const MIN_SCROLL_SENSITIVITY: f32 = 0.1;
+const DECORATION_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long after the last typed character the next one starts a new type-to-select prefix.
+const TYPE_TO_SELECT_TIMEOUT: Duration = Duration::from_secs(1);
+/// How often the roots of local worktrees are checked for having disappeared or come back.
+const ROOT_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// How close to the top or bottom of the list dragged entries start scrolling it, and by how
+/// much every `DRAG_AUTOSCROLL_INTERVAL`.
+const DRAG_AUTOSCROLL_MARGIN: Pixels = px(24.);
+const DRAG_AUTOSCROLL_STEP: Pixels = px(8.);
+const DRAG_AUTOSCROLL_INTERVAL: Duration = Duration::from_millis(16);
+/// How many recently opened projects the panel offers to open when no folder is.
+const RECENT_PROJECTS_LIMIT: usize = 5;
+/// Height above which the open editors scroll, leaving the rest of the panel to the entries.
+const MAX_OPEN_EDITORS_HEIGHT: Pixels = px(200.);
+/// Consecutive failures or timeouts after which a decoration is turned off for the session.
+const MAX_DECORATION_FAILURES: usize = 3;
 
 pub struct ProjectPanel {
     project: Model<Project>,
@@ -59,7 +143,16 @@ pub struct ProjectPanel {
     last_worktree_root_id: Option<ProjectEntryId>,
     last_external_paths_drag_over_entry: Option<ProjectEntryId>,
     expanded_dir_ids: HashMap<WorktreeId, Vec<ProjectEntryId>>,
+    /// Worktrees whose top-level directories get expanded once their root is scanned.
+    pending_top_level_expansions: HashSet<WorktreeId>,
+    /// Worktrees to apply `expand_dirs` and `never_expand_dirs` to, once their initial scan is over.
+    pending_rule_expansions: HashSet<WorktreeId>,
+    /// Worktrees seen done with their initial scan. Only their root is shown before that.
+    scanned_worktrees: HashSet<WorktreeId>,
+    initial_scan_tasks: HashMap<WorktreeId, Task<()>>,
     unfolded_dir_ids: HashSet<ProjectEntryId>,
+    // A directory temporarily shown as the only root of the panel
+    focused_directory: Option<(WorktreeId, ProjectEntryId)>,
     // Currently selected entry in a file tree
     selection: Option<SelectedEntry>,
     marked_entries: BTreeSet<SelectedEntry>,
@@ -67,19 +160,159 @@ pub struct ProjectPanel {
     edit_state: Option<EditState>,
     filename_editor: View<Editor>,
     clipboard: Option<ClipboardEntry>,
-    _dragged_entry_destination: Option<Arc<Path>>,
+    /// Directory the entries being dragged are moved into if dropped where the pointer is.
+    dragged_entry_destination: Option<(WorktreeId, Arc<Path>)>,
     workspace: WeakView<Workspace>,
     width: Option<Pixels>,
+    /// Widths the user picked, keyed by the display the window was on.
+    width_by_display: HashMap<String, Pixels>,
     pending_serialization: Task<Option<()>>,
     show_scrollbar: bool,
     scrollbar_drag_thumb_offset: Rc<Cell<Option<f32>>>,
     hide_scrollbar_task: Option<Task<()>>,
+    /// Characters typed in a row, selecting the next entry whose name starts with them.
+    typed_prefix: String,
+    typed_prefix_reset_task: Option<Task<()>>,
+    generated_paths: PathMatcher,
+    /// Decorations computed for the rendered rows, each by its own provider.
+    decorations: Decorations,
+    /// Whether the rows changed since the row decorations were last refreshed.
+    row_decorations_stale: bool,
+    /// Rows last rendered by the list, around which row decorations are computed.
+    rendered_range: Range<usize>,
+    decoration_failures: HashMap<Decoration, usize>,
+    /// Sizes of the visible files, as read for their last seen mtime, to sort them by.
+    file_sizes: HashMap<ProjectEntryId, (Option<SystemTime>, u64)>,
+    file_size_task: Option<Task<()>>,
+    directory_usage_task: Option<Task<()>>,
+    entry_notes: EntryNotes,
+    entry_tags: EntryTags,
+    entry_annotations_task: Option<Task<()>>,
+    entry_annotations_write_task: Option<Task<()>>,
+    /// Tag that entries have to be tagged with, or contain entries tagged with, to be shown.
+    tag_filter: Option<String>,
+    /// Whether only the files with a git status, and the directories containing them, are shown.
+    show_changed_files_only: bool,
+    /// Whether the host shares its expansion and selection with the guests of the project.
+    is_sharing_tree_view: bool,
+    /// Expansion and selection last sent to the guests, so that they are only sent again once
+    /// they change.
+    last_shared_tree_view: Option<(
+        HashMap<WorktreeId, Vec<ProjectEntryId>>,
+        Option<SelectedEntry>,
+    )>,
+    tree_view_sharing_task: Option<Task<()>>,
+    /// Tree view shared by the host, as seen by a guest.
+    host_tree_view: Option<SharedTreeView>,
+    is_following_host_tree_view: bool,
+    unmounted_worktrees: Vec<UnmountedWorktree>,
+    _root_check_task: Task<()>,
+    /// Inline picker over the visible entries, shown above them.
+    visible_entry_picker: Option<(View<Picker<VisibleEntriesDelegate>>, Subscription)>,
+    filter_editor: View<Editor>,
+    /// Fuzzy query that entries have to match, or contain entries matching, to be shown.
+    entry_filter: Option<String>,
+    /// Entries matching `entry_filter` in each worktree, computed in the background, or `None`
+    /// until the first matches of the query are in.
+    entry_filter_matches: Option<HashMap<WorktreeId, FilterMatches>>,
+    entry_filter_task: Option<Task<()>>,
+    /// Whether to expand the directories of the matches once they are in, as the query changed.
+    expand_entry_filter_matches: bool,
+    file_nesting: HashMap<WorktreeId, FileNesting>,
+    /// Position loaded from the previous session, restored once its entry is scanned.
+    restored_position: Option<SerializedPosition>,
+    operation_history: OperationHistory,
+    /// How much the list scrolls down on each tick while entries are dragged near its edges,
+    /// negative to scroll up.
+    drag_autoscroll_step: Pixels,
+    drag_autoscroll_task: Option<Task<()>>,
+    /// Local projects opened recently, listed while no worktree is open.
+    recent_projects: Vec<LocalPaths>,
+    /// Whether the items listed by `show_open_editors` are shown, or only their header.
+    open_editors_expanded: bool,
+    /// Files with unsaved changes in their open buffers.
+    dirty_entries: HashSet<ProjectEntryId>,
+    buffer_subscriptions: HashMap<EntityId, Subscription>,
+    /// Diagnostics of the files with some, summed up for the directories containing them.
+    diagnostic_summaries: HashMap<WorktreeId, HashMap<Arc<Path>, DiagnosticSummary>>,
+    diagnostic_summaries_task: Option<Task<()>>,
+    /// Tracked files deleted from the disk but not from the index, listed as ghost entries.
+    deleted_files: HashMap<WorktreeId, Vec<Arc<Path>>>,
+    deleted_files_task: Option<Task<()>>,
+    /// Placeholder ids of the ghost entries, kept while the panel lives so that selecting
+    /// a ghost entry survives updates of the list.
+    ghost_entry_ids: HashMap<(WorktreeId, Arc<Path>), ProjectEntryId>,
+    /// The reverse of `ghost_entry_ids`, to look ghost entries up while rendering.
+    ghost_entry_paths: HashMap<ProjectEntryId, (WorktreeId, Arc<Path>)>,
+}
+
+/// Paths of a worktree's entries matching the filter query, and of the directories containing them.
+#[derive(Debug, Default)]
+struct FilterMatches {
+    matches: HashSet<Arc<Path>>,
+    ancestors: HashSet<Arc<Path>>,
+}
+
+impl FilterMatches {
+    fn insert(&mut self, path: Arc<Path>) {
+        for ancestor in path.ancestors().skip(1) {
+            if !self.ancestors.insert(ancestor.into()) {
+                break;
+            }
+        }
+        self.matches.insert(path);
+    }
+
+    /// Whether the entry matches, is inside a matching directory or contains a match.
+    fn contains(&self, path: &Path) -> bool {
+        self.ancestors.contains(path) || path.ancestors().any(|path| self.matches.contains(path))
+    }
+}
+
+/// Files shown under a related file of the same directory, as configured by `file_nesting`.
+#[derive(Debug, Default)]
+struct FileNesting {
+    /// Files with related files nested under them, expandable like directories.
+    sources: HashSet<ProjectEntryId>,
+    /// Nested files shown under their expanded source file.
+    nested: HashSet<ProjectEntryId>,
+}
+
+/// A worktree removed because its root disappeared, e.g. with its drive unmounted,
+/// shown as a placeholder until its root comes back.
+#[derive(Clone, Debug)]
+struct UnmountedWorktree {
+    abs_path: Arc<Path>,
+    root_name: SharedString,
+    /// Directories that were expanded, to expand again once the worktree is added back.
+    expanded_paths: Vec<Arc<Path>>,
+}
+
+/// Row decorations computed in the background.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+enum Decoration {
+    /// A decoration of the rendered rows, named after its provider.
+    Row(&'static str),
+    FileSizes,
+    DeletedFiles,
+}
+
+impl Decoration {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Row(name) => name,
+            Self::FileSizes => "file size",
+            Self::DeletedFiles => "deleted file",
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
 struct EditState {
     worktree_id: WorktreeId,
     entry_id: ProjectEntryId,
+    /// Path of the edited entry, or of the folder a new entry goes into, when the edit started.
+    path: Arc<Path>,
     is_new_entry: bool,
     is_dir: bool,
     processing_filename: Option<String>,
@@ -97,8 +330,24 @@ pub struct EntryDetails {
     icon: Option<Arc<str>>,
     path: Arc<Path>,
     depth: usize,
+    /// The actual depth of entries nested deeper than `max_indent_depth`, which are indented less.
+    full_depth: Option<usize>,
     kind: EntryKind,
     is_ignored: bool,
+    /// Whether the file has unsaved changes in an open buffer.
+    is_dirty: bool,
+    /// Whether the file is deleted from the disk but still tracked, listed as a ghost entry.
+    is_deleted: bool,
+    is_generated: bool,
+    is_nested_repo: bool,
+    /// Whether the directory could not be read because of a lack of permissions.
+    is_inaccessible: bool,
+    /// Diagnostics of the file, or of the files within the directory.
+    diagnostic_summary: Option<DiagnosticSummary>,
+    note: Option<SharedString>,
+    tags: Vec<SharedString>,
+    /// Whether related files are nested under this file.
+    has_nested_files: bool,
     is_expanded: bool,
     is_selected: bool,
     is_marked: bool,
@@ -106,9 +355,16 @@ pub struct EntryDetails {
     is_processing: bool,
     is_cut: bool,
     git_status: Option<GitFileStatus>,
+    /// Numbers of files with each status, for directories showing a status.
+    git_statuses: Option<GitStatuses>,
+    /// Number of entries found so far, for the root of a worktree still in its initial scan.
+    scanned_entry_count: Option<usize>,
+    mtime: Option<SystemTime>,
     is_private: bool,
     worktree_id: WorktreeId,
     canonical_path: Option<PathBuf>,
+    /// Where the entry really is, for entries outside of the worktree reached through a symlink.
+    external_location: Option<PathBuf>,
 }
 
 #[derive(PartialEq, Clone, Default, Debug, Deserialize)]
@@ -129,25 +385,67 @@ actions!(
     project_panel,
     [
         ExpandSelectedEntry,
+        ExpandSelectedEntryRecursively,
         CollapseSelectedEntry,
+        CollapseSelectedEntryRecursively,
         CollapseAllEntries,
+        ExpandAllEntries,
+        ExpandAllOneLevel,
+        CollapseWorktree,
+        ExpandWorktreeOneLevel,
         NewDirectory,
         NewFile,
         Copy,
         CopyPath,
         CopyRelativePath,
+        CopyAsMarkdownLink,
+        CopyFileContents,
+        CopyOwners,
+        AddToAssistantContext,
+        InsertLicenseHeader,
+        ShowFolderStatistics,
+        EditNote,
+        EditTags,
+        ExportPanelConfiguration,
+        RetryReadingFolder,
+        ShowFolderAccessHelp,
+        ImportPanelConfiguration,
+        StopWatchingFolder,
         Duplicate,
         RevealInFinder,
         Cut,
         Paste,
+        PasteWithSourceComment,
         Rename,
         Open,
         OpenPermanent,
+        OpenToTheSideKeepFocus,
+        CloseEditorsForFile,
+        RemoveDuplicateWorktree,
+        ReconnectWorktrees,
         ToggleFocus,
         NewSearchInDirectory,
+        FindFileInDirectory,
         UnfoldDirectory,
         FoldDirectory,
         SelectParent,
+        FocusOnFolder,
+        ExitFolderFocus,
+        OpenLayoutSnapshots,
+        JumpToVisibleEntry,
+        ExpandFrequentlyUsed,
+        ToggleShareTreeView,
+        ToggleFollowHostTreeView,
+        IncreasePanelWidth,
+        DecreasePanelWidth,
+        ResetPanelWidth,
+        RevealActiveEntry,
+        Undo,
+        Redo,
+        Refresh,
+        RestoreDeletedFile,
+        StageDeletion,
+        ToggleChangedFilesOnly,
     ]
 );
 
@@ -163,6 +461,20 @@ pub fn init(assets: impl AssetSource, cx: &mut AppContext) {
         workspace.register_action(|workspace, _: &ToggleFocus, cx| {
             workspace.toggle_panel_focus::<ProjectPanel>(cx);
         });
+        workspace.register_action(|workspace, _: &RevealActiveEntry, cx| {
+            let Some(entry_id) = workspace
+                .active_item(cx)
+                .and_then(|item| item.project_entry_ids(cx).first().copied())
+            else {
+                return;
+            };
+            if let Some(project_panel) = workspace.focus_panel::<ProjectPanel>(cx) {
+                project_panel.update(cx, |project_panel, cx| {
+                    let project = project_panel.project.clone();
+                    project_panel.reveal_entry(project, entry_id, false, cx);
+                });
+            }
+        });
     })
     .detach();
 }
@@ -178,12 +490,28 @@ pub enum Event {
     SplitEntry {
         entry_id: ProjectEntryId,
     },
+    /// Opens the entry in the pane next to the active one, keeping the focus in the panel.
+    OpenedEntryToTheSide {
+        entry_id: ProjectEntryId,
+    },
     Focus,
 }
 
 #[derive(Serialize, Deserialize)]
 struct SerializedProjectPanel {
     width: Option<Pixels>,
+    #[serde(default)]
+    width_by_display: HashMap<String, Pixels>,
+    #[serde(default)]
+    position: Option<SerializedPosition>,
+}
+
+/// Where the panel was left: its selected entry and how far it was scrolled.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct SerializedPosition {
+    worktree_abs_path: PathBuf,
+    selected_path: PathBuf,
+    scroll_offset: Pixels,
 }
 
 struct DraggedProjectEntryView {
@@ -196,15 +524,29 @@ struct DraggedProjectEntryView {
 impl ProjectPanel {
     fn new(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
         let project = workspace.project().clone();
+        let workspace_handle = cx.view().clone();
         let project_panel = cx.new_view(|cx: &mut ViewContext<Self>| {
+            cx.subscribe(&workspace_handle, |_, _, event, cx| match event {
+                workspace::Event::ItemAdded
+                | workspace::Event::ItemRemoved
+                | workspace::Event::ActiveItemChanged => {
+                    if ProjectPanelSettings::get_global(cx).show_open_editors {
+                        cx.notify();
+                    }
+                }
+                _ => {}
+            })
+            .detach();
             let focus_handle = cx.focus_handle();
             cx.on_focus(&focus_handle, Self::focus_in).detach();
             cx.on_focus_out(&focus_handle, |this, _, cx| {
                 this.hide_scrollbar(cx);
+                this.serialize(cx);
             })
             .detach();
             cx.subscribe(&project, |this, project, event, cx| match event {
                 project::Event::ActiveEntryChanged(Some(entry_id)) => {
+                    this.record_directory_usage(*entry_id, cx);
                     if ProjectPanelSettings::get_global(cx).auto_reveal_entries {
                         this.reveal_entry(project, *entry_id, true, cx);
                     }
@@ -213,25 +555,90 @@ impl ProjectPanel {
                     this.reveal_entry(project, *entry_id, false, cx);
                     cx.emit(PanelEvent::Activate);
                 }
+                project::Event::SelectInProjectPanel(entry_ids) => {
+                    this.select_entries(project, entry_ids, cx);
+                    cx.emit(PanelEvent::Activate);
+                }
                 project::Event::ActivateProjectPanel => {
                     cx.emit(PanelEvent::Activate);
                 }
                 project::Event::WorktreeRemoved(id) => {
                     this.expanded_dir_ids.remove(id);
+                    this.deleted_files.remove(id);
+                    this.reload_entry_annotations(cx);
+                    this.update_visible_entries(None, cx);
+                    // The empty panel lists recent projects, which may have changed since it was opened.
+                    if this.project.read(cx).visible_worktrees(cx).next().is_none() {
+                        this.load_recent_projects(cx);
+                    }
+                    cx.notify();
+                }
+                project::Event::WorktreeUpdatedEntries(worktree_id, updated_entries) => {
+                    let paths = updated_entries
+                        .iter()
+                        .map(|(path, _, _)| path.as_ref())
+                        .collect::<Vec<&Path>>();
+                    this.invalidate_row_decorations(*worktree_id, &paths);
+                    this.refresh_entry_filter_matches(cx);
+                    this.update_edit_state_target(cx);
+                    this.update_visible_entries(None, cx);
+                    cx.notify();
+                }
+                project::Event::WorktreeUpdatedGitRepositories => {
+                    this.refresh_deleted_files(cx);
+                }
+                project::Event::WorktreeAdded => {
+                    this.refresh_deleted_files(cx);
+                    this.reload_entry_annotations(cx);
                     this.update_visible_entries(None, cx);
                     cx.notify();
                 }
-                project::Event::WorktreeUpdatedEntries(_, _)
-                | project::Event::WorktreeAdded
-                | project::Event::WorktreeOrderChanged => {
+                project::Event::DiagnosticsUpdated { .. } => {
+                    this.schedule_diagnostic_summaries_refresh(cx);
+                }
+                project::Event::BufferOpened(buffer) => {
+                    this.track_buffer(buffer, cx);
+                    this.refresh_dirty_entries(cx);
+                }
+                project::Event::WorktreeOrderChanged => {
                     this.update_visible_entries(None, cx);
                     cx.notify();
                 }
+                project::Event::HostTreeViewUpdated(tree_view) => {
+                    this.host_tree_view = tree_view.clone();
+                    if this.is_following_host_tree_view {
+                        this.follow_host_tree_view(cx);
+                    }
+                    cx.notify();
+                }
+                project::Event::CollaboratorJoined(_) => {
+                    // Send the whole tree view again, for the new guest.
+                    this.last_shared_tree_view = None;
+                    this.share_tree_view(cx);
+                }
+                project::Event::RemoteIdChanged(None) => {
+                    this.is_sharing_tree_view = false;
+                    this.last_shared_tree_view = None;
+                    this.tree_view_sharing_task = None;
+                    cx.notify();
+                }
                 _ => {}
             })
             .detach();
 
             let filename_editor = cx.new_view(|cx| Editor::single_line(cx));
+            let filter_editor = cx.new_view(|cx| {
+                let mut editor = Editor::single_line(cx);
+                editor.set_placeholder_text("Filter entries…", cx);
+                editor
+            });
+            cx.subscribe(&filter_editor, |this, filter_editor, event, cx| {
+                if let editor::EditorEvent::BufferEdited = event {
+                    let query = filter_editor.read(cx).text(cx);
+                    this.filter_entries(query, cx);
+                }
+            })
+            .detach();
 
             cx.subscribe(&filename_editor, |this, _, event, cx| match event {
                 editor::EditorEvent::BufferEdited
@@ -257,10 +664,36 @@ impl ProjectPanel {
             })
             .detach();
 
-            let mut project_panel_settings = *ProjectPanelSettings::get_global(cx);
-            cx.observe_global::<SettingsStore>(move |_, cx| {
-                let new_settings = *ProjectPanelSettings::get_global(cx);
+            let mut project_panel_settings = ProjectPanelSettings::get_global(cx).clone();
+            let generated_paths = generated_paths_matcher(&project_panel_settings);
+            cx.observe_global::<SettingsStore>(move |this, cx| {
+                let new_settings = ProjectPanelSettings::get_global(cx).clone();
                 if project_panel_settings != new_settings {
+                    if project_panel_settings.generated_paths != new_settings.generated_paths {
+                        this.generated_paths = generated_paths_matcher(&new_settings);
+                    }
+                    this.row_decoration_settings_changed(cx);
+                    if project_panel_settings.show_diagnostics != new_settings.show_diagnostics {
+                        this.refresh_diagnostic_summaries(cx);
+                    }
+                    if project_panel_settings.git_status != new_settings.git_status
+                        || project_panel_settings.show_deleted_files
+                            != new_settings.show_deleted_files
+                    {
+                        this.refresh_deleted_files(cx);
+                    }
+                    if project_panel_settings.git_status_propagation
+                        != new_settings.git_status_propagation
+                        || project_panel_settings.git_status_priority
+                            != new_settings.git_status_priority
+                        || project_panel_settings.file_nesting != new_settings.file_nesting
+                        || project_panel_settings.sort_by != new_settings.sort_by
+                        || project_panel_settings.folders_position != new_settings.folders_position
+                        || project_panel_settings.case_sensitive_sort
+                            != new_settings.case_sensitive_sort
+                    {
+                        this.update_visible_entries(None, cx);
+                    }
                     project_panel_settings = new_settings;
                     cx.notify();
                 }
@@ -276,22 +709,78 @@ impl ProjectPanel {
                 last_worktree_root_id: Default::default(),
                 last_external_paths_drag_over_entry: None,
                 expanded_dir_ids: Default::default(),
+                pending_top_level_expansions: Default::default(),
+                pending_rule_expansions: Default::default(),
+                scanned_worktrees: Default::default(),
+                initial_scan_tasks: Default::default(),
                 unfolded_dir_ids: Default::default(),
+                focused_directory: None,
                 selection: None,
                 marked_entries: Default::default(),
                 edit_state: None,
                 context_menu: None,
                 filename_editor,
                 clipboard: None,
-                _dragged_entry_destination: None,
+                dragged_entry_destination: None,
                 workspace: workspace.weak_handle(),
                 width: None,
+                width_by_display: HashMap::default(),
                 pending_serialization: Task::ready(None),
                 show_scrollbar: !Self::should_autohide_scrollbar(cx),
                 hide_scrollbar_task: None,
+                typed_prefix: String::new(),
+                typed_prefix_reset_task: None,
                 scrollbar_drag_thumb_offset: Default::default(),
+                generated_paths,
+                decorations: Decorations::new(cx),
+                row_decorations_stale: false,
+                rendered_range: 0..0,
+                decoration_failures: Default::default(),
+                file_sizes: HashMap::default(),
+                file_size_task: None,
+                directory_usage_task: None,
+                entry_notes: EntryNotes::default(),
+                entry_tags: EntryTags::default(),
+                entry_annotations_task: None,
+                entry_annotations_write_task: None,
+                tag_filter: None,
+                show_changed_files_only: false,
+                is_sharing_tree_view: false,
+                last_shared_tree_view: None,
+                tree_view_sharing_task: None,
+                host_tree_view: None,
+                is_following_host_tree_view: true,
+                unmounted_worktrees: Vec::new(),
+                _root_check_task: Task::ready(()),
+                visible_entry_picker: None,
+                filter_editor,
+                entry_filter: None,
+                entry_filter_matches: None,
+                entry_filter_task: None,
+                expand_entry_filter_matches: false,
+                file_nesting: HashMap::default(),
+                restored_position: None,
+                operation_history: OperationHistory::default(),
+                drag_autoscroll_step: px(0.),
+                drag_autoscroll_task: None,
+                recent_projects: Vec::new(),
+                diagnostic_summaries: HashMap::default(),
+                diagnostic_summaries_task: None,
+                open_editors_expanded: true,
+                dirty_entries: HashSet::default(),
+                buffer_subscriptions: HashMap::default(),
+                deleted_files: HashMap::default(),
+                deleted_files_task: None,
+                ghost_entry_ids: HashMap::default(),
+                ghost_entry_paths: HashMap::default(),
             };
+            this._root_check_task = this.watch_worktree_roots(cx);
             this.update_visible_entries(None, cx);
+            this.reload_entry_annotations(cx);
+            this.load_recent_projects(cx);
+            this.refresh_diagnostic_summaries(cx);
+            this.refresh_deleted_files(cx);
+            this.track_open_buffers(cx);
 
             this
         });
@@ -355,6 +844,8 @@ impl ProjectPanel {
                                 if !focus_opened_item {
                                     let focus_handle = project_panel.read(cx).focus_handle.clone();
                                     cx.focus(&focus_handle);
+                                } else if ProjectPanelSettings::get_global(cx).auto_hide_on_open {
+                                    project_panel.update(cx, |_, cx| cx.emit(PanelEvent::Close));
                                 }
                             }
                         }
@@ -375,6 +866,31 @@ impl ProjectPanel {
                         }
                     }
                 }
+                &Event::OpenedEntryToTheSide { entry_id } => {
+                    if let Some(worktree) = project.read(cx).worktree_for_entry(entry_id, cx) {
+                        if let Some(entry) = worktree.read(cx).entry_for_id(entry_id) {
+                            // Adding a pane focuses it, so the panel gets the focus back before
+                            // the active pane changes, which keeps later calls on the same side.
+                            let pane = workspace.adjacent_pane(cx);
+                            workspace
+                                .open_path_preview(
+                                    ProjectPath {
+                                        worktree_id: worktree.read(cx).id(),
+                                        path: entry.path.clone(),
+                                    },
+                                    Some(pane.downgrade()),
+                                    false,
+                                    true,
+                                    cx,
+                                )
+                                .detach_and_log_err(cx);
+                            if let Some(project_panel) = project_panel.upgrade() {
+                                let focus_handle = project_panel.read(cx).focus_handle.clone();
+                                cx.focus(&focus_handle);
+                            }
+                        }
+                    }
+                }
                 _ => {}
             }
         })
@@ -404,6 +920,13 @@ impl ProjectPanel {
             if let Some(serialized_panel) = serialized_panel {
                 panel.update(cx, |panel, cx| {
                     panel.width = serialized_panel.width.map(|px| px.round());
+                    panel.width_by_display = serialized_panel
+                        .width_by_display
+                        .into_iter()
+                        .map(|(display, px)| (display, px.round()))
+                        .collect();
+                    panel.restored_position = serialized_panel.position;
+                    panel.restore_position(cx);
                     cx.notify();
                 });
             }
@@ -413,12 +936,20 @@ impl ProjectPanel {
 
     fn serialize(&mut self, cx: &mut ViewContext<Self>) {
         let width = self.width;
+        let width_by_display = self.width_by_display.clone();
+        let position = self
+            .serialized_position(cx)
+            .or_else(|| self.restored_position.clone());
         self.pending_serialization = cx.background_executor().spawn(
             async move {
                 KEY_VALUE_STORE
                     .write_kvp(
                         PROJECT_PANEL_KEY.into(),
-                        serde_json::to_string(&SerializedProjectPanel { width })?,
+                        serde_json::to_string(&SerializedProjectPanel {
+                            width,
+                            width_by_display,
+                            position,
+                        })?,
                     )
                     .await?;
                 anyhow::Ok(())
@@ -427,6 +958,72 @@ impl ProjectPanel {
         );
     }
 
+    fn serialized_position(&self, cx: &AppContext) -> Option<SerializedPosition> {
+        let selection = self.selection?;
+        let worktree = self
+            .project
+            .read(cx)
+            .worktree_for_id(selection.worktree_id, cx)?;
+        let worktree = worktree.read(cx);
+        let entry = worktree.entry_for_id(selection.entry_id)?;
+        Some(SerializedPosition {
+            worktree_abs_path: worktree.abs_path().to_path_buf(),
+            selected_path: entry.path.to_path_buf(),
+            scroll_offset: self.scroll_handle.0.borrow().base_handle.offset().y,
+        })
+    }
+
+    /// Selects the entry and scrolls back to where the panel was left, once the entry is
+    /// scanned. Gives up once its worktree is fully scanned without it.
+    fn restore_position(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(position) = self.restored_position.as_ref() else {
+            return;
+        };
+        let project = self.project.read(cx);
+        let is_scanned =
+            |worktree_id| !project.is_local() || self.scanned_worktrees.contains(&worktree_id);
+        let worktree = project
+            .visible_worktrees(cx)
+            .find(|worktree| *worktree.read(cx).abs_path() == *position.worktree_abs_path);
+        let Some(worktree) = worktree else {
+            if project
+                .visible_worktrees(cx)
+                .all(|worktree| is_scanned(worktree.read(cx).id()))
+                && project.visible_worktrees(cx).next().is_some()
+            {
+                self.restored_position = None;
+            }
+            return;
+        };
+        let worktree = worktree.read(cx);
+        let worktree_id = worktree.id();
+        let Some(entry) = worktree.entry_for_path(&position.selected_path) else {
+            if is_scanned(worktree_id) {
+                self.restored_position = None;
+            }
+            return;
+        };
+        let entry_id = entry.id;
+        let parent_id = entry
+            .path
+            .parent()
+            .and_then(|parent| worktree.entry_for_path(parent))
+            .map(|parent| parent.id);
+        let scroll_offset = position.scroll_offset;
+
+        self.restored_position = None;
+        if let Some(parent_id) = parent_id {
+            self.expand_entry(worktree_id, parent_id, cx);
+        }
+        self.update_visible_entries(Some((worktree_id, entry_id)), cx);
+        self.scroll_handle
+            .0
+            .borrow()
+            .base_handle
+            .set_offset(point(px(0.), scroll_offset));
+        cx.notify();
+    }
+
     fn focus_in(&mut self, cx: &mut ViewContext<Self>) {
         if !self.focus_handle.contains_focused(cx) {
             cx.emit(Event::Focus);
@@ -439,6 +1036,26 @@ impl ProjectPanel {
         entry_id: ProjectEntryId,
         cx: &mut ViewContext<Self>,
     ) {
+        if let Some((worktree_id, _)) = self.ghost_entry(entry_id) {
+            self.selection = Some(SelectedEntry {
+                worktree_id,
+                entry_id,
+            });
+            let context_menu = ContextMenu::build(cx, |menu, _| {
+                menu.context(self.focus_handle.clone())
+                    .action("Restore from Git", Box::new(RestoreDeletedFile))
+                    .action("Stage Deletion", Box::new(StageDeletion))
+            });
+            cx.focus_view(&context_menu);
+            let subscription = cx.subscribe(&context_menu, |this, _, _: &DismissEvent, cx| {
+                this.context_menu.take();
+                cx.notify();
+            });
+            self.context_menu = Some((context_menu, position, subscription));
+            cx.notify();
+            return;
+        }
+
         let this = cx.view().clone();
         let project = self.project.read(cx);
 
@@ -455,6 +1072,7 @@ impl ProjectPanel {
 
         if let Some((worktree, entry)) = self.selected_entry(cx) {
             let auto_fold_dirs = ProjectPanelSettings::get_global(cx).auto_fold_dirs;
+            let enable_trash = ProjectPanelSettings::get_global(cx).enable_trash;
             let is_root = Some(entry) == worktree.root_entry();
             let is_dir = entry.is_dir();
             let is_foldable = auto_fold_dirs && self.is_foldable(entry, worktree);
@@ -463,15 +1081,123 @@ impl ProjectPanel {
             let is_local = project.is_local();
             let is_read_only = project.is_read_only();
             let is_remote = project.is_remote();
+            let is_shared = project.is_shared();
+            let is_sharing_tree_view = self.is_sharing_tree_view;
+            let is_folder_focused = self.focused_directory.is_some();
+            let nested_repo_abs_path = (is_local && is_nested_repo(worktree, entry))
+                .then(|| worktree.abs_path().join(&entry.path));
+            let deployment = is_local
+                .then(|| deployment_for_path(&entry.path, cx))
+                .flatten()
+                .cloned();
+            let is_inaccessible = worktree
+                .as_local()
+                .map_or(false, |worktree| worktree.is_dir_inaccessible(&entry.path));
+            let has_panel_configuration = worktree
+                .entry_for_path(PANEL_CONFIGURATION_PATH)
+                .map_or(false, |entry| entry.is_file());
+            let has_code_owners = self
+                .code_owners_for_entry(worktree_id, &entry.path)
+                .map_or(false, |owners| !owners.is_empty());
+            let file_headers = self.decorations.get::<FileHeaderDecoration>();
+            let is_open = !is_dir && !self.open_items_for_entry(entry.id, cx).is_empty();
+            let unwatched_folders = if is_local && is_dir {
+                unwatched_folders(worktree_id, &entry.path, cx)
+            } else {
+                Vec::new()
+            };
+            let encryption = &ProjectPanelSettings::get_global(cx).encryption;
+            let decrypt_command = encryption
+                .decrypt_command
+                .clone()
+                .filter(|_| is_local && file_headers.is_encrypted(entry.id));
+            let encrypt_command = encryption
+                .encrypt_command
+                .clone()
+                .filter(|_| is_local && file_headers.is_decrypted(entry.id));
+            let insert_license_header_label = if !is_local {
+                None
+            } else if is_dir {
+                (!ProjectPanelSettings::get_global(cx)
+                    .license_headers
+                    .is_empty())
+                .then_some("Insert Missing License Headers")
+            } else {
+                file_headers
+                    .file_header(entry.id)
+                    .map_or(false, |file_header| file_header.is_missing_license_header)
+                    .then_some("Insert License Header")
+            };
+            let linked_git_worktrees = if is_root && is_local {
+                linked_git_worktrees(worktree, project, cx)
+            } else {
+                Vec::new()
+            };
+            let paste_unavailable_reason = match self.clipboard.as_ref() {
+                None => Some("Clipboard is empty"),
+                // Entries of other worktrees are copied through the file system.
+                Some(_) if is_local => None,
+                Some(clipboard) => {
+                    let entries_for_worktree_id = (SelectedEntry {
+                        worktree_id,
+                        entry_id: ProjectEntryId::MIN,
+                    })..(SelectedEntry {
+                        worktree_id,
+                        entry_id: ProjectEntryId::MAX,
+                    });
+                    if clipboard
+                        .items()
+                        .range(entries_for_worktree_id)
+                        .next()
+                        .is_none()
+                    {
+                        Some("Clipboard entries belong to another worktree")
+                    } else {
+                        None
+                    }
+                }
+            };
+            let paste_with_source_comment_unavailable_reason =
+                paste_unavailable_reason.or_else(|| {
+                    self.clipboard
+                        .as_ref()
+                        .filter(|clipboard| clipboard.is_cut())
+                        .map(|_| "Source comments are only added to copied entries")
+                });
 
             let context_menu = ContextMenu::build(cx, |menu, cx| {
                 menu.context(self.focus_handle.clone()).when_else(
                     is_read_only,
                     |menu| {
-                        menu.action("Copy Relative Path", Box::new(CopyRelativePath))
-                            .when(is_dir, |menu| {
-                                menu.action("Search Inside", Box::new(NewSearchInDirectory))
-                            })
+                        menu.when(!is_dir, |menu| {
+                            menu.action("Open to the Side", Box::new(OpenToTheSideKeepFocus))
+                        })
+                        .action("Copy Relative Path", Box::new(CopyRelativePath))
+                        .action("Copy as Markdown Link", Box::new(CopyAsMarkdownLink))
+                        .when(has_code_owners, |menu| {
+                            menu.action("Copy Owners", Box::new(CopyOwners))
+                        })
+                        .action("Add to Assistant Context", Box::new(AddToAssistantContext))
+                        .action("Edit Note…", Box::new(EditNote))
+                        .action("Edit Tags…", Box::new(EditTags))
+                        .when(is_dir, |menu| {
+                            menu.action("Search Inside", Box::new(NewSearchInDirectory))
+                                .action("Find File in This Folder…", Box::new(FindFileInDirectory))
+                        })
+                        .when(is_dir && is_local && !is_inaccessible, |menu| {
+                            menu.action("Folder Statistics", Box::new(ShowFolderStatistics))
+                        })
+                        .when(is_inaccessible, |menu| {
+                            menu.separator()
+                                .action("Retry Reading Folder", Box::new(RetryReadingFolder))
+                                .action("How to Grant Access…", Box::new(ShowFolderAccessHelp))
+                        })
+                        .when(is_dir && !is_root, |menu| {
+                            menu.action("Focus on This Folder", Box::new(FocusOnFolder))
+                        })
+                        .when(is_folder_focused, |menu| {
+                            menu.action("Exit Folder Focus", Box::new(ExitFolderFocus))
+                        })
                     },
                     |menu| {
                         menu.action("New File", Box::new(NewFile))
@@ -479,9 +1205,39 @@ impl ProjectPanel {
                             .separator()
                             .action("Reveal in Finder", Box::new(RevealInFinder))
                             .action("Open in Terminal", Box::new(OpenInTerminal))
+                            .when(!is_dir, |menu| {
+                                menu.action("Open to the Side", Box::new(OpenToTheSideKeepFocus))
+                            })
+                            .when(is_open, |menu| {
+                                menu.action(
+                                    "Close Editor for This File",
+                                    Box::new(CloseEditorsForFile),
+                                )
+                            })
                             .when(is_dir, |menu| {
                                 menu.separator()
                                     .action("Find in Folder…", Box::new(NewSearchInDirectory))
+                                    .action(
+                                        "Find File in This Folder…",
+                                        Box::new(FindFileInDirectory),
+                                    )
+                                    .when(is_local && !is_inaccessible, |menu| {
+                                        menu.action(
+                                            "Folder Statistics",
+                                            Box::new(ShowFolderStatistics),
+                                        )
+                                    })
+                            })
+                            .when(is_inaccessible, |menu| {
+                                menu.separator()
+                                    .action("Retry Reading Folder", Box::new(RetryReadingFolder))
+                                    .action("How to Grant Access…", Box::new(ShowFolderAccessHelp))
+                            })
+                            .when(is_dir && !is_root, |menu| {
+                                menu.action("Focus on This Folder", Box::new(FocusOnFolder))
+                            })
+                            .when(is_folder_focused, |menu| {
+                                menu.action("Exit Folder Focus", Box::new(ExitFolderFocus))
                             })
                             .when(is_unfoldable, |menu| {
                                 menu.action("Unfold Directory", Box::new(UnfoldDirectory))
@@ -489,37 +1245,76 @@ impl ProjectPanel {
                             .when(is_foldable, |menu| {
                                 menu.action("Fold Directory", Box::new(FoldDirectory))
                             })
+                            .when(is_local && is_dir && !is_root, |menu| {
+                                menu.action(
+                                    "Stop Watching This Folder",
+                                    Box::new(StopWatchingFolder),
+                                )
+                            })
+                            .map(|menu| {
+                                unwatched_folders.into_iter().fold(menu, |menu, glob| {
+                                    let name = Path::new(&glob).file_name().map_or_else(
+                                        || glob.clone(),
+                                        |name| name.to_string_lossy().into_owned(),
+                                    );
+                                    menu.entry(
+                                        format!("Watch “{name}” Again"),
+                                        None,
+                                        cx.handler_for(&this, move |this, cx| {
+                                            this.watch_folder_again(worktree_id, glob.clone(), cx)
+                                        }),
+                                    )
+                                })
+                            })
                             .separator()
                             .action("Cut", Box::new(Cut))
                             .action("Copy", Box::new(Copy))
                             .action("Duplicate", Box::new(Duplicate))
-                            // TODO: Paste should always be visible, cbut disabled when clipboard is empty
-                            .when_some(self.clipboard.as_ref(), |menu, entry| {
-                                let entries_for_worktree_id = (SelectedEntry {
-                                    worktree_id,
-                                    entry_id: ProjectEntryId::MIN,
-                                })
-                                    ..(SelectedEntry {
-                                        worktree_id,
-                                        entry_id: ProjectEntryId::MAX,
-                                    });
-                                menu.when(
-                                    entry
-                                        .items()
-                                        .range(entries_for_worktree_id)
-                                        .next()
-                                        .is_some(),
-                                    |menu| menu.action("Paste", Box::new(Paste)),
-                                )
+                            .map(|menu| match paste_unavailable_reason {
+                                Some(reason) => {
+                                    menu.disabled_action("Paste", Box::new(Paste), reason)
+                                }
+                                None => menu.action("Paste", Box::new(Paste)),
+                            })
+                            .when(is_local, |menu| {
+                                match paste_with_source_comment_unavailable_reason {
+                                    Some(reason) => menu.disabled_action(
+                                        "Paste with Source Comment",
+                                        Box::new(PasteWithSourceComment),
+                                        reason,
+                                    ),
+                                    None => menu.action(
+                                        "Paste with Source Comment",
+                                        Box::new(PasteWithSourceComment),
+                                    ),
+                                }
                             })
                             .separator()
                             .action("Copy Path", Box::new(CopyPath))
                             .action("Copy Relative Path", Box::new(CopyRelativePath))
+                            .action("Copy as Markdown Link", Box::new(CopyAsMarkdownLink))
+                            .when(is_local && !is_dir, |menu| {
+                                menu.action("Copy File Contents", Box::new(CopyFileContents))
+                            })
+                            .when(has_code_owners, |menu| {
+                                menu.action("Copy Owners", Box::new(CopyOwners))
+                            })
+                            .action("Add to Assistant Context", Box::new(AddToAssistantContext))
+                            .action("Edit Note…", Box::new(EditNote))
+                            .action("Edit Tags…", Box::new(EditTags))
                             .separator()
                             .action("Rename", Box::new(Rename))
                             .when(!is_root, |menu| {
-                                menu.action("Trash", Box::new(Trash { skip_prompt: false }))
-                                    .action("Delete", Box::new(Delete { skip_prompt: false }))
+                                menu.when(enable_trash, |menu| {
+                                    menu.action(
+                                        "Move to Trash",
+                                        Box::new(Trash { skip_prompt: false }),
+                                    )
+                                })
+                                .action(
+                                    "Delete Permanently",
+                                    Box::new(Delete { skip_prompt: false }),
+                                )
                             })
                             .when(is_local & is_root, |menu| {
                                 menu.separator()
@@ -539,9 +1334,147 @@ impl ProjectPanel {
                                         }),
                                     )
                             })
-                            .when(is_local & is_root, |menu| {
+                            .when_some(insert_license_header_label, |menu, label| {
+                                menu.separator()
+                                    .action(label, Box::new(InsertLicenseHeader))
+                            })
+                            .when(
+                                decrypt_command.is_some() || encrypt_command.is_some(),
+                                |menu| menu.separator(),
+                            )
+                            .when_some(decrypt_command, |menu, command| {
+                                menu.entry(
+                                    "Decrypt and Open",
+                                    None,
+                                    cx.handler_for(&this, move |this, cx| {
+                                        this.decrypt_and_open(&command, entry_id, cx)
+                                    }),
+                                )
+                            })
+                            .when_some(encrypt_command, |menu, command| {
+                                menu.entry(
+                                    "Re-encrypt",
+                                    None,
+                                    cx.handler_for(&this, move |this, cx| {
+                                        this.reencrypt(&command, entry_id, cx)
+                                    }),
+                                )
+                            })
+                            .when_some(deployment, |menu, deployment| {
+                                menu.separator()
+                                    .header("Deployment")
+                                    .when_some(
+                                        deployment.upload_command.clone(),
+                                        |menu, command| {
+                                            menu.entry(
+                                                "Upload",
+                                                None,
+                                                cx.handler_for(&this, move |this, cx| {
+                                                    this.run_deployment_command(
+                                                        "Upload", &command, entry_id, cx,
+                                                    )
+                                                }),
+                                            )
+                                        },
+                                    )
+                                    .when_some(deployment.download_command, |menu, command| {
+                                        menu.entry(
+                                            "Download",
+                                            None,
+                                            cx.handler_for(&this, move |this, cx| {
+                                                this.run_deployment_command(
+                                                    "Download", &command, entry_id, cx,
+                                                )
+                                            }),
+                                        )
+                                    })
+                                    .entry(
+                                        "Refresh Deployment Status",
+                                        None,
+                                        cx.handler_for(&this, |this, cx| {
+                                            this.recheck_deployment_status(cx)
+                                        }),
+                                    )
+                            })
+                            .when_some(nested_repo_abs_path, |menu, abs_path| {
+                                menu.separator().entry(
+                                    "Open Nested Repo as Project",
+                                    None,
+                                    cx.handler_for(&this, move |this, cx| {
+                                        this.open_folder_as_project(abs_path.clone(), cx)
+                                    }),
+                                )
+                            })
+                            .when(!linked_git_worktrees.is_empty(), |mut menu| {
+                                menu = menu.separator().header("Git Worktrees");
+                                for abs_path in linked_git_worktrees {
+                                    let name = abs_path
+                                        .file_name()
+                                        .map(|name| name.to_string_lossy().to_string())
+                                        .unwrap_or_else(|| abs_path.to_string_lossy().to_string());
+                                    menu = menu
+                                        .entry(
+                                            format!("Open {name} as Project"),
+                                            None,
+                                            cx.handler_for(&this, {
+                                                let abs_path = abs_path.clone();
+                                                move |this, cx| {
+                                                    this.open_folder_as_project(
+                                                        abs_path.clone(),
+                                                        cx,
+                                                    )
+                                                }
+                                            }),
+                                        )
+                                        .entry(
+                                            format!("Add {name} to This Project"),
+                                            None,
+                                            cx.handler_for(&this, move |this, cx| {
+                                                this.add_folder_to_project(abs_path.clone(), cx)
+                                            }),
+                                        );
+                                }
+                                menu
+                            })
+                            .when(is_root, |menu| {
+                                menu.separator()
+                                    .action("Collapse This Worktree", Box::new(CollapseWorktree))
+                                    .action(
+                                        "Expand This Worktree One Level",
+                                        Box::new(ExpandWorktreeOneLevel),
+                                    )
+                                    .action(
+                                        "Expand Frequently Used",
+                                        Box::new(ExpandFrequentlyUsed),
+                                    )
+                            })
+                            .when(is_local && is_root, |menu| {
                                 menu.separator()
-                                    .action("Collapse All", Box::new(CollapseAllEntries))
+                                    .action(
+                                        "Export Panel Configuration",
+                                        Box::new(ExportPanelConfiguration),
+                                    )
+                                    .when(has_panel_configuration, |menu| {
+                                        menu.action(
+                                            "Import Panel Configuration",
+                                            Box::new(ImportPanelConfiguration),
+                                        )
+                                    })
+                            })
+                            .when(is_local & is_root, |menu| {
+                                menu.action("Collapse All", Box::new(CollapseAllEntries))
+                                    .action("Expand All", Box::new(ExpandAllEntries))
+                                    .action("Refresh", Box::new(Refresh))
+                            })
+                            .when(is_local && is_root && is_shared, |menu| {
+                                menu.separator().action(
+                                    if is_sharing_tree_view {
+                                        "Stop Sharing My Tree View"
+                                    } else {
+                                        "Share My Tree View"
+                                    },
+                                    Box::new(ToggleShareTreeView),
+                                )
                             })
                     },
                 )
@@ -558,1128 +1491,2003 @@ impl ProjectPanel {
         cx.notify();
     }
 
-    fn is_unfoldable(&self, entry: &Entry, worktree: &Worktree) -> bool {
-        if !entry.is_dir() || self.unfolded_dir_ids.contains(&entry.id) {
-            return false;
-        }
+    fn increase_panel_width(&mut self, _: &IncreasePanelWidth, cx: &mut ViewContext<Self>) {
+        self.resize_by(PANEL_WIDTH_STEP, cx);
+    }
 
-        if let Some(parent_path) = entry.path.parent() {
-            let snapshot = worktree.snapshot();
-            let mut child_entries = snapshot.child_entries(&parent_path);
-            if let Some(child) = child_entries.next() {
-                if child_entries.next().is_none() {
-                    return child.kind.is_dir();
-                }
-            }
-        };
-        false
+    fn decrease_panel_width(&mut self, _: &DecreasePanelWidth, cx: &mut ViewContext<Self>) {
+        self.resize_by(-PANEL_WIDTH_STEP, cx);
     }
 
-    fn is_foldable(&self, entry: &Entry, worktree: &Worktree) -> bool {
-        if entry.is_dir() {
-            let snapshot = worktree.snapshot();
+    fn reset_panel_width(&mut self, _: &ResetPanelWidth, cx: &mut ViewContext<Self>) {
+        self.set_size(None, cx);
+    }
 
-            let mut child_entries = snapshot.child_entries(&entry.path);
-            if let Some(child) = child_entries.next() {
-                if child_entries.next().is_none() {
-                    return child.kind.is_dir();
-                }
-            }
-        }
-        false
+    fn resize_by(&mut self, delta: Pixels, cx: &mut ViewContext<Self>) {
+        let width = (self.size(cx) + delta).max(MIN_PANEL_WIDTH).round();
+        self.set_size(Some(width), cx);
     }
 
-    fn expand_selected_entry(&mut self, _: &ExpandSelectedEntry, cx: &mut ViewContext<Self>) {
-        if let Some((worktree, entry)) = self.selected_entry(cx) {
-            if entry.is_dir() {
-                let worktree_id = worktree.id();
-                let entry_id = entry.id;
-                let expanded_dir_ids =
-                    if let Some(expanded_dir_ids) = self.expanded_dir_ids.get_mut(&worktree_id) {
-                        expanded_dir_ids
-                    } else {
-                        return;
-                    };
-
-                match expanded_dir_ids.binary_search(&entry_id) {
-                    Ok(_) => self.select_next(&SelectNext, cx),
-                    Err(ix) => {
-                        self.project.update(cx, |project, cx| {
-                            project.expand_entry(worktree_id, entry_id, cx);
-                        });
-
-                        expanded_dir_ids.insert(ix, entry_id);
-                        self.update_visible_entries(None, cx);
-                        cx.notify();
-                    }
-                }
-            }
+    /// Watches the buffers opened before the panel for unsaved changes, the ones opened
+    /// afterwards are watched as the project opens them.
+    fn track_open_buffers(&mut self, cx: &mut ViewContext<Self>) {
+        for buffer in self.project.read(cx).opened_buffers() {
+            self.track_buffer(&buffer, cx);
         }
+        self.refresh_dirty_entries(cx);
     }
 
-    fn collapse_selected_entry(&mut self, _: &CollapseSelectedEntry, cx: &mut ViewContext<Self>) {
-        if let Some((worktree, mut entry)) = self.selected_entry(cx) {
-            let worktree_id = worktree.id();
-            let expanded_dir_ids =
-                if let Some(expanded_dir_ids) = self.expanded_dir_ids.get_mut(&worktree_id) {
-                    expanded_dir_ids
-                } else {
-                    return;
-                };
+    fn track_buffer(&mut self, buffer: &Model<Buffer>, cx: &mut ViewContext<Self>) {
+        if self.buffer_subscriptions.contains_key(&buffer.entity_id()) {
+            return;
+        }
+        let subscription = cx.subscribe(buffer, |this, _, event, cx| match event {
+            BufferEvent::DirtyChanged
+            | BufferEvent::Saved
+            | BufferEvent::FileHandleChanged
+            | BufferEvent::Closed => this.refresh_dirty_entries(cx),
+            _ => {}
+        });
+        self.buffer_subscriptions
+            .insert(buffer.entity_id(), subscription);
+    }
 
-            loop {
-                let entry_id = entry.id;
-                match expanded_dir_ids.binary_search(&entry_id) {
-                    Ok(ix) => {
-                        expanded_dir_ids.remove(ix);
-                        self.update_visible_entries(Some((worktree_id, entry_id)), cx);
-                        cx.notify();
-                        break;
-                    }
-                    Err(_) => {
-                        if let Some(parent_entry) =
-                            entry.path.parent().and_then(|p| worktree.entry_for_path(p))
-                        {
-                            entry = parent_entry;
-                        } else {
-                            break;
-                        }
-                    }
+    fn refresh_dirty_entries(&mut self, cx: &mut ViewContext<Self>) {
+        let buffers = self.project.read(cx).opened_buffers();
+        self.buffer_subscriptions.retain(|buffer_id, _| {
+            buffers
+                .iter()
+                .any(|buffer| buffer.entity_id() == *buffer_id)
+        });
+        let dirty_entries = buffers
+            .iter()
+            .filter_map(|buffer| {
+                let buffer = buffer.read(cx);
+                if !buffer.is_dirty() {
+                    return None;
                 }
-            }
+                project::File::from_dyn(buffer.file())?.entry_id
+            })
+            .collect();
+        if self.dirty_entries != dirty_entries {
+            self.dirty_entries = dirty_entries;
+            cx.notify();
         }
     }
 
-    pub fn collapse_all_entries(&mut self, _: &CollapseAllEntries, cx: &mut ViewContext<Self>) {
-        // By keeping entries for fully collapsed worktrees, we avoid expanding them within update_visible_entries
-        // (which is it's default behaviour when there's no entry for a worktree in expanded_dir_ids).
-        self.expanded_dir_ids
-            .retain(|_, expanded_entries| expanded_entries.is_empty());
-        self.update_visible_entries(None, cx);
-        cx.notify();
+    /// Refreshes the diagnostic summaries shortly, along with the other diagnostics updated
+    /// in the meantime.
+    fn schedule_diagnostic_summaries_refresh(&mut self, cx: &mut ViewContext<Self>) {
+        if self.diagnostic_summaries_task.is_some() {
+            return;
+        }
+        self.diagnostic_summaries_task = Some(cx.spawn(|project_panel, mut cx| async move {
+            cx.background_executor()
+                .timer(DIAGNOSTIC_SUMMARIES_THROTTLE)
+                .await;
+            project_panel
+                .update(&mut cx, |project_panel, cx| {
+                    project_panel.diagnostic_summaries_task = None;
+                    project_panel.refresh_diagnostic_summaries(cx);
+                })
+                .ok();
+        }));
     }
 
-    fn toggle_expanded(&mut self, entry_id: ProjectEntryId, cx: &mut ViewContext<Self>) {
-        if let Some(worktree_id) = self.project.read(cx).worktree_id_for_entry(entry_id, cx) {
-            if let Some(expanded_dir_ids) = self.expanded_dir_ids.get_mut(&worktree_id) {
-                self.project.update(cx, |project, cx| {
-                    match expanded_dir_ids.binary_search(&entry_id) {
-                        Ok(ix) => {
-                            expanded_dir_ids.remove(ix);
-                        }
-                        Err(ix) => {
-                            project.expand_entry(worktree_id, entry_id, cx);
-                            expanded_dir_ids.insert(ix, entry_id);
-                        }
-                    }
-                });
-                self.update_visible_entries(Some((worktree_id, entry_id)), cx);
-                cx.focus(&self.focus_handle);
-                cx.notify();
+    fn refresh_diagnostic_summaries(&mut self, cx: &mut ViewContext<Self>) {
+        let show_diagnostics = ProjectPanelSettings::get_global(cx).show_diagnostics;
+        let mut diagnostic_summaries =
+            HashMap::<WorktreeId, HashMap<Arc<Path>, DiagnosticSummary>>::default();
+        if show_diagnostics != ShowDiagnostics::Off {
+            for (project_path, _, summary) in self.project.read(cx).diagnostic_summaries(false, cx)
+            {
+                let warning_count = match show_diagnostics {
+                    ShowDiagnostics::All => summary.warning_count,
+                    ShowDiagnostics::Errors | ShowDiagnostics::Off => 0,
+                };
+                if summary.error_count == 0 && warning_count == 0 {
+                    continue;
+                }
+                let worktree_summaries = diagnostic_summaries
+                    .entry(project_path.worktree_id)
+                    .or_default();
+                for path in project_path.path.ancestors() {
+                    let path_summary = worktree_summaries.entry(Arc::from(path)).or_default();
+                    path_summary.error_count += summary.error_count;
+                    path_summary.warning_count += warning_count;
+                }
             }
         }
+        self.diagnostic_summaries = diagnostic_summaries;
+        cx.notify();
     }
 
-    fn select_prev(&mut self, _: &SelectPrev, cx: &mut ViewContext<Self>) {
-        if let Some(selection) = self.selection {
-            let (mut worktree_ix, mut entry_ix, _) =
-                self.index_for_selection(selection).unwrap_or_default();
-            if entry_ix > 0 {
-                entry_ix -= 1;
-            } else if worktree_ix > 0 {
-                worktree_ix -= 1;
-                entry_ix = self.visible_entries[worktree_ix].1.len() - 1;
-            } else {
-                return;
+    /// Lists, in the background, the tracked files of the local worktrees that are deleted from
+    /// the disk but not from their repository's index.
+    fn refresh_deleted_files(&mut self, cx: &mut ViewContext<Self>) {
+        let settings = ProjectPanelSettings::get_global(cx);
+        if !settings.git_status || !settings.show_deleted_files {
+            self.deleted_files_task = None;
+            if !self.deleted_files.is_empty() {
+                self.deleted_files.clear();
+                self.update_visible_entries(None, cx);
             }
+            return;
+        }
+        let project = self.project.read(cx);
+        if !project.is_local() || self.is_decoration_turned_off(Decoration::DeletedFiles) {
+            return;
+        }
 
-            let (worktree_id, worktree_entries, _) = &self.visible_entries[worktree_ix];
-            let selection = SelectedEntry {
-                worktree_id: *worktree_id,
-                entry_id: worktree_entries[entry_ix].id,
+        let mut repositories = Vec::new();
+        for worktree in project.visible_worktrees(cx) {
+            let Some(worktree) = worktree.read(cx).as_local() else {
+                continue;
             };
-            self.selection = Some(selection);
-            if cx.modifiers().shift {
-                self.marked_entries.insert(selection);
+            for (work_directory, repo_entry) in worktree.repositories() {
+                let Some(local_repo) = worktree.get_local_repo(repo_entry) else {
+                    continue;
+                };
+                // Where the work directory is within the repository, when the repository
+                // contains the worktree rather than the other way around.
+                let Some(location_in_repo) =
+                    repo_entry.relativize(worktree, work_directory).log_err()
+                else {
+                    continue;
+                };
+                repositories.push((
+                    worktree.id(),
+                    work_directory.clone(),
+                    location_in_repo,
+                    local_repo.repo().clone(),
+                ));
             }
-            self.autoscroll(cx);
-            cx.notify();
-        } else {
-            self.select_first(&SelectFirst {}, cx);
         }
+        self.deleted_files_task = Some(self.spawn_decoration_task(
+            Decoration::DeletedFiles,
+            None,
+            async move {
+                let mut deleted_files = HashMap::<WorktreeId, Vec<Arc<Path>>>::default();
+                for (worktree_id, work_directory, location_in_repo, repo) in repositories {
+                    let paths = deleted_files.entry(worktree_id).or_default();
+                    for repo_path in repo.deleted_paths()? {
+                        if let Ok(path) = repo_path.strip_prefix(&*location_in_repo) {
+                            paths.push(work_directory.join(path).into());
+                        }
+                    }
+                }
+                deleted_files.retain(|_, paths| !paths.is_empty());
+                Ok(deleted_files)
+            },
+            |project_panel, deleted_files, cx| {
+                if project_panel.deleted_files != deleted_files {
+                    project_panel.deleted_files = deleted_files;
+                    project_panel.update_visible_entries(None, cx);
+                }
+            },
+            cx,
+        ));
     }
 
-    fn confirm(&mut self, _: &Confirm, cx: &mut ViewContext<Self>) {
-        if let Some(task) = self.confirm_edit(cx) {
-            task.detach_and_notify_err(cx);
-        }
+    /// The worktree and path of the deleted file listed with the given placeholder id.
+    fn ghost_entry(&self, entry_id: ProjectEntryId) -> Option<(WorktreeId, Arc<Path>)> {
+        self.ghost_entry_paths
+            .get(&entry_id)
+            .cloned()
+            .filter(|(worktree_id, path)| {
+                self.deleted_files
+                    .get(worktree_id)
+                    .map_or(false, |paths| paths.contains(path))
+            })
     }
 
-    fn open(&mut self, _: &Open, cx: &mut ViewContext<Self>) {
-        self.open_internal(false, true, false, cx);
+    fn restore_deleted_file(&mut self, _: &RestoreDeletedFile, cx: &mut ViewContext<Self>) {
+        self.update_deleted_files(
+            "Failed to restore files",
+            |repo, paths| repo.restore_paths(paths),
+            cx,
+        );
     }
 
-    fn open_permanent(&mut self, _: &OpenPermanent, cx: &mut ViewContext<Self>) {
-        self.open_internal(true, false, true, cx);
+    fn stage_deletion(&mut self, _: &StageDeletion, cx: &mut ViewContext<Self>) {
+        self.update_deleted_files(
+            "Failed to stage deletions",
+            |repo, paths| repo.stage_deletions(paths),
+            cx,
+        );
     }
 
-    fn open_internal(
+    /// Runs a git operation on the selected deleted files, once per repository.
+    fn update_deleted_files(
         &mut self,
-        mark_selected: bool,
-        allow_preview: bool,
-        focus_opened_item: bool,
+        error_message: &'static str,
+        operation: impl Fn(&dyn GitRepository, &[RepoPath]) -> Result<()> + Send + 'static,
         cx: &mut ViewContext<Self>,
     ) {
-        if let Some((_, entry)) = self.selected_entry(cx) {
-            if entry.is_file() {
-                self.open_entry(
-                    entry.id,
-                    mark_selected,
-                    focus_opened_item,
-                    allow_preview,
-                    cx,
-                );
-            } else {
-                self.toggle_expanded(entry.id, cx);
-            }
+        let ghost_entries = self
+            .marked_entries
+            .iter()
+            .chain(self.selection.as_ref())
+            .filter_map(|selection| self.ghost_entry(selection.entry_id))
+            .collect::<BTreeSet<_>>();
+        let project = self.project.read(cx);
+        let mut repositories =
+            HashMap::<ProjectEntryId, (Arc<dyn GitRepository>, Vec<RepoPath>)>::default();
+        for (worktree_id, path) in ghost_entries {
+            let Some(worktree) = project.worktree_for_id(worktree_id, cx) else {
+                continue;
+            };
+            let Some(worktree) = worktree.read(cx).as_local() else {
+                continue;
+            };
+            let Some((repo_entry, local_repo)) = worktree.repo_for_path(&path) else {
+                continue;
+            };
+            let Some(repo_path) = repo_entry.relativize(worktree, &path).log_err() else {
+                continue;
+            };
+            repositories
+                .entry(repo_entry.work_directory_id())
+                .or_insert_with(|| (local_repo.repo().clone(), Vec::new()))
+                .1
+                .push(repo_path);
+        }
+        if repositories.is_empty() {
+            return;
         }
-    }
-
-    fn confirm_edit(&mut self, cx: &mut ViewContext<Self>) -> Option<Task<Result<()>>> {
-        let edit_state = self.edit_state.as_mut()?;
-        cx.focus(&self.focus_handle);
-
-        let worktree_id = edit_state.worktree_id;
-        let is_new_entry = edit_state.is_new_entry;
-        let filename = self.filename_editor.read(cx).text(cx);
-        edit_state.is_dir = edit_state.is_dir
-            || (edit_state.is_new_entry && filename.ends_with(std::path::MAIN_SEPARATOR));
-        let is_dir = edit_state.is_dir;
-        let worktree = self.project.read(cx).worktree_for_id(worktree_id, cx)?;
-        let entry = worktree.read(cx).entry_for_id(edit_state.entry_id)?.clone();
 
-        let path_already_exists = |path| worktree.read(cx).entry_for_path(path).is_some();
-        let edit_task;
-        let edited_entry_id;
-        if is_new_entry {
-            self.selection = Some(SelectedEntry {
-                worktree_id,
-                entry_id: NEW_ENTRY_ID,
-            });
-            let new_path = entry.path.join(&filename.trim_start_matches('/'));
-            if path_already_exists(new_path.as_path()) {
-                return None;
+        let task = cx.background_executor().spawn(async move {
+            for (repo, paths) in repositories.into_values() {
+                operation(repo.as_ref(), &paths)?;
             }
+            anyhow::Ok(())
+        });
+        cx.spawn(|project_panel, mut cx| async move {
+            let result = task.await;
+            project_panel.update(&mut cx, |project_panel, cx| {
+                project_panel.refresh_deleted_files(cx);
+            })?;
+            result
+        })
+        .detach_and_prompt_err(error_message, cx, |_, _| None);
+    }
 
-            edited_entry_id = NEW_ENTRY_ID;
-            edit_task = self.project.update(cx, |project, cx| {
-                project.create_entry((worktree_id, &new_path), is_dir, cx)
-            });
-        } else {
-            let new_path = if let Some(parent) = entry.path.clone().parent() {
-                parent.join(&filename)
-            } else {
-                filename.clone().into()
+    /// Reads, in the background, the sizes of the visible files that are unknown or outdated,
+    /// re-sorting the entries once they are known. Only done when sorting by size.
+    fn refresh_file_sizes(&mut self, cx: &mut ViewContext<Self>) {
+        if ProjectPanelSettings::get_global(cx).sort_by != EntrySortMode::Size {
+            self.file_sizes.clear();
+            self.file_size_task = None;
+            return;
+        }
+        let project = self.project.read(cx);
+        if !project.is_local() || self.is_decoration_turned_off(Decoration::FileSizes) {
+            return;
+        }
+
+        let mut unsized_files = Vec::new();
+        for (worktree_id, entries, _) in &self.visible_entries {
+            let Some(worktree) = project.worktree_for_id(*worktree_id, cx) else {
+                continue;
             };
-            if path_already_exists(new_path.as_path()) {
-                return None;
+            let worktree_abs_path = worktree.read(cx).abs_path();
+            for entry in entries {
+                if entry.is_file()
+                    && self
+                        .file_sizes
+                        .get(&entry.id)
+                        .map_or(true, |(mtime, _)| *mtime != entry.mtime)
+                {
+                    unsized_files.push((
+                        entry.id,
+                        entry.mtime,
+                        worktree_abs_path.join(&entry.path),
+                    ));
+                }
             }
+        }
+        if unsized_files.is_empty() {
+            return;
+        }
 
-            edited_entry_id = entry.id;
-            edit_task = self.project.update(cx, |project, cx| {
-                project.rename_entry(entry.id, new_path.as_path(), cx)
-            });
+        let fs = self.fs.clone();
+        self.file_size_task = Some(self.spawn_decoration_task(
+            Decoration::FileSizes,
+            None,
+            async move {
+                let mut file_sizes = Vec::with_capacity(unsized_files.len());
+                for (entry_id, mtime, abs_path) in unsized_files {
+                    let len = fs
+                        .metadata(&abs_path)
+                        .await?
+                        .map_or(0, |metadata| metadata.len);
+                    file_sizes.push((entry_id, (mtime, len)));
+                }
+                Ok(file_sizes)
+            },
+            |project_panel, file_sizes, cx| {
+                project_panel.file_sizes.extend(file_sizes);
+                project_panel.update_visible_entries(None, cx);
+                cx.notify();
+            },
+            cx,
+        ));
+    }
+
+    /// Keeps scrolling the list while dragged entries hover near its top or bottom edge, so
+    /// that entries out of view can be dropped onto.
+    fn autoscroll_while_dragging(&mut self, position: Point<Pixels>, cx: &mut ViewContext<Self>) {
+        let bounds = self.scroll_handle.0.borrow().base_handle.bounds();
+        self.drag_autoscroll_step = if !bounds.contains(&position) {
+            px(0.)
+        } else if position.y < bounds.top() + DRAG_AUTOSCROLL_MARGIN {
+            -DRAG_AUTOSCROLL_STEP
+        } else if position.y > bounds.bottom() - DRAG_AUTOSCROLL_MARGIN {
+            DRAG_AUTOSCROLL_STEP
+        } else {
+            px(0.)
         };
+        if self.drag_autoscroll_step == px(0.) {
+            self.drag_autoscroll_task = None;
+            return;
+        }
+        if self.drag_autoscroll_task.is_some() {
+            return;
+        }
 
-        edit_state.processing_filename = Some(filename);
-        cx.notify();
+        self.drag_autoscroll_task = Some(cx.spawn(|project_panel, mut cx| async move {
+            loop {
+                cx.background_executor()
+                    .timer(DRAG_AUTOSCROLL_INTERVAL)
+                    .await;
+                let Ok(true) = project_panel.update(&mut cx, |project_panel, cx| {
+                    if !cx.has_active_drag() || project_panel.drag_autoscroll_step == px(0.) {
+                        project_panel.drag_autoscroll_task = None;
+                        return false;
+                    }
+                    let scroll_handle = &project_panel.scroll_handle.0.borrow().base_handle;
+                    let mut offset = scroll_handle.offset();
+                    offset.y = (offset.y - project_panel.drag_autoscroll_step).min(px(0.));
+                    scroll_handle.set_offset(offset);
+                    cx.notify();
+                    true
+                }) else {
+                    break;
+                };
+            }
+        }));
+    }
 
-        Some(cx.spawn(|project_panel, mut cx| async move {
-            let new_entry = edit_task.await;
-            project_panel.update(&mut cx, |project_panel, cx| {
-                project_panel.edit_state.take();
-                cx.notify();
-            })?;
+    /// Periodically replaces the local worktrees whose root disappeared with placeholders,
+    /// and adds them back once their root returns.
+    fn watch_worktree_roots(&mut self, cx: &mut ViewContext<Self>) -> Task<()> {
+        cx.spawn(|project_panel, mut cx| async move {
+            loop {
+                cx.background_executor().timer(ROOT_CHECK_INTERVAL).await;
+                let Ok(check) = project_panel.update(&mut cx, |project_panel, cx| {
+                    project_panel.check_worktree_roots(false, cx)
+                }) else {
+                    break;
+                };
+                check.await;
+            }
+        })
+    }
 
-            match new_entry {
-                Err(e) => {
-                    project_panel.update(&mut cx, |project_panel, cx| {
-                        project_panel.marked_entries.clear();
-                        project_panel.update_visible_entries(None, cx);
-                    }).ok();
-                    Err(e)?;
-                }
-                Ok(CreatedEntry::Included(new_entry)) => {
-                    project_panel.update(&mut cx, |project_panel, cx| {
-                        if let Some(selection) = &mut project_panel.selection {
-                            if selection.entry_id == edited_entry_id {
-                                selection.worktree_id = worktree_id;
-                                selection.entry_id = new_entry.id;
-                                project_panel.marked_entries.clear();
-                                project_panel.expand_to_selection(cx);
-                            }
-                        }
-                        project_panel.update_visible_entries(None, cx);
-                        if is_new_entry && !is_dir {
-                            project_panel.open_entry(new_entry.id, false, true, false, cx);
-                        }
-                        cx.notify();
-                    })?;
-                }
-                Ok(CreatedEntry::Excluded { abs_path }) => {
-                    if let Some(open_task) = project_panel
-                        .update(&mut cx, |project_panel, cx| {
-                            project_panel.marked_entries.clear();
-                            project_panel.update_visible_entries(None, cx);
+    /// Unmounts the worktrees whose root is gone and remounts the ones whose root is back.
+    /// Roots that cannot be checked, e.g. because of a lack of permissions, are left as they are.
+    fn check_worktree_roots(
+        &mut self,
+        report_unavailable: bool,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<()> {
+        let project = self.project.read(cx);
+        if !project.is_local() {
+            return Task::ready(());
+        }
 
-                            if is_dir {
-                                project_panel.project.update(cx, |_, cx| {
-                                    cx.emit(project::Event::Notification(format!(
-                                        "Created an excluded directory at {abs_path:?}.\nAlter `file_scan_exclusions` in the settings to show it in the panel"
-                                    )))
-                                });
-                                None
-                            } else {
-                                project_panel
-                                    .workspace
-                                    .update(cx, |workspace, cx| {
-                                        workspace.open_abs_path(abs_path, true, cx)
-                                    })
-                                    .ok()
-                            }
-                        })
-                        .ok()
-                        .flatten()
-                    {
-                        let _ = open_task.await?;
-                    }
+        let fs = self.fs.clone();
+        let worktrees = project
+            .visible_worktrees(cx)
+            .map(|worktree| {
+                let worktree = worktree.read(cx);
+                (worktree.id(), worktree.abs_path())
+            })
+            .collect::<Vec<_>>();
+        let unmounted_paths = self
+            .unmounted_worktrees
+            .iter()
+            .map(|unmounted| unmounted.abs_path.clone())
+            .collect::<Vec<_>>();
+        if worktrees.is_empty() && unmounted_paths.is_empty() {
+            return Task::ready(());
+        }
+        cx.spawn(|project_panel, mut cx| async move {
+            let mut missing_worktree_ids = Vec::new();
+            for (worktree_id, abs_path) in worktrees {
+                if matches!(fs.metadata(&abs_path).await, Ok(None)) {
+                    missing_worktree_ids.push(worktree_id);
                 }
             }
-            Ok(())
-        }))
+            let mut returned_paths = Vec::new();
+            for abs_path in unmounted_paths {
+                if matches!(fs.metadata(&abs_path).await, Ok(Some(_))) {
+                    returned_paths.push(abs_path);
+                }
+            }
+            project_panel
+                .update(&mut cx, |project_panel, cx| {
+                    for worktree_id in missing_worktree_ids {
+                        project_panel.unmount_worktree(worktree_id, cx);
+                    }
+                    for abs_path in &returned_paths {
+                        project_panel.remount_worktree(abs_path, cx);
+                    }
+                    if report_unavailable && returned_paths.is_empty() {
+                        let names = project_panel
+                            .unmounted_worktrees
+                            .iter()
+                            .map(|unmounted| format!("“{}”", unmounted.root_name))
+                            .collect::<Vec<_>>();
+                        if !names.is_empty() {
+                            let message = format!("{} still unavailable", names.join(", "));
+                            project_panel
+                                .project
+                                .update(cx, |_, cx| cx.emit(project::Event::Notification(message)));
+                        }
+                    }
+                })
+                .ok();
+        })
     }
 
-    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
-        self.edit_state = None;
-        self.update_visible_entries(None, cx);
-        self.marked_entries.clear();
-        cx.focus(&self.focus_handle);
+    fn unmount_worktree(&mut self, worktree_id: WorktreeId, cx: &mut ViewContext<Self>) {
+        let Some(worktree) = self.project.read(cx).worktree_for_id(worktree_id, cx) else {
+            return;
+        };
+        let worktree = worktree.read(cx);
+        let expanded_paths = self
+            .expanded_dir_ids
+            .get(&worktree_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry_id| worktree.entry_for_id(*entry_id))
+            .map(|entry| entry.path.clone())
+            .collect();
+        self.unmounted_worktrees.push(UnmountedWorktree {
+            abs_path: worktree.abs_path(),
+            root_name: worktree.root_name().to_string().into(),
+            expanded_paths,
+        });
+        self.project
+            .update(cx, |project, cx| project.remove_worktree(worktree_id, cx));
         cx.notify();
     }
 
-    fn open_entry(
-        &mut self,
-        entry_id: ProjectEntryId,
-        mark_selected: bool,
-        focus_opened_item: bool,
-        allow_preview: bool,
-        cx: &mut ViewContext<Self>,
-    ) {
-        cx.emit(Event::OpenedEntry {
-            entry_id,
-            focus_opened_item,
-            allow_preview,
-            mark_selected,
+    /// Adds the worktree of the placeholder back, expanding its directories again once scanned.
+    fn remount_worktree(&mut self, abs_path: &Path, cx: &mut ViewContext<Self>) {
+        let Some(ix) = self
+            .unmounted_worktrees
+            .iter()
+            .position(|unmounted| unmounted.abs_path.as_ref() == abs_path)
+        else {
+            return;
+        };
+        let unmounted = self.unmounted_worktrees.remove(ix);
+        let create_worktree = self.project.update(cx, |project, cx| {
+            project.find_or_create_local_worktree(&unmounted.abs_path, true, cx)
         });
+        cx.spawn(|project_panel, mut cx| async move {
+            let worktree = match create_worktree.await {
+                Ok((worktree, _)) => worktree,
+                Err(error) => {
+                    project_panel.update(&mut cx, |project_panel, cx| {
+                        project_panel.unmounted_worktrees.push(unmounted);
+                        cx.notify();
+                    })?;
+                    return Err(error);
+                }
+            };
+            let scan_complete = worktree.read_with(&cx, |worktree, _| {
+                worktree.as_local().map(|worktree| worktree.scan_complete())
+            })?;
+            if let Some(scan_complete) = scan_complete {
+                scan_complete.await;
+            }
+            project_panel.update(&mut cx, |project_panel, cx| {
+                let worktree = worktree.read(cx);
+                let worktree_id = worktree.id();
+                let mut expanded_dir_ids = unmounted
+                    .expanded_paths
+                    .iter()
+                    .filter_map(|path| worktree.entry_for_path(path))
+                    .filter(|entry| entry.is_dir())
+                    .map(|entry| entry.id)
+                    .collect::<Vec<_>>();
+                expanded_dir_ids.sort_unstable();
+                project_panel.project.update(cx, |project, cx| {
+                    for entry_id in &expanded_dir_ids {
+                        project.expand_entry(worktree_id, *entry_id, cx);
+                    }
+                });
+                project_panel
+                    .expanded_dir_ids
+                    .insert(worktree_id, expanded_dir_ids);
+                project_panel.update_visible_entries(None, cx);
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+        cx.notify();
     }
 
-    fn split_entry(&mut self, entry_id: ProjectEntryId, cx: &mut ViewContext<Self>) {
-        cx.emit(Event::SplitEntry { entry_id });
-    }
-
-    fn new_file(&mut self, _: &NewFile, cx: &mut ViewContext<Self>) {
-        self.add_entry(false, cx)
+    fn reconnect_worktrees(&mut self, _: &ReconnectWorktrees, cx: &mut ViewContext<Self>) {
+        self.check_worktree_roots(true, cx).detach();
     }
 
-    fn new_directory(&mut self, _: &NewDirectory, cx: &mut ViewContext<Self>) {
-        self.add_entry(true, cx)
+    /// Computes a row decoration in the background and applies it. A decoration that fails or
+    /// takes longer than `DECORATION_TIMEOUT` too many times in a row is turned off for the rest
+    /// of the session, and rows render without it instead of waiting for it.
+    fn spawn_decoration_task<T: Send + 'static>(
+        &mut self,
+        decoration: Decoration,
+        debounce: Option<Duration>,
+        compute: impl Future<Output = Result<T>> + Send + 'static,
+        apply: impl FnOnce(&mut Self, T, &mut ViewContext<Self>) + 'static,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<()> {
+        cx.spawn(|project_panel, mut cx| async move {
+            let executor = cx.background_executor().clone();
+            if let Some(debounce) = debounce {
+                executor.timer(debounce).await;
+            }
+            let computation = executor.spawn(compute);
+            let timeout = executor.timer(DECORATION_TIMEOUT);
+            let result = smol::future::or(async move { Some(computation.await) }, async move {
+                timeout.await;
+                None
+            })
+            .await
+            .unwrap_or_else(|| Err(anyhow!("timed out after {DECORATION_TIMEOUT:?}")));
+            project_panel
+                .update(&mut cx, |project_panel, cx| match result {
+                    Ok(value) => {
+                        project_panel.decoration_failures.remove(&decoration);
+                        apply(project_panel, value, cx);
+                        cx.notify();
+                    }
+                    Err(error) => project_panel.record_decoration_failure(decoration, error, cx),
+                })
+                .ok();
+        })
     }
 
-    fn add_entry(&mut self, is_dir: bool, cx: &mut ViewContext<Self>) {
-        if let Some(SelectedEntry {
-            worktree_id,
-            entry_id,
-        }) = self.selection
-        {
-            let directory_id;
-            if let Some((worktree, expanded_dir_ids)) = self
-                .project
-                .read(cx)
-                .worktree_for_id(worktree_id, cx)
-                .zip(self.expanded_dir_ids.get_mut(&worktree_id))
-            {
-                let worktree = worktree.read(cx);
-                if let Some(mut entry) = worktree.entry_for_id(entry_id) {
-                    loop {
-                        if entry.is_dir() {
-                            if let Err(ix) = expanded_dir_ids.binary_search(&entry.id) {
-                                expanded_dir_ids.insert(ix, entry.id);
-                            }
-                            directory_id = entry.id;
-                            break;
-                        } else {
-                            if let Some(parent_path) = entry.path.parent() {
-                                if let Some(parent_entry) = worktree.entry_for_path(parent_path) {
-                                    entry = parent_entry;
-                                    continue;
-                                }
-                            }
-                            return;
-                        }
-                    }
-                } else {
-                    return;
-                };
-            } else {
-                return;
-            };
-            self.marked_entries.clear();
-            self.edit_state = Some(EditState {
-                worktree_id,
-                entry_id: directory_id,
-                is_new_entry: true,
-                is_dir,
-                processing_filename: None,
-            });
-            self.filename_editor.update(cx, |editor, cx| {
-                editor.clear(cx);
-                editor.focus(cx);
-            });
-            self.update_visible_entries(Some((worktree_id, NEW_ENTRY_ID)), cx);
-            self.autoscroll(cx);
-            cx.notify();
+    fn record_decoration_failure(
+        &mut self,
+        decoration: Decoration,
+        error: anyhow::Error,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let row_decoration = match decoration {
+            Decoration::Row(name) => self.decorations.position(name),
+            Decoration::FileSizes | Decoration::DeletedFiles => None,
+        };
+        // Lets the next refresh of the rows start the job over.
+        if let Some(ix) = row_decoration {
+            self.decorations.slot_mut(ix).task = None;
+        }
+        let failures = self.decoration_failures.entry(decoration).or_default();
+        *failures += 1;
+        if *failures < MAX_DECORATION_FAILURES {
+            log::warn!(
+                "failed to compute {} decorations: {error:#}",
+                decoration.name()
+            );
+            return;
         }
-    }
 
-    fn rename(&mut self, _: &Rename, cx: &mut ViewContext<Self>) {
-        if let Some(SelectedEntry {
-            worktree_id,
-            entry_id,
-        }) = self.selection
-        {
-            if let Some(worktree) = self.project.read(cx).worktree_for_id(worktree_id, cx) {
-                if let Some(entry) = worktree.read(cx).entry_for_id(entry_id) {
-                    self.edit_state = Some(EditState {
-                        worktree_id,
-                        entry_id,
-                        is_new_entry: false,
-                        is_dir: entry.is_dir(),
-                        processing_filename: None,
-                    });
-                    let file_name = entry
-                        .path
-                        .file_name()
-                        .map(|s| s.to_string_lossy())
-                        .unwrap_or_default()
-                        .to_string();
-                    let file_stem = entry.path.file_stem().map(|s| s.to_string_lossy());
-                    let selection_end =
-                        file_stem.map_or(file_name.len(), |file_stem| file_stem.len());
-                    self.filename_editor.update(cx, |editor, cx| {
-                        editor.set_text(file_name, cx);
-                        editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
-                            s.select_ranges([0..selection_end])
-                        });
-                        editor.focus(cx);
-                    });
-                    self.update_visible_entries(None, cx);
-                    self.autoscroll(cx);
-                    cx.notify();
+        log::error!(
+            "turning off {} decorations after {failures} failures: {error:#}",
+            decoration.name()
+        );
+        match decoration {
+            Decoration::Row(_) => {
+                if let Some(ix) = row_decoration {
+                    self.decorations.slot_mut(ix).provider.clear();
                 }
             }
+            Decoration::FileSizes => {
+                self.file_sizes.clear();
+                self.file_size_task = None;
+            }
+            Decoration::DeletedFiles => {
+                self.deleted_files.clear();
+                self.deleted_files_task = None;
+                self.update_visible_entries(None, cx);
+            }
         }
+        cx.notify();
     }
 
-    fn trash(&mut self, action: &Trash, cx: &mut ViewContext<Self>) {
-        self.remove(true, action.skip_prompt, cx);
+    fn is_decoration_turned_off(&self, decoration: Decoration) -> bool {
+        self.decoration_failures
+            .get(&decoration)
+            .map_or(false, |failures| *failures >= MAX_DECORATION_FAILURES)
     }
 
-    fn delete(&mut self, action: &Delete, cx: &mut ViewContext<Self>) {
-        self.remove(false, action.skip_prompt, cx);
+    /// Remembers the rows being rendered, computing the row decorations missing around them
+    /// when they changed.
+    fn set_rendered_range(&mut self, range: Range<usize>, cx: &mut ViewContext<Self>) {
+        if self.rendered_range != range || self.row_decorations_stale {
+            self.rendered_range = range;
+            self.row_decorations_stale = false;
+            let rows = self.rendered_rows(cx);
+            for ix in 0..self.decorations.len() {
+                self.refresh_row_decoration(ix, &rows, cx);
+            }
+        }
     }
 
-    fn remove(&mut self, trash: bool, skip_prompt: bool, cx: &mut ViewContext<'_, ProjectPanel>) {
-        maybe!({
-            if self.marked_entries.is_empty() && self.selection.is_none() {
-                return None;
+    /// Returns the rendered rows and the ones within `DECORATION_PREFETCH_ROWS` of them, which
+    /// the row decorations are computed for.
+    fn rendered_rows(&self, cx: &AppContext) -> RenderedRows {
+        let mut rendered_rows = RenderedRows::default();
+        let rendered_range = self.rendered_range.clone();
+        if rendered_range.is_empty() {
+            return rendered_rows;
+        }
+        let prefetched_range = rendered_range
+            .start
+            .saturating_sub(DECORATION_PREFETCH_ROWS)
+            ..rendered_range.end + DECORATION_PREFETCH_ROWS;
+        let project = self.project.read(cx);
+        let mut ix = 0;
+        for (worktree_id, entries, _) in &self.visible_entries {
+            if ix >= prefetched_range.end {
+                break;
             }
-            let project = self.project.read(cx);
-            let items_to_delete = self.marked_entries();
-            let file_paths = items_to_delete
-                .into_iter()
-                .filter_map(|selection| {
-                    Some((
-                        selection.entry_id,
-                        project
-                            .path_for_entry(selection.entry_id, cx)?
-                            .path
-                            .file_name()?
-                            .to_string_lossy()
-                            .into_owned(),
-                    ))
-                })
-                .collect::<Vec<_>>();
-            if file_paths.is_empty() {
-                return None;
+            let worktree_start = ix;
+            ix += entries.len();
+            if ix <= prefetched_range.start {
+                continue;
             }
-            let answer = if !skip_prompt {
-                let operation = if trash { "Trash" } else { "Delete" };
-
-                let prompt =
-                    if let Some((_, path)) = file_paths.first().filter(|_| file_paths.len() == 1) {
-                        format!("{operation} {path}?")
-                    } else {
-                        const CUTOFF_POINT: usize = 10;
-                        let names = if file_paths.len() > CUTOFF_POINT {
-                            let truncated_path_counts = file_paths.len() - CUTOFF_POINT;
-                            let mut paths = file_paths
-                                .iter()
-                                .map(|(_, path)| path.clone())
-                                .take(CUTOFF_POINT)
-                                .collect::<Vec<_>>();
-                            paths.truncate(CUTOFF_POINT);
-                            if truncated_path_counts == 1 {
-                                paths.push(".. 1 file not shown".into());
-                            } else {
-                                paths.push(format!(".. {} files not shown", truncated_path_counts));
-                            }
-                            paths
-                        } else {
-                            file_paths.iter().map(|(_, path)| path.clone()).collect()
-                        };
-
-                        format!(
-                            "Do you want to {} the following {} files?\n{}",
-                            operation.to_lowercase(),
-                            file_paths.len(),
-                            names.join("\n")
-                        )
-                    };
-                Some(cx.prompt(PromptLevel::Info, &prompt, None, &[operation, "Cancel"]))
-            } else {
-                None
+            let Some(worktree) = project.worktree_for_id(*worktree_id, cx) else {
+                continue;
             };
-
-            cx.spawn(|this, mut cx| async move {
-                if let Some(answer) = answer {
-                    if answer.await != Ok(0) {
-                        return Result::<(), anyhow::Error>::Ok(());
-                    }
-                }
-                for (entry_id, _) in file_paths {
-                    this.update(&mut cx, |this, cx| {
-                        this.project
-                            .update(cx, |project, cx| project.delete_entry(entry_id, trash, cx))
-                            .ok_or_else(|| anyhow!("no such entry"))
-                    })??
-                    .await?;
+            rendered_rows.worktrees.push(worktree.read(cx).snapshot());
+            let entry_range = prefetched_range.start.saturating_sub(worktree_start)
+                ..(prefetched_range.end - worktree_start).min(entries.len());
+            for (row, entry) in (worktree_start + entry_range.start..).zip(&entries[entry_range]) {
+                if entry.id == NEW_ENTRY_ID || self.ghost_entry_paths.contains_key(&entry.id) {
+                    continue;
                 }
-                Result::<(), anyhow::Error>::Ok(())
-            })
-            .detach_and_log_err(cx);
-            Some(())
-        });
+                rendered_rows.rows.push(RenderedRow {
+                    worktree_id: *worktree_id,
+                    entry: entry.clone(),
+                    distance: rendered_range
+                        .start
+                        .saturating_sub(row)
+                        .max((row + 1).saturating_sub(rendered_range.end)),
+                });
+            }
+        }
+        rendered_rows
     }
 
-    fn unfold_directory(&mut self, _: &UnfoldDirectory, cx: &mut ViewContext<Self>) {
-        if let Some((worktree, entry)) = self.selected_entry(cx) {
-            self.unfolded_dir_ids.insert(entry.id);
-
-            let snapshot = worktree.snapshot();
-            let mut parent_path = entry.path.parent();
-            while let Some(path) = parent_path {
-                if let Some(parent_entry) = worktree.entry_for_path(path) {
-                    let mut children_iter = snapshot.child_entries(path);
+    /// Starts computing what the decoration misses for the rows, unless it is computing
+    /// something already, in which case the rows are looked at again once that is applied.
+    fn refresh_row_decoration(
+        &mut self,
+        ix: usize,
+        rows: &RenderedRows,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let decoration = Decoration::Row(self.decorations.slot(ix).provider.name());
+        if self.is_decoration_turned_off(decoration) {
+            return;
+        }
+        let project = self.project.read(cx);
+        let slot = self.decorations.slot_mut(ix);
+        if !slot.provider.is_enabled(project, cx) {
+            slot.provider.clear();
+            slot.task = None;
+            return;
+        }
+        if slot.task.is_some() {
+            return;
+        }
+        let Some(job) = slot.provider.compute(rows, project, &self.fs, cx) else {
+            return;
+        };
 
-                    if children_iter.by_ref().take(2).count() > 1 {
-                        break;
-                    }
+        let task = self.spawn_decoration_task(
+            decoration,
+            job.debounce,
+            job.computation,
+            move |project_panel, outcome, cx| {
+                let slot = project_panel.decorations.slot_mut(ix);
+                slot.task = None;
+                slot.provider.apply(outcome);
+                let rows = project_panel.rendered_rows(cx);
+                project_panel.refresh_row_decoration(ix, &rows, cx);
+            },
+            cx,
+        );
+        self.decorations.slot_mut(ix).task = Some(task);
+    }
 
-                    self.unfolded_dir_ids.insert(parent_entry.id);
-                    parent_path = path.parent();
-                } else {
-                    break;
-                }
+    /// Lets the row decorations forget what the changed entries invalidate, cancelling the
+    /// jobs outdated by them.
+    fn invalidate_row_decorations(&mut self, worktree_id: WorktreeId, paths: &[&Path]) {
+        for slot in self.decorations.slots_mut() {
+            if slot.provider.entries_changed(worktree_id, paths) {
+                slot.task = None;
             }
+        }
+        self.row_decorations_stale = true;
+    }
 
-            self.update_visible_entries(None, cx);
-            self.autoscroll(cx);
-            cx.notify();
+    fn row_decoration_settings_changed(&mut self, cx: &mut ViewContext<Self>) {
+        for slot in self.decorations.slots_mut() {
+            if slot.provider.settings_changed(cx) {
+                slot.task = None;
+            }
         }
+        self.row_decorations_stale = true;
     }
 
-    fn fold_directory(&mut self, _: &FoldDirectory, cx: &mut ViewContext<Self>) {
-        if let Some((worktree, entry)) = self.selected_entry(cx) {
-            self.unfolded_dir_ids.remove(&entry.id);
+    fn recheck_deployment_status(&mut self, cx: &mut ViewContext<Self>) {
+        self.decorations
+            .restart::<DeploymentStatusDecoration>(DeploymentStatusDecoration::recheck);
+        self.row_decorations_stale = true;
+        cx.notify();
+    }
 
-            let snapshot = worktree.snapshot();
-            let mut path = &*entry.path;
-            loop {
-                let mut child_entries_iter = snapshot.child_entries(path);
-                if let Some(child) = child_entries_iter.next() {
-                    if child_entries_iter.next().is_none() && child.is_dir() {
-                        self.unfolded_dir_ids.remove(&child.id);
-                        path = &*child.path;
-                    } else {
-                        break;
-                    }
-                } else {
-                    break;
+    fn code_owners_for_entry(&self, worktree_id: WorktreeId, path: &Path) -> Option<&[String]> {
+        self.decorations
+            .get::<CodeOwnerDecoration>()
+            .owners_for_path(worktree_id, path)
+    }
+
+    /// Adds the selected directory to the `file_scan_exclusions` of its worktree's project
+    /// settings, so it is no longer scanned or watched.
+    fn stop_watching_folder(&mut self, _: &StopWatchingFolder, cx: &mut ViewContext<Self>) {
+        let Some((worktree, entry)) = self.selected_entry(cx) else {
+            return;
+        };
+        if !entry.is_dir() || entry.path.as_ref() == Path::new("") {
+            return;
+        }
+        let worktree_id = worktree.id();
+        let glob = entry.path.to_string_lossy().into_owned();
+        // Project settings replace the user's exclusions rather than extending them.
+        let current_exclusions = worktree_settings(worktree_id, cx)
+            .file_scan_exclusions
+            .sources()
+            .to_vec();
+        self.update_local_settings_file::<WorktreeSettings>(
+            worktree_id,
+            move |settings| {
+                let exclusions = settings
+                    .file_scan_exclusions
+                    .get_or_insert(current_exclusions);
+                if !exclusions.contains(&glob) {
+                    exclusions.push(glob);
+                }
+            },
+            cx,
+        );
+    }
+
+    fn watch_folder_again(
+        &mut self,
+        worktree_id: WorktreeId,
+        glob: String,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.update_local_settings_file::<WorktreeSettings>(
+            worktree_id,
+            move |settings| {
+                if let Some(exclusions) = &mut settings.file_scan_exclusions {
+                    exclusions.retain(|exclusion| exclusion != &glob);
                 }
+            },
+            cx,
+        );
+    }
+
+    /// Applies `update` to the settings in the `.zed/settings.json` file of the worktree,
+    /// creating the file if needed.
+    fn update_local_settings_file<T: Settings>(
+        &self,
+        worktree_id: WorktreeId,
+        update: impl 'static + Send + FnOnce(&mut T::FileContent),
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(worktree) = self.project.read(cx).worktree_for_id(worktree_id, cx) else {
+            return;
+        };
+        let settings_path = worktree
+            .read(cx)
+            .abs_path()
+            .join(local_settings_file_relative_path());
+        let fs = self.fs.clone();
+        cx.spawn(|_, mut cx| async move {
+            let old_text = if fs.is_file(&settings_path).await {
+                fs.load(&settings_path).await?
+            } else {
+                String::new()
+            };
+            let old_text = if old_text.trim().is_empty() {
+                "{}\n".to_string()
+            } else {
+                old_text
+            };
+            let new_text = cx.update(|cx| {
+                cx.global::<SettingsStore>()
+                    .new_text_for_update::<T>(old_text, update)
+            })?;
+            if let Some(settings_dir) = settings_path.parent() {
+                fs.create_dir(settings_dir).await?;
             }
+            fs.atomic_write(settings_path, new_text).await
+        })
+        .detach_and_log_err(cx);
+    }
 
-            self.update_visible_entries(None, cx);
-            self.autoscroll(cx);
-            cx.notify();
+    /// Key under which the `name` state of the project is stored, derived from the paths of its
+    /// worktrees.
+    fn project_kvp_key(&self, name: &str, cx: &AppContext) -> Option<String> {
+        let mut worktree_paths = self
+            .project
+            .read(cx)
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.read(cx).abs_path().to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        if worktree_paths.is_empty() {
+            return None;
         }
+        worktree_paths.sort();
+        Some(format!(
+            "{PROJECT_PANEL_KEY}-{name}-{}",
+            worktree_paths.join(",")
+        ))
     }
 
-    fn select_next(&mut self, _: &SelectNext, cx: &mut ViewContext<Self>) {
-        if let Some(selection) = self.selection {
-            let (mut worktree_ix, mut entry_ix, _) =
-                self.index_for_selection(selection).unwrap_or_default();
-            if let Some((_, worktree_entries, _)) = self.visible_entries.get(worktree_ix) {
-                if entry_ix + 1 < worktree_entries.len() {
-                    entry_ix += 1;
-                } else {
-                    worktree_ix += 1;
-                    entry_ix = 0;
-                }
+    /// Counts the opening of a file towards the usage of its directory.
+    fn record_directory_usage(&mut self, entry_id: ProjectEntryId, cx: &mut ViewContext<Self>) {
+        let Some(key) = self.project_kvp_key("directory-usage", cx) else {
+            return;
+        };
+        let project = self.project.read(cx);
+        let Some(worktree) = project.worktree_for_entry(entry_id, cx) else {
+            return;
+        };
+        let worktree = worktree.read(cx);
+        let Some(dir_path) = worktree
+            .entry_for_id(entry_id)
+            .filter(|entry| entry.is_file())
+            .and_then(|entry| entry.path.parent())
+            .filter(|dir_path| !dir_path.as_os_str().is_empty())
+        else {
+            return;
+        };
+        let worktree_abs_path = worktree.abs_path().to_path_buf();
+        let dir_path = dir_path.to_path_buf();
+
+        // Chain the updates, so that none of them overwrites a concurrent one.
+        let previous_task = self.directory_usage_task.take();
+        self.directory_usage_task = Some(cx.background_executor().spawn(async move {
+            if let Some(previous_task) = previous_task {
+                previous_task.await;
             }
+            record_directory_usage(key, worktree_abs_path, dir_path)
+                .await
+                .log_err();
+        }));
+    }
 
-            if let Some((worktree_id, worktree_entries, _)) = self.visible_entries.get(worktree_ix)
+    fn expand_frequently_used(&mut self, _: &ExpandFrequentlyUsed, cx: &mut ViewContext<Self>) {
+        let Some(key) = self.project_kvp_key("directory-usage", cx) else {
+            return;
+        };
+        cx.spawn(|project_panel, mut cx| async move {
+            let usage = cx
+                .background_executor()
+                .spawn(async move { read_directory_usage(&key) })
+                .await?;
+            project_panel.update(&mut cx, |project_panel, cx| {
+                project_panel.expand_directories_by_usage(&usage, cx)
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Collapses everything but the directories files were opened from the most.
+    fn expand_directories_by_usage(&mut self, usage: &DirectoryUsage, cx: &mut ViewContext<Self>) {
+        let project = self.project.read(cx);
+        for worktree in project.visible_worktrees(cx) {
+            let worktree = worktree.read(cx);
+            let Some(root_entry) = worktree.root_entry() else {
+                continue;
+            };
+            let mut expanded_dir_ids = vec![root_entry.id];
+            for dir_path in usage.most_used(&worktree.abs_path(), FREQUENTLY_USED_DIRECTORIES_LIMIT)
             {
-                if let Some(entry) = worktree_entries.get(entry_ix) {
+                expanded_dir_ids.extend(
+                    dir_path
+                        .ancestors()
+                        .filter_map(|ancestor| worktree.entry_for_path(ancestor))
+                        .filter(|entry| entry.is_dir())
+                        .map(|entry| entry.id),
+                );
+            }
+            expanded_dir_ids.sort_unstable();
+            expanded_dir_ids.dedup();
+            self.expanded_dir_ids
+                .insert(worktree.id(), expanded_dir_ids);
+        }
+        self.update_visible_entries(None, cx);
+        cx.notify();
+    }
+
+    fn open_layout_snapshots(&mut self, _: &OpenLayoutSnapshots, cx: &mut ViewContext<Self>) {
+        let Some(key) = self.project_kvp_key("layout-snapshots", cx) else {
+            return;
+        };
+        let project_panel = cx.view().downgrade();
+        let workspace = self.workspace.clone();
+        cx.spawn(|_, mut cx| async move {
+            let snapshots = cx
+                .background_executor()
+                .spawn(async move { read_layout_snapshots(&key) })
+                .await
+                .log_err()
+                .unwrap_or_default();
+            workspace.update(&mut cx, |workspace, cx| {
+                workspace.toggle_modal(cx, |cx| LayoutSnapshots::new(project_panel, snapshots, cx))
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn jump_to_visible_entry(&mut self, _: &JumpToVisibleEntry, cx: &mut ViewContext<Self>) {
+        if let Some((picker, _)) = &self.visible_entry_picker {
+            picker.focus_handle(cx).focus(cx);
+            return;
+        }
+
+        let project = self.project.read(cx);
+        let mut entries = Vec::new();
+        for (worktree_id, worktree_entries, _) in &self.visible_entries {
+            let Some(worktree) = project.worktree_for_id(*worktree_id, cx) else {
+                continue;
+            };
+            let root_name = Path::new(worktree.read(cx).root_name());
+            for entry in worktree_entries {
+                if entry.id != NEW_ENTRY_ID {
                     let selection = SelectedEntry {
                         worktree_id: *worktree_id,
                         entry_id: entry.id,
                     };
-                    self.selection = Some(selection);
-                    if cx.modifiers().shift {
-                        self.marked_entries.insert(selection);
-                    }
-
-                    self.autoscroll(cx);
-                    cx.notify();
+                    let path = root_name.join(&entry.path);
+                    entries.push((selection, path.to_string_lossy().to_string()));
                 }
             }
-        } else {
-            self.select_first(&SelectFirst {}, cx);
         }
+
+        let delegate = VisibleEntriesDelegate::new(cx.view().downgrade(), entries);
+        let picker = cx.new_view(|cx| {
+            Picker::uniform_list(delegate, cx)
+                .modal(false)
+                .max_height(Some(rems(12.).into()))
+        });
+        let subscription = cx.subscribe(&picker, |this, _, _: &DismissEvent, cx| {
+            this.visible_entry_picker = None;
+            cx.focus(&this.focus_handle);
+            cx.notify();
+        });
+        picker.focus_handle(cx).focus(cx);
+        self.visible_entry_picker = Some((picker, subscription));
+        cx.notify();
     }
 
-    fn select_parent(&mut self, _: &SelectParent, cx: &mut ViewContext<Self>) {
-        if let Some((worktree, entry)) = self.selected_entry(cx) {
-            if let Some(parent) = entry.path.parent() {
-                if let Some(parent_entry) = worktree.entry_for_path(parent) {
-                    self.selection = Some(SelectedEntry {
-                        worktree_id: worktree.id(),
-                        entry_id: parent_entry.id,
-                    });
-                    self.autoscroll(cx);
-                    cx.notify();
-                }
-            }
-        } else {
-            self.select_first(&SelectFirst {}, cx);
+    /// Selects and scrolls to an entry picked by `JumpToVisibleEntry`, if it is still visible.
+    pub(crate) fn jump_to_entry(&mut self, selection: SelectedEntry, cx: &mut ViewContext<Self>) {
+        if self.index_for_selection(selection).is_some() {
+            self.selection = Some(selection);
+            self.marked_entries.clear();
+            self.autoscroll(cx);
+            cx.notify();
         }
     }
 
-    fn select_first(&mut self, _: &SelectFirst, cx: &mut ViewContext<Self>) {
-        let worktree = self
-            .visible_entries
-            .first()
-            .and_then(|(worktree_id, _, _)| {
-                self.project.read(cx).worktree_for_id(*worktree_id, cx)
-            });
-        if let Some(worktree) = worktree {
-            let worktree = worktree.read(cx);
-            let worktree_id = worktree.id();
-            if let Some(root_entry) = worktree.root_entry() {
-                let selection = SelectedEntry {
-                    worktree_id,
-                    entry_id: root_entry.id,
-                };
-                self.selection = Some(selection);
-                if cx.modifiers().shift {
-                    self.marked_entries.insert(selection);
-                }
-                self.autoscroll(cx);
-                cx.notify();
-            }
+    fn capture_layout_snapshot(&self, name: String, cx: &AppContext) -> LayoutSnapshot {
+        let project = self.project.read(cx);
+        let entry_location = |worktree_id, entry_id| {
+            let worktree = project.worktree_for_id(worktree_id, cx)?.read(cx);
+            let entry = worktree.entry_for_id(entry_id)?;
+            Some((worktree.abs_path().to_path_buf(), entry.path.to_path_buf()))
+        };
+        let expanded_dirs = project
+            .visible_worktrees(cx)
+            .filter_map(|worktree| {
+                let worktree = worktree.read(cx);
+                let expanded_dir_ids = self.expanded_dir_ids.get(&worktree.id())?;
+                let paths = expanded_dir_ids
+                    .iter()
+                    .filter_map(|entry_id| worktree.entry_for_id(*entry_id))
+                    .map(|entry| entry.path.to_path_buf())
+                    .collect();
+                Some((worktree.abs_path().to_path_buf(), paths))
+            })
+            .collect();
+        LayoutSnapshot {
+            name,
+            expanded_dirs,
+            selection: self
+                .selection
+                .and_then(|selection| entry_location(selection.worktree_id, selection.entry_id)),
+            focused_directory: self
+                .focused_directory
+                .and_then(|(worktree_id, entry_id)| entry_location(worktree_id, entry_id)),
         }
     }
 
-    fn select_last(&mut self, _: &SelectLast, cx: &mut ViewContext<Self>) {
-        let worktree = self.visible_entries.last().and_then(|(worktree_id, _, _)| {
-            self.project.read(cx).worktree_for_id(*worktree_id, cx)
-        });
-        if let Some(worktree) = worktree {
+    pub(crate) fn save_layout_snapshot(&mut self, name: String, cx: &mut ViewContext<Self>) {
+        let Some(key) = self.project_kvp_key("layout-snapshots", cx) else {
+            return;
+        };
+        let snapshot = self.capture_layout_snapshot(name, cx);
+        cx.background_executor()
+            .spawn(write_layout_snapshot(key, snapshot))
+            .detach_and_log_err(cx);
+    }
+
+    /// Restores the expanded directories, selection and folder focus of the snapshot, skipping
+    /// the entries that no longer exist.
+    pub(crate) fn restore_layout_snapshot(
+        &mut self,
+        snapshot: &LayoutSnapshot,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let project = self.project.read(cx);
+        let worktree_for_abs_path = |abs_path: &Path| {
+            project
+                .visible_worktrees(cx)
+                .find(|worktree| worktree.read(cx).abs_path().as_ref() == abs_path)
+        };
+        let find_entry = |(abs_path, path): &(PathBuf, PathBuf)| {
+            let worktree = worktree_for_abs_path(abs_path)?.read(cx);
+            Some((worktree.id(), worktree.entry_for_path(path)?.id))
+        };
+
+        let mut restored_dirs = Vec::new();
+        for (abs_path, paths) in &snapshot.expanded_dirs {
+            let Some(worktree) = worktree_for_abs_path(abs_path) else {
+                continue;
+            };
             let worktree = worktree.read(cx);
-            let worktree_id = worktree.id();
-            if let Some(last_entry) = worktree.entries(true, 0).last() {
-                self.selection = Some(SelectedEntry {
+            let mut expanded_dir_ids = paths
+                .iter()
+                .filter_map(|path| worktree.entry_for_path(path))
+                .filter(|entry| entry.is_dir())
+                .map(|entry| entry.id)
+                .collect::<Vec<_>>();
+            expanded_dir_ids.sort_unstable();
+            restored_dirs.push((worktree.id(), expanded_dir_ids));
+        }
+        let focused_directory = snapshot.focused_directory.as_ref().and_then(find_entry);
+        let selection =
+            snapshot
+                .selection
+                .as_ref()
+                .and_then(find_entry)
+                .map(|(worktree_id, entry_id)| SelectedEntry {
                     worktree_id,
-                    entry_id: last_entry.id,
+                    entry_id,
                 });
-                self.autoscroll(cx);
-                cx.notify();
+
+        self.project.update(cx, |project, cx| {
+            for (worktree_id, expanded_dir_ids) in &restored_dirs {
+                for entry_id in expanded_dir_ids {
+                    project.expand_entry(*worktree_id, *entry_id, cx);
+                }
             }
-        }
+        });
+        self.expanded_dir_ids.extend(restored_dirs);
+        self.focused_directory = focused_directory;
+        self.selection = selection;
+        self.marked_entries.clear();
+        self.update_visible_entries(None, cx);
+        self.autoscroll(cx);
+        cx.notify();
     }
 
-    fn autoscroll(&mut self, cx: &mut ViewContext<Self>) {
-        if let Some((_, _, index)) = self.selection.and_then(|s| self.index_for_selection(s)) {
-            self.scroll_handle.scroll_to_item(index);
-            cx.notify();
+    fn retry_reading_folder(&mut self, _: &RetryReadingFolder, cx: &mut ViewContext<Self>) {
+        let Some(selection) = self.selection else {
+            return;
+        };
+        if let Some(task) = self.project.update(cx, |project, cx| {
+            project.expand_entry(selection.worktree_id, selection.entry_id, cx)
+        }) {
+            task.detach_and_log_err(cx);
         }
     }
 
-    fn cut(&mut self, _: &Cut, cx: &mut ViewContext<Self>) {
-        let entries = self.marked_entries();
-        if !entries.is_empty() {
-            self.clipboard = Some(ClipboardEntry::Cut(entries));
-            cx.notify();
-        }
+    fn show_folder_access_help(&mut self, _: &ShowFolderAccessHelp, cx: &mut ViewContext<Self>) {
+        let Some((worktree, entry)) = self.selected_entry(cx) else {
+            return;
+        };
+        let abs_path = worktree.abs_path().join(&entry.path);
+        let detail = if cfg!(target_os = "macos") {
+            "Grant Zed access to the folder in System Settings › Privacy & Security, under \
+             Files and Folders or Full Disk Access, then retry reading it."
+        } else if cfg!(target_os = "windows") {
+            "Give your user the Read permission in the Security tab of the folder's \
+             properties, then retry reading it."
+        } else {
+            "Zed does not run with elevated permissions. Make the folder readable by your \
+             user, for example with `chmod u+rx` or by changing its owner with `sudo chown`, \
+             then retry reading it."
+        };
+        let message = format!("{} could not be read", abs_path.display());
+        let prompt = cx.prompt(PromptLevel::Info, &message, Some(detail), &["OK"]);
+        cx.foreground_executor()
+            .spawn(async {
+                prompt.await.ok();
+            })
+            .detach();
     }
 
-    fn copy(&mut self, _: &Copy, cx: &mut ViewContext<Self>) {
-        let entries = self.marked_entries();
-        if !entries.is_empty() {
-            self.clipboard = Some(ClipboardEntry::Copied(entries));
-            cx.notify();
+    fn show_folder_statistics(&mut self, _: &ShowFolderStatistics, cx: &mut ViewContext<Self>) {
+        let Some((worktree, entry)) = self.selected_entry(cx) else {
+            return;
+        };
+        if !entry.is_dir() {
+            return;
         }
+        let folder_name = entry.path.file_name().map_or_else(
+            || SharedString::from(worktree.root_name().to_string()),
+            |name| SharedString::from(name.to_string_lossy().into_owned()),
+        );
+        let worktree_abs_path = worktree.abs_path();
+        let files = worktree
+            .files(false, 0)
+            .filter(|file| file.path.starts_with(&entry.path) && !file.is_special_file)
+            .map(|file| {
+                (
+                    file.path
+                        .strip_prefix(&entry.path)
+                        .unwrap_or(&file.path)
+                        .to_path_buf(),
+                    worktree_abs_path.join(&file.path),
+                )
+            })
+            .collect::<Vec<_>>();
+        let fs = self.fs.clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace.toggle_modal(cx, |cx| {
+                    FolderStatisticsModal::new(folder_name, fs, files, cx)
+                })
+            })
+            .ok();
     }
 
-    fn create_paste_path(
+    /// Loads the notes and tags attached to entries of the project.
+    fn reload_entry_annotations(&mut self, cx: &mut ViewContext<Self>) {
+        let (Some(notes_key), Some(tags_key)) = (
+            self.project_kvp_key("entry-notes", cx),
+            self.project_kvp_key("entry-tags", cx),
+        ) else {
+            self.entry_notes = EntryNotes::default();
+            self.entry_tags = EntryTags::default();
+            return;
+        };
+        self.entry_annotations_task = Some(cx.spawn(|project_panel, mut cx| async move {
+            let (entry_notes, entry_tags) = cx
+                .background_executor()
+                .spawn(async move {
+                    (
+                        read_entry_annotations(&notes_key)
+                            .log_err()
+                            .unwrap_or_default(),
+                        read_entry_annotations(&tags_key)
+                            .log_err()
+                            .unwrap_or_default(),
+                    )
+                })
+                .await;
+            project_panel
+                .update(&mut cx, |project_panel, cx| {
+                    project_panel.entry_notes = entry_notes;
+                    project_panel.entry_tags = entry_tags;
+                    project_panel.update_visible_entries(None, cx);
+                    cx.notify();
+                })
+                .ok();
+        }));
+    }
+
+    fn note_for_entry(
         &self,
-        source: &SelectedEntry,
-        (worktree, target_entry): (Model<Worktree>, &Entry),
+        worktree_id: WorktreeId,
+        path: &Path,
         cx: &AppContext,
-    ) -> Option<PathBuf> {
-        let mut new_path = target_entry.path.to_path_buf();
-        // If we're pasting into a file, or a directory into itself, go up one level.
-        if target_entry.is_file() || (target_entry.is_dir() && target_entry.id == source.entry_id) {
-            new_path.pop();
-        }
-        let clipboard_entry_file_name = self
-            .project
+    ) -> Option<&str> {
+        let worktree = self.project.read(cx).worktree_for_id(worktree_id, cx)?;
+        self.entry_notes
+            .get(&worktree.read(cx).abs_path(), path)
+            .map(String::as_str)
+    }
+
+    fn tags_for_entry(&self, worktree_id: WorktreeId, path: &Path, cx: &AppContext) -> &[String] {
+        self.project
             .read(cx)
-            .path_for_entry(source.entry_id, cx)?
-            .path
-            .file_name()?
-            .to_os_string();
-        new_path.push(&clipboard_entry_file_name);
-        let extension = new_path.extension().map(|e| e.to_os_string());
-        let file_name_without_extension = Path::new(&clipboard_entry_file_name).file_stem()?;
-        let mut ix = 0;
-        {
-            let worktree = worktree.read(cx);
-            while worktree.entry_for_path(&new_path).is_some() {
-                new_path.pop();
+            .worktree_for_id(worktree_id, cx)
+            .and_then(|worktree| self.entry_tags.get(&worktree.read(cx).abs_path(), path))
+            .map_or(&[], Vec::as_slice)
+    }
 
-                let mut new_file_name = file_name_without_extension.to_os_string();
-                new_file_name.push(" copy");
-                if ix > 0 {
-                    new_file_name.push(format!(" {}", ix));
-                }
-                if let Some(extension) = extension.as_ref() {
-                    new_file_name.push(".");
-                    new_file_name.push(extension);
-                }
+    /// Returns the tags used in the visible worktrees, sorted by name.
+    fn project_tags(&self, cx: &AppContext) -> Vec<String> {
+        let mut tags = self
+            .project
+            .read(cx)
+            .visible_worktrees(cx)
+            .flat_map(|worktree| {
+                self.entry_tags
+                    .entries(&worktree.read(cx).abs_path())
+                    .flat_map(|(_, tags)| tags.iter().cloned())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
 
-                new_path.push(new_file_name);
-                ix += 1;
-            }
-        }
-        Some(new_path)
+    fn edit_note(&mut self, _: &EditNote, cx: &mut ViewContext<Self>) {
+        self.edit_entry_annotation(EntryAnnotationKind::Note, cx);
     }
 
-    fn paste(&mut self, _: &Paste, cx: &mut ViewContext<Self>) {
-        maybe!({
-            let (worktree, entry) = self.selected_entry_handle(cx)?;
-            let entry = entry.clone();
-            let worktree_id = worktree.read(cx).id();
-            let clipboard_entries = self
-                .clipboard
-                .as_ref()
-                .filter(|clipboard| !clipboard.items().is_empty())?;
+    fn edit_tags(&mut self, _: &EditTags, cx: &mut ViewContext<Self>) {
+        self.edit_entry_annotation(EntryAnnotationKind::Tags, cx);
+    }
 
-            for clipboard_entry in clipboard_entries.items() {
-                if clipboard_entry.worktree_id != worktree_id {
-                    return None;
-                }
-                let new_path =
-                    self.create_paste_path(clipboard_entry, self.selected_entry_handle(cx)?, cx)?;
-                if clipboard_entries.is_cut() {
-                    self.project
-                        .update(cx, |project, cx| {
-                            project.rename_entry(clipboard_entry.entry_id, new_path, cx)
-                        })
-                        .detach_and_log_err(cx)
-                } else {
-                    self.project
-                        .update(cx, |project, cx| {
-                            project.copy_entry(clipboard_entry.entry_id, new_path, cx)
-                        })
-                        .detach_and_log_err(cx)
-                }
+    fn edit_entry_annotation(&mut self, kind: EntryAnnotationKind, cx: &mut ViewContext<Self>) {
+        let Some((worktree, entry)) = self.selected_entry(cx) else {
+            return;
+        };
+        let worktree_id = worktree.id();
+        let path = entry.path.to_path_buf();
+        let entry_name = SharedString::from(entry.path.file_name().map_or_else(
+            || worktree.root_name().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        ));
+        let text = match kind {
+            EntryAnnotationKind::Note => self
+                .note_for_entry(worktree_id, &path, cx)
+                .map(ToOwned::to_owned),
+            EntryAnnotationKind::Tags => {
+                let tags = self.tags_for_entry(worktree_id, &path, cx);
+                (!tags.is_empty()).then(|| tags.join(", "))
             }
-            self.expand_entry(worktree_id, entry.id, cx);
-            Some(())
-        });
+        };
+        let project_panel = cx.view().downgrade();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace.toggle_modal(cx, |cx| {
+                    EntryAnnotationEditor::new(
+                        project_panel,
+                        kind,
+                        worktree_id,
+                        path,
+                        entry_name,
+                        text,
+                        cx,
+                    )
+                })
+            })
+            .ok();
     }
 
-    fn duplicate(&mut self, _: &Duplicate, cx: &mut ViewContext<Self>) {
-        self.copy(&Copy {}, cx);
-        self.paste(&Paste {}, cx);
+    /// Attaches the note to the entry and stores the notes of the project, removing the entry's
+    /// note when `note` is blank.
+    pub(crate) fn set_entry_note(
+        &mut self,
+        worktree_id: WorktreeId,
+        path: &Path,
+        note: &str,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(key) = self.project_kvp_key("entry-notes", cx) else {
+            return;
+        };
+        let Some(worktree) = self.project.read(cx).worktree_for_id(worktree_id, cx) else {
+            return;
+        };
+        let worktree_abs_path = worktree.read(cx).abs_path();
+        let note = note.trim();
+        self.entry_notes.set(
+            &worktree_abs_path,
+            path,
+            (!note.is_empty()).then(|| note.to_string()),
+        );
+        self.write_entry_annotations(write_entry_annotations(key, &self.entry_notes), cx);
+        cx.notify();
     }
 
-    fn copy_path(&mut self, _: &CopyPath, cx: &mut ViewContext<Self>) {
-        if let Some((worktree, entry)) = self.selected_entry(cx) {
-            cx.write_to_clipboard(ClipboardItem::new(
-                worktree
-                    .abs_path()
-                    .join(&entry.path)
-                    .to_string_lossy()
-                    .to_string(),
-            ));
+    /// Replaces the tags of the entry and stores the tags of the project.
+    pub(crate) fn set_entry_tags(
+        &mut self,
+        worktree_id: WorktreeId,
+        path: &Path,
+        tags: Vec<String>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(key) = self.project_kvp_key("entry-tags", cx) else {
+            return;
+        };
+        let Some(worktree) = self.project.read(cx).worktree_for_id(worktree_id, cx) else {
+            return;
+        };
+        let worktree_abs_path = worktree.read(cx).abs_path();
+        self.entry_tags
+            .set(&worktree_abs_path, path, (!tags.is_empty()).then_some(tags));
+        self.write_entry_annotations(write_entry_annotations(key, &self.entry_tags), cx);
+
+        if let Some(tag_filter) = &self.tag_filter {
+            if !self.project_tags(cx).contains(tag_filter) {
+                self.tag_filter = None;
+            }
         }
+        self.update_visible_entries(None, cx);
+        cx.notify();
     }
 
-    fn copy_relative_path(&mut self, _: &CopyRelativePath, cx: &mut ViewContext<Self>) {
-        if let Some((_, entry)) = self.selected_entry(cx) {
-            cx.write_to_clipboard(ClipboardItem::new(entry.path.to_string_lossy().to_string()));
-        }
+    fn write_entry_annotations(
+        &mut self,
+        write: impl Future<Output = Result<()>> + Send + 'static,
+        cx: &mut ViewContext<Self>,
+    ) {
+        // Chain the writes, so that an older state never overwrites a newer one.
+        let previous_task = self.entry_annotations_write_task.take();
+        self.entry_annotations_write_task = Some(cx.background_executor().spawn(async move {
+            if let Some(previous_task) = previous_task {
+                previous_task.await;
+            }
+            write.await.log_err();
+        }));
     }
 
-    fn reveal_in_finder(&mut self, _: &RevealInFinder, cx: &mut ViewContext<Self>) {
-        if let Some((worktree, entry)) = self.selected_entry(cx) {
-            cx.reveal_path(&worktree.abs_path().join(&entry.path));
+    /// Writes the notes, tags and layout snapshots of the selected worktree to its
+    /// `.zed/panel.json`.
+    fn export_panel_configuration(
+        &mut self,
+        _: &ExportPanelConfiguration,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if !self.project.read(cx).is_local() {
+            return;
         }
-    }
-
-    fn open_in_terminal(&mut self, _: &OpenInTerminal, cx: &mut ViewContext<Self>) {
-        if let Some((worktree, entry)) = self.selected_entry(cx) {
-            let abs_path = worktree.abs_path().join(&entry.path);
-            let working_directory = if entry.is_dir() {
-                Some(abs_path)
-            } else {
-                if entry.is_symlink {
-                    abs_path.canonicalize().ok()
-                } else {
-                    Some(abs_path)
-                }
-                .and_then(|path| Some(path.parent()?.to_path_buf()))
-            };
-            if let Some(working_directory) = working_directory {
-                cx.dispatch_action(workspace::OpenTerminal { working_directory }.boxed_clone())
+        let Some((worktree, _)) = self.selected_entry(cx) else {
+            return;
+        };
+        let Some(key) = self.project_kvp_key("layout-snapshots", cx) else {
+            return;
+        };
+        let worktree_abs_path = worktree.abs_path().to_path_buf();
+        let entry_notes = self.entry_notes.clone();
+        let entry_tags = self.entry_tags.clone();
+        let fs = self.fs.clone();
+        cx.spawn(|_, cx| async move {
+            let layout_snapshots = cx
+                .background_executor()
+                .spawn(async move { read_layout_snapshots(&key) })
+                .await?;
+            let configuration = PanelConfiguration::new(
+                &worktree_abs_path,
+                &entry_notes,
+                &entry_tags,
+                &layout_snapshots,
+            );
+            let configuration_path = worktree_abs_path.join(PANEL_CONFIGURATION_PATH);
+            if let Some(configuration_dir) = configuration_path.parent() {
+                fs.create_dir(configuration_dir).await?;
             }
-        }
+            let text = serde_json::to_string_pretty(&configuration)? + "\n";
+            fs.atomic_write(configuration_path, text).await
+        })
+        .detach_and_prompt_err("Failed to export panel configuration", cx, |_, _| None);
     }
 
-    pub fn new_search_in_directory(
+    /// Loads the `.zed/panel.json` of the selected worktree. Its notes and tags replace the ones
+    /// of the same entries and its layout snapshots the ones with the same names.
+    fn import_panel_configuration(
         &mut self,
-        _: &NewSearchInDirectory,
+        _: &ImportPanelConfiguration,
         cx: &mut ViewContext<Self>,
     ) {
-        if let Some((worktree, entry)) = self.selected_entry(cx) {
-            if entry.is_dir() {
-                let include_root = self.project.read(cx).visible_worktrees(cx).count() > 1;
-                let dir_path = if include_root {
-                    let mut full_path = PathBuf::from(worktree.root_name());
-                    full_path.push(&entry.path);
-                    Arc::from(full_path)
-                } else {
-                    entry.path.clone()
-                };
-
-                self.workspace
-                    .update(cx, |workspace, cx| {
-                        search::ProjectSearchView::new_search_in_directory(
-                            workspace, &dir_path, cx,
-                        );
-                    })
-                    .ok();
-            }
+        if !self.project.read(cx).is_local() {
+            return;
         }
+        let Some((worktree, _)) = self.selected_entry(cx) else {
+            return;
+        };
+        let worktree_id = worktree.id();
+        let configuration_path = worktree.abs_path().join(PANEL_CONFIGURATION_PATH);
+        let fs = self.fs.clone();
+        cx.spawn(|project_panel, mut cx| async move {
+            let text = fs.load(&configuration_path).await?;
+            let configuration = serde_json::from_str::<PanelConfiguration>(&text)?;
+            project_panel.update(&mut cx, |project_panel, cx| {
+                project_panel.apply_panel_configuration(worktree_id, configuration, cx)
+            })
+        })
+        .detach_and_prompt_err("Failed to import panel configuration", cx, |_, _| None);
     }
 
-    fn move_entry(
+    fn apply_panel_configuration(
         &mut self,
-        entry_to_move: ProjectEntryId,
-        destination: ProjectEntryId,
-        destination_is_file: bool,
+        worktree_id: WorktreeId,
+        configuration: PanelConfiguration,
         cx: &mut ViewContext<Self>,
     ) {
-        if self
-            .project
-            .read(cx)
-            .entry_is_worktree_root(entry_to_move, cx)
-        {
-            self.move_worktree_root(entry_to_move, destination, cx)
-        } else {
-            self.move_worktree_entry(entry_to_move, destination, destination_is_file, cx)
+        let (Some(notes_key), Some(tags_key), Some(snapshots_key)) = (
+            self.project_kvp_key("entry-notes", cx),
+            self.project_kvp_key("entry-tags", cx),
+            self.project_kvp_key("layout-snapshots", cx),
+        ) else {
+            return;
+        };
+        let Some(worktree) = self.project.read(cx).worktree_for_id(worktree_id, cx) else {
+            return;
+        };
+        let worktree_abs_path = worktree.read(cx).abs_path();
+        let layout_snapshots = configuration.layout_snapshots(&worktree_abs_path);
+        for (path, note) in configuration.notes {
+            self.entry_notes.set(&worktree_abs_path, &path, Some(note));
+        }
+        for (path, tags) in configuration.tags {
+            self.entry_tags.set(&worktree_abs_path, &path, Some(tags));
         }
-    }
-
-    fn move_worktree_root(
-        &mut self,
-        entry_to_move: ProjectEntryId,
-        destination: ProjectEntryId,
-        cx: &mut ViewContext<Self>,
-    ) {
-        self.project.update(cx, |project, cx| {
-            let Some(worktree_to_move) = project.worktree_for_entry(entry_to_move, cx) else {
-                return;
-            };
-            let Some(destination_worktree) = project.worktree_for_entry(destination, cx) else {
-                return;
-            };
 
-            let worktree_id = worktree_to_move.read(cx).id();
-            let destination_id = destination_worktree.read(cx).id();
+        let write_notes = write_entry_annotations(notes_key, &self.entry_notes);
+        let write_tags = write_entry_annotations(tags_key, &self.entry_tags);
+        self.write_entry_annotations(
+            async move {
+                write_notes.await?;
+                write_tags.await?;
+                for snapshot in layout_snapshots {
+                    write_layout_snapshot(snapshots_key.clone(), snapshot).await?;
+                }
+                Ok(())
+            },
+            cx,
+        );
+        self.update_visible_entries(None, cx);
+        cx.notify();
+    }
 
-            project
-                .move_worktree(worktree_id, destination_id, cx)
-                .log_err();
-        });
-        return;
+    /// Shows only the entries tagged with `tag`, expanding the directories they are in.
+    fn set_tag_filter(&mut self, tag: Option<String>, cx: &mut ViewContext<Self>) {
+        if let Some(tag) = &tag {
+            let project = self.project.read(cx);
+            let mut expanded_entries = Vec::new();
+            for worktree in project.visible_worktrees(cx) {
+                let worktree = worktree.read(cx);
+                for (path, tags) in self.entry_tags.entries(&worktree.abs_path()) {
+                    if !tags.contains(tag) {
+                        continue;
+                    }
+                    if let Some(parent) = path.parent().and_then(|p| worktree.entry_for_path(p)) {
+                        expanded_entries.push((worktree.id(), parent.id));
+                    }
+                }
+            }
+            for (worktree_id, entry_id) in expanded_entries {
+                self.expand_entry(worktree_id, entry_id, cx);
+            }
+        }
+        self.tag_filter = tag;
+        self.update_visible_entries(None, cx);
+        cx.notify();
     }
 
-    fn move_worktree_entry(
+    /// Shows only the files with a git status and the directories containing them, for a quick
+    /// review of the working tree, or every entry again.
+    fn toggle_changed_files_only(
         &mut self,
-        entry_to_move: ProjectEntryId,
-        destination: ProjectEntryId,
-        destination_is_file: bool,
+        _: &ToggleChangedFilesOnly,
         cx: &mut ViewContext<Self>,
     ) {
-        let destination_worktree = self.project.update(cx, |project, cx| {
-            let entry_path = project.path_for_entry(entry_to_move, cx)?;
-            let destination_entry_path = project.path_for_entry(destination, cx)?.path.clone();
-
-            let mut destination_path = destination_entry_path.as_ref();
-            if destination_is_file {
-                destination_path = destination_path.parent()?;
-            }
-
-            let mut new_path = destination_path.to_path_buf();
-            new_path.push(entry_path.path.file_name()?);
-            if new_path != entry_path.path.as_ref() {
-                let task = project.rename_entry(entry_to_move, new_path, cx);
-                cx.foreground_executor().spawn(task).detach_and_log_err(cx);
-            }
-
-            project.worktree_id_for_entry(destination, cx)
-        });
+        self.show_changed_files_only = !self.show_changed_files_only;
+        self.update_visible_entries(None, cx);
+        cx.notify();
+    }
 
-        if let Some(destination_worktree) = destination_worktree {
-            self.expand_entry(destination_worktree, destination, cx);
+    /// Shows only the entries whose paths fuzzy match `query`, expanding the directories they are in.
+    fn filter_entries(&mut self, query: String, cx: &mut ViewContext<Self>) {
+        let query = query.trim();
+        let query = (!query.is_empty()).then(|| query.to_string());
+        if query == self.entry_filter {
+            return;
+        }
+        self.entry_filter = query;
+        if self.entry_filter.is_none() {
+            self.entry_filter_task = None;
+            self.entry_filter_matches = None;
+            self.expand_entry_filter_matches = false;
+            self.update_visible_entries(None, cx);
+            cx.notify();
+            return;
         }
+        self.expand_entry_filter_matches = true;
+        self.refresh_entry_filter_matches(cx);
     }
 
-    fn index_for_selection(&self, selection: SelectedEntry) -> Option<(usize, usize, usize)> {
-        let mut entry_index = 0;
-        let mut visible_entries_index = 0;
-        for (worktree_index, (worktree_id, worktree_entries, _)) in
-            self.visible_entries.iter().enumerate()
-        {
-            if *worktree_id == selection.worktree_id {
-                for entry in worktree_entries {
-                    if entry.id == selection.entry_id {
-                        return Some((worktree_index, entry_index, visible_entries_index));
-                    } else {
-                        visible_entries_index += 1;
-                        entry_index += 1;
+    /// Matches the entries against the filter query in the background.
+    fn refresh_entry_filter_matches(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(query) = self.entry_filter.clone() else {
+            return;
+        };
+        let snapshots = self
+            .project
+            .read(cx)
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.read(cx).snapshot())
+            .collect::<Vec<_>>();
+        self.entry_filter_task = Some(cx.spawn(|project_panel, mut cx| async move {
+            let entry_filter_matches = cx
+                .background_executor()
+                .spawn(async move {
+                    snapshots
+                        .iter()
+                        .map(|snapshot| (snapshot.id(), filter_matches(snapshot, &query)))
+                        .collect::<HashMap<_, _>>()
+                })
+                .await;
+            project_panel
+                .update(&mut cx, |project_panel, cx| {
+                    if mem::take(&mut project_panel.expand_entry_filter_matches) {
+                        let project = project_panel.project.read(cx);
+                        let mut expanded_entries = Vec::new();
+                        for (worktree_id, filter_matches) in &entry_filter_matches {
+                            let Some(worktree) = project.worktree_for_id(*worktree_id, cx) else {
+                                continue;
+                            };
+                            let worktree = worktree.read(cx);
+                            expanded_entries.extend(filter_matches.ancestors.iter().filter_map(
+                                |path| Some((*worktree_id, worktree.entry_for_path(path)?.id)),
+                            ));
+                        }
+                        for (worktree_id, entry_id) in expanded_entries {
+                            project_panel.expand_entry(worktree_id, entry_id, cx);
+                        }
                     }
-                }
-                break;
-            } else {
-                visible_entries_index += worktree_entries.len();
+                    project_panel.entry_filter_task = None;
+                    project_panel.entry_filter_matches = Some(entry_filter_matches);
+                    project_panel.update_visible_entries(None, cx);
+                    cx.notify();
+                })
+                .ok();
+        }));
+    }
+
+    fn copy_owners(&mut self, _: &CopyOwners, cx: &mut ViewContext<Self>) {
+        if let Some((worktree, entry)) = self.selected_entry(cx) {
+            if let Some(owners) = self.code_owners_for_entry(worktree.id(), &entry.path) {
+                cx.write_to_clipboard(ClipboardItem::new(owners.join(" ")));
             }
         }
-        None
     }
 
-    // Returns list of entries that should be affected by an operation.
-    // When currently selected entry is not marked, it's treated as the only marked entry.
-    fn marked_entries(&self) -> BTreeSet<SelectedEntry> {
-        let Some(selection) = self.selection else {
-            return Default::default();
+    fn run_deployment_command(
+        &mut self,
+        label: &str,
+        command: &str,
+        entry_id: ProjectEntryId,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(worktree) = self.project.read(cx).worktree_for_entry(entry_id, cx) else {
+            return;
         };
-        if self.marked_entries.contains(&selection) {
-            self.marked_entries.clone()
-        } else {
-            BTreeSet::from_iter([selection])
-        }
-    }
-    pub fn selected_entry<'a>(
-        &self,
-        cx: &'a AppContext,
-    ) -> Option<(&'a Worktree, &'a project::Entry)> {
-        let (worktree, entry) = self.selected_entry_handle(cx)?;
-        Some((worktree.read(cx), entry))
+        let worktree = worktree.read(cx);
+        let Some(entry) = worktree.entry_for_id(entry_id) else {
+            return;
+        };
+        let Some(deployment) = deployment_for_path(&entry.path, cx) else {
+            return;
+        };
+        let deployment_path = entry
+            .path
+            .strip_prefix(&deployment.local)
+            .unwrap_or(&entry.path);
+        let task_cx = TaskContext {
+            cwd: Some(worktree.abs_path().join(&deployment.local)),
+            task_variables: TaskVariables::from_iter([
+                (
+                    VariableName::File,
+                    worktree
+                        .abs_path()
+                        .join(&entry.path)
+                        .to_string_lossy()
+                        .into(),
+                ),
+                (
+                    VariableName::Custom("DEPLOYMENT_PATH".into()),
+                    deployment_path.to_string_lossy().into(),
+                ),
+            ]),
+        };
+        let label = format!("{label} {}", deployment_path.display());
+        self.spawn_entry_command(label, command, task_cx, cx);
     }
 
-    fn selected_entry_handle<'a>(
-        &self,
-        cx: &'a AppContext,
-    ) -> Option<(Model<Worktree>, &'a project::Entry)> {
-        let selection = self.selection?;
-        let project = self.project.read(cx);
-        let worktree = project.worktree_for_id(selection.worktree_id, cx)?;
-        let entry = worktree.read(cx).entry_for_id(selection.entry_id)?;
-        Some((worktree, entry))
+    /// Whether git would ignore the entry being created with the name typed so far, e.g. for
+    /// sources accidentally created under `target/` or `node_modules/`.
+    fn is_new_entry_ignored(&self, cx: &AppContext) -> bool {
+        let Some(edit_state) = self.edit_state.as_ref().filter(|state| state.is_new_entry) else {
+            return false;
+        };
+        let Some(worktree) = self
+            .project
+            .read(cx)
+            .worktree_for_id(edit_state.worktree_id, cx)
+        else {
+            return false;
+        };
+        let worktree = worktree.read(cx);
+        let Some(parent) = worktree.entry_for_id(edit_state.entry_id) else {
+            return false;
+        };
+        let filename = self.filename_editor.read(cx).text(cx);
+        let filename = filename.trim_start_matches('/');
+        if filename.is_empty() {
+            return parent.is_ignored;
+        }
+        parent.is_ignored
+            || worktree.as_local().map_or(false, |worktree| {
+                worktree.is_path_ignored(&parent.path.join(filename), edit_state.is_dir)
+            })
     }
 
-    fn expand_to_selection(&mut self, cx: &mut ViewContext<Self>) -> Option<()> {
-        let (worktree, entry) = self.selected_entry(cx)?;
-        let expanded_dir_ids = self.expanded_dir_ids.entry(worktree.id()).or_default();
-
-        for path in entry.path.ancestors() {
-            let Some(entry) = worktree.entry_for_path(path) else {
+    /// Inserts the configured license header into the marked files, and into all files inside
+    /// marked directories, that don't start with it yet.
+    fn insert_license_header(&mut self, _: &InsertLicenseHeader, cx: &mut ViewContext<Self>) {
+        let license_headers = &ProjectPanelSettings::get_global(cx).license_headers;
+        let project = self.project.read(cx);
+        let mut files = Vec::new();
+        for selection in self.marked_entries() {
+            let Some(worktree) = project.worktree_for_id(selection.worktree_id, cx) else {
                 continue;
             };
-            if entry.is_dir() {
-                if let Err(idx) = expanded_dir_ids.binary_search(&entry.id) {
-                    expanded_dir_ids.insert(idx, entry.id);
+            let worktree = worktree.read(cx);
+            let Some(entry) = worktree.entry_for_id(selection.entry_id) else {
+                continue;
+            };
+            let entries = if entry.is_dir() {
+                worktree
+                    .files(false, 0)
+                    .filter(|file| file.path.starts_with(&entry.path) && !file.is_special_file)
+                    .collect::<Vec<_>>()
+            } else {
+                vec![entry]
+            };
+            for entry in entries {
+                if let Some(header) = license_header_for_path(license_headers, &entry.path) {
+                    files.push((worktree.abs_path().join(&entry.path), header.to_owned()));
                 }
             }
         }
+        if files.is_empty() {
+            return;
+        }
 
-        Some(())
+        let fs = self.fs.clone();
+        cx.background_executor()
+            .spawn(async move {
+                for (abs_path, header) in files {
+                    let text = fs.load(&abs_path).await?;
+                    if let Some(text) = with_license_header(&text, &header) {
+                        fs.atomic_write(abs_path, text).await?;
+                    }
+                }
+                anyhow::Ok(())
+            })
+            .detach_and_log_err(cx);
     }
 
-    fn update_visible_entries(
+    fn decrypt_and_open(
         &mut self,
-        new_selected_entry: Option<(WorktreeId, ProjectEntryId)>,
+        command: &str,
+        entry_id: ProjectEntryId,
         cx: &mut ViewContext<Self>,
     ) {
-        let auto_collapse_dirs = ProjectPanelSettings::get_global(cx).auto_fold_dirs;
-        let project = self.project.read(cx);
-        self.last_worktree_root_id = project
-            .visible_worktrees(cx)
-            .rev()
-            .next()
-            .and_then(|worktree| worktree.read(cx).root_entry())
-            .map(|entry| entry.id);
-
-        self.visible_entries.clear();
-        for worktree in project.visible_worktrees(cx) {
-            let snapshot = worktree.read(cx).snapshot();
-            let worktree_id = snapshot.id();
+        if self.run_encryption_command("Decrypt", command, entry_id, cx) {
+            self.decorations
+                .get_mut::<FileHeaderDecoration>()
+                .set_decrypted(entry_id, true);
+            self.open_entry(entry_id, false, true, false, cx);
+            cx.notify();
+        }
+    }
 
-            let expanded_dir_ids = match self.expanded_dir_ids.entry(worktree_id) {
-                hash_map::Entry::Occupied(e) => e.into_mut(),
-                hash_map::Entry::Vacant(e) => {
-                    // The first time a worktree's root entry becomes available,
-                    // mark that root entry as expanded.
-                    if let Some(entry) = snapshot.root_entry() {
-                        e.insert(vec![entry.id]).as_slice()
-                    } else {
-                        &[]
-                    }
-                }
-            };
+    fn reencrypt(&mut self, command: &str, entry_id: ProjectEntryId, cx: &mut ViewContext<Self>) {
+        if self.run_encryption_command("Encrypt", command, entry_id, cx) {
+            self.decorations
+                .get_mut::<FileHeaderDecoration>()
+                .set_decrypted(entry_id, false);
+            cx.notify();
+        }
+    }
 
-            let mut new_entry_parent_id = None;
-            let mut new_entry_kind = EntryKind::Dir;
-            if let Some(edit_state) = &self.edit_state {
-                if edit_state.worktree_id == worktree_id && edit_state.is_new_entry {
-                    new_entry_parent_id = Some(edit_state.entry_id);
-                    new_entry_kind = if edit_state.is_dir {
-                        EntryKind::Dir
-                    } else {
-                        EntryKind::File(Default::default())
-                    };
+    fn run_encryption_command(
+        &mut self,
+        label: &str,
+        command: &str,
+        entry_id: ProjectEntryId,
+        cx: &mut ViewContext<Self>,
+    ) -> bool {
+        let Some(worktree) = self.project.read(cx).worktree_for_entry(entry_id, cx) else {
+            return false;
+        };
+        let worktree = worktree.read(cx);
+        let Some(entry) = worktree.entry_for_id(entry_id) else {
+            return false;
+        };
+        let task_cx = TaskContext {
+            cwd: Some(worktree.abs_path().to_path_buf()),
+            task_variables: TaskVariables::from_iter([(
+                VariableName::File,
+                worktree
+                    .abs_path()
+                    .join(&entry.path)
+                    .to_string_lossy()
+                    .into(),
+            )]),
+        };
+        let label = format!("{label} {}", entry.path.display());
+        self.spawn_entry_command(label, command, task_cx, cx);
+        true
+    }
+
+    /// Runs a user-configured command for an entry as a task in the terminal.
+    fn spawn_entry_command(
+        &mut self,
+        label: String,
+        command: &str,
+        task_cx: TaskContext,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let task = TaskTemplate {
+            label,
+            command: command.to_string(),
+            ..TaskTemplate::default()
+        };
+        self.workspace
+            .update(cx, |workspace, cx| {
+                schedule_task(
+                    workspace,
+                    TaskSourceKind::UserInput,
+                    &task,
+                    &task_cx,
+                    true,
+                    cx,
+                )
+            })
+            .ok();
+    }
+
+    fn open_folder_as_project(&mut self, abs_path: PathBuf, cx: &mut ViewContext<Self>) {
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace
+                    .open_workspace_for_paths(false, vec![abs_path], cx)
+                    .detach_and_log_err(cx);
+            })
+            .ok();
+    }
+
+    fn add_folder_to_project(&mut self, abs_path: PathBuf, cx: &mut ViewContext<Self>) {
+        self.project
+            .update(cx, |project, cx| {
+                project.find_or_create_local_worktree(abs_path, true, cx)
+            })
+            .detach_and_log_err(cx);
+    }
+
+    fn is_unfoldable(&self, entry: &Entry, worktree: &Worktree) -> bool {
+        if !entry.is_dir() || self.unfolded_dir_ids.contains(&entry.id) {
+            return false;
+        }
+
+        if let Some(parent_path) = entry.path.parent() {
+            let snapshot = worktree.snapshot();
+            let mut child_entries = snapshot.child_entries(&parent_path);
+            if let Some(child) = child_entries.next() {
+                if child_entries.next().is_none() {
+                    return child.kind.is_dir();
                 }
             }
+        };
+        false
+    }
 
-            let mut visible_worktree_entries = Vec::new();
-            let mut entry_iter = snapshot.entries(true, 0);
-            while let Some(entry) = entry_iter.entry() {
-                if auto_collapse_dirs
-                    && entry.kind.is_dir()
-                    && !self.unfolded_dir_ids.contains(&entry.id)
-                {
-                    if let Some(root_path) = snapshot.root_entry() {
-                        let mut child_entries = snapshot.child_entries(&entry.path);
-                        if let Some(child) = child_entries.next() {
-                            if entry.path != root_path.path
-                                && child_entries.next().is_none()
-                                && child.kind.is_dir()
-                            {
-                                entry_iter.advance();
-                                continue;
-                            }
-                        }
-                    }
-                }
+    fn is_foldable(&self, entry: &Entry, worktree: &Worktree) -> bool {
+        if entry.is_dir() {
+            let snapshot = worktree.snapshot();
 
-                visible_worktree_entries.push(entry.clone());
-                if Some(entry.id) == new_entry_parent_id {
-                    visible_worktree_entries.push(Entry {
-                        id: NEW_ENTRY_ID,
-                        kind: new_entry_kind,
-                        path: entry.path.join("\0").into(),
-                        inode: 0,
-                        mtime: entry.mtime,
-                        is_ignored: entry.is_ignored,
-                        is_external: false,
-                        is_private: false,
-                        git_status: entry.git_status,
-                        canonical_path: entry.canonical_path.clone(),
-                        is_symlink: entry.is_symlink,
-                    });
+            let mut child_entries = snapshot.child_entries(&entry.path);
+            if let Some(child) = child_entries.next() {
+                if child_entries.next().is_none() {
+                    return child.kind.is_dir();
                 }
-                if expanded_dir_ids.binary_search(&entry.id).is_err()
-                    && entry_iter.advance_to_sibling()
-                {
-                    continue;
+            }
+        }
+        false
+    }
+
+    fn expand_selected_entry(&mut self, _: &ExpandSelectedEntry, cx: &mut ViewContext<Self>) {
+        if let Some((worktree, entry)) = self.selected_entry(cx) {
+            if entry.is_dir() || self.has_nested_files(worktree.id(), entry.id) {
+                let worktree_id = worktree.id();
+                let entry_id = entry.id;
+                let expanded_dir_ids =
+                    if let Some(expanded_dir_ids) = self.expanded_dir_ids.get_mut(&worktree_id) {
+                        expanded_dir_ids
+                    } else {
+                        return;
+                    };
+
+                match expanded_dir_ids.binary_search(&entry_id) {
+                    Ok(_) => self.select_next(&SelectNext, cx),
+                    Err(ix) => {
+                        if entry.is_dir() {
+                            self.project.update(cx, |project, cx| {
+                                project.expand_entry(worktree_id, entry_id, cx);
+                            });
+                        }
+
+                        expanded_dir_ids.insert(ix, entry_id);
+                        self.update_visible_entries(None, cx);
+                        if ProjectPanelSettings::get_global(cx).selection_follows_expansion {
+                            self.select_first_child(worktree_id, entry_id, cx);
+                        }
+                        cx.notify();
+                    }
                 }
-                entry_iter.advance();
             }
+        }
+    }
 
-            snapshot.propagate_git_statuses(&mut visible_worktree_entries);
-            project::sort_worktree_entries(&mut visible_worktree_entries);
-            self.visible_entries
-                .push((worktree_id, visible_worktree_entries, OnceCell::new()));
+    fn expand_selected_entry_recursively(
+        &mut self,
+        _: &ExpandSelectedEntryRecursively,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let Some((worktree, entry)) = self.selected_entry(cx) {
+            let worktree_id = worktree.id();
+            let entry_id = entry.id;
+            self.expand_entry_recursively(worktree_id, entry_id, cx);
         }
+    }
 
-        if let Some((worktree_id, entry_id)) = new_selected_entry {
-            self.selection = Some(SelectedEntry {
-                worktree_id,
-                entry_id,
-            });
-            if cx.modifiers().shift {
-                self.marked_entries.insert(SelectedEntry {
-                    worktree_id,
-                    entry_id,
-                });
+    /// Expands the directory and all of its descendants. Unloaded descendants, e.g. gitignored
+    /// directories, are loaded and get their own descendants expanded once they are.
+    /// Directories outside of the worktree are left unloaded, as symlinks may loop.
+    fn expand_entry_recursively(
+        &mut self,
+        worktree_id: WorktreeId,
+        entry_id: ProjectEntryId,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(worktree) = self.project.read(cx).worktree_for_id(worktree_id, cx) else {
+            return;
+        };
+        let snapshot = worktree.read(cx).snapshot();
+        let Some(entry) = snapshot
+            .entry_for_id(entry_id)
+            .filter(|entry| entry.is_dir())
+        else {
+            return;
+        };
+        let dirs = snapshot
+            .traverse_from_path(false, true, true, &entry.path)
+            .take_while(|dir| dir.path.starts_with(&entry.path))
+            .map(|dir| {
+                let needs_loading = dir.kind == EntryKind::UnloadedDir && !dir.is_external;
+                (dir.id, needs_loading)
+            })
+            .collect::<Vec<_>>();
+
+        let mut load_tasks = Vec::new();
+        let expanded_dir_ids = self.expanded_dir_ids.entry(worktree_id).or_default();
+        self.project.update(cx, |project, cx| {
+            for (dir_id, needs_loading) in dirs {
+                if let Err(ix) = expanded_dir_ids.binary_search(&dir_id) {
+                    expanded_dir_ids.insert(ix, dir_id);
+                }
+                if let Some(task) = project.expand_entry(worktree_id, dir_id, cx) {
+                    if needs_loading {
+                        load_tasks.push((dir_id, task));
+                    }
+                }
             }
+        });
+        for (dir_id, load_task) in load_tasks {
+            cx.spawn(|project_panel, mut cx| async move {
+                load_task.await?;
+                project_panel.update(&mut cx, |project_panel, cx| {
+                    project_panel.expand_entry_recursively(worktree_id, dir_id, cx);
+                })
+            })
+            .detach_and_log_err(cx);
         }
+        self.update_visible_entries(Some((worktree_id, entry_id)), cx);
+        cx.notify();
     }
 
-    fn expand_entry(
+    /// Selects the first visible child of the directory, if it has any loaded yet.
+    fn select_first_child(
         &mut self,
         worktree_id: WorktreeId,
         entry_id: ProjectEntryId,
         cx: &mut ViewContext<Self>,
     ) {
-        self.project.update(cx, |project, cx| {
-            if let Some((worktree, expanded_dir_ids)) = project
-                .worktree_for_id(worktree_id, cx)
-                .zip(self.expanded_dir_ids.get_mut(&worktree_id))
-            {
-                project.expand_entry(worktree_id, entry_id, cx);
-                let worktree = worktree.read(cx);
+        let Some((worktree_ix, entry_ix, _)) = self.index_for_selection(SelectedEntry {
+            worktree_id,
+            entry_id,
+        }) else {
+            return;
+        };
+        let worktree_entries = &self.visible_entries[worktree_ix].1;
+        let Some(child) = worktree_entries
+            .get(entry_ix + 1)
+            .filter(|child| child.path.parent() == Some(worktree_entries[entry_ix].path.as_ref()))
+        else {
+            return;
+        };
+        self.selection = Some(SelectedEntry {
+            worktree_id,
+            entry_id: child.id,
+        });
+        self.autoscroll(cx);
+    }
 
-                if let Some(mut entry) = worktree.entry_for_id(entry_id) {
-                    loop {
-                        if let Err(ix) = expanded_dir_ids.binary_search(&entry.id) {
-                            expanded_dir_ids.insert(ix, entry.id);
-                        }
+    fn collapse_selected_entry(&mut self, _: &CollapseSelectedEntry, cx: &mut ViewContext<Self>) {
+        if let Some((worktree, mut entry)) = self.selected_entry(cx) {
+            let worktree_id = worktree.id();
+            if ProjectPanelSettings::get_global(cx).selection_follows_expansion
+                && self
+                    .expanded_dir_ids
+                    .get(&worktree_id)
+                    .map_or(true, |expanded_dir_ids| {
+                        expanded_dir_ids.binary_search(&entry.id).is_err()
+                    })
+            {
+                self.select_parent(&SelectParent, cx);
+                return;
+            }
+            let expanded_dir_ids =
+                if let Some(expanded_dir_ids) = self.expanded_dir_ids.get_mut(&worktree_id) {
+                    expanded_dir_ids
+                } else {
+                    return;
+                };
 
+            loop {
+                let entry_id = entry.id;
+                match expanded_dir_ids.binary_search(&entry_id) {
+                    Ok(ix) => {
+                        expanded_dir_ids.remove(ix);
+                        self.update_visible_entries(Some((worktree_id, entry_id)), cx);
+                        cx.notify();
+                        break;
+                    }
+                    Err(_) => {
                         if let Some(parent_entry) =
                             entry.path.parent().and_then(|p| worktree.entry_for_path(p))
                         {
@@ -1690,2124 +3498,11089 @@ impl ProjectPanel {
                     }
                 }
             }
-        });
+        }
     }
 
-    fn drop_external_files(
+    fn collapse_selected_entry_recursively(
         &mut self,
-        paths: &[PathBuf],
-        entry_id: ProjectEntryId,
+        _: &CollapseSelectedEntryRecursively,
         cx: &mut ViewContext<Self>,
     ) {
-        let mut paths: Vec<Arc<Path>> = paths
-            .into_iter()
-            .map(|path| Arc::from(path.clone()))
-            .collect();
-
-        let open_file_after_drop = paths.len() == 1 && paths[0].is_file();
+        let Some((worktree, entry)) = self.selected_entry(cx) else {
+            return;
+        };
+        let worktree_id = worktree.id();
+        let Some(expanded_dir_ids) = self.expanded_dir_ids.get(&worktree_id) else {
+            return;
+        };
+        // Like with `CollapseSelectedEntry`, the nearest expanded directory gets collapsed.
+        let Some(dir_id) = entry
+            .path
+            .ancestors()
+            .filter_map(|path| worktree.entry_for_path(path))
+            .find(|dir| expanded_dir_ids.binary_search(&dir.id).is_ok())
+            .map(|dir| dir.id)
+        else {
+            return;
+        };
+        self.collapse_entry_recursively(worktree_id, dir_id, cx);
+    }
 
-        let Some((target_directory, worktree)) = maybe!({
-            let worktree = self.project.read(cx).worktree_for_entry(entry_id, cx)?;
-            let entry = worktree.read(cx).entry_for_id(entry_id)?;
-            let path = worktree.read(cx).absolutize(&entry.path).ok()?;
-            let target_directory = if path.is_dir() {
-                path
-            } else {
-                path.parent()?.to_path_buf()
-            };
-            Some((target_directory, worktree))
-        }) else {
+    /// Collapses the directory and all of its descendants, so that expanding it again
+    /// shows its children collapsed, rather than the tree previously expanded below it.
+    fn collapse_entry_recursively(
+        &mut self,
+        worktree_id: WorktreeId,
+        entry_id: ProjectEntryId,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(worktree) = self.project.read(cx).worktree_for_id(worktree_id, cx) else {
             return;
         };
+        let worktree = worktree.read(cx);
+        let Some(entry) = worktree.entry_for_id(entry_id) else {
+            return;
+        };
+        if let Some(expanded_dir_ids) = self.expanded_dir_ids.get_mut(&worktree_id) {
+            expanded_dir_ids.retain(|dir_id| {
+                worktree
+                    .entry_for_id(*dir_id)
+                    .map_or(true, |dir| !dir.path.starts_with(&entry.path))
+            });
+        }
+        self.update_visible_entries(Some((worktree_id, entry_id)), cx);
+        cx.notify();
+    }
 
-        let mut paths_to_replace = Vec::new();
-        for path in &paths {
-            if let Some(name) = path.file_name() {
-                let mut target_path = target_directory.clone();
-                target_path.push(name);
-                if target_path.exists() {
-                    paths_to_replace.push((name.to_string_lossy().to_string(), path.clone()));
+    pub fn collapse_all_entries(&mut self, _: &CollapseAllEntries, cx: &mut ViewContext<Self>) {
+        // Keep the root entries expanded, so that every worktree still shows its top level.
+        // Worktrees whose root isn't loaded yet get theirs expanded once it is.
+        let project = self.project.read(cx);
+        for worktree in project.visible_worktrees(cx) {
+            let worktree = worktree.read(cx);
+            match worktree.root_entry() {
+                Some(root_entry) => {
+                    self.expanded_dir_ids
+                        .insert(worktree.id(), vec![root_entry.id]);
+                }
+                None => {
+                    self.expanded_dir_ids.remove(&worktree.id());
                 }
             }
         }
+        self.update_visible_entries(None, cx);
+        cx.notify();
+    }
 
-        cx.spawn(|this, mut cx| {
-            async move {
-                for (filename, original_path) in &paths_to_replace {
-                    let answer = cx
-                        .prompt(
-                            PromptLevel::Info,
-                            format!("A file or folder with name {filename} already exists in the destination folder. Do you want to replace it?").as_str(),
-                            None,
-                            &["Replace", "Cancel"],
-                        )
-                        .await?;
-                    if answer == 1 {
-                        if let Some(item_idx) = paths.iter().position(|p| p == original_path) {
-                            paths.remove(item_idx);
-                        }
-                    }
-                }
+    /// Expands every loaded directory of every worktree. Unloaded directories, e.g. gitignored
+    /// ones, stay collapsed rather than having their whole subtree loaded.
+    fn expand_all_entries(&mut self, _: &ExpandAllEntries, cx: &mut ViewContext<Self>) {
+        let dirs_to_expand = self
+            .project
+            .read(cx)
+            .visible_worktrees(cx)
+            .flat_map(|worktree| {
+                let worktree = worktree.read(cx);
+                let worktree_id = worktree.id();
+                let expanded_dir_ids = self.expanded_dir_ids.get(&worktree_id);
+                worktree
+                    .entries(true, 0)
+                    .filter(|entry| {
+                        matches!(entry.kind, EntryKind::Dir | EntryKind::PendingDir)
+                            && expanded_dir_ids
+                                .map_or(true, |ids| ids.binary_search(&entry.id).is_err())
+                    })
+                    .map(|entry| (worktree_id, entry.id))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        if dirs_to_expand.is_empty() {
+            return;
+        }
 
-                if paths.is_empty() {
-                    return Ok(());
+        self.project.update(cx, |project, cx| {
+            for (worktree_id, entry_id) in dirs_to_expand {
+                project.expand_entry(worktree_id, entry_id, cx);
+                let expanded_dir_ids = self.expanded_dir_ids.entry(worktree_id).or_default();
+                if let Err(ix) = expanded_dir_ids.binary_search(&entry_id) {
+                    expanded_dir_ids.insert(ix, entry_id);
                 }
+            }
+        });
+        self.update_visible_entries(None, cx);
+        cx.notify();
+    }
 
-                let task = worktree.update(&mut cx, |worktree, cx| {
-                    worktree.copy_external_entries(target_directory, paths, true, cx)
-                })?;
+    fn expand_all_one_level(&mut self, _: &ExpandAllOneLevel, cx: &mut ViewContext<Self>) {
+        self.expand_visible_dirs_one_level(None, cx);
+    }
 
-                let opened_entries = task.await?;
-                this.update(&mut cx, |this, cx| {
-                    if open_file_after_drop && !opened_entries.is_empty() {
-                        this.open_entry(opened_entries[0], true, true, false, cx);
-                    }
-                })
-            }
-            .log_err()
-        })
-        .detach();
+    fn collapse_worktree(&mut self, _: &CollapseWorktree, cx: &mut ViewContext<Self>) {
+        if let Some((worktree, _)) = self.selected_entry(cx) {
+            let worktree_id = worktree.id();
+            let Some(root_entry_id) = worktree.root_entry().map(|entry| entry.id) else {
+                return;
+            };
+            self.expanded_dir_ids
+                .insert(worktree_id, vec![root_entry_id]);
+            self.update_visible_entries(Some((worktree_id, root_entry_id)), cx);
+            self.autoscroll(cx);
+            cx.notify();
+        }
     }
 
-    fn drag_onto(
+    fn expand_worktree_one_level(
         &mut self,
-        selections: &DraggedSelection,
-        target_entry_id: ProjectEntryId,
-        is_file: bool,
+        _: &ExpandWorktreeOneLevel,
         cx: &mut ViewContext<Self>,
     ) {
-        let should_copy = cx.modifiers().alt;
-        if should_copy {
-            let _ = maybe!({
-                let project = self.project.read(cx);
-                let target_worktree = project.worktree_for_entry(target_entry_id, cx)?;
-                let target_entry = target_worktree
-                    .read(cx)
-                    .entry_for_id(target_entry_id)?
-                    .clone();
-                for selection in selections.items() {
-                    let new_path = self.create_paste_path(
-                        &selection,
-                        (target_worktree.clone(), &target_entry),
-                        cx,
-                    )?;
-                    self.project
-                        .update(cx, |project, cx| {
-                            project.copy_entry(selection.entry_id, new_path, cx)
-                        })
-                        .detach_and_log_err(cx)
-                }
-
-                Some(())
-            });
-        } else {
-            for selection in selections.items() {
-                self.move_entry(selection.entry_id, target_entry_id, is_file, cx);
-            }
+        if let Some(SelectedEntry { worktree_id, .. }) = self.selection {
+            self.expand_visible_dirs_one_level(Some(worktree_id), cx);
         }
     }
 
-    fn for_each_visible_entry(
-        &self,
-        range: Range<usize>,
-        cx: &mut ViewContext<ProjectPanel>,
-        mut callback: impl FnMut(ProjectEntryId, EntryDetails, &mut ViewContext<ProjectPanel>),
+    /// Expands every visible collapsed directory by exactly one level,
+    /// either in a single worktree or in all of them.
+    fn expand_visible_dirs_one_level(
+        &mut self,
+        worktree_id: Option<WorktreeId>,
+        cx: &mut ViewContext<Self>,
     ) {
-        let mut ix = 0;
-        for (worktree_id, visible_worktree_entries, entries_paths) in &self.visible_entries {
-            if ix >= range.end {
-                return;
+        let dirs_to_expand = self
+            .visible_entries
+            .iter()
+            .filter(|(id, _, _)| worktree_id.map_or(true, |worktree_id| *id == worktree_id))
+            .flat_map(|(worktree_id, entries, _)| {
+                let expanded_dir_ids = self.expanded_dir_ids.get(worktree_id);
+                entries
+                    .iter()
+                    .filter(move |entry| {
+                        entry.id != NEW_ENTRY_ID
+                            && entry.is_dir()
+                            && expanded_dir_ids
+                                .map_or(true, |ids| ids.binary_search(&entry.id).is_err())
+                    })
+                    .map(move |entry| (*worktree_id, entry.id))
+            })
+            .collect::<Vec<_>>();
+        if dirs_to_expand.is_empty() {
+            return;
+        }
+
+        self.project.update(cx, |project, cx| {
+            for (worktree_id, entry_id) in dirs_to_expand {
+                project.expand_entry(worktree_id, entry_id, cx);
+                let expanded_dir_ids = self.expanded_dir_ids.entry(worktree_id).or_default();
+                if let Err(ix) = expanded_dir_ids.binary_search(&entry_id) {
+                    expanded_dir_ids.insert(ix, entry_id);
+                }
             }
+        });
+        self.update_visible_entries(None, cx);
+        cx.notify();
+    }
 
-            if ix + visible_worktree_entries.len() <= range.start {
-                ix += visible_worktree_entries.len();
-                continue;
+    fn focus_on_folder(&mut self, _: &FocusOnFolder, cx: &mut ViewContext<Self>) {
+        if let Some((worktree, entry)) = self.selected_entry(cx) {
+            if entry.is_dir() {
+                let worktree_id = worktree.id();
+                let entry_id = entry.id;
+                self.focused_directory = Some((worktree_id, entry_id));
+                self.marked_entries.clear();
+                self.expand_entry(worktree_id, entry_id, cx);
+                self.update_visible_entries(Some((worktree_id, entry_id)), cx);
+                self.autoscroll(cx);
+                cx.notify();
             }
+        }
+    }
 
-            let end_ix = range.end.min(ix + visible_worktree_entries.len());
-            let (git_status_setting, show_file_icons, show_folder_icons) = {
-                let settings = ProjectPanelSettings::get_global(cx);
-                (
-                    settings.git_status,
-                    settings.file_icons,
-                    settings.folder_icons,
-                )
-            };
-            if let Some(worktree) = self.project.read(cx).worktree_for_id(*worktree_id, cx) {
-                let snapshot = worktree.read(cx).snapshot();
-                let root_name = OsStr::new(snapshot.root_name());
-                let expanded_entry_ids = self
-                    .expanded_dir_ids
-                    .get(&snapshot.id())
-                    .map(Vec::as_slice)
-                    .unwrap_or(&[]);
+    fn exit_folder_focus(&mut self, _: &ExitFolderFocus, cx: &mut ViewContext<Self>) {
+        if self.focused_directory.take().is_some() {
+            self.update_visible_entries(None, cx);
+            self.autoscroll(cx);
+            cx.notify();
+        }
+    }
 
-                let entry_range = range.start.saturating_sub(ix)..end_ix - ix;
-                let entries = entries_paths.get_or_init(|| {
-                    visible_worktree_entries
-                        .iter()
-                        .map(|e| (e.path.clone()))
-                        .collect()
-                });
-                for entry in visible_worktree_entries[entry_range].iter() {
-                    let status = git_status_setting.then(|| entry.git_status).flatten();
-                    let is_expanded = expanded_entry_ids.binary_search(&entry.id).is_ok();
-                    let icon = match entry.kind {
-                        EntryKind::File(_) => {
-                            if show_file_icons {
-                                FileIcons::get_icon(&entry.path, cx)
-                            } else {
-                                None
-                            }
+    fn is_inside_focused_directory(&self, worktree: &Worktree, entry_id: ProjectEntryId) -> bool {
+        let Some((focused_worktree_id, focused_entry_id)) = self.focused_directory else {
+            return true;
+        };
+        focused_worktree_id == worktree.id()
+            && worktree
+                .entry_for_id(focused_entry_id)
+                .zip(worktree.entry_for_id(entry_id))
+                .map_or(false, |(focused_entry, entry)| {
+                    entry.path.starts_with(&focused_entry.path)
+                })
+    }
+
+    fn toggle_expanded(&mut self, entry_id: ProjectEntryId, cx: &mut ViewContext<Self>) {
+        if let Some(worktree_id) = self.project.read(cx).worktree_id_for_entry(entry_id, cx) {
+            // Files with nested files expand without anything to load.
+            let is_dir = !self.has_nested_files(worktree_id, entry_id);
+            if let Some(expanded_dir_ids) = self.expanded_dir_ids.get_mut(&worktree_id) {
+                self.project.update(cx, |project, cx| {
+                    match expanded_dir_ids.binary_search(&entry_id) {
+                        Ok(ix) => {
+                            expanded_dir_ids.remove(ix);
                         }
-                        _ => {
-                            if show_folder_icons {
-                                FileIcons::get_folder_icon(is_expanded, cx)
-                            } else {
-                                FileIcons::get_chevron_icon(is_expanded, cx)
+                        Err(ix) => {
+                            if is_dir {
+                                project.expand_entry(worktree_id, entry_id, cx);
                             }
+                            expanded_dir_ids.insert(ix, entry_id);
                         }
-                    };
+                    }
+                });
+                self.update_visible_entries(Some((worktree_id, entry_id)), cx);
+                cx.focus(&self.focus_handle);
+                cx.notify();
+            }
+        }
+    }
 
-                    let (depth, difference) =
-                        ProjectPanel::calculate_depth_and_difference(entry, &entries);
+    fn has_nested_files(&self, worktree_id: WorktreeId, entry_id: ProjectEntryId) -> bool {
+        self.file_nesting
+            .get(&worktree_id)
+            .map_or(false, |nesting| nesting.sources.contains(&entry_id))
+    }
 
-                    let filename = match difference {
-                        diff if diff > 1 => entry
-                            .path
-                            .iter()
-                            .skip(entry.path.components().count() - diff)
-                            .collect::<PathBuf>()
-                            .to_str()
-                            .unwrap_or_default()
-                            .to_string(),
-                        _ => entry
-                            .path
-                            .file_name()
-                            .map(|name| name.to_string_lossy().into_owned())
-                            .unwrap_or_else(|| root_name.to_string_lossy().to_string()),
-                    };
-                    let selection = SelectedEntry {
-                        worktree_id: snapshot.id(),
-                        entry_id: entry.id,
-                    };
-                    let mut details = EntryDetails {
-                        filename,
-                        icon,
-                        path: entry.path.clone(),
-                        depth,
-                        kind: entry.kind,
-                        is_ignored: entry.is_ignored,
-                        is_expanded,
-                        is_selected: self.selection == Some(selection),
-                        is_marked: self.marked_entries.contains(&selection),
-                        is_editing: false,
-                        is_processing: false,
-                        is_cut: self
-                            .clipboard
-                            .as_ref()
-                            .map_or(false, |e| e.is_cut() && e.items().contains(&selection)),
-                        git_status: status,
-                        is_private: entry.is_private,
-                        worktree_id: *worktree_id,
-                        canonical_path: entry.canonical_path.clone(),
-                    };
+    fn select_prev(&mut self, _: &SelectPrev, cx: &mut ViewContext<Self>) {
+        if let Some(selection) = self.selection {
+            let (mut worktree_ix, mut entry_ix, _) =
+                self.index_for_selection(selection).unwrap_or_default();
+            if entry_ix > 0 {
+                entry_ix -= 1;
+            } else if worktree_ix > 0 {
+                worktree_ix -= 1;
+                entry_ix = self.visible_entries[worktree_ix].1.len() - 1;
+            } else {
+                return;
+            }
 
-                    if let Some(edit_state) = &self.edit_state {
-                        let is_edited_entry = if edit_state.is_new_entry {
-                            entry.id == NEW_ENTRY_ID
-                        } else {
-                            entry.id == edit_state.entry_id
-                        };
-
-                        if is_edited_entry {
-                            if let Some(processing_filename) = &edit_state.processing_filename {
-                                details.is_processing = true;
-                                details.filename.clear();
-                                details.filename.push_str(processing_filename);
-                            } else {
-                                if edit_state.is_new_entry {
-                                    details.filename.clear();
-                                }
-                                details.is_editing = true;
-                            }
-                        }
-                    }
-
-                    callback(entry.id, details, cx);
-                }
+            let (worktree_id, worktree_entries, _) = &self.visible_entries[worktree_ix];
+            let selection = SelectedEntry {
+                worktree_id: *worktree_id,
+                entry_id: worktree_entries[entry_ix].id,
+            };
+            self.selection = Some(selection);
+            if cx.modifiers().shift {
+                self.marked_entries.insert(selection);
             }
-            ix = end_ix;
+            self.autoscroll(cx);
+            cx.notify();
+        } else {
+            self.select_first(&SelectFirst {}, cx);
         }
     }
 
-    fn calculate_depth_and_difference(
-        entry: &Entry,
-        visible_worktree_entries: &HashSet<Arc<Path>>,
-    ) -> (usize, usize) {
-        let (depth, difference) = entry
-            .path
-            .ancestors()
-            .skip(1) // Skip the entry itself
-            .find_map(|ancestor| {
-                if let Some(parent_entry) = visible_worktree_entries.get(ancestor) {
-                    let entry_path_components_count = entry.path.components().count();
-                    let parent_path_components_count = parent_entry.components().count();
-                    let difference = entry_path_components_count - parent_path_components_count;
-                    let depth = parent_entry
-                        .ancestors()
-                        .skip(1)
-                        .filter(|ancestor| visible_worktree_entries.contains(*ancestor))
-                        .count();
-                    Some((depth + 1, difference))
-                } else {
-                    None
-                }
-            })
-            .unwrap_or((0, 0));
+    fn confirm(&mut self, _: &Confirm, cx: &mut ViewContext<Self>) {
+        if let Some(task) = self.confirm_edit(cx) {
+            task.detach_and_notify_err(cx);
+        }
+    }
 
-        (depth, difference)
+    fn open(&mut self, _: &Open, cx: &mut ViewContext<Self>) {
+        self.open_internal(false, true, false, cx);
     }
 
-    fn render_entry(
+    fn open_permanent(&mut self, _: &OpenPermanent, cx: &mut ViewContext<Self>) {
+        self.open_internal(true, false, true, cx);
+    }
+
+    fn open_to_the_side_keep_focus(
+        &mut self,
+        _: &OpenToTheSideKeepFocus,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some((_, entry)) = self.selected_entry(cx) else {
+            return;
+        };
+        if !entry.is_file() {
+            return;
+        }
+        let entry_id = entry.id;
+        if let Some(special_file) = self.special_file(entry_id, cx) {
+            self.prompt_about_special_files(&[special_file], "opened", cx);
+            return;
+        }
+        cx.emit(Event::OpenedEntryToTheSide { entry_id });
+    }
+
+    /// Items of the workspace's panes showing the entry, with the pane they're in.
+    fn open_items_for_entry(
         &self,
         entry_id: ProjectEntryId,
-        details: EntryDetails,
-        cx: &mut ViewContext<Self>,
-    ) -> Stateful<Div> {
-        let kind = details.kind;
-        let settings = ProjectPanelSettings::get_global(cx);
-        let show_editor = details.is_editing && !details.is_processing;
-        let selection = SelectedEntry {
-            worktree_id: details.worktree_id,
-            entry_id,
+        cx: &AppContext,
+    ) -> Vec<(View<Pane>, EntityId)> {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return Vec::new();
         };
-        let is_marked = self.marked_entries.contains(&selection);
-        let is_active = self
-            .selection
-            .map_or(false, |selection| selection.entry_id == entry_id);
-        let width = self.size(cx);
-        let filename_text_color =
-            entry_git_aware_label_color(details.git_status, details.is_ignored, is_marked);
-        let file_name = details.filename.clone();
-        let mut icon = details.icon.clone();
-        if settings.file_icons && show_editor && details.kind.is_file() {
-            let filename = self.filename_editor.read(cx).text(cx);
-            if filename.len() > 2 {
-                icon = FileIcons::get_icon(Path::new(&filename), cx);
+        workspace
+            .read(cx)
+            .panes()
+            .iter()
+            .flat_map(|pane| {
+                pane.read(cx)
+                    .items()
+                    .filter(|item| item.project_entry_ids(cx).as_slice() == [entry_id])
+                    .map(|item| (pane.clone(), item.item_id()))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    fn close_editors_for_file(&mut self, _: &CloseEditorsForFile, cx: &mut ViewContext<Self>) {
+        if let Some((_, entry)) = self.selected_entry(cx) {
+            if entry.is_file() {
+                self.close_editors_for_entry(entry.id, cx);
             }
         }
+    }
 
-        let canonical_path = details
-            .canonical_path
-            .as_ref()
-            .map(|f| f.to_string_lossy().to_string());
-        let path = details.path.clone();
+    fn close_editors_for_entry(&mut self, entry_id: ProjectEntryId, cx: &mut ViewContext<Self>) {
+        for (pane, item_id) in self.open_items_for_entry(entry_id, cx) {
+            pane.update(cx, |pane, cx| {
+                pane.close_item_by_id(item_id, SaveIntent::Close, cx)
+                    .detach_and_log_err(cx)
+            });
+        }
+    }
 
-        let depth = details.depth;
-        let worktree_id = details.worktree_id;
-        let selections = Arc::new(self.marked_entries.clone());
+    fn open_internal(
+        &mut self,
+        mark_selected: bool,
+        allow_preview: bool,
+        focus_opened_item: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let Some((_, entry)) = self.selected_entry(cx) {
+            if entry.is_file() {
+                self.open_entry(
+                    entry.id,
+                    mark_selected,
+                    focus_opened_item,
+                    allow_preview,
+                    cx,
+                );
+            } else {
+                self.toggle_expanded(entry.id, cx);
+            }
+        }
+    }
 
-        let dragged_selection = DraggedSelection {
-            active_selection: selection,
-            marked_selections: selections,
+    fn confirm_edit(&mut self, cx: &mut ViewContext<Self>) -> Option<Task<Result<()>>> {
+        let edit_state = self.edit_state.as_mut()?;
+        cx.focus(&self.focus_handle);
+
+        let worktree_id = edit_state.worktree_id;
+        let is_new_entry = edit_state.is_new_entry;
+        let filename = self.filename_editor.read(cx).text(cx);
+        edit_state.is_dir = edit_state.is_dir
+            || (edit_state.is_new_entry && filename.ends_with(std::path::MAIN_SEPARATOR));
+        let is_dir = edit_state.is_dir;
+        let worktree = self.project.read(cx).worktree_for_id(worktree_id, cx)?;
+        let entry = worktree.read(cx).entry_for_id(edit_state.entry_id)?.clone();
+
+        let case_sensitive = self.is_case_sensitive(worktree.read(cx), cx);
+        // A rename that only changes the case of the name must not collide with the entry itself.
+        let path_already_exists = |path| {
+            find_entry_for_path(worktree.read(cx), path, case_sensitive)
+                .map_or(false, |existing| is_new_entry || existing.id != entry.id)
         };
-        div()
-            .id(entry_id.to_proto() as usize)
-            .on_drag_move::<ExternalPaths>(cx.listener(
-                move |this, event: &DragMoveEvent<ExternalPaths>, cx| {
-                    if event.bounds.contains(&event.event.position) {
-                        if this.last_external_paths_drag_over_entry == Some(entry_id) {
-                            return;
-                        }
-                        this.last_external_paths_drag_over_entry = Some(entry_id);
-                        this.marked_entries.clear();
+        let edit_task;
+        let edited_entry_id;
+        let new_abs_path;
+        let mut new_file_header = None;
+        if is_new_entry {
+            self.selection = Some(SelectedEntry {
+                worktree_id,
+                entry_id: NEW_ENTRY_ID,
+            });
+            let new_path = entry.path.join(&filename.trim_start_matches('/'));
+            if path_already_exists(new_path.as_path()) {
+                return None;
+            }
 
-                        let Some((worktree, path, entry)) = maybe!({
-                            let worktree = this
-                                .project
-                                .read(cx)
-                                .worktree_for_id(selection.worktree_id, cx)?;
-                            let worktree = worktree.read(cx);
-                            let abs_path = worktree.absolutize(&path).log_err()?;
-                            let path = if abs_path.is_dir() {
-                                path.as_ref()
-                            } else {
-                                path.parent()?
-                            };
-                            let entry = worktree.entry_for_path(path)?;
-                            Some((worktree, path, entry))
-                        }) else {
-                            return;
-                        };
+            new_abs_path = worktree.read(cx).abs_path().join(&new_path);
+            new_file_header = (!is_dir)
+                .then(|| {
+                    license_header_for_path(
+                        &ProjectPanelSettings::get_global(cx).license_headers,
+                        &new_path,
+                    )
+                })
+                .flatten()
+                .map(|header| with_license_header("", header).unwrap_or_default());
+            edited_entry_id = NEW_ENTRY_ID;
+            edit_task = self.project.update(cx, |project, cx| {
+                project.create_entry((worktree_id, &new_path), is_dir, cx)
+            });
+        } else {
+            let new_path = if let Some(parent) = entry.path.clone().parent() {
+                parent.join(&filename)
+            } else {
+                filename.clone().into()
+            };
+            if path_already_exists(new_path.as_path()) {
+                return None;
+            }
 
-                        this.marked_entries.insert(SelectedEntry {
-                            entry_id: entry.id,
-                            worktree_id: worktree.id(),
-                        });
+            new_abs_path = worktree.read(cx).abs_path().join(&new_path);
+            edited_entry_id = entry.id;
+            edit_task = self.project.update(cx, |project, cx| {
+                project.rename_entry(entry.id, new_path.as_path(), cx)
+            });
+        };
 
-                        for entry in worktree.child_entries(path) {
-                            this.marked_entries.insert(SelectedEntry {
-                                entry_id: entry.id,
-                                worktree_id: worktree.id(),
-                            });
-                        }
+        edit_state.processing_filename = Some(filename);
+        cx.notify();
 
-                        cx.notify();
+        let old_path = entry.path.to_path_buf();
+        let fs = self.fs.clone();
+        Some(cx.spawn(|project_panel, mut cx| async move {
+            let new_entry = edit_task.await;
+            project_panel.update(&mut cx, |project_panel, cx| {
+                project_panel.edit_state.take();
+                cx.notify();
+            })?;
+
+            match new_entry {
+                Err(e) => {
+                    project_panel.update(&mut cx, |project_panel, cx| {
+                        project_panel.marked_entries.clear();
+                        project_panel.update_visible_entries(None, cx);
+                    }).ok();
+                    if is_too_long_for_windows(&new_abs_path) {
+                        Err(e.context(format!(
+                            "{} is longer than {WINDOWS_MAX_PATH} characters. Try using shorter file or folder names",
+                            new_abs_path.display()
+                        )))?;
                     }
-                },
-            ))
-            .on_drop(
-                cx.listener(move |this, external_paths: &ExternalPaths, cx| {
-                    this.last_external_paths_drag_over_entry = None;
-                    this.marked_entries.clear();
-                    this.drop_external_files(external_paths.paths(), entry_id, cx);
-                    cx.stop_propagation();
-                }),
-            )
-            .on_drag(dragged_selection, move |selection, cx| {
-                cx.new_view(|_| DraggedProjectEntryView {
-                    details: details.clone(),
-                    width,
-                    selection: selection.active_selection,
-                    selections: selection.marked_selections.clone(),
-                })
-            })
-            .drag_over::<DraggedSelection>(|style, _, cx| {
-                style.bg(cx.theme().colors().drop_target_background)
-            })
-            .on_drop(cx.listener(move |this, selections: &DraggedSelection, cx| {
-                this.drag_onto(selections, entry_id, kind.is_file(), cx);
-            }))
-            .child(
-                ListItem::new(entry_id.to_proto() as usize)
-                    .indent_level(depth)
-                    .indent_step_size(px(settings.indent_size))
-                    .selected(is_marked || is_active)
-                    .when_some(canonical_path, |this, path| {
-                        this.end_slot::<AnyElement>(
-                            div()
-                                .id("symlink_icon")
-                                .tooltip(move |cx| {
-                                    Tooltip::text(format!("{path} • Symbolic Link"), cx)
-                                })
-                                .child(
-                                    Icon::new(IconName::ArrowUpRight)
-                                        .size(IconSize::Indicator)
-                                        .color(filename_text_color),
-                                )
-                                .into_any_element(),
-                        )
-                    })
-                    .child(if let Some(icon) = &icon {
-                        h_flex().child(Icon::from_path(icon.to_string()).color(filename_text_color))
-                    } else {
-                        h_flex()
-                            .size(IconSize::default().rems())
-                            .invisible()
-                            .flex_none()
-                    })
-                    .child(
-                        if let (Some(editor), true) = (Some(&self.filename_editor), show_editor) {
-                            h_flex().h_6().w_full().child(editor.clone())
+                    Err(e)?;
+                }
+                Ok(CreatedEntry::Included(new_entry)) => {
+                    if let Some(header) = new_file_header {
+                        fs.atomic_write(new_abs_path.clone(), header).await.log_err();
+                    }
+                    project_panel.update(&mut cx, |project_panel, cx| {
+                        let new_path = new_entry.path.to_path_buf();
+                        let mut operation = FileOperation::default();
+                        if is_new_entry {
+                            operation.push(
+                                worktree_id,
+                                FileOp::Create {
+                                    path: new_path.clone(),
+                                    is_dir,
+                                },
+                                FileOp::Trash { path: new_path },
+                            );
                         } else {
-                            h_flex().h_6().child(
-                                Label::new(file_name)
-                                    .single_line()
-                                    .color(filename_text_color),
-                            )
+                            operation.push(
+                                worktree_id,
+                                FileOp::Rename {
+                                    from: old_path.clone(),
+                                    to: new_path.clone(),
+                                },
+                                FileOp::Rename {
+                                    from: new_path,
+                                    to: old_path,
+                                },
+                            );
                         }
-                        .ml_1(),
-                    )
-                    .on_click(cx.listener(move |this, event: &gpui::ClickEvent, cx| {
-                        if event.down.button == MouseButton::Right || event.down.first_mouse {
-                            return;
+                        project_panel.operation_history.push(operation);
+                        if let Some(selection) = &mut project_panel.selection {
+                            if selection.entry_id == edited_entry_id {
+                                selection.worktree_id = worktree_id;
+                                selection.entry_id = new_entry.id;
+                                project_panel.marked_entries.clear();
+                                project_panel.expand_to_selection(cx);
+                            }
                         }
-                        if !show_editor {
-                            if let Some(selection) =
-                                this.selection.filter(|_| event.down.modifiers.shift)
-                            {
-                                let current_selection = this.index_for_selection(selection);
-                                let target_selection = this.index_for_selection(SelectedEntry {
-                                    entry_id,
-                                    worktree_id,
-                                });
-                                if let Some(((_, _, source_index), (_, _, target_index))) =
-                                    current_selection.zip(target_selection)
-                                {
-                                    let range_start = source_index.min(target_index);
-                                    let range_end = source_index.max(target_index) + 1; // Make the range inclusive.
-                                    let mut new_selections = BTreeSet::new();
-                                    this.for_each_visible_entry(
-                                        range_start..range_end,
-                                        cx,
-                                        |entry_id, details, _| {
-                                            new_selections.insert(SelectedEntry {
-                                                entry_id,
-                                                worktree_id: details.worktree_id,
-                                            });
-                                        },
-                                    );
-
-                                    this.marked_entries = this
-                                        .marked_entries
-                                        .union(&new_selections)
-                                        .cloned()
-                                        .collect();
+                        project_panel.update_visible_entries(None, cx);
+                        if is_new_entry && !is_dir {
+                            project_panel.open_new_file(new_entry.id, cx);
+                        }
+                        cx.notify();
+                    })?;
+                }
+                Ok(CreatedEntry::Excluded { abs_path }) => {
+                    if let Some(open_task) = project_panel
+                        .update(&mut cx, |project_panel, cx| {
+                            project_panel.marked_entries.clear();
+                            project_panel.update_visible_entries(None, cx);
 
-                                    this.selection = Some(SelectedEntry {
-                                        entry_id,
-                                        worktree_id,
-                                    });
-                                    // Ensure that the current entry is selected.
-                                    this.marked_entries.insert(SelectedEntry {
-                                        entry_id,
-                                        worktree_id,
-                                    });
-                                }
-                            } else if event.down.modifiers.secondary() {
-                                if !this.marked_entries.insert(selection) {
-                                    this.marked_entries.remove(&selection);
-                                }
-                            } else if kind.is_dir() {
-                                this.toggle_expanded(entry_id, cx);
+                            if is_dir {
+                                project_panel.project.update(cx, |_, cx| {
+                                    cx.emit(project::Event::Notification(format!(
+                                        "Created an excluded directory at {abs_path:?}.\nAlter `file_scan_exclusions` in the settings to show it in the panel"
+                                    )))
+                                });
+                                None
+                            } else if ProjectPanelSettings::get_global(cx).open_new_files
+                                == OpenNewFiles::None
+                            {
+                                None
                             } else {
-                                let click_count = event.up.click_count;
-                                if click_count > 1 && event.down.modifiers.secondary() {
-                                    this.split_entry(entry_id, cx);
-                                } else {
-                                    this.open_entry(
-                                        entry_id,
-                                        cx.modifiers().secondary(),
-                                        click_count > 1,
-                                        click_count == 1,
-                                        cx,
-                                    );
-                                }
+                                project_panel
+                                    .workspace
+                                    .update(cx, |workspace, cx| {
+                                        workspace.open_abs_path(abs_path, true, cx)
+                                    })
+                                    .ok()
                             }
-                        }
-                    }))
-                    .on_secondary_mouse_down(cx.listener(
-                        move |this, event: &MouseDownEvent, cx| {
-                            // Stop propagation to prevent the catch-all context menu for the project
-                            // panel from being deployed.
-                            cx.stop_propagation();
-                            this.deploy_context_menu(event.position, entry_id, cx);
-                        },
-                    )),
-            )
-            .border_1()
-            .border_r_2()
-            .rounded_none()
-            .hover(|style| {
-                if is_active {
-                    style
+                        })
+                        .ok()
+                        .flatten()
+                    {
+                        let _ = open_task.await?;
+                    }
+                }
+            }
+            Ok(())
+        }))
+    }
+
+    /// Cancels the edit when its entry was removed by someone else, and follows the entry when it
+    /// was moved, so that a new entry never ends up in a folder that was recreated for it.
+    fn update_edit_state_target(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(edit_state) = self
+            .edit_state
+            .as_mut()
+            .filter(|state| state.processing_filename.is_none())
+        else {
+            return;
+        };
+        let current_path = self
+            .project
+            .read(cx)
+            .worktree_for_id(edit_state.worktree_id, cx)
+            .and_then(|worktree| {
+                worktree
+                    .read(cx)
+                    .entry_for_id(edit_state.entry_id)
+                    .map(|entry| entry.path.clone())
+            });
+        let message = match current_path {
+            Some(path) if path == edit_state.path => return,
+            Some(path) => {
+                let message = if edit_state.is_new_entry {
+                    format!(
+                        "{:?} was moved to {path:?}, the new entry will be created there",
+                        edit_state.path
+                    )
                 } else {
-                    let hover_color = cx.theme().colors().ghost_element_hover;
-                    style.bg(hover_color).border_color(hover_color)
+                    format!("{:?} was moved to {path:?}", edit_state.path)
+                };
+                edit_state.path = path;
+                message
+            }
+            None => {
+                let message = if edit_state.is_new_entry {
+                    format!(
+                        "{:?} was removed, the new entry was not created",
+                        edit_state.path
+                    )
+                } else {
+                    format!(
+                        "{:?} was removed, the rename was cancelled",
+                        edit_state.path
+                    )
+                };
+                self.edit_state = None;
+                self.marked_entries.clear();
+                if self.filename_editor.focus_handle(cx).is_focused(cx) {
+                    cx.focus(&self.focus_handle);
                 }
-            })
-            .when(is_marked || is_active, |this| {
-                let colors = cx.theme().colors();
-                this.when(is_marked, |this| this.bg(colors.ghost_element_selected))
-                    .border_color(colors.ghost_element_selected)
-            })
-            .when(
-                is_active && self.focus_handle.contains_focused(cx),
-                |this| this.border_color(Color::Selected.color(cx)),
-            )
+                message
+            }
+        };
+        self.project
+            .update(cx, |_, cx| cx.emit(project::Event::Notification(message)));
     }
 
-    fn render_scrollbar(
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        if self.filter_editor.focus_handle(cx).is_focused(cx) {
+            self.filter_editor.update(cx, |editor, cx| editor.clear(cx));
+        }
+        self.edit_state = None;
+        self.update_visible_entries(None, cx);
+        self.marked_entries.clear();
+        cx.focus(&self.focus_handle);
+        cx.notify();
+    }
+
+    fn open_entry(
+        &mut self,
+        entry_id: ProjectEntryId,
+        mark_selected: bool,
+        focus_opened_item: bool,
+        allow_preview: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let Some(special_file) = self.special_file(entry_id, cx) {
+            self.prompt_about_special_files(&[special_file], "opened", cx);
+            return;
+        }
+        cx.emit(Event::OpenedEntry {
+            entry_id,
+            focus_opened_item,
+            allow_preview,
+            mark_selected,
+        });
+    }
+
+    /// Explains that FIFOs, sockets and devices are never read, as reading them may block.
+    fn prompt_about_special_files(
         &self,
-        items_count: usize,
+        paths: &[Arc<Path>],
+        operation: &str,
         cx: &mut ViewContext<Self>,
-    ) -> Option<Stateful<Div>> {
-        let settings = ProjectPanelSettings::get_global(cx);
-        if settings.scrollbar.show == ShowScrollbar::Never {
-            return None;
+    ) {
+        let names = paths
+            .iter()
+            .map(|path| path.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let message = format!("{names} can't be {operation}");
+        let prompt = cx.prompt(
+            PromptLevel::Info,
+            &message,
+            Some(
+                "Sockets, FIFOs and devices are special files, \
+                 which can't be read like regular files.",
+            ),
+            &["OK"],
+        );
+        cx.foreground_executor()
+            .spawn(async {
+                prompt.await.ok();
+            })
+            .detach();
+    }
+
+    fn open_new_file(&mut self, entry_id: ProjectEntryId, cx: &mut ViewContext<Self>) {
+        match ProjectPanelSettings::get_global(cx).open_new_files {
+            OpenNewFiles::ActivePane => self.open_entry(entry_id, false, true, false, cx),
+            OpenNewFiles::Split => self.split_entry(entry_id, cx),
+            OpenNewFiles::None => {}
         }
-        let scroll_handle = self.scroll_handle.0.borrow();
+    }
 
-        let height = scroll_handle
-            .last_item_height
-            .filter(|_| self.show_scrollbar || self.scrollbar_drag_thumb_offset.get().is_some())?;
+    fn split_entry(&mut self, entry_id: ProjectEntryId, cx: &mut ViewContext<Self>) {
+        cx.emit(Event::SplitEntry { entry_id });
+    }
 
-        let total_list_length = height.0 as f64 * items_count as f64;
-        let current_offset = scroll_handle.base_handle.offset().y.0.min(0.).abs() as f64;
-        let mut percentage = current_offset / total_list_length;
-        let end_offset = (current_offset + scroll_handle.base_handle.bounds().size.height.0 as f64)
-            / total_list_length;
-        // Uniform scroll handle might briefly report an offset greater than the length of a list;
-        // in such case we'll adjust the starting offset as well to keep the scrollbar thumb length stable.
-        let overshoot = (end_offset - 1.).clamp(0., 1.);
-        if overshoot > 0. {
-            percentage -= overshoot;
+    /// Cmd-clicking marks the entry, cmd-double-clicking a file opens it in a split.
+    fn secondary_click_entry(
+        &mut self,
+        selection: SelectedEntry,
+        is_file: bool,
+        click_count: usize,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let is_split = click_count > 1 && is_file;
+        // The first click of a double click toggled the mark already, the second one toggles it
+        // back when splitting.
+        if !is_split || click_count == 2 {
+            if !self.marked_entries.insert(selection) {
+                self.marked_entries.remove(&selection);
+            }
         }
-        const MINIMUM_SCROLLBAR_PERCENTAGE_HEIGHT: f64 = 0.005;
-        if percentage + MINIMUM_SCROLLBAR_PERCENTAGE_HEIGHT > 1.0 || end_offset > total_list_length
-        {
-            return None;
+        if is_split {
+            self.split_entry(selection.entry_id, cx);
         }
-        if total_list_length < scroll_handle.base_handle.bounds().size.height.0 as f64 {
-            return None;
+        cx.notify();
+    }
+
+    /// Rescans the expanded directories of the selected worktree, or of all worktrees when
+    /// nothing is selected, picking up changes that the file system events missed.
+    fn refresh(&mut self, _: &Refresh, cx: &mut ViewContext<Self>) {
+        let worktree_ids = match self.selection {
+            Some(selection) => vec![selection.worktree_id],
+            None => self.expanded_dir_ids.keys().copied().collect(),
+        };
+        let mut tasks = Vec::new();
+        for worktree_id in worktree_ids {
+            let Some(expanded_dir_ids) = self.expanded_dir_ids.get(&worktree_id) else {
+                continue;
+            };
+            for entry_id in expanded_dir_ids {
+                tasks.extend(self.project.update(cx, |project, cx| {
+                    project.expand_entry(worktree_id, *entry_id, cx)
+                }));
+            }
         }
-        let end_offset = end_offset.clamp(percentage + MINIMUM_SCROLLBAR_PERCENTAGE_HEIGHT, 1.);
-        Some(
-            div()
-                .occlude()
-                .id("project-panel-scroll")
-                .on_mouse_move(cx.listener(|_, _, cx| {
-                    cx.notify();
-                    cx.stop_propagation()
-                }))
-                .on_hover(|_, cx| {
-                    cx.stop_propagation();
-                })
-                .on_any_mouse_down(|_, cx| {
-                    cx.stop_propagation();
-                })
-                .on_mouse_up(
-                    MouseButton::Left,
-                    cx.listener(|this, _, cx| {
-                        if this.scrollbar_drag_thumb_offset.get().is_none()
-                            && !this.focus_handle.contains_focused(cx)
-                        {
-                            this.hide_scrollbar(cx);
-                            cx.notify();
-                        }
 
-                        cx.stop_propagation();
-                    }),
-                )
-                .on_scroll_wheel(cx.listener(|_, _, cx| {
-                    cx.notify();
-                }))
-                .h_full()
-                .absolute()
-                .right_0()
-                .top_0()
-                .bottom_0()
-                .w_3()
-                .cursor_default()
-                .child(ProjectPanelScrollbar::new(
-                    percentage as f32..end_offset as f32,
-                    self.scroll_handle.clone(),
-                    self.scrollbar_drag_thumb_offset.clone(),
-                    cx.view().clone().into(),
-                    items_count,
-                )),
-        )
-    }
-
-    fn dispatch_context(&self, cx: &ViewContext<Self>) -> KeyContext {
-        let mut dispatch_context = KeyContext::new_with_defaults();
-        dispatch_context.add("ProjectPanel");
-        dispatch_context.add("menu");
-
-        let identifier = if self.filename_editor.focus_handle(cx).is_focused(cx) {
-            "editing"
-        } else {
-            "not_editing"
-        };
-
-        dispatch_context.add(identifier);
-        dispatch_context
+        cx.spawn(|this, mut cx| async move {
+            for task in tasks {
+                task.await.log_err();
+            }
+            this.update(&mut cx, |this, cx| {
+                this.update_visible_entries(None, cx);
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
     }
 
-    fn should_autohide_scrollbar(cx: &AppContext) -> bool {
-        cx.try_global::<ScrollbarAutoHide>()
-            .map_or_else(|| cx.should_auto_hide_scrollbars(), |autohide| autohide.0)
+    fn new_file(&mut self, _: &NewFile, cx: &mut ViewContext<Self>) {
+        self.add_entry(false, cx)
     }
 
-    fn hide_scrollbar(&mut self, cx: &mut ViewContext<Self>) {
-        const SCROLLBAR_SHOW_INTERVAL: Duration = Duration::from_secs(1);
-        if !Self::should_autohide_scrollbar(cx) {
-            return;
-        }
-        self.hide_scrollbar_task = Some(cx.spawn(|panel, mut cx| async move {
-            cx.background_executor()
-                .timer(SCROLLBAR_SHOW_INTERVAL)
-                .await;
-            panel
-                .update(&mut cx, |panel, cx| {
-                    panel.show_scrollbar = false;
-                    cx.notify();
-                })
-                .log_err();
-        }))
+    fn new_directory(&mut self, _: &NewDirectory, cx: &mut ViewContext<Self>) {
+        self.add_entry(true, cx)
     }
 
-    fn reveal_entry(
-        &mut self,
-        project: Model<Project>,
-        entry_id: ProjectEntryId,
-        skip_ignored: bool,
-        cx: &mut ViewContext<'_, ProjectPanel>,
-    ) {
-        if let Some(worktree) = project.read(cx).worktree_for_entry(entry_id, cx) {
-            let worktree = worktree.read(cx);
-            if skip_ignored
-                && worktree
-                    .entry_for_id(entry_id)
-                    .map_or(true, |entry| entry.is_ignored)
+    fn add_entry(&mut self, is_dir: bool, cx: &mut ViewContext<Self>) {
+        if let Some(SelectedEntry {
+            worktree_id,
+            entry_id,
+        }) = self.selection
+        {
+            let directory_id;
+            let directory_path;
+            if let Some((worktree, expanded_dir_ids)) = self
+                .project
+                .read(cx)
+                .worktree_for_id(worktree_id, cx)
+                .zip(self.expanded_dir_ids.get_mut(&worktree_id))
             {
+                let worktree = worktree.read(cx);
+                if let Some(mut entry) = worktree.entry_for_id(entry_id) {
+                    loop {
+                        if entry.is_dir() {
+                            if let Err(ix) = expanded_dir_ids.binary_search(&entry.id) {
+                                expanded_dir_ids.insert(ix, entry.id);
+                            }
+                            directory_id = entry.id;
+                            directory_path = entry.path.clone();
+                            break;
+                        } else {
+                            if let Some(parent_path) = entry.path.parent() {
+                                if let Some(parent_entry) = worktree.entry_for_path(parent_path) {
+                                    entry = parent_entry;
+                                    continue;
+                                }
+                            }
+                            return;
+                        }
+                    }
+                } else {
+                    return;
+                };
+            } else {
                 return;
-            }
-
-            let worktree_id = worktree.id();
+            };
             self.marked_entries.clear();
-            self.expand_entry(worktree_id, entry_id, cx);
-            self.update_visible_entries(Some((worktree_id, entry_id)), cx);
+            self.edit_state = Some(EditState {
+                worktree_id,
+                entry_id: directory_id,
+                path: directory_path,
+                is_new_entry: true,
+                is_dir,
+                processing_filename: None,
+            });
+            self.filename_editor.update(cx, |editor, cx| {
+                editor.clear(cx);
+                editor.focus(cx);
+            });
+            self.update_visible_entries(Some((worktree_id, NEW_ENTRY_ID)), cx);
             self.autoscroll(cx);
             cx.notify();
         }
     }
-}
 
-impl Render for ProjectPanel {
-    fn render(&mut self, cx: &mut gpui::ViewContext<Self>) -> impl IntoElement {
-        let has_worktree = self.visible_entries.len() != 0;
-        let project = self.project.read(cx);
+    fn rename(&mut self, _: &Rename, cx: &mut ViewContext<Self>) {
+        if let Some(SelectedEntry {
+            worktree_id,
+            entry_id,
+        }) = self.selection
+        {
+            if let Some(worktree) = self.project.read(cx).worktree_for_id(worktree_id, cx) {
+                if let Some(entry) = worktree.read(cx).entry_for_id(entry_id) {
+                    self.edit_state = Some(EditState {
+                        worktree_id,
+                        entry_id,
+                        path: entry.path.clone(),
+                        is_new_entry: false,
+                        is_dir: entry.is_dir(),
+                        processing_filename: None,
+                    });
+                    let file_name = entry
+                        .path
+                        .file_name()
+                        .map(|s| s.to_string_lossy())
+                        .unwrap_or_default()
+                        .to_string();
+                    let file_stem = entry.path.file_stem().map(|s| s.to_string_lossy());
+                    let selection_end =
+                        file_stem.map_or(file_name.len(), |file_stem| file_stem.len());
+                    self.filename_editor.update(cx, |editor, cx| {
+                        editor.set_text(file_name, cx);
+                        editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                            s.select_ranges([0..selection_end])
+                        });
+                        editor.focus(cx);
+                    });
+                    self.update_visible_entries(None, cx);
+                    self.autoscroll(cx);
+                    cx.notify();
+                }
+            }
+        }
+    }
 
-        if has_worktree {
-            let items_count = self
-                .visible_entries
-                .iter()
-                .map(|(_, worktree_entries, _)| worktree_entries.len())
-                .sum();
+    fn trash(&mut self, action: &Trash, cx: &mut ViewContext<Self>) {
+        let trash = ProjectPanelSettings::get_global(cx).enable_trash;
+        // Bindings that trash without asking would otherwise delete permanently, as silently.
+        self.remove(trash, action.skip_prompt && trash, cx);
+    }
 
-            h_flex()
-                .id("project-panel")
-                .group("project-panel")
-                .size_full()
-                .relative()
-                .on_hover(cx.listener(|this, hovered, cx| {
-                    if *hovered {
-                        this.show_scrollbar = true;
-                        this.hide_scrollbar_task.take();
-                        cx.notify();
-                    } else if !this.focus_handle.contains_focused(cx) {
-                        this.hide_scrollbar(cx);
+    fn delete(&mut self, action: &Delete, cx: &mut ViewContext<Self>) {
+        self.remove(false, action.skip_prompt, cx);
+    }
+
+    fn remove(&mut self, trash: bool, skip_prompt: bool, cx: &mut ViewContext<'_, ProjectPanel>) {
+        maybe!({
+            if self.marked_entries.is_empty() && self.selection.is_none() {
+                return None;
+            }
+            let project = self.project.read(cx);
+            let delete_external_entries =
+                ProjectPanelSettings::get_global(cx).delete_external_entries;
+            let (items_to_delete, external_items) = self
+                .marked_entries()
+                .into_iter()
+                .partition::<Vec<_>, _>(|selection| {
+                    delete_external_entries
+                        || !project.worktree_for_id(selection.worktree_id, cx).map_or(
+                            false,
+                            |worktree| {
+                                is_reached_through_symlink(worktree.read(cx), selection.entry_id)
+                            },
+                        )
+                });
+            if !external_items.is_empty() {
+                let message = format!(
+                    "Left out {} {} outside of the project, reached through a symlink",
+                    external_items.len(),
+                    if external_items.len() == 1 {
+                        "entry"
+                    } else {
+                        "entries"
                     }
-                }))
-                .key_context(self.dispatch_context(cx))
-                .on_action(cx.listener(Self::select_next))
-                .on_action(cx.listener(Self::select_prev))
-                .on_action(cx.listener(Self::select_first))
-                .on_action(cx.listener(Self::select_last))
-                .on_action(cx.listener(Self::select_parent))
-                .on_action(cx.listener(Self::expand_selected_entry))
-                .on_action(cx.listener(Self::collapse_selected_entry))
-                .on_action(cx.listener(Self::collapse_all_entries))
-                .on_action(cx.listener(Self::open))
-                .on_action(cx.listener(Self::open_permanent))
-                .on_action(cx.listener(Self::confirm))
-                .on_action(cx.listener(Self::cancel))
-                .on_action(cx.listener(Self::copy_path))
-                .on_action(cx.listener(Self::copy_relative_path))
-                .on_action(cx.listener(Self::new_search_in_directory))
-                .on_action(cx.listener(Self::unfold_directory))
-                .on_action(cx.listener(Self::fold_directory))
-                .when(!project.is_read_only(), |el| {
-                    el.on_action(cx.listener(Self::new_file))
-                        .on_action(cx.listener(Self::new_directory))
-                        .on_action(cx.listener(Self::rename))
-                        .on_action(cx.listener(Self::delete))
-                        .on_action(cx.listener(Self::trash))
-                        .on_action(cx.listener(Self::cut))
-                        .on_action(cx.listener(Self::copy))
-                        .on_action(cx.listener(Self::paste))
-                        .on_action(cx.listener(Self::duplicate))
-                })
-                .when(project.is_local(), |el| {
-                    el.on_action(cx.listener(Self::reveal_in_finder))
-                        .on_action(cx.listener(Self::open_in_terminal))
+                );
+                self.project
+                    .update(cx, |_, cx| cx.emit(project::Event::Notification(message)));
+            }
+            let project = self.project.read(cx);
+            let file_paths = items_to_delete
+                .into_iter()
+                .filter_map(|selection| {
+                    Some((
+                        selection.entry_id,
+                        project
+                            .path_for_entry(selection.entry_id, cx)?
+                            .path
+                            .file_name()?
+                            .to_string_lossy()
+                            .into_owned(),
+                    ))
                 })
-                .on_mouse_down(
-                    MouseButton::Right,
-                    cx.listener(move |this, event: &MouseDownEvent, cx| {
-                        // When deploying the context menu anywhere below the last project entry,
-                        // act as if the user clicked the root of the last worktree.
-                        if let Some(entry_id) = this.last_worktree_root_id {
-                            this.deploy_context_menu(event.position, entry_id, cx);
-                        }
-                    }),
-                )
-                .track_focus(&self.focus_handle)
-                .child(
-                    uniform_list(cx.view().clone(), "entries", items_count, {
-                        |this, range, cx| {
-                            let mut items = Vec::new();
-                            this.for_each_visible_entry(range, cx, |id, details, cx| {
-                                items.push(this.render_entry(id, details, cx));
-                            });
-                            items
-                        }
-                    })
-                    .size_full()
-                    .with_sizing_behavior(ListSizingBehavior::Infer)
-                    .track_scroll(self.scroll_handle.clone()),
-                )
-                .children(self.render_scrollbar(items_count, cx))
-                .children(self.context_menu.as_ref().map(|(menu, position, _)| {
-                    deferred(
-                        anchored()
-                            .position(*position)
-                            .anchor(gpui::AnchorCorner::TopLeft)
-                            .child(menu.clone()),
-                    )
-                    .with_priority(1)
-                }))
+                .collect::<Vec<_>>();
+            if file_paths.is_empty() {
+                return None;
+            }
+            let answer = if !skip_prompt {
+                let operation = if trash { "Trash" } else { "Delete" };
+
+                let prompt =
+                    if let Some((_, path)) = file_paths.first().filter(|_| file_paths.len() == 1) {
+                        format!("{operation} {path}?")
+                    } else {
+                        const CUTOFF_POINT: usize = 10;
+                        let names = if file_paths.len() > CUTOFF_POINT {
+                            let truncated_path_counts = file_paths.len() - CUTOFF_POINT;
+                            let mut paths = file_paths
+                                .iter()
+                                .map(|(_, path)| path.clone())
+                                .take(CUTOFF_POINT)
+                                .collect::<Vec<_>>();
+                            paths.truncate(CUTOFF_POINT);
+                            if truncated_path_counts == 1 {
+                                paths.push(".. 1 file not shown".into());
+                            } else {
+                                paths.push(format!(".. {} files not shown", truncated_path_counts));
+                            }
+                            paths
+                        } else {
+                            file_paths.iter().map(|(_, path)| path.clone()).collect()
+                        };
+
+                        format!(
+                            "Do you want to {} the following {} files?\n{}",
+                            operation.to_lowercase(),
+                            file_paths.len(),
+                            names.join("\n")
+                        )
+                    };
+                let detail = if trash {
+                    "This can only be undone from the system trash."
+                } else {
+                    "This can't be undone."
+                };
+                Some(cx.prompt(
+                    PromptLevel::Info,
+                    &prompt,
+                    Some(detail),
+                    &[operation, "Cancel"],
+                ))
+            } else {
+                None
+            };
+
+            cx.spawn(|this, mut cx| async move {
+                if let Some(answer) = answer {
+                    if answer.await != Ok(0) {
+                        return Result::<(), anyhow::Error>::Ok(());
+                    }
+                }
+                for (entry_id, _) in file_paths {
+                    this.update(&mut cx, |this, cx| {
+                        this.project
+                            .update(cx, |project, cx| project.delete_entry(entry_id, trash, cx))
+                            .ok_or_else(|| anyhow!("no such entry"))
+                    })??
+                    .await?;
+                }
+                Result::<(), anyhow::Error>::Ok(())
+            })
+            .detach_and_log_err(cx);
+            Some(())
+        });
+    }
+
+    fn unfold_directory(&mut self, _: &UnfoldDirectory, cx: &mut ViewContext<Self>) {
+        if let Some((worktree, entry)) = self.selected_entry(cx) {
+            self.unfolded_dir_ids.insert(entry.id);
+
+            let snapshot = worktree.snapshot();
+            let mut parent_path = entry.path.parent();
+            while let Some(path) = parent_path {
+                if let Some(parent_entry) = worktree.entry_for_path(path) {
+                    let mut children_iter = snapshot.child_entries(path);
+
+                    if children_iter.by_ref().take(2).count() > 1 {
+                        break;
+                    }
+
+                    self.unfolded_dir_ids.insert(parent_entry.id);
+                    parent_path = path.parent();
+                } else {
+                    break;
+                }
+            }
+
+            self.update_visible_entries(None, cx);
+            self.autoscroll(cx);
+            cx.notify();
+        }
+    }
+
+    fn fold_directory(&mut self, _: &FoldDirectory, cx: &mut ViewContext<Self>) {
+        if let Some((worktree, entry)) = self.selected_entry(cx) {
+            self.unfolded_dir_ids.remove(&entry.id);
+
+            let snapshot = worktree.snapshot();
+            let mut path = &*entry.path;
+            loop {
+                let mut child_entries_iter = snapshot.child_entries(path);
+                if let Some(child) = child_entries_iter.next() {
+                    if child_entries_iter.next().is_none() && child.is_dir() {
+                        self.unfolded_dir_ids.remove(&child.id);
+                        path = &*child.path;
+                    } else {
+                        break;
+                    }
+                } else {
+                    break;
+                }
+            }
+
+            self.update_visible_entries(None, cx);
+            self.autoscroll(cx);
+            cx.notify();
+        }
+    }
+
+    fn select_next(&mut self, _: &SelectNext, cx: &mut ViewContext<Self>) {
+        if let Some(selection) = self.selection {
+            let (mut worktree_ix, mut entry_ix, _) =
+                self.index_for_selection(selection).unwrap_or_default();
+            if let Some((_, worktree_entries, _)) = self.visible_entries.get(worktree_ix) {
+                if entry_ix + 1 < worktree_entries.len() {
+                    entry_ix += 1;
+                } else {
+                    worktree_ix += 1;
+                    entry_ix = 0;
+                }
+            }
+
+            if let Some((worktree_id, worktree_entries, _)) = self.visible_entries.get(worktree_ix)
+            {
+                if let Some(entry) = worktree_entries.get(entry_ix) {
+                    let selection = SelectedEntry {
+                        worktree_id: *worktree_id,
+                        entry_id: entry.id,
+                    };
+                    self.selection = Some(selection);
+                    if cx.modifiers().shift {
+                        self.marked_entries.insert(selection);
+                    }
+
+                    self.autoscroll(cx);
+                    cx.notify();
+                }
+            }
         } else {
-            v_flex()
-                .id("empty-project_panel")
-                .size_full()
-                .p_4()
-                .track_focus(&self.focus_handle)
-                .child(
-                    Button::new("open_project", "Open a project")
-                        .style(ButtonStyle::Filled)
-                        .full_width()
-                        .key_binding(KeyBinding::for_action(&workspace::Open, cx))
-                        .on_click(cx.listener(|this, _, cx| {
-                            this.workspace
-                                .update(cx, |workspace, cx| workspace.open(&workspace::Open, cx))
-                                .log_err();
-                        })),
-                )
-                .drag_over::<ExternalPaths>(|style, _, cx| {
-                    style.bg(cx.theme().colors().drop_target_background)
+            self.select_first(&SelectFirst {}, cx);
+        }
+    }
+
+    fn select_by_typing(&mut self, event: &KeyDownEvent, cx: &mut ViewContext<Self>) {
+        // Keystrokes typed into the panel's editors and pickers are theirs.
+        if !self.focus_handle.is_focused(cx) {
+            return;
+        }
+        let modifiers = event.keystroke.modifiers;
+        if modifiers.control || modifiers.alt || modifiers.platform || modifiers.function {
+            return;
+        }
+        let Some(typed) = event
+            .keystroke
+            .ime_key
+            .as_ref()
+            .filter(|typed| !typed.chars().any(char::is_control))
+        else {
+            return;
+        };
+
+        self.typed_prefix.push_str(typed);
+        self.typed_prefix_reset_task = Some(cx.spawn(|project_panel, mut cx| async move {
+            cx.background_executor().timer(TYPE_TO_SELECT_TIMEOUT).await;
+            project_panel
+                .update(&mut cx, |project_panel, _| {
+                    project_panel.typed_prefix.clear()
                 })
-                .on_drop(
-                    cx.listener(move |this, external_paths: &ExternalPaths, cx| {
-                        this.last_external_paths_drag_over_entry = None;
-                        this.marked_entries.clear();
-                        if let Some(task) = this
-                            .workspace
-                            .update(cx, |workspace, cx| {
-                                workspace.open_workspace_for_paths(
-                                    true,
-                                    external_paths.paths().to_owned(),
-                                    cx,
-                                )
-                            })
-                            .log_err()
-                        {
-                            task.detach_and_log_err(cx);
-                        }
-                        cx.stop_propagation();
-                    }),
-                )
+                .ok();
+        }));
+        self.select_entry_with_typed_prefix(cx);
+        cx.stop_propagation();
+    }
+
+    /// Selects the next visible entry whose name starts with the typed prefix, wrapping around.
+    fn select_entry_with_typed_prefix(&mut self, cx: &mut ViewContext<Self>) {
+        let project = self.project.read(cx);
+        let entries = self
+            .visible_entries
+            .iter()
+            .flat_map(|(worktree_id, worktree_entries, _)| {
+                worktree_entries
+                    .iter()
+                    .map(move |entry| (*worktree_id, entry))
+            })
+            .filter(|(_, entry)| entry.id != NEW_ENTRY_ID)
+            .collect::<Vec<_>>();
+        let selected_ix = self.selection.and_then(|selection| {
+            entries.iter().position(|(worktree_id, entry)| {
+                *worktree_id == selection.worktree_id && entry.id == selection.entry_id
+            })
+        });
+        // A new prefix moves past the selection, a longer one keeps it while it still matches.
+        let start_ix = match selected_ix {
+            Some(ix) if self.typed_prefix.chars().count() == 1 => ix + 1,
+            Some(ix) => ix,
+            None => 0,
+        };
+
+        let mut new_selection = None;
+        for offset in 0..entries.len() {
+            let (worktree_id, entry) = entries[(start_ix + offset) % entries.len()];
+            let Some(worktree) = project.worktree_for_id(worktree_id, cx) else {
+                continue;
+            };
+            let worktree = worktree.read(cx);
+            let name = entry.path.file_name().map_or_else(
+                || worktree.root_name().into(),
+                |file_name| file_name.to_string_lossy(),
+            );
+            let is_match = if self.is_case_sensitive(worktree, cx) {
+                name.starts_with(&self.typed_prefix)
+            } else {
+                name.to_lowercase()
+                    .starts_with(&self.typed_prefix.to_lowercase())
+            };
+            if is_match {
+                new_selection = Some(SelectedEntry {
+                    worktree_id,
+                    entry_id: entry.id,
+                });
+                break;
+            }
+        }
+
+        if let Some(selection) = new_selection {
+            self.selection = Some(selection);
+            self.autoscroll(cx);
+            cx.notify();
         }
     }
-}
 
-impl Render for DraggedProjectEntryView {
-    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
-        let settings = ProjectPanelSettings::get_global(cx);
-        let ui_font = ThemeSettings::get_global(cx).ui_font.clone();
-        h_flex().font(ui_font).map(|this| {
-            if self.selections.contains(&self.selection) {
-                this.flex_shrink()
-                    .p_1()
-                    .items_end()
-                    .rounded_md()
-                    .child(self.selections.len().to_string())
-            } else {
-                this.bg(cx.theme().colors().background).w(self.width).child(
-                    ListItem::new(self.selection.entry_id.to_proto() as usize)
-                        .indent_level(self.details.depth)
-                        .indent_step_size(px(settings.indent_size))
-                        .child(if let Some(icon) = &self.details.icon {
-                            div().child(Icon::from_path(icon.to_string()))
-                        } else {
-                            div()
-                        })
-                        .child(Label::new(self.details.filename.clone())),
-                )
-            }
-        })
+    fn select_parent(&mut self, _: &SelectParent, cx: &mut ViewContext<Self>) {
+        if let Some((worktree, entry)) = self.selected_entry(cx) {
+            if let Some(parent) = entry.path.parent() {
+                if let Some(parent_entry) = worktree.entry_for_path(parent) {
+                    self.selection = Some(SelectedEntry {
+                        worktree_id: worktree.id(),
+                        entry_id: parent_entry.id,
+                    });
+                    self.autoscroll(cx);
+                    cx.notify();
+                }
+            }
+        } else {
+            self.select_first(&SelectFirst {}, cx);
+        }
+    }
+
+    fn select_first(&mut self, _: &SelectFirst, cx: &mut ViewContext<Self>) {
+        if let Some((worktree_id, worktree_entries, _)) = self.visible_entries.first() {
+            if let Some(first_entry) = worktree_entries.first() {
+                let selection = SelectedEntry {
+                    worktree_id: *worktree_id,
+                    entry_id: first_entry.id,
+                };
+                self.selection = Some(selection);
+                if cx.modifiers().shift {
+                    self.marked_entries.insert(selection);
+                }
+                self.autoscroll(cx);
+                cx.notify();
+            }
+        }
+    }
+
+    fn select_last(&mut self, _: &SelectLast, cx: &mut ViewContext<Self>) {
+        let worktree = self.visible_entries.last().and_then(|(worktree_id, _, _)| {
+            self.project.read(cx).worktree_for_id(*worktree_id, cx)
+        });
+        if let Some(worktree) = worktree {
+            let worktree = worktree.read(cx);
+            let worktree_id = worktree.id();
+            if let Some(last_entry) = worktree.entries(true, 0).last() {
+                self.selection = Some(SelectedEntry {
+                    worktree_id,
+                    entry_id: last_entry.id,
+                });
+                self.autoscroll(cx);
+                cx.notify();
+            }
+        }
+    }
+
+    fn autoscroll(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some((_, _, index)) = self.selection.and_then(|s| self.index_for_selection(s)) {
+            self.scroll_handle.scroll_to_item(index);
+            cx.notify();
+        }
+        self.share_tree_view(cx);
+    }
+
+    fn toggle_share_tree_view(&mut self, _: &ToggleShareTreeView, cx: &mut ViewContext<Self>) {
+        self.is_sharing_tree_view = !self.is_sharing_tree_view;
+        if self.is_sharing_tree_view {
+            self.share_tree_view(cx);
+        } else {
+            self.last_shared_tree_view = None;
+            self.tree_view_sharing_task = None;
+            self.project.read(cx).share_tree_view(None);
+        }
+        cx.notify();
+    }
+
+    fn current_tree_view(&self) -> SharedTreeView {
+        let mut expanded_entry_ids = self
+            .expanded_dir_ids
+            .values()
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+        expanded_entry_ids.sort_unstable();
+        SharedTreeView {
+            expanded_entry_ids,
+            selected_entry_id: self.selection.map(|selection| selection.entry_id),
+        }
+    }
+
+    fn is_tree_view_shared(&self) -> bool {
+        self.last_shared_tree_view
+            .as_ref()
+            .map_or(false, |(expanded_dir_ids, selection)| {
+                *selection == self.selection && *expanded_dir_ids == self.expanded_dir_ids
+            })
+    }
+
+    /// Sends the tree view to the guests once it settles, when it is shared and has changed.
+    fn share_tree_view(&mut self, cx: &mut ViewContext<Self>) {
+        if !self.is_sharing_tree_view || self.is_tree_view_shared() {
+            return;
+        }
+        self.tree_view_sharing_task = Some(cx.spawn(|project_panel, mut cx| async move {
+            cx.background_executor()
+                .timer(TREE_VIEW_SHARING_DEBOUNCE)
+                .await;
+            project_panel
+                .update(&mut cx, |project_panel, cx| {
+                    if project_panel.is_tree_view_shared() {
+                        return;
+                    }
+                    project_panel
+                        .project
+                        .read(cx)
+                        .share_tree_view(Some(project_panel.current_tree_view()));
+                    project_panel.last_shared_tree_view = Some((
+                        project_panel.expanded_dir_ids.clone(),
+                        project_panel.selection,
+                    ));
+                })
+                .ok();
+        }));
+    }
+
+    fn toggle_follow_host_tree_view(
+        &mut self,
+        _: &ToggleFollowHostTreeView,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.is_following_host_tree_view = !self.is_following_host_tree_view;
+        if self.is_following_host_tree_view {
+            self.follow_host_tree_view(cx);
+        }
+        cx.notify();
+    }
+
+    /// Mirrors the expansion and selection of the host's tree view.
+    fn follow_host_tree_view(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(tree_view) = self.host_tree_view.as_ref() else {
+            return;
+        };
+        let project = self.project.read(cx);
+        let mut expanded_dir_ids = HashMap::<WorktreeId, Vec<ProjectEntryId>>::default();
+        for entry_id in &tree_view.expanded_entry_ids {
+            if let Some(worktree_id) = project.worktree_id_for_entry(*entry_id, cx) {
+                expanded_dir_ids
+                    .entry(worktree_id)
+                    .or_default()
+                    .push(*entry_id);
+            }
+        }
+        for entry_ids in expanded_dir_ids.values_mut() {
+            entry_ids.sort_unstable();
+        }
+        let selection = tree_view.selected_entry_id.and_then(|entry_id| {
+            Some(SelectedEntry {
+                worktree_id: project.worktree_id_for_entry(entry_id, cx)?,
+                entry_id,
+            })
+        });
+
+        self.expanded_dir_ids = expanded_dir_ids;
+        self.selection = selection;
+        self.marked_entries.clear();
+        self.update_visible_entries(None, cx);
+        self.autoscroll(cx);
+    }
+
+    fn render_tree_view_sharing_header(&self, cx: &mut ViewContext<Self>) -> Option<Div> {
+        let (label, button_label) = if self.is_sharing_tree_view {
+            ("Sharing your tree view", "Stop Sharing")
+        } else if self.host_tree_view.is_some() {
+            if self.is_following_host_tree_view {
+                ("Following the host's tree view", "Stop Following")
+            } else {
+                ("The host is sharing their tree view", "Follow")
+            }
+        } else {
+            return None;
+        };
+        let is_host = self.is_sharing_tree_view;
+
+        Some(
+            h_flex()
+                .w_full()
+                .flex_none()
+                .px_1()
+                .gap_1()
+                .justify_between()
+                .border_b_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(
+                    Label::new(label)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted)
+                        .single_line(),
+                )
+                .child(
+                    Button::new("tree-view-sharing", button_label)
+                        .label_size(LabelSize::Small)
+                        .on_click(cx.listener(move |this, _, cx| {
+                            if is_host {
+                                this.toggle_share_tree_view(&ToggleShareTreeView, cx);
+                            } else {
+                                this.toggle_follow_host_tree_view(&ToggleFollowHostTreeView, cx);
+                            }
+                        })),
+                ),
+        )
+    }
+
+    fn cut(&mut self, _: &Cut, cx: &mut ViewContext<Self>) {
+        let entries = self.marked_entries();
+        if !entries.is_empty() {
+            self.clipboard = Some(ClipboardEntry::Cut(entries));
+            cx.notify();
+        }
+    }
+
+    fn copy(&mut self, _: &Copy, cx: &mut ViewContext<Self>) {
+        let entries = self.marked_entries();
+        if !entries.is_empty() {
+            self.clipboard = Some(ClipboardEntry::Copied(entries));
+            cx.notify();
+        }
+    }
+
+    fn create_paste_path(
+        &self,
+        source: &SelectedEntry,
+        (worktree, target_entry): (Model<Worktree>, &Entry),
+        cx: &AppContext,
+    ) -> Option<PathBuf> {
+        let mut new_path = target_entry.path.to_path_buf();
+        // If we're pasting into a file, or a directory into itself, go up one level.
+        if target_entry.is_file() || (target_entry.is_dir() && target_entry.id == source.entry_id) {
+            new_path.pop();
+        }
+        let clipboard_entry_file_name = self
+            .project
+            .read(cx)
+            .path_for_entry(source.entry_id, cx)?
+            .path
+            .file_name()?
+            .to_os_string();
+        new_path.push(&clipboard_entry_file_name);
+        Some(available_path(worktree.read(cx), new_path))
+    }
+
+    /// Checks whether a directory would end up inside of itself,
+    /// notifying the user about the blocked operation if so.
+    fn is_pasted_into_own_subtree(
+        &self,
+        source_entry_id: ProjectEntryId,
+        new_path: &Path,
+        is_move: bool,
+        cx: &mut ViewContext<Self>,
+    ) -> bool {
+        let Some(source_path) = self.project.read(cx).path_for_entry(source_entry_id, cx) else {
+            return false;
+        };
+        if !is_inside_own_subtree(&source_path.path, new_path) {
+            return false;
+        }
+        self.project.update(cx, |_, cx| {
+            cx.emit(project::Event::Notification(subtree_cycle_message(
+                &source_path.path,
+                is_move,
+            )))
+        });
+        true
+    }
+
+    fn paste(&mut self, _: &Paste, cx: &mut ViewContext<Self>) {
+        self.paste_entries(false, cx);
+    }
+
+    fn paste_with_source_comment(
+        &mut self,
+        _: &PasteWithSourceComment,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.paste_entries(true, cx);
+    }
+
+    /// Pastes the clipboard entries next to the selected entry, adding a comment naming their
+    /// source at the top of the copied files when `with_source_comment` is set.
+    fn paste_entries(&mut self, with_source_comment: bool, cx: &mut ViewContext<Self>) {
+        maybe!({
+            let (worktree, entry) = self.selected_entry_handle(cx)?;
+            let entry = entry.clone();
+            let worktree_id = worktree.read(cx).id();
+            let clipboard_entries = self
+                .clipboard
+                .as_ref()
+                .filter(|clipboard| !clipboard.items().is_empty())?;
+
+            let is_local = self.project.read(cx).is_local();
+            let with_source_comment =
+                with_source_comment && is_local && !clipboard_entries.is_cut();
+            let mut special_files = Vec::new();
+            let mut pending_ops = Vec::new();
+            for clipboard_entry in clipboard_entries.items() {
+                if clipboard_entry.worktree_id != worktree_id && !is_local {
+                    return None;
+                }
+                if !clipboard_entries.is_cut() {
+                    if let Some(special_file) = self.special_file(clipboard_entry.entry_id, cx) {
+                        special_files.push(special_file);
+                        continue;
+                    }
+                }
+                if clipboard_entry.worktree_id != worktree_id {
+                    pending_ops.extend(self.paste_from_other_worktree(
+                        clipboard_entry,
+                        (worktree.clone(), &entry),
+                        clipboard_entries.is_cut(),
+                        with_source_comment,
+                        cx,
+                    ));
+                    continue;
+                }
+                let new_path =
+                    self.create_paste_path(clipboard_entry, self.selected_entry_handle(cx)?, cx)?;
+                if self.is_pasted_into_own_subtree(
+                    clipboard_entry.entry_id,
+                    &new_path,
+                    clipboard_entries.is_cut(),
+                    cx,
+                ) {
+                    continue;
+                }
+                let source_path = self
+                    .project
+                    .read(cx)
+                    .path_for_entry(clipboard_entry.entry_id, cx)?
+                    .path
+                    .to_path_buf();
+                if clipboard_entries.is_cut() {
+                    let rename = self.project.update(cx, |project, cx| {
+                        project.rename_entry(clipboard_entry.entry_id, new_path.clone(), cx)
+                    });
+                    pending_ops.push(PendingFileOp {
+                        worktree_id,
+                        task: cx
+                            .background_executor()
+                            .spawn(async move { rename.await.map(|_| ()) }),
+                        redo: FileOp::Rename {
+                            from: source_path.clone(),
+                            to: new_path.clone(),
+                        },
+                        undo: FileOp::Rename {
+                            from: new_path,
+                            to: source_path,
+                        },
+                    });
+                } else {
+                    let redo = FileOp::Copy {
+                        source_comment: with_source_comment
+                            .then(|| source_path.to_string_lossy().to_string()),
+                        from: source_path,
+                        to: new_path.clone(),
+                    };
+                    let Some(task) = self.start_file_op(worktree_id, redo.clone(), cx).log_err()
+                    else {
+                        continue;
+                    };
+                    pending_ops.push(PendingFileOp {
+                        worktree_id,
+                        task,
+                        redo,
+                        undo: FileOp::Trash { path: new_path },
+                    });
+                }
+            }
+            self.record_file_ops(pending_ops, cx);
+            if !special_files.is_empty() {
+                self.prompt_about_special_files(&special_files, "copied", cx);
+            }
+            self.expand_entry(worktree_id, entry.id, cx);
+            Some(())
+        });
+    }
+
+    /// Copies or moves the entry of another local worktree next to the target entry through the
+    /// file system, as the project only does it within a worktree.
+    fn paste_from_other_worktree(
+        &self,
+        source: &SelectedEntry,
+        target: (Model<Worktree>, &Entry),
+        is_cut: bool,
+        with_source_comment: bool,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<PendingFileOp> {
+        let source_worktree = self
+            .project
+            .read(cx)
+            .worktree_for_id(source.worktree_id, cx)?;
+        let source_worktree = source_worktree.read(cx);
+        let source_entry = source_worktree.entry_for_id(source.entry_id)?;
+        if is_cut && Some(source_entry) == source_worktree.root_entry() {
+            return None;
+        }
+        let source_path = source_entry.path.clone();
+        let source_abs_path = source_worktree.absolutize(&source_path).ok()?;
+        let new_path = self.create_paste_path(source, (target.0.clone(), target.1), cx)?;
+        let target_worktree_id = target.0.read(cx).id();
+        let target_abs_path = target.0.read(cx).absolutize(&new_path).ok()?;
+        // Worktrees can be nested in one another.
+        if is_inside_own_subtree(&source_abs_path, &target_abs_path) {
+            self.project.update(cx, |_, cx| {
+                cx.emit(project::Event::Notification(subtree_cycle_message(
+                    &source_path,
+                    is_cut,
+                )))
+            });
+            return None;
+        }
+
+        let (redo, undo) = if is_cut {
+            (
+                FileOp::MoveAcross {
+                    from: source_abs_path.clone(),
+                    to: target_abs_path.clone(),
+                },
+                FileOp::MoveAcross {
+                    from: target_abs_path,
+                    to: source_abs_path,
+                },
+            )
+        } else {
+            (
+                FileOp::CopyAcross {
+                    from: source_abs_path,
+                    to: target_abs_path,
+                    source_comment: with_source_comment
+                        .then(|| source_path.to_string_lossy().to_string()),
+                },
+                FileOp::Trash { path: new_path },
+            )
+        };
+        let task = self
+            .start_file_op(target_worktree_id, redo.clone(), cx)
+            .log_err()?;
+        Some(PendingFileOp {
+            worktree_id: target_worktree_id,
+            task,
+            redo,
+            undo,
+        })
+    }
+
+    /// Records the steps of a change once they are applied, leaving out the ones that fail.
+    fn record_file_ops(&mut self, pending_ops: Vec<PendingFileOp>, cx: &mut ViewContext<Self>) {
+        if pending_ops.is_empty() {
+            return;
+        }
+        cx.spawn(|project_panel, mut cx| async move {
+            let mut operation = FileOperation::default();
+            for pending_op in pending_ops {
+                if pending_op.task.await.log_err().is_some() {
+                    operation.push(pending_op.worktree_id, pending_op.redo, pending_op.undo);
+                }
+            }
+            project_panel.update(&mut cx, |project_panel, _| {
+                project_panel.operation_history.push(operation);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn undo(&mut self, _: &Undo, cx: &mut ViewContext<Self>) {
+        let Some(operation) = self.operation_history.start_undo() else {
+            return;
+        };
+        let undo = self.apply_file_ops(operation.undo.clone(), cx);
+        cx.spawn(|project_panel, mut cx| async move {
+            let result = undo.await;
+            project_panel.update(&mut cx, |project_panel, _| {
+                project_panel
+                    .operation_history
+                    .finish_undo(operation, result.is_ok());
+            })?;
+            result
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn redo(&mut self, _: &Redo, cx: &mut ViewContext<Self>) {
+        let Some(operation) = self.operation_history.start_redo() else {
+            return;
+        };
+        let redo = self.apply_file_ops(operation.redo.clone(), cx);
+        cx.spawn(|project_panel, mut cx| async move {
+            let result = redo.await;
+            project_panel.update(&mut cx, |project_panel, _| {
+                project_panel
+                    .operation_history
+                    .finish_redo(operation, result.is_ok());
+            })?;
+            result
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Applies the steps of an undone or redone change one after another, stopping at the
+    /// first one that fails.
+    fn apply_file_ops(
+        &mut self,
+        ops: Vec<(WorktreeId, FileOp)>,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        cx.spawn(|project_panel, mut cx| async move {
+            let mut result = Ok(());
+            for (worktree_id, op) in ops {
+                let task = project_panel.update(&mut cx, |project_panel, cx| {
+                    project_panel.start_file_op(worktree_id, op, cx)
+                })?;
+                if let Err(error) = async move { task?.await }.await {
+                    result = Err(error);
+                    break;
+                }
+            }
+            project_panel.update(&mut cx, |project_panel, cx| {
+                project_panel.update_visible_entries(None, cx);
+                cx.notify();
+            })?;
+            result
+        })
+    }
+
+    fn start_file_op(
+        &self,
+        worktree_id: WorktreeId,
+        op: FileOp,
+        cx: &mut ViewContext<Self>,
+    ) -> Result<Task<Result<()>>> {
+        let project = self.project.read(cx);
+        let is_local = project.is_local();
+        let worktree = project
+            .worktree_for_id(worktree_id, cx)
+            .context("worktree not found")?;
+        let worktree = worktree.read(cx);
+        let source_id = match &op {
+            FileOp::Rename { from: path, .. }
+            | FileOp::Copy { from: path, .. }
+            | FileOp::Trash { path } => Some(
+                worktree
+                    .entry_for_path(path)
+                    .with_context(|| format!("{path:?} does not exist"))?
+                    .id,
+            ),
+            FileOp::Create { .. } | FileOp::CopyAcross { .. } | FileOp::MoveAcross { .. } => None,
+        };
+
+        Ok(match op {
+            FileOp::Create { path, is_dir } => {
+                let create = self.project.update(cx, |project, cx| {
+                    project.create_entry((worktree_id, path.as_path()), is_dir, cx)
+                });
+                cx.background_executor()
+                    .spawn(async move { create.await.map(|_| ()) })
+            }
+            FileOp::Rename { to, .. } => {
+                let source_id = source_id.context("no entry to rename")?;
+                let rename = self
+                    .project
+                    .update(cx, |project, cx| project.rename_entry(source_id, to, cx));
+                cx.background_executor()
+                    .spawn(async move { rename.await.map(|_| ()) })
+            }
+            FileOp::Copy {
+                to, source_comment, ..
+            } => {
+                let source_id = source_id.context("no entry to copy")?;
+                let abs_path = worktree.absolutize(&to)?;
+                let languages = project.languages().clone();
+                let fs = self.fs.clone();
+                let copy = self
+                    .project
+                    .update(cx, |project, cx| project.copy_entry(source_id, to, cx));
+                cx.background_executor().spawn(async move {
+                    copy.await?;
+                    if let Some(source) = source_comment {
+                        prepend_source_comment(fs.as_ref(), &languages, &abs_path, &source)
+                            .await
+                            .log_err();
+                    }
+                    Ok(())
+                })
+            }
+            FileOp::Trash { path } => {
+                let source_id = source_id.context("no entry to trash")?;
+                self.project
+                    .update(cx, |project, cx| project.delete_entry(source_id, true, cx))
+                    .with_context(|| format!("{path:?} can't be trashed"))?
+            }
+            FileOp::CopyAcross {
+                from,
+                to,
+                source_comment,
+            } => {
+                anyhow::ensure!(
+                    is_local,
+                    "entries can only be copied between local worktrees"
+                );
+                let fs = self.fs.clone();
+                let languages = project.languages().clone();
+                let project = self.project.downgrade();
+                cx.spawn(|_, mut cx| async move {
+                    copy_recursive(fs.as_ref(), &from, &to, CopyOptions::default()).await?;
+                    if let Some(source) = source_comment {
+                        prepend_source_comment(fs.as_ref(), &languages, &to, &source)
+                            .await
+                            .log_err();
+                    }
+                    let is_dir = fs.is_dir(&to).await;
+                    project.update(&mut cx, |project, _| {
+                        project.notify_language_servers_of_created_entry(worktree_id, &to, is_dir)
+                    })
+                })
+            }
+            FileOp::MoveAcross { from, to } => {
+                anyhow::ensure!(
+                    is_local,
+                    "entries can only be moved between local worktrees"
+                );
+                let fs = self.fs.clone();
+                cx.background_executor()
+                    .spawn(async move { move_across(fs.as_ref(), &from, &to).await })
+            }
+        })
+    }
+
+    /// Returns the path of the entry when it is a FIFO, socket or device.
+    fn special_file(&self, entry_id: ProjectEntryId, cx: &AppContext) -> Option<Arc<Path>> {
+        let worktree = self.project.read(cx).worktree_for_entry(entry_id, cx)?;
+        worktree
+            .read(cx)
+            .entry_for_id(entry_id)
+            .filter(|entry| entry.is_special_file)
+            .map(|entry| entry.path.clone())
+    }
+
+    fn duplicate(&mut self, _: &Duplicate, cx: &mut ViewContext<Self>) {
+        self.copy(&Copy {}, cx);
+        self.paste(&Paste {}, cx);
+    }
+
+    fn copy_path(&mut self, _: &CopyPath, cx: &mut ViewContext<Self>) {
+        if let Some((worktree, entry)) = self.selected_entry(cx) {
+            cx.write_to_clipboard(ClipboardItem::new(
+                worktree
+                    .abs_path()
+                    .join(&entry.path)
+                    .to_string_lossy()
+                    .to_string(),
+            ));
+        }
+    }
+
+    fn copy_relative_path(&mut self, _: &CopyRelativePath, cx: &mut ViewContext<Self>) {
+        if let Some((_, entry)) = self.selected_entry(cx) {
+            cx.write_to_clipboard(ClipboardItem::new(entry.path.to_string_lossy().to_string()));
+        }
+    }
+
+    fn copy_as_markdown_link(&mut self, _: &CopyAsMarkdownLink, cx: &mut ViewContext<Self>) {
+        if let Some((worktree, entry)) = self.selected_entry(cx) {
+            let file_name = entry
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| worktree.root_name().to_string());
+            let template = &ProjectPanelSettings::get_global(cx).markdown_link_template;
+            cx.write_to_clipboard(ClipboardItem::new(markdown_link(
+                template,
+                &file_name,
+                &entry.path,
+            )));
+        }
+    }
+
+    /// Copies the text of the selected file, or of every marked file as fenced code blocks
+    /// headed by their paths.
+    fn copy_file_contents(&mut self, _: &CopyFileContents, cx: &mut ViewContext<Self>) {
+        let project = self.project.read(cx);
+        let mut files = self
+            .marked_entries()
+            .into_iter()
+            .filter_map(|selection| {
+                let worktree = project.worktree_for_id(selection.worktree_id, cx)?;
+                let worktree = worktree.read(cx);
+                let entry = worktree.entry_for_id(selection.entry_id)?;
+                (worktree.is_local() && entry.is_file() && !entry.is_special_file)
+                    .then(|| (entry.path.clone(), worktree.abs_path().join(&entry.path)))
+            })
+            .collect::<Vec<_>>();
+        if files.is_empty() {
+            return;
+        }
+        files.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let fenced = files.len() > 1;
+        let fs = self.fs.clone();
+        cx.spawn(|project_panel, mut cx| async move {
+            let mut contents = Vec::new();
+            let mut skipped_paths = Vec::new();
+            for (path, abs_path) in files {
+                match fs.load(&abs_path).await {
+                    Ok(text) if text.len() <= MAX_COPIED_FILE_LEN => contents.push((path, text)),
+                    _ => skipped_paths.push(path),
+                }
+            }
+            project_panel.update(&mut cx, |_, cx| {
+                if !contents.is_empty() {
+                    let text = if fenced {
+                        fenced_file_contents(&contents)
+                    } else {
+                        contents.remove(0).1
+                    };
+                    cx.write_to_clipboard(ClipboardItem::new(text));
+                }
+                if !skipped_paths.is_empty() {
+                    let names = skipped_paths
+                        .iter()
+                        .map(|path| path.to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    let prompt = cx.prompt(
+                        PromptLevel::Info,
+                        &format!("{names} couldn't be copied"),
+                        Some("Only text files of up to 256 KB can be copied."),
+                        &["OK"],
+                    );
+                    cx.foreground_executor()
+                        .spawn(async {
+                            prompt.await.ok();
+                        })
+                        .detach();
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn add_to_assistant_context(&mut self, _: &AddToAssistantContext, cx: &mut ViewContext<Self>) {
+        let project = self.project.read(cx);
+        let mut project_paths = self
+            .marked_entries()
+            .into_iter()
+            .filter_map(|selection| project.path_for_entry(selection.entry_id, cx))
+            .collect::<Vec<_>>();
+        project_paths.sort_by(|a, b| a.path.cmp(&b.path));
+        if project_paths.is_empty() {
+            return;
+        }
+        self.workspace
+            .update(cx, |_, cx| {
+                cx.emit(workspace::Event::AddToAssistantContext(project_paths))
+            })
+            .ok();
+    }
+
+    fn reveal_in_finder(&mut self, _: &RevealInFinder, cx: &mut ViewContext<Self>) {
+        if let Some((worktree, entry)) = self.selected_entry(cx) {
+            cx.reveal_path(&worktree.abs_path().join(&entry.path));
+        }
+    }
+
+    fn open_in_terminal(&mut self, _: &OpenInTerminal, cx: &mut ViewContext<Self>) {
+        if let Some((worktree, entry)) = self.selected_entry(cx) {
+            let abs_path = worktree.abs_path().join(&entry.path);
+            let working_directory = if entry.is_dir() {
+                Some(abs_path)
+            } else {
+                if entry.is_symlink {
+                    abs_path.canonicalize().ok()
+                } else {
+                    Some(abs_path)
+                }
+                .and_then(|path| Some(path.parent()?.to_path_buf()))
+            };
+            if let Some(working_directory) = working_directory {
+                cx.dispatch_action(workspace::OpenTerminal { working_directory }.boxed_clone())
+            }
+        }
+    }
+
+    pub fn new_search_in_directory(
+        &mut self,
+        _: &NewSearchInDirectory,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let Some((worktree, entry)) = self.selected_entry(cx) {
+            if entry.is_dir() {
+                let include_root = self.project.read(cx).visible_worktrees(cx).count() > 1;
+                let dir_path = if include_root {
+                    let mut full_path = PathBuf::from(worktree.root_name());
+                    full_path.push(&entry.path);
+                    Arc::from(full_path)
+                } else {
+                    entry.path.clone()
+                };
+
+                self.workspace
+                    .update(cx, |workspace, cx| {
+                        search::ProjectSearchView::new_search_in_directory(
+                            workspace, &dir_path, cx,
+                        );
+                    })
+                    .ok();
+            }
+        }
+    }
+
+    fn move_entry(
+        &mut self,
+        entry_to_move: ProjectEntryId,
+        destination: ProjectEntryId,
+        destination_is_file: bool,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<PendingFileOp> {
+        if self
+            .project
+            .read(cx)
+            .entry_is_worktree_root(entry_to_move, cx)
+        {
+            self.move_worktree_root(entry_to_move, destination, cx);
+            None
+        } else {
+            self.move_worktree_entry(entry_to_move, destination, destination_is_file, cx)
+        }
+    }
+
+    fn move_worktree_root(
+        &mut self,
+        entry_to_move: ProjectEntryId,
+        destination: ProjectEntryId,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.project.update(cx, |project, cx| {
+            let Some(worktree_to_move) = project.worktree_for_entry(entry_to_move, cx) else {
+                return;
+            };
+            let Some(destination_worktree) = project.worktree_for_entry(destination, cx) else {
+                return;
+            };
+
+            let worktree_id = worktree_to_move.read(cx).id();
+            let destination_id = destination_worktree.read(cx).id();
+
+            project
+                .move_worktree(worktree_id, destination_id, cx)
+                .log_err();
+        });
+        return;
+    }
+
+    fn move_worktree_entry(
+        &mut self,
+        entry_to_move: ProjectEntryId,
+        destination: ProjectEntryId,
+        destination_is_file: bool,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<PendingFileOp> {
+        let mut pending_op = None;
+        let destination_worktree = self.project.update(cx, |project, cx| {
+            let entry_path = project.path_for_entry(entry_to_move, cx)?;
+            let destination_project_path = project.path_for_entry(destination, cx)?;
+            let destination_entry_path = destination_project_path.path.clone();
+
+            let mut destination_path = destination_entry_path.as_ref();
+            if destination_is_file {
+                destination_path = destination_path.parent()?;
+            }
+
+            let mut new_path = destination_path.to_path_buf();
+            new_path.push(entry_path.path.file_name()?);
+            if entry_path.worktree_id == destination_project_path.worktree_id
+                && is_inside_own_subtree(&entry_path.path, &new_path)
+            {
+                cx.emit(project::Event::Notification(subtree_cycle_message(
+                    &entry_path.path,
+                    true,
+                )));
+                return None;
+            }
+            if new_path != entry_path.path.as_ref() {
+                let rename = project.rename_entry(entry_to_move, new_path.clone(), cx);
+                let from = entry_path.path.to_path_buf();
+                pending_op = Some(PendingFileOp {
+                    worktree_id: entry_path.worktree_id,
+                    task: cx
+                        .background_executor()
+                        .spawn(async move { rename.await.map(|_| ()) }),
+                    redo: FileOp::Rename {
+                        from: from.clone(),
+                        to: new_path.clone(),
+                    },
+                    undo: FileOp::Rename {
+                        from: new_path,
+                        to: from,
+                    },
+                });
+            }
+
+            project.worktree_id_for_entry(destination, cx)
+        });
+
+        if let Some(destination_worktree) = destination_worktree {
+            self.expand_entry(destination_worktree, destination, cx);
+        }
+        pending_op
+    }
+
+    fn index_for_selection(&self, selection: SelectedEntry) -> Option<(usize, usize, usize)> {
+        let mut entry_index = 0;
+        let mut visible_entries_index = 0;
+        for (worktree_index, (worktree_id, worktree_entries, _)) in
+            self.visible_entries.iter().enumerate()
+        {
+            if *worktree_id == selection.worktree_id {
+                for entry in worktree_entries {
+                    if entry.id == selection.entry_id {
+                        return Some((worktree_index, entry_index, visible_entries_index));
+                    } else {
+                        visible_entries_index += 1;
+                        entry_index += 1;
+                    }
+                }
+                break;
+            } else {
+                visible_entries_index += worktree_entries.len();
+            }
+        }
+        None
+    }
+
+    // Returns list of entries that should be affected by an operation.
+    // When currently selected entry is not marked, it's treated as the only marked entry.
+    fn marked_entries(&self) -> BTreeSet<SelectedEntry> {
+        let Some(selection) = self.selection else {
+            return Default::default();
+        };
+        if self.marked_entries.contains(&selection) {
+            self.marked_entries.clone()
+        } else {
+            BTreeSet::from_iter([selection])
+        }
+    }
+    pub fn selected_entry<'a>(
+        &self,
+        cx: &'a AppContext,
+    ) -> Option<(&'a Worktree, &'a project::Entry)> {
+        let (worktree, entry) = self.selected_entry_handle(cx)?;
+        Some((worktree.read(cx), entry))
+    }
+
+    /// Returns the selected directory, which `FindFileInDirectory` scopes the file finder to.
+    pub fn selected_directory(&self, cx: &AppContext) -> Option<ProjectPath> {
+        let (worktree, entry) = self.selected_entry(cx)?;
+        entry.is_dir().then(|| ProjectPath {
+            worktree_id: worktree.id(),
+            path: entry.path.clone(),
+        })
+    }
+
+    fn selected_entry_handle<'a>(
+        &self,
+        cx: &'a AppContext,
+    ) -> Option<(Model<Worktree>, &'a project::Entry)> {
+        let selection = self.selection?;
+        let project = self.project.read(cx);
+        let worktree = project.worktree_for_id(selection.worktree_id, cx)?;
+        let entry = worktree.read(cx).entry_for_id(selection.entry_id)?;
+        Some((worktree, entry))
+    }
+
+    fn expand_to_selection(&mut self, cx: &mut ViewContext<Self>) -> Option<()> {
+        let (worktree, entry) = self.selected_entry(cx)?;
+        let expanded_dir_ids = self.expanded_dir_ids.entry(worktree.id()).or_default();
+
+        for path in entry.path.ancestors() {
+            let Some(entry) = worktree.entry_for_path(path) else {
+                continue;
+            };
+            if entry.is_dir() {
+                if let Err(idx) = expanded_dir_ids.binary_search(&entry.id) {
+                    expanded_dir_ids.insert(idx, entry.id);
+                }
+            }
+        }
+
+        Some(())
+    }
+
+    /// Expands the root and the directories right below it that are not gitignored, once the
+    /// root directory has been scanned.
+    fn expand_top_level_dirs(&mut self, snapshot: &worktree::Snapshot) {
+        let Some(root_entry) = snapshot
+            .root_entry()
+            .filter(|entry| entry.kind == EntryKind::Dir)
+        else {
+            return;
+        };
+        self.pending_top_level_expansions.remove(&snapshot.id());
+        let expanded_dir_ids = self.expanded_dir_ids.entry(snapshot.id()).or_default();
+        expanded_dir_ids.push(root_entry.id);
+        expanded_dir_ids.extend(
+            snapshot
+                .child_entries(&root_entry.path)
+                .filter(|entry| entry.is_dir() && !entry.is_ignored)
+                .map(|entry| entry.id),
+        );
+        expanded_dir_ids.sort_unstable();
+        expanded_dir_ids.dedup();
+    }
+
+    /// Expands the directories matching `expand_dirs` along with their parents, then collapses
+    /// the ones matching `never_expand_dirs`.
+    fn apply_expansion_rules(
+        &mut self,
+        snapshot: &worktree::Snapshot,
+        expand_dirs: &PathMatcher,
+        never_expand_dirs: &PathMatcher,
+    ) {
+        self.pending_rule_expansions.remove(&snapshot.id());
+        let expanded_dir_ids = self.expanded_dir_ids.entry(snapshot.id()).or_default();
+        expanded_dir_ids.extend(snapshot.root_entry().map(|entry| entry.id));
+        for entry in snapshot.entries(true, 0) {
+            if !entry.is_dir() || entry.path.as_ref() == Path::new("") {
+                continue;
+            }
+            if expand_dirs.is_match(&entry.path) && !never_expand_dirs.is_match(&entry.path) {
+                expanded_dir_ids.extend(
+                    entry
+                        .path
+                        .ancestors()
+                        .filter_map(|path| snapshot.entry_for_path(path))
+                        .map(|entry| entry.id),
+                );
+            }
+        }
+        expanded_dir_ids.retain(|entry_id| {
+            snapshot.entry_for_id(*entry_id).map_or(true, |entry| {
+                entry.path.as_ref() == Path::new("") || !never_expand_dirs.is_match(&entry.path)
+            })
+        });
+        expanded_dir_ids.sort_unstable();
+        expanded_dir_ids.dedup();
+    }
+
+    fn update_visible_entries(
+        &mut self,
+        new_selected_entry: Option<(WorktreeId, ProjectEntryId)>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let auto_collapse_dirs = ProjectPanelSettings::get_global(cx).auto_fold_dirs;
+        let expand_top_level_dirs = ProjectPanelSettings::get_global(cx).expand_top_level_dirs;
+        let expansion_rules = {
+            let settings = ProjectPanelSettings::get_global(cx);
+            (!settings.expand_dirs.is_empty() || !settings.never_expand_dirs.is_empty()).then(
+                || {
+                    let matcher =
+                        |globs: &[String]| PathMatcher::new(globs).log_err().unwrap_or_default();
+                    (
+                        matcher(&settings.expand_dirs),
+                        matcher(&settings.never_expand_dirs),
+                    )
+                },
+            )
+        };
+        let git_status_propagation = ProjectPanelSettings::get_global(cx).git_status_propagation;
+        let file_nesting = ProjectPanelSettings::get_global(cx).file_nesting.clone();
+        let sort_by = ProjectPanelSettings::get_global(cx).sort_by;
+        let folders_position = ProjectPanelSettings::get_global(cx).folders_position;
+        let entry_ordering = EntryOrdering {
+            directories_position: folders_position.into(),
+            case_sensitive: ProjectPanelSettings::get_global(cx).case_sensitive_sort,
+        };
+        let git_status_priority = ProjectPanelSettings::get_global(cx).git_status_rollup_priority();
+        let project = self.project.read(cx);
+        let focused_directory = self.focused_directory.and_then(|(worktree_id, entry_id)| {
+            let worktree = project.worktree_for_id(worktree_id, cx)?;
+            let entry = worktree.read(cx).entry_for_id(entry_id)?;
+            Some((worktree_id, entry_id, entry.path.clone()))
+        });
+        if focused_directory.is_none() {
+            self.focused_directory = None;
+        }
+        self.last_worktree_root_id = match &focused_directory {
+            Some((_, entry_id, _)) => Some(*entry_id),
+            None => project
+                .visible_worktrees(cx)
+                .rev()
+                .next()
+                .and_then(|worktree| worktree.read(cx).root_entry())
+                .map(|entry| entry.id),
+        };
+
+        self.visible_entries.clear();
+        self.file_nesting.clear();
+        let mut scanning_worktrees = Vec::new();
+        for worktree in project.visible_worktrees(cx) {
+            let snapshot = worktree.read(cx).snapshot();
+            let worktree_id = snapshot.id();
+            if !self.scanned_worktrees.contains(&worktree_id) {
+                if worktree
+                    .read(cx)
+                    .as_local()
+                    .map_or(false, |worktree| worktree.is_scanning())
+                {
+                    scanning_worktrees.push(worktree.clone());
+                } else {
+                    self.scanned_worktrees.insert(worktree_id);
+                }
+            }
+            let focused_path = match &focused_directory {
+                Some((focused_worktree_id, _, path)) => {
+                    if *focused_worktree_id != worktree_id {
+                        continue;
+                    }
+                    Some(path.clone())
+                }
+                None => None,
+            };
+
+            if expansion_rules.is_some()
+                && snapshot.root_entry().is_some()
+                && !self.expanded_dir_ids.contains_key(&worktree_id)
+            {
+                self.pending_rule_expansions.insert(worktree_id);
+            }
+            if expand_top_level_dirs
+                && snapshot.root_entry().is_some()
+                && !self.expanded_dir_ids.contains_key(&worktree_id)
+            {
+                self.pending_top_level_expansions.insert(worktree_id);
+            }
+            if self.pending_top_level_expansions.contains(&worktree_id) {
+                self.expand_top_level_dirs(&snapshot);
+            }
+            if let Some((expand_dirs, never_expand_dirs)) = &expansion_rules {
+                if self.scanned_worktrees.contains(&worktree_id)
+                    && self.pending_rule_expansions.contains(&worktree_id)
+                {
+                    self.apply_expansion_rules(&snapshot, expand_dirs, never_expand_dirs);
+                }
+            }
+
+            let expanded_dir_ids = match self.expanded_dir_ids.entry(worktree_id) {
+                hash_map::Entry::Occupied(e) => e.into_mut(),
+                hash_map::Entry::Vacant(e) => {
+                    // The first time a worktree's root entry becomes available,
+                    // mark that root entry as expanded.
+                    if let Some(entry) = snapshot.root_entry() {
+                        e.insert(vec![entry.id]).as_slice()
+                    } else {
+                        &[]
+                    }
+                }
+            };
+
+            let mut new_entry_parent_id = None;
+            let mut new_entry_kind = EntryKind::Dir;
+            if let Some(edit_state) = &self.edit_state {
+                if edit_state.worktree_id == worktree_id && edit_state.is_new_entry {
+                    new_entry_parent_id = Some(edit_state.entry_id);
+                    new_entry_kind = if edit_state.is_dir {
+                        EntryKind::Dir
+                    } else {
+                        EntryKind::File(Default::default())
+                    };
+                }
+            }
+
+            let tagged_paths = self.tag_filter.as_ref().map(|tag_filter| {
+                let mut tagged_paths = FilterMatches::default();
+                for (path, tags) in self.entry_tags.entries(&snapshot.abs_path()) {
+                    if tags.contains(tag_filter) {
+                        tagged_paths.insert(path.into());
+                    }
+                }
+                tagged_paths
+            });
+
+            let filter_matches = self
+                .entry_filter_matches
+                .as_ref()
+                .map(|entry_filter_matches| entry_filter_matches.get(&worktree_id));
+            let deleted_files = self.deleted_files.get(&worktree_id);
+            let mut deleted_file_ancestors = HashSet::<&Path>::default();
+            if self.show_changed_files_only {
+                for path in deleted_files.into_iter().flatten() {
+                    for ancestor in path.ancestors() {
+                        if !deleted_file_ancestors.insert(ancestor) {
+                            break;
+                        }
+                    }
+                }
+            }
+
+            let mut visible_worktree_entries = Vec::new();
+            if focused_path.is_none() && !self.scanned_worktrees.contains(&worktree_id) {
+                // Show the root alone with the scan progress, rather than a tree that grows
+                // while the user is looking at it.
+                visible_worktree_entries.extend(snapshot.root_entry().cloned());
+                self.visible_entries
+                    .push((worktree_id, visible_worktree_entries, OnceCell::new()));
+                continue;
+            }
+            let mut entry_iter = match &focused_path {
+                Some(path) => snapshot.traverse_from_path(true, true, true, path),
+                None => snapshot.entries(true, 0),
+            };
+            while let Some(entry) = entry_iter.entry() {
+                if let Some(focused_path) = &focused_path {
+                    if !entry.path.starts_with(focused_path) {
+                        break;
+                    }
+                }
+                if let Some(tagged_paths) = &tagged_paths {
+                    // Keep tagged entries, their ancestors and their descendants.
+                    if !tagged_paths.contains(&entry.path) {
+                        entry_iter.advance_to_sibling();
+                        continue;
+                    }
+                }
+                if self.show_changed_files_only && entry.path.as_ref() != Path::new("") {
+                    // Keep changed files, including deleted ones, and their ancestors.
+                    let is_changed = if entry.is_dir() {
+                        snapshot.contains_git_statuses(&entry.path)
+                            || deleted_file_ancestors.contains(entry.path.as_ref())
+                    } else {
+                        entry.git_status.is_some()
+                    };
+                    if !is_changed {
+                        entry_iter.advance_to_sibling();
+                        continue;
+                    }
+                }
+                if let Some(filter_matches) = filter_matches {
+                    // Keep matching entries, their ancestors and their descendants.
+                    if !filter_matches
+                        .map_or(false, |filter_matches| filter_matches.contains(&entry.path))
+                    {
+                        entry_iter.advance_to_sibling();
+                        continue;
+                    }
+                }
+                if auto_collapse_dirs
+                    && entry.kind.is_dir()
+                    && !self.unfolded_dir_ids.contains(&entry.id)
+                {
+                    if let Some(root_path) = snapshot.root_entry() {
+                        let mut child_entries = snapshot.child_entries(&entry.path);
+                        if let Some(child) = child_entries.next() {
+                            if entry.path != root_path.path
+                                && focused_path.as_ref() != Some(&entry.path)
+                                && child_entries.next().is_none()
+                                && child.kind.is_dir()
+                            {
+                                entry_iter.advance();
+                                continue;
+                            }
+                        }
+                    }
+                }
+
+                visible_worktree_entries.push(entry.clone());
+                if Some(entry.id) == new_entry_parent_id {
+                    visible_worktree_entries.push(Entry {
+                        id: NEW_ENTRY_ID,
+                        kind: new_entry_kind,
+                        path: entry.path.join("\0").into(),
+                        inode: 0,
+                        mtime: entry.mtime,
+                        is_ignored: entry.is_ignored,
+                        is_external: false,
+                        is_private: false,
+                        is_special_file: false,
+                        git_status: entry.git_status,
+                        canonical_path: entry.canonical_path.clone(),
+                        is_symlink: entry.is_symlink,
+                    });
+                }
+                if expanded_dir_ids.binary_search(&entry.id).is_err()
+                    && entry_iter.advance_to_sibling()
+                {
+                    continue;
+                }
+                entry_iter.advance();
+            }
+            if let Some(deleted_files) = deleted_files {
+                insert_ghost_entries(
+                    &snapshot,
+                    deleted_files,
+                    &mut self.ghost_entry_ids,
+                    &mut self.ghost_entry_paths,
+                    focused_path.as_deref(),
+                    expanded_dir_ids,
+                    &mut visible_worktree_entries,
+                );
+            }
+
+            match git_status_propagation {
+                GitStatusPropagation::None => {}
+                GitStatusPropagation::Nearest => snapshot.propagate_nearest_git_statuses(
+                    &mut visible_worktree_entries,
+                    &git_status_priority,
+                ),
+                GitStatusPropagation::All => snapshot.propagate_git_statuses_with_priority(
+                    &mut visible_worktree_entries,
+                    &git_status_priority,
+                ),
+            }
+            project::sort_worktree_entries_with_ordering(
+                &mut visible_worktree_entries,
+                entry_ordering,
+            );
+            if sort_by != EntrySortMode::Name {
+                sort_entries_by(
+                    &mut visible_worktree_entries,
+                    sort_by,
+                    folders_position,
+                    &self.file_sizes,
+                );
+            }
+            if !file_nesting.is_empty() {
+                let nesting = nest_related_files(
+                    &mut visible_worktree_entries,
+                    &file_nesting,
+                    expanded_dir_ids,
+                );
+                self.file_nesting.insert(worktree_id, nesting);
+            }
+            self.visible_entries
+                .push((worktree_id, visible_worktree_entries, OnceCell::new()));
+        }
+
+        if let Some((worktree_id, entry_id)) = new_selected_entry {
+            self.selection = Some(SelectedEntry {
+                worktree_id,
+                entry_id,
+            });
+            if cx.modifiers().shift {
+                self.marked_entries.insert(SelectedEntry {
+                    worktree_id,
+                    entry_id,
+                });
+            }
+        }
+        self.wait_for_initial_scans(scanning_worktrees, cx);
+        self.row_decorations_stale = true;
+        self.refresh_file_sizes(cx);
+        self.share_tree_view(cx);
+    }
+
+    fn wait_for_initial_scans(
+        &mut self,
+        worktrees: Vec<Model<Worktree>>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        for worktree in worktrees {
+            let worktree_id = worktree.read(cx).id();
+            let hash_map::Entry::Vacant(entry) = self.initial_scan_tasks.entry(worktree_id) else {
+                continue;
+            };
+            let Some(scan_complete) = worktree
+                .read(cx)
+                .as_local()
+                .map(|worktree| worktree.scan_complete())
+            else {
+                continue;
+            };
+            entry.insert(cx.spawn(|project_panel, mut cx| async move {
+                scan_complete.await;
+                project_panel
+                    .update(&mut cx, |project_panel, cx| {
+                        project_panel.initial_scan_tasks.remove(&worktree_id);
+                        project_panel.scanned_worktrees.insert(worktree_id);
+                        project_panel.update_visible_entries(None, cx);
+                        project_panel.restore_position(cx);
+                        cx.notify();
+                    })
+                    .ok();
+            }));
+        }
+    }
+
+    fn expand_entry(
+        &mut self,
+        worktree_id: WorktreeId,
+        entry_id: ProjectEntryId,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.project.update(cx, |project, cx| {
+            if let Some((worktree, expanded_dir_ids)) = project
+                .worktree_for_id(worktree_id, cx)
+                .zip(self.expanded_dir_ids.get_mut(&worktree_id))
+            {
+                project.expand_entry(worktree_id, entry_id, cx);
+                let worktree = worktree.read(cx);
+
+                if let Some(mut entry) = worktree.entry_for_id(entry_id) {
+                    loop {
+                        if let Err(ix) = expanded_dir_ids.binary_search(&entry.id) {
+                            expanded_dir_ids.insert(ix, entry.id);
+                        }
+
+                        if let Some(parent_entry) =
+                            entry.path.parent().and_then(|p| worktree.entry_for_path(p))
+                        {
+                            entry = parent_entry;
+                        } else {
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// Handles paths dropped below the last entry. Folders can either become new
+    /// project folders or be copied into the last root, so ask which one is meant.
+    fn drop_external_paths_on_panel(&mut self, paths: &[PathBuf], cx: &mut ViewContext<Self>) {
+        let Some(last_root_id) = self.last_worktree_root_id else {
+            return;
+        };
+        if !self.project.read(cx).is_local() || !paths.iter().any(|path| path.is_dir()) {
+            self.drop_external_files(paths, last_root_id, cx);
+            return;
+        }
+
+        let Some(target_name) = maybe!({
+            let worktree = self.project.read(cx).worktree_for_entry(last_root_id, cx)?;
+            let worktree = worktree.read(cx);
+            let entry = worktree.entry_for_id(last_root_id)?;
+            Some(
+                entry
+                    .path
+                    .file_name()
+                    .unwrap_or_else(|| OsStr::new(worktree.root_name()))
+                    .to_string_lossy()
+                    .to_string(),
+            )
+        }) else {
+            return;
+        };
+
+        let paths = paths.to_vec();
+        let copy_answer = format!("Copy into {target_name}");
+        let answer = cx.prompt(
+            PromptLevel::Info,
+            "Add the dropped folders to the project?",
+            None,
+            &["Add as Project Folder", &copy_answer, "Cancel"],
+        );
+        cx.spawn(|this, mut cx| async move {
+            match answer.await? {
+                0 => this.update(&mut cx, |this, cx| {
+                    for path in paths {
+                        this.add_folder_to_project(path, cx);
+                    }
+                })?,
+                1 => this.update(&mut cx, |this, cx| {
+                    this.drop_external_files(&paths, last_root_id, cx)
+                })?,
+                _ => {}
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn drop_external_files(
+        &mut self,
+        paths: &[PathBuf],
+        entry_id: ProjectEntryId,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let mut paths: Vec<Arc<Path>> = paths
+            .into_iter()
+            .map(|path| Arc::from(path.clone()))
+            .collect();
+
+        let open_file_after_drop = paths.len() == 1 && paths[0].is_file();
+
+        let Some((target_directory, worktree)) = maybe!({
+            let worktree = self.project.read(cx).worktree_for_entry(entry_id, cx)?;
+            let entry = worktree.read(cx).entry_for_id(entry_id)?;
+            let path = worktree.read(cx).absolutize(&entry.path).ok()?;
+            let target_directory = if path.is_dir() {
+                path
+            } else {
+                path.parent()?.to_path_buf()
+            };
+            Some((target_directory, worktree))
+        }) else {
+            return;
+        };
+
+        let mut paths_to_replace = Vec::new();
+        for path in &paths {
+            if let Some(name) = path.file_name() {
+                let mut target_path = target_directory.clone();
+                target_path.push(name);
+                if target_path.exists() {
+                    paths_to_replace.push((name.to_string_lossy().to_string(), path.clone()));
+                }
+            }
+        }
+
+        cx.spawn(|this, mut cx| {
+            async move {
+                for (filename, original_path) in &paths_to_replace {
+                    let answer = cx
+                        .prompt(
+                            PromptLevel::Info,
+                            format!("A file or folder with name {filename} already exists in the destination folder. Do you want to replace it?").as_str(),
+                            None,
+                            &["Replace", "Cancel"],
+                        )
+                        .await?;
+                    if answer == 1 {
+                        if let Some(item_idx) = paths.iter().position(|p| p == original_path) {
+                            paths.remove(item_idx);
+                        }
+                    }
+                }
+
+                if paths.is_empty() {
+                    return Ok(());
+                }
+
+                let task = worktree.update(&mut cx, |worktree, cx| {
+                    worktree.copy_external_entries(target_directory, paths, true, cx)
+                })?;
+
+                let opened_entries = task.await?;
+                this.update(&mut cx, |this, cx| {
+                    if open_file_after_drop && !opened_entries.is_empty() {
+                        this.open_entry(opened_entries[0], true, true, false, cx);
+                    }
+                })
+            }
+            .log_err()
+        })
+        .detach();
+    }
+
+    /// Marks the directory that external paths dragged over the entry would be dropped into,
+    /// along with its children.
+    fn drag_external_paths_over(
+        &mut self,
+        worktree_id: WorktreeId,
+        entry_id: ProjectEntryId,
+        path: &Path,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.dragged_entry_destination = None;
+        if self.last_external_paths_drag_over_entry == Some(entry_id) {
+            return;
+        }
+        self.last_external_paths_drag_over_entry = Some(entry_id);
+        self.marked_entries.clear();
+
+        let Some((worktree, path, entry)) = maybe!({
+            let worktree = self.project.read(cx).worktree_for_id(worktree_id, cx)?;
+            let worktree = worktree.read(cx);
+            let path = if worktree.entry_for_path(path)?.is_dir() {
+                path
+            } else {
+                path.parent()?
+            };
+            let entry = worktree.entry_for_path(path)?;
+            Some((worktree, path, entry))
+        }) else {
+            return;
+        };
+
+        self.marked_entries.insert(SelectedEntry {
+            entry_id: entry.id,
+            worktree_id: worktree.id(),
+        });
+
+        for entry in worktree.child_entries(path) {
+            self.marked_entries.insert(SelectedEntry {
+                entry_id: entry.id,
+                worktree_id: worktree.id(),
+            });
+        }
+
+        cx.notify();
+    }
+
+    /// Highlights the directory the dragged entries would be moved into: the hovered one,
+    /// or the parent of the hovered file.
+    fn drag_entries_over(
+        &mut self,
+        worktree_id: WorktreeId,
+        path: &Arc<Path>,
+        is_file: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let destination = if is_file {
+            path.parent().map(Arc::from)
+        } else {
+            Some(path.clone())
+        };
+        let destination = destination.map(|path| (worktree_id, path));
+        if self.dragged_entry_destination != destination {
+            self.dragged_entry_destination = destination;
+            cx.notify();
+        }
+    }
+
+    fn drag_onto(
+        &mut self,
+        selections: &DraggedSelection,
+        target_entry_id: ProjectEntryId,
+        is_file: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.dragged_entry_destination = None;
+        let selections = self.outermost_selections(selections, cx);
+        let should_copy = cx.modifiers().alt;
+        if should_copy {
+            let mut pending_ops = Vec::new();
+            let _ = maybe!({
+                let project = self.project.read(cx);
+                let target_worktree = project.worktree_for_entry(target_entry_id, cx)?;
+                let target_entry = target_worktree
+                    .read(cx)
+                    .entry_for_id(target_entry_id)?
+                    .clone();
+                for selection in &selections {
+                    let new_path = self.create_paste_path(
+                        selection,
+                        (target_worktree.clone(), &target_entry),
+                        cx,
+                    )?;
+                    if selection.worktree_id != target_worktree.read(cx).id()
+                        || self.is_pasted_into_own_subtree(selection.entry_id, &new_path, false, cx)
+                    {
+                        continue;
+                    }
+                    let source_path = self
+                        .project
+                        .read(cx)
+                        .path_for_entry(selection.entry_id, cx)?
+                        .path
+                        .to_path_buf();
+                    let copy = self.project.update(cx, |project, cx| {
+                        project.copy_entry(selection.entry_id, new_path.clone(), cx)
+                    });
+                    pending_ops.push(PendingFileOp {
+                        worktree_id: selection.worktree_id,
+                        task: cx
+                            .background_executor()
+                            .spawn(async move { copy.await.map(|_| ()) }),
+                        redo: FileOp::Copy {
+                            from: source_path,
+                            to: new_path.clone(),
+                            source_comment: None,
+                        },
+                        undo: FileOp::Trash { path: new_path },
+                    });
+                }
+
+                Some(())
+            });
+            self.record_file_ops(pending_ops, cx);
+        } else {
+            let pending_ops = selections
+                .iter()
+                .filter_map(|selection| {
+                    self.move_entry(selection.entry_id, target_entry_id, is_file, cx)
+                })
+                .collect();
+            self.record_file_ops(pending_ops, cx);
+        }
+    }
+
+    /// The dragged entries, leaving out the ones inside of other dragged directories since
+    /// they come along with them.
+    fn outermost_selections(
+        &self,
+        selections: &DraggedSelection,
+        cx: &AppContext,
+    ) -> Vec<SelectedEntry> {
+        let project = self.project.read(cx);
+        let paths = selections
+            .items()
+            .filter_map(|selection| {
+                Some((*selection, project.path_for_entry(selection.entry_id, cx)?))
+            })
+            .collect::<Vec<_>>();
+        paths
+            .iter()
+            .filter(|(_, path)| {
+                !paths.iter().any(|(_, other)| {
+                    other.worktree_id == path.worktree_id
+                        && other.path != path.path
+                        && path.path.starts_with(&other.path)
+                })
+            })
+            .map(|(selection, _)| *selection)
+            .collect()
+    }
+
+    fn for_each_visible_entry(
+        &self,
+        range: Range<usize>,
+        cx: &mut ViewContext<ProjectPanel>,
+        mut callback: impl FnMut(ProjectEntryId, EntryDetails, &mut ViewContext<ProjectPanel>),
+    ) {
+        let mut ix = 0;
+        for (worktree_id, visible_worktree_entries, entries_paths) in &self.visible_entries {
+            if ix >= range.end {
+                return;
+            }
+
+            if ix + visible_worktree_entries.len() <= range.start {
+                ix += visible_worktree_entries.len();
+                continue;
+            }
+
+            let end_ix = range.end.min(ix + visible_worktree_entries.len());
+            let (
+                git_status_setting,
+                git_status_propagation,
+                show_file_icons,
+                show_folder_icons,
+                max_indent_depth,
+            ) = {
+                let settings = ProjectPanelSettings::get_global(cx);
+                (
+                    settings.git_status,
+                    settings.git_status_propagation,
+                    settings.file_icons,
+                    settings.folder_icons,
+                    settings.max_indent_depth,
+                )
+            };
+            if let Some(worktree) = self.project.read(cx).worktree_for_id(*worktree_id, cx) {
+                let snapshot = worktree.read(cx).snapshot();
+                let root_name = OsStr::new(snapshot.root_name());
+                let expanded_entry_ids = self
+                    .expanded_dir_ids
+                    .get(&snapshot.id())
+                    .map(Vec::as_slice)
+                    .unwrap_or(&[]);
+
+                let entry_range = range.start.saturating_sub(ix)..end_ix - ix;
+                let nested_repositories = OnceCell::new();
+                let entries = entries_paths.get_or_init(|| {
+                    visible_worktree_entries
+                        .iter()
+                        .map(|e| (e.path.clone()))
+                        .collect()
+                });
+                for entry in visible_worktree_entries[entry_range].iter() {
+                    let status = git_status_setting.then(|| entry.git_status).flatten();
+                    let git_statuses =
+                        status.filter(|_| entry.is_dir()).and_then(
+                            |_| match git_status_propagation {
+                                GitStatusPropagation::None => None,
+                                GitStatusPropagation::Nearest => {
+                                    Some(snapshot.nearest_git_statuses(&entry.path))
+                                }
+                                GitStatusPropagation::All => Some(
+                                    snapshot.propagated_git_statuses(
+                                        &entry.path,
+                                        nested_repositories
+                                            .get_or_init(|| snapshot.nested_repository_statuses()),
+                                    ),
+                                ),
+                            },
+                        );
+                    let is_expanded = expanded_entry_ids.binary_search(&entry.id).is_ok();
+                    let icon = match entry.kind {
+                        EntryKind::File(_) if entry.is_special_file => {
+                            show_file_icons.then(|| Arc::from(IconName::Server.path()))
+                        }
+                        EntryKind::File(_) => {
+                            if show_file_icons {
+                                FileIcons::get_icon(&entry.path, cx)
+                            } else {
+                                None
+                            }
+                        }
+                        _ => {
+                            if show_folder_icons {
+                                FileIcons::get_folder_icon(is_expanded, cx)
+                            } else {
+                                FileIcons::get_chevron_icon(is_expanded, cx)
+                            }
+                        }
+                    };
+
+                    let (depth, difference) =
+                        ProjectPanel::calculate_depth_and_difference(entry, &entries);
+                    let file_nesting = self.file_nesting.get(worktree_id);
+                    let depth = if file_nesting
+                        .map_or(false, |nesting| nesting.nested.contains(&entry.id))
+                    {
+                        depth + 1
+                    } else {
+                        depth
+                    };
+
+                    let mut filename = match difference {
+                        diff if diff > 1 => entry
+                            .path
+                            .iter()
+                            .skip(entry.path.components().count() - diff)
+                            .collect::<PathBuf>()
+                            .to_str()
+                            .unwrap_or_default()
+                            .to_string(),
+                        _ => entry
+                            .path
+                            .file_name()
+                            .map(|name| name.to_string_lossy().into_owned())
+                            .unwrap_or_else(|| root_name.to_string_lossy().to_string()),
+                    };
+                    let full_depth = (depth > max_indent_depth).then_some(depth);
+                    if full_depth.is_some() {
+                        filename.insert_str(0, "…/");
+                    }
+                    let selection = SelectedEntry {
+                        worktree_id: snapshot.id(),
+                        entry_id: entry.id,
+                    };
+                    let mut details = EntryDetails {
+                        filename,
+                        icon,
+                        path: entry.path.clone(),
+                        depth: depth.min(max_indent_depth),
+                        full_depth,
+                        kind: entry.kind,
+                        is_ignored: entry.is_ignored,
+                        is_dirty: self.dirty_entries.contains(&entry.id),
+                        is_deleted: self.ghost_entry(entry.id).is_some(),
+                        is_generated: self.generated_paths.is_match(&entry.path),
+                        is_nested_repo: is_nested_repo(&snapshot, entry),
+                        is_inaccessible: worktree
+                            .read(cx)
+                            .as_local()
+                            .map_or(false, |worktree| worktree.is_dir_inaccessible(&entry.path)),
+                        diagnostic_summary: self
+                            .diagnostic_summaries
+                            .get(worktree_id)
+                            .and_then(|summaries| summaries.get(&entry.path))
+                            .copied(),
+                        note: self
+                            .note_for_entry(*worktree_id, &entry.path, cx)
+                            .map(|note| SharedString::from(note.to_string())),
+                        tags: self
+                            .tags_for_entry(*worktree_id, &entry.path, cx)
+                            .iter()
+                            .map(|tag| SharedString::from(tag.clone()))
+                            .collect(),
+                        has_nested_files: file_nesting
+                            .map_or(false, |nesting| nesting.sources.contains(&entry.id)),
+                        is_expanded,
+                        is_selected: self.selection == Some(selection),
+                        is_marked: self.marked_entries.contains(&selection),
+                        is_editing: false,
+                        is_processing: false,
+                        is_cut: self
+                            .clipboard
+                            .as_ref()
+                            .map_or(false, |e| e.is_cut() && e.items().contains(&selection)),
+                        git_status: status,
+                        git_statuses,
+                        scanned_entry_count: (entry.path.as_ref() == Path::new("")
+                            && !self.scanned_worktrees.contains(worktree_id))
+                        .then(|| snapshot.entry_count()),
+                        mtime: entry.mtime,
+                        is_private: entry.is_private,
+                        worktree_id: *worktree_id,
+                        canonical_path: entry.canonical_path.clone(),
+                        external_location: external_location(&snapshot, entry),
+                    };
+
+                    if let Some(edit_state) = &self.edit_state {
+                        let is_edited_entry = if edit_state.is_new_entry {
+                            entry.id == NEW_ENTRY_ID
+                        } else {
+                            entry.id == edit_state.entry_id
+                        };
+
+                        if is_edited_entry {
+                            if let Some(processing_filename) = &edit_state.processing_filename {
+                                details.is_processing = true;
+                                details.filename.clear();
+                                details.filename.push_str(processing_filename);
+                            } else {
+                                if edit_state.is_new_entry {
+                                    details.filename.clear();
+                                }
+                                details.is_editing = true;
+                            }
+                        }
+                    }
+
+                    callback(entry.id, details, cx);
+                }
+            }
+            ix = end_ix;
+        }
+    }
+
+    fn calculate_depth_and_difference(
+        entry: &Entry,
+        visible_worktree_entries: &HashSet<Arc<Path>>,
+    ) -> (usize, usize) {
+        let (depth, difference) = entry
+            .path
+            .ancestors()
+            .skip(1) // Skip the entry itself
+            .find_map(|ancestor| {
+                if let Some(parent_entry) = visible_worktree_entries.get(ancestor) {
+                    let entry_path_components_count = entry.path.components().count();
+                    let parent_path_components_count = parent_entry.components().count();
+                    let difference = entry_path_components_count - parent_path_components_count;
+                    let depth = parent_entry
+                        .ancestors()
+                        .skip(1)
+                        .filter(|ancestor| visible_worktree_entries.contains(*ancestor))
+                        .count();
+                    Some((depth + 1, difference))
+                } else {
+                    None
+                }
+            })
+            .unwrap_or((0, 0));
+
+        (depth, difference)
+    }
+
+    fn render_entry(
+        &self,
+        entry_id: ProjectEntryId,
+        details: EntryDetails,
+        cx: &mut ViewContext<Self>,
+    ) -> Stateful<Div> {
+        let kind = details.kind;
+        let settings = ProjectPanelSettings::get_global(cx);
+        let show_editor = details.is_editing && !details.is_processing;
+        let selection = SelectedEntry {
+            worktree_id: details.worktree_id,
+            entry_id,
+        };
+        let is_marked = self.marked_entries.contains(&selection);
+        let is_active = self
+            .selection
+            .map_or(false, |selection| selection.entry_id == entry_id);
+        let panel_focused = self.focus_handle.contains_focused(cx);
+        let icon_size = entry_icon_size(settings, cx);
+        let width = self.size(cx);
+        let is_generated = details.is_generated && !details.is_ignored;
+        let is_deleted = details.is_deleted;
+        let filename_text_color = if is_deleted {
+            Color::Deleted
+        } else if is_generated {
+            Color::Disabled
+        } else {
+            entry_git_aware_label_color(details.git_status, details.is_ignored, is_marked)
+        };
+        let file_name = details.filename.clone();
+        let mut icon = details.icon.clone();
+        if settings.file_icons && show_editor && details.kind.is_file() {
+            let filename = self.filename_editor.read(cx).text(cx);
+            if filename.len() > 2 {
+                icon = FileIcons::get_icon(Path::new(&filename), cx);
+            }
+        }
+
+        let is_new_entry_ignored =
+            show_editor && entry_id == NEW_ENTRY_ID && self.is_new_entry_ignored(cx);
+        let is_nested_repo = details.is_nested_repo;
+        let is_dirty = details.is_dirty;
+        let is_inaccessible = details.is_inaccessible;
+        let diagnostic_summary = details.diagnostic_summary;
+        let git_status_glyph = details
+            .git_status
+            .filter(|_| settings.git_status_icons)
+            .map(git_status_glyph);
+        let decorations = self.decorations.decorate(&DecoratedRow {
+            worktree_id: details.worktree_id,
+            entry_id,
+            path: &details.path,
+            is_dir: kind.is_dir(),
+            mtime: details.mtime,
+        });
+        let note = details.note.clone();
+        let scanned_entry_count = details.scanned_entry_count;
+        let tooltip_text = is_deleted
+            .then(|| "Deleted, Not Committed".to_string())
+            .into_iter()
+            .chain(is_generated.then(|| "Generated file, changes may be overwritten".to_string()))
+            .chain(
+                details
+                    .full_depth
+                    .map(|depth| format!("{} • {depth} levels deep", details.path.display())),
+            )
+            .chain(decorations.tooltip_lines)
+            .chain(details.git_statuses.map(git_statuses_summary))
+            .reduce(|a, b| format!("{a}\n{b}"));
+        let is_mirrored = settings.is_mirrored();
+        // Collapsed chevrons point towards the entry name, which is on their left when mirrored.
+        let flip_chevron =
+            is_mirrored && kind.is_dir() && !settings.folder_icons && !details.is_expanded;
+        let tags = details.tags.clone();
+        let canonical_path = details
+            .canonical_path
+            .as_ref()
+            .map(|f| f.to_string_lossy().to_string());
+        let external_location = details
+            .external_location
+            .as_ref()
+            .map(|path| path.to_string_lossy().to_string());
+        let path = details.path.clone();
+        let dragged_over_path = path.clone();
+        let is_drag_destination = cx.has_active_drag()
+            && self.dragged_entry_destination.as_ref().map_or(
+                false,
+                |(destination_worktree_id, destination)| {
+                    *destination_worktree_id == details.worktree_id && *destination == path
+                },
+            );
+
+        let depth = details.depth;
+        let worktree_id = details.worktree_id;
+        let has_nested_files = details.has_nested_files;
+        let is_expanded = details.is_expanded;
+        let selections = Arc::new(self.marked_entries.clone());
+
+        let dragged_selection = DraggedSelection {
+            active_selection: selection,
+            marked_selections: selections,
+        };
+        // Badges at the end of the row, all of them shown side by side.
+        let mut badges = Vec::new();
+        if let Some((glyph, description)) = git_status_glyph {
+            badges.push(
+                div()
+                    .id("git_status_glyph")
+                    .tooltip(move |cx| Tooltip::text(description, cx))
+                    .child(
+                        Label::new(glyph)
+                            .size(LabelSize::Small)
+                            .color(filename_text_color),
+                    )
+                    .into_any_element(),
+            );
+        }
+        if let Some(summary) = diagnostic_summary {
+            let tooltip_text = diagnostic_summary_text(summary);
+            let diagnostics = if kind.is_dir() {
+                h_flex().child(Indicator::dot().color(if summary.error_count > 0 {
+                    Color::Error
+                } else {
+                    Color::Warning
+                }))
+            } else {
+                h_flex()
+                    .gap_1()
+                    .when(summary.error_count > 0, |this| {
+                        this.child(
+                            Label::new(summary.error_count.to_string())
+                                .size(LabelSize::Small)
+                                .color(Color::Error),
+                        )
+                    })
+                    .when(summary.warning_count > 0, |this| {
+                        this.child(
+                            Label::new(summary.warning_count.to_string())
+                                .size(LabelSize::Small)
+                                .color(Color::Warning),
+                        )
+                    })
+            };
+            badges.push(
+                diagnostics
+                    .id("diagnostics")
+                    .tooltip(move |cx| Tooltip::text(tooltip_text.clone(), cx))
+                    .into_any_element(),
+            );
+        }
+        if is_nested_repo {
+            badges.push(
+                div()
+                    .id("nested_repo_icon")
+                    .tooltip(|cx| Tooltip::text("Nested Git Repository", cx))
+                    .child(
+                        Icon::new(IconName::FileGit)
+                            .size(IconSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .into_any_element(),
+            );
+        }
+        if is_inaccessible {
+            badges.push(
+                div()
+                    .id("no_access_icon")
+                    .tooltip(|cx| {
+                        Tooltip::with_meta(
+                            "No Access",
+                            Some(&ShowFolderAccessHelp),
+                            "This folder could not be read",
+                            cx,
+                        )
+                    })
+                    .child(
+                        Icon::new(IconName::FileLock)
+                            .size(IconSize::Small)
+                            .color(Color::Error),
+                    )
+                    .into_any_element(),
+            );
+        }
+        badges.extend(decorations.badges);
+        if let Some(path) = canonical_path {
+            badges.push(
+                div()
+                    .id("symlink_icon")
+                    .tooltip(move |cx| Tooltip::text(format!("{path} • Symbolic Link"), cx))
+                    .child(
+                        Icon::new(IconName::ArrowUpRight)
+                            .size(IconSize::Indicator)
+                            .color(filename_text_color),
+                    )
+                    .into_any_element(),
+            );
+        }
+        if let Some(path) = external_location {
+            badges.push(
+                div()
+                    .id("external_icon")
+                    .tooltip(move |cx| {
+                        Tooltip::text(format!("{path} • Outside of the Project"), cx)
+                    })
+                    .child(
+                        Icon::new(IconName::ExternalLink)
+                            .size(IconSize::Indicator)
+                            .color(Color::Muted),
+                    )
+                    .into_any_element(),
+            );
+        }
+        div()
+            .id(entry_id.to_proto() as usize)
+            .on_drag_move::<ExternalPaths>(cx.listener(
+                move |this, event: &DragMoveEvent<ExternalPaths>, cx| {
+                    if event.bounds.contains(&event.event.position) {
+                        this.drag_external_paths_over(selection.worktree_id, entry_id, &path, cx);
+                    }
+                },
+            ))
+            .on_drop(
+                cx.listener(move |this, external_paths: &ExternalPaths, cx| {
+                    this.last_external_paths_drag_over_entry = None;
+                    this.marked_entries.clear();
+                    this.drop_external_files(external_paths.paths(), entry_id, cx);
+                    cx.stop_propagation();
+                }),
+            )
+            .on_drag(dragged_selection, move |selection, cx| {
+                cx.new_view(|_| DraggedProjectEntryView {
+                    details: details.clone(),
+                    width,
+                    selection: selection.active_selection,
+                    selections: selection.marked_selections.clone(),
+                })
+            })
+            .on_drag_move::<DraggedSelection>(cx.listener(
+                move |this, event: &DragMoveEvent<DraggedSelection>, cx| {
+                    if event.bounds.contains(&event.event.position) {
+                        this.drag_entries_over(worktree_id, &dragged_over_path, kind.is_file(), cx);
+                    }
+                },
+            ))
+            .on_drop(cx.listener(move |this, selections: &DraggedSelection, cx| {
+                this.drag_onto(selections, entry_id, kind.is_file(), cx);
+            }))
+            .when(
+                kind.is_file() && settings.close_editor_on_middle_click,
+                |this| {
+                    this.on_mouse_down(
+                        MouseButton::Middle,
+                        cx.listener(move |this, _, cx| {
+                            cx.stop_propagation();
+                            this.close_editors_for_entry(entry_id, cx);
+                        }),
+                    )
+                },
+            )
+            .child(
+                ListItem::new(entry_id.to_proto() as usize)
+                    .indent_level(depth)
+                    .indent_step_size(px(settings.indent_size))
+                    .mirrored(is_mirrored)
+                    .selected((is_marked || is_active) && panel_focused)
+                    .when(has_nested_files, |this| {
+                        this.toggle(is_expanded)
+                            .on_toggle(cx.listener(move |this, _, cx| {
+                                this.toggle_expanded(entry_id, cx);
+                            }))
+                    })
+                    .when_some(tooltip_text, |this, text| {
+                        this.tooltip(move |cx| Tooltip::text(text.clone(), cx))
+                    })
+                    .when_some(decorations.start_slot, |this, start_slot| {
+                        this.start_slot(start_slot)
+                    })
+                    .when(!badges.is_empty(), |this| {
+                        this.end_slot(h_flex().gap_1().children(badges))
+                    })
+                    .child(if let Some(icon) = &icon {
+                        h_flex().child(
+                            Icon::from_path(icon.to_string())
+                                .size(icon_size)
+                                .color(filename_text_color)
+                                .when(flip_chevron, |icon| {
+                                    icon.transform(Transformation::rotate(percentage(0.5)))
+                                }),
+                        )
+                    } else {
+                        h_flex().size(icon_size.rems()).invisible().flex_none()
+                    })
+                    .child(
+                        if let (Some(editor), true) = (Some(&self.filename_editor), show_editor) {
+                            h_flex().h_6().w_full().gap_2().child(editor.clone()).when(
+                                is_new_entry_ignored,
+                                |this| {
+                                    this.child(
+                                        Label::new("Will be ignored by git")
+                                            .size(LabelSize::Small)
+                                            .color(Color::Warning)
+                                            .single_line(),
+                                    )
+                                },
+                            )
+                        } else {
+                            h_flex()
+                                .h_6()
+                                .gap_2()
+                                .child(
+                                    Label::new(file_name)
+                                        .single_line()
+                                        .strikethrough(is_deleted)
+                                        .color(filename_text_color),
+                                )
+                                .when(is_dirty, |this| {
+                                    this.child(
+                                        div()
+                                            .id("unsaved_changes_indicator")
+                                            .tooltip(|cx| Tooltip::text("Unsaved Changes", cx))
+                                            .child(Indicator::dot().color(Color::Accent)),
+                                    )
+                                })
+                                .when_some(scanned_entry_count, |this, count| {
+                                    this.child(
+                                        Label::new(format!(
+                                            "scanning… {} entries",
+                                            format_count(count)
+                                        ))
+                                        .size(LabelSize::Small)
+                                        .color(Color::Muted)
+                                        .single_line(),
+                                    )
+                                })
+                                .children(decorations.labels.into_iter().map(|label| {
+                                    Label::new(label)
+                                        .size(LabelSize::Small)
+                                        .color(Color::Muted)
+                                        .single_line()
+                                }))
+                                .when_some(note, |this, note| {
+                                    this.child(
+                                        div()
+                                            .id("entry_note_icon")
+                                            .tooltip(move |cx| Tooltip::text(note.clone(), cx))
+                                            .child(
+                                                Icon::new(IconName::MessageBubbles)
+                                                    .size(IconSize::XSmall)
+                                                    .color(Color::Muted),
+                                            ),
+                                    )
+                                })
+                                .children(tags.into_iter().map(|tag| {
+                                    div()
+                                        .px_1()
+                                        .rounded_md()
+                                        .bg(cx.theme().colors().element_background)
+                                        .child(
+                                            Label::new(tag)
+                                                .size(LabelSize::XSmall)
+                                                .color(Color::Muted)
+                                                .single_line(),
+                                        )
+                                }))
+                        }
+                        .ml_1(),
+                    )
+                    .on_click(cx.listener(move |this, event: &gpui::ClickEvent, cx| {
+                        if event.down.button == MouseButton::Right || event.down.first_mouse {
+                            return;
+                        }
+                        if !show_editor {
+                            if let Some(selection) =
+                                this.selection.filter(|_| event.down.modifiers.shift)
+                            {
+                                let current_selection = this.index_for_selection(selection);
+                                let target_selection = this.index_for_selection(SelectedEntry {
+                                    entry_id,
+                                    worktree_id,
+                                });
+                                if let Some(((_, _, source_index), (_, _, target_index))) =
+                                    current_selection.zip(target_selection)
+                                {
+                                    let range_start = source_index.min(target_index);
+                                    let range_end = source_index.max(target_index) + 1; // Make the range inclusive.
+                                    let mut new_selections = BTreeSet::new();
+                                    this.for_each_visible_entry(
+                                        range_start..range_end,
+                                        cx,
+                                        |entry_id, details, _| {
+                                            new_selections.insert(SelectedEntry {
+                                                entry_id,
+                                                worktree_id: details.worktree_id,
+                                            });
+                                        },
+                                    );
+
+                                    this.marked_entries = this
+                                        .marked_entries
+                                        .union(&new_selections)
+                                        .cloned()
+                                        .collect();
+
+                                    this.selection = Some(SelectedEntry {
+                                        entry_id,
+                                        worktree_id,
+                                    });
+                                    // Ensure that the current entry is selected.
+                                    this.marked_entries.insert(SelectedEntry {
+                                        entry_id,
+                                        worktree_id,
+                                    });
+                                }
+                            } else if event.down.modifiers.secondary() {
+                                this.secondary_click_entry(
+                                    selection,
+                                    kind.is_file(),
+                                    event.up.click_count,
+                                    cx,
+                                );
+                            } else if kind.is_dir() && event.down.modifiers.alt {
+                                if is_expanded {
+                                    this.collapse_entry_recursively(worktree_id, entry_id, cx);
+                                } else {
+                                    this.expand_entry_recursively(worktree_id, entry_id, cx);
+                                }
+                            } else if kind.is_dir() {
+                                this.toggle_expanded(entry_id, cx);
+                            } else if is_deleted {
+                                this.selection = Some(selection);
+                                cx.notify();
+                            } else {
+                                let click_count = event.up.click_count;
+                                this.open_entry(
+                                    entry_id,
+                                    cx.modifiers().secondary(),
+                                    click_count > 1,
+                                    click_count == 1,
+                                    cx,
+                                );
+                            }
+                        }
+                    }))
+                    .on_secondary_mouse_down(cx.listener(
+                        move |this, event: &MouseDownEvent, cx| {
+                            // Stop propagation to prevent the catch-all context menu for the project
+                            // panel from being deployed.
+                            cx.stop_propagation();
+                            this.deploy_context_menu(event.position, entry_id, cx);
+                        },
+                    )),
+            )
+            .border_1()
+            .border_r_2()
+            .rounded_none()
+            .hover(|style| {
+                if is_active {
+                    style
+                } else {
+                    let hover_color = cx.theme().colors().ghost_element_hover;
+                    style.bg(hover_color).border_color(hover_color)
+                }
+            })
+            .when(is_marked || is_active, |this| {
+                let mut selected_color = cx.theme().colors().ghost_element_selected;
+                // Without the keyboard focus the selection is muted, so that only the row
+                // where keystrokes land looks selected.
+                if !panel_focused {
+                    selected_color.fade_out(0.5);
+                }
+                this.when(is_marked || !panel_focused, |this| this.bg(selected_color))
+                    .border_color(selected_color)
+            })
+            .when(is_active && panel_focused, |this| {
+                this.border_color(Color::Selected.color(cx))
+            })
+            .when(is_drag_destination, |this| {
+                this.bg(cx.theme().colors().drop_target_background)
+            })
+    }
+
+    fn render_scrollbar(
+        &self,
+        items_count: usize,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<Stateful<Div>> {
+        let settings = ProjectPanelSettings::get_global(cx);
+        if settings.scrollbar.show == ShowScrollbar::Never {
+            return None;
+        }
+        let scroll_handle = self.scroll_handle.0.borrow();
+
+        let height = scroll_handle
+            .last_item_height
+            .filter(|_| self.show_scrollbar || self.scrollbar_drag_thumb_offset.get().is_some())?;
+
+        let total_list_length = height.0 as f64 * items_count as f64;
+        let current_offset = scroll_handle.base_handle.offset().y.0.min(0.).abs() as f64;
+        let mut percentage = current_offset / total_list_length;
+        let end_offset = (current_offset + scroll_handle.base_handle.bounds().size.height.0 as f64)
+            / total_list_length;
+        // Uniform scroll handle might briefly report an offset greater than the length of a list;
+        // in such case we'll adjust the starting offset as well to keep the scrollbar thumb length stable.
+        let overshoot = (end_offset - 1.).clamp(0., 1.);
+        if overshoot > 0. {
+            percentage -= overshoot;
+        }
+        const MINIMUM_SCROLLBAR_PERCENTAGE_HEIGHT: f64 = 0.005;
+        if percentage + MINIMUM_SCROLLBAR_PERCENTAGE_HEIGHT > 1.0 || end_offset > total_list_length
+        {
+            return None;
+        }
+        if total_list_length < scroll_handle.base_handle.bounds().size.height.0 as f64 {
+            return None;
+        }
+        let end_offset = end_offset.clamp(percentage + MINIMUM_SCROLLBAR_PERCENTAGE_HEIGHT, 1.);
+        Some(
+            div()
+                .occlude()
+                .id("project-panel-scroll")
+                .on_mouse_move(cx.listener(|_, _, cx| {
+                    cx.notify();
+                    cx.stop_propagation()
+                }))
+                .on_hover(|_, cx| {
+                    cx.stop_propagation();
+                })
+                .on_any_mouse_down(|_, cx| {
+                    cx.stop_propagation();
+                })
+                .on_mouse_up(
+                    MouseButton::Left,
+                    cx.listener(|this, _, cx| {
+                        if this.scrollbar_drag_thumb_offset.get().is_none()
+                            && !this.focus_handle.contains_focused(cx)
+                        {
+                            this.hide_scrollbar(cx);
+                            cx.notify();
+                        }
+
+                        cx.stop_propagation();
+                    }),
+                )
+                .on_scroll_wheel(cx.listener(|_, _, cx| {
+                    cx.notify();
+                }))
+                .h_full()
+                .absolute()
+                .right_0()
+                .top_0()
+                .bottom_0()
+                .w_3()
+                .cursor_default()
+                .child(ProjectPanelScrollbar::new(
+                    percentage as f32..end_offset as f32,
+                    self.scroll_handle.clone(),
+                    self.scrollbar_drag_thumb_offset.clone(),
+                    cx.view().clone().into(),
+                    items_count,
+                )),
+        )
+    }
+
+    /// Finds a visible worktree whose root is the root of another one, or inside of it, and
+    /// returns it after the worktree it duplicates.
+    fn duplicate_worktree(&self, cx: &AppContext) -> Option<(Model<Worktree>, Model<Worktree>)> {
+        let worktrees = self
+            .project
+            .read(cx)
+            .visible_worktrees(cx)
+            .collect::<Vec<_>>();
+        for (ix, a) in worktrees.iter().enumerate() {
+            for b in &worktrees[ix + 1..] {
+                let a_path = a.read(cx).abs_path();
+                let b_path = b.read(cx).abs_path();
+                if b_path.starts_with(&a_path) {
+                    return Some((a.clone(), b.clone()));
+                } else if a_path.starts_with(&b_path) {
+                    return Some((b.clone(), a.clone()));
+                }
+            }
+        }
+        None
+    }
+
+    fn remove_duplicate_worktree(
+        &mut self,
+        _: &RemoveDuplicateWorktree,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let Some((_, duplicate)) = self.duplicate_worktree(cx) {
+            let worktree_id = duplicate.read(cx).id();
+            self.project
+                .update(cx, |project, cx| project.remove_worktree(worktree_id, cx));
+        }
+    }
+
+    fn render_duplicate_worktree_header(&self, cx: &mut ViewContext<Self>) -> Option<Div> {
+        let (worktree, duplicate) = self.duplicate_worktree(cx)?;
+        let (worktree, duplicate) = (worktree.read(cx), duplicate.read(cx));
+        let label = if worktree.abs_path() == duplicate.abs_path() {
+            format!("“{}” is added twice", worktree.root_name())
+        } else {
+            format!(
+                "“{}” is also shown inside “{}”",
+                duplicate.root_name(),
+                worktree.root_name()
+            )
+        };
+
+        Some(
+            h_flex()
+                .w_full()
+                .flex_none()
+                .px_1()
+                .gap_1()
+                .justify_between()
+                .border_b_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(
+                    h_flex()
+                        .gap_1()
+                        .child(
+                            Icon::new(IconName::ExclamationTriangle)
+                                .size(IconSize::Small)
+                                .color(Color::Warning),
+                        )
+                        .child(
+                            Label::new(label)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .single_line(),
+                        ),
+                )
+                .child(
+                    Button::new("remove-duplicate-worktree", "Remove Duplicate")
+                        .label_size(LabelSize::Small)
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.remove_duplicate_worktree(&RemoveDuplicateWorktree, cx);
+                        })),
+                ),
+        )
+    }
+
+    /// Returns the local worktrees whose volume has less space left than the warning threshold.
+    fn worktrees_low_on_space(&self, cx: &AppContext) -> Vec<(Model<Worktree>, u64)> {
+        let threshold = ProjectPanelSettings::get_global(cx).low_disk_space_warning_mb;
+        if threshold == 0 {
+            return Vec::new();
+        }
+        self.project
+            .read(cx)
+            .visible_worktrees(cx)
+            .filter_map(|worktree| {
+                let space = self
+                    .decorations
+                    .get::<AvailableSpaceDecoration>()
+                    .available_space(worktree.read(cx).id())?;
+                (space < threshold * 1024 * 1024).then_some((worktree, space))
+            })
+            .collect()
+    }
+
+    fn render_low_space_header(&self, cx: &mut ViewContext<Self>) -> Option<Div> {
+        let worktrees = self.worktrees_low_on_space(cx);
+        let (worktree, space) = worktrees.first()?;
+        let label = format!(
+            "Only {} left on the disk of “{}”",
+            format_size(*space),
+            worktree.read(cx).root_name()
+        );
+
+        Some(
+            h_flex()
+                .w_full()
+                .flex_none()
+                .px_1()
+                .gap_1()
+                .border_b_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(
+                    Icon::new(IconName::ExclamationTriangle)
+                        .size(IconSize::Small)
+                        .color(Color::Warning),
+                )
+                .child(
+                    Label::new(label)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted)
+                        .single_line(),
+                ),
+        )
+    }
+
+    fn load_recent_projects(&mut self, cx: &mut ViewContext<Self>) {
+        cx.spawn(|this, mut cx| async move {
+            let workspaces = WORKSPACE_DB
+                .recent_workspaces_on_disk()
+                .await
+                .log_err()
+                .unwrap_or_default();
+            let recent_projects = workspaces
+                .into_iter()
+                .filter_map(|(_, location)| match location {
+                    SerializedWorkspaceLocation::Local(paths, _) => Some(paths),
+                    SerializedWorkspaceLocation::DevServer(_) => None,
+                })
+                .take(RECENT_PROJECTS_LIMIT)
+                .collect();
+            this.update(&mut cx, |this, cx| {
+                this.recent_projects = recent_projects;
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn render_recent_projects(&self, cx: &mut ViewContext<Self>) -> Option<Div> {
+        if self.recent_projects.is_empty() {
+            return None;
+        }
+        Some(
+            v_flex()
+                .gap_1()
+                .child(
+                    Label::new("Recent Projects")
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+                .children(self.recent_projects.iter().enumerate().map(|(ix, paths)| {
+                    let label = paths
+                        .paths()
+                        .iter()
+                        .map(|path| path.compact().to_string_lossy().into_owned())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    let paths = paths.paths().to_vec();
+                    Button::new(("recent_project", ix), label)
+                        .style(ButtonStyle::Subtle)
+                        .full_width()
+                        .label_size(LabelSize::Small)
+                        .on_click(cx.listener(move |this, _, cx| {
+                            if let Some(task) = this
+                                .workspace
+                                .update(cx, |workspace, cx| {
+                                    workspace.open_workspace_for_paths(true, paths.clone(), cx)
+                                })
+                                .log_err()
+                            {
+                                task.detach_and_log_err(cx);
+                            }
+                        }))
+                })),
+        )
+    }
+
+    fn render_unmounted_worktrees(&self, cx: &mut ViewContext<Self>) -> Option<Div> {
+        if self.unmounted_worktrees.is_empty() {
+            return None;
+        }
+
+        Some(
+            v_flex()
+                .w_full()
+                .flex_none()
+                .border_b_1()
+                .border_color(cx.theme().colors().border_variant)
+                .children(
+                    self.unmounted_worktrees
+                        .iter()
+                        .enumerate()
+                        .map(|(ix, unmounted)| {
+                            h_flex()
+                                .id(("unmounted-worktree", ix))
+                                .w_full()
+                                .px_1()
+                                .gap_1()
+                                .justify_between()
+                                .tooltip({
+                                    let abs_path = unmounted.abs_path.clone();
+                                    move |cx| {
+                                        Tooltip::with_meta(
+                                            "Unavailable",
+                                            None,
+                                            abs_path.to_string_lossy().to_string(),
+                                            cx,
+                                        )
+                                    }
+                                })
+                                .child(
+                                    h_flex()
+                                        .gap_1()
+                                        .child(
+                                            Icon::new(IconName::Folder)
+                                                .size(IconSize::Small)
+                                                .color(Color::Disabled),
+                                        )
+                                        .child(
+                                            Label::new(unmounted.root_name.clone())
+                                                .size(LabelSize::Small)
+                                                .color(Color::Disabled)
+                                                .single_line(),
+                                        ),
+                                )
+                                .child(
+                                    Button::new(("reconnect-worktree", ix), "Reconnect")
+                                        .label_size(LabelSize::Small)
+                                        .on_click(cx.listener(|this, _, cx| {
+                                            this.reconnect_worktrees(&ReconnectWorktrees, cx);
+                                        })),
+                                )
+                        }),
+                ),
+        )
+    }
+
+    fn render_folder_focus_header(&self, cx: &mut ViewContext<Self>) -> Option<Div> {
+        let (worktree_id, entry_id) = self.focused_directory?;
+        let worktree = self.project.read(cx).worktree_for_id(worktree_id, cx)?;
+        let worktree = worktree.read(cx);
+        let entry = worktree.entry_for_id(entry_id)?;
+        let mut breadcrumb = PathBuf::from(worktree.root_name());
+        breadcrumb.push(&entry.path);
+
+        Some(
+            h_flex()
+                .w_full()
+                .flex_none()
+                .px_1()
+                .gap_1()
+                .border_b_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(
+                    IconButton::new("exit-folder-focus", IconName::ArrowLeft)
+                        .icon_size(IconSize::Small)
+                        .tooltip(|cx| {
+                            Tooltip::for_action("Exit Folder Focus", &ExitFolderFocus, cx)
+                        })
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.exit_folder_focus(&ExitFolderFocus, cx);
+                        })),
+                )
+                .child(
+                    Label::new(breadcrumb.to_string_lossy().to_string())
+                        .size(LabelSize::Small)
+                        .color(Color::Muted)
+                        .single_line(),
+                ),
+        )
+    }
+
+    fn render_open_editors(&self, cx: &mut ViewContext<Self>) -> Option<Div> {
+        if !ProjectPanelSettings::get_global(cx).show_open_editors {
+            return None;
+        }
+        let workspace = self.workspace.upgrade()?;
+        let workspace = workspace.read(cx);
+        let active_item_id = workspace.active_item(cx).map(|item| item.item_id());
+        let mut open_editors = Vec::new();
+        if self.open_editors_expanded {
+            for pane in workspace.panes() {
+                for item in pane.read(cx).items() {
+                    let is_active = active_item_id == Some(item.item_id());
+                    open_editors.push(self.render_open_editor(pane, item.as_ref(), is_active, cx));
+                }
+            }
+        }
+
+        Some(
+            v_flex()
+                .w_full()
+                .flex_none()
+                .px_1()
+                .border_b_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(
+                    ListHeader::new("Open Editors")
+                        .toggle(self.open_editors_expanded)
+                        .on_toggle(cx.listener(|this, _, cx| {
+                            this.open_editors_expanded = !this.open_editors_expanded;
+                            cx.notify();
+                        })),
+                )
+                .child(
+                    div()
+                        .id("open-editors")
+                        .max_h(MAX_OPEN_EDITORS_HEIGHT)
+                        .overflow_y_scroll()
+                        .children(open_editors),
+                ),
+        )
+    }
+
+    fn render_open_editor(
+        &self,
+        pane: &View<Pane>,
+        item: &dyn ItemHandle,
+        is_active: bool,
+        cx: &ViewContext<Self>,
+    ) -> ListItem {
+        let item_id = item.item_id();
+        let params = TabContentParams {
+            detail: Some(0),
+            selected: is_active,
+            preview: false,
+        };
+        ListItem::new(("open-editor", item_id))
+            .inset(true)
+            .spacing(ListItemSpacing::Dense)
+            .selected(is_active)
+            .start_slot(render_item_indicator(item.boxed_clone(), cx))
+            .child(item.tab_content(params, cx))
+            .end_hover_slot(
+                IconButton::new("close-open-editor", IconName::Close)
+                    .icon_size(IconSize::Small)
+                    .tooltip(|cx| Tooltip::text("Close", cx))
+                    .on_click({
+                        let pane = pane.downgrade();
+                        move |_, cx| close_open_editor(&pane, item_id, cx)
+                    }),
+            )
+            .on_click({
+                let pane = pane.downgrade();
+                move |_, cx| activate_open_editor(&pane, item_id, cx)
+            })
+    }
+
+    fn render_toolbar(&self, cx: &mut ViewContext<Self>) -> Option<Div> {
+        if !ProjectPanelSettings::get_global(cx).show_toolbar {
+            return None;
+        }
+        let is_read_only = self.project.read(cx).is_read_only();
+
+        Some(
+            h_flex()
+                .w_full()
+                .flex_none()
+                .px_1()
+                .gap_1()
+                .justify_end()
+                .border_b_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(
+                    IconButton::new("toolbar-new-file", IconName::File)
+                        .icon_size(IconSize::Small)
+                        .disabled(is_read_only)
+                        .tooltip(|cx| Tooltip::for_action("New File", &NewFile, cx))
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.select_root_if_unselected(cx);
+                            this.new_file(&NewFile, cx);
+                        })),
+                )
+                .child(
+                    IconButton::new("toolbar-new-folder", IconName::Folder)
+                        .icon_size(IconSize::Small)
+                        .disabled(is_read_only)
+                        .tooltip(|cx| Tooltip::for_action("New Folder", &NewDirectory, cx))
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.select_root_if_unselected(cx);
+                            this.new_directory(&NewDirectory, cx);
+                        })),
+                )
+                .child(
+                    IconButton::new("toolbar-changed-files-only", IconName::FileGit)
+                        .icon_size(IconSize::Small)
+                        .selected(self.show_changed_files_only)
+                        .tooltip(|cx| {
+                            Tooltip::for_action(
+                                "Show Changed Files Only",
+                                &ToggleChangedFilesOnly,
+                                cx,
+                            )
+                        })
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.toggle_changed_files_only(&ToggleChangedFilesOnly, cx);
+                        })),
+                )
+                .child(
+                    IconButton::new("toolbar-collapse-all", IconName::ListTree)
+                        .icon_size(IconSize::Small)
+                        .tooltip(|cx| Tooltip::for_action("Collapse All", &CollapseAllEntries, cx))
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.collapse_all_entries(&CollapseAllEntries, cx);
+                        })),
+                )
+                .child(
+                    IconButton::new("toolbar-refresh", IconName::RotateCw)
+                        .icon_size(IconSize::Small)
+                        .tooltip(|cx| Tooltip::for_action("Refresh", &Refresh, cx))
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.refresh(&Refresh, cx);
+                        })),
+                ),
+        )
+    }
+
+    /// Selects the root of the first worktree when nothing is, so that the toolbar creates
+    /// entries there.
+    fn select_root_if_unselected(&mut self, cx: &mut ViewContext<Self>) {
+        if self.selection.is_some() {
+            return;
+        }
+        let Some(worktree) = self.project.read(cx).visible_worktrees(cx).next() else {
+            return;
+        };
+        let worktree = worktree.read(cx);
+        if let Some(root_entry) = worktree.root_entry() {
+            self.selection = Some(SelectedEntry {
+                worktree_id: worktree.id(),
+                entry_id: root_entry.id,
+            });
+        }
+    }
+
+    fn render_filter_editor(&self, cx: &mut ViewContext<Self>) -> Div {
+        h_flex()
+            .w_full()
+            .flex_none()
+            .px_2()
+            .py_1()
+            .gap_2()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                Icon::new(IconName::MagnifyingGlass)
+                    .size(IconSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(self.filter_editor.clone())
+    }
+
+    fn render_tag_filter_header(&self, cx: &mut ViewContext<Self>) -> Option<Div> {
+        let tags = self.project_tags(cx);
+        if tags.is_empty() {
+            return None;
+        }
+
+        Some(
+            h_flex()
+                .w_full()
+                .flex_none()
+                .flex_wrap()
+                .px_1()
+                .gap_1()
+                .border_b_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(
+                    Icon::new(IconName::Hash)
+                        .size(IconSize::Small)
+                        .color(Color::Muted),
+                )
+                .children(tags.into_iter().enumerate().map(|(ix, tag)| {
+                    let is_selected = self.tag_filter.as_ref() == Some(&tag);
+                    Button::new(("tag-filter", ix), tag.clone())
+                        .label_size(LabelSize::Small)
+                        .selected(is_selected)
+                        .on_click(cx.listener(move |this, _, cx| {
+                            let tag = (!is_selected).then(|| tag.clone());
+                            this.set_tag_filter(tag, cx);
+                        }))
+                })),
+        )
+    }
+
+    fn dispatch_context(&self, cx: &ViewContext<Self>) -> KeyContext {
+        let mut dispatch_context = KeyContext::new_with_defaults();
+        dispatch_context.add("ProjectPanel");
+        dispatch_context.add("menu");
+
+        let is_picking_entry = self
+            .visible_entry_picker
+            .as_ref()
+            .map_or(false, |(picker, _)| {
+                picker.focus_handle(cx).contains_focused(cx)
+            });
+        let identifier = if self.filename_editor.focus_handle(cx).is_focused(cx)
+            || self.filter_editor.focus_handle(cx).is_focused(cx)
+            || is_picking_entry
+        {
+            "editing"
+        } else {
+            "not_editing"
+        };
+
+        dispatch_context.add(identifier);
+
+        // Flags describing the panel state, for keybindings that depend on it,
+        // e.g. `ProjectPanel && selection_is_dir` to expand folders but open files.
+        if let Some((_, entry)) = self.selected_entry(cx) {
+            dispatch_context.add("has_selection");
+            if entry.is_dir() {
+                dispatch_context.add("selection_is_dir");
+            }
+        }
+        if self.tag_filter.is_some()
+            || self.show_changed_files_only
+            || self.entry_filter.is_some()
+            || self.focused_directory.is_some()
+        {
+            dispatch_context.add("filter_active");
+        }
+        if self.marked_entries.len() > 1
+            && self
+                .selection
+                .map_or(false, |selection| self.marked_entries.contains(&selection))
+        {
+            dispatch_context.add("multiselect");
+        }
+        dispatch_context
+    }
+
+    fn should_autohide_scrollbar(cx: &AppContext) -> bool {
+        cx.try_global::<ScrollbarAutoHide>()
+            .map_or_else(|| cx.should_auto_hide_scrollbars(), |autohide| autohide.0)
+    }
+
+    fn hide_scrollbar(&mut self, cx: &mut ViewContext<Self>) {
+        const SCROLLBAR_SHOW_INTERVAL: Duration = Duration::from_secs(1);
+        if !Self::should_autohide_scrollbar(cx) {
+            return;
+        }
+        self.hide_scrollbar_task = Some(cx.spawn(|panel, mut cx| async move {
+            cx.background_executor()
+                .timer(SCROLLBAR_SHOW_INTERVAL)
+                .await;
+            panel
+                .update(&mut cx, |panel, cx| {
+                    panel.show_scrollbar = false;
+                    cx.notify();
+                })
+                .log_err();
+        }))
+    }
+
+    /// Reveals and selects the entry at the given path, matching its components
+    /// case-insensitively when the worktree's navigation allows it.
+    /// Returns `false` if no such entry exists.
+    pub fn reveal_path(&mut self, project_path: &ProjectPath, cx: &mut ViewContext<Self>) -> bool {
+        let Some(worktree) = self
+            .project
+            .read(cx)
+            .worktree_for_id(project_path.worktree_id, cx)
+        else {
+            return false;
+        };
+        let worktree = worktree.read(cx);
+        let case_sensitive = self.is_case_sensitive(worktree, cx);
+        let Some(entry_id) =
+            find_entry_for_path(worktree, &project_path.path, case_sensitive).map(|entry| entry.id)
+        else {
+            return false;
+        };
+
+        self.reveal_entry(self.project.clone(), entry_id, false, cx);
+        true
+    }
+
+    /// Creates the file, or the directory when `query` ends with a separator, that a path typed
+    /// elsewhere (e.g. in the file finder) refers to, then reveals it and opens created files.
+    /// A leading worktree name picks the worktree in multi-root projects; otherwise the worktree
+    /// of the selected entry is used.
+    pub fn create_entry_for_query(
+        &mut self,
+        query: &str,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        let is_dir = query.ends_with('/') || query.ends_with(std::path::MAIN_SEPARATOR);
+        let query = Path::new(query.trim_matches(|c| c == '/' || c == std::path::MAIN_SEPARATOR));
+        if query.as_os_str().is_empty() {
+            return Task::ready(Err(anyhow!("No path to create")));
+        }
+
+        let project = self.project.read(cx);
+        let mut worktrees = project.visible_worktrees(cx);
+        let mut components = query.components();
+        let named_worktree = components.next().and_then(|first| {
+            let rest = components.as_path();
+            let worktree = worktrees
+                .find(|worktree| OsStr::new(worktree.read(cx).root_name()) == first.as_os_str())?;
+            (!rest.as_os_str().is_empty()).then(|| (worktree, rest))
+        });
+        let Some((worktree, path)) = named_worktree
+            .filter(|_| project.visible_worktrees(cx).count() > 1)
+            .or_else(|| {
+                let worktree = self
+                    .selection
+                    .and_then(|selection| project.worktree_for_id(selection.worktree_id, cx))
+                    .or_else(|| project.visible_worktrees(cx).next())?;
+                Some((worktree, query))
+            })
+        else {
+            return Task::ready(Err(anyhow!("No worktree to create {query:?} in")));
+        };
+        let worktree_id = worktree.read(cx).id();
+        let path: Arc<Path> = Arc::from(path);
+
+        if let Some(entry_id) = worktree
+            .read(cx)
+            .entry_for_path(&path)
+            .map(|entry| entry.id)
+        {
+            self.reveal_entry(self.project.clone(), entry_id, false, cx);
+            if !is_dir {
+                self.open_entry(entry_id, false, true, false, cx);
+            }
+            return Task::ready(Ok(()));
+        }
+
+        let create_entry = self.project.update(cx, |project, cx| {
+            project.create_entry((worktree_id, path), is_dir, cx)
+        });
+        cx.spawn(|project_panel, mut cx| async move {
+            if let CreatedEntry::Included(entry) = create_entry.await? {
+                project_panel.update(&mut cx, |project_panel, cx| {
+                    project_panel.reveal_entry(project_panel.project.clone(), entry.id, false, cx);
+                    if !is_dir {
+                        project_panel.open_entry(entry.id, false, true, false, cx);
+                    }
+                })?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Creates a file with `contents`, reveals it and opens it, for features that move code into a
+    /// new file. A conflicting name is resolved like pasting does, and the file starts with its
+    /// license header. Returns `None` when the file ended up excluded from the worktree.
+    pub fn create_and_open(
+        &mut self,
+        path: ProjectPath,
+        contents: String,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<Option<ProjectEntryId>>> {
+        let Some(worktree) = self.project.read(cx).worktree_for_id(path.worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("worktree not found")));
+        };
+        let worktree = worktree.read(cx);
+        let mut new_path = path.path.to_path_buf();
+        if find_entry_for_path(worktree, &new_path, self.is_case_sensitive(worktree, cx)).is_some()
+        {
+            new_path = available_path(worktree, new_path);
+        }
+        let contents = license_header_for_path(
+            &ProjectPanelSettings::get_global(cx).license_headers,
+            &new_path,
+        )
+        .and_then(|header| with_license_header(&contents, header))
+        .unwrap_or(contents);
+        let abs_path = worktree.abs_path().join(&new_path);
+
+        let create_entry = self.project.update(cx, |project, cx| {
+            project.create_entry((path.worktree_id, new_path.as_path()), false, cx)
+        });
+        let fs = self.fs.clone();
+        cx.spawn(|project_panel, mut cx| async move {
+            let created_entry = create_entry.await?;
+            fs.atomic_write(abs_path, contents).await?;
+            match created_entry {
+                CreatedEntry::Included(entry) => {
+                    project_panel.update(&mut cx, |project_panel, cx| {
+                        project_panel.reveal_entry(
+                            project_panel.project.clone(),
+                            entry.id,
+                            false,
+                            cx,
+                        );
+                        project_panel.open_entry(entry.id, false, true, false, cx);
+                    })?;
+                    Ok(Some(entry.id))
+                }
+                CreatedEntry::Excluded { abs_path } => {
+                    let open_task = project_panel.update(&mut cx, |project_panel, cx| {
+                        project_panel.workspace.update(cx, |workspace, cx| {
+                            workspace.open_abs_path(abs_path, true, cx)
+                        })
+                    })??;
+                    open_task.await?;
+                    Ok(None)
+                }
+            }
+        })
+    }
+
+    /// Applies the operations to the worktree one after another, for callers that change many
+    /// entries at once. They are checked like the interactive ones: existing destinations are
+    /// handled by `conflict_policy`, directories can't be moved inside of themselves and new
+    /// files start with their license header.
+    pub fn apply_batch_operations(
+        &mut self,
+        worktree_id: WorktreeId,
+        operations: Vec<BatchOperation>,
+        conflict_policy: ConflictPolicy,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<BatchOutcome>> {
+        cx.spawn(|project_panel, mut cx| async move {
+            let mut outcome = BatchOutcome::default();
+            let mut result = Ok(());
+            for operation in operations {
+                let task = project_panel.update(&mut cx, |project_panel, cx| {
+                    project_panel.start_batch_operation(
+                        worktree_id,
+                        operation.clone(),
+                        conflict_policy,
+                        cx,
+                    )
+                })?;
+                match task {
+                    Ok(Some((applied_operation, task))) => {
+                        if let Err(error) = task.await {
+                            result = Err(error);
+                            break;
+                        }
+                        outcome.applied.push(applied_operation);
+                    }
+                    Ok(None) => outcome.skipped.push(operation),
+                    Err(error) => {
+                        result = Err(error);
+                        break;
+                    }
+                }
+            }
+            project_panel.update(&mut cx, |project_panel, cx| {
+                project_panel.update_visible_entries(None, cx);
+                cx.notify();
+            })?;
+            result.map(|()| outcome)
+        })
+    }
+
+    /// Checks the operation against the current state of the worktree and starts it,
+    /// returning `None` when it is skipped because of a conflict.
+    fn start_batch_operation(
+        &mut self,
+        worktree_id: WorktreeId,
+        mut operation: BatchOperation,
+        conflict_policy: ConflictPolicy,
+        cx: &mut ViewContext<Self>,
+    ) -> Result<Option<(BatchOperation, Task<Result<()>>)>> {
+        let worktree = self
+            .project
+            .read(cx)
+            .worktree_for_id(worktree_id, cx)
+            .context("worktree not found")?;
+        let worktree = worktree.read(cx);
+        let case_sensitive = self.is_case_sensitive(worktree, cx);
+        let source = match &operation {
+            BatchOperation::Create { .. } => None,
+            BatchOperation::Move { from: path, .. } | BatchOperation::Delete { path, .. } => Some(
+                worktree
+                    .entry_for_path(path)
+                    .with_context(|| format!("{path:?} does not exist"))?
+                    .clone(),
+            ),
+        };
+        if let Some(destination) = operation.destination() {
+            // A move that only changes the case of the name must not collide with the entry itself.
+            let conflict = find_entry_for_path(worktree, destination, case_sensitive).map_or(
+                false,
+                |existing| {
+                    source
+                        .as_ref()
+                        .map_or(true, |source| existing.id != source.id)
+                },
+            );
+            if conflict {
+                match conflict_policy {
+                    ConflictPolicy::Fail => anyhow::bail!("{destination:?} already exists"),
+                    ConflictPolicy::Skip => return Ok(None),
+                    ConflictPolicy::KeepBoth => {
+                        let destination = available_path(worktree, destination.to_path_buf());
+                        operation = operation.with_destination(destination);
+                    }
+                }
+            }
+        }
+
+        let task = match &operation {
+            BatchOperation::Create { path, is_dir } => {
+                let header = (!is_dir)
+                    .then(|| {
+                        license_header_for_path(
+                            &ProjectPanelSettings::get_global(cx).license_headers,
+                            path,
+                        )
+                    })
+                    .flatten()
+                    .and_then(|header| with_license_header("", header));
+                let abs_path = worktree.abs_path().join(path);
+                let create = self.project.update(cx, |project, cx| {
+                    project.create_entry((worktree_id, path.as_path()), *is_dir, cx)
+                });
+                let fs = self.fs.clone();
+                cx.background_executor().spawn(async move {
+                    if let CreatedEntry::Included(_) = create.await? {
+                        if let Some(header) = header {
+                            fs.atomic_write(abs_path, header).await?;
+                        }
+                    }
+                    anyhow::Ok(())
+                })
+            }
+            BatchOperation::Move { from, to } => {
+                let source = source.context("no entry to move")?;
+                if source.is_dir() && to.starts_with(from) {
+                    anyhow::bail!("{from:?} can't be moved inside of itself");
+                }
+                let rename = self.project.update(cx, |project, cx| {
+                    project.rename_entry(source.id, to.as_path(), cx)
+                });
+                cx.background_executor()
+                    .spawn(async move { rename.await.map(|_| ()) })
+            }
+            BatchOperation::Delete { path, trash } => {
+                let source = source.context("no entry to delete")?;
+                self.project
+                    .update(cx, |project, cx| {
+                        project.delete_entry(source.id, *trash, cx)
+                    })
+                    .with_context(|| format!("{path:?} can't be deleted"))?
+            }
+        };
+        Ok(Some((operation, task)))
+    }
+
+    fn is_case_sensitive(&self, worktree: &Worktree, cx: &AppContext) -> bool {
+        match ProjectPanelSettings::get_global(cx).navigation_case_sensitivity {
+            NavigationCaseSensitivity::Auto => worktree
+                .as_local()
+                .map_or(true, |worktree| worktree.is_case_sensitive()),
+            NavigationCaseSensitivity::Sensitive => true,
+            NavigationCaseSensitivity::Insensitive => false,
+        }
+    }
+
+    /// Reveals all `entry_ids` and marks them, selecting the first one, so that panel operations
+    /// apply to all of them at once.
+    fn select_entries(
+        &mut self,
+        project: Model<Project>,
+        entry_ids: &[ProjectEntryId],
+        cx: &mut ViewContext<Self>,
+    ) {
+        let mut marked_entries = BTreeSet::new();
+        for &entry_id in entry_ids {
+            let Some(worktree) = project.read(cx).worktree_for_entry(entry_id, cx) else {
+                continue;
+            };
+            let worktree_id = worktree.read(cx).id();
+            self.expand_entry(worktree_id, entry_id, cx);
+            marked_entries.insert(SelectedEntry {
+                worktree_id,
+                entry_id,
+            });
+        }
+        let Some(first_entry) = entry_ids.iter().find_map(|&entry_id| {
+            marked_entries
+                .iter()
+                .find(|selection| selection.entry_id == entry_id)
+                .copied()
+        }) else {
+            return;
+        };
+
+        self.focused_directory = None;
+        self.marked_entries = marked_entries;
+        self.update_visible_entries(Some((first_entry.worktree_id, first_entry.entry_id)), cx);
+        self.autoscroll(cx);
+        cx.notify();
+    }
+
+    fn reveal_entry(
+        &mut self,
+        project: Model<Project>,
+        entry_id: ProjectEntryId,
+        skip_ignored: bool,
+        cx: &mut ViewContext<'_, ProjectPanel>,
+    ) {
+        if let Some(worktree) = project.read(cx).worktree_for_entry(entry_id, cx) {
+            let worktree = worktree.read(cx);
+            if skip_ignored
+                && worktree
+                    .entry_for_id(entry_id)
+                    .map_or(true, |entry| entry.is_ignored)
+            {
+                return;
+            }
+            if !self.is_inside_focused_directory(worktree, entry_id) {
+                // Automatic reveals keep the folder focus, explicit ones escape it.
+                if skip_ignored {
+                    return;
+                }
+                self.focused_directory = None;
+            }
+
+            let worktree_id = worktree.id();
+            self.marked_entries.clear();
+            self.expand_entry(worktree_id, entry_id, cx);
+            self.update_visible_entries(Some((worktree_id, entry_id)), cx);
+            self.autoscroll(cx);
+            cx.notify();
+        }
+    }
+}
+
+impl Render for ProjectPanel {
+    fn render(&mut self, cx: &mut gpui::ViewContext<Self>) -> impl IntoElement {
+        let has_worktree = self.visible_entries.len() != 0;
+        let project = self.project.read(cx);
+
+        if has_worktree {
+            let items_count = self
+                .visible_entries
+                .iter()
+                .map(|(_, worktree_entries, _)| worktree_entries.len())
+                .sum();
+
+            v_flex()
+                .id("project-panel")
+                .group("project-panel")
+                .size_full()
+                .relative()
+                .on_hover(cx.listener(|this, hovered, cx| {
+                    if *hovered {
+                        this.show_scrollbar = true;
+                        this.hide_scrollbar_task.take();
+                        cx.notify();
+                    } else if !this.focus_handle.contains_focused(cx) {
+                        this.hide_scrollbar(cx);
+                    }
+                }))
+                .key_context(self.dispatch_context(cx))
+                .on_action(cx.listener(Self::select_next))
+                .on_action(cx.listener(Self::select_prev))
+                .on_action(cx.listener(Self::select_first))
+                .on_action(cx.listener(Self::select_last))
+                .on_action(cx.listener(Self::select_parent))
+                .on_key_down(cx.listener(Self::select_by_typing))
+                .on_drag_move::<DraggedSelection>(cx.listener(
+                    |this, event: &DragMoveEvent<DraggedSelection>, cx| {
+                        this.autoscroll_while_dragging(event.event.position, cx);
+                    },
+                ))
+                .on_action(cx.listener(Self::expand_selected_entry))
+                .on_action(cx.listener(Self::expand_selected_entry_recursively))
+                .on_action(cx.listener(Self::collapse_selected_entry))
+                .on_action(cx.listener(Self::collapse_selected_entry_recursively))
+                .on_action(cx.listener(Self::collapse_all_entries))
+                .on_action(cx.listener(Self::expand_all_entries))
+                .on_action(cx.listener(Self::expand_all_one_level))
+                .on_action(cx.listener(Self::collapse_worktree))
+                .on_action(cx.listener(Self::expand_worktree_one_level))
+                .on_action(cx.listener(Self::open))
+                .on_action(cx.listener(Self::open_permanent))
+                .on_action(cx.listener(Self::open_to_the_side_keep_focus))
+                .on_action(cx.listener(Self::close_editors_for_file))
+                .on_action(cx.listener(Self::remove_duplicate_worktree))
+                .on_action(cx.listener(Self::reconnect_worktrees))
+                .on_action(cx.listener(Self::confirm))
+                .on_action(cx.listener(Self::cancel))
+                .on_action(cx.listener(Self::copy_path))
+                .on_action(cx.listener(Self::copy_relative_path))
+                .on_action(cx.listener(Self::copy_as_markdown_link))
+                .on_action(cx.listener(Self::copy_file_contents))
+                .on_action(cx.listener(Self::add_to_assistant_context))
+                .on_action(cx.listener(Self::copy_owners))
+                .on_action(cx.listener(Self::insert_license_header))
+                .on_action(cx.listener(Self::show_folder_statistics))
+                .on_action(cx.listener(Self::retry_reading_folder))
+                .on_action(cx.listener(Self::show_folder_access_help))
+                .on_action(cx.listener(Self::edit_note))
+                .on_action(cx.listener(Self::edit_tags))
+                .on_action(cx.listener(Self::export_panel_configuration))
+                .on_action(cx.listener(Self::import_panel_configuration))
+                .on_action(cx.listener(Self::stop_watching_folder))
+                .on_action(cx.listener(Self::open_layout_snapshots))
+                .on_action(cx.listener(Self::jump_to_visible_entry))
+                .on_action(cx.listener(Self::expand_frequently_used))
+                .on_action(cx.listener(Self::toggle_share_tree_view))
+                .on_action(cx.listener(Self::toggle_follow_host_tree_view))
+                .on_action(cx.listener(Self::new_search_in_directory))
+                .on_action(cx.listener(Self::unfold_directory))
+                .on_action(cx.listener(Self::fold_directory))
+                .on_action(cx.listener(Self::focus_on_folder))
+                .on_action(cx.listener(Self::exit_folder_focus))
+                .on_action(cx.listener(Self::increase_panel_width))
+                .on_action(cx.listener(Self::decrease_panel_width))
+                .on_action(cx.listener(Self::reset_panel_width))
+                .on_action(cx.listener(Self::refresh))
+                .on_action(cx.listener(Self::toggle_changed_files_only))
+                .when(!project.is_read_only(), |el| {
+                    el.on_action(cx.listener(Self::new_file))
+                        .on_action(cx.listener(Self::new_directory))
+                        .on_action(cx.listener(Self::rename))
+                        .on_action(cx.listener(Self::delete))
+                        .on_action(cx.listener(Self::trash))
+                        .on_action(cx.listener(Self::undo))
+                        .on_action(cx.listener(Self::redo))
+                        .on_action(cx.listener(Self::cut))
+                        .on_action(cx.listener(Self::copy))
+                        .on_action(cx.listener(Self::paste))
+                        .on_action(cx.listener(Self::duplicate))
+                })
+                .when(project.is_local(), |el| {
+                    el.on_action(cx.listener(Self::reveal_in_finder))
+                        .on_action(cx.listener(Self::open_in_terminal))
+                })
+                .when(project.is_local() && !project.is_read_only(), |el| {
+                    el.on_action(cx.listener(Self::paste_with_source_comment))
+                        .on_action(cx.listener(Self::restore_deleted_file))
+                        .on_action(cx.listener(Self::stage_deletion))
+                })
+                .on_mouse_down(
+                    MouseButton::Right,
+                    cx.listener(move |this, event: &MouseDownEvent, cx| {
+                        // When deploying the context menu anywhere below the last project entry,
+                        // act as if the user clicked the root of the last worktree.
+                        if let Some(entry_id) = this.last_worktree_root_id {
+                            this.deploy_context_menu(event.position, entry_id, cx);
+                        }
+                    }),
+                )
+                .on_drop(
+                    cx.listener(move |this, external_paths: &ExternalPaths, cx| {
+                        this.last_external_paths_drag_over_entry = None;
+                        this.marked_entries.clear();
+                        this.drop_external_paths_on_panel(external_paths.paths(), cx);
+                        cx.stop_propagation();
+                    }),
+                )
+                .track_focus(&self.focus_handle)
+                .children(self.render_toolbar(cx))
+                .children(self.render_open_editors(cx))
+                .children(self.render_duplicate_worktree_header(cx))
+                .children(self.render_low_space_header(cx))
+                .children(self.render_tree_view_sharing_header(cx))
+                .children(self.render_folder_focus_header(cx))
+                .children(self.render_tag_filter_header(cx))
+                .child(self.render_filter_editor(cx))
+                .children(self.render_unmounted_worktrees(cx))
+                .children(self.visible_entry_picker.as_ref().map(|(picker, _)| {
+                    div()
+                        .flex_none()
+                        .border_b_1()
+                        .border_color(cx.theme().colors().border_variant)
+                        .child(picker.clone())
+                }))
+                .child(
+                    uniform_list(cx.view().clone(), "entries", items_count, {
+                        |this, range, cx| {
+                            this.set_rendered_range(range.clone(), cx);
+                            let mut items = Vec::new();
+                            this.for_each_visible_entry(range, cx, |id, details, cx| {
+                                items.push(this.render_entry(id, details, cx));
+                            });
+                            items
+                        }
+                    })
+                    .size_full()
+                    .with_sizing_behavior(ListSizingBehavior::Infer)
+                    .with_scroll_sensitivity(
+                        ProjectPanelSettings::get_global(cx)
+                            .scroll_sensitivity
+                            .max(MIN_SCROLL_SENSITIVITY),
+                    )
+                    .track_scroll(self.scroll_handle.clone()),
+                )
+                .children(self.render_scrollbar(items_count, cx))
+                .children(self.context_menu.as_ref().map(|(menu, position, _)| {
+                    deferred(
+                        anchored()
+                            .position(*position)
+                            .anchor(
+                                if ProjectPanelSettings::get_global(cx).dock
+                                    == ProjectPanelDockPosition::Right
+                                {
+                                    gpui::AnchorCorner::TopRight
+                                } else {
+                                    gpui::AnchorCorner::TopLeft
+                                },
+                            )
+                            .child(menu.clone()),
+                    )
+                    .with_priority(1)
+                }))
+        } else {
+            v_flex()
+                .id("empty-project_panel")
+                .size_full()
+                .p_4()
+                .gap_2()
+                .track_focus(&self.focus_handle)
+                .on_action(cx.listener(Self::reconnect_worktrees))
+                .children(self.render_unmounted_worktrees(cx))
+                .child(
+                    Button::new("open_project", "Open a project")
+                        .style(ButtonStyle::Filled)
+                        .full_width()
+                        .key_binding(KeyBinding::for_action(&workspace::Open, cx))
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.workspace
+                                .update(cx, |workspace, cx| workspace.open(&workspace::Open, cx))
+                                .log_err();
+                        })),
+                )
+                .children(self.render_recent_projects(cx))
+                .drag_over::<ExternalPaths>(|style, _, cx| {
+                    style.bg(cx.theme().colors().drop_target_background)
+                })
+                .on_drop(
+                    cx.listener(move |this, external_paths: &ExternalPaths, cx| {
+                        this.last_external_paths_drag_over_entry = None;
+                        this.marked_entries.clear();
+                        if let Some(task) = this
+                            .workspace
+                            .update(cx, |workspace, cx| {
+                                workspace.open_workspace_for_paths(
+                                    true,
+                                    external_paths.paths().to_owned(),
+                                    cx,
+                                )
+                            })
+                            .log_err()
+                        {
+                            task.detach_and_log_err(cx);
+                        }
+                        cx.stop_propagation();
+                    }),
+                )
+        }
+    }
+}
+
+impl Render for DraggedProjectEntryView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let settings = ProjectPanelSettings::get_global(cx);
+        let ui_font = ThemeSettings::get_global(cx).ui_font.clone();
+        h_flex().font(ui_font).map(|this| {
+            if self.selections.contains(&self.selection) {
+                this.flex_shrink()
+                    .p_1()
+                    .items_end()
+                    .rounded_md()
+                    .child(self.selections.len().to_string())
+            } else {
+                this.bg(cx.theme().colors().background).w(self.width).child(
+                    ListItem::new(self.selection.entry_id.to_proto() as usize)
+                        .indent_level(self.details.depth)
+                        .indent_step_size(px(settings.indent_size))
+                        .mirrored(settings.is_mirrored())
+                        .child(if let Some(icon) = &self.details.icon {
+                            div().child(
+                                Icon::from_path(icon.to_string())
+                                    .size(entry_icon_size(settings, cx)),
+                            )
+                        } else {
+                            div()
+                        })
+                        .child(Label::new(self.details.filename.clone())),
+                )
+            }
+        })
+    }
+}
+
+impl EventEmitter<Event> for ProjectPanel {}
+
+impl EventEmitter<PanelEvent> for ProjectPanel {}
+
+impl Panel for ProjectPanel {
+    fn position(&self, cx: &WindowContext) -> DockPosition {
+        match ProjectPanelSettings::get_global(cx).dock {
+            ProjectPanelDockPosition::Left => DockPosition::Left,
+            ProjectPanelDockPosition::Right => DockPosition::Right,
+        }
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
+        settings::update_settings_file::<ProjectPanelSettings>(
+            self.fs.clone(),
+            cx,
+            move |settings| {
+                let dock = match position {
+                    DockPosition::Left | DockPosition::Bottom => ProjectPanelDockPosition::Left,
+                    DockPosition::Right => ProjectPanelDockPosition::Right,
+                };
+                settings.dock = Some(dock);
+            },
+        );
+    }
+
+    fn size(&self, cx: &WindowContext) -> Pixels {
+        display_key(cx)
+            .and_then(|display| self.width_by_display.get(&display).copied())
+            .or(self.width)
+            .unwrap_or_else(|| ProjectPanelSettings::get_global(cx).default_width)
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
+        self.width = size;
+        if let Some(display) = display_key(cx) {
+            match size {
+                Some(size) => self.width_by_display.insert(display, size),
+                None => self.width_by_display.remove(&display),
+            };
+        }
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn icon(&self, cx: &WindowContext) -> Option<IconName> {
+        ProjectPanelSettings::get_global(cx)
+            .button
+            .then(|| IconName::FileTree)
+    }
+
+    fn icon_tooltip(&self, _cx: &WindowContext) -> Option<&'static str> {
+        Some("Project Panel")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+
+    fn peek_on_hover(&self, cx: &WindowContext) -> bool {
+        ProjectPanelSettings::get_global(cx).peek_on_hover
+    }
+
+    fn persistent_name() -> &'static str {
+        "Project Panel"
+    }
+
+    fn starts_open(&self, cx: &WindowContext) -> bool {
+        let project = &self.project.read(cx);
+        project.dev_server_project_id().is_some()
+            || project.visible_worktrees(cx).any(|tree| {
+                tree.read(cx)
+                    .root_entry()
+                    .map_or(false, |entry| entry.is_dir())
+            })
+    }
+}
+
+impl FocusableView for ProjectPanel {
+    fn focus_handle(&self, _cx: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+/// Looks up an entry by path, optionally ignoring the case of each component.
+/// An exact match always wins over a case-insensitive one.
+fn find_entry_for_path<'a>(
+    worktree: &'a Worktree,
+    path: &Path,
+    case_sensitive: bool,
+) -> Option<&'a Entry> {
+    if let Some(entry) = worktree.entry_for_path(path) {
+        return Some(entry);
+    }
+    if case_sensitive {
+        return None;
+    }
+
+    let mut entry = worktree.root_entry()?;
+    for component in path.components() {
+        let name = component.as_os_str().to_string_lossy().to_lowercase();
+        entry = worktree.child_entries(&entry.path).find(|child| {
+            child.path.file_name().map_or(false, |child_name| {
+                child_name.to_string_lossy().to_lowercase() == name
+            })
+        })?;
+    }
+    Some(entry)
+}
+
+/// Identifies the display the window is on, so that widths picked on a laptop screen
+/// and on an external monitor are remembered separately.
+fn display_key(cx: &WindowContext) -> Option<String> {
+    Some(cx.display()?.uuid().ok()?.to_string())
+}
+
+/// The size of entry icons, converted to rems so that it stays the same whatever the UI font size.
+fn entry_icon_size(settings: &ProjectPanelSettings, cx: &WindowContext) -> IconSize {
+    IconSize::Custom(rems(settings.icon_size.pixels().0 / cx.rem_size().0))
+}
+
+/// Wraps the text of each file in a code block headed by its path, with a fence longer than
+/// any run of backticks in the text.
+fn fenced_file_contents(files: &[(Arc<Path>, String)]) -> String {
+    files
+        .iter()
+        .map(|(path, text)| {
+            let mut fence = "```".to_string();
+            while text.contains(&fence) {
+                fence.push('`');
+            }
+            let newline = if text.is_empty() || text.ends_with('\n') {
+                ""
+            } else {
+                "\n"
+            };
+            let path = path
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            format!("{fence}{path}\n{text}{newline}{fence}\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Describes the numbers of files with each status, e.g. "1 conflicted, 3 modified".
+fn git_statuses_summary(statuses: GitStatuses) -> String {
+    [
+        (statuses.conflict, "conflicted"),
+        (statuses.modified, "modified"),
+        (statuses.added, "added"),
+    ]
+    .into_iter()
+    .filter(|(count, _)| *count > 0)
+    .map(|(count, label)| format!("{count} {label}"))
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+/// The glyph shown after entries with the given status, with its description.
+fn git_status_glyph(status: GitFileStatus) -> (&'static str, &'static str) {
+    match status {
+        GitFileStatus::Added => ("A", "Added"),
+        GitFileStatus::Modified => ("M", "Modified"),
+        GitFileStatus::Conflict => ("!", "Conflict"),
+    }
+}
+
+fn diagnostic_summary_text(summary: DiagnosticSummary) -> String {
+    [
+        (summary.error_count, "error"),
+        (summary.warning_count, "warning"),
+    ]
+    .into_iter()
+    .filter(|(count, _)| *count > 0)
+    .map(|(count, label)| {
+        if count == 1 {
+            format!("{count} {label}")
+        } else {
+            format!("{count} {label}s")
+        }
+    })
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+/// Formats the count with thousands separators, e.g. "12,345".
+fn format_count(count: usize) -> String {
+    let digits = count.to_string();
+    let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+    for (ix, digit) in digits.chars().enumerate() {
+        if ix > 0 && (digits.len() - ix) % 3 == 0 {
+            formatted.push(',');
+        }
+        formatted.push(digit);
+    }
+    formatted
+}
+
+/// Fills in `{filename}` and `{path}` in the template. The path always uses forward slashes
+/// and has its spaces escaped, so the link stays valid Markdown.
+fn markdown_link(template: &str, file_name: &str, path: &Path) -> String {
+    let mut link_path = path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    if link_path.is_empty() {
+        link_path.push('.');
+    }
+    template
+        .replace("{filename}", file_name)
+        .replace("{path}", &link_path.replace(' ', "%20"))
+}
+
+/// Re-sorts the siblings of entries sorted by name, keeping directories where
+/// `folders_position` puts them and falling back to the name order for entries that compare
+/// equal in `sort_by`.
+fn sort_entries_by(
+    entries: &mut Vec<Entry>,
+    sort_by: EntrySortMode,
+    folders_position: FoldersPosition,
+    file_sizes: &HashMap<ProjectEntryId, (Option<SystemTime>, u64)>,
+) {
+    let ixs_by_path = entries
+        .iter()
+        .enumerate()
+        .map(|(ix, entry)| (entry.path.clone(), ix))
+        .collect::<HashMap<_, _>>();
+    let mut roots = Vec::new();
+    let mut children = HashMap::<usize, Vec<usize>>::default();
+    for (ix, entry) in entries.iter().enumerate() {
+        let parent_ix = entry
+            .path
+            .ancestors()
+            .skip(1)
+            .find_map(|ancestor| ixs_by_path.get(ancestor).copied());
+        match parent_ix {
+            Some(parent_ix) => children.entry(parent_ix).or_default().push(ix),
+            None => roots.push(ix),
+        }
+    }
+
+    let compare = |a: &usize, b: &usize| {
+        let (a, b) = (&entries[*a], &entries[*b]);
+        let kind_ordering = match folders_position {
+            FoldersPosition::First => b.is_dir().cmp(&a.is_dir()),
+            FoldersPosition::Last => a.is_dir().cmp(&b.is_dir()),
+            FoldersPosition::Mixed => cmp::Ordering::Equal,
+        };
+        kind_ordering.then_with(|| match sort_by {
+            EntrySortMode::Name => cmp::Ordering::Equal,
+            EntrySortMode::Extension => {
+                let extension = |entry: &Entry| {
+                    entry
+                        .is_file()
+                        .then(|| entry.path.extension())
+                        .flatten()
+                        .map(|extension| extension.to_string_lossy().to_lowercase())
+                };
+                extension(a).cmp(&extension(b))
+            }
+            EntrySortMode::ModificationTime => b.mtime.cmp(&a.mtime),
+            EntrySortMode::Size => {
+                let size = |entry: &Entry| file_sizes.get(&entry.id).map(|(_, len)| *len);
+                size(b).cmp(&size(a))
+            }
+        })
+    };
+    roots.sort_by(&compare);
+    for siblings in children.values_mut() {
+        siblings.sort_by(&compare);
+    }
+
+    let mut sorted_ixs = Vec::with_capacity(entries.len());
+    let mut stack = roots.into_iter().rev().collect::<Vec<_>>();
+    while let Some(ix) = stack.pop() {
+        sorted_ixs.push(ix);
+        if let Some(siblings) = children.get(&ix) {
+            stack.extend(siblings.iter().rev());
+        }
+    }
+    let mut old_entries = mem::take(entries).into_iter().map(Some).collect::<Vec<_>>();
+    entries.extend(
+        sorted_ixs
+            .into_iter()
+            .filter_map(|ix| old_entries[ix].take()),
+    );
+}
+
+/// Moves the files related to a source file by the `file_nesting` patterns right after it,
+/// leaving them out while the source is collapsed. Nesting is one level deep: files nested
+/// under another one keep their own related files at the top level. Files related to several
+/// sources go under the first one of the entries, the patterns being visited in order.
+fn nest_related_files(
+    entries: &mut Vec<Entry>,
+    patterns: &std::collections::BTreeMap<String, Vec<String>>,
+    expanded_entry_ids: &[ProjectEntryId],
+) -> FileNesting {
+    let files_by_path = entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| entry.is_file())
+        .map(|(ix, entry)| (entry.path.clone(), ix))
+        .collect::<HashMap<_, _>>();
+    let mut related_files = Vec::new();
+    for (source_ix, entry) in entries.iter().enumerate() {
+        let Some((parent, file_name)) = entry
+            .path
+            .parent()
+            .zip(entry.path.file_name().and_then(OsStr::to_str))
+            .filter(|_| entry.is_file())
+        else {
+            continue;
+        };
+        for (source_pattern, nested_patterns) in patterns {
+            let Some(capture) = nesting_capture(source_pattern, file_name) else {
+                continue;
+            };
+            for nested_pattern in nested_patterns {
+                let nested_name = nested_pattern.replacen('*', capture, 1);
+                if nested_name == file_name {
+                    continue;
+                }
+                if let Some(&nested_ix) = files_by_path.get(parent.join(nested_name).as_path()) {
+                    related_files.push((source_ix, nested_ix));
+                }
+            }
+        }
+    }
+
+    let related_ixs = related_files
+        .iter()
+        .map(|(_, nested_ix)| *nested_ix)
+        .collect::<HashSet<_>>();
+    let mut nested_ixs_by_source = HashMap::<usize, Vec<usize>>::default();
+    let mut nested_ixs = HashSet::new();
+    for (source_ix, nested_ix) in related_files {
+        if !related_ixs.contains(&source_ix) && nested_ixs.insert(nested_ix) {
+            nested_ixs_by_source
+                .entry(source_ix)
+                .or_default()
+                .push(nested_ix);
+        }
+    }
+
+    let mut nesting = FileNesting::default();
+    let mut old_entries = mem::take(entries).into_iter().map(Some).collect::<Vec<_>>();
+    for ix in 0..old_entries.len() {
+        if nested_ixs.contains(&ix) {
+            continue;
+        }
+        let Some(entry) = old_entries[ix].take() else {
+            continue;
+        };
+        let source_id = entry.id;
+        entries.push(entry);
+        if let Some(source_nested_ixs) = nested_ixs_by_source.get_mut(&ix) {
+            nesting.sources.insert(source_id);
+            if expanded_entry_ids.binary_search(&source_id).is_ok() {
+                source_nested_ixs.sort_unstable();
+                for &nested_ix in source_nested_ixs.iter() {
+                    if let Some(nested_entry) = old_entries[nested_ix].take() {
+                        nesting.nested.insert(nested_entry.id);
+                        entries.push(nested_entry);
+                    }
+                }
+            }
+        }
+    }
+    nesting
+}
+
+/// The part of `file_name` matched by the `*` of `pattern`, e.g. `foo` for `*.ts` and `foo.ts`.
+fn nesting_capture<'a>(pattern: &str, file_name: &'a str) -> Option<&'a str> {
+    let (prefix, suffix) = pattern.split_once('*')?;
+    file_name
+        .strip_prefix(prefix)?
+        .strip_suffix(suffix)
+        .filter(|capture| !capture.is_empty())
+}
+
+/// The worktree's entries fuzzy matching `query`, leaving out the ignored ones.
+fn filter_matches(snapshot: &worktree::Snapshot, query: &str) -> FilterMatches {
+    let candidates = snapshot
+        .entries(false, 0)
+        .filter(|entry| !entry.path.as_os_str().is_empty())
+        .map(|entry| PathMatchCandidate {
+            path: &entry.path,
+            char_bag: match entry.kind {
+                EntryKind::File(char_bag) => char_bag,
+                _ => CharBag::from_iter(entry.path.to_string_lossy().to_lowercase().chars()),
+            },
+        })
+        .collect();
+    let path_matches = fuzzy::match_fixed_path_set(
+        candidates,
+        snapshot.id().to_usize(),
+        query,
+        false,
+        usize::MAX,
+    );
+    let mut filter_matches = FilterMatches::default();
+    for path_match in path_matches {
+        filter_matches.insert(path_match.path);
+    }
+    filter_matches
+}
+
+/// Whether the directory is the work directory of its own git repository (not a submodule)
+/// inside the worktree, rather than the worktree root.
+fn is_nested_repo(snapshot: &worktree::Snapshot, entry: &Entry) -> bool {
+    entry.is_dir()
+        && entry.path.parent().is_some()
+        && snapshot
+            .repository_for_work_directory(&entry.path)
+            .is_some()
+        && !snapshot.is_submodule(&entry.path)
+}
+
+/// Checkouts linked to the worktree's root repository with `git worktree add`,
+/// excluding those that are already open in the project.
+fn linked_git_worktrees(worktree: &Worktree, project: &Project, cx: &AppContext) -> Vec<PathBuf> {
+    let Some(repo) = worktree
+        .as_local()
+        .and_then(|worktree| worktree.local_git_repo(Path::new("")))
+    else {
+        return Vec::new();
+    };
+    let mut abs_paths = repo.linked_worktrees().log_err().unwrap_or_default();
+    abs_paths.retain(|abs_path| {
+        project
+            .worktrees()
+            .all(|worktree| worktree.read(cx).abs_path().as_ref() != abs_path.as_path())
+    });
+    abs_paths
+}
+
+/// Lists the deleted files of the worktree under their nearest remaining ancestor, when that
+/// directory is shown expanded.
+fn insert_ghost_entries(
+    snapshot: &worktree::Snapshot,
+    deleted_files: &[Arc<Path>],
+    ghost_entry_ids: &mut HashMap<(WorktreeId, Arc<Path>), ProjectEntryId>,
+    ghost_entry_paths: &mut HashMap<ProjectEntryId, (WorktreeId, Arc<Path>)>,
+    focused_path: Option<&Path>,
+    expanded_dir_ids: &[ProjectEntryId],
+    visible_worktree_entries: &mut Vec<Entry>,
+) {
+    let worktree_id = snapshot.id();
+    for path in deleted_files {
+        if snapshot.entry_for_path(path).is_some()
+            || focused_path.map_or(false, |focused_path| !path.starts_with(focused_path))
+        {
+            continue;
+        }
+        let Some(ancestor) = path
+            .ancestors()
+            .skip(1)
+            .find_map(|ancestor| snapshot.entry_for_path(ancestor))
+        else {
+            continue;
+        };
+        if expanded_dir_ids.binary_search(&ancestor.id).is_err()
+            || !visible_worktree_entries
+                .iter()
+                .any(|entry| entry.id == ancestor.id)
+        {
+            continue;
+        }
+
+        let ghost_entry_count = ghost_entry_ids.len();
+        let id = *ghost_entry_ids
+            .entry((worktree_id, path.clone()))
+            .or_insert_with(|| {
+                let id = ProjectEntryId::from_proto(
+                    NEW_ENTRY_ID.to_proto() - 1 - ghost_entry_count as u64,
+                );
+                ghost_entry_paths.insert(id, (worktree_id, path.clone()));
+                id
+            });
+        visible_worktree_entries.push(Entry {
+            id,
+            kind: EntryKind::File(Default::default()),
+            path: path.clone(),
+            inode: 0,
+            mtime: None,
+            is_ignored: false,
+            is_external: false,
+            is_private: false,
+            is_special_file: false,
+            git_status: None,
+            canonical_path: None,
+            is_symlink: false,
+        });
+    }
+}
+
+/// Returns where an external entry really is, from the target of the symlink it is reached through.
+fn external_location(snapshot: &worktree::Snapshot, entry: &Entry) -> Option<PathBuf> {
+    if !entry.is_external {
+        return None;
+    }
+    entry.path.ancestors().find_map(|ancestor| {
+        let target = snapshot.entry_for_path(ancestor)?.canonical_path.as_ref()?;
+        Some(target.join(entry.path.strip_prefix(ancestor).ok()?))
+    })
+}
+
+/// Whether the entry is outside of the worktree, inside of a symlinked directory rather than the
+/// symlink itself, so that deleting it removes files of another location.
+fn is_reached_through_symlink(worktree: &Worktree, entry_id: ProjectEntryId) -> bool {
+    worktree.entry_for_id(entry_id).map_or(false, |entry| {
+        entry.is_external
+            && entry
+                .path
+                .parent()
+                .and_then(|parent| worktree.entry_for_path(parent))
+                .map_or(false, |parent| parent.is_external)
+    })
+}
+
+fn activate_open_editor(pane: &WeakView<Pane>, item_id: EntityId, cx: &mut WindowContext) {
+    pane.update(cx, |pane, cx| {
+        if let Some(ix) = pane.items().position(|item| item.item_id() == item_id) {
+            pane.activate_item(ix, true, true, cx);
+        }
+    })
+    .ok();
+}
+
+fn close_open_editor(pane: &WeakView<Pane>, item_id: EntityId, cx: &mut WindowContext) {
+    pane.update(cx, |pane, cx| {
+        pane.close_item_by_id(item_id, SaveIntent::Close, cx)
+            .detach_and_log_err(cx);
+    })
+    .ok();
+}
+
+fn generated_paths_matcher(settings: &ProjectPanelSettings) -> PathMatcher {
+    PathMatcher::new(&settings.generated_paths)
+        .log_err()
+        .unwrap_or_default()
+}
+
+fn worktree_settings(worktree_id: WorktreeId, cx: &AppContext) -> &WorktreeSettings {
+    WorktreeSettings::get(
+        Some(SettingsLocation {
+            worktree_id: worktree_id.to_usize(),
+            path: Path::new(""),
+        }),
+        cx,
+    )
+}
+
+/// Returns the directories directly inside `dir_path` that the worktree's project settings
+/// excluded from scanning, as their exclusion globs.
+fn unwatched_folders(worktree_id: WorktreeId, dir_path: &Path, cx: &AppContext) -> Vec<String> {
+    let global_exclusions = WorktreeSettings::get_global(cx)
+        .file_scan_exclusions
+        .sources();
+    worktree_settings(worktree_id, cx)
+        .file_scan_exclusions
+        .sources()
+        .iter()
+        .filter(|glob| {
+            !global_exclusions.contains(glob)
+                && !glob.contains(['*', '?', '[', '{'])
+                && Path::new(glob.as_str()).parent() == Some(dir_path)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Windows refuses longer paths, unless long path support is enabled in the system.
+const WINDOWS_MAX_PATH: usize = 260;
+
+/// Whether the path is over the limit Windows applies to paths, counted in UTF-16 units.
+#[cfg(target_os = "windows")]
+fn is_too_long_for_windows(abs_path: &Path) -> bool {
+    use std::os::windows::ffi::OsStrExt;
+
+    abs_path.as_os_str().encode_wide().count() >= WINDOWS_MAX_PATH
+}
+
+#[cfg(not(target_os = "windows"))]
+fn is_too_long_for_windows(_: &Path) -> bool {
+    false
+}
+
+/// Whether `new_path` lies strictly inside of `source_path`,
+/// i.e. a directory would be moved or copied into one of its own descendants.
+fn is_inside_own_subtree(source_path: &Path, new_path: &Path) -> bool {
+    new_path != source_path && new_path.starts_with(source_path)
+}
+
+fn subtree_cycle_message(source_path: &Path, is_move: bool) -> String {
+    let operation = if is_move { "move" } else { "copy" };
+    format!(
+        "Cannot {operation} {:?} into itself or one of its subfolders",
+        source_path
+    )
+}
+
+impl ClipboardEntry {
+    fn is_cut(&self) -> bool {
+        matches!(self, Self::Cut { .. })
+    }
+
+    fn items(&self) -> &BTreeSet<SelectedEntry> {
+        match self {
+            ClipboardEntry::Copied(entries) | ClipboardEntry::Cut(entries) => entries,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use collections::HashSet;
+    use decorations::{
+        parse_checksums, ModificationAge, AVAILABLE_SPACE_DEBOUNCE, FILE_HEADER_BATCH_INTERVAL,
+        FILE_HEADER_BATCH_SIZE,
+    };
+    use gpui::{Empty, TestAppContext, View, VisualTestContext, WindowHandle};
+    use language::{Language, LanguageConfig, LanguageMatcher};
+    use picker::PickerDelegate;
+    use pretty_assertions::assert_eq;
+    use project::{FakeFs, RemoveOptions, WorktreeSettings};
+    use project_panel_settings::GitStatusKind;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::path::{Path, PathBuf};
+    use workspace::{
+        item::{Item, ProjectItem},
+        register_project_item, AppState,
+    };
+
+    #[gpui::test]
+    async fn test_visible_list(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root1",
+            json!({
+                ".dockerignore": "",
+                ".git": {
+                    "HEAD": "",
+                },
+                "a": {
+                    "0": { "q": "", "r": "", "s": "" },
+                    "1": { "t": "", "u": "" },
+                    "2": { "v": "", "w": "", "x": "", "y": "" },
+                },
+                "b": {
+                    "3": { "Q": "" },
+                    "4": { "R": "", "S": "", "T": "", "U": "" },
+                },
+                "C": {
+                    "5": {},
+                    "6": { "V": "", "W": "" },
+                    "7": { "X": "" },
+                    "8": { "Y": {}, "Z": "" }
+                }
+            }),
+        )
+        .await;
+        fs.insert_tree(
+            "/root2",
+            json!({
+                "d": {
+                    "9": ""
+                },
+                "e": {}
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root1".as_ref(), "/root2".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..50, cx),
+            &[
+                "v root1",
+                "    > .git",
+                "    > a",
+                "    > b",
+                "    > C",
+                "      .dockerignore",
+                "v root2",
+                "    > d",
+                "    > e",
+            ]
+        );
+
+        toggle_expand_dir(&panel, "root1/b", cx);
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..50, cx),
+            &[
+                "v root1",
+                "    > .git",
+                "    > a",
+                "    v b  <== selected",
+                "        > 3",
+                "        > 4",
+                "    > C",
+                "      .dockerignore",
+                "v root2",
+                "    > d",
+                "    > e",
+            ]
+        );
+
+        assert_eq!(
+            visible_entries_as_strings(&panel, 6..9, cx),
+            &[
+                //
+                "    > C",
+                "      .dockerignore",
+                "v root2",
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_exclusions_in_visible_list(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<WorktreeSettings>(cx, |worktree_settings| {
+                    worktree_settings.file_scan_exclusions =
+                        Some(vec!["**/.git".to_string(), "**/4/**".to_string()]);
+                });
+            });
+        });
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/root1",
+            json!({
+                ".dockerignore": "",
+                ".git": {
+                    "HEAD": "",
+                },
+                "a": {
+                    "0": { "q": "", "r": "", "s": "" },
+                    "1": { "t": "", "u": "" },
+                    "2": { "v": "", "w": "", "x": "", "y": "" },
+                },
+                "b": {
+                    "3": { "Q": "" },
+                    "4": { "R": "", "S": "", "T": "", "U": "" },
+                },
+                "C": {
+                    "5": {},
+                    "6": { "V": "", "W": "" },
+                    "7": { "X": "" },
+                    "8": { "Y": {}, "Z": "" }
+                }
+            }),
+        )
+        .await;
+        fs.insert_tree(
+            "/root2",
+            json!({
+                "d": {
+                    "4": ""
+                },
+                "e": {}
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root1".as_ref(), "/root2".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..50, cx),
+            &[
+                "v root1",
+                "    > a",
+                "    > b",
+                "    > C",
+                "      .dockerignore",
+                "v root2",
+                "    > d",
+                "    > e",
+            ]
+        );
+
+        toggle_expand_dir(&panel, "root1/b", cx);
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..50, cx),
+            &[
+                "v root1",
+                "    > a",
+                "    v b  <== selected",
+                "        > 3",
+                "    > C",
+                "      .dockerignore",
+                "v root2",
+                "    > d",
+                "    > e",
+            ]
+        );
+
+        toggle_expand_dir(&panel, "root2/d", cx);
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..50, cx),
+            &[
+                "v root1",
+                "    > a",
+                "    v b",
+                "        > 3",
+                "    > C",
+                "      .dockerignore",
+                "v root2",
+                "    v d  <== selected",
+                "    > e",
+            ]
+        );
+
+        toggle_expand_dir(&panel, "root2/e", cx);
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..50, cx),
+            &[
+                "v root1",
+                "    > a",
+                "    v b",
+                "        > 3",
+                "    > C",
+                "      .dockerignore",
+                "v root2",
+                "    v d",
+                "    v e  <== selected",
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_auto_collapse_dir_paths(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root1",
+            json!({
+                "dir_1": {
+                    "nested_dir_1": {
+                        "nested_dir_2": {
+                            "nested_dir_3": {
+                                "file_a.java": "// File contents",
+                                "file_b.java": "// File contents",
+                                "file_c.java": "// File contents",
+                                "nested_dir_4": {
+                                    "nested_dir_5": {
+                                        "file_d.java": "// File contents",
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }),
+        )
+        .await;
+        fs.insert_tree(
+            "/root2",
+            json!({
+                "dir_2": {
+                    "file_1.java": "// File contents",
+                }
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root1".as_ref(), "/root2".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        cx.update(|cx| {
+            let settings = ProjectPanelSettings::get_global(cx).clone();
+            ProjectPanelSettings::override_global(
+                ProjectPanelSettings {
+                    auto_fold_dirs: true,
+                    ..settings
+                },
+                cx,
+            );
+        });
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root1",
+                "    > dir_1/nested_dir_1/nested_dir_2/nested_dir_3",
+                "v root2",
+                "    > dir_2",
+            ]
+        );
+
+        toggle_expand_dir(
+            &panel,
+            "root1/dir_1/nested_dir_1/nested_dir_2/nested_dir_3",
+            cx,
+        );
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root1",
+                "    v dir_1/nested_dir_1/nested_dir_2/nested_dir_3  <== selected",
+                "        > nested_dir_4/nested_dir_5",
+                "          file_a.java",
+                "          file_b.java",
+                "          file_c.java",
+                "v root2",
+                "    > dir_2",
+            ]
+        );
+
+        toggle_expand_dir(
+            &panel,
+            "root1/dir_1/nested_dir_1/nested_dir_2/nested_dir_3/nested_dir_4/nested_dir_5",
+            cx,
+        );
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root1",
+                "    v dir_1/nested_dir_1/nested_dir_2/nested_dir_3",
+                "        v nested_dir_4/nested_dir_5  <== selected",
+                "              file_d.java",
+                "          file_a.java",
+                "          file_b.java",
+                "          file_c.java",
+                "v root2",
+                "    > dir_2",
+            ]
+        );
+        toggle_expand_dir(&panel, "root2/dir_2", cx);
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root1",
+                "    v dir_1/nested_dir_1/nested_dir_2/nested_dir_3",
+                "        v nested_dir_4/nested_dir_5",
+                "              file_d.java",
+                "          file_a.java",
+                "          file_b.java",
+                "          file_c.java",
+                "v root2",
+                "    v dir_2  <== selected",
+                "          file_1.java",
+            ]
+        );
+    }
+
+    #[gpui::test(iterations = 30)]
+    async fn test_editing_files(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root1",
+            json!({
+                ".dockerignore": "",
+                ".git": {
+                    "HEAD": "",
+                },
+                "a": {
+                    "0": { "q": "", "r": "", "s": "" },
+                    "1": { "t": "", "u": "" },
+                    "2": { "v": "", "w": "", "x": "", "y": "" },
+                },
+                "b": {
+                    "3": { "Q": "" },
+                    "4": { "R": "", "S": "", "T": "", "U": "" },
+                },
+                "C": {
+                    "5": {},
+                    "6": { "V": "", "W": "" },
+                    "7": { "X": "" },
+                    "8": { "Y": {}, "Z": "" }
+                }
+            }),
+        )
+        .await;
+        fs.insert_tree(
+            "/root2",
+            json!({
+                "d": {
+                    "9": ""
+                },
+                "e": {}
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root1".as_ref(), "/root2".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| {
+                let panel = ProjectPanel::new(workspace, cx);
+                workspace.add_panel(panel.clone(), cx);
+                panel
+            })
+            .unwrap();
+
+        select_path(&panel, "root1", cx);
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root1  <== selected",
+                "    > .git",
+                "    > a",
+                "    > b",
+                "    > C",
+                "      .dockerignore",
+                "v root2",
+                "    > d",
+                "    > e",
+            ]
+        );
+
+        // Add a file with the root folder selected. The filename editor is placed
+        // before the first file in the root folder.
+        panel.update(cx, |panel, cx| panel.new_file(&NewFile, cx));
+        panel.update(cx, |panel, cx| {
+            assert!(panel.filename_editor.read(cx).is_focused(cx));
+        });
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root1",
+                "    > .git",
+                "    > a",
+                "    > b",
+                "    > C",
+                "      [EDITOR: '']  <== selected",
+                "      .dockerignore",
+                "v root2",
+                "    > d",
+                "    > e",
+            ]
+        );
+
+        let confirm = panel.update(cx, |panel, cx| {
+            panel
+                .filename_editor
+                .update(cx, |editor, cx| editor.set_text("the-new-filename", cx));
+            panel.confirm_edit(cx).unwrap()
+        });
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root1",
+                "    > .git",
+                "    > a",
+                "    > b",
+                "    > C",
+                "      [PROCESSING: 'the-new-filename']  <== selected",
+                "      .dockerignore",
+                "v root2",
+                "    > d",
+                "    > e",
+            ]
+        );
+
+        confirm.await.unwrap();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root1",
+                "    > .git",
+                "    > a",
+                "    > b",
+                "    > C",
+                "      .dockerignore",
+                "      the-new-filename  <== selected  <== marked",
+                "v root2",
+                "    > d",
+                "    > e",
+            ]
+        );
+
+        select_path(&panel, "root1/b", cx);
+        panel.update(cx, |panel, cx| panel.new_file(&NewFile, cx));
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root1",
+                "    > .git",
+                "    > a",
+                "    v b",
+                "        > 3",
+                "        > 4",
+                "          [EDITOR: '']  <== selected",
+                "    > C",
+                "      .dockerignore",
+                "      the-new-filename",
+            ]
+        );
+
+        panel
+            .update(cx, |panel, cx| {
+                panel
+                    .filename_editor
+                    .update(cx, |editor, cx| editor.set_text("another-filename.txt", cx));
+                panel.confirm_edit(cx).unwrap()
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root1",
+                "    > .git",
+                "    > a",
+                "    v b",
+                "        > 3",
+                "        > 4",
+                "          another-filename.txt  <== selected  <== marked",
+                "    > C",
+                "      .dockerignore",
+                "      the-new-filename",
+            ]
+        );
+
+        select_path(&panel, "root1/b/another-filename.txt", cx);
+        panel.update(cx, |panel, cx| panel.rename(&Rename, cx));
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root1",
+                "    > .git",
+                "    > a",
+                "    v b",
+                "        > 3",
+                "        > 4",
+                "          [EDITOR: 'another-filename.txt']  <== selected  <== marked",
+                "    > C",
+                "      .dockerignore",
+                "      the-new-filename",
+            ]
+        );
+
+        let confirm = panel.update(cx, |panel, cx| {
+            panel.filename_editor.update(cx, |editor, cx| {
+                let file_name_selections = editor.selections.all::<usize>(cx);
+                assert_eq!(file_name_selections.len(), 1, "File editing should have a single selection, but got: {file_name_selections:?}");
+                let file_name_selection = &file_name_selections[0];
+                assert_eq!(file_name_selection.start, 0, "Should select the file name from the start");
+                assert_eq!(file_name_selection.end, "another-filename".len(), "Should not select file extension");
+
+                editor.set_text("a-different-filename.tar.gz", cx)
+            });
+            panel.confirm_edit(cx).unwrap()
+        });
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root1",
+                "    > .git",
+                "    > a",
+                "    v b",
+                "        > 3",
+                "        > 4",
+                "          [PROCESSING: 'a-different-filename.tar.gz']  <== selected  <== marked",
+                "    > C",
+                "      .dockerignore",
+                "      the-new-filename",
+            ]
+        );
+
+        confirm.await.unwrap();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root1",
+                "    > .git",
+                "    > a",
+                "    v b",
+                "        > 3",
+                "        > 4",
+                "          a-different-filename.tar.gz  <== selected",
+                "    > C",
+                "      .dockerignore",
+                "      the-new-filename",
+            ]
+        );
+
+        panel.update(cx, |panel, cx| panel.rename(&Rename, cx));
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root1",
+                "    > .git",
+                "    > a",
+                "    v b",
+                "        > 3",
+                "        > 4",
+                "          [EDITOR: 'a-different-filename.tar.gz']  <== selected",
+                "    > C",
+                "      .dockerignore",
+                "      the-new-filename",
+            ]
+        );
+
+        panel.update(cx, |panel, cx| {
+            panel.filename_editor.update(cx, |editor, cx| {
+                let file_name_selections = editor.selections.all::<usize>(cx);
+                assert_eq!(file_name_selections.len(), 1, "File editing should have a single selection, but got: {file_name_selections:?}");
+                let file_name_selection = &file_name_selections[0];
+                assert_eq!(file_name_selection.start, 0, "Should select the file name from the start");
+                assert_eq!(file_name_selection.end, "a-different-filename.tar".len(), "Should not select file extension, but still may select anything up to the last dot..");
+
+            });
+            panel.cancel(&menu::Cancel, cx)
+        });
+
+        panel.update(cx, |panel, cx| panel.new_directory(&NewDirectory, cx));
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root1",
+                "    > .git",
+                "    > a",
+                "    v b",
+                "        > [EDITOR: '']  <== selected",
+                "        > 3",
+                "        > 4",
+                "          a-different-filename.tar.gz",
+                "    > C",
+                "      .dockerignore",
+            ]
+        );
+
+        let confirm = panel.update(cx, |panel, cx| {
+            panel
+                .filename_editor
+                .update(cx, |editor, cx| editor.set_text("new-dir", cx));
+            panel.confirm_edit(cx).unwrap()
+        });
+        panel.update(cx, |panel, cx| panel.select_next(&Default::default(), cx));
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root1",
+                "    > .git",
+                "    > a",
+                "    v b",
+                "        > [PROCESSING: 'new-dir']",
+                "        > 3  <== selected",
+                "        > 4",
+                "          a-different-filename.tar.gz",
+                "    > C",
+                "      .dockerignore",
+            ]
+        );
+
+        confirm.await.unwrap();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root1",
+                "    > .git",
+                "    > a",
+                "    v b",
+                "        > 3  <== selected",
+                "        > 4",
+                "        > new-dir",
+                "          a-different-filename.tar.gz",
+                "    > C",
+                "      .dockerignore",
+            ]
+        );
+
+        panel.update(cx, |panel, cx| panel.rename(&Default::default(), cx));
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root1",
+                "    > .git",
+                "    > a",
+                "    v b",
+                "        > [EDITOR: '3']  <== selected",
+                "        > 4",
+                "        > new-dir",
+                "          a-different-filename.tar.gz",
+                "    > C",
+                "      .dockerignore",
+            ]
+        );
+
+        // Dismiss the rename editor when it loses focus.
+        workspace.update(cx, |_, cx| cx.blur()).unwrap();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root1",
+                "    > .git",
+                "    > a",
+                "    v b",
+                "        > 3  <== selected",
+                "        > 4",
+                "        > new-dir",
+                "          a-different-filename.tar.gz",
+                "    > C",
+                "      .dockerignore",
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_new_entry_folder_changed_externally(cx: &mut gpui::TestAppContext) {
+        init_test_with_editor(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "dir": {
+                    "file.txt": "",
+                },
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| {
+                let panel = ProjectPanel::new(workspace, cx);
+                workspace.add_panel(panel.clone(), cx);
+                panel
+            })
+            .unwrap();
+        let notifications = Rc::new(std::cell::RefCell::new(Vec::new()));
+        let _subscription = cx.update({
+            let notifications = notifications.clone();
+            |cx| {
+                cx.subscribe(&project, move |_, event: &project::Event, _| {
+                    if let project::Event::Notification(message) = event {
+                        notifications.borrow_mut().push(message.clone());
+                    }
+                })
+            }
+        });
+
+        select_path(&panel, "root/dir", cx);
+        panel.update(cx, |panel, cx| panel.new_file(&NewFile, cx));
+        fs.rename(
+            Path::new("/root/dir"),
+            Path::new("/root/moved"),
+            Default::default(),
+        )
+        .await
+        .unwrap();
+        cx.run_until_parked();
+        assert_eq!(
+            notifications.borrow_mut().drain(..).collect::<Vec<_>>(),
+            ["\"dir\" was moved to \"moved\", the new entry will be created there"]
+        );
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root",
+                "    v moved",
+                "          [EDITOR: '']  <== selected",
+                "          file.txt",
+            ]
+        );
+
+        let confirm = panel.update(cx, |panel, cx| {
+            panel
+                .filename_editor
+                .update(cx, |editor, cx| editor.set_text("new.txt", cx));
+            panel.confirm_edit(cx).unwrap()
+        });
+        confirm.await.unwrap();
+        cx.run_until_parked();
+        assert!(find_project_entry(&panel, "root/moved/new.txt", cx).is_some());
+        assert!(find_project_entry(&panel, "root/dir", cx).is_none());
+
+        select_path(&panel, "root/moved", cx);
+        panel.update(cx, |panel, cx| panel.new_directory(&NewDirectory, cx));
+        fs.remove_dir(
+            Path::new("/root/moved"),
+            RemoveOptions {
+                recursive: true,
+                ignore_if_not_exists: false,
+            },
+        )
+        .await
+        .unwrap();
+        cx.run_until_parked();
+        assert_eq!(
+            notifications.borrow().as_slice(),
+            ["\"moved\" was removed, the new entry was not created"]
+        );
+        panel.update(cx, |panel, cx| {
+            assert!(panel.edit_state.is_none());
+            assert!(!panel.filename_editor.read(cx).is_focused(cx));
+        });
+        assert_eq!(visible_entries_as_strings(&panel, 0..10, cx), &["v root"]);
+    }
+
+    #[gpui::test(iterations = 10)]
+    async fn test_adding_directories_via_file(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root1",
+            json!({
+                ".dockerignore": "",
+                ".git": {
+                    "HEAD": "",
+                },
+                "a": {
+                    "0": { "q": "", "r": "", "s": "" },
+                    "1": { "t": "", "u": "" },
+                    "2": { "v": "", "w": "", "x": "", "y": "" },
+                },
+                "b": {
+                    "3": { "Q": "" },
+                    "4": { "R": "", "S": "", "T": "", "U": "" },
+                },
+                "C": {
+                    "5": {},
+                    "6": { "V": "", "W": "" },
+                    "7": { "X": "" },
+                    "8": { "Y": {}, "Z": "" }
+                }
+            }),
+        )
+        .await;
+        fs.insert_tree(
+            "/root2",
+            json!({
+                "d": {
+                    "9": ""
+                },
+                "e": {}
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root1".as_ref(), "/root2".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| {
+                let panel = ProjectPanel::new(workspace, cx);
+                workspace.add_panel(panel.clone(), cx);
+                panel
+            })
+            .unwrap();
+
+        select_path(&panel, "root1", cx);
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root1  <== selected",
+                "    > .git",
+                "    > a",
+                "    > b",
+                "    > C",
+                "      .dockerignore",
+                "v root2",
+                "    > d",
+                "    > e",
+            ]
+        );
+
+        // Add a file with the root folder selected. The filename editor is placed
+        // before the first file in the root folder.
+        panel.update(cx, |panel, cx| panel.new_file(&NewFile, cx));
+        panel.update(cx, |panel, cx| {
+            assert!(panel.filename_editor.read(cx).is_focused(cx));
+        });
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root1",
+                "    > .git",
+                "    > a",
+                "    > b",
+                "    > C",
+                "      [EDITOR: '']  <== selected",
+                "      .dockerignore",
+                "v root2",
+                "    > d",
+                "    > e",
+            ]
+        );
+
+        let confirm = panel.update(cx, |panel, cx| {
+            panel.filename_editor.update(cx, |editor, cx| {
+                editor.set_text("/bdir1/dir2/the-new-filename", cx)
+            });
+            panel.confirm_edit(cx).unwrap()
+        });
+
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root1",
+                "    > .git",
+                "    > a",
+                "    > b",
+                "    > C",
+                "      [PROCESSING: '/bdir1/dir2/the-new-filename']  <== selected",
+                "      .dockerignore",
+                "v root2",
+                "    > d",
+                "    > e",
+            ]
+        );
+
+        confirm.await.unwrap();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..13, cx),
+            &[
+                "v root1",
+                "    > .git",
+                "    > a",
+                "    > b",
+                "    v bdir1",
+                "        v dir2",
+                "              the-new-filename  <== selected  <== marked",
+                "    > C",
+                "      .dockerignore",
+                "v root2",
+                "    > d",
+                "    > e",
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_adding_directory_via_file(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root1",
+            json!({
+                ".dockerignore": "",
+                ".git": {
+                    "HEAD": "",
+                },
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root1".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| {
+                let panel = ProjectPanel::new(workspace, cx);
+                workspace.add_panel(panel.clone(), cx);
+                panel
+            })
+            .unwrap();
+
+        select_path(&panel, "root1", cx);
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &["v root1  <== selected", "    > .git", "      .dockerignore",]
+        );
+
+        // Add a file with the root folder selected. The filename editor is placed
+        // before the first file in the root folder.
+        panel.update(cx, |panel, cx| panel.new_file(&NewFile, cx));
+        panel.update(cx, |panel, cx| {
+            assert!(panel.filename_editor.read(cx).is_focused(cx));
+        });
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root1",
+                "    > .git",
+                "      [EDITOR: '']  <== selected",
+                "      .dockerignore",
+            ]
+        );
+
+        let confirm = panel.update(cx, |panel, cx| {
+            panel
+                .filename_editor
+                .update(cx, |editor, cx| editor.set_text("/new_dir/", cx));
+            panel.confirm_edit(cx).unwrap()
+        });
+
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root1",
+                "    > .git",
+                "      [PROCESSING: '/new_dir/']  <== selected",
+                "      .dockerignore",
+            ]
+        );
+
+        confirm.await.unwrap();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..13, cx),
+            &[
+                "v root1",
+                "    > .git",
+                "    v new_dir  <== selected",
+                "      .dockerignore",
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_copy_paste(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root1",
+            json!({
+                "one.two.txt": "",
+                "one.txt": ""
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root1".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+
+        panel.update(cx, |panel, cx| {
+            panel.select_next(&Default::default(), cx);
+            panel.select_next(&Default::default(), cx);
+        });
+
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..50, cx),
+            &[
+                //
+                "v root1",
+                "      one.two.txt  <== selected",
+                "      one.txt",
+            ]
+        );
+
+        // Regression test - file name is created correctly when
+        // the copied file's name contains multiple dots.
+        panel.update(cx, |panel, cx| {
+            panel.copy(&Default::default(), cx);
+            panel.paste(&Default::default(), cx);
+        });
+        cx.executor().run_until_parked();
+
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..50, cx),
+            &[
+                //
+                "v root1",
+                "      one.two copy.txt",
+                "      one.two.txt  <== selected",
+                "      one.txt",
+            ]
+        );
+
+        panel.update(cx, |panel, cx| {
+            panel.paste(&Default::default(), cx);
+        });
+        cx.executor().run_until_parked();
+
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..50, cx),
+            &[
+                //
+                "v root1",
+                "      one.two copy 1.txt",
+                "      one.two copy.txt",
+                "      one.two.txt  <== selected",
+                "      one.txt",
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_paste_with_source_comment(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root1",
+            json!({
+                "config": {
+                    "settings.toml": "key = 1\n",
+                    "run.sh": "#!/bin/sh\necho hi\n",
+                    "data.bin": "\u{0}\u{1}",
+                },
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root1".as_ref()], cx).await;
+        add_languages_with_comments(&project, cx);
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+
+        toggle_expand_dir(&panel, "root1/config", cx);
+        for file in [
+            "root1/config/settings.toml",
+            "root1/config/run.sh",
+            "root1/config/data.bin",
+        ] {
+            select_path(&panel, file, cx);
+            panel.update(cx, |panel, cx| {
+                panel.copy(&Default::default(), cx);
+                panel.paste_with_source_comment(&Default::default(), cx);
+            });
+            cx.executor().run_until_parked();
+        }
+
+        assert_eq!(
+            fs.load(Path::new("/root1/config/settings copy.toml"))
+                .await
+                .unwrap(),
+            "# Copied from config/settings.toml\nkey = 1\n"
+        );
+        assert_eq!(
+            fs.load(Path::new("/root1/config/run copy.sh"))
+                .await
+                .unwrap(),
+            "#!/bin/sh\n# Copied from config/run.sh\necho hi\n",
+            "Shebang line should stay first"
+        );
+        assert_eq!(
+            fs.load(Path::new("/root1/config/data copy.bin"))
+                .await
+                .unwrap(),
+            "\u{0}\u{1}",
+            "Files in no known language should be copied as they are"
+        );
+
+        panel.update(cx, |panel, cx| panel.undo(&Undo, cx));
+        cx.executor().run_until_parked();
+        assert!(find_project_entry(&panel, "root1/config/data copy.bin", cx).is_none());
+        panel.update(cx, |panel, cx| panel.redo(&Redo, cx));
+        cx.executor().run_until_parked();
+        assert!(find_project_entry(&panel, "root1/config/data copy.bin", cx).is_some());
+
+        panel.update(cx, |panel, cx| panel.undo(&Undo, cx));
+        cx.executor().run_until_parked();
+        panel.update(cx, |panel, cx| panel.undo(&Undo, cx));
+        cx.executor().run_until_parked();
+        assert!(
+            find_project_entry(&panel, "root1/config/run copy.sh", cx).is_none(),
+            "Pastes with source comments should be undoable"
+        );
+        panel.update(cx, |panel, cx| panel.redo(&Redo, cx));
+        cx.executor().run_until_parked();
+        assert_eq!(
+            fs.load(Path::new("/root1/config/run copy.sh"))
+                .await
+                .unwrap(),
+            "#!/bin/sh\n# Copied from config/run.sh\necho hi\n",
+            "Redoing the paste should add the comment again"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_paste_with_source_comment_across_worktrees(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree("/root1", json!({ "main.rs": "fn main() {}\n" }))
+            .await;
+        fs.insert_tree("/root2", json!({ "lib.rs": "" })).await;
+
+        let project = Project::test(fs.clone(), ["/root1".as_ref(), "/root2".as_ref()], cx).await;
+        add_languages_with_comments(&project, cx);
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
+
+        select_path(&panel, "root2/lib.rs", cx);
+        panel.update(cx, |panel, _| {
+            panel.marked_entries.insert(panel.selection.unwrap());
+        });
+        select_path(&panel, "root1/main.rs", cx);
+        panel.update(cx, |panel, cx| {
+            panel.marked_entries.insert(panel.selection.unwrap());
+            panel.copy(&Default::default(), cx);
+            panel.paste_with_source_comment(&Default::default(), cx);
+        });
+        cx.run_until_parked();
+
+        assert_eq!(
+            fs.load(Path::new("/root1/main copy.rs")).await.unwrap(),
+            "// Copied from main.rs\nfn main() {}\n",
+        );
+        assert_eq!(
+            fs.load(Path::new("/root1/lib.rs")).await.unwrap(),
+            "// Copied from lib.rs\n",
+            "Entries of other worktrees should be pasted with a comment as well"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_copy_paste_directory(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "a": {
+                    "one.txt": "",
+                    "two.txt": "",
+                    "inner_dir": {
+                        "three.txt": "",
+                        "four.txt": "",
+                    }
+                },
+                "b": {}
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+
+        select_path(&panel, "root/a", cx);
+        panel.update(cx, |panel, cx| {
+            panel.copy(&Default::default(), cx);
+            panel.select_next(&Default::default(), cx);
+            panel.paste(&Default::default(), cx);
+        });
+        cx.executor().run_until_parked();
+
+        let pasted_dir = find_project_entry(&panel, "root/b/a", cx);
+        assert_ne!(pasted_dir, None, "Pasted directory should have an entry");
+
+        let pasted_dir_file = find_project_entry(&panel, "root/b/a/one.txt", cx);
+        assert_ne!(
+            pasted_dir_file, None,
+            "Pasted directory file should have an entry"
+        );
+
+        let pasted_dir_inner_dir = find_project_entry(&panel, "root/b/a/inner_dir", cx);
+        assert_ne!(
+            pasted_dir_inner_dir, None,
+            "Directories inside pasted directory should have an entry"
+        );
+
+        toggle_expand_dir(&panel, "root/b/a", cx);
+        toggle_expand_dir(&panel, "root/b/a/inner_dir", cx);
+
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..50, cx),
+            &[
+                //
+                "v root",
+                "    > a",
+                "    v b",
+                "        v a",
+                "            v inner_dir  <== selected",
+                "                  four.txt",
+                "                  three.txt",
+                "              one.txt",
+                "              two.txt",
+            ]
+        );
+
+        select_path(&panel, "root", cx);
+        panel.update(cx, |panel, cx| panel.paste(&Default::default(), cx));
+        cx.executor().run_until_parked();
+        panel.update(cx, |panel, cx| panel.paste(&Default::default(), cx));
+        cx.executor().run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..50, cx),
+            &[
+                //
+                "v root  <== selected",
+                "    > a",
+                "    > a copy",
+                "    > a copy 1",
+                "    v b",
+                "        v a",
+                "            v inner_dir",
+                "                  four.txt",
+                "                  three.txt",
+                "              one.txt",
+                "              two.txt"
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_paste_into_own_subtree(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root1",
+            json!({
+                "a": {
+                    "b": {
+                        "c.txt": "",
+                    },
+                },
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root1".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+
+        toggle_expand_dir(&panel, "root1/a", cx);
+        toggle_expand_dir(&panel, "root1/a/b", cx);
+        select_path(&panel, "root1/a", cx);
+        panel.update(cx, |panel, cx| panel.cut(&Default::default(), cx));
+        select_path(&panel, "root1/a/b", cx);
+        panel.update(cx, |panel, cx| panel.paste(&Default::default(), cx));
+        cx.executor().run_until_parked();
+
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..50, cx),
+            &[
+                //
+                "v root1",
+                "    v a",
+                "        v b  <== selected",
+                "              c.txt",
+            ],
+            "Should not move a directory into its own descendant"
+        );
+        workspace
+            .update(cx, |workspace, cx| {
+                let notifications = workspace.notification_ids();
+                assert_eq!(
+                    notifications.len(),
+                    1,
+                    "Should explain why the paste was blocked"
+                );
+                workspace.dismiss_notification(notifications.first().unwrap(), cx);
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_remove_opened_file(cx: &mut gpui::TestAppContext) {
+        init_test_with_editor(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/src",
+            json!({
+                "test": {
+                    "first.rs": "// First Rust file",
+                    "second.rs": "// Second Rust file",
+                    "third.rs": "// Third Rust file",
+                }
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/src".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+
+        toggle_expand_dir(&panel, "src/test", cx);
+        select_path(&panel, "src/test/first.rs", cx);
+        panel.update(cx, |panel, cx| panel.open(&Open, cx));
+        cx.executor().run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v src",
+                "    v test",
+                "          first.rs  <== selected",
+                "          second.rs",
+                "          third.rs"
+            ]
+        );
+        ensure_single_file_is_opened(&workspace, "test/first.rs", cx);
+
+        submit_deletion(&panel, cx);
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v src",
+                "    v test",
+                "          second.rs",
+                "          third.rs"
+            ],
+            "Project panel should have no deleted file, no other file is selected in it"
+        );
+        ensure_no_open_items_and_panes(&workspace, cx);
+
+        select_path(&panel, "src/test/second.rs", cx);
+        panel.update(cx, |panel, cx| panel.open(&Open, cx));
+        cx.executor().run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v src",
+                "    v test",
+                "          second.rs  <== selected",
+                "          third.rs"
+            ]
+        );
+        ensure_single_file_is_opened(&workspace, "test/second.rs", cx);
+
+        workspace
+            .update(cx, |workspace, cx| {
+                let active_items = workspace
+                    .panes()
+                    .iter()
+                    .filter_map(|pane| pane.read(cx).active_item())
+                    .collect::<Vec<_>>();
+                assert_eq!(active_items.len(), 1);
+                let open_editor = active_items
+                    .into_iter()
+                    .next()
+                    .unwrap()
+                    .downcast::<Editor>()
+                    .expect("Open item should be an editor");
+                open_editor.update(cx, |editor, cx| editor.set_text("Another text!", cx));
+            })
+            .unwrap();
+        submit_deletion_skipping_prompt(&panel, cx);
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &["v src", "    v test", "          third.rs"],
+            "Project panel should have no deleted file, with one last file remaining"
+        );
+        ensure_no_open_items_and_panes(&workspace, cx);
+    }
+
+    #[gpui::test]
+    async fn test_create_duplicate_items(cx: &mut gpui::TestAppContext) {
+        init_test_with_editor(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/src",
+            json!({
+                "test": {
+                    "first.rs": "// First Rust file",
+                    "second.rs": "// Second Rust file",
+                    "third.rs": "// Third Rust file",
+                }
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/src".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| {
+                let panel = ProjectPanel::new(workspace, cx);
+                workspace.add_panel(panel.clone(), cx);
+                panel
+            })
+            .unwrap();
+
+        select_path(&panel, "src/", cx);
+        panel.update(cx, |panel, cx| panel.confirm(&Confirm, cx));
+        cx.executor().run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                //
+                "v src  <== selected",
+                "    > test"
+            ]
+        );
+        panel.update(cx, |panel, cx| panel.new_directory(&NewDirectory, cx));
+        panel.update(cx, |panel, cx| {
+            assert!(panel.filename_editor.read(cx).is_focused(cx));
+        });
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                //
+                "v src",
+                "    > [EDITOR: '']  <== selected",
+                "    > test"
+            ]
+        );
+        panel.update(cx, |panel, cx| {
+            panel
+                .filename_editor
+                .update(cx, |editor, cx| editor.set_text("test", cx));
+            assert!(
+                panel.confirm_edit(cx).is_none(),
+                "Should not allow to confirm on conflicting new directory name"
+            )
+        });
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                //
+                "v src",
+                "    > test"
+            ],
+            "File list should be unchanged after failed folder create confirmation"
+        );
+
+        select_path(&panel, "src/test/", cx);
+        panel.update(cx, |panel, cx| panel.confirm(&Confirm, cx));
+        cx.executor().run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                //
+                "v src",
+                "    > test  <== selected"
+            ]
+        );
+        panel.update(cx, |panel, cx| panel.new_file(&NewFile, cx));
+        panel.update(cx, |panel, cx| {
+            assert!(panel.filename_editor.read(cx).is_focused(cx));
+        });
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v src",
+                "    v test",
+                "          [EDITOR: '']  <== selected",
+                "          first.rs",
+                "          second.rs",
+                "          third.rs"
+            ]
+        );
+        panel.update(cx, |panel, cx| {
+            panel
+                .filename_editor
+                .update(cx, |editor, cx| editor.set_text("first.rs", cx));
+            assert!(
+                panel.confirm_edit(cx).is_none(),
+                "Should not allow to confirm on conflicting new file name"
+            )
+        });
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v src",
+                "    v test",
+                "          first.rs",
+                "          second.rs",
+                "          third.rs"
+            ],
+            "File list should be unchanged after failed file create confirmation"
+        );
+
+        select_path(&panel, "src/test/first.rs", cx);
+        panel.update(cx, |panel, cx| panel.confirm(&Confirm, cx));
+        cx.executor().run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v src",
+                "    v test",
+                "          first.rs  <== selected",
+                "          second.rs",
+                "          third.rs"
+            ],
+        );
+        panel.update(cx, |panel, cx| panel.rename(&Rename, cx));
+        panel.update(cx, |panel, cx| {
+            assert!(panel.filename_editor.read(cx).is_focused(cx));
+        });
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v src",
+                "    v test",
+                "          [EDITOR: 'first.rs']  <== selected",
+                "          second.rs",
+                "          third.rs"
+            ]
+        );
+        panel.update(cx, |panel, cx| {
+            panel
+                .filename_editor
+                .update(cx, |editor, cx| editor.set_text("second.rs", cx));
+            assert!(
+                panel.confirm_edit(cx).is_none(),
+                "Should not allow to confirm on conflicting file rename"
+            )
+        });
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v src",
+                "    v test",
+                "          first.rs  <== selected",
+                "          second.rs",
+                "          third.rs"
+            ],
+            "File list should be unchanged after failed rename confirmation"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_dir_toggle_collapse(cx: &mut gpui::TestAppContext) {
+        init_test_with_editor(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/project_root",
+            json!({
+                "dir_1": {
+                    "nested_dir": {
+                        "file_a.py": "# File contents",
+                    }
+                },
+                "file_1.py": "# File contents",
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+
+        panel.update(cx, |panel, cx| panel.open(&Open, cx));
+        cx.executor().run_until_parked();
+        select_path(&panel, "project_root/dir_1", cx);
+        panel.update(cx, |panel, cx| panel.open(&Open, cx));
+        select_path(&panel, "project_root/dir_1/nested_dir", cx);
+        panel.update(cx, |panel, cx| panel.open(&Open, cx));
+        panel.update(cx, |panel, cx| panel.open(&Open, cx));
+        cx.executor().run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v project_root",
+                "    v dir_1",
+                "        > nested_dir  <== selected",
+                "      file_1.py",
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_collapse_all_entries(cx: &mut gpui::TestAppContext) {
+        init_test_with_editor(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/project_root",
+            json!({
+                "dir_1": {
+                    "nested_dir": {
+                        "file_a.py": "# File contents",
+                        "file_b.py": "# File contents",
+                        "file_c.py": "# File contents",
+                    },
+                    "file_1.py": "# File contents",
+                    "file_2.py": "# File contents",
+                    "file_3.py": "# File contents",
+                },
+                "dir_2": {
+                    "file_1.py": "# File contents",
+                    "file_2.py": "# File contents",
+                    "file_3.py": "# File contents",
+                }
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+
+        panel.update(cx, |panel, cx| {
+            panel.collapse_all_entries(&CollapseAllEntries, cx)
+        });
+        cx.executor().run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &["v project_root", "    > dir_1", "    > dir_2",]
+        );
+
+        // Open dir_1 and make sure nested_dir was collapsed when running collapse_all_entries
+        toggle_expand_dir(&panel, "project_root/dir_1", cx);
+        cx.executor().run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v project_root",
+                "    v dir_1  <== selected",
+                "        > nested_dir",
+                "          file_1.py",
+                "          file_2.py",
+                "          file_3.py",
+                "    > dir_2",
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_collapse_worktree(cx: &mut gpui::TestAppContext) {
+        init_test_with_editor(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root1",
+            json!({ "dir_1": { "nested_dir": { "a.py": "" }, "b.py": "" } }),
+        )
+        .await;
+        fs.insert_tree("/root2", json!({ "dir_2": { "c.py": "" } }))
+            .await;
+
+        let project = Project::test(fs.clone(), ["/root1".as_ref(), "/root2".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
+
+        toggle_expand_dir(&panel, "root2/dir_2", cx);
+        toggle_expand_dir(&panel, "root1/dir_1", cx);
+        toggle_expand_dir(&panel, "root1/dir_1/nested_dir", cx);
+        select_path(&panel, "root1/dir_1/nested_dir/a.py", cx);
+        panel.update(cx, |panel, cx| {
+            panel.collapse_worktree(&CollapseWorktree, cx)
+        });
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root1  <== selected",
+                "    > dir_1",
+                "v root2",
+                "    v dir_2",
+                "          c.py",
+            ],
+            "Only the worktree of the selection should collapse"
+        );
+
+        toggle_expand_dir(&panel, "root1/dir_1", cx);
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root1",
+                "    v dir_1  <== selected",
+                "        > nested_dir",
+                "          b.py",
+                "v root2",
+                "    v dir_2",
+                "          c.py",
+            ],
+            "Nested directories should stay collapsed"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_recent_projects_reload_when_empty(cx: &mut gpui::TestAppContext) {
+        init_test_with_editor(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree("/root1", json!({ "a.txt": "" })).await;
+        fs.insert_tree("/root2", json!({ "b.txt": "" })).await;
+
+        let project = Project::test(fs.clone(), ["/root1".as_ref(), "/root2".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
+
+        let stale_projects = vec![LocalPaths::new(["/stale"])];
+        panel.update(cx, |panel, _| {
+            panel.recent_projects = stale_projects.clone();
+        });
+        let worktree_ids = project.read_with(cx, |project, cx| {
+            project
+                .worktrees(cx)
+                .map(|worktree| worktree.read(cx).id())
+                .collect::<Vec<_>>()
+        });
+
+        project.update(cx, |project, cx| {
+            project.remove_worktree(worktree_ids[1], cx)
+        });
+        cx.run_until_parked();
+        panel.update(cx, |panel, _| {
+            assert_eq!(
+                panel.recent_projects, stale_projects,
+                "Recent projects should not reload while a folder is still open"
+            );
+        });
+
+        project.update(cx, |project, cx| {
+            project.remove_worktree(worktree_ids[0], cx)
+        });
+        cx.run_until_parked();
+        panel.update(cx, |panel, _| {
+            assert!(
+                !panel.recent_projects.contains(&stale_projects[0]),
+                "Recent projects should reload once the last folder is removed"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_folder_focus(cx: &mut gpui::TestAppContext) {
+        init_test_with_editor(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/project_root",
+            json!({
+                "dir_1": {
+                    "nested_dir": {
+                        "file_a.py": "# File contents",
+                    },
+                    "file_1.py": "# File contents",
+                },
+                "dir_2": {
+                    "file_2.py": "# File contents",
+                }
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+
+        select_path(&panel, "project_root/dir_1", cx);
+        panel.update(cx, |panel, cx| panel.focus_on_folder(&FocusOnFolder, cx));
+        cx.executor().run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v dir_1  <== selected",
+                "    > nested_dir",
+                "      file_1.py",
+            ],
+            "Focused folder should become the only visible root"
+        );
+
+        panel.update(cx, |panel, cx| {
+            panel.exit_folder_focus(&ExitFolderFocus, cx)
+        });
+        cx.executor().run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v project_root",
+                "    v dir_1  <== selected",
+                "        > nested_dir",
+                "          file_1.py",
+                "    > dir_2",
+            ],
+            "Exiting the focus should restore the full tree, keeping the expansion state"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_expand_all_one_level(cx: &mut gpui::TestAppContext) {
+        init_test_with_editor(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/project_root",
+            json!({
+                "dir_1": {
+                    "nested_dir": {
+                        "deepest_dir": {
+                            "file_a.py": "# File contents",
+                        },
+                    },
+                    "file_1.py": "# File contents",
+                },
+                "dir_2": {
+                    "file_2.py": "# File contents",
+                }
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+
+        panel.update(cx, |panel, cx| {
+            panel.expand_all_one_level(&ExpandAllOneLevel, cx)
+        });
+        cx.executor().run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v project_root",
+                "    v dir_1",
+                "        > nested_dir",
+                "          file_1.py",
+                "    v dir_2",
+                "          file_2.py",
+            ]
+        );
+
+        panel.update(cx, |panel, cx| {
+            panel.expand_all_one_level(&ExpandAllOneLevel, cx)
+        });
+        cx.executor().run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v project_root",
+                "    v dir_1",
+                "        v nested_dir",
+                "            > deepest_dir",
+                "          file_1.py",
+                "    v dir_2",
+                "          file_2.py",
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_expand_all_entries(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/project_root",
+            json!({
+                ".git": {},
+                ".gitignore": "ignored_dir",
+                "dir_1": {
+                    "nested_dir": {
+                        "deepest_dir": {
+                            "file_a.py": "# File contents",
+                        },
+                    },
+                    "file_1.py": "# File contents",
+                },
+                "dir_2": {
+                    "file_2.py": "# File contents",
+                },
+                "ignored_dir": {
+                    "file_3.py": "# File contents",
+                },
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+
+        panel.update(cx, |panel, cx| {
+            panel.expand_all_entries(&ExpandAllEntries, cx)
+        });
+        cx.executor().run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..20, cx),
+            &[
+                "v project_root",
+                "    v dir_1",
+                "        v nested_dir",
+                "            v deepest_dir",
+                "                  file_a.py",
+                "          file_1.py",
+                "    v dir_2",
+                "          file_2.py",
+                "    > ignored_dir",
+                "      .gitignore",
+            ],
+            "All loaded directories should be expanded, leaving the unloaded ignored ones collapsed"
+        );
+
+        panel.update(cx, |panel, cx| {
+            panel.collapse_all_entries(&CollapseAllEntries, cx)
+        });
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..20, cx),
+            &[
+                "v project_root",
+                "    > dir_1",
+                "    > dir_2",
+                "    > ignored_dir",
+                "      .gitignore",
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_expand_selected_entry_recursively(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/project_root",
+            json!({
+                ".gitignore": "ignored_dir",
+                "dir_1": {
+                    "nested_dir": {
+                        "deepest_dir": {
+                            "file_a.py": "# File contents",
+                        },
+                    },
+                    "ignored_dir": {
+                        "inner_dir": {
+                            "file_b.py": "# File contents",
+                        },
+                    },
+                    "file_1.py": "# File contents",
+                },
+                "dir_2": {
+                    "file_2.py": "# File contents",
+                },
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+
+        select_path(&panel, "project_root/dir_1", cx);
+        panel.update(cx, |panel, cx| {
+            panel.expand_selected_entry_recursively(&ExpandSelectedEntryRecursively, cx)
+        });
+        cx.executor().run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..20, cx),
+            &[
+                "v project_root",
+                "    v dir_1  <== selected",
+                "        v ignored_dir",
+                "            v inner_dir",
+                "                  file_b.py",
+                "        v nested_dir",
+                "            v deepest_dir",
+                "                  file_a.py",
+                "          file_1.py",
+                "    > dir_2",
+                "      .gitignore",
+            ],
+            "All descendants should be expanded, loading the unloaded ones"
+        );
+
+        select_path(
+            &panel,
+            "project_root/dir_1/ignored_dir/inner_dir/file_b.py",
+            cx,
+        );
+        panel.update(cx, |panel, cx| {
+            panel.collapse_selected_entry_recursively(&CollapseSelectedEntryRecursively, cx)
+        });
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..20, cx),
+            &[
+                "v project_root",
+                "    v dir_1",
+                "        v ignored_dir",
+                "            > inner_dir  <== selected",
+                "        v nested_dir",
+                "            v deepest_dir",
+                "                  file_a.py",
+                "          file_1.py",
+                "    > dir_2",
+                "      .gitignore",
+            ]
+        );
+
+        select_path(&panel, "project_root/dir_1", cx);
+        panel.update(cx, |panel, cx| {
+            panel.collapse_selected_entry_recursively(&CollapseSelectedEntryRecursively, cx)
+        });
+        panel.update(cx, |panel, cx| {
+            panel.expand_selected_entry(&ExpandSelectedEntry, cx)
+        });
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..20, cx),
+            &[
+                "v project_root",
+                "    v dir_1  <== selected",
+                "        > ignored_dir",
+                "        > nested_dir",
+                "          file_1.py",
+                "    > dir_2",
+                "      .gitignore",
+            ],
+            "Re-expanding a recursively collapsed directory should show its children collapsed"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_new_file_move(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.as_fake().insert_tree("/root", json!({})).await;
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+
+        // Make a new buffer with no backing file
+        workspace
+            .update(cx, |workspace, cx| {
+                Editor::new_file(workspace, &Default::default(), cx)
+            })
+            .unwrap();
+
+        cx.executor().run_until_parked();
+
+        // "Save as" the buffer, creating a new backing file for it
+        let save_task = workspace
+            .update(cx, |workspace, cx| {
+                workspace.save_active_item(workspace::SaveIntent::Save, cx)
+            })
+            .unwrap();
+
+        cx.executor().run_until_parked();
+        cx.simulate_new_path_selection(|_| Some(PathBuf::from("/root/new")));
+        save_task.await.unwrap();
+
+        // Rename the file
+        select_path(&panel, "root/new", cx);
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &["v root", "      new  <== selected"]
+        );
+        panel.update(cx, |panel, cx| panel.rename(&Rename, cx));
+        panel.update(cx, |panel, cx| {
+            panel
+                .filename_editor
+                .update(cx, |editor, cx| editor.set_text("newer", cx));
+        });
+        panel.update(cx, |panel, cx| panel.confirm(&Confirm, cx));
+
+        cx.executor().run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &["v root", "      newer  <== selected"]
+        );
+
+        workspace
+            .update(cx, |workspace, cx| {
+                workspace.save_active_item(workspace::SaveIntent::Save, cx)
+            })
+            .unwrap()
+            .await
+            .unwrap();
+
+        cx.executor().run_until_parked();
+        // assert that saving the file doesn't restore "new"
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &["v root", "      newer  <== selected"]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_multiple_marked_entries(cx: &mut gpui::TestAppContext) {
+        init_test_with_editor(cx);
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/project_root",
+            json!({
+                "dir_1": {
+                    "nested_dir": {
+                        "file_a.py": "# File contents",
+                    }
+                },
+                "file_1.py": "# File contents",
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        let worktree_id =
+            cx.update(|cx| project.read(cx).worktrees().next().unwrap().read(cx).id());
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.update(|cx| {
+            panel.update(cx, |this, cx| {
+                this.select_next(&Default::default(), cx);
+                this.expand_selected_entry(&Default::default(), cx);
+                this.expand_selected_entry(&Default::default(), cx);
+                this.select_next(&Default::default(), cx);
+                this.expand_selected_entry(&Default::default(), cx);
+                this.select_next(&Default::default(), cx);
+            })
+        });
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v project_root",
+                "    v dir_1",
+                "        v nested_dir",
+                "              file_a.py  <== selected",
+                "      file_1.py",
+            ]
+        );
+        let modifiers_with_shift = gpui::Modifiers {
+            shift: true,
+            ..Default::default()
+        };
+        cx.simulate_modifiers_change(modifiers_with_shift);
+        cx.update(|cx| {
+            panel.update(cx, |this, cx| {
+                this.select_next(&Default::default(), cx);
+            })
+        });
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v project_root",
+                "    v dir_1",
+                "        v nested_dir",
+                "              file_a.py",
+                "      file_1.py  <== selected  <== marked",
+            ]
+        );
+        cx.update(|cx| {
+            panel.update(cx, |this, cx| {
+                this.select_prev(&Default::default(), cx);
+            })
+        });
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v project_root",
+                "    v dir_1",
+                "        v nested_dir",
+                "              file_a.py  <== selected  <== marked",
+                "      file_1.py  <== marked",
+            ]
+        );
+        cx.update(|cx| {
+            panel.update(cx, |this, cx| {
+                let drag = DraggedSelection {
+                    active_selection: this.selection.unwrap(),
+                    marked_selections: Arc::new(this.marked_entries.clone()),
+                };
+                let target_entry = this
+                    .project
+                    .read(cx)
+                    .entry_for_path(&(worktree_id, "").into(), cx)
+                    .unwrap();
+                this.drag_onto(&drag, target_entry.id, false, cx);
+            });
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v project_root",
+                "    v dir_1",
+                "        v nested_dir",
+                "      file_1.py  <== marked",
+                "      file_a.py  <== selected  <== marked",
+            ]
+        );
+        // ESC clears out all marks
+        cx.update(|cx| {
+            panel.update(cx, |this, cx| {
+                this.cancel(&menu::Cancel, cx);
+            })
+        });
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v project_root",
+                "    v dir_1",
+                "        v nested_dir",
+                "      file_1.py",
+                "      file_a.py  <== selected",
+            ]
+        );
+        // ESC clears out all marks
+        cx.update(|cx| {
+            panel.update(cx, |this, cx| {
+                this.select_prev(&SelectPrev, cx);
+                this.select_next(&SelectNext, cx);
+            })
+        });
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v project_root",
+                "    v dir_1",
+                "        v nested_dir",
+                "      file_1.py  <== marked",
+                "      file_a.py  <== selected  <== marked",
+            ]
+        );
+        cx.simulate_modifiers_change(Default::default());
+        cx.update(|cx| {
+            panel.update(cx, |this, cx| {
+                this.cut(&Cut, cx);
+                this.select_prev(&SelectPrev, cx);
+                this.select_prev(&SelectPrev, cx);
+
+                this.paste(&Paste, cx);
+                // this.expand_selected_entry(&ExpandSelectedEntry, cx);
+            })
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v project_root",
+                "    v dir_1",
+                "        v nested_dir  <== selected",
+                "              file_1.py  <== marked",
+                "              file_a.py  <== marked",
+            ]
+        );
+        cx.simulate_modifiers_change(modifiers_with_shift);
+        cx.update(|cx| {
+            panel.update(cx, |this, cx| {
+                this.expand_selected_entry(&Default::default(), cx);
+                this.select_next(&SelectNext, cx);
+                this.select_next(&SelectNext, cx);
+            })
+        });
+        submit_deletion(&panel, cx);
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &["v project_root", "    v dir_1", "        v nested_dir",]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_trash_prompts_when_trash_is_disabled(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<ProjectPanelSettings>(cx, |settings| {
+                    settings.enable_trash = Some(false);
+                });
+            })
+        });
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree("/root", json!({ "a.txt": "", "b.txt": "" }))
+            .await;
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
+
+        select_path(&panel, "root/a.txt", cx);
+        panel.update(cx, |panel, cx| {
+            panel.trash(&Trash { skip_prompt: true }, cx)
+        });
+        assert!(
+            cx.has_pending_prompt(),
+            "Deleting permanently should prompt, even when trashing would not"
+        );
+        cx.simulate_prompt_answer(1);
+        cx.run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &["v root", "      a.txt  <== selected", "      b.txt"]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_open_in_terminal(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree("/root", json!({ "dir": { "file.txt": "" } }))
+            .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let opened_terminals = Rc::new(std::cell::RefCell::new(Vec::new()));
+        cx.update({
+            let opened_terminals = opened_terminals.clone();
+            |cx| {
+                cx.on_action(move |action: &workspace::OpenTerminal, _| {
+                    opened_terminals
+                        .borrow_mut()
+                        .push(action.working_directory.clone());
+                })
+            }
+        });
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        toggle_expand_dir(&panel, "root/dir", cx);
+
+        // Files open a terminal in their folder.
+        select_path(&panel, "root/dir/file.txt", cx);
+        panel.update(cx, |panel, cx| panel.open_in_terminal(&OpenInTerminal, cx));
+        cx.run_until_parked();
+        select_path(&panel, "root/dir", cx);
+        panel.update(cx, |panel, cx| panel.open_in_terminal(&OpenInTerminal, cx));
+        cx.run_until_parked();
+        assert_eq!(
+            opened_terminals.borrow().as_slice(),
+            [PathBuf::from("/root/dir"), PathBuf::from("/root/dir")]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_drag_destination(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "a": { "a1.txt": "" },
+                "b": {},
+                "c.txt": "",
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        toggle_expand_dir(&panel, "root/a", cx);
+        let destination = |cx: &mut VisualTestContext| {
+            panel.update(cx, |panel, _| {
+                panel
+                    .dragged_entry_destination
+                    .as_ref()
+                    .map(|(_, path)| path.to_path_buf())
+            })
+        };
+
+        let drag = start_drag(&panel, "root/c.txt", cx);
+        drag_entries_over(&panel, "root/b", cx);
+        assert_eq!(destination(cx), Some(PathBuf::from("b")));
+
+        // Hovering a file targets its folder.
+        drag_entries_over(&panel, "root/a/a1.txt", cx);
+        assert_eq!(destination(cx), Some(PathBuf::from("a")));
+        drag_entries_over(&panel, "root/c.txt", cx);
+        assert_eq!(destination(cx), Some(PathBuf::from("")));
+
+        drag_entries_over(&panel, "root/a/a1.txt", cx);
+        drop_on(&panel, &drag, "root/a/a1.txt", cx);
+        assert_eq!(destination(cx), None);
+        assert!(find_project_entry(&panel, "root/a/c.txt", cx).is_some());
+    }
+
+    #[gpui::test]
+    async fn test_drag_and_drop(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "a": {
+                    "a1.txt": "",
+                    "inner": {},
+                },
+                "b": {},
+                "c.txt": "",
+                "d.txt": "",
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        let notifications = Rc::new(std::cell::RefCell::new(Vec::new()));
+        let _subscription = cx.update({
+            let notifications = notifications.clone();
+            |cx| {
+                cx.subscribe(&project, move |_, event: &project::Event, _| {
+                    if let project::Event::Notification(message) = event {
+                        notifications.borrow_mut().push(message.clone());
+                    }
+                })
+            }
+        });
+
+        let drag = start_drag(&panel, "root/c.txt", cx);
+        drop_on(&panel, &drag, "root/b", cx);
+        assert!(find_project_entry(&panel, "root/c.txt", cx).is_none());
+        assert!(find_project_entry(&panel, "root/b/c.txt", cx).is_some());
+
+        // Dropping on a file moves into its folder.
+        let drag = start_drag(&panel, "root/b/c.txt", cx);
+        drop_on(&panel, &drag, "root/a/a1.txt", cx);
+        assert!(find_project_entry(&panel, "root/b/c.txt", cx).is_none());
+        assert!(find_project_entry(&panel, "root/a/c.txt", cx).is_some());
+
+        // Holding alt copies instead.
+        cx.simulate_modifiers_change(gpui::Modifiers {
+            alt: true,
+            ..Default::default()
+        });
+        let drag = start_drag(&panel, "root/a/c.txt", cx);
+        drop_on(&panel, &drag, "root/b", cx);
+        cx.simulate_modifiers_change(Default::default());
+        assert!(find_project_entry(&panel, "root/a/c.txt", cx).is_some());
+        assert!(find_project_entry(&panel, "root/b/c.txt", cx).is_some());
+
+        // The marked entries are dragged along with the dragged row.
+        for path in ["root/b/c.txt", "root/d.txt"] {
+            let entry_id = find_project_entry(&panel, path, cx).unwrap();
+            panel.update(cx, |panel, cx| {
+                let worktree_id = panel
+                    .project
+                    .read(cx)
+                    .worktree_id_for_entry(entry_id, cx)
+                    .unwrap();
+                panel.marked_entries.insert(SelectedEntry {
+                    worktree_id,
+                    entry_id,
+                });
+            });
+        }
+        let drag = start_drag(&panel, "root/d.txt", cx);
+        drop_on(&panel, &drag, "root/a/inner", cx);
+        panel.update(cx, |panel, _| panel.marked_entries.clear());
+        assert!(find_project_entry(&panel, "root/a/inner/c.txt", cx).is_some());
+        assert!(find_project_entry(&panel, "root/a/inner/d.txt", cx).is_some());
+        assert!(find_project_entry(&panel, "root/b/c.txt", cx).is_none());
+        assert!(find_project_entry(&panel, "root/d.txt", cx).is_none());
+
+        // A folder can't be dropped into itself or one of its subfolders.
+        let drag = start_drag(&panel, "root/a", cx);
+        drop_on(&panel, &drag, "root/a/inner", cx);
+        assert!(find_project_entry(&panel, "root/a", cx).is_some());
+        assert!(find_project_entry(&panel, "root/a/inner/a", cx).is_none());
+        assert_eq!(
+            notifications.borrow().as_slice(),
+            ["Cannot move \"a\" into itself or one of its subfolders"]
+        );
+
+        // External paths hovering a file mark its folder and the folder's children.
+        drag_external_paths_over(&panel, "root/a/a1.txt", cx);
+        let expected = ["root/a", "root/a/a1.txt", "root/a/c.txt", "root/a/inner"]
+            .into_iter()
+            .map(|path| find_project_entry(&panel, path, cx).unwrap())
+            .collect::<Vec<_>>();
+        panel.update(cx, |panel, _| {
+            assert_eq!(
+                panel
+                    .marked_entries
+                    .iter()
+                    .map(|entry| entry.entry_id)
+                    .collect::<HashSet<_>>(),
+                expected.into_iter().collect::<HashSet<_>>()
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_autoreveal_and_gitignored_files(cx: &mut gpui::TestAppContext) {
+        init_test_with_editor(cx);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<WorktreeSettings>(cx, |worktree_settings| {
+                    worktree_settings.file_scan_exclusions = Some(Vec::new());
+                });
+                store.update_user_settings::<ProjectPanelSettings>(cx, |project_panel_settings| {
+                    project_panel_settings.auto_reveal_entries = Some(false)
+                });
+            })
+        });
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/project_root",
+            json!({
+                ".git": {},
+                ".gitignore": "**/gitignored_dir",
+                "dir_1": {
+                    "file_1.py": "# File 1_1 contents",
+                    "file_2.py": "# File 1_2 contents",
+                    "file_3.py": "# File 1_3 contents",
+                    "gitignored_dir": {
+                        "file_a.py": "# File contents",
+                        "file_b.py": "# File contents",
+                        "file_c.py": "# File contents",
+                    },
+                },
+                "dir_2": {
+                    "file_1.py": "# File 2_1 contents",
+                    "file_2.py": "# File 2_2 contents",
+                    "file_3.py": "# File 2_3 contents",
+                }
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..20, cx),
+            &[
+                "v project_root",
+                "    > .git",
+                "    > dir_1",
+                "    > dir_2",
+                "      .gitignore",
+            ]
+        );
+
+        let dir_1_file = find_project_entry(&panel, "project_root/dir_1/file_1.py", cx)
+            .expect("dir 1 file is not ignored and should have an entry");
+        let dir_2_file = find_project_entry(&panel, "project_root/dir_2/file_1.py", cx)
+            .expect("dir 2 file is not ignored and should have an entry");
+        let gitignored_dir_file =
+            find_project_entry(&panel, "project_root/dir_1/gitignored_dir/file_a.py", cx);
+        assert_eq!(
+            gitignored_dir_file, None,
+            "File in the gitignored dir should not have an entry before its dir is toggled"
+        );
+
+        toggle_expand_dir(&panel, "project_root/dir_1", cx);
+        toggle_expand_dir(&panel, "project_root/dir_1/gitignored_dir", cx);
+        cx.executor().run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..20, cx),
+            &[
+                "v project_root",
+                "    > .git",
+                "    v dir_1",
+                "        v gitignored_dir  <== selected",
+                "              file_a.py",
+                "              file_b.py",
+                "              file_c.py",
+                "          file_1.py",
+                "          file_2.py",
+                "          file_3.py",
+                "    > dir_2",
+                "      .gitignore",
+            ],
+            "Should show gitignored dir file list in the project panel"
+        );
+        let gitignored_dir_file =
+            find_project_entry(&panel, "project_root/dir_1/gitignored_dir/file_a.py", cx)
+                .expect("after gitignored dir got opened, a file entry should be present");
+
+        toggle_expand_dir(&panel, "project_root/dir_1/gitignored_dir", cx);
+        toggle_expand_dir(&panel, "project_root/dir_1", cx);
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..20, cx),
+            &[
+                "v project_root",
+                "    > .git",
+                "    > dir_1  <== selected",
+                "    > dir_2",
+                "      .gitignore",
+            ],
+            "Should hide all dir contents again and prepare for the auto reveal test"
+        );
+
+        for file_entry in [dir_1_file, dir_2_file, gitignored_dir_file] {
+            panel.update(cx, |panel, cx| {
+                panel.project.update(cx, |_, cx| {
+                    cx.emit(project::Event::ActiveEntryChanged(Some(file_entry)))
+                })
+            });
+            cx.run_until_parked();
+            assert_eq!(
+                visible_entries_as_strings(&panel, 0..20, cx),
+                &[
+                    "v project_root",
+                    "    > .git",
+                    "    > dir_1  <== selected",
+                    "    > dir_2",
+                    "      .gitignore",
+                ],
+                "When no auto reveal is enabled, the selected entry should not be revealed in the project panel"
+            );
+        }
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<ProjectPanelSettings>(cx, |project_panel_settings| {
+                    project_panel_settings.auto_reveal_entries = Some(true)
+                });
+            })
+        });
+
+        panel.update(cx, |panel, cx| {
+            panel.project.update(cx, |_, cx| {
+                cx.emit(project::Event::ActiveEntryChanged(Some(dir_1_file)))
+            })
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..20, cx),
+            &[
+                "v project_root",
+                "    > .git",
+                "    v dir_1",
+                "        > gitignored_dir",
+                "          file_1.py  <== selected",
+                "          file_2.py",
+                "          file_3.py",
+                "    > dir_2",
+                "      .gitignore",
+            ],
+            "When auto reveal is enabled, not ignored dir_1 entry should be revealed"
+        );
+
+        panel.update(cx, |panel, cx| {
+            panel.project.update(cx, |_, cx| {
+                cx.emit(project::Event::ActiveEntryChanged(Some(dir_2_file)))
+            })
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..20, cx),
+            &[
+                "v project_root",
+                "    > .git",
+                "    v dir_1",
+                "        > gitignored_dir",
+                "          file_1.py",
+                "          file_2.py",
+                "          file_3.py",
+                "    v dir_2",
+                "          file_1.py  <== selected",
+                "          file_2.py",
+                "          file_3.py",
+                "      .gitignore",
+            ],
+            "When auto reveal is enabled, not ignored dir_2 entry should be revealed"
+        );
+
+        panel.update(cx, |panel, cx| {
+            panel.project.update(cx, |_, cx| {
+                cx.emit(project::Event::ActiveEntryChanged(Some(
+                    gitignored_dir_file,
+                )))
+            })
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..20, cx),
+            &[
+                "v project_root",
+                "    > .git",
+                "    v dir_1",
+                "        > gitignored_dir",
+                "          file_1.py",
+                "          file_2.py",
+                "          file_3.py",
+                "    v dir_2",
+                "          file_1.py  <== selected",
+                "          file_2.py",
+                "          file_3.py",
+                "      .gitignore",
+            ],
+            "When auto reveal is enabled, a gitignored selected entry should not be revealed in the project panel"
+        );
+
+        panel.update(cx, |panel, cx| {
+            panel.project.update(cx, |_, cx| {
+                cx.emit(project::Event::RevealInProjectPanel(gitignored_dir_file))
+            })
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..20, cx),
+            &[
+                "v project_root",
+                "    > .git",
+                "    v dir_1",
+                "        v gitignored_dir",
+                "              file_a.py  <== selected",
+                "              file_b.py",
+                "              file_c.py",
+                "          file_1.py",
+                "          file_2.py",
+                "          file_3.py",
+                "    v dir_2",
+                "          file_1.py",
+                "          file_2.py",
+                "          file_3.py",
+                "      .gitignore",
+            ],
+            "When a gitignored entry is explicitly revealed, it should be shown in the project tree"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_explicit_reveal(cx: &mut gpui::TestAppContext) {
+        init_test_with_editor(cx);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<WorktreeSettings>(cx, |worktree_settings| {
+                    worktree_settings.file_scan_exclusions = Some(Vec::new());
+                });
+                store.update_user_settings::<ProjectPanelSettings>(cx, |project_panel_settings| {
+                    project_panel_settings.auto_reveal_entries = Some(false)
+                });
+            })
+        });
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/project_root",
+            json!({
+                ".git": {},
+                ".gitignore": "**/gitignored_dir",
+                "dir_1": {
+                    "file_1.py": "# File 1_1 contents",
+                    "file_2.py": "# File 1_2 contents",
+                    "file_3.py": "# File 1_3 contents",
+                    "gitignored_dir": {
+                        "file_a.py": "# File contents",
+                        "file_b.py": "# File contents",
+                        "file_c.py": "# File contents",
+                    },
+                },
+                "dir_2": {
+                    "file_1.py": "# File 2_1 contents",
+                    "file_2.py": "# File 2_2 contents",
+                    "file_3.py": "# File 2_3 contents",
+                }
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..20, cx),
+            &[
+                "v project_root",
+                "    > .git",
+                "    > dir_1",
+                "    > dir_2",
+                "      .gitignore",
+            ]
+        );
+
+        let dir_1_file = find_project_entry(&panel, "project_root/dir_1/file_1.py", cx)
+            .expect("dir 1 file is not ignored and should have an entry");
+        let dir_2_file = find_project_entry(&panel, "project_root/dir_2/file_1.py", cx)
+            .expect("dir 2 file is not ignored and should have an entry");
+        let gitignored_dir_file =
+            find_project_entry(&panel, "project_root/dir_1/gitignored_dir/file_a.py", cx);
+        assert_eq!(
+            gitignored_dir_file, None,
+            "File in the gitignored dir should not have an entry before its dir is toggled"
+        );
+
+        toggle_expand_dir(&panel, "project_root/dir_1", cx);
+        toggle_expand_dir(&panel, "project_root/dir_1/gitignored_dir", cx);
+        cx.run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..20, cx),
+            &[
+                "v project_root",
+                "    > .git",
+                "    v dir_1",
+                "        v gitignored_dir  <== selected",
+                "              file_a.py",
+                "              file_b.py",
+                "              file_c.py",
+                "          file_1.py",
+                "          file_2.py",
+                "          file_3.py",
+                "    > dir_2",
+                "      .gitignore",
+            ],
+            "Should show gitignored dir file list in the project panel"
+        );
+        let gitignored_dir_file =
+            find_project_entry(&panel, "project_root/dir_1/gitignored_dir/file_a.py", cx)
+                .expect("after gitignored dir got opened, a file entry should be present");
+
+        toggle_expand_dir(&panel, "project_root/dir_1/gitignored_dir", cx);
+        toggle_expand_dir(&panel, "project_root/dir_1", cx);
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..20, cx),
+            &[
+                "v project_root",
+                "    > .git",
+                "    > dir_1  <== selected",
+                "    > dir_2",
+                "      .gitignore",
+            ],
+            "Should hide all dir contents again and prepare for the explicit reveal test"
+        );
+
+        for file_entry in [dir_1_file, dir_2_file, gitignored_dir_file] {
+            panel.update(cx, |panel, cx| {
+                panel.project.update(cx, |_, cx| {
+                    cx.emit(project::Event::ActiveEntryChanged(Some(file_entry)))
+                })
+            });
+            cx.run_until_parked();
+            assert_eq!(
+                visible_entries_as_strings(&panel, 0..20, cx),
+                &[
+                    "v project_root",
+                    "    > .git",
+                    "    > dir_1  <== selected",
+                    "    > dir_2",
+                    "      .gitignore",
+                ],
+                "When no auto reveal is enabled, the selected entry should not be revealed in the project panel"
+            );
+        }
+
+        panel.update(cx, |panel, cx| {
+            panel.project.update(cx, |_, cx| {
+                cx.emit(project::Event::RevealInProjectPanel(dir_1_file))
+            })
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..20, cx),
+            &[
+                "v project_root",
+                "    > .git",
+                "    v dir_1",
+                "        > gitignored_dir",
+                "          file_1.py  <== selected",
+                "          file_2.py",
+                "          file_3.py",
+                "    > dir_2",
+                "      .gitignore",
+            ],
+            "With no auto reveal, explicit reveal should show the dir_1 entry in the project panel"
+        );
+
+        panel.update(cx, |panel, cx| {
+            panel.project.update(cx, |_, cx| {
+                cx.emit(project::Event::RevealInProjectPanel(dir_2_file))
+            })
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..20, cx),
+            &[
+                "v project_root",
+                "    > .git",
+                "    v dir_1",
+                "        > gitignored_dir",
+                "          file_1.py",
+                "          file_2.py",
+                "          file_3.py",
+                "    v dir_2",
+                "          file_1.py  <== selected",
+                "          file_2.py",
+                "          file_3.py",
+                "      .gitignore",
+            ],
+            "With no auto reveal, explicit reveal should show the dir_2 entry in the project panel"
+        );
+
+        panel.update(cx, |panel, cx| {
+            panel.project.update(cx, |_, cx| {
+                cx.emit(project::Event::RevealInProjectPanel(gitignored_dir_file))
+            })
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..20, cx),
+            &[
+                "v project_root",
+                "    > .git",
+                "    v dir_1",
+                "        v gitignored_dir",
+                "              file_a.py  <== selected",
+                "              file_b.py",
+                "              file_c.py",
+                "          file_1.py",
+                "          file_2.py",
+                "          file_3.py",
+                "    v dir_2",
+                "          file_1.py",
+                "          file_2.py",
+                "          file_3.py",
+                "      .gitignore",
+            ],
+            "With no auto reveal, explicit reveal should show the gitignored entry in the project panel"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_reveal_active_entry(cx: &mut gpui::TestAppContext) {
+        init_test_with_editor(cx);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<ProjectPanelSettings>(cx, |project_panel_settings| {
+                    project_panel_settings.auto_reveal_entries = Some(false)
+                });
+            })
+        });
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/project_root",
+            json!({
+                "dir_1": {
+                    "nested_dir": {
+                        "file_a.py": "# File contents",
+                    },
+                },
+                "dir_2": {
+                    "file_1.py": "# File contents",
+                },
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| {
+                let panel = ProjectPanel::new(workspace, cx);
+                workspace.add_panel(panel.clone(), cx);
+                panel
+            })
+            .unwrap();
+
+        let worktree_id =
+            cx.update(|cx| project.read(cx).worktrees().next().unwrap().read(cx).id());
+        workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_path((worktree_id, "dir_1/nested_dir/file_a.py"), None, true, cx)
+            })
+            .unwrap()
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..20, cx),
+            &["v project_root", "    > dir_1", "    > dir_2"],
+            "With auto reveal off, opening a file should not reveal it"
+        );
+
+        cx.dispatch_action(RevealActiveEntry);
+        cx.run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..20, cx),
+            &[
+                "v project_root",
+                "    v dir_1",
+                "        v nested_dir",
+                "              file_a.py  <== selected",
+                "    > dir_2",
+            ],
+            "The active file should be revealed and selected on demand"
+        );
+        panel.update(cx, |panel, cx| {
+            assert!(
+                panel.focus_handle.contains_focused(cx),
+                "Revealing the active file should focus the panel"
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_secondary_double_click_splits_without_marking(cx: &mut gpui::TestAppContext) {
+        init_test_with_editor(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree("/root1", json!({ "a.txt": "", "b.txt": "" }))
+            .await;
+
+        let project = Project::test(fs.clone(), ["/root1".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| {
+                let panel = ProjectPanel::new(workspace, cx);
+                workspace.add_panel(panel.clone(), cx);
+                panel
+            })
+            .unwrap();
+        cx.run_until_parked();
+
+        select_path(&panel, "root1/a.txt", cx);
+        panel.update(cx, |panel, cx| {
+            let selection = panel.selection.unwrap();
+            panel.secondary_click_entry(selection, true, 1, cx);
+            assert!(panel.marked_entries.contains(&selection));
+        });
+
+        select_path(&panel, "root1/b.txt", cx);
+        panel.update(cx, |panel, cx| {
+            let selection = panel.selection.unwrap();
+            panel.secondary_click_entry(selection, true, 1, cx);
+            panel.secondary_click_entry(selection, true, 2, cx);
+            assert!(
+                !panel.marked_entries.contains(&selection),
+                "Double clicking should leave the file unmarked"
+            );
+            assert_eq!(panel.marked_entries.len(), 1);
+        });
+        cx.run_until_parked();
+        workspace
+            .update(cx, |workspace, cx| {
+                assert_eq!(workspace.panes().len(), 2);
+                let active_path = workspace
+                    .active_item(cx)
+                    .and_then(|item| item.project_path(cx))
+                    .map(|path| path.path.to_path_buf());
+                assert_eq!(active_path, Some(PathBuf::from("b.txt")));
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_open_editors_activate_and_close(cx: &mut gpui::TestAppContext) {
+        init_test_with_editor(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree("/root1", json!({ "a.txt": "", "b.txt": "" }))
+            .await;
+
+        let project = Project::test(fs.clone(), ["/root1".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        workspace
+            .update(cx, |workspace, cx| {
+                let panel = ProjectPanel::new(workspace, cx);
+                workspace.add_panel(panel, cx);
+            })
+            .unwrap();
+        let worktree_id =
+            cx.update(|cx| project.read(cx).worktrees().next().unwrap().read(cx).id());
+        let mut item_ids = Vec::new();
+        for path in ["a.txt", "b.txt"] {
+            let item = workspace
+                .update(cx, |workspace, cx| {
+                    workspace.open_path((worktree_id, path), None, true, cx)
+                })
+                .unwrap()
+                .await
+                .unwrap();
+            item_ids.push(item.item_id());
+        }
+        let pane = workspace
+            .update(cx, |workspace, _| workspace.active_pane().clone())
+            .unwrap();
+        let active_item_id = |cx: &mut VisualTestContext| {
+            pane.update(cx, |pane, _| pane.active_item().map(|item| item.item_id()))
+        };
+        assert_eq!(active_item_id(cx), Some(item_ids[1]));
+
+        cx.update(|cx| activate_open_editor(&pane.downgrade(), item_ids[0], cx));
+        assert_eq!(active_item_id(cx), Some(item_ids[0]));
+
+        cx.update(|cx| close_open_editor(&pane.downgrade(), item_ids[0], cx));
+        cx.run_until_parked();
+        let open_item_ids = pane.update(cx, |pane, _| {
+            pane.items().map(|item| item.item_id()).collect::<Vec<_>>()
+        });
+        assert_eq!(open_item_ids, &[item_ids[1]]);
+    }
+
+    #[gpui::test]
+    async fn test_open_new_files(cx: &mut gpui::TestAppContext) {
+        init_test_with_editor(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree("/root1", json!({ "a.txt": "" })).await;
+
+        let project = Project::test(fs.clone(), ["/root1".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| {
+                let panel = ProjectPanel::new(workspace, cx);
+                workspace.add_panel(panel.clone(), cx);
+                panel
+            })
+            .unwrap();
+
+        let create_file = |open_new_files, name: &str, cx: &mut VisualTestContext| {
+            cx.update(|cx| {
+                cx.update_global::<SettingsStore, _>(|store, cx| {
+                    store.update_user_settings::<ProjectPanelSettings>(cx, |settings| {
+                        settings.open_new_files = Some(open_new_files)
+                    });
+                })
+            });
+            select_path(&panel, "root1", cx);
+            panel.update(cx, |panel, cx| panel.new_file(&NewFile, cx));
+            let confirm = panel.update(cx, |panel, cx| {
+                panel
+                    .filename_editor
+                    .update(cx, |editor, cx| editor.set_text(name, cx));
+                panel.confirm_edit(cx).unwrap()
+            });
+            cx.run_until_parked();
+            cx.background_executor.block(confirm).unwrap();
+        };
+
+        create_file(OpenNewFiles::None, "b.txt", cx);
+        workspace
+            .update(cx, |workspace, cx| {
+                assert!(
+                    workspace.active_item(cx).is_none(),
+                    "New files should stay closed"
+                );
+            })
+            .unwrap();
+
+        create_file(OpenNewFiles::ActivePane, "c.txt", cx);
+        create_file(OpenNewFiles::Split, "d.txt", cx);
+        workspace
+            .update(cx, |workspace, cx| {
+                assert_eq!(workspace.panes().len(), 2, "The last file should be split");
+                let open_paths = workspace
+                    .panes()
+                    .iter()
+                    .map(|pane| {
+                        pane.read(cx)
+                            .active_item()
+                            .and_then(|item| item.project_path(cx))
+                            .map(|path| path.path.to_path_buf())
+                    })
+                    .collect::<Vec<_>>();
+                assert_eq!(
+                    open_paths,
+                    [Some(PathBuf::from("c.txt")), Some(PathBuf::from("d.txt"))]
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_creating_excluded_entries(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<WorktreeSettings>(cx, |project_settings| {
+                    project_settings.file_scan_exclusions =
+                        Some(vec!["excluded_dir".to_string(), "**/.git".to_string()]);
+                });
+            });
+        });
+
+        cx.update(|cx| {
+            register_project_item::<TestProjectItemView>(cx);
+        });
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root1",
+            json!({
+                ".dockerignore": "",
+                ".git": {
+                    "HEAD": "",
+                },
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root1".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| {
+                let panel = ProjectPanel::new(workspace, cx);
+                workspace.add_panel(panel.clone(), cx);
+                panel
+            })
+            .unwrap();
+
+        select_path(&panel, "root1", cx);
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &["v root1  <== selected", "      .dockerignore",]
+        );
+        workspace
+            .update(cx, |workspace, cx| {
+                assert!(
+                    workspace.active_item(cx).is_none(),
+                    "Should have no active items in the beginning"
+                );
+            })
+            .unwrap();
+
+        let excluded_file_path = ".git/COMMIT_EDITMSG";
+        let excluded_dir_path = "excluded_dir";
+
+        panel.update(cx, |panel, cx| panel.new_file(&NewFile, cx));
+        panel.update(cx, |panel, cx| {
+            assert!(panel.filename_editor.read(cx).is_focused(cx));
+        });
+        panel
+            .update(cx, |panel, cx| {
+                panel
+                    .filename_editor
+                    .update(cx, |editor, cx| editor.set_text(excluded_file_path, cx));
+                panel.confirm_edit(cx).unwrap()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..13, cx),
+            &["v root1", "      .dockerignore"],
+            "Excluded dir should not be shown after opening a file in it"
+        );
+        panel.update(cx, |panel, cx| {
+            assert!(
+                !panel.filename_editor.read(cx).is_focused(cx),
+                "Should have closed the file name editor"
+            );
+        });
+        workspace
+            .update(cx, |workspace, cx| {
+                let active_entry_path = workspace
+                    .active_item(cx)
+                    .expect("should have opened and activated the excluded item")
+                    .act_as::<TestProjectItemView>(cx)
+                    .expect(
+                        "should have opened the corresponding project item for the excluded item",
+                    )
+                    .read(cx)
+                    .path
+                    .clone();
+                assert_eq!(
+                    active_entry_path.path.as_ref(),
+                    Path::new(excluded_file_path),
+                    "Should open the excluded file"
+                );
+
+                assert!(
+                    workspace.notification_ids().is_empty(),
+                    "Should have no notifications after opening an excluded file"
+                );
+            })
+            .unwrap();
+        assert!(
+            fs.is_file(Path::new("/root1/.git/COMMIT_EDITMSG")).await,
+            "Should have created the excluded file"
+        );
+
+        select_path(&panel, "root1", cx);
+        panel.update(cx, |panel, cx| panel.new_directory(&NewDirectory, cx));
+        panel.update(cx, |panel, cx| {
+            assert!(panel.filename_editor.read(cx).is_focused(cx));
+        });
+        panel
+            .update(cx, |panel, cx| {
+                panel
+                    .filename_editor
+                    .update(cx, |editor, cx| editor.set_text(excluded_file_path, cx));
+                panel.confirm_edit(cx).unwrap()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..13, cx),
+            &["v root1", "      .dockerignore"],
+            "Should not change the project panel after trying to create an excluded directorya directory with the same name as the excluded file"
+        );
+        panel.update(cx, |panel, cx| {
+            assert!(
+                !panel.filename_editor.read(cx).is_focused(cx),
+                "Should have closed the file name editor"
+            );
+        });
+        workspace
+            .update(cx, |workspace, cx| {
+                let notifications = workspace.notification_ids();
+                assert_eq!(
+                    notifications.len(),
+                    1,
+                    "Should receive one notification with the error message"
+                );
+                workspace.dismiss_notification(notifications.first().unwrap(), cx);
+                assert!(workspace.notification_ids().is_empty());
+            })
+            .unwrap();
+
+        select_path(&panel, "root1", cx);
+        panel.update(cx, |panel, cx| panel.new_directory(&NewDirectory, cx));
+        panel.update(cx, |panel, cx| {
+            assert!(panel.filename_editor.read(cx).is_focused(cx));
+        });
+        panel
+            .update(cx, |panel, cx| {
+                panel
+                    .filename_editor
+                    .update(cx, |editor, cx| editor.set_text(excluded_dir_path, cx));
+                panel.confirm_edit(cx).unwrap()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..13, cx),
+            &["v root1", "      .dockerignore"],
+            "Should not change the project panel after trying to create an excluded directory"
+        );
+        panel.update(cx, |panel, cx| {
+            assert!(
+                !panel.filename_editor.read(cx).is_focused(cx),
+                "Should have closed the file name editor"
+            );
+        });
+        workspace
+            .update(cx, |workspace, cx| {
+                let notifications = workspace.notification_ids();
+                assert_eq!(
+                    notifications.len(),
+                    1,
+                    "Should receive one notification explaining that no directory is actually shown"
+                );
+                workspace.dismiss_notification(notifications.first().unwrap(), cx);
+                assert!(workspace.notification_ids().is_empty());
+            })
+            .unwrap();
+        assert!(
+            fs.is_dir(Path::new("/root1/excluded_dir")).await,
+            "Should have created the excluded directory"
+        );
     }
-}
 
-impl EventEmitter<Event> for ProjectPanel {}
+    #[gpui::test]
+    async fn test_reveal_path_case_insensitive(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
 
-impl EventEmitter<PanelEvent> for ProjectPanel {}
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/project_root",
+            json!({
+                "Src": {
+                    "Main.rs": "// File contents",
+                },
+                "README.md": "# Readme",
+            }),
+        )
+        .await;
 
-impl Panel for ProjectPanel {
-    fn position(&self, cx: &WindowContext) -> DockPosition {
-        match ProjectPanelSettings::get_global(cx).dock {
-            ProjectPanelDockPosition::Left => DockPosition::Left,
-            ProjectPanelDockPosition::Right => DockPosition::Right,
-        }
-    }
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        let worktree_id = cx.update(|cx| {
+            project
+                .read(cx)
+                .visible_worktrees(cx)
+                .next()
+                .unwrap()
+                .read(cx)
+                .id()
+        });
+        let project_path = ProjectPath {
+            worktree_id,
+            path: Arc::from(Path::new("src/main.rs")),
+        };
 
-    fn position_is_valid(&self, position: DockPosition) -> bool {
-        matches!(position, DockPosition::Left | DockPosition::Right)
-    }
+        assert!(
+            !panel.update(cx, |panel, cx| panel.reveal_path(&project_path, cx)),
+            "Fake file system is case-sensitive, so the lookup should fail"
+        );
 
-    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
-        settings::update_settings_file::<ProjectPanelSettings>(
-            self.fs.clone(),
-            cx,
-            move |settings| {
-                let dock = match position {
-                    DockPosition::Left | DockPosition::Bottom => ProjectPanelDockPosition::Left,
-                    DockPosition::Right => ProjectPanelDockPosition::Right,
-                };
-                settings.dock = Some(dock);
-            },
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<ProjectPanelSettings>(cx, |project_panel_settings| {
+                    project_panel_settings.navigation_case_sensitivity =
+                        Some(NavigationCaseSensitivity::Insensitive)
+                });
+            })
+        });
+        assert!(panel.update(cx, |panel, cx| panel.reveal_path(&project_path, cx)));
+        cx.executor().run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v project_root",
+                "    v Src",
+                "          Main.rs  <== selected",
+                "      README.md",
+            ],
+            "Entries should keep their on-disk case"
         );
     }
 
-    fn size(&self, cx: &WindowContext) -> Pixels {
-        self.width
-            .unwrap_or_else(|| ProjectPanelSettings::get_global(cx).default_width)
-    }
+    #[gpui::test]
+    async fn test_select_in_project_panel(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
 
-    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
-        self.width = size;
-        self.serialize(cx);
-        cx.notify();
-    }
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/project_root",
+            json!({
+                "a": {
+                    "one.rs": "",
+                    "two.rs": "",
+                },
+                "b": {
+                    "three.rs": "",
+                },
+                "c.rs": "",
+            }),
+        )
+        .await;
 
-    fn icon(&self, cx: &WindowContext) -> Option<IconName> {
-        ProjectPanelSettings::get_global(cx)
-            .button
-            .then(|| IconName::FileTree)
-    }
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
 
-    fn icon_tooltip(&self, _cx: &WindowContext) -> Option<&'static str> {
-        Some("Project Panel")
+        let two = find_project_entry(&panel, "project_root/a/two.rs", cx).unwrap();
+        let three = find_project_entry(&panel, "project_root/b/three.rs", cx).unwrap();
+        panel.update(cx, |panel, cx| {
+            panel.project.update(cx, |_, cx| {
+                cx.emit(project::Event::SelectInProjectPanel(vec![two, three]))
+            })
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v project_root",
+                "    v a",
+                "          one.rs",
+                "          two.rs  <== selected  <== marked",
+                "    v b",
+                "          three.rs  <== marked",
+                "      c.rs",
+            ],
+            "All requested entries should be revealed and marked, with the first one selected"
+        );
     }
 
-    fn toggle_action(&self) -> Box<dyn Action> {
-        Box::new(ToggleFocus)
-    }
+    #[gpui::test]
+    async fn test_expand_top_level_dirs(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<WorktreeSettings>(cx, |worktree_settings| {
+                    worktree_settings.file_scan_exclusions = Some(vec!["**/.git".to_string()]);
+                });
+                store.update_user_settings::<ProjectPanelSettings>(cx, |project_panel_settings| {
+                    project_panel_settings.expand_top_level_dirs = Some(true)
+                });
+            })
+        });
 
-    fn persistent_name() -> &'static str {
-        "Project Panel"
-    }
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/project_root",
+            json!({
+                ".git": {},
+                ".gitignore": "target/\n",
+                "docs": { "guide.md": "" },
+                "src": {
+                    "nested": { "lib.rs": "" },
+                    "main.rs": "",
+                },
+                "target": { "debug": {} },
+            }),
+        )
+        .await;
 
-    fn starts_open(&self, cx: &WindowContext) -> bool {
-        let project = &self.project.read(cx);
-        project.dev_server_project_id().is_some()
-            || project.visible_worktrees(cx).any(|tree| {
-                tree.read(cx)
-                    .root_entry()
-                    .map_or(false, |entry| entry.is_dir())
-            })
-    }
-}
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..20, cx),
+            &[
+                "v project_root",
+                "    v docs",
+                "          guide.md",
+                "    v src",
+                "        > nested",
+                "          main.rs",
+                "    > target",
+                "      .gitignore",
+            ],
+            "Top-level directories should be expanded, except for the gitignored ones"
+        );
 
-impl FocusableView for ProjectPanel {
-    fn focus_handle(&self, _cx: &AppContext) -> FocusHandle {
-        self.focus_handle.clone()
+        toggle_expand_dir(&panel, "project_root/docs", cx);
+        fs.insert_tree("/project_root/tests", json!({ "it.rs": "" }))
+            .await;
+        cx.run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..20, cx),
+            &[
+                "v project_root",
+                "    > docs",
+                "    v src",
+                "        > nested",
+                "          main.rs",
+                "    > target",
+                "    > tests",
+                "      .gitignore",
+            ],
+            "The expansion should only happen when the project is opened"
+        );
     }
-}
 
-impl ClipboardEntry {
-    fn is_cut(&self) -> bool {
-        matches!(self, Self::Cut { .. })
-    }
+    #[gpui::test]
+    async fn test_new_entry_ignored_by_git(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/project_root",
+            json!({
+                ".git": {},
+                ".gitignore": "target/\n*.log\n",
+                "src": {
+                    "main.rs": "",
+                },
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
 
-    fn items(&self) -> &BTreeSet<SelectedEntry> {
-        match self {
-            ClipboardEntry::Copied(entries) | ClipboardEntry::Cut(entries) => entries,
+        select_path(&panel, "project_root", cx);
+        panel.update(cx, |panel, cx| panel.new_file(&NewFile, cx));
+        for (filename, expected) in [
+            ("", false),
+            ("notes.md", false),
+            ("src/lib.rs", false),
+            ("debug.log", true),
+            ("target/generated.rs", true),
+        ] {
+            panel.update(cx, |panel, cx| {
+                panel
+                    .filename_editor
+                    .update(cx, |editor, cx| editor.set_text(filename, cx));
+                assert_eq!(
+                    panel.is_new_entry_ignored(cx),
+                    expected,
+                    "Unexpected ignore state for new file {filename:?}"
+                );
+            });
         }
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use collections::HashSet;
-    use gpui::{Empty, TestAppContext, View, VisualTestContext, WindowHandle};
-    use pretty_assertions::assert_eq;
-    use project::{FakeFs, WorktreeSettings};
-    use serde_json::json;
-    use settings::SettingsStore;
-    use std::path::{Path, PathBuf};
-    use workspace::{
-        item::{Item, ProjectItem},
-        register_project_item, AppState,
-    };
 
     #[gpui::test]
-    async fn test_visible_list(cx: &mut gpui::TestAppContext) {
+    async fn test_detect_encrypted_files(cx: &mut gpui::TestAppContext) {
         init_test(cx);
 
         let fs = FakeFs::new(cx.executor().clone());
         fs.insert_tree(
-            "/root1",
+            "/project_root",
             json!({
-                ".dockerignore": "",
-                ".git": {
-                    "HEAD": "",
-                },
-                "a": {
-                    "0": { "q": "", "r": "", "s": "" },
-                    "1": { "t": "", "u": "" },
-                    "2": { "v": "", "w": "", "x": "", "y": "" },
-                },
-                "b": {
-                    "3": { "Q": "" },
-                    "4": { "R": "", "S": "", "T": "", "U": "" },
-                },
-                "C": {
-                    "5": {},
-                    "6": { "V": "", "W": "" },
-                    "7": { "X": "" },
-                    "8": { "Y": {}, "Z": "" }
-                }
+                "secrets.env": "\u{0}GITCRYPT\u{0}ciphertext",
+                "values.yaml": "password: ENC[AES256_GCM,data:abc=,type:str]",
+                "plain.txt": "password: hunter2",
             }),
         )
         .await;
+
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
+        render_rows(&panel, 0..4, cx);
+
+        for (path, expected) in [
+            ("project_root/secrets.env", true),
+            ("project_root/values.yaml", true),
+            ("project_root/plain.txt", false),
+        ] {
+            let entry_id = find_project_entry(&panel, path, cx).unwrap();
+            assert_eq!(
+                panel.update(cx, |panel, _| {
+                    panel
+                        .decorations
+                        .get::<FileHeaderDecoration>()
+                        .is_encrypted(entry_id)
+                }),
+                expected,
+                "Unexpected encryption state for {path}"
+            );
+        }
+    }
+
+    #[gpui::test]
+    async fn test_file_headers_prefetched_around_rendered_rows(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        let files = (0..300)
+            .map(|ix| (format!("file_{ix:03}.txt"), json!("")))
+            .collect::<serde_json::Map<_, _>>();
+        fs.insert_tree("/root", serde_json::Value::Object(files))
+            .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
+        let is_sniffed = |path: &str, cx: &mut VisualTestContext| {
+            let entry_id = find_project_entry(&panel, path, cx).unwrap();
+            panel.update(cx, |panel, _| {
+                panel
+                    .decorations
+                    .get::<FileHeaderDecoration>()
+                    .file_header(entry_id)
+                    .is_some()
+            })
+        };
+        let sniffed_count = |cx: &mut VisualTestContext| {
+            (0..300)
+                .filter(|ix| is_sniffed(&format!("root/file_{ix:03}.txt"), cx))
+                .count()
+        };
+        assert_eq!(
+            sniffed_count(cx),
+            0,
+            "Nothing is sniffed before rows render"
+        );
+
+        render_rows(&panel, 0..1, cx);
+        let count = sniffed_count(cx);
+        assert!(count >= FILE_HEADER_BATCH_SIZE);
+        assert!(count < DECORATION_PREFETCH_ROWS);
+
+        for _ in 0..10 {
+            cx.executor().advance_clock(FILE_HEADER_BATCH_INTERVAL);
+            cx.run_until_parked();
+        }
+        assert_eq!(sniffed_count(cx), DECORATION_PREFETCH_ROWS);
+        assert!(is_sniffed("root/file_099.txt", cx));
+        assert!(!is_sniffed("root/file_100.txt", cx));
+
+        render_rows(&panel, 250..260, cx);
+        assert!(is_sniffed("root/file_249.txt", cx));
+        assert!(is_sniffed("root/file_258.txt", cx));
+        assert!(!is_sniffed("root/file_150.txt", cx));
+    }
+
+    #[gpui::test]
+    async fn test_license_headers(cx: &mut gpui::TestAppContext) {
+        init_test_with_editor(cx);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<ProjectPanelSettings>(cx, |project_panel_settings| {
+                    project_panel_settings.license_headers = Some(HashMap::from_iter([(
+                        "rs".to_string(),
+                        "// Copyright Acme\n// SPDX-License-Identifier: MIT".to_string(),
+                    )]));
+                });
+            })
+        });
+
+        let fs = FakeFs::new(cx.executor().clone());
         fs.insert_tree(
-            "/root2",
+            "/project_root",
             json!({
-                "d": {
-                    "9": ""
+                "src": {
+                    "licensed.rs": "// Copyright Acme\r\n// SPDX-License-Identifier: MIT\r\nfn a() {}",
+                    "script.rs": "#!/usr/bin/env run-cargo-script\nfn main() {}",
+                    "unlicensed.rs": "fn b() {}",
                 },
-                "e": {}
+                "README.md": "# Project",
             }),
         )
         .await;
 
-        let project = Project::test(fs.clone(), ["/root1".as_ref(), "/root2".as_ref()], cx).await;
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
         let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
         let cx = &mut VisualTestContext::from_window(*workspace, cx);
         let panel = workspace
             .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
             .unwrap();
+        toggle_expand_dir(&panel, "project_root/src", cx);
+        cx.run_until_parked();
+        render_rows(&panel, 0..6, cx);
+
+        let is_missing_license_header = |path: &str, cx: &mut VisualTestContext| {
+            let entry_id = find_project_entry(&panel, path, cx).unwrap();
+            panel.update(cx, |panel, _| {
+                panel
+                    .decorations
+                    .get::<FileHeaderDecoration>()
+                    .file_header(entry_id)
+                    .map_or(false, |file_header| file_header.is_missing_license_header)
+            })
+        };
+        for (path, expected) in [
+            ("project_root/src/licensed.rs", false),
+            ("project_root/src/script.rs", true),
+            ("project_root/src/unlicensed.rs", true),
+            ("project_root/README.md", false),
+        ] {
+            assert_eq!(
+                is_missing_license_header(path, cx),
+                expected,
+                "Unexpected license header state for {path}"
+            );
+        }
+
+        select_path(&panel, "project_root/src", cx);
+        panel.update(cx, |panel, cx| {
+            panel.insert_license_header(&InsertLicenseHeader, cx)
+        });
+        cx.run_until_parked();
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..50, cx),
-            &[
-                "v root1",
-                "    > .git",
-                "    > a",
-                "    > b",
-                "    > C",
-                "      .dockerignore",
-                "v root2",
-                "    > d",
-                "    > e",
-            ]
+            fs.load("/project_root/src/script.rs".as_ref()).await.unwrap(),
+            "#!/usr/bin/env run-cargo-script\n// Copyright Acme\n// SPDX-License-Identifier: MIT\nfn main() {}"
         );
-
-        toggle_expand_dir(&panel, "root1/b", cx);
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..50, cx),
-            &[
-                "v root1",
-                "    > .git",
-                "    > a",
-                "    v b  <== selected",
-                "        > 3",
-                "        > 4",
-                "    > C",
-                "      .dockerignore",
-                "v root2",
-                "    > d",
-                "    > e",
-            ]
+            fs.load("/project_root/src/unlicensed.rs".as_ref())
+                .await
+                .unwrap(),
+            "// Copyright Acme\n// SPDX-License-Identifier: MIT\nfn b() {}"
         );
+        assert_eq!(
+            fs.load("/project_root/src/licensed.rs".as_ref())
+                .await
+                .unwrap(),
+            "// Copyright Acme\r\n// SPDX-License-Identifier: MIT\r\nfn a() {}"
+        );
+        render_rows(&panel, 0..6, cx);
+        for path in [
+            "project_root/src/script.rs",
+            "project_root/src/unlicensed.rs",
+        ] {
+            assert!(
+                !is_missing_license_header(path, cx),
+                "{path} is still missing its header"
+            );
+        }
 
+        select_path(&panel, "project_root/src", cx);
+        panel.update(cx, |panel, cx| panel.new_file(&NewFile, cx));
+        panel
+            .update(cx, |panel, cx| {
+                panel
+                    .filename_editor
+                    .update(cx, |editor, cx| editor.set_text("new.rs", cx));
+                panel.confirm_edit(cx).unwrap()
+            })
+            .await
+            .unwrap();
+        cx.run_until_parked();
         assert_eq!(
-            visible_entries_as_strings(&panel, 6..9, cx),
-            &[
-                //
-                "    > C",
-                "      .dockerignore",
-                "v root2",
-            ]
+            fs.load("/project_root/src/new.rs".as_ref()).await.unwrap(),
+            "// Copyright Acme\n// SPDX-License-Identifier: MIT\n"
         );
     }
 
     #[gpui::test]
-    async fn test_exclusions_in_visible_list(cx: &mut gpui::TestAppContext) {
+    async fn test_folder_statistics(cx: &mut gpui::TestAppContext) {
+        use folder_statistics::{ExtensionStatistics, FolderStatistics};
+
         init_test(cx);
-        cx.update(|cx| {
-            cx.update_global::<SettingsStore, _>(|store, cx| {
-                store.update_user_settings::<WorktreeSettings>(cx, |worktree_settings| {
-                    worktree_settings.file_scan_exclusions =
-                        Some(vec!["**/.git".to_string(), "**/4/**".to_string()]);
-                });
-            });
-        });
 
-        let fs = FakeFs::new(cx.background_executor.clone());
+        let fs = FakeFs::new(cx.executor().clone());
         fs.insert_tree(
-            "/root1",
+            "/project_root",
             json!({
-                ".dockerignore": "",
-                ".git": {
-                    "HEAD": "",
+                "src": {
+                    "lib.rs": "mod a;\nmod b;\n",
+                    "a.rs": "fn a() {}",
+                    "nested": { "b.rs": "fn b() {\n}\n" },
+                    "Makefile": "all:\n\tcargo build\n",
                 },
-                "a": {
-                    "0": { "q": "", "r": "", "s": "" },
-                    "1": { "t": "", "u": "" },
-                    "2": { "v": "", "w": "", "x": "", "y": "" },
+                "README.md": "# Project",
+            }),
+        )
+        .await;
+        fs.insert_file("/project_root/src/logo.png", vec![0x89, 0, 0, 0x0a, 0x0a])
+            .await;
+
+        let files = ["lib.rs", "a.rs", "nested/b.rs", "Makefile", "logo.png"]
+            .into_iter()
+            .map(|path| {
+                (
+                    PathBuf::from(path),
+                    Path::new("/project_root/src").join(path),
+                )
+            })
+            .collect();
+        let statistics = FolderStatistics::compute(fs.clone(), files).await;
+        assert_eq!(statistics.file_count, 5);
+        assert_eq!(statistics.total_lines, 7);
+        assert_eq!(statistics.total_bytes, 57);
+        assert_eq!(
+            statistics.extensions,
+            vec![
+                ExtensionStatistics {
+                    extension: "rs".to_string(),
+                    file_count: 3,
+                    lines: 5,
                 },
-                "b": {
-                    "3": { "Q": "" },
-                    "4": { "R": "", "S": "", "T": "", "U": "" },
+                ExtensionStatistics {
+                    extension: "no extension".to_string(),
+                    file_count: 1,
+                    lines: 2,
                 },
-                "C": {
-                    "5": {},
-                    "6": { "V": "", "W": "" },
-                    "7": { "X": "" },
-                    "8": { "Y": {}, "Z": "" }
-                }
+                ExtensionStatistics {
+                    extension: "png".to_string(),
+                    file_count: 1,
+                    lines: 0,
+                },
+            ]
+        );
+        assert_eq!(
+            statistics.largest_files.first(),
+            Some(&(PathBuf::from("Makefile"), 18))
+        );
+        assert_eq!(
+            statistics
+                .summary("src")
+                .lines()
+                .take(4)
+                .collect::<Vec<_>>(),
+            vec!["Statistics for src", "Files: 5", "Lines: 7", "Size: 57 B"]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_stop_watching_folder(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/project_root",
+            json!({
+                "src": { "main.rs": "" },
+                "target": { "debug": { "app": "" } },
             }),
         )
         .await;
+
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &["v project_root", "    > src", "    > target"]
+        );
+
+        select_path(&panel, "project_root/target", cx);
+        panel.update(cx, |panel, cx| {
+            panel.stop_watching_folder(&StopWatchingFolder, cx)
+        });
+        cx.run_until_parked();
+        let settings_text = fs
+            .load("/project_root/.zed/settings.json".as_ref())
+            .await
+            .unwrap();
+        assert!(
+            settings_text.contains("\"file_scan_exclusions\"")
+                && settings_text.contains("\"target\""),
+            "Unexpected project settings: {settings_text}"
+        );
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &["v project_root", "    > .zed", "    > src"]
+        );
+
+        let worktree_id = panel.update(cx, |panel, cx| {
+            panel
+                .project
+                .read(cx)
+                .worktrees()
+                .next()
+                .unwrap()
+                .read(cx)
+                .id()
+        });
+        assert_eq!(
+            cx.update(|cx| unwatched_folders(worktree_id, Path::new(""), cx)),
+            vec!["target".to_string()]
+        );
+        panel.update(cx, |panel, cx| {
+            panel.watch_folder_again(worktree_id, "target".to_string(), cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &["v project_root", "    > .zed", "    > src", "    > target"]
+        );
+        assert!(cx
+            .update(|cx| unwatched_folders(worktree_id, Path::new(""), cx))
+            .is_empty());
+    }
+
+    #[gpui::test]
+    async fn test_layout_snapshots(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
         fs.insert_tree(
-            "/root2",
+            "/project_root",
             json!({
-                "d": {
-                    "4": ""
-                },
-                "e": {}
+                "api": { "routes": { "users.rs": "" } },
+                "frontend": { "app.ts": "" },
             }),
         )
         .await;
 
-        let project = Project::test(fs.clone(), ["/root1".as_ref(), "/root2".as_ref()], cx).await;
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
         let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
         let cx = &mut VisualTestContext::from_window(*workspace, cx);
         let panel = workspace
             .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
             .unwrap();
-        assert_eq!(
-            visible_entries_as_strings(&panel, 0..50, cx),
-            &[
-                "v root1",
-                "    > a",
-                "    > b",
-                "    > C",
-                "      .dockerignore",
-                "v root2",
-                "    > d",
-                "    > e",
-            ]
-        );
+        cx.run_until_parked();
 
-        toggle_expand_dir(&panel, "root1/b", cx);
+        toggle_expand_dir(&panel, "project_root/api", cx);
+        toggle_expand_dir(&panel, "project_root/api/routes", cx);
+        select_path(&panel, "project_root/api/routes/users.rs", cx);
+        let api_layout = &[
+            "v project_root",
+            "    v api",
+            "        v routes",
+            "              users.rs  <== selected",
+            "    > frontend",
+        ];
+        assert_eq!(visible_entries_as_strings(&panel, 0..10, cx), api_layout);
+        let snapshot = panel.update(cx, |panel, cx| {
+            panel.capture_layout_snapshot("API work".to_string(), cx)
+        });
+        assert_eq!(snapshot.name, "API work");
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..50, cx),
-            &[
-                "v root1",
-                "    > a",
-                "    v b  <== selected",
-                "        > 3",
-                "    > C",
-                "      .dockerignore",
-                "v root2",
-                "    > d",
-                "    > e",
-            ]
+            snapshot.selection,
+            Some((
+                PathBuf::from("/project_root"),
+                PathBuf::from("api/routes/users.rs")
+            ))
         );
 
-        toggle_expand_dir(&panel, "root2/d", cx);
+        toggle_expand_dir(&panel, "project_root/api", cx);
+        toggle_expand_dir(&panel, "project_root/frontend", cx);
+        select_path(&panel, "project_root/frontend/app.ts", cx);
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..50, cx),
+            visible_entries_as_strings(&panel, 0..10, cx),
             &[
-                "v root1",
-                "    > a",
-                "    v b",
-                "        > 3",
-                "    > C",
-                "      .dockerignore",
-                "v root2",
-                "    v d  <== selected",
-                "    > e",
+                "v project_root",
+                "    > api",
+                "    v frontend",
+                "          app.ts  <== selected",
             ]
         );
 
-        toggle_expand_dir(&panel, "root2/e", cx);
-        assert_eq!(
-            visible_entries_as_strings(&panel, 0..50, cx),
-            &[
-                "v root1",
-                "    > a",
-                "    v b",
-                "        > 3",
-                "    > C",
-                "      .dockerignore",
-                "v root2",
-                "    v d",
-                "    v e  <== selected",
-            ]
-        );
+        panel.update(cx, |panel, cx| panel.restore_layout_snapshot(&snapshot, cx));
+        cx.run_until_parked();
+        assert_eq!(visible_entries_as_strings(&panel, 0..10, cx), api_layout);
     }
 
     #[gpui::test]
-    async fn test_auto_collapse_dir_paths(cx: &mut gpui::TestAppContext) {
+    async fn test_entry_notes(cx: &mut gpui::TestAppContext) {
         init_test(cx);
 
         let fs = FakeFs::new(cx.executor().clone());
         fs.insert_tree(
-            "/root1",
+            "/project_root",
             json!({
-                "dir_1": {
-                    "nested_dir_1": {
-                        "nested_dir_2": {
-                            "nested_dir_3": {
-                                "file_a.java": "// File contents",
-                                "file_b.java": "// File contents",
-                                "file_c.java": "// File contents",
-                                "nested_dir_4": {
-                                    "nested_dir_5": {
-                                        "file_d.java": "// File contents",
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+                "src": { "legacy.rs": "", "main.rs": "" },
             }),
         )
         .await;
+
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
+        let worktree_id =
+            cx.update(|cx| project.read(cx).worktrees().next().unwrap().read(cx).id());
+
+        panel.update(cx, |panel, _| {
+            let root = Path::new("/project_root");
+            panel.entry_notes.set(
+                root,
+                Path::new("src/legacy.rs"),
+                Some("Remove after the migration".to_string()),
+            );
+            panel
+                .entry_notes
+                .set(root, Path::new("src"), Some("Being split up".to_string()));
+            panel.entry_notes.set(root, Path::new("src"), None);
+        });
+        panel.update(cx, |panel, cx| {
+            assert_eq!(
+                panel.note_for_entry(worktree_id, Path::new("src/legacy.rs"), cx),
+                Some("Remove after the migration")
+            );
+            assert_eq!(
+                panel.note_for_entry(worktree_id, Path::new("src"), cx),
+                None
+            );
+            assert_eq!(
+                panel.note_for_entry(worktree_id, Path::new("src/main.rs"), cx),
+                None
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_dispatch_context_flags(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/project_root",
+            json!({ "src": { "main.rs": "" }, "README.md": "" }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
+
+        let flags = |cx: &mut VisualTestContext| {
+            panel.update(cx, |panel, cx| {
+                let context = panel.dispatch_context(cx);
+                [
+                    "has_selection",
+                    "selection_is_dir",
+                    "filter_active",
+                    "multiselect",
+                ]
+                .into_iter()
+                .filter(|flag| context.contains(flag))
+                .collect::<Vec<_>>()
+            })
+        };
+        assert!(flags(cx).is_empty());
+
+        select_path(&panel, "project_root/src", cx);
+        assert_eq!(flags(cx), ["has_selection", "selection_is_dir"]);
+
+        select_path(&panel, "project_root/README.md", cx);
+        assert_eq!(flags(cx), ["has_selection"]);
+
+        let src = find_project_entry(&panel, "project_root/src", cx).unwrap();
+        panel.update(cx, |panel, _| {
+            let selection = panel.selection.unwrap();
+            panel.marked_entries.insert(selection);
+            panel.marked_entries.insert(SelectedEntry {
+                worktree_id: selection.worktree_id,
+                entry_id: src,
+            });
+            panel.tag_filter = Some("deprecated".to_string());
+        });
+        assert_eq!(flags(cx), ["has_selection", "filter_active", "multiselect"]);
+    }
+
+    #[gpui::test]
+    async fn test_entry_filter(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
         fs.insert_tree(
-            "/root2",
+            "/project_root",
             json!({
-                "dir_2": {
-                    "file_1.java": "// File contents",
-                }
+                "api": { "v1": { "users.rs": "", "orders.rs": "" }, "v2": { "users.rs": "" } },
+                "docs": { "guide.md": "" },
+                "README.md": "",
             }),
         )
         .await;
 
-        let project = Project::test(fs.clone(), ["/root1".as_ref(), "/root2".as_ref()], cx).await;
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
         let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
         let cx = &mut VisualTestContext::from_window(*workspace, cx);
-        cx.update(|cx| {
-            let settings = *ProjectPanelSettings::get_global(cx);
-            ProjectPanelSettings::override_global(
-                ProjectPanelSettings {
-                    auto_fold_dirs: true,
-                    ..settings
-                },
-                cx,
-            );
-        });
         let panel = workspace
             .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
             .unwrap();
-        assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &[
-                "v root1",
-                "    > dir_1/nested_dir_1/nested_dir_2/nested_dir_3",
-                "v root2",
-                "    > dir_2",
-            ]
-        );
+        cx.run_until_parked();
 
-        toggle_expand_dir(
-            &panel,
-            "root1/dir_1/nested_dir_1/nested_dir_2/nested_dir_3",
-            cx,
-        );
+        panel.update(cx, |panel, cx| {
+            panel
+                .filter_editor
+                .update(cx, |editor, cx| editor.set_text("orders", cx))
+        });
+        cx.run_until_parked();
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
+            visible_entries_as_strings(&panel, 0..20, cx),
             &[
-                "v root1",
-                "    v dir_1/nested_dir_1/nested_dir_2/nested_dir_3  <== selected",
-                "        > nested_dir_4/nested_dir_5",
-                "          file_a.java",
-                "          file_b.java",
-                "          file_c.java",
-                "v root2",
-                "    > dir_2",
-            ]
+                "v project_root",
+                "    v api",
+                "        v v1",
+                "              orders.rs",
+            ],
+            "The directories of the matching entries should be expanded"
         );
 
-        toggle_expand_dir(
-            &panel,
-            "root1/dir_1/nested_dir_1/nested_dir_2/nested_dir_3/nested_dir_4/nested_dir_5",
-            cx,
-        );
+        panel.update(cx, |panel, cx| {
+            panel
+                .filter_editor
+                .update(cx, |editor, cx| editor.set_text("users", cx))
+        });
+        cx.run_until_parked();
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
+            visible_entries_as_strings(&panel, 0..20, cx),
             &[
-                "v root1",
-                "    v dir_1/nested_dir_1/nested_dir_2/nested_dir_3",
-                "        v nested_dir_4/nested_dir_5  <== selected",
-                "              file_d.java",
-                "          file_a.java",
-                "          file_b.java",
-                "          file_c.java",
-                "v root2",
-                "    > dir_2",
+                "v project_root",
+                "    v api",
+                "        v v1",
+                "              users.rs",
+                "        v v2",
+                "              users.rs",
             ]
         );
-        toggle_expand_dir(&panel, "root2/dir_2", cx);
-        assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &[
-                "v root1",
-                "    v dir_1/nested_dir_1/nested_dir_2/nested_dir_3",
-                "        v nested_dir_4/nested_dir_5",
-                "              file_d.java",
-                "          file_a.java",
-                "          file_b.java",
-                "          file_c.java",
-                "v root2",
-                "    v dir_2  <== selected",
-                "          file_1.java",
-            ]
+
+        panel.update(cx, |panel, cx| {
+            panel
+                .filter_editor
+                .update(cx, |editor, cx| editor.set_text("", cx))
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..20, cx),
+            &[
+                "v project_root",
+                "    v api",
+                "        v v1",
+                "              orders.rs",
+                "              users.rs",
+                "        v v2",
+                "              users.rs",
+                "    > docs",
+                "      README.md",
+            ],
+            "Clearing the filter should show all entries, keeping the directories expanded"
         );
     }
 
-    #[gpui::test(iterations = 30)]
-    async fn test_editing_files(cx: &mut gpui::TestAppContext) {
+    #[gpui::test]
+    async fn test_select_by_typing(cx: &mut gpui::TestAppContext) {
         init_test(cx);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<ProjectPanelSettings>(cx, |project_panel_settings| {
+                    project_panel_settings.navigation_case_sensitivity =
+                        Some(NavigationCaseSensitivity::Insensitive)
+                });
+            })
+        });
 
         let fs = FakeFs::new(cx.executor().clone());
         fs.insert_tree(
-            "/root1",
-            json!({
-                ".dockerignore": "",
-                ".git": {
-                    "HEAD": "",
-                },
-                "a": {
-                    "0": { "q": "", "r": "", "s": "" },
-                    "1": { "t": "", "u": "" },
-                    "2": { "v": "", "w": "", "x": "", "y": "" },
-                },
-                "b": {
-                    "3": { "Q": "" },
-                    "4": { "R": "", "S": "", "T": "", "U": "" },
-                },
-                "C": {
-                    "5": {},
-                    "6": { "V": "", "W": "" },
-                    "7": { "X": "" },
-                    "8": { "Y": {}, "Z": "" }
-                }
-            }),
-        )
-        .await;
-        fs.insert_tree(
-            "/root2",
+            "/root",
             json!({
-                "d": {
-                    "9": ""
-                },
-                "e": {}
+                "apple": {},
+                "Bin": {},
+                "banana.txt": "",
+                "bar.rs": "",
+                "cherry.md": "",
             }),
         )
         .await;
 
-        let project = Project::test(fs.clone(), ["/root1".as_ref(), "/root2".as_ref()], cx).await;
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
         let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
         let cx = &mut VisualTestContext::from_window(*workspace, cx);
         let panel = workspace
             .update(cx, |workspace, cx| {
                 let panel = ProjectPanel::new(workspace, cx);
                 workspace.add_panel(panel.clone(), cx);
+                workspace.focus_panel::<ProjectPanel>(cx);
                 panel
             })
             .unwrap();
+        cx.run_until_parked();
 
-        select_path(&panel, "root1", cx);
+        cx.simulate_keystrokes("b");
         assert_eq!(
             visible_entries_as_strings(&panel, 0..10, cx),
             &[
-                "v root1  <== selected",
-                "    > .git",
-                "    > a",
-                "    > b",
-                "    > C",
-                "      .dockerignore",
-                "v root2",
-                "    > d",
-                "    > e",
+                "v root",
+                "    > apple",
+                "    > Bin  <== selected",
+                "      banana.txt",
+                "      bar.rs",
+                "      cherry.md",
             ]
         );
 
-        // Add a file with the root folder selected. The filename editor is placed
-        // before the first file in the root folder.
-        panel.update(cx, |panel, cx| panel.new_file(&NewFile, cx));
-        panel.update(cx, |panel, cx| {
-            assert!(panel.filename_editor.read(cx).is_focused(cx));
-        });
+        cx.simulate_keystrokes("a");
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &[
-                "v root1",
-                "    > .git",
-                "    > a",
-                "    > b",
-                "    > C",
-                "      [EDITOR: '']  <== selected",
-                "      .dockerignore",
-                "v root2",
-                "    > d",
-                "    > e",
-            ]
+            visible_entries_as_strings(&panel, 3..4, cx),
+            &["      banana.txt  <== selected"],
+            "Typing more characters should extend the prefix"
         );
-
-        let confirm = panel.update(cx, |panel, cx| {
-            panel
-                .filename_editor
-                .update(cx, |editor, cx| editor.set_text("the-new-filename", cx));
-            panel.confirm_edit(cx).unwrap()
-        });
+        cx.simulate_keystrokes("r");
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &[
-                "v root1",
-                "    > .git",
-                "    > a",
-                "    > b",
-                "    > C",
-                "      [PROCESSING: 'the-new-filename']  <== selected",
-                "      .dockerignore",
-                "v root2",
-                "    > d",
-                "    > e",
-            ]
+            visible_entries_as_strings(&panel, 4..5, cx),
+            &["      bar.rs  <== selected"]
         );
 
-        confirm.await.unwrap();
+        cx.executor()
+            .advance_clock(TYPE_TO_SELECT_TIMEOUT + Duration::from_millis(100));
+        cx.simulate_keystrokes("b");
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &[
-                "v root1",
-                "    > .git",
-                "    > a",
-                "    > b",
-                "    > C",
-                "      .dockerignore",
-                "      the-new-filename  <== selected  <== marked",
-                "v root2",
-                "    > d",
-                "    > e",
-            ]
+            visible_entries_as_strings(&panel, 2..3, cx),
+            &["    > Bin  <== selected"],
+            "After the timeout, typing should start a new prefix and wrap around"
         );
 
-        select_path(&panel, "root1/b", cx);
-        panel.update(cx, |panel, cx| panel.new_file(&NewFile, cx));
+        cx.executor()
+            .advance_clock(TYPE_TO_SELECT_TIMEOUT + Duration::from_millis(100));
+        cx.simulate_keystrokes("c");
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &[
-                "v root1",
-                "    > .git",
-                "    > a",
-                "    v b",
-                "        > 3",
-                "        > 4",
-                "          [EDITOR: '']  <== selected",
-                "    > C",
-                "      .dockerignore",
-                "      the-new-filename",
-            ]
+            visible_entries_as_strings(&panel, 5..6, cx),
+            &["      cherry.md  <== selected"]
         );
+    }
 
-        panel
-            .update(cx, |panel, cx| {
-                panel
-                    .filename_editor
-                    .update(cx, |editor, cx| editor.set_text("another-filename.txt", cx));
-                panel.confirm_edit(cx).unwrap()
+    #[gpui::test]
+    async fn test_file_nesting(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<ProjectPanelSettings>(cx, |project_panel_settings| {
+                    project_panel_settings.file_nesting = Some(
+                        [(
+                            "*.ts".to_string(),
+                            vec!["*.js".to_string(), "*.d.ts".to_string()],
+                        )]
+                        .into_iter()
+                        .collect(),
+                    )
+                });
             })
-            .await
+        });
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "src": {
+                    "app.d.ts": "",
+                    "app.js": "",
+                    "app.ts": "",
+                    "index.js": "",
+                    "util.js": "",
+                    "util.ts": "",
+                },
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
             .unwrap();
+
+        toggle_expand_dir(&panel, "root/src", cx);
         assert_eq!(
             visible_entries_as_strings(&panel, 0..10, cx),
             &[
-                "v root1",
-                "    > .git",
-                "    > a",
-                "    v b",
-                "        > 3",
-                "        > 4",
-                "          another-filename.txt  <== selected  <== marked",
-                "    > C",
-                "      .dockerignore",
-                "      the-new-filename",
-            ]
+                "v root",
+                "    v src  <== selected",
+                "        > app.ts",
+                "          index.js",
+                "        > util.ts",
+            ],
+            "Related files should be nested under their collapsed source file"
         );
 
-        select_path(&panel, "root1/b/another-filename.txt", cx);
-        panel.update(cx, |panel, cx| panel.rename(&Rename, cx));
+        let app_ts = find_project_entry(&panel, "root/src/app.ts", cx).unwrap();
+        panel.update(cx, |panel, cx| panel.toggle_expanded(app_ts, cx));
         assert_eq!(
             visible_entries_as_strings(&panel, 0..10, cx),
             &[
-                "v root1",
-                "    > .git",
-                "    > a",
-                "    v b",
-                "        > 3",
-                "        > 4",
-                "          [EDITOR: 'another-filename.txt']  <== selected  <== marked",
-                "    > C",
-                "      .dockerignore",
-                "      the-new-filename",
+                "v root",
+                "    v src",
+                "        v app.ts  <== selected",
+                "              app.d.ts",
+                "              app.js",
+                "          index.js",
+                "        > util.ts",
             ]
         );
 
-        let confirm = panel.update(cx, |panel, cx| {
-            panel.filename_editor.update(cx, |editor, cx| {
-                let file_name_selections = editor.selections.all::<usize>(cx);
-                assert_eq!(file_name_selections.len(), 1, "File editing should have a single selection, but got: {file_name_selections:?}");
-                let file_name_selection = &file_name_selections[0];
-                assert_eq!(file_name_selection.start, 0, "Should select the file name from the start");
-                assert_eq!(file_name_selection.end, "another-filename".len(), "Should not select file extension");
+        select_path(&panel, "root/src/util.ts", cx);
+        panel.update(cx, |panel, cx| {
+            panel.expand_selected_entry(&ExpandSelectedEntry, cx)
+        });
+        assert_eq!(
+            visible_entries_as_strings(&panel, 5..10, cx),
+            &[
+                "          index.js",
+                "        v util.ts  <== selected",
+                "              util.js",
+            ],
+            "Source files should expand from the keyboard too"
+        );
+    }
 
-                editor.set_text("a-different-filename.tar.gz", cx)
-            });
-            panel.confirm_edit(cx).unwrap()
+    #[gpui::test]
+    async fn test_file_nesting_overlapping_patterns(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<ProjectPanelSettings>(cx, |project_panel_settings| {
+                    project_panel_settings.file_nesting = Some(
+                        [
+                            ("*.tsx".to_string(), vec!["*.js".to_string()]),
+                            ("*.ts".to_string(), vec!["*.js".to_string()]),
+                            ("*.d.ts".to_string(), vec!["*.js".to_string()]),
+                        ]
+                        .into_iter()
+                        .collect(),
+                    )
+                });
+            })
         });
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "src": {
+                    "app.js": "",
+                    "app.ts": "",
+                    "app.tsx": "",
+                    "lib.d.ts": "",
+                    "lib.js": "",
+                },
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+
+        toggle_expand_dir(&panel, "root/src", cx);
+        for _ in 0..3 {
+            panel.update(cx, |panel, cx| panel.update_visible_entries(None, cx));
+            assert_eq!(
+                visible_entries_as_strings(&panel, 0..10, cx),
+                &[
+                    "v root",
+                    "    v src  <== selected",
+                    "        > app.ts",
+                    "          app.tsx",
+                    "        > lib.d.ts",
+                ],
+                "Files related to several sources should always go under the same one"
+            );
+        }
+    }
+
+    #[gpui::test]
+    async fn test_sort_by(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "a.rs": "x",
+                "b.md": "xxxxx",
+                "c.rs": "xxx",
+                "d": { "e.txt": "" },
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
+
+        let set_sort_by = |sort_by: EntrySortMode, cx: &mut VisualTestContext| {
+            cx.update(|cx| {
+                cx.update_global::<SettingsStore, _>(|store, cx| {
+                    store.update_user_settings::<ProjectPanelSettings>(cx, |settings| {
+                        settings.sort_by = Some(sort_by);
+                    });
+                })
+            });
+            cx.run_until_parked();
+        };
+
+        set_sort_by(EntrySortMode::Extension, cx);
         assert_eq!(
             visible_entries_as_strings(&panel, 0..10, cx),
             &[
-                "v root1",
-                "    > .git",
-                "    > a",
-                "    v b",
-                "        > 3",
-                "        > 4",
-                "          [PROCESSING: 'a-different-filename.tar.gz']  <== selected  <== marked",
-                "    > C",
-                "      .dockerignore",
-                "      the-new-filename",
-            ]
+                "v root",
+                "    > d",
+                "      b.md",
+                "      a.rs",
+                "      c.rs"
+            ],
+            "Files should be sorted by extension, then by name, after the directories"
         );
 
-        confirm.await.unwrap();
+        set_sort_by(EntrySortMode::Size, cx);
         assert_eq!(
             visible_entries_as_strings(&panel, 0..10, cx),
             &[
-                "v root1",
-                "    > .git",
-                "    > a",
-                "    v b",
-                "        > 3",
-                "        > 4",
-                "          a-different-filename.tar.gz  <== selected",
-                "    > C",
-                "      .dockerignore",
-                "      the-new-filename",
-            ]
+                "v root",
+                "    > d",
+                "      b.md",
+                "      c.rs",
+                "      a.rs"
+            ],
+            "Files should be sorted by size once it is read, the largest first"
         );
 
-        panel.update(cx, |panel, cx| panel.rename(&Rename, cx));
+        set_sort_by(EntrySortMode::Name, cx);
         assert_eq!(
             visible_entries_as_strings(&panel, 0..10, cx),
             &[
-                "v root1",
-                "    > .git",
-                "    > a",
-                "    v b",
-                "        > 3",
-                "        > 4",
-                "          [EDITOR: 'a-different-filename.tar.gz']  <== selected",
-                "    > C",
-                "      .dockerignore",
-                "      the-new-filename",
+                "v root",
+                "    > d",
+                "      a.rs",
+                "      b.md",
+                "      c.rs"
             ]
         );
+    }
 
-        panel.update(cx, |panel, cx| {
-            panel.filename_editor.update(cx, |editor, cx| {
-                let file_name_selections = editor.selections.all::<usize>(cx);
-                assert_eq!(file_name_selections.len(), 1, "File editing should have a single selection, but got: {file_name_selections:?}");
-                let file_name_selection = &file_name_selections[0];
-                assert_eq!(file_name_selection.start, 0, "Should select the file name from the start");
-                assert_eq!(file_name_selection.end, "a-different-filename.tar".len(), "Should not select file extension, but still may select anything up to the last dot..");
+    #[gpui::test]
+    async fn test_folders_position(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "a.rs": "",
+                "b": { "c.rs": "" },
+                "d.rs": "",
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
+        toggle_expand_dir(&panel, "root/b", cx);
 
+        let set_folders_position = |position: FoldersPosition, cx: &mut VisualTestContext| {
+            cx.update(|cx| {
+                cx.update_global::<SettingsStore, _>(|store, cx| {
+                    store.update_user_settings::<ProjectPanelSettings>(cx, |settings| {
+                        settings.folders_position = Some(position);
+                    });
+                })
             });
-            panel.cancel(&menu::Cancel, cx)
-        });
+            cx.run_until_parked();
+        };
 
-        panel.update(cx, |panel, cx| panel.new_directory(&NewDirectory, cx));
+        set_folders_position(FoldersPosition::Last, cx);
         assert_eq!(
             visible_entries_as_strings(&panel, 0..10, cx),
             &[
-                "v root1",
-                "    > .git",
-                "    > a",
-                "    v b",
-                "        > [EDITOR: '']  <== selected",
-                "        > 3",
-                "        > 4",
-                "          a-different-filename.tar.gz",
-                "    > C",
-                "      .dockerignore",
+                "v root",
+                "      a.rs",
+                "      d.rs",
+                "    v b  <== selected",
+                "          c.rs",
             ]
         );
 
-        let confirm = panel.update(cx, |panel, cx| {
-            panel
-                .filename_editor
-                .update(cx, |editor, cx| editor.set_text("new-dir", cx));
-            panel.confirm_edit(cx).unwrap()
-        });
-        panel.update(cx, |panel, cx| panel.select_next(&Default::default(), cx));
+        set_folders_position(FoldersPosition::Mixed, cx);
         assert_eq!(
             visible_entries_as_strings(&panel, 0..10, cx),
             &[
-                "v root1",
-                "    > .git",
-                "    > a",
-                "    v b",
-                "        > [PROCESSING: 'new-dir']",
-                "        > 3  <== selected",
-                "        > 4",
-                "          a-different-filename.tar.gz",
-                "    > C",
-                "      .dockerignore",
+                "v root",
+                "      a.rs",
+                "    v b  <== selected",
+                "          c.rs",
+                "      d.rs",
             ]
         );
 
-        confirm.await.unwrap();
+        set_folders_position(FoldersPosition::First, cx);
         assert_eq!(
             visible_entries_as_strings(&panel, 0..10, cx),
             &[
-                "v root1",
-                "    > .git",
-                "    > a",
-                "    v b",
-                "        > 3  <== selected",
-                "        > 4",
-                "        > new-dir",
-                "          a-different-filename.tar.gz",
-                "    > C",
-                "      .dockerignore",
+                "v root",
+                "    v b  <== selected",
+                "          c.rs",
+                "      a.rs",
+                "      d.rs",
             ]
         );
+    }
 
-        panel.update(cx, |panel, cx| panel.rename(&Default::default(), cx));
+    #[gpui::test]
+    async fn test_case_sensitive_sort(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "a.rs": "",
+                "B.rs": "",
+                "c.rs": "",
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &["v root", "      a.rs", "      B.rs", "      c.rs"]
+        );
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<ProjectPanelSettings>(cx, |settings| {
+                    settings.case_sensitive_sort = Some(true);
+                });
+            })
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &["v root", "      B.rs", "      a.rs", "      c.rs"],
+            "Uppercase names should come first when sorting byte-wise"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_refresh_picks_up_missed_changes(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree("/root", json!({ "a.txt": "", "dir": { "b.txt": "" } }))
+            .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
+        toggle_expand_dir(&panel, "root/dir", cx);
+
+        fs.pause_events();
+        fs.insert_file("/root/dir/c.txt", Vec::new()).await;
+        cx.run_until_parked();
+        assert!(find_project_entry(&panel, "root/dir/c.txt", cx).is_none());
+
+        panel.update(cx, |panel, cx| panel.refresh(&Refresh, cx));
+        cx.run_until_parked();
         assert_eq!(
             visible_entries_as_strings(&panel, 0..10, cx),
             &[
-                "v root1",
-                "    > .git",
-                "    > a",
-                "    v b",
-                "        > [EDITOR: '3']  <== selected",
-                "        > 4",
-                "        > new-dir",
-                "          a-different-filename.tar.gz",
-                "    > C",
-                "      .dockerignore",
+                "v root",
+                "    v dir  <== selected",
+                "          b.txt",
+                "          c.txt",
+                "      a.txt",
             ]
         );
+    }
+
+    #[gpui::test]
+    async fn test_diagnostic_summaries(cx: &mut gpui::TestAppContext) {
+        use language::{
+            Diagnostic, DiagnosticEntry, DiagnosticSeverity, LanguageServerId, PointUtf16,
+            Unclipped,
+        };
+
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({ "src": { "lib.rs": "", "main.rs": "" }, "README.md": "" }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
+
+        let diagnostic = |row, severity| DiagnosticEntry {
+            range: Unclipped(PointUtf16::new(row, 0))..Unclipped(PointUtf16::new(row, 1)),
+            diagnostic: Diagnostic {
+                severity,
+                is_primary: true,
+                group_id: row as usize,
+                ..Default::default()
+            },
+        };
+        project.update(cx, |project, cx| {
+            project
+                .update_diagnostic_entries(
+                    LanguageServerId(0),
+                    PathBuf::from("/root/src/main.rs"),
+                    None,
+                    vec![
+                        diagnostic(0, DiagnosticSeverity::ERROR),
+                        diagnostic(1, DiagnosticSeverity::WARNING),
+                    ],
+                    cx,
+                )
+                .unwrap();
+            project
+                .update_diagnostic_entries(
+                    LanguageServerId(0),
+                    PathBuf::from("/root/src/lib.rs"),
+                    None,
+                    vec![diagnostic(0, DiagnosticSeverity::WARNING)],
+                    cx,
+                )
+                .unwrap();
+        });
+        cx.executor().advance_clock(DIAGNOSTIC_SUMMARIES_THROTTLE);
+        cx.run_until_parked();
+
+        let summary = |path: &str, cx: &mut VisualTestContext| {
+            panel.update(cx, |panel, _| {
+                panel
+                    .diagnostic_summaries
+                    .values()
+                    .find_map(|summaries| summaries.get(Path::new(path)).copied())
+                    .map(|summary| (summary.error_count, summary.warning_count))
+            })
+        };
+        assert_eq!(summary("src/main.rs", cx), Some((1, 1)));
+        assert_eq!(summary("src/lib.rs", cx), Some((0, 1)));
+        assert_eq!(summary("src", cx), Some((1, 2)));
+        assert_eq!(summary("", cx), Some((1, 2)));
+        assert_eq!(summary("README.md", cx), None);
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<ProjectPanelSettings>(cx, |settings| {
+                    settings.show_diagnostics = Some(ShowDiagnostics::Errors);
+                });
+            })
+        });
+        cx.run_until_parked();
+        assert_eq!(summary("src/main.rs", cx), Some((1, 0)));
+        assert_eq!(summary("src/lib.rs", cx), None);
+        assert_eq!(summary("src", cx), Some((1, 0)));
+    }
+
+    #[gpui::test]
+    async fn test_dirty_entries(cx: &mut gpui::TestAppContext) {
+        init_test_with_editor(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree("/root", json!({ "a.txt": "alpha", "b.txt": "beta" }))
+            .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
+
+        let worktree_id =
+            cx.update(|cx| project.read(cx).worktrees().next().unwrap().read(cx).id());
+        let editor = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_path((worktree_id, "a.txt"), None, true, cx)
+            })
+            .unwrap()
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap();
+        cx.run_until_parked();
+        let a_txt = find_project_entry(&panel, "root/a.txt", cx).unwrap();
+        panel.update(cx, |panel, _| assert!(panel.dirty_entries.is_empty()));
+
+        editor.update(cx, |editor, cx| editor.set_text("changed", cx));
+        cx.run_until_parked();
+        panel.update(cx, |panel, _| {
+            assert_eq!(panel.dirty_entries, HashSet::from_iter([a_txt]));
+        });
+
+        let buffer = editor.update(cx, |editor, cx| {
+            editor.buffer().read(cx).as_singleton().unwrap()
+        });
+        project
+            .update(cx, |project, cx| project.save_buffer(buffer, cx))
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        panel.update(cx, |panel, _| assert!(panel.dirty_entries.is_empty()));
 
-        // Dismiss the rename editor when it loses focus.
-        workspace.update(cx, |_, cx| cx.blur()).unwrap();
-        assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &[
-                "v root1",
-                "    > .git",
-                "    > a",
-                "    v b",
-                "        > 3  <== selected",
-                "        > 4",
-                "        > new-dir",
-                "          a-different-filename.tar.gz",
-                "    > C",
-                "      .dockerignore",
-            ]
-        );
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/root/b.txt", cx)
+            })
+            .await
+            .unwrap();
+        buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "changed ")], None, cx));
+        cx.run_until_parked();
+        let b_txt = find_project_entry(&panel, "root/b.txt", cx).unwrap();
+        panel.update(cx, |panel, _| {
+            assert_eq!(
+                panel.dirty_entries,
+                HashSet::from_iter([b_txt]),
+                "Buffers opened without an editor should be watched too"
+            );
+        });
     }
 
-    #[gpui::test(iterations = 10)]
-    async fn test_adding_directories_via_file(cx: &mut gpui::TestAppContext) {
+    #[gpui::test]
+    async fn test_paste_across_worktrees(cx: &mut gpui::TestAppContext) {
         init_test(cx);
 
         let fs = FakeFs::new(cx.executor().clone());
         fs.insert_tree(
             "/root1",
             json!({
-                ".dockerignore": "",
-                ".git": {
-                    "HEAD": "",
-                },
-                "a": {
-                    "0": { "q": "", "r": "", "s": "" },
-                    "1": { "t": "", "u": "" },
-                    "2": { "v": "", "w": "", "x": "", "y": "" },
-                },
-                "b": {
-                    "3": { "Q": "" },
-                    "4": { "R": "", "S": "", "T": "", "U": "" },
-                },
-                "C": {
-                    "5": {},
-                    "6": { "V": "", "W": "" },
-                    "7": { "X": "" },
-                    "8": { "Y": {}, "Z": "" }
-                }
+                "a.txt": "alpha",
+                "b": { "c.txt": "gamma" },
             }),
         )
         .await;
+        fs.insert_tree("/root2", json!({ "d": {} })).await;
+
+        let project = Project::test(fs.clone(), ["/root1".as_ref(), "/root2".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
+
+        select_path(&panel, "root1/a.txt", cx);
+        panel.update(cx, |panel, cx| panel.copy(&Default::default(), cx));
+        select_path(&panel, "root2/d", cx);
+        panel.update(cx, |panel, cx| panel.paste(&Default::default(), cx));
+        cx.run_until_parked();
+        assert!(find_project_entry(&panel, "root1/a.txt", cx).is_some());
+        assert!(find_project_entry(&panel, "root2/d/a.txt", cx).is_some());
+        assert_eq!(fs.load("/root2/d/a.txt".as_ref()).await.unwrap(), "alpha");
+
+        toggle_expand_dir(&panel, "root1/b", cx);
+        select_path(&panel, "root1/b", cx);
+        panel.update(cx, |panel, cx| panel.cut(&Default::default(), cx));
+        select_path(&panel, "root2/d", cx);
+        panel.update(cx, |panel, cx| panel.paste(&Default::default(), cx));
+        cx.run_until_parked();
+        assert!(
+            find_project_entry(&panel, "root1/b", cx).is_none(),
+            "Cut entries should be removed from their worktree once pasted"
+        );
+        assert_eq!(fs.load("/root2/d/b/c.txt".as_ref()).await.unwrap(), "gamma");
+        assert!(find_project_entry(&panel, "root2/d/b/c.txt", cx).is_some());
+
+        panel.update(cx, |panel, cx| panel.undo(&Undo, cx));
+        cx.run_until_parked();
+        assert!(
+            find_project_entry(&panel, "root1/b/c.txt", cx).is_some(),
+            "Undoing a move between worktrees should move the entries back"
+        );
+        assert!(find_project_entry(&panel, "root2/d/b", cx).is_none());
+
+        panel.update(cx, |panel, cx| panel.undo(&Undo, cx));
+        cx.run_until_parked();
+        assert!(find_project_entry(&panel, "root2/d/a.txt", cx).is_none());
+        assert!(find_project_entry(&panel, "root1/a.txt", cx).is_some());
+    }
+
+    #[gpui::test]
+    async fn test_drag_multiple_entries(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
         fs.insert_tree(
-            "/root2",
+            "/root",
             json!({
-                "d": {
-                    "9": ""
-                },
-                "e": {}
+                "a": { "b.txt": "" },
+                "c.txt": "",
+                "d": {},
             }),
         )
         .await;
 
-        let project = Project::test(fs.clone(), ["/root1".as_ref(), "/root2".as_ref()], cx).await;
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
         let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
         let cx = &mut VisualTestContext::from_window(*workspace, cx);
         let panel = workspace
-            .update(cx, |workspace, cx| {
-                let panel = ProjectPanel::new(workspace, cx);
-                workspace.add_panel(panel.clone(), cx);
-                panel
-            })
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
             .unwrap();
+        cx.run_until_parked();
 
-        select_path(&panel, "root1", cx);
-        assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &[
-                "v root1  <== selected",
-                "    > .git",
-                "    > a",
-                "    > b",
-                "    > C",
-                "      .dockerignore",
-                "v root2",
-                "    > d",
-                "    > e",
-            ]
-        );
-
-        // Add a file with the root folder selected. The filename editor is placed
-        // before the first file in the root folder.
-        panel.update(cx, |panel, cx| panel.new_file(&NewFile, cx));
+        let worktree_id =
+            cx.update(|cx| project.read(cx).worktrees().next().unwrap().read(cx).id());
+        let selected_entry = |path: &str, cx: &mut VisualTestContext| SelectedEntry {
+            worktree_id,
+            entry_id: find_project_entry(&panel, path, cx).unwrap(),
+        };
+        let active_selection = selected_entry("root/a", cx);
+        let marked_selections = [
+            active_selection,
+            selected_entry("root/a/b.txt", cx),
+            selected_entry("root/c.txt", cx),
+        ];
+        let dragged_selection = DraggedSelection {
+            active_selection,
+            marked_selections: Arc::new(BTreeSet::from_iter(marked_selections)),
+        };
+        let target = find_project_entry(&panel, "root/d", cx).unwrap();
         panel.update(cx, |panel, cx| {
-            assert!(panel.filename_editor.read(cx).is_focused(cx));
-        });
-        assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &[
-                "v root1",
-                "    > .git",
-                "    > a",
-                "    > b",
-                "    > C",
-                "      [EDITOR: '']  <== selected",
-                "      .dockerignore",
-                "v root2",
-                "    > d",
-                "    > e",
-            ]
-        );
-
-        let confirm = panel.update(cx, |panel, cx| {
-            panel.filename_editor.update(cx, |editor, cx| {
-                editor.set_text("/bdir1/dir2/the-new-filename", cx)
-            });
-            panel.confirm_edit(cx).unwrap()
+            panel.drag_onto(&dragged_selection, target, false, cx)
         });
-
-        assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &[
-                "v root1",
-                "    > .git",
-                "    > a",
-                "    > b",
-                "    > C",
-                "      [PROCESSING: '/bdir1/dir2/the-new-filename']  <== selected",
-                "      .dockerignore",
-                "v root2",
-                "    > d",
-                "    > e",
-            ]
+        cx.run_until_parked();
+        assert!(
+            find_project_entry(&panel, "root/d/a/b.txt", cx).is_some(),
+            "Entries inside of dragged directories should move along with them"
         );
+        assert!(find_project_entry(&panel, "root/d/c.txt", cx).is_some());
+        assert!(find_project_entry(&panel, "root/a", cx).is_none());
 
-        confirm.await.unwrap();
-        assert_eq!(
-            visible_entries_as_strings(&panel, 0..13, cx),
-            &[
-                "v root1",
-                "    > .git",
-                "    > a",
-                "    > b",
-                "    v bdir1",
-                "        v dir2",
-                "              the-new-filename  <== selected  <== marked",
-                "    > C",
-                "      .dockerignore",
-                "v root2",
-                "    > d",
-                "    > e",
-            ]
+        panel.update(cx, |panel, cx| panel.undo(&Undo, cx));
+        cx.run_until_parked();
+        assert!(
+            find_project_entry(&panel, "root/a/b.txt", cx).is_some(),
+            "A single undo should move all the dragged entries back"
         );
+        assert!(find_project_entry(&panel, "root/c.txt", cx).is_some());
+        assert!(find_project_entry(&panel, "root/d/c.txt", cx).is_none());
     }
 
     #[gpui::test]
-    async fn test_adding_directory_via_file(cx: &mut gpui::TestAppContext) {
-        init_test(cx);
+    async fn test_undo_redo_file_operations(cx: &mut gpui::TestAppContext) {
+        init_test_with_editor(cx);
 
         let fs = FakeFs::new(cx.executor().clone());
         fs.insert_tree(
-            "/root1",
+            "/root",
             json!({
-                ".dockerignore": "",
-                ".git": {
-                    "HEAD": "",
-                },
+                "a.txt": "alpha",
+                "dir": { "b.txt": "beta" },
             }),
         )
         .await;
 
-        let project = Project::test(fs.clone(), ["/root1".as_ref()], cx).await;
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
         let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
         let cx = &mut VisualTestContext::from_window(*workspace, cx);
         let panel = workspace
-            .update(cx, |workspace, cx| {
-                let panel = ProjectPanel::new(workspace, cx);
-                workspace.add_panel(panel.clone(), cx);
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
+
+        select_path(&panel, "root/a.txt", cx);
+        panel.update(cx, |panel, cx| panel.rename(&Default::default(), cx));
+        panel
+            .update(cx, |panel, cx| {
                 panel
+                    .filename_editor
+                    .update(cx, |editor, cx| editor.set_text("c.txt", cx));
+                panel.confirm_edit(cx).unwrap()
             })
+            .await
             .unwrap();
+        cx.run_until_parked();
+        assert!(find_project_entry(&panel, "root/c.txt", cx).is_some());
 
-        select_path(&panel, "root1", cx);
-        assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &["v root1  <== selected", "    > .git", "      .dockerignore",]
+        panel.update(cx, |panel, cx| panel.undo(&Undo, cx));
+        cx.run_until_parked();
+        assert!(find_project_entry(&panel, "root/a.txt", cx).is_some());
+        assert!(find_project_entry(&panel, "root/c.txt", cx).is_none());
+
+        panel.update(cx, |panel, cx| panel.redo(&Redo, cx));
+        cx.run_until_parked();
+        assert!(find_project_entry(&panel, "root/c.txt", cx).is_some());
+        assert!(find_project_entry(&panel, "root/a.txt", cx).is_none());
+
+        fs.remove_file("/root/c.txt".as_ref(), Default::default())
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        panel.update(cx, |panel, cx| panel.undo(&Undo, cx));
+        cx.run_until_parked();
+        assert!(find_project_entry(&panel, "root/a.txt", cx).is_none());
+        fs.insert_file("/root/c.txt", b"gamma".to_vec()).await;
+        cx.run_until_parked();
+        panel.update(cx, |panel, cx| panel.undo(&Undo, cx));
+        cx.run_until_parked();
+        assert!(
+            find_project_entry(&panel, "root/a.txt", cx).is_some(),
+            "A change that failed to be undone should stay undoable"
         );
 
-        // Add a file with the root folder selected. The filename editor is placed
-        // before the first file in the root folder.
-        panel.update(cx, |panel, cx| panel.new_file(&NewFile, cx));
+        select_path(&panel, "root/dir", cx);
         panel.update(cx, |panel, cx| {
-            assert!(panel.filename_editor.read(cx).is_focused(cx));
+            panel.trash(&Trash { skip_prompt: true }, cx)
         });
-        assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &[
-                "v root1",
-                "    > .git",
-                "      [EDITOR: '']  <== selected",
-                "      .dockerignore",
-            ]
+        cx.run_until_parked();
+        assert!(find_project_entry(&panel, "root/dir", cx).is_none());
+
+        panel.update(cx, |panel, cx| panel.redo(&Redo, cx));
+        cx.run_until_parked();
+        assert!(
+            find_project_entry(&panel, "root/c.txt", cx).is_some(),
+            "Trashing should go to the system trash without being recorded as a change"
         );
+        assert!(find_project_entry(&panel, "root/dir", cx).is_none());
+    }
 
-        let confirm = panel.update(cx, |panel, cx| {
-            panel
-                .filename_editor
-                .update(cx, |editor, cx| editor.set_text("/new_dir/", cx));
-            panel.confirm_edit(cx).unwrap()
-        });
+    #[gpui::test]
+    async fn test_restore_position(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "a": { "b": { "c.rs": "" } },
+                "d.rs": "",
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
 
+        panel.update(cx, |panel, cx| {
+            panel.restored_position = Some(SerializedPosition {
+                worktree_abs_path: PathBuf::from("/root"),
+                selected_path: PathBuf::from("a/b/c.rs"),
+                scroll_offset: px(0.),
+            });
+            panel.restore_position(cx);
+            assert!(panel.restored_position.is_none());
+        });
         assert_eq!(
             visible_entries_as_strings(&panel, 0..10, cx),
             &[
-                "v root1",
-                "    > .git",
-                "      [PROCESSING: '/new_dir/']  <== selected",
-                "      .dockerignore",
+                "v root",
+                "    v a",
+                "        v b",
+                "              c.rs  <== selected",
+                "      d.rs",
             ]
         );
+        let position = panel.update(cx, |panel, cx| panel.serialized_position(cx).unwrap());
+        assert_eq!(position.worktree_abs_path, PathBuf::from("/root"));
+        assert_eq!(position.selected_path, PathBuf::from("a/b/c.rs"));
 
-        confirm.await.unwrap();
+        panel.update(cx, |panel, cx| {
+            panel.restored_position = Some(SerializedPosition {
+                worktree_abs_path: PathBuf::from("/elsewhere"),
+                selected_path: PathBuf::from("d.rs"),
+                scroll_offset: px(0.),
+            });
+            panel.restore_position(cx);
+            assert!(
+                panel.restored_position.is_none(),
+                "Positions in worktrees that are not open should be dropped once scanned"
+            );
+        });
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..13, cx),
-            &[
-                "v root1",
-                "    > .git",
-                "    v new_dir  <== selected",
-                "      .dockerignore",
-            ]
+            visible_entries_as_strings(&panel, 3..4, cx),
+            &["              c.rs  <== selected"]
         );
     }
 
     #[gpui::test]
-    async fn test_copy_paste(cx: &mut gpui::TestAppContext) {
+    async fn test_tag_filter(cx: &mut gpui::TestAppContext) {
         init_test(cx);
 
         let fs = FakeFs::new(cx.executor().clone());
         fs.insert_tree(
-            "/root1",
+            "/project_root",
             json!({
-                "one.two.txt": "",
-                "one.txt": ""
+                "api": { "v1": { "users.rs": "", "orders.rs": "" }, "v2": { "users.rs": "" } },
+                "docs": { "guide.md": "" },
+                "README.md": "",
             }),
         )
         .await;
 
-        let project = Project::test(fs.clone(), ["/root1".as_ref()], cx).await;
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
         let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
         let cx = &mut VisualTestContext::from_window(*workspace, cx);
         let panel = workspace
             .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
             .unwrap();
-
-        panel.update(cx, |panel, cx| {
-            panel.select_next(&Default::default(), cx);
-            panel.select_next(&Default::default(), cx);
-        });
+        cx.run_until_parked();
 
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..50, cx),
-            &[
-                //
-                "v root1",
-                "      one.two.txt  <== selected",
-                "      one.txt",
-            ]
+            entry_annotations::parse_tags("#deprecated, needs-review  deprecated"),
+            vec!["deprecated".to_string(), "needs-review".to_string()]
         );
-
-        // Regression test - file name is created correctly when
-        // the copied file's name contains multiple dots.
         panel.update(cx, |panel, cx| {
-            panel.copy(&Default::default(), cx);
-            panel.paste(&Default::default(), cx);
+            let root = Path::new("/project_root");
+            panel.entry_tags.set(
+                root,
+                Path::new("api/v1"),
+                Some(vec!["deprecated".to_string()]),
+            );
+            panel.entry_tags.set(
+                root,
+                Path::new("docs/guide.md"),
+                Some(vec!["needs-review".to_string(), "deprecated".to_string()]),
+            );
+            assert_eq!(panel.project_tags(cx), vec!["deprecated", "needs-review"]);
+            panel.set_tag_filter(Some("deprecated".to_string()), cx);
         });
-        cx.executor().run_until_parked();
-
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..50, cx),
+            visible_entries_as_strings(&panel, 0..20, cx),
             &[
-                //
-                "v root1",
-                "      one.two copy.txt",
-                "      one.two.txt  <== selected",
-                "      one.txt",
+                "v project_root",
+                "    v api",
+                "        > v1",
+                "    v docs",
+                "          guide.md",
             ]
         );
 
         panel.update(cx, |panel, cx| {
-            panel.paste(&Default::default(), cx);
+            panel.set_tag_filter(Some("needs-review".to_string()), cx)
         });
-        cx.executor().run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..20, cx),
+            &["v project_root", "    v docs", "          guide.md"]
+        );
 
+        panel.update(cx, |panel, cx| panel.set_tag_filter(None, cx));
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..50, cx),
+            visible_entries_as_strings(&panel, 0..20, cx),
             &[
-                //
-                "v root1",
-                "      one.two copy 1.txt",
-                "      one.two copy.txt",
-                "      one.two.txt  <== selected",
-                "      one.txt",
+                "v project_root",
+                "    v api",
+                "        > v1",
+                "        > v2",
+                "    v docs",
+                "          guide.md",
+                "      README.md",
             ]
         );
     }
 
     #[gpui::test]
-    async fn test_copy_paste_directory(cx: &mut gpui::TestAppContext) {
+    async fn test_export_and_import_panel_configuration(cx: &mut gpui::TestAppContext) {
         init_test(cx);
 
         let fs = FakeFs::new(cx.executor().clone());
         fs.insert_tree(
-            "/root",
+            "/shared_setup",
             json!({
-                "a": {
-                    "one.txt": "",
-                    "two.txt": "",
-                    "inner_dir": {
-                        "three.txt": "",
-                        "four.txt": "",
-                    }
-                },
-                "b": {}
+                "src": { "legacy.rs": "", "main.rs": "" },
             }),
         )
         .await;
 
-        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let project = Project::test(fs.clone(), ["/shared_setup".as_ref()], cx).await;
         let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
         let cx = &mut VisualTestContext::from_window(*workspace, cx);
         let panel = workspace
             .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
             .unwrap();
+        cx.run_until_parked();
 
-        select_path(&panel, "root/a", cx);
+        let root = Path::new("/shared_setup");
+        panel.update(cx, |panel, _| {
+            panel.entry_notes.set(
+                root,
+                Path::new("src/legacy.rs"),
+                Some("Remove after the migration".to_string()),
+            );
+            panel.entry_tags.set(
+                root,
+                Path::new("src"),
+                Some(vec!["needs-review".to_string()]),
+            );
+        });
+        select_path(&panel, "shared_setup", cx);
         panel.update(cx, |panel, cx| {
-            panel.copy(&Default::default(), cx);
-            panel.select_next(&Default::default(), cx);
-            panel.paste(&Default::default(), cx);
+            panel.export_panel_configuration(&ExportPanelConfiguration, cx)
         });
-        cx.executor().run_until_parked();
-
-        let pasted_dir = find_project_entry(&panel, "root/b/a", cx);
-        assert_ne!(pasted_dir, None, "Pasted directory should have an entry");
+        cx.run_until_parked();
 
-        let pasted_dir_file = find_project_entry(&panel, "root/b/a/one.txt", cx);
-        assert_ne!(
-            pasted_dir_file, None,
-            "Pasted directory file should have an entry"
+        let text = fs.load(&root.join(PANEL_CONFIGURATION_PATH)).await.unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&text).unwrap(),
+            json!({
+                "notes": { "src/legacy.rs": "Remove after the migration" },
+                "tags": { "src": ["needs-review"] },
+            })
         );
 
-        let pasted_dir_inner_dir = find_project_entry(&panel, "root/b/a/inner_dir", cx);
-        assert_ne!(
-            pasted_dir_inner_dir, None,
-            "Directories inside pasted directory should have an entry"
-        );
+        panel.update(cx, |panel, _| {
+            panel.entry_notes = EntryNotes::default();
+            panel.entry_tags = EntryTags::default();
+        });
+        panel.update(cx, |panel, cx| {
+            panel.import_panel_configuration(&ImportPanelConfiguration, cx)
+        });
+        cx.run_until_parked();
+        panel.update(cx, |panel, cx| {
+            let worktree_id = panel
+                .project
+                .read(cx)
+                .worktrees()
+                .next()
+                .unwrap()
+                .read(cx)
+                .id();
+            assert_eq!(
+                panel.note_for_entry(worktree_id, Path::new("src/legacy.rs"), cx),
+                Some("Remove after the migration")
+            );
+            assert_eq!(
+                panel.tags_for_entry(worktree_id, Path::new("src"), cx),
+                &["needs-review".to_string()]
+            );
+        });
+    }
 
-        toggle_expand_dir(&panel, "root/b/a", cx);
-        toggle_expand_dir(&panel, "root/b/a/inner_dir", cx);
+    #[gpui::test]
+    async fn test_expand_frequently_used(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/project_root",
+            json!({
+                "api": { "routes": { "users.rs": "" }, "models": { "user.rs": "" } },
+                "docs": { "guide.md": "" },
+                "frontend": { "app.ts": "" },
+            }),
+        )
+        .await;
 
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
+        toggle_expand_dir(&panel, "project_root/docs", cx);
+
+        let root = Path::new("/project_root");
+        let mut usage = DirectoryUsage::default();
+        usage.record(root, Path::new("api/routes"));
+        usage.record(root, Path::new("frontend"));
+        usage.record(root, Path::new("api/routes"));
+        usage.record(root, Path::new("deleted"));
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..50, cx),
-            &[
-                //
-                "v root",
-                "    > a",
-                "    v b",
-                "        v a",
-                "            v inner_dir  <== selected",
-                "                  four.txt",
-                "                  three.txt",
-                "              one.txt",
-                "              two.txt",
-            ]
+            usage.most_used(root, 2),
+            vec![Path::new("api/routes"), Path::new("deleted")]
         );
+        assert!(usage.most_used(Path::new("/other_root"), 2).is_empty());
 
-        select_path(&panel, "root", cx);
-        panel.update(cx, |panel, cx| panel.paste(&Default::default(), cx));
-        cx.executor().run_until_parked();
-        panel.update(cx, |panel, cx| panel.paste(&Default::default(), cx));
-        cx.executor().run_until_parked();
+        panel.update(cx, |panel, cx| {
+            panel.expand_directories_by_usage(&usage, cx)
+        });
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..50, cx),
+            visible_entries_as_strings(&panel, 0..20, cx),
             &[
-                //
-                "v root  <== selected",
-                "    > a",
-                "    > a copy",
-                "    > a copy 1",
-                "    v b",
-                "        v a",
-                "            v inner_dir",
-                "                  four.txt",
-                "                  three.txt",
-                "              one.txt",
-                "              two.txt"
+                "v project_root",
+                "    v api",
+                "        > models",
+                "        v routes",
+                "              users.rs",
+                "    > docs",
+                "    v frontend",
+                "          app.ts",
             ]
         );
     }
 
     #[gpui::test]
-    async fn test_remove_opened_file(cx: &mut gpui::TestAppContext) {
-        init_test_with_editor(cx);
+    async fn test_follow_host_tree_view(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
 
         let fs = FakeFs::new(cx.executor().clone());
         fs.insert_tree(
-            "/src",
+            "/project_root",
             json!({
-                "test": {
-                    "first.rs": "// First Rust file",
-                    "second.rs": "// Second Rust file",
-                    "third.rs": "// Third Rust file",
-                }
+                "api": { "routes": { "users.rs": "" } },
+                "frontend": { "app.ts": "" },
             }),
         )
         .await;
 
-        let project = Project::test(fs.clone(), ["/src".as_ref()], cx).await;
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
         let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
         let cx = &mut VisualTestContext::from_window(*workspace, cx);
         let panel = workspace
             .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
             .unwrap();
+        cx.run_until_parked();
 
-        toggle_expand_dir(&panel, "src/test", cx);
-        select_path(&panel, "src/test/first.rs", cx);
-        panel.update(cx, |panel, cx| panel.open(&Open, cx));
-        cx.executor().run_until_parked();
+        // Capture a tree view the way the host would share it.
+        toggle_expand_dir(&panel, "project_root/api", cx);
+        toggle_expand_dir(&panel, "project_root/api/routes", cx);
+        select_path(&panel, "project_root/api/routes/users.rs", cx);
+        let host_tree_view = panel.update(cx, |panel, _| panel.current_tree_view());
+        let host_layout = &[
+            "v project_root",
+            "    v api",
+            "        v routes",
+            "              users.rs  <== selected",
+            "    > frontend",
+        ];
+        assert_eq!(visible_entries_as_strings(&panel, 0..10, cx), host_layout);
+
+        toggle_expand_dir(&panel, "project_root/api", cx);
+        select_path(&panel, "project_root/frontend", cx);
+        project.update(cx, |_, cx| {
+            cx.emit(project::Event::HostTreeViewUpdated(Some(
+                host_tree_view.clone(),
+            )))
+        });
+        cx.run_until_parked();
         assert_eq!(
             visible_entries_as_strings(&panel, 0..10, cx),
-            &[
-                "v src",
-                "    v test",
-                "          first.rs  <== selected",
-                "          second.rs",
-                "          third.rs"
-            ]
+            host_layout,
+            "Guests should follow the host's tree view by default"
         );
-        ensure_single_file_is_opened(&workspace, "test/first.rs", cx);
 
-        submit_deletion(&panel, cx);
+        panel.update(cx, |panel, cx| {
+            panel.toggle_follow_host_tree_view(&ToggleFollowHostTreeView, cx)
+        });
+        select_path(&panel, "project_root/frontend", cx);
+        project.update(cx, |_, cx| {
+            cx.emit(project::Event::HostTreeViewUpdated(Some(host_tree_view)))
+        });
+        cx.run_until_parked();
         assert_eq!(
             visible_entries_as_strings(&panel, 0..10, cx),
             &[
-                "v src",
-                "    v test",
-                "          second.rs",
-                "          third.rs"
+                "v project_root",
+                "    v api",
+                "        v routes",
+                "              users.rs",
+                "    > frontend  <== selected",
             ],
-            "Project panel should have no deleted file, no other file is selected in it"
+            "Updates should not be applied after unfollowing"
         );
-        ensure_no_open_items_and_panes(&workspace, cx);
 
-        select_path(&panel, "src/test/second.rs", cx);
-        panel.update(cx, |panel, cx| panel.open(&Open, cx));
-        cx.executor().run_until_parked();
-        assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &[
-                "v src",
-                "    v test",
-                "          second.rs  <== selected",
-                "          third.rs"
-            ]
-        );
-        ensure_single_file_is_opened(&workspace, "test/second.rs", cx);
+        project.update(cx, |_, cx| {
+            cx.emit(project::Event::HostTreeViewUpdated(None))
+        });
+        cx.run_until_parked();
+        panel.update(cx, |panel, cx| {
+            assert!(panel.host_tree_view.is_none());
+            assert!(panel.render_tree_view_sharing_header(cx).is_none());
+        });
+    }
+
+    #[gpui::test]
+    async fn test_decoration_timeouts(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "CODEOWNERS": "* @everyone",
+                "file.txt": "",
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
+        render_rows(&panel, 0..3, cx);
+        let worktree_id = project.update(cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        });
+        let has_code_owners = |cx: &mut VisualTestContext| {
+            panel.update(cx, |panel, _| {
+                panel
+                    .decorations
+                    .get::<CodeOwnerDecoration>()
+                    .owners_for_path(worktree_id, Path::new("file.txt"))
+                    .is_some()
+            })
+        };
+        let decoration = Decoration::Row("code owner");
+        assert!(has_code_owners(cx));
+
+        for failures in 1..=MAX_DECORATION_FAILURES {
+            panel.update(cx, |panel, cx| {
+                let ix = panel.decorations.position(decoration.name()).unwrap();
+                let task = panel.spawn_decoration_task(
+                    decoration,
+                    None,
+                    smol::future::pending::<Result<()>>(),
+                    |_, _, _| {},
+                    cx,
+                );
+                panel.decorations.slot_mut(ix).task = Some(task);
+            });
+            cx.executor().advance_clock(DECORATION_TIMEOUT);
+            cx.run_until_parked();
+            panel.update(cx, |panel, _| {
+                assert_eq!(panel.decoration_failures[&decoration], failures);
+            });
+            assert_eq!(!has_code_owners(cx), failures == MAX_DECORATION_FAILURES);
+        }
+
+        // Once turned off, the decoration is no longer computed.
+        panel.update(cx, |panel, cx| {
+            panel.invalidate_row_decorations(worktree_id, &[Path::new("CODEOWNERS")]);
+            panel.set_rendered_range(0..3, cx);
+            let ix = panel.decorations.position(decoration.name()).unwrap();
+            assert!(panel.decorations.slot(ix).task.is_none());
+        });
+        cx.run_until_parked();
+        assert!(!has_code_owners(cx));
+    }
+
+    #[gpui::test]
+    async fn test_open_to_the_side_keep_focus(cx: &mut gpui::TestAppContext) {
+        init_test_with_editor(cx);
 
-        workspace
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "a.txt": "a",
+                "b.txt": "b",
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
             .update(cx, |workspace, cx| {
-                let active_items = workspace
-                    .panes()
-                    .iter()
-                    .filter_map(|pane| pane.read(cx).active_item())
-                    .collect::<Vec<_>>();
-                assert_eq!(active_items.len(), 1);
-                let open_editor = active_items
-                    .into_iter()
-                    .next()
-                    .unwrap()
-                    .downcast::<Editor>()
-                    .expect("Open item should be an editor");
-                open_editor.update(cx, |editor, cx| editor.set_text("Another text!", cx));
+                let panel = ProjectPanel::new(workspace, cx);
+                workspace.add_panel(panel.clone(), cx);
+                panel
             })
             .unwrap();
-        submit_deletion_skipping_prompt(&panel, cx);
-        assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &["v src", "    v test", "          third.rs"],
-            "Project panel should have no deleted file, with one last file remaining"
-        );
-        ensure_no_open_items_and_panes(&workspace, cx);
+        let center_pane = workspace
+            .update(cx, |workspace, _| workspace.active_pane().clone())
+            .unwrap();
+        panel.update(cx, |panel, cx| panel.focus_handle.focus(cx));
+
+        for path in ["a.txt", "b.txt"] {
+            select_path(&panel, format!("root/{path}"), cx);
+            panel.update(cx, |panel, cx| {
+                panel.open_to_the_side_keep_focus(&OpenToTheSideKeepFocus, cx)
+            });
+            cx.run_until_parked();
+
+            workspace
+                .update(cx, |workspace, cx| {
+                    assert_eq!(workspace.panes().len(), 2);
+                    assert_eq!(workspace.active_pane(), &center_pane);
+                    assert!(center_pane.read(cx).active_item().is_none());
+                    let side_pane = workspace
+                        .panes()
+                        .iter()
+                        .find(|pane| **pane != center_pane)
+                        .unwrap();
+                    let opened_path = side_pane
+                        .read(cx)
+                        .active_item()
+                        .and_then(|item| item.project_path(cx))
+                        .unwrap();
+                    assert_eq!(opened_path.path.as_ref(), Path::new(path));
+                })
+                .unwrap();
+            panel.update(cx, |panel, cx| assert!(panel.focus_handle.is_focused(cx)));
+        }
     }
 
     #[gpui::test]
-    async fn test_create_duplicate_items(cx: &mut gpui::TestAppContext) {
+    async fn test_close_editors_for_file(cx: &mut gpui::TestAppContext) {
         init_test_with_editor(cx);
 
         let fs = FakeFs::new(cx.executor().clone());
         fs.insert_tree(
-            "/src",
+            "/root",
             json!({
-                "test": {
-                    "first.rs": "// First Rust file",
-                    "second.rs": "// Second Rust file",
-                    "third.rs": "// Third Rust file",
-                }
+                "a.txt": "a",
+                "b.txt": "b",
             }),
         )
         .await;
 
-        let project = Project::test(fs.clone(), ["/src".as_ref()], cx).await;
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
         let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
         let cx = &mut VisualTestContext::from_window(*workspace, cx);
         let panel = workspace
@@ -3818,1123 +14591,1404 @@ mod tests {
             })
             .unwrap();
 
-        select_path(&panel, "src/", cx);
-        panel.update(cx, |panel, cx| panel.confirm(&Confirm, cx));
-        cx.executor().run_until_parked();
-        assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &[
-                //
-                "v src  <== selected",
-                "    > test"
-            ]
-        );
-        panel.update(cx, |panel, cx| panel.new_directory(&NewDirectory, cx));
+        for path in ["root/a.txt", "root/b.txt"] {
+            let entry_id = find_project_entry(&panel, path, cx).unwrap();
+            panel.update(cx, |panel, cx| {
+                panel.open_entry(entry_id, false, false, false, cx)
+            });
+            cx.run_until_parked();
+        }
+        let a_id = find_project_entry(&panel, "root/a.txt", cx).unwrap();
         panel.update(cx, |panel, cx| {
-            assert!(panel.filename_editor.read(cx).is_focused(cx));
+            assert_eq!(panel.open_items_for_entry(a_id, cx).len(), 1);
         });
-        assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &[
-                //
-                "v src",
-                "    > [EDITOR: '']  <== selected",
-                "    > test"
-            ]
-        );
+
+        select_path(&panel, "root/a.txt", cx);
         panel.update(cx, |panel, cx| {
-            panel
-                .filename_editor
-                .update(cx, |editor, cx| editor.set_text("test", cx));
-            assert!(
-                panel.confirm_edit(cx).is_none(),
-                "Should not allow to confirm on conflicting new directory name"
-            )
+            panel.close_editors_for_file(&CloseEditorsForFile, cx)
         });
-        assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &[
-                //
-                "v src",
-                "    > test"
-            ],
-            "File list should be unchanged after failed folder create confirmation"
-        );
-
-        select_path(&panel, "src/test/", cx);
-        panel.update(cx, |panel, cx| panel.confirm(&Confirm, cx));
-        cx.executor().run_until_parked();
-        assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &[
-                //
-                "v src",
-                "    > test  <== selected"
-            ]
-        );
-        panel.update(cx, |panel, cx| panel.new_file(&NewFile, cx));
+        cx.run_until_parked();
+        workspace
+            .update(cx, |workspace, cx| {
+                let open_paths = workspace
+                    .active_pane()
+                    .read(cx)
+                    .items()
+                    .filter_map(|item| item.project_path(cx))
+                    .map(|path| path.path)
+                    .collect::<Vec<_>>();
+                assert_eq!(open_paths, [Arc::from(Path::new("b.txt"))]);
+            })
+            .unwrap();
         panel.update(cx, |panel, cx| {
-            assert!(panel.filename_editor.read(cx).is_focused(cx));
+            assert!(panel.open_items_for_entry(a_id, cx).is_empty());
         });
-        assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &[
-                "v src",
-                "    v test",
-                "          [EDITOR: '']  <== selected",
-                "          first.rs",
-                "          second.rs",
-                "          third.rs"
-            ]
-        );
+    }
+
+    #[gpui::test]
+    async fn test_remove_duplicate_worktree(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "sub": {
+                    "file.txt": "",
+                },
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root/sub".as_ref(), "/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
         panel.update(cx, |panel, cx| {
-            panel
-                .filename_editor
-                .update(cx, |editor, cx| editor.set_text("first.rs", cx));
-            assert!(
-                panel.confirm_edit(cx).is_none(),
-                "Should not allow to confirm on conflicting new file name"
-            )
+            let (worktree, duplicate) = panel.duplicate_worktree(cx).unwrap();
+            assert_eq!(worktree.read(cx).root_name(), "root");
+            assert_eq!(duplicate.read(cx).root_name(), "sub");
+            assert!(panel.render_duplicate_worktree_header(cx).is_some());
         });
-        assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &[
-                "v src",
-                "    v test",
-                "          first.rs",
-                "          second.rs",
-                "          third.rs"
-            ],
-            "File list should be unchanged after failed file create confirmation"
-        );
 
-        select_path(&panel, "src/test/first.rs", cx);
-        panel.update(cx, |panel, cx| panel.confirm(&Confirm, cx));
-        cx.executor().run_until_parked();
-        assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &[
-                "v src",
-                "    v test",
-                "          first.rs  <== selected",
-                "          second.rs",
-                "          third.rs"
-            ],
-        );
-        panel.update(cx, |panel, cx| panel.rename(&Rename, cx));
         panel.update(cx, |panel, cx| {
-            assert!(panel.filename_editor.read(cx).is_focused(cx));
+            panel.remove_duplicate_worktree(&RemoveDuplicateWorktree, cx)
         });
+        cx.run_until_parked();
         assert_eq!(
             visible_entries_as_strings(&panel, 0..10, cx),
-            &[
-                "v src",
-                "    v test",
-                "          [EDITOR: 'first.rs']  <== selected",
-                "          second.rs",
-                "          third.rs"
-            ]
+            &["v root", "    > sub"]
         );
         panel.update(cx, |panel, cx| {
-            panel
-                .filename_editor
-                .update(cx, |editor, cx| editor.set_text("second.rs", cx));
-            assert!(
-                panel.confirm_edit(cx).is_none(),
-                "Should not allow to confirm on conflicting file rename"
-            )
+            assert!(panel.duplicate_worktree(cx).is_none());
+            assert!(panel.render_duplicate_worktree_header(cx).is_none());
         });
-        assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &[
-                "v src",
-                "    v test",
-                "          first.rs  <== selected",
-                "          second.rs",
-                "          third.rs"
-            ],
-            "File list should be unchanged after failed rename confirmation"
-        );
     }
 
     #[gpui::test]
-    async fn test_dir_toggle_collapse(cx: &mut gpui::TestAppContext) {
-        init_test_with_editor(cx);
+    async fn test_unmounted_worktree_placeholder(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
 
         let fs = FakeFs::new(cx.executor().clone());
-        fs.insert_tree(
-            "/project_root",
-            json!({
-                "dir_1": {
-                    "nested_dir": {
-                        "file_a.py": "# File contents",
-                    }
-                },
-                "file_1.py": "# File contents",
-            }),
-        )
-        .await;
+        fs.insert_tree("/root", json!({ "a.txt": "" })).await;
+        fs.insert_tree("/drive", json!({ "dir": { "b.txt": "" }, "c.txt": "" }))
+            .await;
 
-        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        let project = Project::test(fs.clone(), ["/root".as_ref(), "/drive".as_ref()], cx).await;
         let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
         let cx = &mut VisualTestContext::from_window(*workspace, cx);
         let panel = workspace
             .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
             .unwrap();
+        toggle_expand_dir(&panel, "drive/dir", cx);
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root",
+                "      a.txt",
+                "v drive",
+                "    v dir  <== selected",
+                "          b.txt",
+                "      c.txt",
+            ]
+        );
+
+        fs.remove_dir(
+            "/drive".as_ref(),
+            RemoveOptions {
+                recursive: true,
+                ignore_if_not_exists: false,
+            },
+        )
+        .await
+        .unwrap();
+        cx.executor().advance_clock(ROOT_CHECK_INTERVAL);
+        cx.run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &["v root", "      a.txt"]
+        );
+        panel.update(cx, |panel, cx| {
+            assert_eq!(panel.unmounted_worktrees.len(), 1);
+            assert_eq!(panel.unmounted_worktrees[0].root_name.as_ref(), "drive");
+            assert!(panel.render_unmounted_worktrees(cx).is_some());
+        });
 
-        panel.update(cx, |panel, cx| panel.open(&Open, cx));
-        cx.executor().run_until_parked();
-        select_path(&panel, "project_root/dir_1", cx);
-        panel.update(cx, |panel, cx| panel.open(&Open, cx));
-        select_path(&panel, "project_root/dir_1/nested_dir", cx);
-        panel.update(cx, |panel, cx| panel.open(&Open, cx));
-        panel.update(cx, |panel, cx| panel.open(&Open, cx));
-        cx.executor().run_until_parked();
+        fs.insert_tree("/drive", json!({ "dir": { "b.txt": "" }, "c.txt": "" }))
+            .await;
+        cx.executor().advance_clock(ROOT_CHECK_INTERVAL);
+        cx.run_until_parked();
         assert_eq!(
             visible_entries_as_strings(&panel, 0..10, cx),
             &[
-                "v project_root",
-                "    v dir_1",
-                "        > nested_dir  <== selected",
-                "      file_1.py",
+                "v root",
+                "      a.txt",
+                "v drive",
+                "    v dir",
+                "          b.txt",
+                "      c.txt",
             ]
         );
+        panel.update(cx, |panel, cx| {
+            assert!(panel.unmounted_worktrees.is_empty());
+            assert!(panel.render_unmounted_worktrees(cx).is_none());
+        });
     }
 
     #[gpui::test]
-    async fn test_collapse_all_entries(cx: &mut gpui::TestAppContext) {
-        init_test_with_editor(cx);
+    async fn test_jump_to_visible_entry(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
 
         let fs = FakeFs::new(cx.executor().clone());
         fs.insert_tree(
-            "/project_root",
+            "/root",
             json!({
-                "dir_1": {
-                    "nested_dir": {
-                        "file_a.py": "# File contents",
-                        "file_b.py": "# File contents",
-                        "file_c.py": "# File contents",
-                    },
-                    "file_1.py": "# File contents",
-                    "file_2.py": "# File contents",
-                    "file_3.py": "# File contents",
+                "dir1": {
+                    "a.txt": "",
+                    "nested": { "target.txt": "" },
                 },
-                "dir_2": {
-                    "file_1.py": "# File contents",
-                    "file_2.py": "# File contents",
-                    "file_3.py": "# File contents",
-                }
+                "dir2": { "target.txt": "" },
             }),
         )
         .await;
-
-        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
         let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
         let cx = &mut VisualTestContext::from_window(*workspace, cx);
         let panel = workspace
             .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
             .unwrap();
+        cx.run_until_parked();
+        toggle_expand_dir(&panel, "root/dir1", cx);
 
         panel.update(cx, |panel, cx| {
-            panel.collapse_all_entries(&CollapseAllEntries, cx)
+            panel.jump_to_visible_entry(&JumpToVisibleEntry, cx)
         });
-        cx.executor().run_until_parked();
-        assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &["v project_root", "    > dir_1", "    > dir_2",]
-        );
+        let picker = panel.update(cx, |panel, _| {
+            panel.visible_entry_picker.as_ref().unwrap().0.clone()
+        });
+        cx.run_until_parked();
+        picker.update(cx, |picker, _| assert_eq!(picker.delegate.match_count(), 5));
 
-        // Open dir_1 and make sure nested_dir was collapsed when running collapse_all_entries
-        toggle_expand_dir(&panel, "project_root/dir_1", cx);
-        cx.executor().run_until_parked();
+        picker.update(cx, |picker, cx| picker.update_matches("target".into(), cx));
+        cx.run_until_parked();
+        picker.update(cx, |picker, _| assert_eq!(picker.delegate.match_count(), 0));
+
+        picker.update(cx, |picker, cx| picker.update_matches("dir2".into(), cx));
+        cx.run_until_parked();
+        picker.update(cx, |picker, cx| picker.delegate.confirm(false, cx));
+        cx.run_until_parked();
         assert_eq!(
             visible_entries_as_strings(&panel, 0..10, cx),
             &[
-                "v project_root",
-                "    v dir_1  <== selected",
-                "        > nested_dir",
-                "          file_1.py",
-                "          file_2.py",
-                "          file_3.py",
-                "    > dir_2",
+                "v root",
+                "    v dir1",
+                "        > nested",
+                "          a.txt",
+                "    > dir2  <== selected",
             ]
         );
+        panel.update(cx, |panel, _| assert!(panel.visible_entry_picker.is_none()));
     }
 
     #[gpui::test]
-    async fn test_new_file_move(cx: &mut gpui::TestAppContext) {
+    async fn test_git_status_colors(cx: &mut gpui::TestAppContext) {
         init_test(cx);
 
         let fs = FakeFs::new(cx.executor().clone());
-        fs.as_fake().insert_tree("/root", json!({})).await;
-        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+        insert_git_status_tree(&fs).await;
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
         let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
         let cx = &mut VisualTestContext::from_window(*workspace, cx);
         let panel = workspace
             .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
             .unwrap();
+        cx.run_until_parked();
 
-        // Make a new buffer with no backing file
-        workspace
-            .update(cx, |workspace, cx| {
-                Editor::new_file(workspace, &Default::default(), cx)
-            })
-            .unwrap();
-
-        cx.executor().run_until_parked();
-
-        // "Save as" the buffer, creating a new backing file for it
-        let save_task = workspace
-            .update(cx, |workspace, cx| {
-                workspace.save_active_item(workspace::SaveIntent::Save, cx)
-            })
-            .unwrap();
-
-        cx.executor().run_until_parked();
-        cx.simulate_new_path_selection(|_| Some(PathBuf::from("/root/new")));
-        save_task.await.unwrap();
-
-        // Rename the file
-        select_path(&panel, "root/new", cx);
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &["v root", "      new  <== selected"]
+            visible_entries_with_label_colors(&panel, 0..20, cx),
+            &[
+                "v root  <== conflict",
+                "    > .git  <== ignored",
+                "    > dir1  <== conflict",
+                "    > dir2  <== modified",
+                "      .gitignore",
+                "      a.txt  <== modified",
+                "      b.txt",
+                "      ignored.txt  <== ignored",
+            ]
         );
-        panel.update(cx, |panel, cx| panel.rename(&Rename, cx));
-        panel.update(cx, |panel, cx| {
-            panel
-                .filename_editor
-                .update(cx, |editor, cx| editor.set_text("newer", cx));
-        });
-        panel.update(cx, |panel, cx| panel.confirm(&Confirm, cx));
 
-        cx.executor().run_until_parked();
+        toggle_expand_dir(&panel, "root/dir1", cx);
+        toggle_expand_dir(&panel, "root/dir1/sub", cx);
+        toggle_expand_dir(&panel, "root/dir2", cx);
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &["v root", "      newer  <== selected"]
+            visible_entries_with_label_colors(&panel, 0..20, cx),
+            &[
+                "v root  <== conflict",
+                "    > .git  <== ignored",
+                "    v dir1  <== conflict",
+                "        v sub  <== conflict",
+                "              clean.txt",
+                "              conflict.txt  <== conflict",
+                "          added.txt  <== added",
+                "    v dir2  <== modified  <== selected",
+                "          added.txt  <== added",
+                "          modified.txt  <== modified",
+                "      .gitignore",
+                "      a.txt  <== modified",
+                "      b.txt",
+                "      ignored.txt  <== ignored",
+            ]
         );
 
-        workspace
-            .update(cx, |workspace, cx| {
-                workspace.save_active_item(workspace::SaveIntent::Save, cx)
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<ProjectPanelSettings>(cx, |settings| {
+                    settings.git_status = Some(false);
+                });
             })
-            .unwrap()
-            .await
-            .unwrap();
-
-        cx.executor().run_until_parked();
-        // assert that saving the file doesn't restore "new"
+        });
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &["v root", "      newer  <== selected"]
+            visible_entries_with_label_colors(&panel, 0..20, cx),
+            &[
+                "v root",
+                "    > .git  <== ignored",
+                "    v dir1",
+                "        v sub",
+                "              clean.txt",
+                "              conflict.txt",
+                "          added.txt",
+                "    v dir2  <== selected",
+                "          added.txt",
+                "          modified.txt",
+                "      .gitignore",
+                "      a.txt",
+                "      b.txt",
+                "      ignored.txt  <== ignored",
+            ]
         );
     }
 
     #[gpui::test]
-    async fn test_multiple_marked_entries(cx: &mut gpui::TestAppContext) {
-        init_test_with_editor(cx);
-        let fs = FakeFs::new(cx.executor().clone());
-        fs.insert_tree(
-            "/project_root",
-            json!({
-                "dir_1": {
-                    "nested_dir": {
-                        "file_a.py": "# File contents",
-                    }
-                },
-                "file_1.py": "# File contents",
-            }),
-        )
-        .await;
+    async fn test_git_status_propagation_settings(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
 
-        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
-        let worktree_id =
-            cx.update(|cx| project.read(cx).worktrees().next().unwrap().read(cx).id());
+        let fs = FakeFs::new(cx.executor().clone());
+        insert_git_status_tree(&fs).await;
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
         let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
         let cx = &mut VisualTestContext::from_window(*workspace, cx);
         let panel = workspace
             .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
             .unwrap();
-        cx.update(|cx| {
-            panel.update(cx, |this, cx| {
-                this.select_next(&Default::default(), cx);
-                this.expand_selected_entry(&Default::default(), cx);
-                this.expand_selected_entry(&Default::default(), cx);
-                this.select_next(&Default::default(), cx);
-                this.expand_selected_entry(&Default::default(), cx);
-                this.select_next(&Default::default(), cx);
-            })
-        });
-        assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &[
-                "v project_root",
-                "    v dir_1",
-                "        v nested_dir",
-                "              file_a.py  <== selected",
-                "      file_1.py",
-            ]
-        );
-        let modifiers_with_shift = gpui::Modifiers {
-            shift: true,
-            ..Default::default()
+        cx.run_until_parked();
+        toggle_expand_dir(&panel, "root/dir1", cx);
+        toggle_expand_dir(&panel, "root/dir1/sub", cx);
+        toggle_expand_dir(&panel, "root/dir2", cx);
+        let dirs_with_label_colors = |cx: &mut VisualTestContext| {
+            visible_entries_with_label_colors(&panel, 0..20, cx)
+                .into_iter()
+                .filter(|entry| entry.contains("v "))
+                .collect::<Vec<_>>()
         };
-        cx.simulate_modifiers_change(modifiers_with_shift);
+
         cx.update(|cx| {
-            panel.update(cx, |this, cx| {
-                this.select_next(&Default::default(), cx);
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<ProjectPanelSettings>(cx, |settings| {
+                    settings.git_status_propagation = Some(GitStatusPropagation::Nearest);
+                });
             })
         });
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
+            dirs_with_label_colors(cx),
             &[
-                "v project_root",
-                "    v dir_1",
-                "        v nested_dir",
-                "              file_a.py",
-                "      file_1.py  <== selected  <== marked",
+                "v root  <== modified",
+                "    v dir1  <== added",
+                "        v sub  <== conflict",
+                "    v dir2  <== modified  <== selected",
             ]
         );
+
         cx.update(|cx| {
-            panel.update(cx, |this, cx| {
-                this.select_prev(&Default::default(), cx);
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<ProjectPanelSettings>(cx, |settings| {
+                    settings.git_status_propagation = Some(GitStatusPropagation::None);
+                });
             })
         });
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
+            dirs_with_label_colors(cx),
             &[
-                "v project_root",
-                "    v dir_1",
-                "        v nested_dir",
-                "              file_a.py  <== selected  <== marked",
-                "      file_1.py  <== marked",
+                "v root",
+                "    v dir1",
+                "        v sub",
+                "    v dir2  <== selected"
             ]
         );
+        assert!(visible_entries_with_label_colors(&panel, 0..20, cx)
+            .contains(&"              conflict.txt  <== conflict".to_string()));
+
         cx.update(|cx| {
-            panel.update(cx, |this, cx| {
-                let drag = DraggedSelection {
-                    active_selection: this.selection.unwrap(),
-                    marked_selections: Arc::new(this.marked_entries.clone()),
-                };
-                let target_entry = this
-                    .project
-                    .read(cx)
-                    .entry_for_path(&(worktree_id, "").into(), cx)
-                    .unwrap();
-                this.drag_onto(&drag, target_entry.id, false, cx);
-            });
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<ProjectPanelSettings>(cx, |settings| {
+                    settings.git_status_propagation = Some(GitStatusPropagation::All);
+                    settings.git_status_priority =
+                        Some(vec![GitStatusKind::Added, GitStatusKind::Modified]);
+                });
+            })
         });
-        cx.run_until_parked();
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
+            dirs_with_label_colors(cx),
             &[
-                "v project_root",
-                "    v dir_1",
-                "        v nested_dir",
-                "      file_1.py  <== marked",
-                "      file_a.py  <== selected  <== marked",
-            ]
+                "v root  <== added",
+                "    v dir1  <== added",
+                "        v sub  <== conflict",
+                "    v dir2  <== added  <== selected",
+            ],
+            "Statuses left out of the priority should still show after the listed ones"
         );
-        // ESC clears out all marks
+
         cx.update(|cx| {
-            panel.update(cx, |this, cx| {
-                this.cancel(&menu::Cancel, cx);
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<ProjectPanelSettings>(cx, |settings| {
+                    settings.git_status_priority = Some(vec![GitStatusKind::Modified]);
+                });
             })
         });
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
+            dirs_with_label_colors(cx),
             &[
-                "v project_root",
-                "    v dir_1",
-                "        v nested_dir",
-                "      file_1.py",
-                "      file_a.py  <== selected",
+                "v root  <== modified",
+                "    v dir1  <== conflict",
+                "        v sub  <== conflict",
+                "    v dir2  <== modified  <== selected",
             ]
         );
-        // ESC clears out all marks
-        cx.update(|cx| {
-            panel.update(cx, |this, cx| {
-                this.select_prev(&SelectPrev, cx);
-                this.select_next(&SelectNext, cx);
-            })
-        });
+    }
+
+    #[gpui::test]
+    async fn test_git_status_updates(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        insert_git_status_tree(&fs).await;
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
+        toggle_expand_dir(&panel, "root/dir2", cx);
+
+        fs.set_status_for_repo_via_git_operation(
+            Path::new("/root/.git"),
+            &[
+                (Path::new("b.txt"), GitFileStatus::Added),
+                (Path::new("dir2/added.txt"), GitFileStatus::Conflict),
+            ],
+        );
+        cx.run_until_parked();
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
+            visible_entries_with_label_colors(&panel, 0..20, cx),
             &[
-                "v project_root",
-                "    v dir_1",
-                "        v nested_dir",
-                "      file_1.py  <== marked",
-                "      file_a.py  <== selected  <== marked",
+                "v root  <== conflict",
+                "    > .git  <== ignored",
+                "    > dir1",
+                "    v dir2  <== conflict  <== selected",
+                "          added.txt  <== conflict",
+                "          modified.txt",
+                "      .gitignore",
+                "      a.txt",
+                "      b.txt  <== added",
+                "      ignored.txt  <== ignored",
             ]
         );
-        cx.simulate_modifiers_change(Default::default());
-        cx.update(|cx| {
-            panel.update(cx, |this, cx| {
-                this.cut(&Cut, cx);
-                this.select_prev(&SelectPrev, cx);
-                this.select_prev(&SelectPrev, cx);
 
-                this.paste(&Paste, cx);
-                // this.expand_selected_entry(&ExpandSelectedEntry, cx);
-            })
-        });
+        fs.set_status_for_repo_via_git_operation(Path::new("/root/.git"), &[]);
         cx.run_until_parked();
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
+            visible_entries_with_label_colors(&panel, 0..20, cx),
             &[
-                "v project_root",
-                "    v dir_1",
-                "        v nested_dir  <== selected",
-                "              file_1.py  <== marked",
-                "              file_a.py  <== marked",
+                "v root",
+                "    > .git  <== ignored",
+                "    > dir1",
+                "    v dir2  <== selected",
+                "          added.txt",
+                "          modified.txt",
+                "      .gitignore",
+                "      a.txt",
+                "      b.txt",
+                "      ignored.txt  <== ignored",
             ]
         );
-        cx.simulate_modifiers_change(modifiers_with_shift);
-        cx.update(|cx| {
-            panel.update(cx, |this, cx| {
-                this.expand_selected_entry(&Default::default(), cx);
-                this.select_next(&SelectNext, cx);
-                this.select_next(&SelectNext, cx);
-            })
-        });
-        submit_deletion(&panel, cx);
-        assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &["v project_root", "    v dir_1", "        v nested_dir",]
-        );
     }
+
     #[gpui::test]
-    async fn test_autoreveal_and_gitignored_files(cx: &mut gpui::TestAppContext) {
-        init_test_with_editor(cx);
-        cx.update(|cx| {
-            cx.update_global::<SettingsStore, _>(|store, cx| {
-                store.update_user_settings::<WorktreeSettings>(cx, |worktree_settings| {
-                    worktree_settings.file_scan_exclusions = Some(Vec::new());
-                });
-                store.update_user_settings::<ProjectPanelSettings>(cx, |project_panel_settings| {
-                    project_panel_settings.auto_reveal_entries = Some(false)
-                });
-            })
-        });
+    async fn test_generated_paths(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
 
-        let fs = FakeFs::new(cx.background_executor.clone());
+        let fs = FakeFs::new(cx.executor().clone());
         fs.insert_tree(
-            "/project_root",
+            "/root",
             json!({
-                ".git": {},
-                ".gitignore": "**/gitignored_dir",
-                "dir_1": {
-                    "file_1.py": "# File 1_1 contents",
-                    "file_2.py": "# File 1_2 contents",
-                    "file_3.py": "# File 1_3 contents",
-                    "gitignored_dir": {
-                        "file_a.py": "# File contents",
-                        "file_b.py": "# File contents",
-                        "file_c.py": "# File contents",
-                    },
-                },
-                "dir_2": {
-                    "file_1.py": "# File 2_1 contents",
-                    "file_2.py": "# File 2_2 contents",
-                    "file_3.py": "# File 2_3 contents",
-                }
+                "dist": { "bundle.js": "" },
+                "src": { "main.rs": "" },
             }),
         )
         .await;
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
+        toggle_expand_dir(&panel, "root/dist", cx);
+        toggle_expand_dir(&panel, "root/src", cx);
+        let generated_entries = |cx: &mut VisualTestContext| {
+            let mut filenames = Vec::new();
+            panel.update(cx, |panel, cx| {
+                panel.for_each_visible_entry(0..20, cx, |_, details, _| {
+                    if details.is_generated {
+                        filenames.push(details.filename);
+                    }
+                });
+            });
+            filenames
+        };
+        assert!(generated_entries(cx).is_empty());
 
-        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<ProjectPanelSettings>(cx, |settings| {
+                    settings.generated_paths = Some(vec!["dist/**".to_string()]);
+                });
+            })
+        });
+        cx.run_until_parked();
+        assert_eq!(generated_entries(cx), &["bundle.js"]);
+    }
+
+    #[gpui::test]
+    async fn test_changed_files_only(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        insert_git_status_tree(&fs).await;
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
         let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
         let cx = &mut VisualTestContext::from_window(*workspace, cx);
         let panel = workspace
             .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
             .unwrap();
+        cx.run_until_parked();
+        toggle_expand_dir(&panel, "root/dir1", cx);
+        toggle_expand_dir(&panel, "root/dir1/sub", cx);
 
+        panel.update(cx, |panel, cx| {
+            panel.toggle_changed_files_only(&ToggleChangedFilesOnly, cx)
+        });
         assert_eq!(
             visible_entries_as_strings(&panel, 0..20, cx),
             &[
-                "v project_root",
-                "    > .git",
-                "    > dir_1",
-                "    > dir_2",
-                "      .gitignore",
+                "v root",
+                "    v dir1",
+                "        v sub  <== selected",
+                "              conflict.txt",
+                "          added.txt",
+                "    > dir2",
+                "      a.txt",
             ]
         );
 
-        let dir_1_file = find_project_entry(&panel, "project_root/dir_1/file_1.py", cx)
-            .expect("dir 1 file is not ignored and should have an entry");
-        let dir_2_file = find_project_entry(&panel, "project_root/dir_2/file_1.py", cx)
-            .expect("dir 2 file is not ignored and should have an entry");
-        let gitignored_dir_file =
-            find_project_entry(&panel, "project_root/dir_1/gitignored_dir/file_a.py", cx);
-        assert_eq!(
-            gitignored_dir_file, None,
-            "File in the gitignored dir should not have an entry before its dir is toggled"
-        );
-
-        toggle_expand_dir(&panel, "project_root/dir_1", cx);
-        toggle_expand_dir(&panel, "project_root/dir_1/gitignored_dir", cx);
-        cx.executor().run_until_parked();
+        panel.update(cx, |panel, cx| {
+            panel.toggle_changed_files_only(&ToggleChangedFilesOnly, cx)
+        });
         assert_eq!(
             visible_entries_as_strings(&panel, 0..20, cx),
             &[
-                "v project_root",
+                "v root",
                 "    > .git",
-                "    v dir_1",
-                "        v gitignored_dir  <== selected",
-                "              file_a.py",
-                "              file_b.py",
-                "              file_c.py",
-                "          file_1.py",
-                "          file_2.py",
-                "          file_3.py",
-                "    > dir_2",
+                "    v dir1",
+                "        v sub  <== selected",
+                "              clean.txt",
+                "              conflict.txt",
+                "          added.txt",
+                "    > dir2",
                 "      .gitignore",
-            ],
-            "Should show gitignored dir file list in the project panel"
+                "      a.txt",
+                "      b.txt",
+                "      ignored.txt",
+            ]
         );
-        let gitignored_dir_file =
-            find_project_entry(&panel, "project_root/dir_1/gitignored_dir/file_a.py", cx)
-                .expect("after gitignored dir got opened, a file entry should be present");
+    }
 
-        toggle_expand_dir(&panel, "project_root/dir_1/gitignored_dir", cx);
-        toggle_expand_dir(&panel, "project_root/dir_1", cx);
+    #[gpui::test]
+    async fn test_deleted_files(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                ".git": {},
+                "a.txt": "",
+                "dir": {
+                    "b.txt": "",
+                },
+            }),
+        )
+        .await;
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<ProjectPanelSettings>(cx, |settings| {
+                    settings.show_deleted_files = Some(true);
+                });
+            })
+        });
+        fs.with_git_state(Path::new("/root/.git"), true, |state| {
+            state.deleted_paths = vec![
+                RepoPath::from(Path::new("dir/gone.txt")),
+                RepoPath::from(Path::new("removed.txt")),
+            ];
+        });
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
+        let deleted_entries = |cx: &mut VisualTestContext| {
+            let mut filenames = Vec::new();
+            panel.update(cx, |panel, cx| {
+                panel.for_each_visible_entry(0..20, cx, |_, details, _| {
+                    if details.is_deleted {
+                        filenames.push(details.filename);
+                    }
+                });
+            });
+            filenames
+        };
+
+        toggle_expand_dir(&panel, "root/dir", cx);
         assert_eq!(
             visible_entries_as_strings(&panel, 0..20, cx),
             &[
-                "v project_root",
+                "v root",
                 "    > .git",
-                "    > dir_1  <== selected",
-                "    > dir_2",
-                "      .gitignore",
-            ],
-            "Should hide all dir contents again and prepare for the auto reveal test"
+                "    v dir  <== selected",
+                "          b.txt",
+                "          gone.txt",
+                "      a.txt",
+                "      removed.txt",
+            ]
         );
+        assert_eq!(deleted_entries(cx), &["gone.txt", "removed.txt"]);
 
-        for file_entry in [dir_1_file, dir_2_file, gitignored_dir_file] {
-            panel.update(cx, |panel, cx| {
-                panel.project.update(cx, |_, cx| {
-                    cx.emit(project::Event::ActiveEntryChanged(Some(file_entry)))
-                })
+        toggle_expand_dir(&panel, "root/dir", cx);
+        assert_eq!(deleted_entries(cx), &["removed.txt"]);
+        toggle_expand_dir(&panel, "root/dir", cx);
+
+        panel.update(cx, |panel, cx| {
+            let (&(worktree_id, _), &entry_id) = panel
+                .ghost_entry_ids
+                .iter()
+                .find(|((_, path), _)| path.as_ref() == Path::new("dir/gone.txt"))
+                .unwrap();
+            panel.selection = Some(SelectedEntry {
+                worktree_id,
+                entry_id,
             });
-            cx.run_until_parked();
+            panel.restore_deleted_file(&RestoreDeletedFile, cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(deleted_entries(cx), &["removed.txt"]);
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<ProjectPanelSettings>(cx, |settings| {
+                    settings.show_deleted_files = Some(false);
+                });
+            })
+        });
+        cx.run_until_parked();
+        assert!(deleted_entries(cx).is_empty());
+    }
+
+    #[gpui::test]
+    async fn test_low_disk_space_warning(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree("/root", json!({ "a.txt": "" })).await;
+        fs.set_available_space(10 * 1024 * 1024 * 1024);
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
+        render_rows(&panel, 0..2, cx);
+        cx.executor().advance_clock(AVAILABLE_SPACE_DEBOUNCE);
+        cx.run_until_parked();
+        panel.update(cx, |panel, cx| {
+            let root_id = panel
+                .project
+                .read(cx)
+                .worktrees()
+                .next()
+                .unwrap()
+                .read(cx)
+                .id();
             assert_eq!(
-                visible_entries_as_strings(&panel, 0..20, cx),
-                &[
-                    "v project_root",
-                    "    > .git",
-                    "    > dir_1  <== selected",
-                    "    > dir_2",
-                    "      .gitignore",
-                ],
-                "When no auto reveal is enabled, the selected entry should not be revealed in the project panel"
+                panel
+                    .decorations
+                    .get::<AvailableSpaceDecoration>()
+                    .available_space(root_id),
+                Some(10 * 1024 * 1024 * 1024)
             );
-        }
+            assert!(panel.render_low_space_header(cx).is_none());
+        });
+
+        fs.set_available_space(100 * 1024 * 1024);
+        fs.create_file("/root/b.txt".as_ref(), Default::default())
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        render_rows(&panel, 0..3, cx);
+        cx.executor().advance_clock(AVAILABLE_SPACE_DEBOUNCE);
+        cx.run_until_parked();
+        panel.update(cx, |panel, cx| {
+            assert_eq!(panel.worktrees_low_on_space(cx).len(), 1);
+            assert!(panel.render_low_space_header(cx).is_some());
+        });
 
         cx.update(|cx| {
             cx.update_global::<SettingsStore, _>(|store, cx| {
-                store.update_user_settings::<ProjectPanelSettings>(cx, |project_panel_settings| {
-                    project_panel_settings.auto_reveal_entries = Some(true)
+                store.update_user_settings::<ProjectPanelSettings>(cx, |settings| {
+                    settings.low_disk_space_warning_mb = Some(0);
                 });
             })
         });
-
         panel.update(cx, |panel, cx| {
-            panel.project.update(cx, |_, cx| {
-                cx.emit(project::Event::ActiveEntryChanged(Some(dir_1_file)))
+            assert!(panel.render_low_space_header(cx).is_none());
+        });
+    }
+
+    #[gpui::test]
+    async fn test_expansion_rules(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<ProjectPanelSettings>(cx, |project_panel_settings| {
+                    project_panel_settings.expand_top_level_dirs = Some(true);
+                    project_panel_settings.expand_dirs = Some(vec!["src/*".to_string()]);
+                    project_panel_settings.never_expand_dirs =
+                        Some(vec!["vendor".to_string(), "src/b".to_string()]);
+                });
             })
         });
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/project_root",
+            json!({
+                "docs": { "guide.md": "" },
+                "src": {
+                    "a": { "x.rs": "" },
+                    "b": { "y.rs": "" },
+                    "main.rs": "",
+                },
+                "vendor": { "lib": {} },
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
         cx.run_until_parked();
         assert_eq!(
             visible_entries_as_strings(&panel, 0..20, cx),
             &[
                 "v project_root",
-                "    > .git",
-                "    v dir_1",
-                "        > gitignored_dir",
-                "          file_1.py  <== selected",
-                "          file_2.py",
-                "          file_3.py",
-                "    > dir_2",
-                "      .gitignore",
-            ],
-            "When auto reveal is enabled, not ignored dir_1 entry should be revealed"
+                "    v docs",
+                "          guide.md",
+                "    v src",
+                "        v a",
+                "              x.rs",
+                "        > b",
+                "          main.rs",
+                "    > vendor",
+            ]
         );
 
-        panel.update(cx, |panel, cx| {
-            panel.project.update(cx, |_, cx| {
-                cx.emit(project::Event::ActiveEntryChanged(Some(dir_2_file)))
-            })
-        });
+        // The rules only apply when the project is loaded.
+        toggle_expand_dir(&panel, "project_root/vendor", cx);
+        fs.create_dir("/project_root/src/c".as_ref()).await.unwrap();
         cx.run_until_parked();
         assert_eq!(
             visible_entries_as_strings(&panel, 0..20, cx),
             &[
                 "v project_root",
-                "    > .git",
-                "    v dir_1",
-                "        > gitignored_dir",
-                "          file_1.py",
-                "          file_2.py",
-                "          file_3.py",
-                "    v dir_2",
-                "          file_1.py  <== selected",
-                "          file_2.py",
-                "          file_3.py",
-                "      .gitignore",
-            ],
-            "When auto reveal is enabled, not ignored dir_2 entry should be revealed"
+                "    v docs",
+                "          guide.md",
+                "    v src",
+                "        v a",
+                "              x.rs",
+                "        > b",
+                "        > c",
+                "          main.rs",
+                "    v vendor",
+                "        > lib",
+            ]
         );
+    }
 
-        panel.update(cx, |panel, cx| {
-            panel.project.update(cx, |_, cx| {
-                cx.emit(project::Event::ActiveEntryChanged(Some(
-                    gitignored_dir_file,
-                )))
+    #[gpui::test]
+    async fn test_external_entries(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "dir1": {
+                    "deps": {},
+                    "a.rs": "",
+                },
+                "dir2": {
+                    "src": { "e.rs": "" },
+                },
+            }),
+        )
+        .await;
+        fs.create_symlink("/root/dir1/deps/dep-dir2".as_ref(), "../../dir2".into())
+            .await
+            .unwrap();
+
+        let project = Project::test(fs.clone(), ["/root/dir1".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
+
+        let notifications = Rc::new(std::cell::RefCell::new(Vec::new()));
+        cx.update(|cx| {
+            let notifications = notifications.clone();
+            cx.subscribe(&project, move |_, event: &project::Event, _| {
+                if let project::Event::Notification(message) = event {
+                    notifications.borrow_mut().push(message.clone());
+                }
             })
+            .detach();
         });
+
+        toggle_expand_dir(&panel, "dir1/deps", cx);
+        cx.run_until_parked();
+        toggle_expand_dir(&panel, "dir1/deps/dep-dir2", cx);
+        cx.run_until_parked();
+        toggle_expand_dir(&panel, "dir1/deps/dep-dir2/src", cx);
         cx.run_until_parked();
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..20, cx),
+            visible_entries_as_strings(&panel, 0..10, cx),
             &[
-                "v project_root",
-                "    > .git",
-                "    v dir_1",
-                "        > gitignored_dir",
-                "          file_1.py",
-                "          file_2.py",
-                "          file_3.py",
-                "    v dir_2",
-                "          file_1.py  <== selected",
-                "          file_2.py",
-                "          file_3.py",
-                "      .gitignore",
-            ],
-            "When auto reveal is enabled, a gitignored selected entry should not be revealed in the project panel"
+                "v dir1",
+                "    v deps",
+                "        v dep-dir2",
+                "            v src",
+                "                  e.rs",
+                "      a.rs",
+            ]
         );
-
         panel.update(cx, |panel, cx| {
-            panel.project.update(cx, |_, cx| {
-                cx.emit(project::Event::RevealInProjectPanel(gitignored_dir_file))
-            })
+            let worktree = panel.project.read(cx).worktrees().next().unwrap();
+            let snapshot = worktree.read(cx).snapshot();
+            let location =
+                |path: &str| external_location(&snapshot, snapshot.entry_for_path(path).unwrap());
+            assert_eq!(location("a.rs"), None);
+            assert_eq!(location("deps/dep-dir2"), Some(PathBuf::from("/root/dir2")));
+            assert_eq!(
+                location("deps/dep-dir2/src/e.rs"),
+                Some(PathBuf::from("/root/dir2/src/e.rs"))
+            );
         });
+
+        // Entries reached through the symlink are left out of deletions by default.
+        select_path(&panel, "dir1/deps/dep-dir2/src/e.rs", cx);
+        panel.update(cx, |panel, cx| panel.remove(false, true, cx));
         cx.run_until_parked();
+        assert!(fs.is_file(Path::new("/root/dir2/src/e.rs")).await);
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..20, cx),
-            &[
-                "v project_root",
-                "    > .git",
-                "    v dir_1",
-                "        v gitignored_dir",
-                "              file_a.py  <== selected",
-                "              file_b.py",
-                "              file_c.py",
-                "          file_1.py",
-                "          file_2.py",
-                "          file_3.py",
-                "    v dir_2",
-                "          file_1.py",
-                "          file_2.py",
-                "          file_3.py",
-                "      .gitignore",
-            ],
-            "When a gitignored entry is explicitly revealed, it should be shown in the project tree"
+            notifications.borrow().as_slice(),
+            &["Left out 1 entry outside of the project, reached through a symlink"]
         );
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<ProjectPanelSettings>(cx, |settings| {
+                    settings.delete_external_entries = Some(true);
+                });
+            })
+        });
+        panel.update(cx, |panel, cx| panel.remove(false, true, cx));
+        cx.run_until_parked();
+        assert!(!fs.is_file(Path::new("/root/dir2/src/e.rs")).await);
     }
 
     #[gpui::test]
-    async fn test_explicit_reveal(cx: &mut gpui::TestAppContext) {
-        init_test_with_editor(cx);
+    async fn test_max_indent_depth(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
         cx.update(|cx| {
             cx.update_global::<SettingsStore, _>(|store, cx| {
-                store.update_user_settings::<WorktreeSettings>(cx, |worktree_settings| {
-                    worktree_settings.file_scan_exclusions = Some(Vec::new());
-                });
                 store.update_user_settings::<ProjectPanelSettings>(cx, |project_panel_settings| {
-                    project_panel_settings.auto_reveal_entries = Some(false)
+                    project_panel_settings.max_indent_depth = Some(2);
                 });
             })
         });
 
-        let fs = FakeFs::new(cx.background_executor.clone());
+        let fs = FakeFs::new(cx.executor().clone());
         fs.insert_tree(
-            "/project_root",
+            "/root",
             json!({
-                ".git": {},
-                ".gitignore": "**/gitignored_dir",
-                "dir_1": {
-                    "file_1.py": "# File 1_1 contents",
-                    "file_2.py": "# File 1_2 contents",
-                    "file_3.py": "# File 1_3 contents",
-                    "gitignored_dir": {
-                        "file_a.py": "# File contents",
-                        "file_b.py": "# File contents",
-                        "file_c.py": "# File contents",
+                "a": {
+                    "b": {
+                        "c": { "d.txt": "" },
                     },
                 },
-                "dir_2": {
-                    "file_1.py": "# File 2_1 contents",
-                    "file_2.py": "# File 2_2 contents",
-                    "file_3.py": "# File 2_3 contents",
-                }
             }),
         )
         .await;
 
-        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
         let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
         let cx = &mut VisualTestContext::from_window(*workspace, cx);
         let panel = workspace
             .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
             .unwrap();
-
+        for path in ["root/a", "root/a/b", "root/a/b/c"] {
+            toggle_expand_dir(&panel, path, cx);
+        }
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..20, cx),
+            visible_entries_as_strings(&panel, 0..10, cx),
             &[
-                "v project_root",
-                "    > .git",
-                "    > dir_1",
-                "    > dir_2",
-                "      .gitignore",
+                "v root",
+                "    v a",
+                "        v b",
+                "        v …/c",
+                "          …/d.txt",
             ]
         );
-
-        let dir_1_file = find_project_entry(&panel, "project_root/dir_1/file_1.py", cx)
-            .expect("dir 1 file is not ignored and should have an entry");
-        let dir_2_file = find_project_entry(&panel, "project_root/dir_2/file_1.py", cx)
-            .expect("dir 2 file is not ignored and should have an entry");
-        let gitignored_dir_file =
-            find_project_entry(&panel, "project_root/dir_1/gitignored_dir/file_a.py", cx);
-        assert_eq!(
-            gitignored_dir_file, None,
-            "File in the gitignored dir should not have an entry before its dir is toggled"
-        );
-
-        toggle_expand_dir(&panel, "project_root/dir_1", cx);
-        toggle_expand_dir(&panel, "project_root/dir_1/gitignored_dir", cx);
-        cx.run_until_parked();
-        assert_eq!(
-            visible_entries_as_strings(&panel, 0..20, cx),
-            &[
-                "v project_root",
-                "    > .git",
-                "    v dir_1",
-                "        v gitignored_dir  <== selected",
-                "              file_a.py",
-                "              file_b.py",
-                "              file_c.py",
-                "          file_1.py",
-                "          file_2.py",
-                "          file_3.py",
-                "    > dir_2",
-                "      .gitignore",
-            ],
-            "Should show gitignored dir file list in the project panel"
-        );
-        let gitignored_dir_file =
-            find_project_entry(&panel, "project_root/dir_1/gitignored_dir/file_a.py", cx)
-                .expect("after gitignored dir got opened, a file entry should be present");
-
-        toggle_expand_dir(&panel, "project_root/dir_1/gitignored_dir", cx);
-        toggle_expand_dir(&panel, "project_root/dir_1", cx);
-        assert_eq!(
-            visible_entries_as_strings(&panel, 0..20, cx),
-            &[
-                "v project_root",
-                "    > .git",
-                "    > dir_1  <== selected",
-                "    > dir_2",
-                "      .gitignore",
-            ],
-            "Should hide all dir contents again and prepare for the explicit reveal test"
-        );
-
-        for file_entry in [dir_1_file, dir_2_file, gitignored_dir_file] {
-            panel.update(cx, |panel, cx| {
-                panel.project.update(cx, |_, cx| {
-                    cx.emit(project::Event::ActiveEntryChanged(Some(file_entry)))
-                })
+        panel.update(cx, |panel, cx| {
+            let mut full_depths = Vec::new();
+            panel.for_each_visible_entry(0..10, cx, |_, details, _| {
+                full_depths.push(details.full_depth)
             });
-            cx.run_until_parked();
-            assert_eq!(
-                visible_entries_as_strings(&panel, 0..20, cx),
-                &[
-                    "v project_root",
-                    "    > .git",
-                    "    > dir_1  <== selected",
-                    "    > dir_2",
-                    "      .gitignore",
-                ],
-                "When no auto reveal is enabled, the selected entry should not be revealed in the project panel"
-            );
-        }
+            assert_eq!(full_depths, &[None, None, None, Some(3), Some(4)]);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_selection_follows_expansion(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<ProjectPanelSettings>(cx, |project_panel_settings| {
+                    project_panel_settings.selection_follows_expansion = Some(true);
+                });
+            })
+        });
 
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "a": { "x.txt": "", "y.txt": "" },
+                "b.txt": "",
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+
+        select_path(&panel, "root/a", cx);
         panel.update(cx, |panel, cx| {
-            panel.project.update(cx, |_, cx| {
-                cx.emit(project::Event::RevealInProjectPanel(dir_1_file))
-            })
+            panel.expand_selected_entry(&ExpandSelectedEntry, cx)
         });
-        cx.run_until_parked();
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..20, cx),
+            visible_entries_as_strings(&panel, 0..10, cx),
             &[
-                "v project_root",
-                "    > .git",
-                "    v dir_1",
-                "        > gitignored_dir",
-                "          file_1.py  <== selected",
-                "          file_2.py",
-                "          file_3.py",
-                "    > dir_2",
-                "      .gitignore",
-            ],
-            "With no auto reveal, explicit reveal should show the dir_1 entry in the project panel"
+                "v root",
+                "    v a",
+                "          x.txt  <== selected",
+                "          y.txt",
+                "      b.txt",
+            ]
         );
 
+        // Collapsing from a file selects its directory, which stays expanded.
         panel.update(cx, |panel, cx| {
-            panel.project.update(cx, |_, cx| {
-                cx.emit(project::Event::RevealInProjectPanel(dir_2_file))
-            })
+            panel.collapse_selected_entry(&CollapseSelectedEntry, cx)
         });
-        cx.run_until_parked();
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..20, cx),
+            visible_entries_as_strings(&panel, 0..10, cx),
             &[
-                "v project_root",
-                "    > .git",
-                "    v dir_1",
-                "        > gitignored_dir",
-                "          file_1.py",
-                "          file_2.py",
-                "          file_3.py",
-                "    v dir_2",
-                "          file_1.py  <== selected",
-                "          file_2.py",
-                "          file_3.py",
-                "      .gitignore",
-            ],
-            "With no auto reveal, explicit reveal should show the dir_2 entry in the project panel"
+                "v root",
+                "    v a  <== selected",
+                "          x.txt",
+                "          y.txt",
+                "      b.txt",
+            ]
         );
 
         panel.update(cx, |panel, cx| {
-            panel.project.update(cx, |_, cx| {
-                cx.emit(project::Event::RevealInProjectPanel(gitignored_dir_file))
-            })
+            panel.collapse_selected_entry(&CollapseSelectedEntry, cx)
         });
-        cx.run_until_parked();
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..20, cx),
-            &[
-                "v project_root",
-                "    > .git",
-                "    v dir_1",
-                "        v gitignored_dir",
-                "              file_a.py  <== selected",
-                "              file_b.py",
-                "              file_c.py",
-                "          file_1.py",
-                "          file_2.py",
-                "          file_3.py",
-                "    v dir_2",
-                "          file_1.py",
-                "          file_2.py",
-                "          file_3.py",
-                "      .gitignore",
-            ],
-            "With no auto reveal, explicit reveal should show the gitignored entry in the project panel"
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &["v root", "    > a  <== selected", "      b.txt"]
         );
     }
 
     #[gpui::test]
-    async fn test_creating_excluded_entries(cx: &mut gpui::TestAppContext) {
+    async fn test_copy_file_contents(cx: &mut gpui::TestAppContext) {
         init_test(cx);
-        cx.update(|cx| {
-            cx.update_global::<SettingsStore, _>(|store, cx| {
-                store.update_user_settings::<WorktreeSettings>(cx, |project_settings| {
-                    project_settings.file_scan_exclusions =
-                        Some(vec!["excluded_dir".to_string(), "**/.git".to_string()]);
-                });
-            });
-        });
-
-        cx.update(|cx| {
-            register_project_item::<TestProjectItemView>(cx);
-        });
 
         let fs = FakeFs::new(cx.executor().clone());
         fs.insert_tree(
-            "/root1",
+            "/project_root",
             json!({
-                ".dockerignore": "",
-                ".git": {
-                    "HEAD": "",
-                },
+                "README.md": "```sh\ncargo run\n```\n",
+                "src": { "lib.rs": "pub fn f() {}" },
             }),
         )
         .await;
 
-        let project = Project::test(fs.clone(), ["/root1".as_ref()], cx).await;
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
         let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
         let cx = &mut VisualTestContext::from_window(*workspace, cx);
         let panel = workspace
-            .update(cx, |workspace, cx| {
-                let panel = ProjectPanel::new(workspace, cx);
-                workspace.add_panel(panel.clone(), cx);
-                panel
-            })
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
             .unwrap();
+        cx.run_until_parked();
 
-        select_path(&panel, "root1", cx);
+        select_path(&panel, "project_root/src/lib.rs", cx);
+        panel.update(cx, |panel, cx| {
+            panel.copy_file_contents(&CopyFileContents, cx)
+        });
+        cx.run_until_parked();
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..10, cx),
-            &["v root1  <== selected", "      .dockerignore",]
+            cx.read_from_clipboard().map(|item| item.text().to_owned()),
+            Some("pub fn f() {}".to_string())
         );
-        workspace
-            .update(cx, |workspace, cx| {
-                assert!(
-                    workspace.active_item(cx).is_none(),
-                    "Should have no active items in the beginning"
-                );
-            })
+
+        let readme = find_project_entry(&panel, "project_root/README.md", cx).unwrap();
+        panel.update(cx, |panel, cx| {
+            let selection = panel.selection.unwrap();
+            panel.marked_entries.insert(selection);
+            panel.marked_entries.insert(SelectedEntry {
+                worktree_id: selection.worktree_id,
+                entry_id: readme,
+            });
+            panel.copy_file_contents(&CopyFileContents, cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            cx.read_from_clipboard().map(|item| item.text().to_owned()),
+            Some(
+                "````README.md\n```sh\ncargo run\n```\n````\n\n```src/lib.rs\npub fn f() {}\n```\n"
+                    .to_string()
+            )
+        );
+    }
+
+    #[gpui::test]
+    async fn test_add_to_assistant_context(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/project_root",
+            json!({ "src": { "main.rs": "" }, "README.md": "" }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
             .unwrap();
+        cx.run_until_parked();
 
-        let excluded_file_path = ".git/COMMIT_EDITMSG";
-        let excluded_dir_path = "excluded_dir";
+        let added_paths = Rc::new(std::cell::RefCell::new(Vec::new()));
+        let workspace_view = workspace.root_view(cx).unwrap();
+        let _subscription = cx.update(|cx| {
+            let added_paths = added_paths.clone();
+            cx.subscribe(&workspace_view, move |_, event, _| {
+                if let workspace::Event::AddToAssistantContext(paths) = event {
+                    added_paths.borrow_mut().extend(
+                        paths
+                            .iter()
+                            .map(|path| path.path.to_string_lossy().into_owned()),
+                    );
+                }
+            })
+        });
 
-        panel.update(cx, |panel, cx| panel.new_file(&NewFile, cx));
+        select_path(&panel, "project_root/src", cx);
+        let readme = find_project_entry(&panel, "project_root/README.md", cx).unwrap();
         panel.update(cx, |panel, cx| {
-            assert!(panel.filename_editor.read(cx).is_focused(cx));
+            let selection = panel.selection.unwrap();
+            panel.marked_entries.insert(selection);
+            panel.marked_entries.insert(SelectedEntry {
+                worktree_id: selection.worktree_id,
+                entry_id: readme,
+            });
+            panel.add_to_assistant_context(&AddToAssistantContext, cx)
         });
-        panel
-            .update(cx, |panel, cx| {
-                panel
-                    .filename_editor
-                    .update(cx, |editor, cx| editor.set_text(excluded_file_path, cx));
-                panel.confirm_edit(cx).unwrap()
-            })
-            .await
+        cx.run_until_parked();
+        assert_eq!(*added_paths.borrow(), ["README.md", "src"]);
+    }
+
+    #[gpui::test]
+    async fn test_batch_operations(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/project_root",
+            json!({
+                "docs": { "guide.md": "" },
+                "notes.txt": "",
+                "old.txt": "",
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
             .unwrap();
+        cx.run_until_parked();
+        let worktree_id = cx.update(|cx| {
+            project
+                .read(cx)
+                .visible_worktrees(cx)
+                .next()
+                .unwrap()
+                .read(cx)
+                .id()
+        });
+
+        let apply = |operations, conflict_policy, cx: &mut VisualTestContext| {
+            let task = panel.update(cx, |panel, cx| {
+                panel.apply_batch_operations(worktree_id, operations, conflict_policy, cx)
+            });
+            cx.run_until_parked();
+            cx.background_executor.block(task)
+        };
 
+        let outcome = apply(
+            vec![
+                BatchOperation::Create {
+                    path: "notes.txt".into(),
+                    is_dir: false,
+                },
+                BatchOperation::Move {
+                    from: "old.txt".into(),
+                    to: "docs/old.txt".into(),
+                },
+            ],
+            ConflictPolicy::KeepBoth,
+            cx,
+        )
+        .unwrap();
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..13, cx),
-            &["v root1", "      .dockerignore"],
-            "Excluded dir should not be shown after opening a file in it"
+            outcome.applied,
+            [
+                BatchOperation::Create {
+                    path: "notes copy.txt".into(),
+                    is_dir: false,
+                },
+                BatchOperation::Move {
+                    from: "old.txt".into(),
+                    to: "docs/old.txt".into(),
+                },
+            ]
         );
-        panel.update(cx, |panel, cx| {
-            assert!(
-                !panel.filename_editor.read(cx).is_focused(cx),
-                "Should have closed the file name editor"
-            );
-        });
-        workspace
-            .update(cx, |workspace, cx| {
-                let active_entry_path = workspace
-                    .active_item(cx)
-                    .expect("should have opened and activated the excluded item")
-                    .act_as::<TestProjectItemView>(cx)
-                    .expect(
-                        "should have opened the corresponding project item for the excluded item",
-                    )
-                    .read(cx)
-                    .path
-                    .clone();
-                assert_eq!(
-                    active_entry_path.path.as_ref(),
-                    Path::new(excluded_file_path),
-                    "Should open the excluded file"
-                );
 
-                assert!(
-                    workspace.notification_ids().is_empty(),
-                    "Should have no notifications after opening an excluded file"
-                );
-            })
-            .unwrap();
+        let create_notes = BatchOperation::Create {
+            path: "notes.txt".into(),
+            is_dir: false,
+        };
+        let outcome = apply(vec![create_notes.clone()], ConflictPolicy::Skip, cx).unwrap();
+        assert_eq!(outcome.skipped, [create_notes.clone()]);
+        assert!(apply(vec![create_notes], ConflictPolicy::Fail, cx).is_err());
         assert!(
-            fs.is_file(Path::new("/root1/.git/COMMIT_EDITMSG")).await,
-            "Should have created the excluded file"
+            apply(
+                vec![BatchOperation::Move {
+                    from: "docs".into(),
+                    to: "docs/nested".into(),
+                }],
+                ConflictPolicy::Fail,
+                cx,
+            )
+            .is_err(),
+            "Directories can't be moved inside of themselves"
+        );
+
+        apply(
+            vec![BatchOperation::Delete {
+                path: "notes.txt".into(),
+                trash: false,
+            }],
+            ConflictPolicy::Fail,
+            cx,
+        )
+        .unwrap();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &["v project_root", "    > docs", "      notes copy.txt",]
         );
+    }
+
+    #[gpui::test]
+    async fn test_create_and_open(cx: &mut gpui::TestAppContext) {
+        init_test_with_editor(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/project_root",
+            json!({
+                "src": { "lib.rs": "" },
+            }),
+        )
+        .await;
 
-        select_path(&panel, "root1", cx);
-        panel.update(cx, |panel, cx| panel.new_directory(&NewDirectory, cx));
-        panel.update(cx, |panel, cx| {
-            assert!(panel.filename_editor.read(cx).is_focused(cx));
-        });
-        panel
-            .update(cx, |panel, cx| {
-                panel
-                    .filename_editor
-                    .update(cx, |editor, cx| editor.set_text(excluded_file_path, cx));
-                panel.confirm_edit(cx).unwrap()
-            })
-            .await
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
             .unwrap();
+        cx.run_until_parked();
+        let worktree_id =
+            cx.update(|cx| project.read(cx).worktrees().next().unwrap().read(cx).id());
+
+        let create_and_open = |contents: &str, cx: &mut VisualTestContext| {
+            let task = panel.update(cx, |panel, cx| {
+                panel.create_and_open(
+                    ProjectPath {
+                        worktree_id,
+                        path: Arc::from(Path::new("src/extracted.rs")),
+                    },
+                    contents.to_string(),
+                    cx,
+                )
+            });
+            cx.run_until_parked();
+            cx.background_executor.block(task).unwrap()
+        };
 
+        assert!(create_and_open("fn one() {}\n", cx).is_some());
+        assert!(create_and_open("fn two() {}\n", cx).is_some());
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..13, cx),
-            &["v root1", "      .dockerignore"],
-            "Should not change the project panel after trying to create an excluded directorya directory with the same name as the excluded file"
+            fs.load("/project_root/src/extracted.rs".as_ref())
+                .await
+                .unwrap(),
+            "fn one() {}\n"
+        );
+        assert_eq!(
+            fs.load("/project_root/src/extracted copy.rs".as_ref())
+                .await
+                .unwrap(),
+            "fn two() {}\n"
+        );
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v project_root",
+                "    v src",
+                "          extracted copy.rs  <== selected",
+                "          extracted.rs",
+                "          lib.rs",
+            ]
         );
-        panel.update(cx, |panel, cx| {
-            assert!(
-                !panel.filename_editor.read(cx).is_focused(cx),
-                "Should have closed the file name editor"
-            );
-        });
         workspace
             .update(cx, |workspace, cx| {
-                let notifications = workspace.notification_ids();
+                let active_path = workspace.active_item(cx).unwrap().project_path(cx).unwrap();
                 assert_eq!(
-                    notifications.len(),
-                    1,
-                    "Should receive one notification with the error message"
+                    active_path.path.as_ref(),
+                    Path::new("src/extracted copy.rs")
                 );
-                workspace.dismiss_notification(notifications.first().unwrap(), cx);
-                assert!(workspace.notification_ids().is_empty());
             })
             .unwrap();
+    }
 
-        select_path(&panel, "root1", cx);
-        panel.update(cx, |panel, cx| panel.new_directory(&NewDirectory, cx));
-        panel.update(cx, |panel, cx| {
-            assert!(panel.filename_editor.read(cx).is_focused(cx));
-        });
-        panel
-            .update(cx, |panel, cx| {
-                panel
-                    .filename_editor
-                    .update(cx, |editor, cx| editor.set_text(excluded_dir_path, cx));
-                panel.confirm_edit(cx).unwrap()
-            })
-            .await
+    #[gpui::test]
+    async fn test_code_owners(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/project_root",
+            json!({
+                ".github": {
+                    "CODEOWNERS": "# Default owners\n* @org/core\n/docs/ @org/docs # Writers\n*.css @design\ndocs/generated/\n",
+                },
+                "docs": {
+                    "guide.md": "",
+                    "style.css": "",
+                    "generated": { "api.md": "" },
+                },
+                "src": { "main.rs": "" },
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/project_root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
             .unwrap();
+        cx.run_until_parked();
+        render_rows(&panel, 0..4, cx);
 
+        let owners = |path: &str, cx: &mut VisualTestContext| {
+            panel.update(cx, |panel, cx| {
+                let worktree_id = panel
+                    .project
+                    .read(cx)
+                    .visible_worktrees(cx)
+                    .next()
+                    .unwrap()
+                    .read(cx)
+                    .id();
+                panel
+                    .code_owners_for_entry(worktree_id, Path::new(path))
+                    .map(|owners| owners.join(" "))
+            })
+        };
+        assert_eq!(owners("src/main.rs", cx).as_deref(), Some("@org/core"));
+        assert_eq!(owners("docs", cx).as_deref(), Some("@org/docs"));
+        assert_eq!(owners("docs/guide.md", cx).as_deref(), Some("@org/docs"));
+        assert_eq!(owners("docs/style.css", cx).as_deref(), Some("@design"));
         assert_eq!(
-            visible_entries_as_strings(&panel, 0..13, cx),
-            &["v root1", "      .dockerignore"],
-            "Should not change the project panel after trying to create an excluded directory"
+            owners("docs/generated/api.md", cx).as_deref(),
+            Some(""),
+            "Rules without owners leave paths unowned"
         );
-        panel.update(cx, |panel, cx| {
-            assert!(
-                !panel.filename_editor.read(cx).is_focused(cx),
-                "Should have closed the file name editor"
-            );
-        });
-        workspace
-            .update(cx, |workspace, cx| {
-                let notifications = workspace.notification_ids();
-                assert_eq!(
-                    notifications.len(),
-                    1,
-                    "Should receive one notification explaining that no directory is actually shown"
-                );
-                workspace.dismiss_notification(notifications.first().unwrap(), cx);
-                assert!(workspace.notification_ids().is_empty());
+    }
+
+    #[gpui::test]
+    fn test_mirrored_layout(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let is_mirrored = |dock, mirror_in_right_dock, cx: &mut gpui::TestAppContext| {
+            cx.update(|cx| {
+                cx.update_global::<SettingsStore, _>(|store, cx| {
+                    store.update_user_settings::<ProjectPanelSettings>(cx, |settings| {
+                        settings.dock = Some(dock);
+                        settings.mirror_in_right_dock = Some(mirror_in_right_dock);
+                    });
+                });
+                ProjectPanelSettings::get_global(cx).is_mirrored()
             })
-            .unwrap();
+        };
+        assert!(!is_mirrored(ProjectPanelDockPosition::Right, false, cx));
         assert!(
-            fs.is_dir(Path::new("/root1/excluded_dir")).await,
-            "Should have created the excluded directory"
+            !is_mirrored(ProjectPanelDockPosition::Left, true, cx),
+            "The layout is only mirrored in the right dock"
+        );
+        assert!(is_mirrored(ProjectPanelDockPosition::Right, true, cx));
+    }
+
+    #[test]
+    fn test_git_statuses_summary() {
+        assert_eq!(
+            git_statuses_summary(GitStatuses {
+                added: 2,
+                modified: 0,
+                conflict: 1,
+            }),
+            "1 conflicted, 2 added"
+        );
+    }
+
+    #[test]
+    fn test_git_status_glyph() {
+        assert_eq!(git_status_glyph(GitFileStatus::Added).0, "A");
+        assert_eq!(git_status_glyph(GitFileStatus::Modified).0, "M");
+        assert_eq!(git_status_glyph(GitFileStatus::Conflict).0, "!");
+    }
+
+    #[test]
+    fn test_format_count() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(1000), "1,000");
+        assert_eq!(format_count(12345), "12,345");
+        assert_eq!(format_count(1234567), "1,234,567");
+    }
+
+    #[test]
+    fn test_modification_age() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        let days_ago = |days| now - Duration::from_secs(days * 24 * 60 * 60);
+        assert_eq!(ModificationAge::new(now, now), ModificationAge::Day);
+        assert_eq!(
+            ModificationAge::new(days_ago(3), now),
+            ModificationAge::Week
+        );
+        assert_eq!(
+            ModificationAge::new(days_ago(20), now),
+            ModificationAge::Month
+        );
+        assert_eq!(
+            ModificationAge::new(days_ago(200), now),
+            ModificationAge::Year
+        );
+        assert_eq!(
+            ModificationAge::new(days_ago(400), now),
+            ModificationAge::Older
+        );
+        assert_eq!(
+            ModificationAge::new(now + Duration::from_secs(60), now),
+            ModificationAge::Day,
+            "Files modified in the future are the hottest"
+        );
+    }
+
+    #[test]
+    fn test_parse_deployment_checksums() {
+        let checksums =
+            parse_checksums("E3B0C442  ./index.html\n5d41402a *assets/logo.png\n\nmalformed\n");
+        assert_eq!(
+            checksums,
+            HashMap::from_iter([
+                (PathBuf::from("index.html"), "e3b0c442".to_string()),
+                (PathBuf::from("assets/logo.png"), "5d41402a".to_string()),
+            ])
         );
     }
 
@@ -4957,6 +16011,12 @@ mod tests {
         });
     }
 
+    /// Renders the rows as the list would, letting the row decorations be computed for them.
+    fn render_rows(panel: &View<ProjectPanel>, range: Range<usize>, cx: &mut VisualTestContext) {
+        panel.update(cx, |panel, cx| panel.set_rendered_range(range, cx));
+        cx.run_until_parked();
+    }
+
     fn select_path(panel: &View<ProjectPanel>, path: impl AsRef<Path>, cx: &mut VisualTestContext) {
         let path = path.as_ref();
         panel.update(cx, |panel, cx| {
@@ -4992,6 +16052,104 @@ mod tests {
         })
     }
 
+    /// Starts dragging the row at `path`, carrying the marked entries along like the rows do.
+    fn start_drag(
+        panel: &View<ProjectPanel>,
+        path: impl AsRef<Path>,
+        cx: &mut VisualTestContext,
+    ) -> DraggedSelection {
+        let path = path.as_ref();
+        let entry_id = find_project_entry(panel, path, cx)
+            .unwrap_or_else(|| panic!("no entry for path {path:?}"));
+        panel.update(cx, |panel, cx| {
+            let worktree_id = panel
+                .project
+                .read(cx)
+                .worktree_id_for_entry(entry_id, cx)
+                .unwrap();
+            DraggedSelection {
+                active_selection: SelectedEntry {
+                    worktree_id,
+                    entry_id,
+                },
+                marked_selections: Arc::new(panel.marked_entries.clone()),
+            }
+        })
+    }
+
+    /// Drops the dragged entries on the row at `path`, with the modifiers currently held.
+    fn drop_on(
+        panel: &View<ProjectPanel>,
+        drag: &DraggedSelection,
+        path: impl AsRef<Path>,
+        cx: &mut VisualTestContext,
+    ) {
+        let path = path.as_ref();
+        let entry_id = find_project_entry(panel, path, cx)
+            .unwrap_or_else(|| panic!("no entry for path {path:?}"));
+        panel.update(cx, |panel, cx| {
+            let is_file = panel
+                .project
+                .read(cx)
+                .worktree_for_entry(entry_id, cx)
+                .and_then(|worktree| worktree.read(cx).entry_for_id(entry_id).cloned())
+                .unwrap()
+                .is_file();
+            panel.drag_onto(drag, entry_id, is_file, cx);
+        });
+        cx.run_until_parked();
+    }
+
+    /// Moves the dragged entries over the row at `path`.
+    fn drag_entries_over(
+        panel: &View<ProjectPanel>,
+        path: impl AsRef<Path>,
+        cx: &mut VisualTestContext,
+    ) {
+        let path = path.as_ref();
+        let entry_id = find_project_entry(panel, path, cx)
+            .unwrap_or_else(|| panic!("no entry for path {path:?}"));
+        panel.update(cx, |panel, cx| {
+            let (worktree_id, entry) = {
+                let worktree = panel
+                    .project
+                    .read(cx)
+                    .worktree_for_entry(entry_id, cx)
+                    .unwrap()
+                    .read(cx);
+                (
+                    worktree.id(),
+                    worktree.entry_for_id(entry_id).unwrap().clone(),
+                )
+            };
+            panel.drag_entries_over(worktree_id, &entry.path, entry.is_file(), cx);
+        });
+    }
+
+    /// Moves external paths being dragged over the row at `path`.
+    fn drag_external_paths_over(
+        panel: &View<ProjectPanel>,
+        path: impl AsRef<Path>,
+        cx: &mut VisualTestContext,
+    ) {
+        let path = path.as_ref();
+        panel.update(cx, |panel, cx| {
+            for worktree in panel.project.read(cx).worktrees().collect::<Vec<_>>() {
+                let (worktree_id, relative_path, entry_id) = {
+                    let worktree = worktree.read(cx);
+                    let Ok(relative_path) = path.strip_prefix(worktree.root_name()) else {
+                        continue;
+                    };
+                    let entry = worktree.entry_for_path(relative_path).unwrap();
+                    (worktree.id(), entry.path.clone(), entry.id)
+                };
+                panel.drag_external_paths_over(worktree_id, entry_id, &relative_path, cx);
+                return;
+            }
+            panic!("no worktree for path {path:?}");
+        });
+    }
+
     fn visible_entries_as_strings(
         panel: &View<ProjectPanel>,
         range: Range<usize>,
@@ -5016,7 +16174,7 @@ mod tests {
                 }
 
                 let indent = "    ".repeat(details.depth);
-                let icon = if details.kind.is_dir() {
+                let icon = if details.kind.is_dir() || details.has_nested_files {
                     if details.is_expanded {
                         "v "
                     } else {
@@ -5050,6 +16208,107 @@ mod tests {
         result
     }
 
+    async fn insert_git_status_tree(fs: &FakeFs) {
+        fs.insert_tree(
+            "/root",
+            json!({
+                ".git": {},
+                ".gitignore": "ignored.txt",
+                "a.txt": "",
+                "b.txt": "",
+                "ignored.txt": "",
+                "dir1": {
+                    "added.txt": "",
+                    "sub": {
+                        "clean.txt": "",
+                        "conflict.txt": "",
+                    },
+                },
+                "dir2": {
+                    "added.txt": "",
+                    "modified.txt": "",
+                },
+            }),
+        )
+        .await;
+        fs.set_status_for_repo_via_git_operation(
+            Path::new("/root/.git"),
+            &[
+                (Path::new("a.txt"), GitFileStatus::Modified),
+                (Path::new("dir1/added.txt"), GitFileStatus::Added),
+                (Path::new("dir1/sub/conflict.txt"), GitFileStatus::Conflict),
+                (Path::new("dir2/added.txt"), GitFileStatus::Added),
+                (Path::new("dir2/modified.txt"), GitFileStatus::Modified),
+            ],
+        );
+    }
+
+    /// Like `visible_entries_as_strings`, with the status shown by the color of each name.
+    fn visible_entries_with_label_colors(
+        panel: &View<ProjectPanel>,
+        range: Range<usize>,
+        cx: &mut VisualTestContext,
+    ) -> Vec<String> {
+        let mut result = Vec::new();
+        panel.update(cx, |panel, cx| {
+            panel.for_each_visible_entry(range, cx, |_, details, _| {
+                let indent = "    ".repeat(details.depth);
+                let icon = if details.kind.is_dir() {
+                    if details.is_expanded {
+                        "v "
+                    } else {
+                        "> "
+                    }
+                } else {
+                    "  "
+                };
+                let color = match entry_git_aware_label_color(
+                    details.git_status,
+                    details.is_ignored,
+                    false,
+                ) {
+                    Color::Created => "  <== added",
+                    Color::Modified => "  <== modified",
+                    Color::Conflict => "  <== conflict",
+                    Color::Ignored => "  <== ignored",
+                    _ => "",
+                };
+                let selected = if details.is_selected {
+                    "  <== selected"
+                } else {
+                    ""
+                };
+                result.push(format!(
+                    "{indent}{icon}{}{color}{selected}",
+                    details.filename
+                ));
+            });
+        });
+        result
+    }
+
+    fn add_languages_with_comments(project: &Model<Project>, cx: &mut TestAppContext) {
+        let languages = project.read_with(cx, |project, _| project.languages().clone());
+        for (name, suffixes, line_comment) in [
+            ("Rust", vec!["rs"], "// "),
+            ("TOML", vec!["toml"], "# "),
+            ("Shell Script", vec!["sh"], "# "),
+        ] {
+            languages.add(Arc::new(Language::new(
+                LanguageConfig {
+                    name: name.into(),
+                    matcher: LanguageMatcher {
+                        path_suffixes: suffixes.into_iter().map(String::from).collect(),
+                        ..Default::default()
+                    },
+                    line_comments: vec![line_comment.into()],
+                    ..Default::default()
+                },
+                None,
+            )));
+        }
+    }
+
     fn init_test(cx: &mut TestAppContext) {
         cx.update(|cx| {
             let settings_store = SettingsStore::test(cx);
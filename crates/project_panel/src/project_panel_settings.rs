@@ -1,8 +1,15 @@
 use anyhow;
-use gpui::Pixels;
+use git::repository::GitFileStatus;
+use gpui::{px, Pixels};
+use project::DirectoriesPosition;
 use schemars::JsonSchema;
 use serde_derive::{Deserialize, Serialize};
 use settings::{Settings, SettingsSources};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
+use worktree::GitStatuses;
 
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, Copy, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -11,17 +18,50 @@ pub enum ProjectPanelDockPosition {
     Right,
 }
 
-#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct ProjectPanelSettings {
     pub button: bool,
     pub default_width: Pixels,
     pub dock: ProjectPanelDockPosition,
+    pub mirror_in_right_dock: bool,
     pub file_icons: bool,
     pub folder_icons: bool,
+    pub icon_size: EntryIconSize,
     pub git_status: bool,
+    pub git_status_propagation: GitStatusPropagation,
+    pub git_status_priority: Vec<GitStatusKind>,
+    pub git_status_icons: bool,
+    pub show_deleted_files: bool,
     pub indent_size: f32,
+    pub max_indent_depth: usize,
+    pub scroll_sensitivity: f32,
     pub auto_reveal_entries: bool,
+    pub selection_follows_expansion: bool,
     pub auto_fold_dirs: bool,
+    pub expand_top_level_dirs: bool,
+    pub expand_dirs: Vec<String>,
+    pub never_expand_dirs: Vec<String>,
+    pub auto_hide_on_open: bool,
+    pub open_new_files: OpenNewFiles,
+    pub close_editor_on_middle_click: bool,
+    pub delete_external_entries: bool,
+    pub enable_trash: bool,
+    pub peek_on_hover: bool,
+    pub modification_heat: bool,
+    pub low_disk_space_warning_mb: u64,
+    pub generated_paths: Vec<String>,
+    pub navigation_case_sensitivity: NavigationCaseSensitivity,
+    pub sort_by: EntrySortMode,
+    pub folders_position: FoldersPosition,
+    pub case_sensitive_sort: bool,
+    pub show_toolbar: bool,
+    pub show_diagnostics: ShowDiagnostics,
+    pub show_open_editors: bool,
+    pub markdown_link_template: String,
+    pub deployments: Vec<DeploymentMapping>,
+    pub encryption: EncryptionSettings,
+    pub license_headers: HashMap<String, String>,
+    pub file_nesting: BTreeMap<String, Vec<String>>,
     pub scrollbar: ScrollbarSettings,
 }
 
@@ -38,6 +78,185 @@ pub enum ShowScrollbar {
     Never,
 }
 
+/// How large the file and folder icons of entries are, regardless of the UI font size.
+///
+/// Default: medium
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryIconSize {
+    /// 12px
+    Small,
+    /// 16px
+    #[default]
+    Medium,
+    /// 20px
+    Large,
+}
+
+impl EntryIconSize {
+    pub fn pixels(self) -> Pixels {
+        match self {
+            EntryIconSize::Small => px(12.),
+            EntryIconSize::Medium => px(16.),
+            EntryIconSize::Large => px(20.),
+        }
+    }
+}
+
+/// Which ancestor directories of a changed file show its git status.
+///
+/// Default: all
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GitStatusPropagation {
+    /// Only show the status on the file itself.
+    None,
+    /// Show the status on the directory directly containing the file.
+    Nearest,
+    /// Show the status on every ancestor directory.
+    #[default]
+    All,
+}
+
+/// A git status of files, as shown on the directories containing them.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GitStatusKind {
+    Conflict,
+    Modified,
+    Added,
+}
+
+impl From<GitStatusKind> for GitFileStatus {
+    fn from(kind: GitStatusKind) -> Self {
+        match kind {
+            GitStatusKind::Conflict => GitFileStatus::Conflict,
+            GitStatusKind::Modified => GitFileStatus::Modified,
+            GitStatusKind::Added => GitFileStatus::Added,
+        }
+    }
+}
+
+/// How the entries of a directory are sorted, directories being placed by `folders_position`.
+///
+/// Default: name
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EntrySortMode {
+    /// Sort by name.
+    #[default]
+    Name,
+    /// Sort files by extension, then by name.
+    Extension,
+    /// Sort by modification time, the most recently modified first.
+    ModificationTime,
+    /// Sort files by size, the largest first.
+    Size,
+}
+
+/// Where directories are sorted relative to the files next to them.
+///
+/// Default: first
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FoldersPosition {
+    /// Before the files.
+    #[default]
+    First,
+    /// After the files.
+    Last,
+    /// Interleaved with the files.
+    Mixed,
+}
+
+impl From<FoldersPosition> for DirectoriesPosition {
+    fn from(position: FoldersPosition) -> Self {
+        match position {
+            FoldersPosition::First => DirectoriesPosition::First,
+            FoldersPosition::Last => DirectoriesPosition::Last,
+            FoldersPosition::Mixed => DirectoriesPosition::Mixed,
+        }
+    }
+}
+
+/// Which diagnostics are counted next to files and flagged on their ancestor folders.
+///
+/// Default: all
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShowDiagnostics {
+    /// Don't show diagnostics.
+    Off,
+    /// Only show errors.
+    Errors,
+    /// Show errors and warnings.
+    #[default]
+    All,
+}
+
+/// How paths typed or looked up in the project panel are matched against entry names.
+/// Entry names are always displayed with their on-disk case.
+///
+/// Default: auto
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NavigationCaseSensitivity {
+    /// Match case-insensitively on case-insensitive file systems.
+    #[default]
+    Auto,
+    /// Always match case-sensitively.
+    Sensitive,
+    /// Always match case-insensitively.
+    Insensitive,
+}
+
+/// Where files created in the project panel are opened.
+///
+/// Default: active_pane
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OpenNewFiles {
+    /// Open them in the active pane.
+    #[default]
+    ActivePane,
+    /// Open them in a new split of the active pane.
+    Split,
+    /// Leave them closed, keeping the focus in the panel.
+    None,
+}
+
+/// A directory of a worktree that is deployed to a remote target.
+/// Commands run in a shell inside the deployed directory, where `$ZED_FILE` is the absolute
+/// path of the entry and `$ZED_CUSTOM_DEPLOYMENT_PATH` its path relative to that directory.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct DeploymentMapping {
+    /// The deployed directory, relative to the worktree root.
+    pub local: PathBuf,
+    /// Command printing the checksums of the remote files in `sha256sum` format,
+    /// e.g. `ssh host 'cd /srv/app && find . -type f -exec sha256sum {} +'`.
+    pub checksum_command: String,
+    /// Command uploading an entry to the remote target.
+    #[serde(default)]
+    pub upload_command: Option<String>,
+    /// Command downloading an entry from the remote target.
+    #[serde(default)]
+    pub download_command: Option<String>,
+}
+
+/// Commands for files encrypted at rest (e.g. with git-crypt or sops), run in a shell in the
+/// worktree root with `$ZED_FILE` set to the absolute path of the file.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct EncryptionSettings {
+    /// Command decrypting the file in place, e.g. `sops --decrypt --in-place "$ZED_FILE"`.
+    ///
+    /// Default: null
+    pub decrypt_command: Option<String>,
+    /// Command encrypting the file in place, e.g. `sops --encrypt --in-place "$ZED_FILE"`.
+    ///
+    /// Default: null
+    pub encrypt_command: Option<String>,
+}
+
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct ScrollbarSettings {
     /// When to show the scrollbar in the project panel.
@@ -68,6 +287,11 @@ pub struct ProjectPanelSettingsContent {
     ///
     /// Default: left
     pub dock: Option<ProjectPanelDockPosition>,
+    /// Whether to lay entries out from right to left while the panel is docked right,
+    /// indenting them from the right edge and placing their icons on the right.
+    ///
+    /// Default: false
+    pub mirror_in_right_dock: Option<bool>,
     /// Whether to show file icons in the project panel.
     ///
     /// Default: true
@@ -76,29 +300,216 @@ pub struct ProjectPanelSettingsContent {
     ///
     /// Default: true
     pub folder_icons: Option<bool>,
+    /// How large the file and folder icons are: "small", "medium" or "large".
+    /// Unlike the rest of the panel, they don't scale with the UI font size.
+    ///
+    /// Default: medium
+    pub icon_size: Option<EntryIconSize>,
     /// Whether to show the git status in the project panel.
     ///
     /// Default: true
     pub git_status: Option<bool>,
+    /// Which ancestor directories of a changed file show its git status,
+    /// with conflicts taking precedence over modifications and additions.
+    ///
+    /// Default: all
+    pub git_status_propagation: Option<GitStatusPropagation>,
+    /// Which status a directory shows when its files have several, the first of the list
+    /// winning. Statuses left out of the list come after the listed ones, in the default order.
+    ///
+    /// Default: ["conflict", "modified", "added"]
+    pub git_status_priority: Option<Vec<GitStatusKind>>,
+    /// Whether to show a glyph after the entries with a git status, "A" for added,
+    /// "M" for modified and "!" for conflicted, in addition to their color.
+    ///
+    /// Default: false
+    pub git_status_icons: Option<bool>,
+    /// Whether to list tracked files that are deleted but not committed yet, struck
+    /// through, to restore them from git or stage their deletion. Listing them takes
+    /// a full status scan of the repository whenever it changes.
+    ///
+    /// Default: false
+    pub show_deleted_files: Option<bool>,
     /// Amount of indentation (in pixels) for nested items.
     ///
     /// Default: 20
     pub indent_size: Option<f32>,
+    /// How many levels deep entries are indented at most. Deeper entries are shown at that
+    /// depth, with a `…/` prefix and their full depth in the tooltip.
+    ///
+    /// Default: 16
+    pub max_indent_depth: Option<usize>,
+    /// How much faster or slower than elsewhere the panel scrolls with the mouse wheel or
+    /// trackpad, e.g. 0.5 to scroll half as far, which helps to keep track of huge trees.
+    ///
+    /// Default: 1.0
+    pub scroll_sensitivity: Option<f32>,
     /// Whether to reveal it in the project panel automatically,
     /// when a corresponding project entry becomes active.
     /// Gitignored entries are never auto revealed.
     ///
     /// Default: true
     pub auto_reveal_entries: Option<bool>,
+    /// Whether expanding a directory from the keyboard moves the selection to its first child,
+    /// and collapsing from the keyboard first moves the selection to the parent directory, then
+    /// collapses it with the selection staying on it, like the trees of other editors do.
+    ///
+    /// Default: false
+    pub selection_follows_expansion: Option<bool>,
     /// Whether to fold directories automatically
     /// when directory has only one directory inside.
     ///
     /// Default: false
     pub auto_fold_dirs: Option<bool>,
+    /// Whether to expand the top-level directories of a newly opened project
+    /// one level, keeping the gitignored ones collapsed.
+    ///
+    /// Default: false
+    pub expand_top_level_dirs: Option<bool>,
+    /// Globs of directories to expand once a newly opened project is loaded (e.g. `src`, or
+    /// `src/*` to also expand its subdirectories), along with their parents.
+    ///
+    /// Default: []
+    pub expand_dirs: Option<Vec<String>>,
+    /// Globs of directories (e.g. `vendor`, `**/node_modules`) to keep collapsed when a newly
+    /// opened project is loaded, even if `expand_dirs` or `expand_top_level_dirs` would
+    /// expand them.
+    ///
+    /// Default: []
+    pub never_expand_dirs: Option<Vec<String>>,
+    /// Whether to close the dock after a file is opened from the project panel.
+    /// Toggling the panel focus opens it again.
+    ///
+    /// Default: false
+    pub auto_hide_on_open: Option<bool>,
+    /// Where files created in the project panel are opened: "active_pane", "split"
+    /// for a new split of the active pane, or "none" to leave them closed.
+    ///
+    /// Default: active_pane
+    pub open_new_files: Option<OpenNewFiles>,
+    /// Whether middle-clicking a file closes the editors it is open in, like
+    /// middle-clicking their tabs does.
+    ///
+    /// Default: false
+    pub close_editor_on_middle_click: Option<bool>,
+    /// Whether deleting can remove entries outside of the project, reached through a symlinked
+    /// directory. Otherwise they are left out of deletions, and only the symlink itself can be
+    /// deleted.
+    ///
+    /// Default: false
+    pub delete_external_entries: Option<bool>,
+    /// Whether removing entries moves them to the trash by default. Otherwise the trash
+    /// action deletes them permanently too, like "Delete Permanently" always does.
+    ///
+    /// Default: true
+    pub enable_trash: Option<bool>,
+    /// Whether hovering the window edge temporarily shows the project panel
+    /// while its dock is closed, hiding it again once the pointer leaves.
+    ///
+    /// Default: false
+    pub peek_on_hover: Option<bool>,
+    /// Whether to mark entries with how recently they were modified, from hot (within a day)
+    /// to cold (over a year ago). Directories use their most recently modified file.
+    ///
+    /// Default: false
+    pub modification_heat: Option<bool>,
+    /// Below how many megabytes of free disk space on the volume of a worktree the panel warns
+    /// about it, as saves start failing once the disk is full. 0 turns the warning off. The
+    /// space left is shown in the tooltip of worktree roots either way.
+    ///
+    /// Default: 1024
+    pub low_disk_space_warning_mb: Option<u64>,
+    /// Globs of build artifacts and other generated paths (e.g. `target/**`, `dist/**`, `*.min.js`)
+    /// whose entries are rendered dimmed, to discourage accidental edits.
+    ///
+    /// Default: []
+    pub generated_paths: Option<Vec<String>>,
+    /// How typed paths are matched against entry names when navigating the panel:
+    /// "auto" follows the file system, "sensitive" and "insensitive" override it.
+    ///
+    /// Default: auto
+    pub navigation_case_sensitivity: Option<NavigationCaseSensitivity>,
+    /// How the entries of a directory are sorted: "name", "extension",
+    /// "modification_time" or "size".
+    ///
+    /// Default: name
+    pub sort_by: Option<EntrySortMode>,
+    /// Where directories are sorted relative to files: "first", "last", or "mixed"
+    /// to interleave them with files.
+    ///
+    /// Default: first
+    pub folders_position: Option<FoldersPosition>,
+    /// Whether entry names are sorted byte-wise, uppercase letters first, like `ls` does,
+    /// instead of ignoring case.
+    ///
+    /// Default: false
+    pub case_sensitive_sort: Option<bool>,
+    /// Whether to show a row of buttons above the entries to create a file or a folder,
+    /// collapse all directories and rescan the worktrees.
+    ///
+    /// Default: false
+    pub show_toolbar: Option<bool>,
+    /// Which diagnostics to count next to files, with the most severe one flagged on
+    /// the folders containing them.
+    ///
+    /// Default: all
+    pub show_diagnostics: Option<ShowDiagnostics>,
+    /// Whether to list the items open in the panes above the entries, to switch to them
+    /// or close them.
+    ///
+    /// Default: false
+    pub show_open_editors: Option<bool>,
+    /// Template used by "Copy as Markdown Link", where `{filename}` is replaced
+    /// with the entry name and `{path}` with its worktree-relative path.
+    ///
+    /// Default: "[{filename}]({path})"
+    pub markdown_link_template: Option<String>,
+    /// Directories deployed to a remote target. Files whose checksum differs from the remote
+    /// one are marked as out of sync, and get "Upload" and "Download" context actions.
+    ///
+    /// Default: []
+    pub deployments: Option<Vec<DeploymentMapping>>,
+    /// Commands behind the "Decrypt and Open" and "Re-encrypt" actions of encrypted files.
+    pub encryption: Option<EncryptionSettings>,
+    /// License headers expected at the top of source files, keyed by file extension.
+    /// Files missing theirs are flagged, and new files start with it.
+    ///
+    /// Default: {}
+    pub license_headers: Option<HashMap<String, String>>,
+    /// Related files nested under a source file of the same directory, keyed by the source
+    /// file pattern. The `*` of the nested file patterns stands for what the `*` of the source
+    /// pattern matched, e.g. {"*.ts": ["*.js", "*.d.ts"]} nests `foo.js` under `foo.ts`.
+    /// A file related to several source files is nested under the first one listed.
+    ///
+    /// Default: {}
+    pub file_nesting: Option<BTreeMap<String, Vec<String>>>,
     /// Scrollbar-related settings
     pub scrollbar: Option<ScrollbarSettingsContent>,
 }
 
+impl ProjectPanelSettings {
+    /// Whether entries are laid out from right to left.
+    pub fn is_mirrored(&self) -> bool {
+        self.mirror_in_right_dock && self.dock == ProjectPanelDockPosition::Right
+    }
+
+    /// The order in which statuses win on directories, `git_status_priority` followed by the
+    /// statuses it leaves out.
+    pub fn git_status_rollup_priority(&self) -> Vec<GitFileStatus> {
+        let mut priority = self
+            .git_status_priority
+            .iter()
+            .map(|kind| GitFileStatus::from(*kind))
+            .collect::<Vec<_>>();
+        for status in GitStatuses::DEFAULT_PRIORITY {
+            if !priority.contains(&status) {
+                priority.push(status);
+            }
+        }
+        priority
+    }
+}
+
 impl Settings for ProjectPanelSettings {
     const KEY: Option<&'static str> = Some("project_panel");
 
@@ -299,6 +299,7 @@ messages!(
     (UpdateContacts, Foreground),
     (UpdateDiagnosticSummary, Foreground),
     (UpdateDiffBase, Foreground),
+    (UpdateSharedTreeView, Foreground),
     (UpdateFollowers, Foreground),
     (UpdateInviteInfo, Foreground),
     (UpdateLanguageServer, Foreground),
@@ -507,6 +508,7 @@ entity_messages!(
     UpdateDiffBase,
     UpdateLanguageServer,
     UpdateProject,
+    UpdateSharedTreeView,
     UpdateProjectCollaborator,
     UpdateWorktree,
     UpdateWorktreeSettings,
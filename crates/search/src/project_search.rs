@@ -36,8 +36,8 @@ use util::paths::PathMatcher;
 use workspace::{
     item::{BreadcrumbText, Item, ItemEvent, ItemHandle, TabContentParams},
     searchable::{Direction, SearchableItem, SearchableItemHandle},
-    DeploySearch, ItemNavHistory, NewSearch, Pane, ToolbarItemEvent, ToolbarItemLocation,
-    ToolbarItemView, Workspace, WorkspaceId,
+    DeploySearch, DraggedSelection, ItemNavHistory, NewSearch, Pane, ToolbarItemEvent,
+    ToolbarItemLocation, ToolbarItemView, Workspace, WorkspaceId,
 };
 
 const MIN_INPUT_WIDTH_REMS: f32 = 15.;
@@ -45,7 +45,13 @@ const MAX_INPUT_WIDTH_REMS: f32 = 30.;
 
 actions!(
     project_search,
-    [SearchInNew, ToggleFocus, NextField, ToggleFilters]
+    [
+        SearchInNew,
+        ToggleFocus,
+        NextField,
+        ToggleFilters,
+        SelectInProjectPanel
+    ]
 );
 
 #[derive(Default)]
@@ -62,6 +68,12 @@ pub fn init(cx: &mut AppContext) {
         register_workspace_action(workspace, move |search_bar, _: &ToggleFilters, cx| {
             search_bar.toggle_filters(cx);
         });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &SelectInProjectPanel, cx| {
+                search_bar.select_in_project_panel(cx);
+            },
+        );
         register_workspace_action(workspace, move |search_bar, _: &ToggleCaseSensitive, cx| {
             search_bar.toggle_search_option(SearchOptions::CASE_SENSITIVE, cx);
         });
@@ -533,6 +545,37 @@ impl ProjectSearchView {
         });
     }
 
+    /// Marks every file with search results in the project panel, so they can be moved or
+    /// deleted together.
+    fn select_in_project_panel(&mut self, cx: &mut ViewContext<Self>) {
+        let model = self.model.read(cx);
+        let project = model.project.clone();
+        let mut matched_files = model
+            .excerpts
+            .read(cx)
+            .all_buffers()
+            .into_iter()
+            .filter_map(|buffer| {
+                let file = project::File::from_dyn(buffer.read(cx).file())?;
+                let entry_id = file.project_entry_id(cx)?;
+                Some((file.worktree_id(cx), file.path.clone(), entry_id))
+            })
+            .collect::<Vec<_>>();
+        if matched_files.is_empty() {
+            return;
+        }
+        matched_files.sort_by(|(worktree_a, path_a, _), (worktree_b, path_b, _)| {
+            worktree_a.cmp(worktree_b).then_with(|| path_a.cmp(path_b))
+        });
+        let entry_ids = matched_files
+            .into_iter()
+            .map(|(_, _, entry_id)| entry_id)
+            .collect();
+        project.update(cx, |_, cx| {
+            cx.emit(project::Event::SelectInProjectPanel(entry_ids))
+        });
+    }
+
     fn current_settings(&self) -> ProjectSearchSettings {
         ProjectSearchSettings {
             search_options: self.search_options,
@@ -1072,6 +1115,55 @@ impl ProjectSearchView {
         }
     }
 
+    /// Appends the dragged project entries to the include or exclude filter, directories as globs
+    /// matching everything inside of them.
+    fn add_dragged_entries_to_filter(
+        &mut self,
+        selection: &DraggedSelection,
+        panel: InputPanel,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let project = self.model.read(cx).project.read(cx);
+        let include_root = project.visible_worktrees(cx).count() > 1;
+        let globs = selection
+            .items()
+            .filter_map(|selected| {
+                let worktree = project.worktree_for_entry(selected.entry_id, cx)?;
+                let worktree = worktree.read(cx);
+                let entry = worktree.entry_for_id(selected.entry_id)?;
+                let mut path = if include_root {
+                    Path::new(worktree.root_name()).join(&entry.path)
+                } else {
+                    entry.path.to_path_buf()
+                };
+                if entry.is_dir() {
+                    path.push("**");
+                }
+                Some(path.to_string_lossy().into_owned())
+            })
+            .collect::<Vec<_>>();
+        if globs.is_empty() {
+            return;
+        }
+        let editor = match panel {
+            InputPanel::Include => &self.included_files_editor,
+            InputPanel::Exclude => &self.excluded_files_editor,
+            InputPanel::Query => return,
+        };
+        editor.update(cx, |editor, cx| {
+            let mut text = editor.text(cx).trim_end().trim_end_matches(',').to_string();
+            for glob in globs {
+                if !text.is_empty() {
+                    text.push_str(", ");
+                }
+                text.push_str(&glob);
+            }
+            editor.set_text(text, cx);
+        });
+        self.filters_enabled = true;
+        cx.notify();
+    }
+
     fn move_focus_to_results(&mut self, cx: &mut ViewContext<Self>) {
         if !self.results_editor.focus_handle(cx).is_focused(cx)
             && !self.model.read(cx).match_ranges.is_empty()
@@ -1161,6 +1253,20 @@ impl ProjectSearchBar {
         });
     }
 
+    fn add_dragged_entries_to_filter(
+        &mut self,
+        selection: &DraggedSelection,
+        panel: InputPanel,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.add_dragged_entries_to_filter(selection, panel, cx);
+                search_view.search(cx);
+            });
+        }
+    }
+
     fn toggle_search_option(&mut self, option: SearchOptions, cx: &mut ViewContext<Self>) -> bool {
         if let Some(search_view) = self.active_project_search.as_ref() {
             search_view.update(cx, |search_view, cx| {
@@ -1210,6 +1316,14 @@ impl ProjectSearchBar {
         }
     }
 
+    fn select_in_project_panel(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.select_in_project_panel(cx)
+            });
+        }
+    }
+
     fn move_focus_to_results(&self, cx: &mut ViewContext<Self>) {
         if let Some(search_view) = self.active_project_search.as_ref() {
             search_view.update(cx, |search_view, cx| {
@@ -1445,6 +1559,18 @@ impl Render for ProjectSearchBar {
                     }))
                     .tooltip(|cx| Tooltip::for_action("Go to next match", &SelectNextMatch, cx)),
             )
+            .child(
+                IconButton::new("project-search-select-in-project-panel", IconName::FileTree)
+                    .disabled(search.model.read(cx).match_ranges.is_empty())
+                    .on_click(cx.listener(|this, _, cx| this.select_in_project_panel(cx)))
+                    .tooltip(|cx| {
+                        Tooltip::for_action(
+                            "Select matched files in project panel",
+                            &SelectInProjectPanel,
+                            cx,
+                        )
+                    }),
+            )
             .child(
                 h_flex()
                     .min_w(rems_from_px(40.))
@@ -1527,6 +1653,12 @@ impl Render for ProjectSearchBar {
                         .border_1()
                         .border_color(search.border_color_for(InputPanel::Include, cx))
                         .rounded_lg()
+                        .drag_over::<DraggedSelection>(|style, _, cx| {
+                            style.bg(cx.theme().colors().drop_target_background)
+                        })
+                        .on_drop(cx.listener(|this, selection: &DraggedSelection, cx| {
+                            this.add_dragged_entries_to_filter(selection, InputPanel::Include, cx)
+                        }))
                         .child(self.render_text_input(&search.included_files_editor, cx))
                         .child(
                             SearchOptions::INCLUDE_IGNORED.as_button(
@@ -1550,6 +1682,12 @@ impl Render for ProjectSearchBar {
                         .border_1()
                         .border_color(search.border_color_for(InputPanel::Exclude, cx))
                         .rounded_lg()
+                        .drag_over::<DraggedSelection>(|style, _, cx| {
+                            style.bg(cx.theme().colors().drop_target_background)
+                        })
+                        .on_drop(cx.listener(|this, selection: &DraggedSelection, cx| {
+                            this.add_dragged_entries_to_filter(selection, InputPanel::Exclude, cx)
+                        }))
                         .child(self.render_text_input(&search.excluded_files_editor, cx)),
                 )
         });
@@ -2466,6 +2604,72 @@ pub mod tests {
             .unwrap();
     }
 
+    #[gpui::test]
+    async fn test_drop_entries_on_filters(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a": {
+                    "one.rs": "const ONE: usize = 1;",
+                },
+                "b": {
+                    "two.rs": "const TWO: usize = 2;",
+                },
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let selected_entry = |path: &str, cx: &mut TestAppContext| {
+            project.read_with(cx, |project, cx| {
+                let worktree = project.worktrees().next().unwrap().read(cx);
+                workspace::SelectedEntry {
+                    worktree_id: worktree.id(),
+                    entry_id: worktree.entry_for_path(path).unwrap().id,
+                }
+            })
+        };
+        let a_dir = selected_entry("a", cx);
+        let two_file = selected_entry("b/two.rs", cx);
+        let search = cx.new_model(|cx| ProjectSearch::new(project.clone(), cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view
+                    .included_files_editor
+                    .update(cx, |editor, cx| editor.set_text("*.rs,", cx));
+                search_view.add_dragged_entries_to_filter(
+                    &DraggedSelection {
+                        active_selection: a_dir,
+                        marked_selections: Arc::new([a_dir, two_file].into_iter().collect()),
+                    },
+                    InputPanel::Include,
+                    cx,
+                );
+                search_view.add_dragged_entries_to_filter(
+                    &DraggedSelection {
+                        active_selection: two_file,
+                        marked_selections: Arc::default(),
+                    },
+                    InputPanel::Exclude,
+                    cx,
+                );
+                assert!(search_view.filters_enabled);
+                assert_eq!(
+                    search_view.included_files_editor.read(cx).text(cx),
+                    "*.rs, a/**, b/two.rs"
+                );
+                assert_eq!(
+                    search_view.excluded_files_editor.read(cx).text(cx),
+                    "b/two.rs"
+                );
+            })
+            .unwrap();
+    }
+
     #[gpui::test]
     async fn test_search_query_history(cx: &mut TestAppContext) {
         init_test(cx);
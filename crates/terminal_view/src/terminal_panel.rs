@@ -29,7 +29,7 @@ use workspace::{
     item::Item,
     pane,
     ui::IconName,
-    DraggedTab, NewTerminal, Pane, ToggleZoom, Workspace,
+    DraggedSelection, DraggedTab, NewTerminal, Pane, ToggleZoom, Workspace,
 };
 
 use anyhow::Result;
@@ -169,6 +169,22 @@ impl TerminalPanel {
                             }
                         }
                     }
+                } else if let Some(selection) = dropped_item.downcast_ref::<DraggedSelection>() {
+                    let entry_paths = workspace
+                        .update(cx, |workspace, cx| {
+                            let project = workspace.project().read(cx);
+                            selection
+                                .items()
+                                .filter_map(|selected| {
+                                    let project_path =
+                                        project.path_for_entry(selected.entry_id, cx)?;
+                                    project.absolute_path(&project_path, cx)
+                                })
+                                .collect::<Vec<_>>()
+                        })
+                        .log_err()
+                        .unwrap_or_default();
+                    add_paths_to_terminal(pane, &entry_paths, cx);
                 } else if let Some(&entry_id) = dropped_item.downcast_ref::<ProjectEntryId>() {
                     if let Some(entry_path) = workspace
                         .update(cx, |workspace, cx| {
@@ -152,7 +152,7 @@ impl RenderOnce for IconButton {
                         IconSize::Indicator => Spacing::None.px(cx),
                         IconSize::XSmall => Spacing::XSmall.px(cx),
                         IconSize::Small => Spacing::XSmall.px(cx),
-                        IconSize::Medium => Spacing::XSmall.px(cx),
+                        IconSize::Medium | IconSize::Custom(_) => Spacing::XSmall.px(cx),
                     };
 
                     this.width((icon_size + padding * 2.).into())
@@ -1,10 +1,10 @@
 use crate::{
-    h_flex, prelude::*, v_flex, Icon, IconName, KeyBinding, Label, List, ListItem, ListSeparator,
-    ListSubHeader, WithRemSize,
+    h_flex, prelude::*, v_flex, Icon, IconName, KeyBinding, Label, ListItem, ListSeparator,
+    ListSubHeader, Tooltip, WithRemSize,
 };
 use gpui::{
     px, Action, AnyElement, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView,
-    IntoElement, Render, Subscription, View, VisualContext,
+    IntoElement, Render, ScrollHandle, Subscription, View, VisualContext,
 };
 use menu::{SelectFirst, SelectLast, SelectNext, SelectPrev};
 use settings::Settings;
@@ -21,6 +21,8 @@ enum ContextMenuItem {
         icon: Option<IconName>,
         handler: Rc<dyn Fn(Option<&FocusHandle>, &mut WindowContext)>,
         action: Option<Box<dyn Action>>,
+        disabled: bool,
+        tooltip: Option<SharedString>,
     },
     CustomEntry {
         entry_render: Box<dyn Fn(&mut WindowContext) -> AnyElement>,
@@ -36,6 +38,7 @@ pub struct ContextMenu {
     selected_index: Option<usize>,
     delayed: bool,
     clicked: bool,
+    scroll_handle: ScrollHandle,
     _on_blur_subscription: Subscription,
 }
 
@@ -68,6 +71,7 @@ impl ContextMenu {
                     selected_index: None,
                     delayed: false,
                     clicked: false,
+                    scroll_handle: ScrollHandle::new(),
                     _on_blur_subscription,
                 },
                 cx,
@@ -102,6 +106,8 @@ impl ContextMenu {
             handler: Rc::new(move |_, cx| handler(cx)),
             icon: None,
             action,
+            disabled: false,
+            tooltip: None,
         });
         self
     }
@@ -119,6 +125,8 @@ impl ContextMenu {
             handler: Rc::new(move |_, cx| handler(cx)),
             icon: None,
             action,
+            disabled: false,
+            tooltip: None,
         });
         self
     }
@@ -154,7 +162,37 @@ impl ContextMenu {
         self
     }
 
-    pub fn action(mut self, label: impl Into<SharedString>, action: Box<dyn Action>) -> Self {
+    pub fn action(self, label: impl Into<SharedString>, action: Box<dyn Action>) -> Self {
+        self.action_disabled_when(false, label, action)
+    }
+
+    /// Adds an action entry that is shown greyed out and cannot be selected when `disabled` is true.
+    pub fn action_disabled_when(
+        self,
+        disabled: bool,
+        label: impl Into<SharedString>,
+        action: Box<dyn Action>,
+    ) -> Self {
+        self.action_with_tooltip(disabled, label, action, None)
+    }
+
+    /// Adds an action entry that is shown greyed out, with `reason` as its tooltip.
+    pub fn disabled_action(
+        self,
+        label: impl Into<SharedString>,
+        action: Box<dyn Action>,
+        reason: impl Into<SharedString>,
+    ) -> Self {
+        self.action_with_tooltip(true, label, action, Some(reason.into()))
+    }
+
+    fn action_with_tooltip(
+        mut self,
+        disabled: bool,
+        label: impl Into<SharedString>,
+        action: Box<dyn Action>,
+        tooltip: Option<SharedString>,
+    ) -> Self {
         self.items.push(ContextMenuItem::Entry {
             toggled: None,
             label: label.into(),
@@ -167,6 +205,8 @@ impl ContextMenu {
                 cx.dispatch_action(action.boxed_clone());
             }),
             icon: None,
+            disabled,
+            tooltip,
         });
         self
     }
@@ -179,6 +219,8 @@ impl ContextMenu {
             action: Some(action.boxed_clone()),
             handler: Rc::new(move |_, cx| cx.dispatch_action(action.boxed_clone())),
             icon: Some(IconName::Link),
+            disabled: false,
+            tooltip: None,
         });
         self
     }
@@ -203,6 +245,7 @@ impl ContextMenu {
 
     fn select_first(&mut self, _: &SelectFirst, cx: &mut ViewContext<Self>) {
         self.selected_index = self.items.iter().position(|item| item.is_selectable());
+        self.scroll_to_selection();
         cx.notify();
     }
 
@@ -210,6 +253,7 @@ impl ContextMenu {
         for (ix, item) in self.items.iter().enumerate().rev() {
             if item.is_selectable() {
                 self.selected_index = Some(ix);
+                self.scroll_to_selection();
                 return Some(ix);
             }
         }
@@ -227,6 +271,7 @@ impl ContextMenu {
             for (ix, item) in self.items.iter().enumerate().skip(ix + 1) {
                 if item.is_selectable() {
                     self.selected_index = Some(ix);
+                    self.scroll_to_selection();
                     cx.notify();
                     break;
                 }
@@ -241,6 +286,7 @@ impl ContextMenu {
             for (ix, item) in self.items.iter().enumerate().take(ix).rev() {
                 if item.is_selectable() {
                     self.selected_index = Some(ix);
+                    self.scroll_to_selection();
                     cx.notify();
                     break;
                 }
@@ -250,6 +296,13 @@ impl ContextMenu {
         }
     }
 
+    /// Keeps the selected item visible when the menu is taller than the window.
+    fn scroll_to_selection(&self) {
+        if let Some(ix) = self.selected_index {
+            self.scroll_handle.scroll_to_item(ix);
+        }
+    }
+
     pub fn on_action_dispatch(&mut self, dispatched: &Box<dyn Action>, cx: &mut ViewContext<Self>) {
         if self.clicked {
             cx.propagate();
@@ -259,6 +312,7 @@ impl ContextMenu {
         if let Some(ix) = self.items.iter().position(|item| {
             if let ContextMenuItem::Entry {
                 action: Some(action),
+                disabled: false,
                 ..
             } = item
             {
@@ -293,7 +347,7 @@ impl ContextMenuItem {
             ContextMenuItem::Separator => false,
             ContextMenuItem::Label { .. } => false,
             ContextMenuItem::Header(_) => false,
-            ContextMenuItem::Entry { .. } => true,
+            ContextMenuItem::Entry { disabled, .. } => !disabled,
             ContextMenuItem::CustomEntry { selectable, .. } => *selectable,
         }
     }
@@ -320,6 +374,7 @@ impl Render for ContextMenu {
                         for item in self.items.iter() {
                             if let ContextMenuItem::Entry {
                                 action: Some(action),
+                                disabled: false,
                                 ..
                             } = item
                             {
@@ -332,122 +387,154 @@ impl Render for ContextMenu {
                         el
                     })
                     .flex_none()
-                    .child(List::new().children(self.items.iter_mut().enumerate().map(
-                        |(ix, item)| {
-                            match item {
-                                ContextMenuItem::Separator => ListSeparator.into_any_element(),
-                                ContextMenuItem::Header(header) => {
-                                    ListSubHeader::new(header.clone())
-                                        .inset(true)
-                                        .into_any_element()
-                                }
-                                ContextMenuItem::Label(label) => ListItem::new(ix)
-                                    .inset(true)
-                                    .disabled(true)
-                                    .child(Label::new(label.clone()))
-                                    .into_any_element(),
-                                ContextMenuItem::Entry {
-                                    toggled,
-                                    label,
-                                    handler,
-                                    icon,
-                                    action,
-                                } => {
-                                    let handler = handler.clone();
-                                    let menu = cx.view().downgrade();
-
-                                    let label_element = if let Some(icon) = icon {
-                                        h_flex()
-                                            .gap_1()
-                                            .child(Label::new(label.clone()))
-                                            .child(Icon::new(*icon))
+                    .child(
+                        v_flex()
+                            .id("context-menu-items")
+                            .w_full()
+                            .py_1()
+                            .max_h(cx.viewport_size().height)
+                            .overflow_y_scroll()
+                            .track_scroll(&self.scroll_handle)
+                            .children(self.items.iter_mut().enumerate().map(|(ix, item)| {
+                                match item {
+                                    ContextMenuItem::Separator => ListSeparator.into_any_element(),
+                                    ContextMenuItem::Header(header) => {
+                                        ListSubHeader::new(header.clone())
+                                            .inset(true)
                                             .into_any_element()
-                                    } else {
-                                        Label::new(label.clone()).into_any_element()
-                                    };
-
-                                    ListItem::new(ix)
+                                    }
+                                    ContextMenuItem::Label(label) => ListItem::new(ix)
                                         .inset(true)
-                                        .selected(Some(ix) == self.selected_index)
-                                        .when_some(*toggled, |list_item, toggled| {
-                                            list_item.start_slot(if toggled {
-                                                v_flex().flex_none().child(
-                                                    Icon::new(IconName::Check).color(Color::Accent),
-                                                )
-                                            } else {
-                                                v_flex()
-                                                    .flex_none()
-                                                    .size(IconSize::default().rems())
-                                            })
-                                        })
-                                        .child(
+                                        .disabled(true)
+                                        .child(Label::new(label.clone()))
+                                        .into_any_element(),
+                                    ContextMenuItem::Entry {
+                                        toggled,
+                                        label,
+                                        handler,
+                                        icon,
+                                        action,
+                                        disabled,
+                                        tooltip,
+                                    } => {
+                                        let handler = handler.clone();
+                                        let menu = cx.view().downgrade();
+                                        let disabled = *disabled;
+                                        let label_color = if disabled {
+                                            Color::Disabled
+                                        } else {
+                                            Color::Default
+                                        };
+
+                                        let label_element = if let Some(icon) = icon {
                                             h_flex()
-                                                .w_full()
-                                                .justify_between()
-                                                .child(label_element)
-                                                .debug_selector(|| format!("MENU_ITEM-{}", label))
-                                                .children(action.as_ref().and_then(|action| {
-                                                    self.action_context
-                                                        .as_ref()
-                                                        .map(|focus| {
-                                                            KeyBinding::for_action_in(
-                                                                &**action, focus, cx,
-                                                            )
-                                                        })
-                                                        .unwrap_or_else(|| {
-                                                            KeyBinding::for_action(&**action, cx)
-                                                        })
-                                                        .map(|binding| div().ml_4().child(binding))
-                                                })),
-                                        )
-                                        .on_click({
-                                            let context = self.action_context.clone();
-                                            move |_, cx| {
-                                                handler(context.as_ref(), cx);
-                                                menu.update(cx, |menu, cx| {
-                                                    menu.clicked = true;
-                                                    cx.emit(DismissEvent);
-                                                })
-                                                .ok();
-                                            }
-                                        })
-                                        .into_any_element()
-                                }
-                                ContextMenuItem::CustomEntry {
-                                    entry_render,
-                                    handler,
-                                    selectable,
-                                } => {
-                                    let handler = handler.clone();
-                                    let menu = cx.view().downgrade();
-                                    ListItem::new(ix)
-                                        .inset(true)
-                                        .selected(if *selectable {
-                                            Some(ix) == self.selected_index
+                                                .gap_1()
+                                                .child(Label::new(label.clone()).color(label_color))
+                                                .child(Icon::new(*icon).color(label_color))
+                                                .into_any_element()
                                         } else {
-                                            false
-                                        })
-                                        .selectable(*selectable)
-                                        .on_click({
-                                            let context = self.action_context.clone();
-                                            let selectable = *selectable;
-                                            move |_, cx| {
-                                                if selectable {
-                                                    handler(context.as_ref(), cx);
-                                                    menu.update(cx, |menu, cx| {
-                                                        menu.clicked = true;
-                                                        cx.emit(DismissEvent);
+                                            Label::new(label.clone())
+                                                .color(label_color)
+                                                .into_any_element()
+                                        };
+
+                                        ListItem::new(ix)
+                                            .inset(true)
+                                            .disabled(disabled)
+                                            .selected(Some(ix) == self.selected_index)
+                                            .when_some(tooltip.clone(), |list_item, tooltip| {
+                                                list_item.tooltip(move |cx| {
+                                                    Tooltip::text(tooltip.clone(), cx)
+                                                })
+                                            })
+                                            .when_some(*toggled, |list_item, toggled| {
+                                                list_item.start_slot(if toggled {
+                                                    v_flex().flex_none().child(
+                                                        Icon::new(IconName::Check)
+                                                            .color(Color::Accent),
+                                                    )
+                                                } else {
+                                                    v_flex()
+                                                        .flex_none()
+                                                        .size(IconSize::default().rems())
+                                                })
+                                            })
+                                            .child(
+                                                h_flex()
+                                                    .w_full()
+                                                    .justify_between()
+                                                    .child(label_element)
+                                                    .debug_selector(|| {
+                                                        format!("MENU_ITEM-{}", label)
                                                     })
-                                                    .ok();
+                                                    .children(action.as_ref().and_then(|action| {
+                                                        self.action_context
+                                                            .as_ref()
+                                                            .map(|focus| {
+                                                                KeyBinding::for_action_in(
+                                                                    &**action, focus, cx,
+                                                                )
+                                                            })
+                                                            .unwrap_or_else(|| {
+                                                                KeyBinding::for_action(
+                                                                    &**action, cx,
+                                                                )
+                                                            })
+                                                            .map(|binding| {
+                                                                div().ml_4().child(binding)
+                                                            })
+                                                    })),
+                                            )
+                                            .when(!disabled, |list_item| {
+                                                list_item.on_click({
+                                                    let context = self.action_context.clone();
+                                                    move |_, cx| {
+                                                        handler(context.as_ref(), cx);
+                                                        menu.update(cx, |menu, cx| {
+                                                            menu.clicked = true;
+                                                            cx.emit(DismissEvent);
+                                                        })
+                                                        .ok();
+                                                    }
+                                                })
+                                            })
+                                            .into_any_element()
+                                    }
+                                    ContextMenuItem::CustomEntry {
+                                        entry_render,
+                                        handler,
+                                        selectable,
+                                    } => {
+                                        let handler = handler.clone();
+                                        let menu = cx.view().downgrade();
+                                        ListItem::new(ix)
+                                            .inset(true)
+                                            .selected(if *selectable {
+                                                Some(ix) == self.selected_index
+                                            } else {
+                                                false
+                                            })
+                                            .selectable(*selectable)
+                                            .on_click({
+                                                let context = self.action_context.clone();
+                                                let selectable = *selectable;
+                                                move |_, cx| {
+                                                    if selectable {
+                                                        handler(context.as_ref(), cx);
+                                                        menu.update(cx, |menu, cx| {
+                                                            menu.clicked = true;
+                                                            cx.emit(DismissEvent);
+                                                        })
+                                                        .ok();
+                                                    }
                                                 }
-                                            }
-                                        })
-                                        .child(entry_render(cx))
-                                        .into_any_element()
+                                            })
+                                            .child(entry_render(cx))
+                                            .into_any_element()
+                                    }
                                 }
-                            }
-                        },
-                    ))),
+                            })),
+                    ),
             ),
         )
     }
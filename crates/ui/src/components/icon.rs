@@ -64,6 +64,8 @@ pub enum IconSize {
     #[default]
     /// 16px
     Medium,
+    /// A size in [`Rems`], for icons sized by user settings.
+    Custom(Rems),
 }
 
 impl IconSize {
@@ -73,6 +75,7 @@ impl IconSize {
             IconSize::XSmall => rems_from_px(12.),
             IconSize::Small => rems_from_px(14.),
             IconSize::Medium => rems_from_px(16.),
+            IconSize::Custom(size) => size,
         }
     }
 }
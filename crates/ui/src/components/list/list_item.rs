@@ -36,6 +36,7 @@ pub struct ListItem {
     on_secondary_mouse_down: Option<Box<dyn Fn(&MouseDownEvent, &mut WindowContext) + 'static>>,
     children: SmallVec<[AnyElement; 2]>,
     selectable: bool,
+    mirrored: bool,
 }
 
 impl ListItem {
@@ -58,6 +59,7 @@ impl ListItem {
             tooltip: None,
             children: SmallVec::new(),
             selectable: true,
+            mirrored: false,
         }
     }
 
@@ -104,6 +106,13 @@ impl ListItem {
         self
     }
 
+    /// Lays the item out from right to left, indenting it from the right edge
+    /// and placing the toggle and the start slot on the right side.
+    pub fn mirrored(mut self, mirrored: bool) -> Self {
+        self.mirrored = mirrored;
+        self
+    }
+
     pub fn toggle(mut self, toggle: impl Into<Option<bool>>) -> Self {
         self.toggle = toggle.into();
         self
@@ -155,14 +164,18 @@ impl ParentElement for ListItem {
 
 impl RenderOnce for ListItem {
     fn render(self, cx: &mut WindowContext) -> impl IntoElement {
+        let indent = self.indent_level as f32 * self.indent_step_size;
         h_flex()
             .id(self.id)
             .w_full()
             .relative()
             // When an item is inset draw the indent spacing outside of the item
             .when(self.inset, |this| {
-                this.ml(self.indent_level as f32 * self.indent_step_size)
-                    .px_2()
+                if self.mirrored {
+                    this.mr(indent).px_2()
+                } else {
+                    this.ml(indent).px_2()
+                }
             })
             .when(!self.inset && !self.disabled, |this| {
                 this
@@ -186,6 +199,7 @@ impl RenderOnce for ListItem {
                     .relative()
                     .gap_1()
                     .px_2()
+                    .when(self.mirrored, |this| this.flex_row_reverse())
                     .map(|this| match self.spacing {
                         ListItemSpacing::Dense => this,
                         ListItemSpacing::Sparse => this.py_1(),
@@ -220,16 +234,24 @@ impl RenderOnce for ListItem {
                     .map(|this| {
                         if self.inset {
                             this.rounded_md()
+                        } else if self.mirrored {
+                            this.mr(indent)
                         } else {
                             // When an item is not inset draw the indent spacing inside of the item
-                            this.ml(self.indent_level as f32 * self.indent_step_size)
+                            this.ml(indent)
                         }
                     })
                     .children(self.toggle.map(|is_open| {
                         div()
                             .flex()
                             .absolute()
-                            .left(rems(-1.))
+                            .map(|this| {
+                                if self.mirrored {
+                                    this.right(rems(-1.))
+                                } else {
+                                    this.left(rems(-1.))
+                                }
+                            })
                             .when(is_open, |this| this.visible_on_hover(""))
                             .child(Disclosure::new("toggle", is_open).on_toggle(self.on_toggle))
                     }))
@@ -240,6 +262,7 @@ impl RenderOnce for ListItem {
                             .flex_basis(relative(0.25))
                             .gap_1()
                             .overflow_hidden()
+                            .when(self.mirrored, |this| this.flex_row_reverse())
                             .children(self.start_slot)
                             .children(self.children),
                     )
@@ -260,7 +283,13 @@ impl RenderOnce for ListItem {
                             h_flex()
                                 .h_full()
                                 .absolute()
-                                .right_2()
+                                .map(|this| {
+                                    if self.mirrored {
+                                        this.left_2()
+                                    } else {
+                                        this.right_2()
+                                    }
+                                })
                                 .top_0()
                                 .visible_on_hover("list_item")
                                 .child(end_hover_slot),
@@ -15,6 +15,7 @@ use ui::{h_flex, ContextMenu, IconButton, Tooltip};
 use ui::{prelude::*, right_click_menu};
 
 const RESIZE_HANDLE_SIZE: Pixels = Pixels(6.);
+const PEEK_STRIP_SIZE: Pixels = Pixels(4.);
 
 pub enum PanelEvent {
     ZoomIn,
@@ -42,6 +43,10 @@ pub trait Panel: FocusableView + EventEmitter<PanelEvent> {
     fn starts_open(&self, _cx: &WindowContext) -> bool {
         false
     }
+    /// Whether hovering the window edge of a closed dock should temporarily show this panel.
+    fn peek_on_hover(&self, _cx: &WindowContext) -> bool {
+        false
+    }
     fn set_zoomed(&mut self, _zoomed: bool, _cx: &mut ViewContext<Self>) {}
     fn set_active(&mut self, _active: bool, _cx: &mut ViewContext<Self>) {}
 }
@@ -61,6 +66,7 @@ pub trait PanelHandle: Send + Sync {
     fn icon_tooltip(&self, cx: &WindowContext) -> Option<&'static str>;
     fn toggle_action(&self, cx: &WindowContext) -> Box<dyn Action>;
     fn icon_label(&self, cx: &WindowContext) -> Option<String>;
+    fn peek_on_hover(&self, cx: &WindowContext) -> bool;
     fn focus_handle(&self, cx: &AppContext) -> FocusHandle;
     fn to_any(&self) -> AnyView;
 }
@@ -125,6 +131,10 @@ where
         self.read(cx).icon_label(cx)
     }
 
+    fn peek_on_hover(&self, cx: &WindowContext) -> bool {
+        self.read(cx).peek_on_hover(cx)
+    }
+
     fn to_any(&self) -> AnyView {
         self.clone().into()
     }
@@ -146,6 +156,8 @@ pub struct Dock {
     position: DockPosition,
     panel_entries: Vec<PanelEntry>,
     is_open: bool,
+    /// Whether the dock is only open while hovered, see [`Panel::peek_on_hover`].
+    peeking: bool,
     active_panel_index: usize,
     focus_handle: FocusHandle,
     pub(crate) serialized_dock: Option<DockData>,
@@ -214,6 +226,7 @@ impl Dock {
                 panel_entries: Default::default(),
                 active_panel_index: 0,
                 is_open: false,
+                peeking: false,
                 focus_handle: focus_handle.clone(),
                 _subscriptions: [focus_subscription, zoom_subscription],
                 serialized_dock: None,
@@ -301,6 +314,7 @@ impl Dock {
     }
 
     pub(crate) fn set_open(&mut self, open: bool, cx: &mut ViewContext<Self>) {
+        self.peeking = false;
         if open != self.is_open {
             self.is_open = open;
             if let Some(active_panel) = self.panel_entries.get(self.active_panel_index) {
@@ -311,6 +325,19 @@ impl Dock {
         }
     }
 
+    fn peek(&mut self, cx: &mut ViewContext<Self>) {
+        if !self.is_open {
+            self.set_open(true, cx);
+            self.peeking = true;
+        }
+    }
+
+    fn end_peek(&mut self, cx: &mut ViewContext<Self>) {
+        if self.peeking {
+            self.set_open(false, cx);
+        }
+    }
+
     pub fn set_panel_zoomed(&mut self, panel: &AnyView, zoomed: bool, cx: &mut ViewContext<Self>) {
         for entry in &mut self.panel_entries {
             if entry.panel.panel_id() == panel.entity_id() {
@@ -622,8 +649,16 @@ impl Render for Dock {
             };
 
             div()
+                .id("dock")
                 .key_context(dispatch_context)
                 .track_focus(&self.focus_handle)
+                .when(self.peeking, |this| {
+                    this.on_hover(cx.listener(|dock, hovered, cx| {
+                        if !*hovered {
+                            dock.end_peek(cx);
+                        }
+                    }))
+                })
                 .flex()
                 .bg(cx.theme().colors().panel_background)
                 .border_color(cx.theme().colors().border)
@@ -652,9 +687,28 @@ impl Render for Dock {
                 )
                 .when(self.resizeable, |this| this.child(create_resize_handle()))
         } else {
+            let peek_on_hover = self
+                .active_panel()
+                .map_or(false, |panel| panel.peek_on_hover(cx));
             div()
+                .id("dock")
                 .key_context(dispatch_context)
                 .track_focus(&self.focus_handle)
+                .when(peek_on_hover, |this| {
+                    this.child(
+                        div()
+                            .id("peek-strip")
+                            .map(|this| match self.position().axis() {
+                                Axis::Horizontal => this.w(PEEK_STRIP_SIZE).h_full(),
+                                Axis::Vertical => this.h(PEEK_STRIP_SIZE).w_full(),
+                            })
+                            .on_hover(cx.listener(|dock, hovered, cx| {
+                                if *hovered {
+                                    dock.peek(cx);
+                                }
+                            })),
+                    )
+                })
         }
     }
 }
@@ -549,6 +549,8 @@ pub enum Event {
     ContactRequestedJoin(u64),
     WorkspaceCreated(WeakView<Workspace>),
     SpawnTask(SpawnInTerminal),
+    /// Files or directories to add to the context of the assistant.
+    AddToAssistantContext(Vec<ProjectPath>),
     OpenBundledFile {
         text: Cow<'static, str>,
         title: &'static str,
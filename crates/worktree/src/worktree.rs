@@ -47,7 +47,7 @@ use std::{
     ffi::OsStr,
     fmt,
     future::Future,
-    mem,
+    io, mem,
     ops::{AddAssign, Deref, DerefMut, Sub},
     path::{Path, PathBuf},
     pin::Pin,
@@ -197,6 +197,10 @@ pub struct RepositoryEntry {
     ///     work_directory: pointing to "" entry
     ///     location_in_repo: Some("my_sub_folder_1/project_root")
     pub(crate) location_in_repo: Option<Arc<Path>>,
+
+    /// Work directories of the submodules registered in the repository, relative to the
+    /// worktree root. Like location_in_repo, they are only known locally.
+    pub(crate) submodule_work_directories: Arc<[Arc<Path>]>,
 }
 
 impl RepositoryEntry {
@@ -208,6 +212,13 @@ impl RepositoryEntry {
         *self.work_directory
     }
 
+    /// Whether the repository with the given work directory is a submodule of this one.
+    pub fn has_submodule(&self, work_directory: &Path) -> bool {
+        self.submodule_work_directories
+            .iter()
+            .any(|submodule| submodule.as_ref() == work_directory)
+    }
+
     pub fn work_directory(&self, snapshot: &Snapshot) -> Option<RepositoryWorkDirectory> {
         snapshot
             .entry_for_id(self.work_directory_id())
@@ -299,6 +310,8 @@ pub struct LocalSnapshot {
     /// All of the git repositories in the worktree, indexed by the project entry
     /// id of their parent directory.
     git_repositories: TreeMap<ProjectEntryId, LocalRepositoryEntry>,
+    /// Directories that could not be read because of a lack of permissions.
+    inaccessible_dirs: HashSet<Arc<Path>>,
 }
 
 struct BackgroundScannerState {
@@ -414,6 +427,7 @@ impl Worktree {
             let mut snapshot = LocalSnapshot {
                 ignores_by_parent_abs_path: Default::default(),
                 git_repositories: Default::default(),
+                inaccessible_dirs: Default::default(),
                 snapshot: Snapshot::new(
                     cx.entity_id().as_u64(),
                     abs_path
@@ -1214,6 +1228,10 @@ impl LocalWorktree {
         changes.into()
     }
 
+    pub fn is_scanning(&self) -> bool {
+        *self.is_scanning.1.borrow()
+    }
+
     pub fn scan_complete(&self) -> impl Future<Output = ()> {
         let mut is_scanning_rx = self.is_scanning.1.clone();
         async move {
@@ -1236,6 +1254,10 @@ impl LocalWorktree {
         self.settings.clone()
     }
 
+    pub fn is_case_sensitive(&self) -> bool {
+        self.fs_case_sensitive
+    }
+
     pub fn local_git_repo(&self, path: &Path) -> Option<Arc<dyn GitRepository>> {
         self.repo_for_path(path)
             .map(|(_, entry)| entry.repo_ptr.clone())
@@ -1254,6 +1276,16 @@ impl LocalWorktree {
 
         cx.spawn(|this, mut cx| async move {
             let abs_path = abs_path?;
+            if fs
+                .metadata(&abs_path)
+                .await?
+                .map_or(false, |metadata| metadata.is_special_file)
+            {
+                return Err(anyhow!(
+                    "{} is a FIFO, socket or device and can't be opened",
+                    abs_path.display()
+                ));
+            }
             let text = fs.load(&abs_path).await?;
             let mut index_task = None;
             let snapshot = this.update(&mut cx, |this, _| this.as_local().unwrap().snapshot())?;
@@ -2055,6 +2087,7 @@ impl Snapshot {
                             // the location_in_repo field, since git operations don't happen locally
                             // anyway.
                             location_in_repo: None,
+                            submodule_work_directories: Arc::from(Vec::new()),
                         },
                     )
                 }
@@ -2071,6 +2104,10 @@ impl Snapshot {
         Ok(())
     }
 
+    pub fn entry_count(&self) -> usize {
+        self.entries_by_path.summary().count
+    }
+
     pub fn file_count(&self) -> usize {
         self.entries_by_path.summary().file_count
     }
@@ -2194,10 +2231,21 @@ impl Snapshot {
     /// Updates the `git_status` of the given entries such that files'
     /// statuses bubble up to their ancestor directories.
     pub fn propagate_git_statuses(&self, result: &mut [Entry]) {
+        self.propagate_git_statuses_with_priority(result, &GitStatuses::DEFAULT_PRIORITY)
+    }
+
+    /// Like [`Self::propagate_git_statuses`], with directories showing the first status of
+    /// `priority` among the files inside of them.
+    pub fn propagate_git_statuses_with_priority(
+        &self,
+        result: &mut [Entry],
+        priority: &[GitFileStatus],
+    ) {
         let mut cursor = self
             .entries_by_path
             .cursor::<(TraversalProgress, GitStatuses)>();
         let mut entry_stack = Vec::<(usize, GitStatuses)>::new();
+        let nested_repositories = self.nested_repository_statuses();
 
         let mut result_ix = 0;
         loop {
@@ -2224,17 +2272,12 @@ impl Snapshot {
                     &(),
                 );
 
-                let statuses = cursor.start().1 - prev_statuses;
+                // Changes inside nested repositories are not changes of the containing one.
+                let statuses = cursor.start().1
+                    - prev_statuses
+                    - nested_repositories.under(&result[entry_ix].path);
 
-                result[entry_ix].git_status = if statuses.conflict > 0 {
-                    Some(GitFileStatus::Conflict)
-                } else if statuses.modified > 0 {
-                    Some(GitFileStatus::Modified)
-                } else if statuses.added > 0 {
-                    Some(GitFileStatus::Added)
-                } else {
-                    None
-                };
+                result[entry_ix].git_status = statuses.rollup(priority);
             } else {
                 if result[result_ix].is_dir() {
                     cursor.seek_forward(
@@ -2249,6 +2292,84 @@ impl Snapshot {
         }
     }
 
+    /// Updates the `git_status` of the given directories to the statuses of the files
+    /// directly inside of them, so that statuses only bubble up one level.
+    pub fn propagate_nearest_git_statuses(&self, result: &mut [Entry], priority: &[GitFileStatus]) {
+        for entry in result.iter_mut().filter(|entry| entry.is_dir()) {
+            entry.git_status = self.nearest_git_statuses(&entry.path).rollup(priority);
+        }
+    }
+
+    /// Counts the statuses of the files under `path`, leaving out the repositories nested in it.
+    pub fn propagated_git_statuses(
+        &self,
+        path: &Path,
+        nested_repositories: &NestedRepositoryStatuses,
+    ) -> GitStatuses {
+        self.subtree_git_statuses(path) - nested_repositories.under(path)
+    }
+
+    /// Counts the statuses of the repositories nested in other ones without being their
+    /// submodules, whose changes don't bubble up to the folders containing them.
+    pub fn nested_repository_statuses(&self) -> NestedRepositoryStatuses {
+        let mut statuses = Vec::new();
+        let mut enclosing_repositories = Vec::<(&Arc<Path>, &RepositoryEntry)>::new();
+        for (work_directory, repository) in self.repositories() {
+            while enclosing_repositories
+                .last()
+                .map_or(false, |(enclosing_work_directory, _)| {
+                    !work_directory.starts_with(enclosing_work_directory)
+                })
+            {
+                enclosing_repositories.pop();
+            }
+            if let Some((_, enclosing_repository)) = enclosing_repositories.last() {
+                if !enclosing_repository.has_submodule(work_directory) {
+                    statuses.push((
+                        work_directory.clone(),
+                        self.subtree_git_statuses(work_directory),
+                    ));
+                }
+            }
+            enclosing_repositories.push((work_directory, repository));
+        }
+        NestedRepositoryStatuses(statuses)
+    }
+
+    /// Whether the repository with the given work directory is registered as a submodule of
+    /// the repository containing it.
+    pub fn is_submodule(&self, work_directory: &Path) -> bool {
+        work_directory
+            .ancestors()
+            .skip(1)
+            .find_map(|ancestor| self.repository_for_work_directory(ancestor))
+            .map_or(false, |repository| repository.has_submodule(work_directory))
+    }
+
+    /// Whether any file under `path` has a git status, including in the repositories nested in it.
+    pub fn contains_git_statuses(&self, path: &Path) -> bool {
+        self.subtree_git_statuses(path) != GitStatuses::default()
+    }
+
+    /// Counts the statuses of the files directly inside of `path`.
+    pub fn nearest_git_statuses(&self, path: &Path) -> GitStatuses {
+        let mut statuses = GitStatuses::default();
+        for child in self.child_entries(path).filter(|child| child.is_file()) {
+            statuses += GitStatuses::from(child.git_status);
+        }
+        statuses
+    }
+
+    fn subtree_git_statuses(&self, path: &Path) -> GitStatuses {
+        let mut cursor = self
+            .entries_by_path
+            .cursor::<(TraversalProgress, GitStatuses)>();
+        cursor.seek(&TraversalTarget::Path(path), Bias::Left, &());
+        let start = cursor.start().1;
+        cursor.seek_forward(&TraversalTarget::PathSuccessor(path), Bias::Left, &());
+        cursor.start().1 - start
+    }
+
     pub fn paths(&self) -> impl Iterator<Item = &Arc<Path>> {
         let empty_path = Path::new("");
         self.entries_by_path
@@ -2318,6 +2439,12 @@ impl Snapshot {
 }
 
 impl LocalSnapshot {
+    /// Whether the directory could not be read because of a lack of permissions. Such
+    /// directories are left unloaded, so that expanding them retries reading them.
+    pub fn is_dir_inaccessible(&self, path: &Path) -> bool {
+        self.inaccessible_dirs.contains(path)
+    }
+
     pub fn repo_for_path(&self, path: &Path) -> Option<(RepositoryEntry, &LocalRepositoryEntry)> {
         let (_, repo_entry) = self.repository_and_work_directory_for_path(path)?;
         let work_directory_id = repo_entry.work_directory_id();
@@ -2469,6 +2596,13 @@ impl LocalSnapshot {
         inodes
     }
 
+    /// Whether git would ignore the path, which doesn't need to exist yet.
+    pub fn is_path_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let abs_path = self.abs_path().join(path);
+        self.ignore_stack_for_abs_path(&abs_path, is_dir)
+            .is_abs_path_ignored(&abs_path, is_dir)
+    }
+
     fn ignore_stack_for_abs_path(&self, abs_path: &Path, is_dir: bool) -> Arc<IgnoreStack> {
         let mut new_ignores = Vec::new();
         for (index, ancestor) in abs_path.ancestors().enumerate() {
@@ -2721,6 +2855,7 @@ impl BackgroundScannerState {
             .edit(entries_by_path_edits, &());
         self.snapshot.entries_by_id.edit(entries_by_id_edits, &());
 
+        self.snapshot.inaccessible_dirs.remove(parent_path);
         if let Err(ix) = self.changed_paths.binary_search(parent_path) {
             self.changed_paths.insert(ix, parent_path.clone());
         }
@@ -2729,6 +2864,18 @@ impl BackgroundScannerState {
         self.snapshot.check_invariants(false);
     }
 
+    fn mark_dir_inaccessible(&mut self, path: &Arc<Path>) {
+        let Some(mut entry) = self.snapshot.entry_for_path(path).cloned() else {
+            return;
+        };
+        entry.kind = EntryKind::UnloadedDir;
+        self.snapshot.entries_by_path.insert_or_replace(entry, &());
+        self.snapshot.inaccessible_dirs.insert(path.clone());
+        if let Err(ix) = self.changed_paths.binary_search(path) {
+            self.changed_paths.insert(ix, path.clone());
+        }
+    }
+
     fn remove_path(&mut self, path: &Path) {
         let mut new_entries;
         let removed_entries;
@@ -2828,6 +2975,11 @@ impl BackgroundScannerState {
             RepositoryEntry {
                 work_directory: work_dir_id.into(),
                 branch: repository.branch_name().map(Into::into),
+                submodule_work_directories: submodule_work_directories(
+                    &work_dir_path,
+                    location_in_repo.as_deref(),
+                    repository.as_ref(),
+                ),
                 location_in_repo,
             },
         );
@@ -2844,6 +2996,28 @@ impl BackgroundScannerState {
     }
 }
 
+/// The work directories of the submodules of the repository at `work_dir_path`, relative to the
+/// worktree root, leaving out those outside of the worktree.
+fn submodule_work_directories(
+    work_dir_path: &Path,
+    location_in_repo: Option<&Path>,
+    repository: &dyn GitRepository,
+) -> Arc<[Arc<Path>]> {
+    repository
+        .submodule_paths()
+        .log_err()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|path| {
+            let path = match location_in_repo {
+                Some(location_in_repo) => path.strip_prefix(location_in_repo).ok()?,
+                None => path.as_path(),
+            };
+            Some(Arc::from(work_dir_path.join(path)))
+        })
+        .collect()
+}
+
 async fn build_gitignore(abs_path: &Path, fs: &dyn Fs) -> Result<Gitignore> {
     let contents = fs.load(abs_path).await?;
     let parent = abs_path.parent().unwrap_or_else(|| Path::new("/"));
@@ -3128,6 +3302,9 @@ pub struct Entry {
     pub git_status: Option<GitFileStatus>,
     /// Whether this entry is considered to be a `.env` file.
     pub is_private: bool,
+    /// Whether this entry is a FIFO, a socket or a device, which is never read. Only known for
+    /// local worktrees.
+    pub is_special_file: bool,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -3185,6 +3362,7 @@ impl Entry {
             is_ignored: false,
             is_external: false,
             is_private: false,
+            is_special_file: metadata.is_special_file,
             git_status: None,
         }
     }
@@ -3242,15 +3420,7 @@ impl sum_tree::Item for Entry {
             non_ignored_file_count = 0;
         }
 
-        let mut statuses = GitStatuses::default();
-        match self.git_status {
-            Some(status) => match status {
-                GitFileStatus::Added => statuses.added = 1,
-                GitFileStatus::Modified => statuses.modified = 1,
-                GitFileStatus::Conflict => statuses.conflict = 1,
-            },
-            None => {}
-        }
+        let statuses = GitStatuses::from(self.git_status);
 
         EntrySummary {
             max_path: self.path.clone(),
@@ -3809,10 +3979,20 @@ impl BackgroundScanner {
         let mut root_canonical_path = None;
         let mut new_entries: Vec<Entry> = Vec::new();
         let mut new_jobs: Vec<Option<ScanJob>> = Vec::new();
-        let mut child_paths = self
-            .fs
-            .read_dir(&job.abs_path)
-            .await?
+        let read_dir = match self.fs.read_dir(&job.abs_path).await {
+            Ok(read_dir) => read_dir,
+            Err(error) => {
+                let is_permission_denied =
+                    error.downcast_ref::<io::Error>().map_or(false, |error| {
+                        error.kind() == io::ErrorKind::PermissionDenied
+                    });
+                if is_permission_denied {
+                    self.state.lock().mark_dir_inaccessible(&job.path);
+                }
+                return Err(error);
+            }
+        };
+        let mut child_paths = read_dir
             .filter_map(|entry| async {
                 match entry {
                     Ok(entry) => Some(entry),
@@ -4347,7 +4527,14 @@ impl BackgroundScanner {
                             .snapshot
                             .snapshot
                             .repository_entries
-                            .update(&work_dir, |entry| entry.branch = branch.map(Into::into));
+                            .update(&work_dir, |entry| {
+                                entry.branch = branch.map(Into::into);
+                                entry.submodule_work_directories = submodule_work_directories(
+                                    &work_dir.0,
+                                    entry.location_in_repo.as_deref(),
+                                    repo.as_ref(),
+                                );
+                            });
                         (work_dir, repository.repo_ptr.clone())
                     }
                 };
@@ -4836,11 +5023,50 @@ impl<'a> Default for TraversalProgress<'a> {
     }
 }
 
-#[derive(Clone, Debug, Default, Copy)]
-struct GitStatuses {
-    added: usize,
-    modified: usize,
-    conflict: usize,
+/// Numbers of files with each git status.
+#[derive(Clone, Debug, Default, Copy, PartialEq, Eq)]
+pub struct GitStatuses {
+    pub added: usize,
+    pub modified: usize,
+    pub conflict: usize,
+}
+
+impl GitStatuses {
+    /// Conflicts take precedence over modifications, which take precedence over additions.
+    pub const DEFAULT_PRIORITY: [GitFileStatus; 3] = [
+        GitFileStatus::Conflict,
+        GitFileStatus::Modified,
+        GitFileStatus::Added,
+    ];
+
+    pub fn count(&self, status: GitFileStatus) -> usize {
+        match status {
+            GitFileStatus::Added => self.added,
+            GitFileStatus::Modified => self.modified,
+            GitFileStatus::Conflict => self.conflict,
+        }
+    }
+
+    /// Returns the first status of `priority` that any of the files has.
+    pub fn rollup(&self, priority: &[GitFileStatus]) -> Option<GitFileStatus> {
+        priority
+            .iter()
+            .copied()
+            .find(|status| self.count(*status) > 0)
+    }
+}
+
+impl From<Option<GitFileStatus>> for GitStatuses {
+    fn from(status: Option<GitFileStatus>) -> Self {
+        let mut statuses = GitStatuses::default();
+        match status {
+            Some(GitFileStatus::Added) => statuses.added = 1,
+            Some(GitFileStatus::Modified) => statuses.modified = 1,
+            Some(GitFileStatus::Conflict) => statuses.conflict = 1,
+            None => {}
+        }
+        statuses
+    }
 }
 
 impl AddAssign for GitStatuses {
@@ -4869,6 +5095,34 @@ impl<'a> sum_tree::Dimension<'a, EntrySummary> for GitStatuses {
     }
 }
 
+/// The statuses of the repositories nested in other ones without being their submodules,
+/// ordered by work directory.
+#[derive(Clone, Debug, Default)]
+pub struct NestedRepositoryStatuses(Vec<(Arc<Path>, GitStatuses)>);
+
+impl NestedRepositoryStatuses {
+    /// Sums the statuses of the nested repositories under `path`, counting the repositories
+    /// nested in each other once.
+    pub fn under(&self, path: &Path) -> GitStatuses {
+        let start = self
+            .0
+            .partition_point(|(work_directory, _)| work_directory.as_ref() <= path);
+        let mut statuses = GitStatuses::default();
+        let mut last_counted: Option<&Path> = None;
+        for (work_directory, work_directory_statuses) in self.0[start..]
+            .iter()
+            .take_while(|(work_directory, _)| work_directory.starts_with(path))
+        {
+            if last_counted.map_or(false, |last| work_directory.starts_with(last)) {
+                continue;
+            }
+            statuses += *work_directory_statuses;
+            last_counted = Some(work_directory);
+        }
+        statuses
+    }
+}
+
 pub struct Traversal<'a> {
     cursor: sum_tree::Cursor<'a, Entry, TraversalProgress<'a>>,
     include_ignored: bool,
@@ -5073,6 +5327,7 @@ impl<'a> TryFrom<(&'a CharBag, proto::Entry)> for Entry {
             is_external: entry.is_external,
             git_status: git_status_from_proto(entry.git_status),
             is_private: false,
+            is_special_file: false,
             is_symlink: entry.is_symlink,
         })
     }
@@ -1,6 +1,6 @@
 use crate::{
-    worktree_settings::WorktreeSettings, Entry, EntryKind, Event, PathChange, Snapshot, Worktree,
-    WorktreeModelHandle,
+    worktree_settings::WorktreeSettings, Entry, EntryKind, Event, GitStatuses, PathChange,
+    Snapshot, Worktree, WorktreeModelHandle,
 };
 use anyhow::Result;
 use fs::{FakeFs, Fs, RealFs, RemoveOptions};
@@ -388,6 +388,52 @@ async fn test_renaming_case_only(cx: &mut TestAppContext) {
     });
 }
 
+#[cfg(unix)]
+#[gpui::test]
+async fn test_special_files(cx: &mut TestAppContext) {
+    cx.executor().allow_parking();
+    init_test(cx);
+
+    let fs = Arc::new(RealFs::default());
+    let temp_root = temp_tree(json!({
+        "server.rs": "",
+    }));
+    let _listener =
+        std::os::unix::net::UnixListener::bind(temp_root.path().join("server.sock")).unwrap();
+
+    let tree = Worktree::local(
+        temp_root.path(),
+        true,
+        fs.clone(),
+        Default::default(),
+        &mut cx.to_async(),
+    )
+    .await
+    .unwrap();
+
+    cx.read(|cx| tree.read(cx).as_local().unwrap().scan_complete())
+        .await;
+    tree.read_with(cx, |tree, _| {
+        assert_eq!(
+            tree.entries(true, 0)
+                .map(|entry| (entry.path.as_ref(), entry.is_special_file))
+                .collect::<Vec<_>>(),
+            vec![
+                (Path::new(""), false),
+                (Path::new("server.rs"), false),
+                (Path::new("server.sock"), true),
+            ]
+        );
+    });
+
+    let error = tree
+        .update(cx, |tree, cx| tree.load_file(Path::new("server.sock"), cx))
+        .await
+        .err()
+        .unwrap();
+    assert!(error.to_string().contains("can't be opened"), "{error}");
+}
+
 #[gpui::test]
 async fn test_open_gitignored_files(cx: &mut TestAppContext) {
     init_test(cx);
@@ -2426,6 +2472,243 @@ async fn test_propagate_git_statuses(cx: &mut TestAppContext) {
     }
 }
 
+#[gpui::test]
+async fn test_propagate_nearest_git_statuses(cx: &mut TestAppContext) {
+    init_test(cx);
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/root",
+        json!({
+            ".git": {},
+            "a": {
+                "b.txt": "",
+                "c": {
+                    "d.txt": "",
+                    "e.txt": "",
+                },
+            },
+            "f": {
+                "g.txt": "",
+            },
+        }),
+    )
+    .await;
+
+    fs.set_status_for_repo_via_git_operation(
+        &Path::new("/root/.git"),
+        &[
+            (Path::new("a/c/d.txt"), GitFileStatus::Added),
+            (Path::new("a/c/e.txt"), GitFileStatus::Conflict),
+            (Path::new("f/g.txt"), GitFileStatus::Modified),
+        ],
+    );
+
+    let tree = Worktree::local(
+        Path::new("/root"),
+        true,
+        fs.clone(),
+        Default::default(),
+        &mut cx.to_async(),
+    )
+    .await
+    .unwrap();
+
+    cx.read(|cx| tree.read(cx).as_local().unwrap().scan_complete())
+        .await;
+
+    cx.executor().run_until_parked();
+    let snapshot = tree.read_with(cx, |tree, _| tree.snapshot());
+
+    let expected_statuses = [
+        (Path::new(""), None),
+        (Path::new("a"), None),
+        (Path::new("a/b.txt"), None),
+        (Path::new("a/c"), Some(GitFileStatus::Conflict)),
+        (Path::new("a/c/d.txt"), Some(GitFileStatus::Added)),
+        (Path::new("f"), Some(GitFileStatus::Modified)),
+    ];
+    let mut entries = expected_statuses
+        .iter()
+        .map(|(path, _)| snapshot.entry_for_path(path).unwrap().clone())
+        .collect::<Vec<_>>();
+    snapshot.propagate_nearest_git_statuses(&mut entries, &GitStatuses::DEFAULT_PRIORITY);
+    assert_eq!(
+        entries
+            .iter()
+            .map(|e| (e.path.as_ref(), e.git_status))
+            .collect::<Vec<_>>(),
+        expected_statuses
+    );
+
+    assert_eq!(
+        snapshot.nearest_git_statuses(Path::new("a/c")),
+        GitStatuses {
+            added: 1,
+            modified: 0,
+            conflict: 1,
+        }
+    );
+    let mut entries = vec![snapshot.entry_for_path("a/c").unwrap().clone()];
+    snapshot.propagate_nearest_git_statuses(
+        &mut entries,
+        &[GitFileStatus::Added, GitFileStatus::Conflict],
+    );
+    assert_eq!(
+        entries[0].git_status,
+        Some(GitFileStatus::Added),
+        "Statuses earlier in the priority win"
+    );
+}
+
+#[gpui::test]
+async fn test_propagate_git_statuses_stops_at_nested_repositories(cx: &mut TestAppContext) {
+    init_test(cx);
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/root",
+        json!({
+            ".git": {},
+            "a": {
+                "nested": {
+                    ".git": {},
+                    "b.txt": "",
+                },
+                "c.txt": "",
+            },
+        }),
+    )
+    .await;
+
+    fs.set_status_for_repo_via_git_operation(
+        &Path::new("/root/a/nested/.git"),
+        &[(Path::new("b.txt"), GitFileStatus::Modified)],
+    );
+
+    let tree = Worktree::local(
+        Path::new("/root"),
+        true,
+        fs.clone(),
+        Default::default(),
+        &mut cx.to_async(),
+    )
+    .await
+    .unwrap();
+
+    cx.read(|cx| tree.read(cx).as_local().unwrap().scan_complete())
+        .await;
+
+    cx.executor().run_until_parked();
+    let snapshot = tree.read_with(cx, |tree, _| tree.snapshot());
+
+    let mut entries = [
+        Path::new(""),
+        Path::new("a"),
+        Path::new("a/nested"),
+        Path::new("a/nested/b.txt"),
+    ]
+    .iter()
+    .map(|path| snapshot.entry_for_path(path).unwrap().clone())
+    .collect::<Vec<_>>();
+    snapshot.propagate_git_statuses(&mut entries);
+    assert_eq!(
+        entries
+            .iter()
+            .map(|e| (e.path.as_ref(), e.git_status))
+            .collect::<Vec<_>>(),
+        &[
+            (Path::new(""), None),
+            (Path::new("a"), None),
+            (Path::new("a/nested"), Some(GitFileStatus::Modified)),
+            (Path::new("a/nested/b.txt"), Some(GitFileStatus::Modified)),
+        ]
+    );
+}
+
+#[gpui::test]
+async fn test_propagate_git_statuses_through_submodules(cx: &mut TestAppContext) {
+    init_test(cx);
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/root",
+        json!({
+            ".git": {},
+            "a": {
+                "nested": {
+                    ".git": {},
+                    "b.txt": "",
+                },
+                "submodule": {
+                    ".git": {},
+                    "c.txt": "",
+                },
+            },
+        }),
+    )
+    .await;
+
+    fs.with_git_state(&Path::new("/root/.git"), false, |state| {
+        state.submodule_paths = vec!["a/submodule".into()];
+    });
+    fs.set_status_for_repo_via_git_operation(
+        &Path::new("/root/a/nested/.git"),
+        &[(Path::new("b.txt"), GitFileStatus::Added)],
+    );
+    fs.set_status_for_repo_via_git_operation(
+        &Path::new("/root/a/submodule/.git"),
+        &[(Path::new("c.txt"), GitFileStatus::Modified)],
+    );
+
+    let tree = Worktree::local(
+        Path::new("/root"),
+        true,
+        fs.clone(),
+        Default::default(),
+        &mut cx.to_async(),
+    )
+    .await
+    .unwrap();
+
+    cx.read(|cx| tree.read(cx).as_local().unwrap().scan_complete())
+        .await;
+
+    cx.executor().run_until_parked();
+    let snapshot = tree.read_with(cx, |tree, _| tree.snapshot());
+    assert!(snapshot.is_submodule(Path::new("a/submodule")));
+    assert!(!snapshot.is_submodule(Path::new("a/nested")));
+
+    let mut entries = [
+        Path::new(""),
+        Path::new("a"),
+        Path::new("a/nested"),
+        Path::new("a/submodule"),
+    ]
+    .iter()
+    .map(|path| snapshot.entry_for_path(path).unwrap().clone())
+    .collect::<Vec<_>>();
+    snapshot.propagate_git_statuses(&mut entries);
+    assert_eq!(
+        entries
+            .iter()
+            .map(|e| (e.path.as_ref(), e.git_status))
+            .collect::<Vec<_>>(),
+        &[
+            (Path::new(""), Some(GitFileStatus::Modified)),
+            (Path::new("a"), Some(GitFileStatus::Modified)),
+            (Path::new("a/nested"), Some(GitFileStatus::Added)),
+            (Path::new("a/submodule"), Some(GitFileStatus::Modified)),
+        ]
+    );
+    let nested_repositories = snapshot.nested_repository_statuses();
+    assert_eq!(
+        snapshot.propagated_git_statuses(Path::new("a"), &nested_repositories),
+        GitStatuses {
+            added: 0,
+            modified: 1,
+            conflict: 0,
+        }
+    );
+}
+
 #[track_caller]
 fn git_init(path: &Path) -> git2::Repository {
     git2::Repository::init(path).expect("Failed to initialize git repository")